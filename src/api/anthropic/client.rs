@@ -14,6 +14,7 @@ pub(super) const ANTHROPIC_API_BASE: &str = "https://api.anthropic.com/v1";
 #[derive(Clone)]
 pub struct AnthropicClient {
     pub(super) client: reqwest::Client,
+    pub(super) retry: utils::RetryPolicy,
 }
 
 /// Anthropic API version pin sent on every request. Bump only after
@@ -41,7 +42,19 @@ impl AnthropicClient {
 
         let client = utils::build_http_client(headers, utils::REQUEST_TIMEOUT)?;
 
-        Ok(Self { client })
+        Ok(Self {
+            client,
+            retry: utils::RetryPolicy::default(),
+        })
+    }
+
+    pub fn with_retry_policy(mut self, retry: utils::RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    pub fn retry_policy(&self) -> utils::RetryPolicy {
+        self.retry
     }
 
     /// Check if we can reach the API endpoint
@@ -63,13 +76,12 @@ impl AnthropicClient {
         let request = prepare_request(request);
         let beta = anthropic_beta_for(&request.model);
 
-        let response = utils::send_once(
-            "Anthropic",
+        let response = utils::send_with_reconnect("Anthropic", self.retry, || {
             self.client
                 .post(&url)
                 .header(BETA_HEADER_NAME, beta)
-                .json(&request),
-        )
+                .json(&request)
+        })
         .await?;
 
         // Read the body as text first so a JSON-shape mismatch surfaces
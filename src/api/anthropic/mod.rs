@@ -181,6 +181,8 @@ mod tests {
             reasoning: None,
             prompt_cache_key: None,
             context_management: None,
+            temperature: None,
+            top_p: None,
         };
 
         let json = serde_json::to_value(&request).unwrap();
@@ -204,6 +206,8 @@ mod tests {
             reasoning: None,
             prompt_cache_key: None,
             context_management: None,
+            temperature: None,
+            top_p: None,
         };
 
         let json = serde_json::to_value(&request).unwrap();
@@ -226,12 +230,41 @@ mod tests {
             reasoning: None,
             prompt_cache_key: Some("session-1".to_string()),
             context_management: None,
+            temperature: None,
+            top_p: None,
         };
 
         let prepared = prepare_request(request);
         assert!(prepared.prompt_cache_key.is_none());
     }
 
+    #[test]
+    fn prepare_request_fits_sampling_to_thinking() {
+        let request = |thinking: Option<Thinking>| CreateMessageRequest {
+            model: crate::api::model_info::CLAUDE_SONNET.to_string(),
+            max_tokens: 8192,
+            messages: vec![],
+            system: None,
+            tools: None,
+            stream: None,
+            thinking,
+            output_config: None,
+            reasoning: None,
+            prompt_cache_key: None,
+            context_management: None,
+            temperature: Some(1.5),
+            top_p: Some(0.5),
+        };
+
+        let thinking = prepare_request(request(Some(Thinking::adaptive())));
+        assert_eq!(thinking.temperature, None);
+        assert_eq!(thinking.top_p, Some(0.95));
+
+        let plain = prepare_request(request(None));
+        assert_eq!(plain.temperature, Some(1.0));
+        assert_eq!(plain.top_p, Some(0.5));
+    }
+
     #[test]
     fn sanitizer_drops_openai_reasoning_blocks_before_anthropic_call() {
         // Regression: a session that started on OpenAI accumulates
@@ -64,13 +64,12 @@ impl AnthropicClient {
 
         let url = format!("{}/messages", ANTHROPIC_API_BASE);
 
-        let response = utils::send_once(
-            "Anthropic",
+        let response = utils::send_with_reconnect("Anthropic", self.retry, || {
             self.client
                 .post(&url)
                 .header(BETA_HEADER_NAME, beta)
-                .json(&request),
-        )
+                .json(&request)
+        })
         .await?;
 
         let byte_stream = response.bytes_stream().map(|chunk_result| {
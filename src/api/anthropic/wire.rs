@@ -58,6 +58,12 @@ pub const LEGACY_THINKING_BUDGET_LOW: u32 = 1024;
 pub const LEGACY_THINKING_BUDGET_MEDIUM: u32 = 5120;
 pub const LEGACY_THINKING_BUDGET_HIGH: u32 = 16384;
 
+/// Highest temperature the Messages API accepts; OpenAI allows twice this.
+const MAX_TEMPERATURE: f32 = 1.0;
+
+/// Lowest `top_p` the Messages API accepts while thinking is on.
+const THINKING_MIN_TOP_P: f32 = 0.95;
+
 /// Default trigger floor for server-side compaction. Below this the
 /// model probably hasn't earned compaction yet, and triggering early
 /// would waste a compaction round-trip on a still-small history.
@@ -118,6 +124,15 @@ pub(super) fn prepare_request(mut request: CreateMessageRequest) -> CreateMessag
     // send it and trigger a 400.
     request.reasoning = None;
 
+    // Thinking requires the default temperature of 1 and a top-p of at
+    // least 0.95. Adjust rather than send values the API would reject.
+    if request.thinking.is_some() {
+        request.temperature = None;
+        request.top_p = request.top_p.map(|p| p.max(THINKING_MIN_TOP_P));
+    } else {
+        request.temperature = request.temperature.map(|t| t.min(MAX_TEMPERATURE));
+    }
+
     if let Some(tools) = request.tools.take() {
         let filtered: Vec<Tool> = tools
             .into_iter()
@@ -0,0 +1,83 @@
+//! HTTP client, connectivity check, and the non-streaming
+//! [`GeminiClient::create_message`] path. The streaming path lives in
+//! [`super::stream`]; the request body shape and response conversion
+//! live in [`super::wire`].
+
+use crate::api::gemini::GeminiClient;
+use crate::api::gemini::wire::{GeminiResponse, build_request_body, build_response};
+use crate::api::types::{CreateMessageRequest, CreateMessageResponse};
+use crate::api::utils;
+use crate::error::{Result, SofosError};
+use reqwest::header::{HeaderMap, HeaderValue};
+
+pub(super) const GEMINI_API_BASE: &str = "https://generativelanguage.googleapis.com/v1beta";
+
+/// Header the Gemini API reads the key from. Keeps the key out of the
+/// URL, where it would end up in proxy and error logs.
+const API_KEY_HEADER: &str = "x-goog-api-key";
+
+impl GeminiClient {
+    pub fn new(api_key: String) -> Result<Self> {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            API_KEY_HEADER,
+            HeaderValue::from_str(&api_key)
+                .map_err(|e| SofosError::Config(format!("Invalid API key format: {}", e)))?,
+        );
+
+        let client = utils::build_http_client(headers, utils::REQUEST_TIMEOUT)?;
+
+        Ok(Self {
+            client,
+            retry: utils::RetryPolicy::default(),
+        })
+    }
+
+    pub fn with_retry_policy(mut self, retry: utils::RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    pub fn retry_policy(&self) -> utils::RetryPolicy {
+        self.retry
+    }
+
+    pub async fn check_connectivity(&self) -> Result<()> {
+        utils::check_api_connectivity(
+            &self.client,
+            GEMINI_API_BASE,
+            "Gemini",
+            "https://aistudio.google.com/status",
+        )
+        .await
+    }
+
+    pub async fn create_message(
+        &self,
+        request: CreateMessageRequest,
+    ) -> Result<CreateMessageResponse> {
+        let body = build_request_body(&request);
+        let url = format!(
+            "{}/models/{}:generateContent",
+            GEMINI_API_BASE, request.model
+        );
+
+        let response =
+            utils::send_with_reconnect("Gemini", self.retry, || self.client.post(&url).json(&body))
+                .await?;
+
+        let text = response
+            .text()
+            .await
+            .map_err(|e| SofosError::Api(format!("Failed to read Gemini response body: {}", e)))?;
+        let parsed: GeminiResponse = serde_json::from_str(&text).map_err(|e| {
+            SofosError::Api(format!(
+                "Failed to parse Gemini response: {} (body preview: {})",
+                e,
+                utils::sanitize_provider_error_body(&text)
+            ))
+        })?;
+
+        build_response(parsed, &request.model)
+    }
+}
@@ -0,0 +1,250 @@
+//! Google Gemini `generateContent` API client. The submodules carry the
+//! same split as the OpenAI client:
+//!
+//! - [`client`] — HTTP client construction, connectivity check, and
+//!   the non-streaming `create_message` path.
+//! - [`wire`] — request body shaping (`contents`, `systemInstruction`,
+//!   `thinkingConfig`, function declarations), the on-the-wire response
+//!   shape, and the `build_response` conversion into the shared
+//!   `CreateMessageResponse`.
+//! - [`stream`] — the SSE parser and the streaming `create_message`
+//!   path; chunks are merged and fed to `build_response` so the
+//!   streaming and non-streaming call shapes match one-to-one.
+
+pub mod client;
+pub mod stream;
+pub mod wire;
+
+#[derive(Clone)]
+pub struct GeminiClient {
+    pub(super) client: reqwest::Client,
+    pub(super) retry: crate::api::utils::RetryPolicy,
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::api::gemini::wire::{GeminiResponse, build_request_body, build_response};
+    use crate::api::types::*;
+    use serde_json::json;
+
+    fn request(messages: Vec<Message>) -> CreateMessageRequest {
+        CreateMessageRequest {
+            model: crate::api::model_info::GEMINI_FLASH.to_string(),
+            max_tokens: 4096,
+            messages,
+            system: None,
+            tools: None,
+            stream: None,
+            thinking: None,
+            output_config: None,
+            reasoning: None,
+            prompt_cache_key: None,
+            context_management: None,
+            temperature: None,
+            top_p: None,
+        }
+    }
+
+    fn response(value: serde_json::Value) -> GeminiResponse {
+        serde_json::from_value(value).expect("fixture parses")
+    }
+
+    #[test]
+    fn body_carries_system_thinking_and_function_declarations() {
+        let mut req = request(vec![Message::user("hi")]);
+        req.system = Some(vec![SystemPrompt::new_cached_with_ttl(
+            "Be brief.".to_string(),
+            None,
+        )]);
+        req.reasoning = Some(crate::api::Reasoning::with_effort("low"));
+        req.tools = Some(vec![Tool::Regular {
+            name: "read_file".to_string(),
+            description: "Read a file".to_string(),
+            input_schema: json!({"type": "object"}),
+            cache_control: None,
+        }]);
+
+        let body = build_request_body(&req);
+
+        assert_eq!(body["systemInstruction"]["parts"][0]["text"], "Be brief.");
+        assert_eq!(body["generationConfig"]["maxOutputTokens"], 4096);
+        assert_eq!(
+            body["generationConfig"]["thinkingConfig"],
+            json!({"thinkingBudget": 1024, "includeThoughts": true})
+        );
+        assert_eq!(
+            body["tools"][0]["functionDeclarations"][0]["name"],
+            "read_file"
+        );
+        assert_eq!(
+            body["contents"][0],
+            json!({"role": "user", "parts": [{"text": "hi"}]})
+        );
+    }
+
+    #[test]
+    fn body_omits_optional_sections_when_unset() {
+        let body = build_request_body(&request(vec![Message::user("hi")]));
+        assert!(body.get("systemInstruction").is_none());
+        assert!(body.get("tools").is_none());
+        assert!(body["generationConfig"].get("thinkingConfig").is_none());
+    }
+
+    #[test]
+    fn tool_results_are_sent_under_the_function_name() {
+        let messages = vec![
+            Message::user("list files"),
+            Message {
+                role: "assistant".to_string(),
+                content: MessageContent::Blocks {
+                    content: vec![MessageContentBlock::ToolUse {
+                        id: "call_1".to_string(),
+                        name: "list_directory".to_string(),
+                        input: json!({"path": "."}),
+                        cache_control: None,
+                    }],
+                },
+            },
+            Message::user_with_tool_results(vec![MessageContentBlock::ToolResult {
+                tool_use_id: "call_1".to_string(),
+                content: "a.rs".to_string(),
+                cache_control: None,
+            }]),
+        ];
+
+        let body = build_request_body(&request(messages));
+
+        assert_eq!(body["contents"][1]["role"], "model");
+        assert_eq!(
+            body["contents"][1]["parts"][0]["functionCall"]["name"],
+            "list_directory"
+        );
+        assert_eq!(
+            body["contents"][2]["parts"][0]["functionResponse"],
+            json!({"name": "list_directory", "response": {"content": "a.rs"}})
+        );
+    }
+
+    #[test]
+    fn response_maps_parts_finish_reason_and_usage() {
+        let parsed = response(json!({
+            "responseId": "r1",
+            "modelVersion": "gemini-2.5-flash",
+            "candidates": [{
+                "content": {"role": "model", "parts": [
+                    {"text": "Planning the listing.", "thought": true},
+                    {"text": "Listing now."},
+                    {"functionCall": {"name": "list_directory", "args": {"path": "."}}}
+                ]},
+                "finishReason": "STOP"
+            }],
+            "usageMetadata": {
+                "promptTokenCount": 100,
+                "candidatesTokenCount": 20,
+                "thoughtsTokenCount": 30,
+                "cachedContentTokenCount": 40
+            }
+        }));
+
+        let built = build_response(parsed, "fallback").expect("builds");
+
+        assert_eq!(built.model, "gemini-2.5-flash");
+        assert_eq!(built.stop_reason.as_deref(), Some("end_turn"));
+        assert!(
+            matches!(&built.content[0], ContentBlock::Summary { summary } if summary == "Planning the listing.")
+        );
+        assert!(matches!(&built.content[1], ContentBlock::Text { text } if text == "Listing now."));
+        match &built.content[2] {
+            ContentBlock::ToolUse { id, name, input } => {
+                assert!(id.starts_with("call_"), "missing ids are generated");
+                assert_eq!(name, "list_directory");
+                assert_eq!(input, &json!({"path": "."}));
+            }
+            other => panic!("expected a tool call, got {:?}", other),
+        }
+        assert_eq!(built.usage.input_tokens, 100);
+        assert_eq!(
+            built.usage.output_tokens, 50,
+            "thoughts are billed as output"
+        );
+        assert_eq!(built.usage.cache_read_input_tokens, Some(40));
+    }
+
+    #[test]
+    fn finish_reasons_map_onto_shared_stop_reasons() {
+        let stop_reason = |reason: &str| {
+            let parsed = response(json!({
+                "candidates": [{"content": {"parts": []}, "finishReason": reason}]
+            }));
+            build_response(parsed, "m").expect("builds").stop_reason
+        };
+        assert_eq!(stop_reason("MAX_TOKENS").as_deref(), Some("max_tokens"));
+        assert_eq!(stop_reason("SAFETY").as_deref(), Some("refusal"));
+
+        let blocked = response(json!({"promptFeedback": {"blockReason": "SAFETY"}}));
+        let built = build_response(blocked, "m").expect("a blocked prompt still builds");
+        assert_eq!(built.stop_reason.as_deref(), Some("refusal"));
+        assert!(built.content.is_empty());
+    }
+
+    mod streaming {
+        use super::*;
+        use crate::api::gemini::stream::parse_stream;
+        use crate::api::utils::sse_test_support::sse_stream_from_events;
+        use crate::error::SofosError;
+        use std::sync::atomic::AtomicBool;
+        use std::sync::{Arc, Mutex};
+
+        fn flag() -> Arc<AtomicBool> {
+            Arc::new(AtomicBool::new(false))
+        }
+
+        #[tokio::test]
+        async fn deltas_reach_callbacks_and_merge_into_one_response() {
+            let events = vec![
+                json!({"candidates": [{"content": {"parts": [{"text": "Think", "thought": true}]}}]}),
+                json!({"candidates": [{"content": {"parts": [{"text": "ing.", "thought": true}]}}]}),
+                json!({"candidates": [{"content": {"parts": [{"text": "Hello"}]}}]}),
+                json!({
+                    "candidates": [{"content": {"parts": [{"text": ", world"}]}, "finishReason": "STOP"}],
+                    "usageMetadata": {"promptTokenCount": 5, "candidatesTokenCount": 3}
+                }),
+            ];
+            let text_chunks: Arc<Mutex<Vec<String>>> = Arc::new(Mutex::new(Vec::new()));
+            let think_chunks: Arc<Mutex<Vec<String>>> = Arc::new(Mutex::new(Vec::new()));
+            let t = text_chunks.clone();
+            let th = think_chunks.clone();
+
+            let built = parse_stream(
+                sse_stream_from_events(events),
+                "gemini-2.5-flash",
+                move |s| t.lock().unwrap().push(s.to_string()),
+                move |s| th.lock().unwrap().push(s.to_string()),
+                flag(),
+            )
+            .await
+            .expect("parse_stream succeeds");
+
+            assert_eq!(text_chunks.lock().unwrap().as_slice(), ["Hello", ", world"]);
+            assert_eq!(think_chunks.lock().unwrap().as_slice(), ["Think", "ing."]);
+            assert_eq!(built.content.len(), 2);
+            assert!(
+                matches!(&built.content[0], ContentBlock::Summary { summary } if summary == "Thinking.")
+            );
+            assert!(
+                matches!(&built.content[1], ContentBlock::Text { text } if text == "Hello, world")
+            );
+            assert_eq!(built.stop_reason.as_deref(), Some("end_turn"));
+            assert_eq!(built.usage.output_tokens, 3);
+        }
+
+        #[tokio::test]
+        async fn error_event_fails_the_stream() {
+            let events = vec![json!({"error": {"message": "quota exhausted"}})];
+            let err = parse_stream(sse_stream_from_events(events), "m", |_| {}, |_| {}, flag())
+                .await
+                .expect_err("error events surface");
+            assert!(matches!(err, SofosError::Api(msg) if msg.contains("quota exhausted")));
+        }
+    }
+}
@@ -0,0 +1,146 @@
+//! SSE parser for the Gemini `streamGenerateContent` endpoint. Every
+//! event is a partial `generateContent` response; text and thought
+//! deltas go to their callbacks as they arrive, and the chunks are
+//! folded into one response that [`super::wire::build_response`] turns
+//! into the same [`CreateMessageResponse`] the non-streaming path
+//! returns.
+
+use crate::api::gemini::GeminiClient;
+use crate::api::gemini::client::GEMINI_API_BASE;
+use crate::api::gemini::wire::{GeminiResponse, build_request_body, build_response};
+use crate::api::types::*;
+use crate::api::utils;
+use crate::api::utils::MAX_SSE_BUFFER_BYTES;
+use crate::error::{Result, SofosError};
+use futures::stream::{Stream, StreamExt};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+impl GeminiClient {
+    /// Streaming counterpart to [`GeminiClient::create_message`]. Fires
+    /// `on_text_delta` for reply text and `on_thinking_delta` for
+    /// thought summaries.
+    pub async fn create_message_streaming<FText, FThink>(
+        &self,
+        request: CreateMessageRequest,
+        on_text_delta: FText,
+        on_thinking_delta: FThink,
+        interrupt_flag: Arc<AtomicBool>,
+    ) -> Result<CreateMessageResponse>
+    where
+        FText: Fn(&str) + Send + Sync,
+        FThink: Fn(&str) + Send + Sync,
+    {
+        let body = build_request_body(&request);
+        let url = format!(
+            "{}/models/{}:streamGenerateContent?alt=sse",
+            GEMINI_API_BASE, request.model
+        );
+        let response =
+            utils::send_with_reconnect("Gemini", self.retry, || self.client.post(&url).json(&body))
+                .await?;
+
+        let byte_stream = response.bytes_stream().map(|chunk_result| {
+            chunk_result.map_err(|e| SofosError::NetworkError(format!("Stream read error: {}", e)))
+        });
+        parse_stream(
+            byte_stream,
+            &request.model,
+            on_text_delta,
+            on_thinking_delta,
+            interrupt_flag,
+        )
+        .await
+    }
+}
+
+/// Drive a pre-built SSE byte stream through the Gemini parser. Split
+/// out from [`GeminiClient::create_message_streaming`] so tests can feed
+/// fixtures without an HTTP layer.
+pub(crate) async fn parse_stream<S, B, FText, FThink>(
+    byte_stream: S,
+    model: &str,
+    on_text_delta: FText,
+    on_thinking_delta: FThink,
+    interrupt_flag: Arc<AtomicBool>,
+) -> Result<CreateMessageResponse>
+where
+    S: Stream<Item = Result<B>> + Unpin,
+    B: AsRef<[u8]>,
+    FText: Fn(&str) + Send + Sync,
+    FThink: Fn(&str) + Send + Sync,
+{
+    let mut byte_stream = byte_stream;
+    // Raw bytes are buffered and only decoded at line boundaries, so a
+    // codepoint split across two HTTP chunks stays intact.
+    let mut buffer: Vec<u8> = Vec::new();
+    let mut assembled: Option<GeminiResponse> = None;
+
+    while let Some(chunk_result) = byte_stream.next().await {
+        if interrupt_flag.load(Ordering::SeqCst) {
+            return Err(SofosError::Interrupted);
+        }
+
+        let chunk = chunk_result?;
+        buffer.extend_from_slice(chunk.as_ref());
+        if buffer.len() > MAX_SSE_BUFFER_BYTES {
+            return Err(SofosError::Api(format!(
+                "Gemini SSE buffer exceeded {} MB without a line terminator; \
+                 likely a misbehaving server or middlebox",
+                MAX_SSE_BUFFER_BYTES / (1024 * 1024)
+            )));
+        }
+
+        while let Some(pos) = buffer.iter().position(|b| *b == b'\n') {
+            if interrupt_flag.load(Ordering::SeqCst) {
+                return Err(SofosError::Interrupted);
+            }
+            let line = String::from_utf8_lossy(&buffer[..pos]).into_owned();
+            buffer.drain(..=pos);
+
+            let Some(json_str) = line.trim_end().strip_prefix("data: ") else {
+                continue;
+            };
+            let event: serde_json::Value = match serde_json::from_str(json_str) {
+                Ok(v) => v,
+                Err(e) => {
+                    tracing::debug!(
+                        error = %e,
+                        preview = %json_str.chars().take(200).collect::<String>(),
+                        "failed to parse Gemini streaming event"
+                    );
+                    continue;
+                }
+            };
+            if let Some(error) = event.get("error") {
+                let message = error
+                    .get("message")
+                    .and_then(|m| m.as_str())
+                    .unwrap_or("Unknown streaming error");
+                return Err(SofosError::Api(format!("Streaming error: {}", message)));
+            }
+
+            let chunk: GeminiResponse = serde_json::from_value(event).map_err(|e| {
+                SofosError::Api(format!("Failed to parse Gemini streaming chunk: {}", e))
+            })?;
+            let deltas = chunk
+                .candidates
+                .iter()
+                .take(1)
+                .filter_map(|candidate| candidate.content.as_ref())
+                .flat_map(|content| &content.parts);
+            for part in deltas {
+                match (&part.text, part.thought) {
+                    (Some(text), true) => on_thinking_delta(text),
+                    (Some(text), false) => on_text_delta(text),
+                    (None, _) => {}
+                }
+            }
+            assembled.get_or_insert_with(Default::default).absorb(chunk);
+        }
+    }
+
+    let response = assembled
+        .ok_or_else(|| SofosError::Api("Gemini stream ended without a response".to_string()))?;
+    build_response(response, model)
+}
@@ -0,0 +1,403 @@
+//! Request body shaping for the Gemini `generateContent` endpoint, the
+//! response-side wire types, and the conversion back into the shared
+//! [`CreateMessageResponse`] shape. The non-streaming `create_message`
+//! decodes one [`GeminiResponse`]; the streaming parser folds every
+//! chunk into one with [`GeminiResponse::absorb`]. Both then go through
+//! `build_response`, so the two call shapes produce identical results.
+
+use crate::api::types::*;
+use crate::api::utils;
+use crate::error::{Result, SofosError};
+use rand::RngExt;
+use serde::Deserialize;
+use serde_json::json;
+use std::collections::HashMap;
+
+const ROLE_MODEL: &str = "model";
+const ROLE_USER: &str = "user";
+
+/// `thinkingBudget` sent for each effort level. Gemini Flash caps the
+/// budget at 24_576 tokens, so `High` stays valid on every Gemini model
+/// in the catalog.
+const THINKING_BUDGET_LOW: u32 = 1_024;
+const THINKING_BUDGET_MEDIUM: u32 = 8_192;
+const THINKING_BUDGET_HIGH: u32 = 24_576;
+
+/// Finish reasons Gemini reports when it withheld the answer on policy
+/// grounds. Mapped onto the shared `refusal` stop reason.
+const BLOCKED_FINISH_REASONS: &[&str] = &[
+    "SAFETY",
+    "RECITATION",
+    "BLOCKLIST",
+    "PROHIBITED_CONTENT",
+    "SPII",
+    "IMAGE_SAFETY",
+];
+
+fn thinking_budget(effort: &str) -> u32 {
+    match effort {
+        "low" => THINKING_BUDGET_LOW,
+        "medium" => THINKING_BUDGET_MEDIUM,
+        _ => THINKING_BUDGET_HIGH,
+    }
+}
+
+pub(super) fn build_request_body(request: &CreateMessageRequest) -> serde_json::Value {
+    let mut body = json!({
+        "contents": build_contents(&request.messages),
+        "generationConfig": {
+            "maxOutputTokens": request.max_tokens,
+        },
+    });
+    if let Some(temperature) = request.temperature {
+        body["generationConfig"]["temperature"] = json!(temperature);
+    }
+    if let Some(top_p) = request.top_p {
+        body["generationConfig"]["topP"] = json!(top_p);
+    }
+
+    let system_text = request
+        .system
+        .iter()
+        .flatten()
+        .map(|system| system.text.as_str())
+        .collect::<Vec<_>>()
+        .join("\n\n");
+    if !system_text.is_empty() {
+        body["systemInstruction"] = json!({"parts": [{"text": system_text}]});
+    }
+
+    // `includeThoughts` returns thought summaries, which stream to the
+    // user the same way OpenAI's reasoning summaries do.
+    if let Some(reasoning) = &request.reasoning {
+        body["generationConfig"]["thinkingConfig"] = json!({
+            "thinkingBudget": thinking_budget(&reasoning.effort),
+            "includeThoughts": true,
+        });
+    }
+
+    // Only our own function tools are sent; the provider-hosted web
+    // search tools belong to Anthropic and OpenAI.
+    let declarations: Vec<serde_json::Value> = request
+        .tools
+        .iter()
+        .flatten()
+        .filter_map(|tool| match tool {
+            Tool::Regular {
+                name,
+                description,
+                input_schema,
+                ..
+            } => Some(json!({
+                "name": name,
+                "description": description,
+                "parametersJsonSchema": input_schema,
+            })),
+            _ => None,
+        })
+        .collect();
+    if !declarations.is_empty() {
+        body["tools"] = json!([{"functionDeclarations": declarations}]);
+    }
+
+    body
+}
+
+/// Convert the conversation into Gemini `contents`. A tool result only
+/// carries the call id, while Gemini's `functionResponse` needs the
+/// function name, so the names are looked up from the earlier calls.
+pub(super) fn build_contents(messages: &[Message]) -> Vec<serde_json::Value> {
+    let tool_names: HashMap<&str, &str> = messages
+        .iter()
+        .filter_map(|msg| match &msg.content {
+            MessageContent::Blocks { content } => Some(content),
+            MessageContent::Text { .. } => None,
+        })
+        .flatten()
+        .filter_map(|block| match block {
+            MessageContentBlock::ToolUse { id, name, .. } => Some((id.as_str(), name.as_str())),
+            _ => None,
+        })
+        .collect();
+
+    let mut contents = Vec::new();
+    for msg in messages {
+        let role = if msg.role == "assistant" {
+            ROLE_MODEL
+        } else {
+            ROLE_USER
+        };
+        let mut parts = Vec::new();
+        match &msg.content {
+            MessageContent::Text { content } => parts.push(json!({"text": content})),
+            MessageContent::Blocks { content } => {
+                for block in content {
+                    match block {
+                        MessageContentBlock::Text { text, .. } => {
+                            parts.push(json!({"text": text}));
+                        }
+                        MessageContentBlock::Image { source, .. } => match source {
+                            ImageSource::Base64 { media_type, data } => {
+                                parts.push(json!({
+                                    "inlineData": {"mimeType": media_type, "data": data}
+                                }));
+                            }
+                            // `fileData` only takes Gemini-hosted files,
+                            // so a web image is passed as its URL.
+                            ImageSource::Url { url } => {
+                                parts.push(json!({"text": format!("Image: {}", url)}));
+                            }
+                        },
+                        MessageContentBlock::ToolUse { name, input, .. } => {
+                            parts.push(json!({
+                                "functionCall": {"name": name, "args": input}
+                            }));
+                        }
+                        MessageContentBlock::ToolResult {
+                            tool_use_id,
+                            content,
+                            ..
+                        } => {
+                            let name = tool_names
+                                .get(tool_use_id.as_str())
+                                .copied()
+                                .unwrap_or(tool_use_id);
+                            parts.push(json!({
+                                "functionResponse": {
+                                    "name": name,
+                                    "response": {"content": content},
+                                }
+                            }));
+                        }
+                        MessageContentBlock::ServerToolUse { name, input, .. } => {
+                            parts.push(json!({
+                                "text": format!("Server tool call {} with args: {}", name, input)
+                            }));
+                        }
+                        MessageContentBlock::WebSearchToolResult {
+                            tool_use_id,
+                            content,
+                            ..
+                        } => {
+                            parts.push(json!({
+                                "text": format!(
+                                    "Web search results for {} ({} items)",
+                                    tool_use_id,
+                                    content.len()
+                                )
+                            }));
+                        }
+                        // Reasoning from this or another provider can't
+                        // be replayed to Gemini; the visible reply
+                        // already carries what the model concluded.
+                        MessageContentBlock::Thinking { .. }
+                        | MessageContentBlock::Summary { .. }
+                        | MessageContentBlock::Compaction { .. }
+                        | MessageContentBlock::Reasoning { .. } => {}
+                    }
+                }
+            }
+        }
+        if !parts.is_empty() {
+            contents.push(json!({"role": role, "parts": parts}));
+        }
+    }
+    contents
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(super) struct GeminiResponse {
+    #[serde(default)]
+    pub(super) candidates: Vec<GeminiCandidate>,
+    #[serde(default)]
+    pub(super) usage_metadata: Option<GeminiUsage>,
+    #[serde(default)]
+    pub(super) model_version: Option<String>,
+    #[serde(default)]
+    pub(super) response_id: Option<String>,
+    #[serde(default)]
+    pub(super) prompt_feedback: Option<GeminiPromptFeedback>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(super) struct GeminiCandidate {
+    #[serde(default)]
+    pub(super) content: Option<GeminiContent>,
+    #[serde(default)]
+    pub(super) finish_reason: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub(super) struct GeminiContent {
+    #[serde(default)]
+    pub(super) parts: Vec<GeminiPart>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(super) struct GeminiPart {
+    #[serde(default)]
+    pub(super) text: Option<String>,
+    /// Set on thought-summary parts, which carry reasoning rather than
+    /// the reply.
+    #[serde(default)]
+    pub(super) thought: bool,
+    #[serde(default)]
+    pub(super) function_call: Option<GeminiFunctionCall>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(super) struct GeminiFunctionCall {
+    pub(super) name: String,
+    #[serde(default)]
+    pub(super) args: serde_json::Value,
+    #[serde(default)]
+    pub(super) id: Option<String>,
+}
+
+/// `promptTokenCount` already includes `cachedContentTokenCount`, like
+/// OpenAI's `input_tokens`. Thinking tokens are billed as output.
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(super) struct GeminiUsage {
+    #[serde(default)]
+    pub(super) prompt_token_count: Option<u32>,
+    #[serde(default)]
+    pub(super) candidates_token_count: Option<u32>,
+    #[serde(default)]
+    pub(super) cached_content_token_count: Option<u32>,
+    #[serde(default)]
+    pub(super) thoughts_token_count: Option<u32>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(super) struct GeminiPromptFeedback {
+    #[serde(default)]
+    pub(super) block_reason: Option<String>,
+}
+
+impl GeminiResponse {
+    /// Fold a streamed chunk into the response assembled so far. Text
+    /// parts arrive as deltas, so a text part continuing a text part of
+    /// the same kind is appended to it; the finish reason and usage of
+    /// the last chunk that carries them win.
+    pub(super) fn absorb(&mut self, chunk: GeminiResponse) {
+        if self.response_id.is_none() {
+            self.response_id = chunk.response_id;
+        }
+        if self.model_version.is_none() {
+            self.model_version = chunk.model_version;
+        }
+        if chunk.usage_metadata.is_some() {
+            self.usage_metadata = chunk.usage_metadata;
+        }
+        if chunk.prompt_feedback.is_some() {
+            self.prompt_feedback = chunk.prompt_feedback;
+        }
+        let Some(incoming) = chunk.candidates.into_iter().next() else {
+            return;
+        };
+        if self.candidates.is_empty() {
+            self.candidates.push(GeminiCandidate::default());
+        }
+        let candidate = &mut self.candidates[0];
+        if incoming.finish_reason.is_some() {
+            candidate.finish_reason = incoming.finish_reason;
+        }
+        let parts = &mut candidate.content.get_or_insert_with(Default::default).parts;
+        for part in incoming.content.map(|c| c.parts).unwrap_or_default() {
+            match (parts.last_mut(), &part.text) {
+                (Some(last), Some(delta))
+                    if last.function_call.is_none()
+                        && part.function_call.is_none()
+                        && last.thought == part.thought =>
+                {
+                    last.text.get_or_insert_with(String::new).push_str(delta);
+                }
+                _ => parts.push(part),
+            }
+        }
+    }
+}
+
+/// An id for a function call Gemini returned without one. Tool results
+/// are paired with their call by this id for the rest of the session.
+fn generated_call_id() -> String {
+    format!("call_{:016x}", rand::rng().random_range(0..=u64::MAX))
+}
+
+/// Convert a (possibly merged) Gemini response into the shared
+/// `CreateMessageResponse` shape. `model` names the response when
+/// Gemini omits `modelVersion`.
+pub(super) fn build_response(
+    response: GeminiResponse,
+    model: &str,
+) -> Result<CreateMessageResponse> {
+    let blocked = response
+        .prompt_feedback
+        .as_ref()
+        .and_then(|f| f.block_reason.clone());
+    let candidate = response.candidates.into_iter().next();
+    if candidate.is_none() && blocked.is_none() {
+        return Err(SofosError::Api(
+            "Gemini response contained no candidates".to_string(),
+        ));
+    }
+
+    let mut content_blocks = Vec::new();
+    let mut finish_reason = None;
+    if let Some(candidate) = candidate {
+        finish_reason = candidate.finish_reason;
+        for part in candidate.content.map(|c| c.parts).unwrap_or_default() {
+            if let Some(call) = part.function_call {
+                let input = if call.args.is_null() {
+                    json!({})
+                } else {
+                    call.args
+                };
+                content_blocks.push(ContentBlock::ToolUse {
+                    id: call.id.unwrap_or_else(generated_call_id),
+                    name: call.name,
+                    input,
+                });
+            } else if let Some(text) = part.text.filter(|t| !t.trim().is_empty()) {
+                content_blocks.push(if part.thought {
+                    ContentBlock::Summary { summary: text }
+                } else {
+                    ContentBlock::Text { text }
+                });
+            }
+        }
+    }
+
+    let stop_reason = match (blocked, finish_reason.as_deref()) {
+        (Some(_), _) => Some("refusal".to_string()),
+        (None, Some("STOP")) => Some("end_turn".to_string()),
+        (None, Some("MAX_TOKENS")) => Some("max_tokens".to_string()),
+        (None, Some(reason)) if BLOCKED_FINISH_REASONS.contains(&reason) => {
+            Some("refusal".to_string())
+        }
+        (None, Some(reason)) => Some(reason.to_ascii_lowercase()),
+        (None, None) => None,
+    };
+
+    let usage = response.usage_metadata.unwrap_or_default();
+    Ok(utils::build_message_response(
+        response.response_id.unwrap_or_default(),
+        response.model_version.unwrap_or_else(|| model.to_string()),
+        content_blocks,
+        stop_reason,
+        Usage {
+            input_tokens: usage.prompt_token_count.unwrap_or(0),
+            output_tokens: usage
+                .candidates_token_count
+                .unwrap_or(0)
+                .saturating_add(usage.thoughts_token_count.unwrap_or(0)),
+            cache_read_input_tokens: usage.cached_content_token_count,
+            cache_creation_input_tokens: None,
+        },
+    ))
+}
@@ -1,4 +1,5 @@
 pub mod anthropic;
+pub mod gemini;
 pub mod model_info;
 pub mod morph;
 pub mod openai;
@@ -9,20 +10,102 @@ pub mod utils;
 pub use model_info::Model;
 
 pub use anthropic::AnthropicClient;
+pub use gemini::GeminiClient;
 pub use morph::MorphClient;
 pub use openai::OpenAIClient;
 pub use types::*;
 
+use crate::error::{Result, SofosError};
+use model_info::Provider;
 use std::sync::Arc;
 use std::sync::atomic::AtomicBool;
 
+/// Output tokens asked for by [`LlmClient::check_model`]. OpenAI rejects
+/// a `max_output_tokens` below 16.
+const MODEL_CHECK_MAX_TOKENS: u32 = 16;
+
+/// Prompt sent by [`LlmClient::check_model`].
+const MODEL_CHECK_PROMPT: &str = "Reply with OK.";
+
+/// API keys for each provider, any of which may be missing. Kept for the
+/// whole session so `/model` can build a client for another provider.
+#[derive(Clone, Default)]
+pub struct ProviderKeys {
+    pub anthropic: Option<String>,
+    pub openai: Option<String>,
+    pub gemini: Option<String>,
+}
+
+impl ProviderKeys {
+    /// The key for `provider`, or a `Config` error naming the variable
+    /// to set.
+    pub fn key_for(&self, provider: Provider) -> Result<String> {
+        let key = match provider {
+            Provider::Anthropic => &self.anthropic,
+            Provider::OpenAI => &self.openai,
+            Provider::Gemini => &self.gemini,
+        };
+        key.clone()
+            .ok_or_else(|| SofosError::Config(format!("{} not found", provider.api_key_env())))
+    }
+
+    pub fn has_key_for(&self, provider: Provider) -> bool {
+        self.key_for(provider).is_ok()
+    }
+}
+
 #[derive(Clone)]
 pub enum LlmClient {
     Anthropic(AnthropicClient),
     OpenAI(OpenAIClient),
+    Gemini(GeminiClient),
 }
 
 impl LlmClient {
+    /// Build the client for `provider` from its key in `keys`.
+    pub fn for_provider(provider: Provider, keys: &ProviderKeys) -> Result<Self> {
+        let key = keys.key_for(provider)?;
+        Ok(match provider {
+            Provider::Anthropic => LlmClient::Anthropic(AnthropicClient::new(key)?),
+            Provider::OpenAI => LlmClient::OpenAI(OpenAIClient::new(key, None)?),
+            Provider::Gemini => LlmClient::Gemini(GeminiClient::new(key)?),
+        })
+    }
+
+    /// Whether requests go to an `--api-base` server rather than a
+    /// provider's own API.
+    pub fn uses_custom_endpoint(&self) -> bool {
+        matches!(self, LlmClient::OpenAI(client) if client.uses_custom_base())
+    }
+
+    /// Whether this client's requests carry `tool`. Each hosted web
+    /// search goes only to its own provider, and not to a custom endpoint.
+    pub fn sends_tool(&self, tool: &Tool) -> bool {
+        match tool {
+            Tool::Regular { .. } => true,
+            Tool::AnthropicWebSearch { .. } => matches!(self, LlmClient::Anthropic(_)),
+            Tool::OpenAIWebSearch { .. } => {
+                matches!(self, LlmClient::OpenAI(_)) && !self.uses_custom_endpoint()
+            }
+        }
+    }
+
+    pub fn retry_policy(&self) -> utils::RetryPolicy {
+        match self {
+            LlmClient::Anthropic(client) => client.retry_policy(),
+            LlmClient::OpenAI(client) => client.retry_policy(),
+            LlmClient::Gemini(client) => client.retry_policy(),
+        }
+    }
+
+    pub fn with_retry_policy(self, retry: utils::RetryPolicy) -> Self {
+        match self {
+            LlmClient::Anthropic(client) => LlmClient::Anthropic(client.with_retry_policy(retry)),
+            LlmClient::OpenAI(client) => LlmClient::OpenAI(client.with_retry_policy(retry)),
+            LlmClient::Gemini(client) => LlmClient::Gemini(client.with_retry_policy(retry)),
+        }
+    }
+
     pub async fn create_message(
         &self,
         request: types::CreateMessageRequest,
@@ -30,6 +113,7 @@ impl LlmClient {
         match self {
             LlmClient::Anthropic(client) => client.create_message(request).await,
             LlmClient::OpenAI(client) => client.create_message(request).await,
+            LlmClient::Gemini(client) => client.create_message(request).await,
         }
     }
 
@@ -65,6 +149,16 @@ impl LlmClient {
                     )
                     .await
             }
+            LlmClient::Gemini(client) => {
+                client
+                    .create_message_streaming(
+                        request,
+                        on_text_delta,
+                        on_thinking_delta,
+                        interrupt_flag,
+                    )
+                    .await
+            }
         }
     }
 
@@ -72,13 +166,134 @@ impl LlmClient {
         match self {
             LlmClient::Anthropic(client) => client.check_connectivity().await,
             LlmClient::OpenAI(client) => client.check_connectivity().await,
+            LlmClient::Gemini(client) => client.check_connectivity().await,
         }
     }
 
+    /// Send one tiny request with `model`, so an unknown model or one the
+    /// key may not use is reported up front rather than on the first turn.
+    pub async fn check_model(&self, model: &str) -> Result<()> {
+        let request = CreateMessageRequest {
+            model: model.to_string(),
+            max_tokens: MODEL_CHECK_MAX_TOKENS,
+            messages: vec![Message::user(MODEL_CHECK_PROMPT)],
+            system: None,
+            tools: None,
+            stream: None,
+            thinking: None,
+            output_config: None,
+            reasoning: None,
+            prompt_cache_key: None,
+            context_management: None,
+            temperature: None,
+            top_p: None,
+        };
+        self.create_message(request)
+            .await
+            .map(|_| ())
+            .map_err(|e| model_check_error(model, e))
+    }
+
     pub fn provider_name(&self) -> &'static str {
         match self {
             LlmClient::Anthropic(_) => "Anthropic",
             LlmClient::OpenAI(_) => "OpenAI",
+            LlmClient::Gemini(_) => "Gemini",
+        }
+    }
+}
+
+/// Name `model` in a failed check when the provider refused it: 404 for
+/// an unknown model, 403 for one the key may not use, or a 400 about the
+/// model. Other failures pass through unchanged.
+fn model_check_error(model: &str, error: SofosError) -> SofosError {
+    let SofosError::Api(message) = &error else {
+        return error;
+    };
+    let has_status = |code: u16| message.contains(&format!("status {} ", code));
+    let about_model = message.to_ascii_lowercase().contains("model");
+    if has_status(404) || has_status(403) || (has_status(400) && about_model) {
+        return SofosError::Api(format!(
+            "model '{}' not available for this key: {}",
+            model, message
+        ));
+    }
+    error
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn client_for_provider_needs_that_providers_key() {
+        let keys = ProviderKeys {
+            gemini: Some("g-key".to_string()),
+            ..ProviderKeys::default()
+        };
+
+        assert!(matches!(
+            LlmClient::for_provider(Provider::Gemini, &keys),
+            Ok(LlmClient::Gemini(_))
+        ));
+        match LlmClient::for_provider(Provider::OpenAI, &keys) {
+            Err(SofosError::Config(msg)) => assert!(msg.contains("OPENAI_API_KEY"), "got: {msg}"),
+            _ => panic!("expected a missing-key error"),
         }
+        assert!(!keys.has_key_for(Provider::Anthropic));
+    }
+
+    #[test]
+    fn each_client_sends_one_web_search_at_most() {
+        let anthropic_search = Tool::AnthropicWebSearch {
+            tool_type: "web_search_20250305".to_string(),
+            name: "web_search".to_string(),
+            max_uses: None,
+            allowed_domains: None,
+            blocked_domains: None,
+            cache_control: None,
+        };
+        let openai_search = Tool::OpenAIWebSearch {
+            tool_type: "web_search".to_string(),
+        };
+        let anthropic = LlmClient::Anthropic(AnthropicClient::new("k".to_string()).unwrap());
+        let openai = LlmClient::OpenAI(OpenAIClient::new("k".to_string(), None).unwrap());
+        let custom = LlmClient::OpenAI(
+            OpenAIClient::new(String::new(), Some("http://localhost:11434/v1".to_string()))
+                .unwrap(),
+        );
+        let gemini = LlmClient::Gemini(GeminiClient::new("k".to_string()).unwrap());
+
+        assert!(anthropic.sends_tool(&anthropic_search) && !anthropic.sends_tool(&openai_search));
+        assert!(openai.sends_tool(&openai_search) && !openai.sends_tool(&anthropic_search));
+        for client in [custom, gemini] {
+            assert!(!client.sends_tool(&anthropic_search) && !client.sends_tool(&openai_search));
+        }
+    }
+
+    #[test]
+    fn refused_models_are_named_in_the_check_error() {
+        let failed = |status: &str, body: &str| {
+            SofosError::Api(format!(
+                "OpenAI API request failed with status {} after 1 attempt(s): {}",
+                status, body
+            ))
+        };
+        for refused in [
+            failed("404 Not Found", "The model does not exist"),
+            failed("403 Forbidden", "project has no access"),
+            failed("400 Bad Request", "invalid model ID"),
+        ] {
+            let msg = model_check_error("gpt-x", refused).to_string();
+            assert!(
+                msg.contains("model 'gpt-x' not available for this key"),
+                "{msg}"
+            );
+        }
+
+        let unauthorized = model_check_error("gpt-x", failed("401 Unauthorized", "bad key"));
+        assert!(!unauthorized.to_string().contains("not available"));
+        let offline = model_check_error("gpt-x", SofosError::NetworkError("down".into()));
+        assert!(matches!(offline, SofosError::NetworkError(_)));
     }
 }
@@ -15,6 +15,8 @@
 //! one deletion in the same array.
 
 use crate::api::{ReasoningEffort, ReasoningMode};
+use std::collections::BTreeMap;
+use std::sync::OnceLock;
 
 /// Tiered-pricing rule. Some OpenAI models charge a premium for the
 /// *entire session* once a single prompt's input crosses a documented
@@ -34,6 +36,7 @@ pub struct PremiumPricingTier {
 pub enum Provider {
     Anthropic,
     OpenAI,
+    Gemini,
 }
 
 impl Provider {
@@ -41,6 +44,16 @@ impl Provider {
         match self {
             Provider::Anthropic => "Anthropic",
             Provider::OpenAI => "OpenAI",
+            Provider::Gemini => "Gemini",
+        }
+    }
+
+    /// Environment variable that carries this provider's API key.
+    pub fn api_key_env(self) -> &'static str {
+        match self {
+            Provider::Anthropic => "ANTHROPIC_API_KEY",
+            Provider::OpenAI => "OPENAI_API_KEY",
+            Provider::Gemini => "GEMINI_API_KEY",
         }
     }
 }
@@ -57,7 +70,7 @@ pub struct Model {
     /// picker.
     pub description: &'static str,
     /// LLM vendor that hosts this model. The picker greys out rows
-    /// whose provider doesn't match the running session's client.
+    /// on a provider the session has no API key for.
     pub provider: Provider,
     /// API context-window ceiling in tokens.
     pub context_window: u32,
@@ -117,6 +130,35 @@ impl Model {
         ((self.context_window as u64).saturating_mul(95) / 100) as u32
     }
 
+    /// [`effort_support_error`] against this entry, naming the model as
+    /// `name`, so a model only a custom endpoint knows is reported by
+    /// the name the user gave it.
+    pub fn effort_support_error(&self, name: &str, effort: ReasoningEffort) -> Option<String> {
+        if self.supported_efforts.contains(&effort) {
+            return None;
+        }
+        Some(format!(
+            "Model `{}` does not accept reasoning effort `{}`. Supported levels: {}.",
+            name,
+            effort.as_label(),
+            self.supported_efforts_label(),
+        ))
+    }
+
+    /// The supported level after `current`, wrapping to the lowest after
+    /// the highest. Drives the Ctrl+T effort shortcut.
+    pub fn next_effort(&self, current: ReasoningEffort) -> Option<ReasoningEffort> {
+        let next = self
+            .supported_efforts
+            .iter()
+            .position(|e| *e == current)
+            .map_or(0, |i| i + 1);
+        self.supported_efforts
+            .get(next)
+            .or_else(|| self.supported_efforts.first())
+            .copied()
+    }
+
     /// Comma-separated lowercase labels of every effort level this
     /// model accepts (`"low, medium, high, xhigh"` and so on).
     /// Surfaced verbatim in the CLI startup error and in
@@ -183,6 +225,8 @@ pub const GPT_FLAGSHIP: &str = "gpt-5.5";
 pub const GPT_MID_TIER: &str = "gpt-5.4";
 pub const GPT_MINI: &str = "gpt-5.4-mini";
 pub const GPT_CODEX: &str = "gpt-5.3-codex";
+pub const GEMINI_PRO: &str = "gemini-2.5-pro";
+pub const GEMINI_FLASH: &str = "gemini-2.5-flash";
 
 /// Every model the application accepts on `--model`, in the order they
 /// appear in the `/model` picker. The strongest models come first; the
@@ -424,8 +468,105 @@ pub const SUPPORTED_MODELS: &[Model] = &[
         price_output_per_m: 14.0,
         premium_tier: None,
     },
+    // Gemini Pro bills prompts over 200K input tokens at a higher rate,
+    // but per request rather than for the rest of the session, so it
+    // doesn't fit `PremiumPricingTier`. The 180K auto-compact trigger
+    // keeps every prompt on the listed rate instead.
+    Model {
+        name: GEMINI_PRO,
+        description: "Google's strongest Gemini model - long context and deep reasoning",
+        provider: Provider::Gemini,
+        context_window: 1_048_576,
+        auto_compact_token_limit: Some(180_000),
+        requires_adaptive_thinking: false,
+        supports_server_compaction: false,
+        supported_efforts: &[
+            ReasoningEffort::Low,
+            ReasoningEffort::Medium,
+            ReasoningEffort::High,
+        ],
+        supports_pro_mode: false,
+        price_input_per_m: 1.25,
+        price_output_per_m: 10.0,
+        premium_tier: None,
+    },
+    Model {
+        name: GEMINI_FLASH,
+        description: "Fast, low-cost Gemini model with thinking",
+        provider: Provider::Gemini,
+        context_window: 1_048_576,
+        auto_compact_token_limit: Some(250_000),
+        requires_adaptive_thinking: false,
+        supports_server_compaction: false,
+        supported_efforts: &[
+            ReasoningEffort::Low,
+            ReasoningEffort::Medium,
+            ReasoningEffort::High,
+        ],
+        supports_pro_mode: false,
+        price_input_per_m: 0.30,
+        price_output_per_m: 2.5,
+        premium_tier: None,
+    },
+];
+
+/// Describes any model outside the catalog while a custom endpoint is
+/// active. Nothing is known about such a model, so it gets a common
+/// 128K window, the three effort levels every reasoning model takes,
+/// and no price: a local server costs nothing per token.
+pub const CUSTOM_ENDPOINT_MODEL: Model = Model {
+    name: "custom",
+    description: "Model served by a custom OpenAI-compatible endpoint",
+    provider: Provider::OpenAI,
+    context_window: 128_000,
+    auto_compact_token_limit: None,
+    requires_adaptive_thinking: false,
+    supports_server_compaction: false,
+    supported_efforts: &[
+        ReasoningEffort::Low,
+        ReasoningEffort::Medium,
+        ReasoningEffort::High,
+    ],
+    supports_pro_mode: false,
+    price_input_per_m: 0.0,
+    price_output_per_m: 0.0,
+    premium_tier: None,
+};
+
+/// Short names `--model` and `/model` accept for a full model id.
+/// `[aliases]` in the config file adds more or replaces these.
+pub const BUILTIN_MODEL_ALIASES: &[(&str, &str)] = &[
+    ("sonnet", CLAUDE_SONNET),
+    ("opus", CLAUDE_OPUS),
+    ("haiku", CLAUDE_HAIKU),
+    ("codex", GPT_CODEX),
 ];
 
+/// `[aliases]` from the config files, keyed in lowercase.
+static MODEL_ALIASES: OnceLock<BTreeMap<String, String>> = OnceLock::new();
+
+/// Install the configured aliases. Only the first call takes effect.
+pub fn set_model_aliases(aliases: BTreeMap<String, String>) {
+    let _ = MODEL_ALIASES.set(aliases);
+}
+
+/// The model id `name` is an alias for, ignoring case, or `None` when
+/// it is not an alias. Configured aliases win over the built-in ones.
+pub fn expand_alias(name: &str) -> Option<String> {
+    expand_alias_in(MODEL_ALIASES.get(), name)
+}
+
+fn expand_alias_in(configured: Option<&BTreeMap<String, String>>, name: &str) -> Option<String> {
+    let key = name.trim().to_ascii_lowercase();
+    if let Some(id) = configured.and_then(|aliases| aliases.get(&key)) {
+        return Some(id.clone());
+    }
+    BUILTIN_MODEL_ALIASES
+        .iter()
+        .find(|(alias, _)| *alias == key)
+        .map(|(_, id)| id.to_string())
+}
+
 /// Comma-separated list of every supported model id, in catalog
 /// order. Used by [`model_support_error`] and surfaced in the CLI
 /// startup error so the user sees the same labels both places.
@@ -475,7 +616,18 @@ pub fn provider_for(name: &str) -> Provider {
 /// CLI rejects unknown ids up front so this fallback only fires from
 /// internal call sites that pass arbitrary strings.
 pub fn lookup(name: &str) -> &'static Model {
-    canonical_model(name).unwrap_or(&SUPPORTED_MODELS[DEFAULT_MODEL_INDEX])
+    lookup_on(name, false)
+}
+
+/// [`lookup`] for a session whose client talks to an `--api-base`
+/// endpoint when `custom_endpoint` is set. Unknown ids are then the
+/// endpoint's own models and get [`CUSTOM_ENDPOINT_MODEL`].
+pub fn lookup_on(name: &str, custom_endpoint: bool) -> &'static Model {
+    match canonical_model(name) {
+        Some(model) => model,
+        None if custom_endpoint => &CUSTOM_ENDPOINT_MODEL,
+        None => &SUPPORTED_MODELS[DEFAULT_MODEL_INDEX],
+    }
 }
 
 /// Human-readable rejection message for an unsupported `(model, effort)`
@@ -485,16 +637,7 @@ pub fn lookup(name: &str) -> &'static Model {
 /// Surfaced to the user from the startup validator and the `/effort`
 /// handler so the failure mode is the same in both places.
 pub fn effort_support_error(name: &str, effort: ReasoningEffort) -> Option<String> {
-    let info = lookup(name);
-    if info.supported_efforts.contains(&effort) {
-        return None;
-    }
-    Some(format!(
-        "Model `{}` does not accept reasoning effort `{}`. Supported levels: {}.",
-        name,
-        effort.as_label(),
-        info.supported_efforts_label(),
-    ))
+    lookup(name).effort_support_error(name, effort)
 }
 
 /// Comma-separated list of every model that accepts `reasoning.mode:
@@ -535,6 +678,19 @@ pub fn supports_pro_mode(name: &str) -> bool {
 mod tests {
     use super::*;
 
+    #[test]
+    fn next_effort_steps_through_supported_levels_and_wraps() {
+        let opus = lookup(CLAUDE_OPUS);
+        assert_eq!(
+            opus.next_effort(ReasoningEffort::Medium),
+            Some(ReasoningEffort::High)
+        );
+        assert_eq!(
+            opus.next_effort(ReasoningEffort::Max),
+            Some(ReasoningEffort::Low)
+        );
+    }
+
     #[test]
     fn provider_routes_supported_models_correctly() {
         assert_eq!(provider_for(CLAUDE_FABLE), Provider::Anthropic);
@@ -548,6 +704,8 @@ mod tests {
         assert_eq!(provider_for(GPT_MID_TIER), Provider::OpenAI);
         assert_eq!(provider_for(GPT_MINI), Provider::OpenAI);
         assert_eq!(provider_for(GPT_CODEX), Provider::OpenAI);
+        assert_eq!(provider_for(GEMINI_PRO), Provider::Gemini);
+        assert_eq!(provider_for(GEMINI_FLASH), Provider::Gemini);
         // Case insensitivity covers an upper-cased `--model` argument.
         assert_eq!(
             provider_for(&CLAUDE_OPUS.to_uppercase()),
@@ -562,6 +720,7 @@ mod tests {
     fn provider_label_is_human_readable() {
         assert_eq!(Provider::OpenAI.label(), "OpenAI");
         assert_eq!(Provider::Anthropic.label(), "Anthropic");
+        assert_eq!(Provider::Gemini.label(), "Gemini");
     }
 
     #[test]
@@ -581,10 +740,24 @@ mod tests {
                 GPT_MID_TIER,
                 GPT_MINI,
                 GPT_CODEX,
+                GEMINI_PRO,
+                GEMINI_FLASH,
             ]
         );
     }
 
+    #[test]
+    fn custom_endpoint_describes_models_outside_the_catalog() {
+        assert_eq!(
+            lookup_on("qwen2.5-coder", true).name,
+            CUSTOM_ENDPOINT_MODEL.name
+        );
+        assert_eq!(lookup_on("qwen2.5-coder", true).provider, Provider::OpenAI);
+        assert_eq!(lookup_on(GPT_MINI, true).name, GPT_MINI);
+        assert_eq!(lookup("qwen2.5-coder").name, CLAUDE_SONNET);
+        assert!(model_support_error("qwen2.5-coder").is_some());
+    }
+
     #[test]
     fn default_model_is_the_cli_default() {
         assert_eq!(Model::default().name, CLAUDE_SONNET);
@@ -597,6 +770,35 @@ mod tests {
         assert_eq!(m.name, CLAUDE_SONNET);
     }
 
+    #[test]
+    fn aliases_expand_to_supported_ids_and_config_wins() {
+        for (alias, id) in BUILTIN_MODEL_ALIASES {
+            assert!(canonical_model(id).is_some(), "{alias} -> {id}");
+        }
+        assert_eq!(
+            expand_alias_in(None, "Sonnet").as_deref(),
+            Some(CLAUDE_SONNET)
+        );
+        assert_eq!(expand_alias_in(None, CLAUDE_SONNET), None);
+
+        let configured = BTreeMap::from([
+            ("sonnet".to_string(), CLAUDE_FABLE.to_string()),
+            ("mini".to_string(), GPT_MINI.to_string()),
+        ]);
+        assert_eq!(
+            expand_alias_in(Some(&configured), "sonnet").as_deref(),
+            Some(CLAUDE_FABLE)
+        );
+        assert_eq!(
+            expand_alias_in(Some(&configured), "MINI").as_deref(),
+            Some(GPT_MINI)
+        );
+        assert_eq!(
+            expand_alias_in(Some(&configured), "opus").as_deref(),
+            Some(CLAUDE_OPUS)
+        );
+    }
+
     #[test]
     fn model_support_error_accepts_whitelist_and_rejects_others() {
         for m in SUPPORTED_MODELS {
@@ -13,34 +13,75 @@ use reqwest::header::{AUTHORIZATION, HeaderMap, HeaderValue};
 pub(super) const OPENAI_API_BASE: &str = "https://api.openai.com/v1";
 
 impl OpenAIClient {
-    pub fn new(api_key: String) -> Result<Self> {
+    /// `base_url` points the client at an OpenAI-compatible server
+    /// instead of OpenAI. Such servers often need no key, so an empty
+    /// `api_key` sends no `Authorization` header.
+    pub fn new(api_key: String, base_url: Option<String>) -> Result<Self> {
         let mut headers = HeaderMap::new();
-        headers.insert(
-            AUTHORIZATION,
-            HeaderValue::from_str(&format!("Bearer {}", api_key))
-                .map_err(|e| SofosError::Config(format!("Invalid API key format: {}", e)))?,
-        );
+        if !api_key.is_empty() {
+            headers.insert(
+                AUTHORIZATION,
+                HeaderValue::from_str(&format!("Bearer {}", api_key))
+                    .map_err(|e| SofosError::Config(format!("Invalid API key format: {}", e)))?,
+            );
+        }
 
         let client = utils::build_http_client(headers, utils::REQUEST_TIMEOUT)?;
+        let base_url = base_url
+            .map(|url| url.trim_end_matches('/').to_string())
+            .unwrap_or_else(|| OPENAI_API_BASE.to_string());
+
+        Ok(Self {
+            client,
+            retry: utils::RetryPolicy::default(),
+            base_url,
+        })
+    }
+
+    pub fn uses_custom_base(&self) -> bool {
+        self.base_url != OPENAI_API_BASE
+    }
+
+    /// Drop the parts of `request` only OpenAI itself understands when
+    /// talking to a custom endpoint: local servers reject reasoning
+    /// settings on models without thinking, and have no hosted web
+    /// search.
+    pub(super) fn prepare_request(
+        &self,
+        mut request: CreateMessageRequest,
+    ) -> CreateMessageRequest {
+        if self.uses_custom_base() {
+            request.reasoning = None;
+            if let Some(tools) = request.tools.as_mut() {
+                tools.retain(|tool| !matches!(tool, Tool::OpenAIWebSearch { .. }));
+            }
+        }
+        request
+    }
+
+    pub fn with_retry_policy(mut self, retry: utils::RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
 
-        Ok(Self { client })
+    pub fn retry_policy(&self) -> utils::RetryPolicy {
+        self.retry
     }
 
     pub async fn check_connectivity(&self) -> Result<()> {
-        utils::check_api_connectivity(
-            &self.client,
-            OPENAI_API_BASE,
-            "OpenAI",
-            "https://status.openai.com",
-        )
-        .await
+        let status_url = if self.uses_custom_base() {
+            self.base_url.as_str()
+        } else {
+            "https://status.openai.com"
+        };
+        utils::check_api_connectivity(&self.client, &self.base_url, "OpenAI", status_url).await
     }
 
     pub async fn create_message(
         &self,
         request: CreateMessageRequest,
     ) -> Result<CreateMessageResponse> {
-        self.call_responses(request).await
+        self.call_responses(self.prepare_request(request)).await
     }
 
     async fn call_responses(&self, request: CreateMessageRequest) -> Result<CreateMessageResponse> {
@@ -59,7 +100,7 @@ impl OpenAIClient {
             }
         }
 
-        let url = format!("{}/responses", OPENAI_API_BASE);
+        let url = format!("{}/responses", self.base_url);
 
         if std::env::var("SOFOS_DEBUG").is_ok() {
             eprintln!("\n=== OpenAI /responses Request Body ===");
@@ -71,7 +112,9 @@ impl OpenAIClient {
             eprintln!("======================================\n");
         }
 
-        let response = utils::send_once("OpenAI", self.client.post(&url).json(&body)).await?;
+        let response =
+            utils::send_with_reconnect("OpenAI", self.retry, || self.client.post(&url).json(&body))
+                .await?;
 
         let response_text = response.text().await?;
 
@@ -17,6 +17,10 @@ pub mod wire;
 #[derive(Clone)]
 pub struct OpenAIClient {
     pub(super) client: reqwest::Client,
+    pub(super) retry: crate::api::utils::RetryPolicy,
+    /// `/responses` and connectivity checks go here. OpenAI's own API
+    /// unless `--api-base` names an OpenAI-compatible server.
+    pub(super) base_url: String,
 }
 
 #[cfg(test)]
@@ -41,6 +45,8 @@ mod tests {
             reasoning: None,
             prompt_cache_key: key.map(str::to_string),
             context_management: None,
+            temperature: None,
+            top_p: None,
         }
     }
 
@@ -50,6 +56,60 @@ mod tests {
         assert_eq!(body["prompt_cache_key"], "session-xyz");
     }
 
+    #[test]
+    fn custom_endpoint_drops_reasoning_and_hosted_web_search() {
+        let mut req = req_with_cache_key(None);
+        req.reasoning = Some(crate::api::Reasoning::with_effort("medium"));
+        req.tools = Some(vec![
+            Tool::Regular {
+                name: "read_file".to_string(),
+                description: "Read a file".to_string(),
+                input_schema: serde_json::json!({"type": "object"}),
+                cache_control: None,
+            },
+            Tool::OpenAIWebSearch {
+                tool_type: "web_search".to_string(),
+            },
+        ]);
+
+        let openai = super::OpenAIClient::new("key".to_string(), None).unwrap();
+        let kept = openai.prepare_request(req.clone());
+        assert!(kept.reasoning.is_some());
+        assert_eq!(kept.tools.as_ref().map(Vec::len), Some(2));
+
+        let local = super::OpenAIClient::new(
+            String::new(),
+            Some("http://localhost:11434/v1/".to_string()),
+        )
+        .unwrap();
+        assert_eq!(local.base_url, "http://localhost:11434/v1");
+        let stripped = local.prepare_request(req);
+        assert!(stripped.reasoning.is_none());
+        assert!(matches!(
+            stripped.tools.as_deref(),
+            Some([Tool::Regular { .. }])
+        ));
+    }
+
+    #[test]
+    fn responses_body_carries_sampling_only_when_set() {
+        let body = build_responses_body(&req_with_cache_key(None));
+        assert!(body.get("temperature").is_none());
+        assert!(body.get("top_p").is_none());
+
+        let mut req = req_with_cache_key(None);
+        req.temperature = Some(0.5);
+        req.top_p = Some(0.25);
+        let body = build_responses_body(&req);
+        assert_eq!(body["temperature"], 0.5);
+        assert_eq!(body["top_p"], 0.25);
+
+        req.reasoning = Some(crate::api::Reasoning::with_effort("medium"));
+        let body = build_responses_body(&req);
+        assert!(body.get("temperature").is_none());
+        assert!(body.get("top_p").is_none());
+    }
+
     #[test]
     fn responses_body_omits_prompt_cache_key_when_none() {
         let body = build_responses_body(&req_with_cache_key(None));
@@ -124,6 +184,8 @@ mod tests {
             reasoning: None,
             prompt_cache_key: None,
             context_management: None,
+            temperature: None,
+            top_p: None,
         };
         let body = build_responses_body(&req);
         let inputs = body
@@ -180,6 +242,8 @@ mod tests {
             reasoning: None,
             prompt_cache_key: None,
             context_management: None,
+            temperature: None,
+            top_p: None,
         };
         let body = build_responses_body(&req);
         let inputs = body.get("input").and_then(|v| v.as_array()).unwrap();
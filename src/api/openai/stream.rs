@@ -7,7 +7,6 @@
 //! values.
 
 use crate::api::openai::OpenAIClient;
-use crate::api::openai::client::OPENAI_API_BASE;
 use crate::api::openai::wire::{OpenAIResponse, build_response, build_responses_body};
 use crate::api::types::*;
 use crate::api::utils;
@@ -38,11 +37,14 @@ impl OpenAIClient {
         FText: Fn(&str) + Send + Sync,
         FThink: Fn(&str) + Send + Sync,
     {
+        let request = self.prepare_request(request);
         let mut body = build_responses_body(&request);
         body["stream"] = json!(true);
 
-        let url = format!("{}/responses", OPENAI_API_BASE);
-        let response = utils::send_once("OpenAI", self.client.post(&url).json(&body)).await?;
+        let url = format!("{}/responses", self.base_url);
+        let response =
+            utils::send_with_reconnect("OpenAI", self.retry, || self.client.post(&url).json(&body))
+                .await?;
 
         let byte_stream = response.bytes_stream().map(|chunk_result| {
             chunk_result.map_err(|e| SofosError::NetworkError(format!("Stream read error: {}", e)))
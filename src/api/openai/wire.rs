@@ -37,6 +37,16 @@ pub(super) fn build_responses_body(request: &CreateMessageRequest) -> serde_json
     if let Some(ref cache_key) = request.prompt_cache_key {
         body["prompt_cache_key"] = json!(cache_key);
     }
+    // Reasoning models reject sampling parameters outright; only a
+    // custom endpoint, which has reasoning stripped, gets them.
+    if request.reasoning.is_none() {
+        if let Some(temperature) = request.temperature {
+            body["temperature"] = json!(temperature);
+        }
+        if let Some(top_p) = request.top_p {
+            body["top_p"] = json!(top_p);
+        }
+    }
 
     if let Some(tool_list) = request.tools.clone() {
         let tools: Vec<serde_json::Value> = tool_list
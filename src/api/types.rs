@@ -80,6 +80,14 @@ pub struct CreateMessageRequest {
     /// every message before it. Cleared on the OpenAI path.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub context_management: Option<ContextManagement>,
+    /// Sampling temperature from `--temperature`; unset keeps the
+    /// provider default. Anthropic drops it while thinking is on.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f32>,
+    /// Nucleus-sampling cutoff from `--top-p`; unset keeps the provider
+    /// default.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_p: Option<f32>,
 }
 
 /// Anthropic `context_management` configuration. Currently models a
@@ -373,6 +381,37 @@ impl ReasoningMode {
     }
 }
 
+/// Highest `--temperature` any provider accepts.
+pub const MAX_TEMPERATURE: f32 = 2.0;
+
+/// Sampling overrides from `--temperature` and `--top-p`. Each field
+/// left unset keeps the provider's default.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Sampling {
+    pub temperature: Option<f32>,
+    pub top_p: Option<f32>,
+}
+
+impl Sampling {
+    /// Why the values are out of range, if they are: temperature must lie
+    /// in `0..=MAX_TEMPERATURE` and top-p in `0..=1`.
+    pub fn validation_error(&self) -> Option<String> {
+        if self
+            .temperature
+            .is_some_and(|t| !(0.0..=MAX_TEMPERATURE).contains(&t))
+        {
+            return Some(format!(
+                "--temperature must be between 0 and {}",
+                MAX_TEMPERATURE
+            ));
+        }
+        if self.top_p.is_some_and(|p| !(0.0..=1.0).contains(&p)) {
+            return Some("--top-p must be between 0 and 1".to_string());
+        }
+        None
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WebSearchResult {
     #[serde(rename = "type")]
@@ -4,6 +4,7 @@ use colored::Colorize;
 use rand::RngExt;
 use reqwest::header::{CONTENT_TYPE, HeaderMap, HeaderValue};
 use std::future::Future;
+use std::sync::OnceLock;
 use std::time::Duration;
 
 /// Client-level ceiling for the main LLM providers (Anthropic, OpenAI).
@@ -21,7 +22,13 @@ pub const REQUEST_TIMEOUT: Duration = Duration::from_secs(1800);
 /// kills a request the dispatcher would still be happy to wait for.
 pub const MORPH_REQUEST_TIMEOUT: Duration = Duration::from_secs(600);
 pub const MAX_RETRIES: u32 = 2;
+/// Retries the main provider endpoints get unless `[retry] max_retries`
+/// says otherwise.
+pub const PROVIDER_MAX_RETRIES: u32 = 3;
 pub const INITIAL_RETRY_DELAY_MS: u64 = 1000;
+/// Retries [`with_retries`] gives a 429, so an ongoing limit doesn't burn
+/// through every retry slot waiting.
+const MAX_RATE_LIMIT_RETRIES: u32 = 1;
 const JITTER_FACTOR: f64 = 0.3; // Add 0-30% random jitter
 
 /// Default `Content-Type` applied by [`build_http_client`] when the
@@ -442,11 +449,42 @@ pub fn sanitize_provider_error_body(body: &str) -> String {
 /// hours) can't lock sofos for an unreasonable wait.
 const MAX_RATE_LIMIT_RETRY_AFTER: Duration = Duration::from_secs(60);
 
-/// `ServerError` and `RateLimited` trigger a retry — transport failures
-/// and other 4xx statuses fail fast. `RateLimited` carries the
+/// How many times a failed request is retried, and the first backoff
+/// delay, which doubles on every retry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    pub max_retries: u32,
+    pub base_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: PROVIDER_MAX_RETRIES,
+            base_delay: Duration::from_millis(INITIAL_RETRY_DELAY_MS),
+        }
+    }
+}
+
+impl From<crate::config::RetrySettings> for RetryPolicy {
+    fn from(settings: crate::config::RetrySettings) -> Self {
+        let default = Self::default();
+        Self {
+            max_retries: settings.max_retries.unwrap_or(default.max_retries),
+            base_delay: settings
+                .base_delay_ms
+                .map(Duration::from_millis)
+                .unwrap_or(default.base_delay),
+        }
+    }
+}
+
+/// `ServerError`, `RateLimited`, and transient network failures trigger
+/// a retry — other transport failures and 4xx statuses fail fast.
+/// `RateLimited` carries the
 /// `Retry-After` value the server asked for, capped at
-/// [`MAX_RATE_LIMIT_RETRY_AFTER`]; the retry loop is also capped at one
-/// extra attempt for this variant so an ongoing limit doesn't burn
+/// [`MAX_RATE_LIMIT_RETRY_AFTER`]; [`with_retries`] also caps it at one
+/// extra attempt so an ongoing limit doesn't burn
 /// through every retry slot waiting.
 #[derive(Debug)]
 pub enum ApiCallError {
@@ -470,11 +508,30 @@ pub enum ApiCallError {
 
 impl ApiCallError {
     fn is_retryable(&self) -> bool {
-        matches!(self, Self::ServerError { .. } | Self::RateLimited { .. })
+        match self {
+            Self::Transport(e) => is_connection_failure(e) || e.is_timeout(),
+            Self::ServerError { .. } | Self::RateLimited { .. } => true,
+            Self::ClientError { .. } => false,
+        }
+    }
+
+    fn is_connection_failure(&self) -> bool {
+        matches!(self, Self::Transport(e) if is_connection_failure(e))
+    }
+
+    /// Failures the main endpoints retry: a dropped connection, a rate
+    /// limit, or a 5xx. A timeout is not, since the provider may still be
+    /// working through the request.
+    fn is_retryable_on_main_endpoint(&self) -> bool {
+        match self {
+            Self::RateLimited { .. } | Self::ServerError { .. } => true,
+            _ => self.is_connection_failure(),
+        }
     }
 
     fn describe(&self) -> String {
         match self {
+            Self::Transport(e) if is_connection_failure(e) => format!("Connection failed: {}", e),
             Self::Transport(e) => format!("Request failed: {}", e),
             Self::ServerError { status, .. } => format!("Server error {}", status),
             Self::RateLimited { retry_after, .. } => match retry_after {
@@ -486,6 +543,48 @@ impl ApiCallError {
     }
 }
 
+/// DNS failures, refused connections, and connections dropped before a
+/// response arrived. These are network blips, not provider errors.
+fn is_connection_failure(error: &reqwest::Error) -> bool {
+    if error.is_connect() {
+        return true;
+    }
+    let mut source = std::error::Error::source(error);
+    while let Some(inner) = source {
+        if let Some(io) = inner.downcast_ref::<std::io::Error>() {
+            if matches!(
+                io.kind(),
+                std::io::ErrorKind::ConnectionReset
+                    | std::io::ErrorKind::ConnectionAborted
+                    | std::io::ErrorKind::BrokenPipe
+            ) {
+                return true;
+            }
+        }
+        source = inner.source();
+    }
+    false
+}
+
+/// Shows a short status next to the spinner while a request waits to be
+/// retried; `None` clears it once the retry is sent.
+pub type RetryNoticeHandler = Box<dyn Fn(Option<&str>) + Send + Sync>;
+
+static RETRY_NOTICE_HANDLER: OnceLock<RetryNoticeHandler> = OnceLock::new();
+
+/// Install a process-global retry notice handler. Can only be set once —
+/// subsequent calls are silently ignored. Returns `true` if the handler
+/// was installed, `false` if one was already registered.
+pub fn set_retry_notice_handler(handler: RetryNoticeHandler) -> bool {
+    RETRY_NOTICE_HANDLER.set(handler).is_ok()
+}
+
+fn notify_retry(status: Option<&str>) {
+    if let Some(handler) = RETRY_NOTICE_HANDLER.get() {
+        handler(status);
+    }
+}
+
 /// Read the `Retry-After` header in its seconds-since-now form and clamp
 /// the result to [`MAX_RATE_LIMIT_RETRY_AFTER`]. RFC 7231 also allows an
 /// HTTP-date form, but every API we integrate with uses the seconds
@@ -536,16 +635,27 @@ pub async fn send_classified(
 }
 
 /// Use this when a retry would re-burn an expensive call — the main
-/// Anthropic and OpenAI endpoints, where a 5xx or timeout is surfaced to
-/// the user immediately rather than quietly re-running a long thinking
-/// phase.
-pub async fn send_once(
+/// Anthropic and OpenAI endpoints, where timeouts are surfaced to the
+/// user immediately rather than quietly re-running a long thinking
+/// phase. Connection failures, 429s, and 5xx responses are retried under
+/// `policy`, 429s included up to its full retry count. `build` is called
+/// once per attempt.
+pub async fn send_with_reconnect<B>(
     service_name: &str,
-    request: reqwest::RequestBuilder,
-) -> Result<reqwest::Response> {
-    send_classified(request)
-        .await
-        .map_err(|e| api_call_error_to_sofos(service_name, 1, e))
+    policy: RetryPolicy,
+    build: B,
+) -> Result<reqwest::Response>
+where
+    B: Fn() -> reqwest::RequestBuilder,
+{
+    retry_loop(
+        service_name,
+        policy,
+        || send_classified(build()),
+        ApiCallError::is_retryable_on_main_endpoint,
+        policy.max_retries,
+    )
+    .await
 }
 
 fn api_call_error_to_sofos(service_name: &str, attempts: u32, e: ApiCallError) -> SofosError {
@@ -576,55 +686,55 @@ fn api_call_error_to_sofos(service_name: &str, attempts: u32, e: ApiCallError) -
     }
 }
 
-/// Retries 5xx responses and 429 rate-limit responses; transport
-/// failures and other 4xx statuses fail fast, since retrying those
-/// either re-burns expensive work or re-hits a deterministic client
-/// error. A 429 is retried at most once, using the server-supplied
-/// `Retry-After` delay (capped at [`MAX_RATE_LIMIT_RETRY_AFTER`]) when
-/// present and the exponential-backoff delay otherwise.
+/// Retries 5xx responses, 429 rate-limit responses, and transient
+/// network failures (DNS, refused or reset connections, timeouts);
+/// other 4xx statuses fail fast, since retrying those re-hits a
+/// deterministic client error. A 429 is retried at most once, using the
+/// server-supplied `Retry-After` delay (capped at
+/// [`MAX_RATE_LIMIT_RETRY_AFTER`]) when present and the
+/// exponential-backoff delay otherwise.
 pub async fn with_retries<F, Fut, T>(service_name: &str, operation: F) -> Result<T>
 where
     F: Fn() -> Fut,
     Fut: Future<Output = std::result::Result<T, ApiCallError>>,
 {
-    let mut retry_delay = Duration::from_millis(INITIAL_RETRY_DELAY_MS);
+    let policy = RetryPolicy {
+        max_retries: MAX_RETRIES,
+        ..RetryPolicy::default()
+    };
+    retry_loop(
+        service_name,
+        policy,
+        operation,
+        ApiCallError::is_retryable,
+        MAX_RATE_LIMIT_RETRIES,
+    )
+    .await
+}
+
+/// The shared backoff loop: `should_retry` picks which failures get
+/// another attempt, and a 429 gets at most `rate_limit_retries`.
+async fn retry_loop<F, Fut, T>(
+    service_name: &str,
+    policy: RetryPolicy,
+    operation: F,
+    should_retry: fn(&ApiCallError) -> bool,
+    rate_limit_retries: u32,
+) -> Result<T>
+where
+    F: Fn() -> Fut,
+    Fut: Future<Output = std::result::Result<T, ApiCallError>>,
+{
+    let max_retries = policy.max_retries;
+    let mut retry_delay = policy.base_delay;
     let mut next_delay_override: Option<Duration> = None;
     let mut rate_limit_attempts: u32 = 0;
-    const MAX_RATE_LIMIT_RETRIES: u32 = 1;
-
-    for attempt in 0..=MAX_RETRIES {
-        if attempt > 0 {
-            // Server-supplied `Retry-After` wins over the
-            // exponential-backoff schedule for one iteration. Jitter is
-            // applied either way so a synchronised retry storm from
-            // many clients on the same shared limit doesn't all wake
-            // up at the same instant.
-            let base_delay = next_delay_override.take().unwrap_or(retry_delay);
-            let jitter = rand::rng().random_range(0.0..JITTER_FACTOR);
-            let jittered_delay = base_delay.mul_f64(1.0 + jitter);
-
-            tracing::warn!(
-                service = service_name,
-                attempt = attempt,
-                max_retries = MAX_RETRIES,
-                delay_ms = jittered_delay.as_millis() as u64,
-                "Retrying API request after retryable error"
-            );
-            eprintln!(
-                " {} retrying in {:?}... (attempt {}/{})",
-                format!("{}:", service_name).bright_yellow(),
-                jittered_delay,
-                attempt,
-                MAX_RETRIES
-            );
-            tokio::time::sleep(jittered_delay).await;
-            retry_delay *= 2;
-        }
 
+    for attempt in 0..=max_retries {
         match operation().await {
             Ok(result) => return Ok(result),
             Err(e) => {
-                let retryable = e.is_retryable();
+                let retryable = should_retry(&e);
                 let is_rate_limited = matches!(e, ApiCallError::RateLimited { .. });
                 if is_rate_limited {
                     rate_limit_attempts += 1;
@@ -637,8 +747,47 @@ where
                     }
                 }
                 let rate_limit_cap_reached =
-                    is_rate_limited && rate_limit_attempts > MAX_RATE_LIMIT_RETRIES;
-                if attempt < MAX_RETRIES && retryable && !rate_limit_cap_reached {
+                    is_rate_limited && rate_limit_attempts > rate_limit_retries;
+                if attempt < max_retries && retryable && !rate_limit_cap_reached {
+                    // Server-supplied `Retry-After` wins over the
+                    // exponential-backoff schedule for one iteration.
+                    // Jitter is applied either way so a synchronised
+                    // retry storm from many clients on the same shared
+                    // limit doesn't all wake up at the same instant.
+                    let base_delay = next_delay_override.take().unwrap_or(retry_delay);
+                    let jitter = rand::rng().random_range(0.0..JITTER_FACTOR);
+                    let jittered_delay = base_delay.mul_f64(1.0 + jitter);
+                    let next_attempt = attempt + 1;
+                    let reason = e.describe();
+
+                    tracing::warn!(
+                        service = service_name,
+                        attempt = next_attempt,
+                        max_retries = max_retries,
+                        delay_ms = jittered_delay.as_millis() as u64,
+                        reason = %reason,
+                        "Retrying API request after retryable error"
+                    );
+                    eprintln!(
+                        " {} {}, retrying in {:?}... (attempt {}/{})",
+                        format!("{}:", service_name).bright_yellow(),
+                        reason,
+                        jittered_delay,
+                        next_attempt,
+                        max_retries
+                    );
+                    let status = if e.is_connection_failure() {
+                        "reconnecting"
+                    } else {
+                        "retrying"
+                    };
+                    notify_retry(Some(&format!(
+                        "{} ({}/{})",
+                        status, next_attempt, max_retries
+                    )));
+                    tokio::time::sleep(jittered_delay).await;
+                    notify_retry(None);
+                    retry_delay *= 2;
                     continue;
                 }
                 let attempts = attempt + 1;
@@ -875,6 +1024,49 @@ mod tests {
         assert_eq!(attempts.load(Ordering::SeqCst), 3);
     }
 
+    /// A real connection failure: nothing listens on port 1.
+    async fn refused_connection() -> ApiCallError {
+        match reqwest::Client::new()
+            .get("http://127.0.0.1:1")
+            .send()
+            .await
+        {
+            Err(e) => ApiCallError::Transport(e),
+            Ok(_) => panic!("expected a refused connection"),
+        }
+    }
+
+    #[tokio::test]
+    async fn with_retries_retries_connection_failure_then_succeeds() {
+        use std::sync::atomic::{AtomicU32, Ordering};
+        let attempts = AtomicU32::new(0);
+        let result: Result<&'static str> = with_retries("Test", || {
+            let n = attempts.fetch_add(1, Ordering::SeqCst);
+            async move {
+                if n == 0 {
+                    Err(refused_connection().await)
+                } else {
+                    Ok("done")
+                }
+            }
+        })
+        .await;
+        assert_eq!(result.unwrap(), "done");
+        assert_eq!(attempts.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn refused_connection_counts_as_a_connection_failure() {
+        let refused = refused_connection().await;
+        assert!(refused.is_connection_failure());
+        assert!(refused.is_retryable());
+        let server = ApiCallError::ServerError {
+            status: reqwest::StatusCode::BAD_GATEWAY,
+            body: String::new(),
+        };
+        assert!(!server.is_connection_failure());
+    }
+
     #[tokio::test]
     async fn with_retries_does_not_retry_client_error() {
         use std::sync::atomic::{AtomicU32, Ordering};
@@ -893,6 +1085,85 @@ mod tests {
         assert_eq!(attempts.load(Ordering::SeqCst), 1);
     }
 
+    #[test]
+    fn main_endpoints_retry_server_errors_but_not_client_errors() {
+        let server = |code| ApiCallError::ServerError {
+            status: reqwest::StatusCode::from_u16(code).unwrap(),
+            body: String::new(),
+        };
+        let rate_limited = ApiCallError::RateLimited {
+            retry_after: None,
+            body: String::new(),
+        };
+        assert!(rate_limited.is_retryable_on_main_endpoint());
+        for code in [500, 502, 503, 529] {
+            assert!(server(code).is_retryable_on_main_endpoint(), "{code}");
+        }
+        assert!(
+            !ApiCallError::ClientError {
+                status: reqwest::StatusCode::UNAUTHORIZED,
+                body: String::new(),
+            }
+            .is_retryable_on_main_endpoint()
+        );
+    }
+
+    #[tokio::test]
+    async fn retry_loop_honours_the_policy_retry_count() {
+        use std::sync::atomic::{AtomicU32, Ordering};
+        let attempts = AtomicU32::new(0);
+        let policy = RetryPolicy {
+            max_retries: 1,
+            base_delay: Duration::from_millis(1),
+        };
+        let result: Result<&'static str> = retry_loop(
+            "Test",
+            policy,
+            || {
+                attempts.fetch_add(1, Ordering::SeqCst);
+                async move {
+                    Err(ApiCallError::ServerError {
+                        status: reqwest::StatusCode::from_u16(529).unwrap(),
+                        body: "overloaded".into(),
+                    })
+                }
+            },
+            ApiCallError::is_retryable_on_main_endpoint,
+            policy.max_retries,
+        )
+        .await;
+        assert!(result.is_err());
+        assert_eq!(attempts.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn main_endpoints_retry_a_rate_limit_up_to_the_policy_count() {
+        use std::sync::atomic::{AtomicU32, Ordering};
+        let attempts = AtomicU32::new(0);
+        let policy = RetryPolicy {
+            max_retries: 3,
+            base_delay: Duration::from_millis(1),
+        };
+        let result: Result<&'static str> = retry_loop(
+            "Test",
+            policy,
+            || {
+                attempts.fetch_add(1, Ordering::SeqCst);
+                async move {
+                    Err(ApiCallError::RateLimited {
+                        retry_after: Some(Duration::from_millis(1)),
+                        body: "slow down".into(),
+                    })
+                }
+            },
+            ApiCallError::is_retryable_on_main_endpoint,
+            policy.max_retries,
+        )
+        .await;
+        assert!(result.is_err());
+        assert_eq!(attempts.load(Ordering::SeqCst), 4);
+    }
+
     #[test]
     fn merge_default_headers_adds_content_type_when_absent() {
         let merged = merge_default_headers(HeaderMap::new());
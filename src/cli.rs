@@ -1,5 +1,11 @@
+use crate::api::{ProviderKeys, Sampling};
+use crate::config::DefaultFlags;
 use crate::error::SofosError;
-use clap::Parser;
+use crate::tools::bash::output::BashLimits;
+use clap::parser::ValueSource;
+use clap::{ArgMatches, Parser};
+use std::collections::HashMap;
+use std::time::Duration;
 
 /// Default for the deprecated `--thinking-budget` flag. Kept as a named
 /// const so `main.rs` can warn when the user supplies a value that
@@ -11,8 +17,8 @@ pub const THINKING_BUDGET_DEFAULT: u32 = 5120;
 #[derive(Parser, Debug)]
 #[command(
     name = "sofos",
-    about = "An interactive AI coding assistant powered by Claude or OpenAI",
-    long_about = "Sofos is an AI-powered coding assistant (Claude / OpenAI) that can help you write code, edit files, and search the web.",
+    about = "An interactive AI coding assistant powered by Claude, OpenAI or Gemini",
+    long_about = "Sofos is an AI-powered coding assistant (Claude / OpenAI / Gemini) that can help you write code, edit files, and search the web.",
     version
 )]
 pub struct Cli {
@@ -22,21 +28,74 @@ pub struct Cli {
     #[arg(long, env = "OPENAI_API_KEY")]
     pub openai_api_key: Option<String>,
 
+    #[arg(long, env = "GEMINI_API_KEY")]
+    pub gemini_api_key: Option<String>,
+
     #[arg(long, env = "MORPH_API_KEY")]
     pub morph_api_key: Option<String>,
 
+    /// Base URL of an OpenAI-compatible server, such as a local Ollama
+    /// at `http://localhost:11434/v1`. Every model is then served through
+    /// it, and names outside the catalog are accepted.
+    #[arg(long, env = "SOFOS_API_BASE", value_name = "URL")]
+    pub api_base: Option<String>,
+
+    /// Bearer token for the `--api-base` server. Without it no key is
+    /// sent; the OpenAI key never goes to a custom endpoint.
+    #[arg(long, env = "SOFOS_API_BASE_KEY", value_name = "KEY")]
+    pub api_base_key: Option<String>,
+
+    /// Work on this directory instead of the current one, like `git -C`.
+    /// Every tool, the sandbox, and the session history use it as root.
+    #[arg(short = 'C', long, value_name = "DIR")]
+    pub workspace: Option<std::path::PathBuf>,
+
     /// Initial prompt to send (if not provided, starts interactive REPL)
     #[arg(short, long)]
     pub prompt: Option<String>,
 
+    /// Run the prompt in this file once, after filling its `{{key}}`
+    /// placeholders from `--var`.
+    #[arg(long, value_name = "FILE", conflicts_with = "prompt")]
+    pub prompt_template: Option<std::path::PathBuf>,
+
+    /// A `key=value` for `--prompt-template`. Repeatable.
+    #[arg(long = "var", value_name = "KEY=VALUE", requires = "prompt_template")]
+    pub vars: Vec<String>,
+
     /// Resume a previous conversation session
     #[arg(short, long)]
     pub resume: bool,
 
+    /// Resume the most recently updated session without the picker
+    #[arg(
+        short = 'c',
+        long = "continue",
+        conflicts_with_all = ["resume", "import"]
+    )]
+    pub continue_session: bool,
+
+    /// Import an exported session file into this workspace and resume it
+    #[arg(long, value_name = "FILE", conflicts_with = "resume")]
+    pub import: Option<std::path::PathBuf>,
+
+    /// Pick a session to resume among those whose title or preview
+    /// contains this text (case-insensitive)
+    #[arg(
+        long,
+        value_name = "QUERY",
+        conflicts_with_all = ["resume", "continue_session", "import"]
+    )]
+    pub search: Option<String>,
+
     /// Check API connectivity and exit
     #[arg(long)]
     pub check_connection: bool,
 
+    /// With --check-connection, also send a tiny request with the model
+    #[arg(long, requires = "check_connection")]
+    pub check_model: bool,
+
     #[arg(long, default_value = crate::api::model_info::DEFAULT_MODEL_NAME)]
     pub model: String,
 
@@ -98,18 +157,378 @@ pub struct Cli {
     /// without confinement. Overridden by `--readonly` when both are given.
     #[arg(long)]
     pub no_sandbox: bool,
+
+    /// When `read_file` or `write_file` gets a bare file name that does
+    /// not exist at the workspace root, look it up by name across the
+    /// workspace: one match is used, several are listed back to the model.
+    #[arg(long)]
+    pub smart_paths: bool,
+
+    /// Preview changes instead of making them: tools that write files or
+    /// run shell commands tell the model what they would have done, while
+    /// reading and searching work as usual.
+    #[arg(long)]
+    pub dry_run: bool,
+
+    /// Show a preview of every file write, edit, move, and copy and ask
+    /// before applying it. A declined change is reported to the model.
+    /// Toggle it during a session with `/confirm`.
+    #[arg(long)]
+    pub confirm_edits: bool,
+
+    /// Before a file tool overwrites a file, copy its current content to
+    /// `.sofos/backups/<path>.<timestamp>`.
+    #[arg(long)]
+    pub backups: bool,
+
+    /// Append every tool call and permission decision to
+    /// `.sofos/audit.log` as JSON lines, for later review.
+    #[arg(long)]
+    pub audit: bool,
+
+    /// Send images opened with `view_image` at full resolution instead
+    /// of downscaling ones larger than `[images] max_dimension`.
+    #[arg(long)]
+    pub no_image_resize: bool,
+
+    /// Seconds a bash command may run before it is killed. Default 300.
+    #[arg(long, value_name = "SECS", value_parser = clap::value_parser!(u64).range(1..))]
+    pub bash_timeout: Option<u64>,
+
+    /// Most bytes kept from a bash command's stdout, and separately its
+    /// stderr, before the command is stopped. Default 10 MiB.
+    #[arg(long, value_name = "BYTES", value_parser = clap::value_parser!(u64).range(1..))]
+    pub max_output_bytes: Option<u64>,
+
+    /// Response length: concise, normal, or detailed. Default `normal`.
+    //
+    // Parsed as a raw `String` and validated in `main`, like
+    // `--reasoning-mode`.
+    #[arg(long, default_value = "normal")]
+    pub verbosity: String,
+
+    /// What `--prompt` prints: text, or json for a single JSON object
+    /// with the answer, tool calls, usage and cost. Default `text`.
+    //
+    // Validated in `main`, like `--verbosity`.
+    #[arg(long, value_name = "FORMAT", default_value = "text")]
+    pub output: String,
+
+    /// Most messages kept in the conversation history. Overrides
+    /// `[conversation] max_messages`; default 500, minimum 10.
+    #[arg(long, value_name = "N")]
+    pub max_messages: Option<usize>,
+
+    /// Most tool-call rounds in one turn before the loop stops and asks
+    /// the model to summarise. Default 200, minimum 1.
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u32).range(1..))]
+    pub max_tool_iterations: Option<u32>,
+
+    /// When a response stops at the output token limit, ask the model to
+    /// continue it, up to three times per turn.
+    #[arg(long)]
+    pub auto_continue: bool,
+
+    /// Shorten `read_file` headers, and answer a repeat read of an
+    /// unchanged file with a pointer to the earlier result.
+    #[arg(long)]
+    pub compact_tool_results: bool,
+
+    /// Add the text of this file to the built-in system prompt, ahead of
+    /// the `AGENTS.md` and `.sofos/instructions.md` instructions.
+    #[arg(long, value_name = "FILE")]
+    pub system_prompt_file: Option<std::path::PathBuf>,
+
+    /// Use `--system-prompt-file` instead of the built-in system prompt.
+    #[arg(long, requires = "system_prompt_file")]
+    pub replace_system_prompt: bool,
+
+    /// Stop once the session's estimated cost reaches this many US
+    /// dollars. `[budget] on_exceeded = "ask"` prompts instead in
+    /// interactive sessions.
+    #[arg(long, value_name = "USD")]
+    pub max_cost: Option<f64>,
+
+    /// Stop once the session has used this many tokens, input and
+    /// output combined. Prompts instead like `--max-cost`.
+    #[arg(long, value_name = "N")]
+    pub max_total_tokens: Option<u64>,
+
+    /// Sampling temperature, 0 to 2. Unset keeps the provider default.
+    /// Anthropic caps it at 1 and ignores it while thinking is on.
+    #[arg(long, value_name = "T")]
+    pub temperature: Option<f32>,
+
+    /// Nucleus-sampling cutoff, 0 to 1. Unset keeps the provider default.
+    /// Anthropic raises it to at least 0.95 while thinking is on.
+    #[arg(long, value_name = "P")]
+    pub top_p: Option<f32>,
+
+    /// Preload a named input the assistant can read with `read_input`,
+    /// without a file path. `-` reads standard input. Repeatable.
+    #[arg(long = "input", value_name = "NAME=FILE")]
+    pub inputs: Vec<String>,
+
+    /// Syntax-highlighting theme for code blocks, file views and diffs.
+    /// Default `base16-ocean.dark`; light terminals suit
+    /// `base16-ocean.light`, `InspiredGitHub` or `Solarized (light)`.
+    //
+    // Validated in `main` against the bundled themes.
+    #[arg(long, env = "SOFOS_THEME", value_name = "NAME")]
+    pub theme: Option<String>,
+
+    /// Print without colour. Setting `NO_COLOR` does the same.
+    #[arg(long)]
+    pub no_color: bool,
 }
 
 impl Cli {
-    pub fn get_anthropic_api_key(&self) -> Result<String, SofosError> {
-        self.api_key
-            .clone()
-            .ok_or_else(|| SofosError::Config("ANTHROPIC_API_KEY not found".to_string()))
+    /// Fill the flags that were given neither on the command line nor in
+    /// the environment from the config files' `[defaults]`, so explicit
+    /// flags always win. Values are validated later like the flags.
+    pub fn apply_config_defaults(&mut self, matches: &ArgMatches, defaults: DefaultFlags) {
+        let unset = |id: &str| {
+            matches!(
+                matches.value_source(id),
+                None | Some(ValueSource::DefaultValue)
+            )
+        };
+        if let Some(model) = defaults.model.filter(|_| unset("model")) {
+            self.model = model;
+        }
+        if let Some(max_tokens) = defaults.max_tokens.filter(|_| unset("max_tokens")) {
+            self.max_tokens = max_tokens;
+        }
+        if let Some(effort) = defaults
+            .reasoning_effort
+            .filter(|_| unset("reasoning_effort"))
+        {
+            self.reasoning_effort = effort;
+        }
+        if let Some(readonly) = defaults.readonly.filter(|_| unset("readonly")) {
+            self.readonly = readonly;
+        }
+        if let Some(theme) = defaults.theme.filter(|_| unset("theme")) {
+            self.theme = Some(theme);
+        }
+    }
+
+    /// Every provider key given on the command line or in the
+    /// environment, so `/model` can switch providers mid-session.
+    pub fn provider_keys(&self) -> ProviderKeys {
+        ProviderKeys {
+            anthropic: self.api_key.clone(),
+            openai: self.openai_api_key.clone(),
+            gemini: self.gemini_api_key.clone(),
+        }
+    }
+
+    /// Bash limits from `--bash-timeout` and `--max-output-bytes`, with
+    /// the defaults for whichever is unset.
+    pub fn bash_limits(&self) -> BashLimits {
+        let defaults = BashLimits::default();
+        BashLimits {
+            timeout: self
+                .bash_timeout
+                .map(Duration::from_secs)
+                .unwrap_or(defaults.timeout),
+            max_output_bytes: self
+                .max_output_bytes
+                .map(|bytes| usize::try_from(bytes).unwrap_or(usize::MAX))
+                .unwrap_or(defaults.max_output_bytes),
+        }
+    }
+
+    /// Sampling overrides from `--temperature` and `--top-p`.
+    pub fn sampling(&self) -> Sampling {
+        Sampling {
+            temperature: self.temperature,
+            top_p: self.top_p,
+        }
+    }
+
+    /// Fill `--prompt-template` from `--var` and use it as the prompt.
+    /// Does nothing without a template.
+    pub fn apply_prompt_template(&mut self) -> Result<(), SofosError> {
+        let Some(path) = &self.prompt_template else {
+            return Ok(());
+        };
+        let template = std::fs::read_to_string(path).map_err(|e| {
+            SofosError::Config(format!(
+                "cannot read prompt template '{}': {}",
+                path.display(),
+                e
+            ))
+        })?;
+        let vars = self
+            .vars
+            .iter()
+            .map(|var| {
+                var.split_once('=')
+                    .filter(|(key, _)| is_template_key(key))
+                    .ok_or_else(|| {
+                        SofosError::Config(format!(
+                            "invalid --var '{}'; expected <key>=<value>",
+                            var
+                        ))
+                    })
+            })
+            .collect::<Result<HashMap<_, _>, _>>()?;
+        self.prompt = Some(render_prompt_template(&template, &vars)?);
+        Ok(())
+    }
+
+    /// Fold piped standard input into the one-shot prompt. It becomes the
+    /// prompt on its own, or follows `--prompt` as context, as in
+    /// `git diff | sofos -p "review these changes"`.
+    pub fn apply_piped_stdin(&mut self, stdin: impl std::io::Read) -> Result<(), SofosError> {
+        let piped = crate::tools::inputs::read_capped(stdin, "stdin")?;
+        self.prompt = prompt_with_piped_input(self.prompt.take(), &piped);
+        Ok(())
+    }
+}
+
+/// `prompt` followed by `piped`, or either alone. Blank piped input
+/// leaves the prompt as it was.
+fn prompt_with_piped_input(prompt: Option<String>, piped: &str) -> Option<String> {
+    let piped = piped.trim();
+    if piped.is_empty() {
+        return prompt;
+    }
+    Some(match prompt {
+        Some(prompt) => format!("{}\n\n{}", prompt, piped),
+        None => piped.to_string(),
+    })
+}
+
+const TEMPLATE_OPEN: &str = "{{";
+const TEMPLATE_CLOSE: &str = "}}";
+
+fn is_template_key(key: &str) -> bool {
+    !key.is_empty()
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'))
+}
+
+/// Replace each `{{key}}` (inner whitespace allowed) with its value.
+/// Braces around anything that is not a key are kept as written. Every
+/// placeholder without a value is listed in the error.
+fn render_prompt_template(
+    template: &str,
+    vars: &HashMap<&str, &str>,
+) -> Result<String, SofosError> {
+    let mut output = String::with_capacity(template.len());
+    let mut missing: Vec<&str> = Vec::new();
+    let mut rest = template;
+    while let Some(start) = rest.find(TEMPLATE_OPEN) {
+        let after_open = &rest[start + TEMPLATE_OPEN.len()..];
+        let Some(end) = after_open.find(TEMPLATE_CLOSE) else {
+            break;
+        };
+        let key = after_open[..end].trim();
+        output.push_str(&rest[..start]);
+        if !is_template_key(key) {
+            output.push_str(TEMPLATE_OPEN);
+            rest = after_open;
+            continue;
+        }
+        match vars.get(key) {
+            Some(value) => output.push_str(value),
+            None if !missing.contains(&key) => missing.push(key),
+            None => {}
+        }
+        rest = &after_open[end + TEMPLATE_CLOSE.len()..];
+    }
+    output.push_str(rest);
+    if missing.is_empty() {
+        Ok(output)
+    } else {
+        Err(SofosError::Config(format!(
+            "prompt template has no value for: {}. Pass each with --var <key>=<value>",
+            missing.join(", ")
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn config_defaults_fill_only_flags_left_unset() {
+        use clap::{CommandFactory, FromArgMatches};
+
+        let matches = Cli::command()
+            .try_get_matches_from(["sofos", "--max-tokens", "1000"])
+            .unwrap();
+        let mut cli = Cli::from_arg_matches(&matches).unwrap();
+        cli.apply_config_defaults(
+            &matches,
+            DefaultFlags {
+                model: Some("gpt-5.6-luna".to_string()),
+                max_tokens: Some(64000),
+                reasoning_effort: Some("high".to_string()),
+                readonly: Some(true),
+                theme: None,
+            },
+        );
+
+        assert_eq!(cli.model, "gpt-5.6-luna");
+        assert_eq!(cli.max_tokens, 1000, "the explicit flag wins");
+        assert_eq!(cli.reasoning_effort, "high");
+        assert!(cli.readonly);
+    }
+
+    #[test]
+    fn max_tool_iterations_must_be_positive() {
+        use clap::CommandFactory;
+
+        let parse =
+            |n: &str| Cli::command().try_get_matches_from(["sofos", "--max-tool-iterations", n]);
+        assert!(parse("0").is_err());
+        let matches = parse("25").unwrap();
+        assert_eq!(matches.get_one::<u32>("max_tool_iterations"), Some(&25));
+    }
+
+    #[test]
+    fn piped_input_follows_the_prompt_or_stands_alone() {
+        assert_eq!(
+            prompt_with_piped_input(Some("review".to_string()), "diff --git a b\n"),
+            Some("review\n\ndiff --git a b".to_string())
+        );
+        assert_eq!(
+            prompt_with_piped_input(None, "explain this error\n"),
+            Some("explain this error".to_string())
+        );
+        assert_eq!(
+            prompt_with_piped_input(Some("review".to_string()), " \n"),
+            Some("review".to_string())
+        );
+        assert_eq!(prompt_with_piped_input(None, ""), None);
+    }
+
+    fn vars<'a>(pairs: &[(&'a str, &'a str)]) -> HashMap<&'a str, &'a str> {
+        pairs.iter().copied().collect()
+    }
+
+    #[test]
+    fn template_placeholders_are_filled() {
+        let rendered = render_prompt_template(
+            "review {{file}} for {{ concern }}; {{file}} again, {not} {{ }}",
+            &vars(&[("file", "src/main.rs"), ("concern", "panics")]),
+        )
+        .unwrap();
+        assert_eq!(
+            rendered,
+            "review src/main.rs for panics; src/main.rs again, {not} {{ }}"
+        );
     }
 
-    pub fn get_openai_api_key(&self) -> Result<String, SofosError> {
-        self.openai_api_key
-            .clone()
-            .ok_or_else(|| SofosError::Config("OPENAI_API_KEY not found".to_string()))
+    #[test]
+    fn missing_template_values_are_all_listed() {
+        let err =
+            render_prompt_template("{{a}} {{b}} {{a}} {{c}}", &vars(&[("b", "set")])).unwrap_err();
+        assert!(err.to_string().contains("no value for: a, c."), "{err}");
     }
 }
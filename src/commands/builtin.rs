@@ -2,6 +2,7 @@ use super::CommandResult;
 use crate::config::PermissionPreset;
 use crate::error::Result;
 use crate::repl::Repl;
+use crate::tools::permissions::settings::RuleList;
 use crate::ui::UI;
 
 pub fn exit_command(repl: &mut Repl) -> Result<CommandResult> {
@@ -17,6 +18,11 @@ pub fn clear_command(repl: &mut Repl) -> Result<CommandResult> {
     Ok(CommandResult::Continue)
 }
 
+pub fn clear_keep_last_command(repl: &mut Repl, count: usize) -> Result<CommandResult> {
+    repl.handle_clear_keep_last(count)?;
+    Ok(CommandResult::Continue)
+}
+
 pub fn resume_command(repl: &mut Repl) -> Result<CommandResult> {
     if let Err(e) = repl.handle_resume_command() {
         UI::print_error_with_hint(&e);
@@ -24,6 +30,13 @@ pub fn resume_command(repl: &mut Repl) -> Result<CommandResult> {
     Ok(CommandResult::Continue)
 }
 
+pub fn resume_file_command(repl: &mut Repl, path: &str) -> Result<CommandResult> {
+    if let Err(e) = repl.handle_resume_file_command(path) {
+        UI::print_error_with_hint(&e);
+    }
+    Ok(CommandResult::Continue)
+}
+
 pub fn effort_picker_command(repl: &mut Repl) -> Result<CommandResult> {
     // The TUI worker intercepts this and opens the inline picker;
     // this fallback only runs in non-interactive mode.
@@ -51,6 +64,25 @@ pub fn permissions_set_command(repl: &mut Repl, preset: PermissionPreset) -> Res
     Ok(CommandResult::Continue)
 }
 
+pub fn permission_rules_command(repl: &mut Repl) -> Result<CommandResult> {
+    repl.handle_permission_rules_show()?;
+    Ok(CommandResult::Continue)
+}
+
+pub fn permission_rule_set_command(
+    repl: &mut Repl,
+    list: RuleList,
+    rule: &str,
+) -> Result<CommandResult> {
+    repl.handle_permission_rule_set(list, rule)?;
+    Ok(CommandResult::Continue)
+}
+
+pub fn permission_rule_remove_command(repl: &mut Repl, rule: &str) -> Result<CommandResult> {
+    repl.handle_permission_rule_remove(rule)?;
+    Ok(CommandResult::Continue)
+}
+
 pub fn compact_command(repl: &mut Repl) -> Result<CommandResult> {
     repl.handle_compact_command()?;
     Ok(CommandResult::Continue)
@@ -79,6 +111,122 @@ pub fn mode_picker_command(repl: &mut Repl) -> Result<CommandResult> {
     Ok(CommandResult::Continue)
 }
 
+pub fn verbosity_show_command(repl: &mut Repl) -> Result<CommandResult> {
+    repl.handle_verbosity_show();
+    Ok(CommandResult::Continue)
+}
+
+pub fn verbosity_set_command(
+    repl: &mut Repl,
+    verbosity: crate::config::Verbosity,
+) -> Result<CommandResult> {
+    repl.handle_verbosity_set(verbosity);
+    Ok(CommandResult::Continue)
+}
+
+pub fn scope_show_command(repl: &mut Repl) -> Result<CommandResult> {
+    repl.handle_scope_show();
+    Ok(CommandResult::Continue)
+}
+
+pub fn scope_set_command(repl: &mut Repl, dir: &str) -> Result<CommandResult> {
+    if let Err(e) = repl.handle_scope_set(dir) {
+        UI::print_error_with_hint(&e);
+    }
+    Ok(CommandResult::Continue)
+}
+
+pub fn scope_reset_command(repl: &mut Repl) -> Result<CommandResult> {
+    if let Err(e) = repl.handle_scope_reset() {
+        UI::print_error_with_hint(&e);
+    }
+    Ok(CommandResult::Continue)
+}
+
+pub fn confirm_command(repl: &mut Repl) -> Result<CommandResult> {
+    repl.handle_confirm_toggle();
+    Ok(CommandResult::Continue)
+}
+
+pub fn files_command(repl: &mut Repl) -> Result<CommandResult> {
+    repl.handle_files_show();
+    Ok(CommandResult::Continue)
+}
+
+pub fn cost_command(repl: &mut Repl) -> Result<CommandResult> {
+    repl.handle_cost_show();
+    Ok(CommandResult::Continue)
+}
+
+pub fn tokens_command(repl: &mut Repl) -> Result<CommandResult> {
+    repl.handle_tokens_show();
+    Ok(CommandResult::Continue)
+}
+
+pub fn pin_command(repl: &mut Repl) -> Result<CommandResult> {
+    repl.handle_pin();
+    Ok(CommandResult::Continue)
+}
+
+pub fn unpin_command(repl: &mut Repl) -> Result<CommandResult> {
+    repl.handle_unpin();
+    Ok(CommandResult::Continue)
+}
+
+pub fn export_command(repl: &mut Repl, path: &str) -> Result<CommandResult> {
+    if let Err(e) = repl.handle_export_command(path) {
+        UI::print_error_with_hint(&e);
+    }
+    Ok(CommandResult::Continue)
+}
+
+pub fn rename_command(repl: &mut Repl, title: &str) -> Result<CommandResult> {
+    if let Err(e) = repl.handle_rename_command(title) {
+        UI::print_error_with_hint(&e);
+    }
+    Ok(CommandResult::Continue)
+}
+
+pub fn save_command(repl: &mut Repl, name: &str) -> Result<CommandResult> {
+    if let Err(e) = repl.handle_save_command(name) {
+        UI::print_error_with_hint(&e);
+    }
+    Ok(CommandResult::Continue)
+}
+
+pub fn diff_command(repl: &mut Repl) -> Result<CommandResult> {
+    repl.handle_diff_show();
+    Ok(CommandResult::Continue)
+}
+
+pub fn fork_command(repl: &mut Repl) -> Result<CommandResult> {
+    if let Err(e) = repl.handle_fork_command() {
+        UI::print_error_with_hint(&e);
+    }
+    Ok(CommandResult::Continue)
+}
+
+pub fn undo_command(repl: &mut Repl, count: usize) -> Result<CommandResult> {
+    repl.handle_undo(count);
+    Ok(CommandResult::Continue)
+}
+
+pub fn retry_command(repl: &mut Repl) -> Result<CommandResult> {
+    // An interrupted retry is the user's choice, same as an interrupted
+    // prompt, so it isn't reported as an error.
+    match repl.handle_retry() {
+        Ok(()) | Err(crate::error::SofosError::Interrupted) => {}
+        Err(e) => return Err(e),
+    }
+    println!();
+    Ok(CommandResult::Continue)
+}
+
+pub fn tools_command(repl: &mut Repl) -> Result<CommandResult> {
+    repl.handle_tools_show();
+    Ok(CommandResult::Continue)
+}
+
 pub fn mode_set_command(repl: &mut Repl, mode: crate::api::ReasoningMode) -> Result<CommandResult> {
     repl.handle_mode_set(mode);
     Ok(CommandResult::Continue)
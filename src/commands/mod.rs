@@ -1,5 +1,6 @@
 use crate::error::Result;
 use crate::repl::Repl;
+use crate::tools::permissions::settings::RuleList;
 
 pub mod builtin;
 
@@ -17,7 +18,12 @@ pub enum CommandResult {
 pub enum Command {
     Exit,
     Clear,
+    /// `/clear keep-last <n>` — clear all but the last `n` exchanges.
+    ClearKeepLast(usize),
     Resume,
+    /// `/resume-file <path>` — import an exported session file and
+    /// resume it. The path keeps its original case.
+    ResumeFile(String),
     /// `/effort` — open the reasoning-effort picker.
     EffortPicker,
     /// `/effort <level>` — set the level directly. Per-model
@@ -29,6 +35,14 @@ pub enum Command {
     /// (read-only / sandboxed-ask / sandboxed-retry / sandboxed-strict /
     /// unsandboxed).
     PermissionsSet(crate::config::PermissionPreset),
+    /// `/permissions rules` — list the allow, deny, and ask rules with
+    /// the file each comes from.
+    PermissionRules,
+    /// `/permissions allow|deny|ask <rule>` — add a rule to a list, or
+    /// move it there from another. The rule keeps its original case.
+    PermissionRuleSet(RuleList, String),
+    /// `/permissions remove <rule>` — drop a rule from the local config.
+    PermissionRuleRemove(String),
     Compact,
     /// `/model` with no argument — open the model picker.
     ModelPicker,
@@ -43,6 +57,48 @@ pub enum Command {
     /// mode is validated per-model (the GPT-5.6 family), matching
     /// `--reasoning-mode`.
     ModeSet(crate::api::ReasoningMode),
+    /// `/verbosity` — show the current response-length level.
+    VerbosityShow,
+    /// `/verbosity <concise|normal|detailed>` — switch the level.
+    VerbositySet(crate::config::Verbosity),
+    /// `/scope` — show the active working scope.
+    ScopeShow,
+    /// `/scope <subdir>` — narrow the tools to a subdirectory. The path
+    /// keeps its original case.
+    ScopeSet(String),
+    /// `/scope reset` — work from the project root again.
+    ScopeReset,
+    /// `/files` — list every file touched this session.
+    Files,
+    /// `/cost` — show the tokens and estimated cost so far.
+    Cost,
+    /// `/tokens` — show how much of the context window is in use.
+    Tokens,
+    /// `/pin` — keep the last prompt through trimming and compaction.
+    Pin,
+    /// `/unpin` — clear every pin.
+    Unpin,
+    /// `/tools` — list the tools and the health of optional ones.
+    Tools,
+    /// `/retry` — send the last prompt again in place of its answer.
+    Retry,
+    /// `/undo [n]` — drop the last `n` exchanges (default 1).
+    Undo(usize),
+    /// `/rename <title>` — name the current session. The title keeps its
+    /// original case.
+    Rename(String),
+    /// `/save <name>` — keep a named copy of the session to resume later
+    /// while this one carries on. The name keeps its original case.
+    Save(String),
+    /// `/fork` — continue in a copy of the session under a new id.
+    Fork,
+    /// `/diff` — show the workspace's uncommitted changes.
+    Diff,
+    /// `/confirm` — toggle asking before each file change.
+    Confirm,
+    /// `/export <file.md>` — write the conversation as Markdown. The path
+    /// keeps its original case.
+    Export(String),
 }
 
 /// Slash-command names, defined once so the parser and the catalog (and
@@ -52,11 +108,32 @@ const CMD_QUIT: &str = "/quit";
 const CMD_QUIT_SHORT: &str = "/q";
 const CMD_CLEAR: &str = "/clear";
 const CMD_RESUME: &str = "/resume";
+const CMD_RESUME_FILE: &str = "/resume-file";
 const CMD_EFFORT: &str = "/effort";
 const CMD_MODE: &str = "/mode";
 const CMD_MODEL: &str = "/model";
 const CMD_COMPACT: &str = "/compact";
 const CMD_PERMISSIONS: &str = "/permissions";
+const CMD_VERBOSITY: &str = "/verbosity";
+const CMD_SCOPE: &str = "/scope";
+const CMD_FILES: &str = "/files";
+const CMD_COST: &str = "/cost";
+const CMD_TOKENS: &str = "/tokens";
+const CMD_PIN: &str = "/pin";
+const CMD_UNPIN: &str = "/unpin";
+const CMD_TOOLS: &str = "/tools";
+const CMD_RETRY: &str = "/retry";
+const CMD_UNDO: &str = "/undo";
+const CMD_RENAME: &str = "/rename";
+const CMD_SAVE: &str = "/save";
+const CMD_FORK: &str = "/fork";
+const CMD_DIFF: &str = "/diff";
+const CMD_CONFIRM: &str = "/confirm";
+const CMD_EXPORT: &str = "/export";
+const SCOPE_RESET_ARG: &str = "reset";
+const CLEAR_KEEP_LAST_ARG: &str = "keep-last";
+const PERMISSIONS_RULES_ARG: &str = "rules";
+const PERMISSIONS_REMOVE_ARG: &str = "remove";
 
 impl Command {
     pub fn from_str(s: &str) -> Option<Self> {
@@ -65,13 +142,34 @@ impl Command {
             CMD_EXIT | CMD_QUIT | CMD_QUIT_SHORT => Some(Command::Exit),
             CMD_CLEAR => Some(Command::Clear),
             CMD_RESUME => Some(Command::Resume),
+            CMD_RESUME_FILE => Some(Command::ResumeFile(String::new())),
             CMD_EFFORT => Some(Command::EffortPicker),
             CMD_MODE => Some(Command::ModePicker),
             CMD_PERMISSIONS => Some(Command::PermissionsPicker),
             CMD_COMPACT => Some(Command::Compact),
             CMD_MODEL => Some(Command::ModelPicker),
+            CMD_VERBOSITY => Some(Command::VerbosityShow),
+            CMD_SCOPE => Some(Command::ScopeShow),
+            CMD_FILES => Some(Command::Files),
+            CMD_COST => Some(Command::Cost),
+            CMD_TOKENS => Some(Command::Tokens),
+            CMD_PIN => Some(Command::Pin),
+            CMD_UNPIN => Some(Command::Unpin),
+            CMD_TOOLS => Some(Command::Tools),
+            CMD_RETRY => Some(Command::Retry),
+            CMD_UNDO => Some(Command::Undo(1)),
+            CMD_RENAME => Some(Command::Rename(String::new())),
+            CMD_SAVE => Some(Command::Save(String::new())),
+            CMD_FORK => Some(Command::Fork),
+            CMD_DIFF => Some(Command::Diff),
+            CMD_CONFIRM => Some(Command::Confirm),
+            CMD_EXPORT => Some(Command::Export(String::new())),
             _ => {
-                if let Some(arg) = lower.strip_prefix("/effort ") {
+                if lower.starts_with("/resume-file ") {
+                    // Slice the original input so the path keeps its case.
+                    let path = s.get(CMD_RESUME_FILE.len()..).unwrap_or_default().trim();
+                    Some(Command::ResumeFile(path.to_string()))
+                } else if let Some(arg) = lower.strip_prefix("/effort ") {
                     let trimmed = arg.trim();
                     if trimmed.is_empty() {
                         Some(Command::EffortPicker)
@@ -94,11 +192,65 @@ impl Command {
                     }
                 } else if let Some(arg) = lower.strip_prefix("/permissions ") {
                     let trimmed = arg.trim();
+                    // Slice the original input so a rule keeps its case.
+                    let original = s.get(CMD_PERMISSIONS.len()..).unwrap_or_default().trim();
                     if trimmed.is_empty() {
                         Some(Command::PermissionsPicker)
+                    } else if trimmed == PERMISSIONS_RULES_ARG {
+                        Some(Command::PermissionRules)
+                    } else if let Some((verb, rule)) = original.split_once(char::is_whitespace) {
+                        let rule = rule.trim().to_string();
+                        if verb.eq_ignore_ascii_case(PERMISSIONS_REMOVE_ARG) {
+                            Some(Command::PermissionRuleRemove(rule))
+                        } else {
+                            RuleList::parse(verb).map(|list| Command::PermissionRuleSet(list, rule))
+                        }
                     } else {
                         crate::config::PermissionPreset::parse(trimmed).map(Command::PermissionsSet)
                     }
+                } else if let Some(arg) = lower.strip_prefix("/verbosity ") {
+                    let trimmed = arg.trim();
+                    if trimmed.is_empty() {
+                        Some(Command::VerbosityShow)
+                    } else {
+                        crate::config::Verbosity::parse(trimmed).map(Command::VerbositySet)
+                    }
+                } else if lower.starts_with("/export ") {
+                    let path = s.get(CMD_EXPORT.len()..).unwrap_or_default().trim();
+                    Some(Command::Export(path.to_string()))
+                } else if lower.starts_with("/rename ") {
+                    let title = s.get(CMD_RENAME.len()..).unwrap_or_default().trim();
+                    Some(Command::Rename(title.to_string()))
+                } else if lower.starts_with("/save ") {
+                    let name = s.get(CMD_SAVE.len()..).unwrap_or_default().trim();
+                    Some(Command::Save(name.to_string()))
+                } else if let Some(arg) = lower.strip_prefix("/clear ") {
+                    let count = arg.trim().strip_prefix(CLEAR_KEEP_LAST_ARG)?;
+                    if !count.is_empty() && !count.starts_with(char::is_whitespace) {
+                        return None;
+                    }
+                    match count.trim() {
+                        "" => Some(Command::ClearKeepLast(1)),
+                        n => n
+                            .parse()
+                            .ok()
+                            .filter(|n| *n > 0)
+                            .map(Command::ClearKeepLast),
+                    }
+                } else if let Some(arg) = lower.strip_prefix("/undo ") {
+                    match arg.trim() {
+                        "" => Some(Command::Undo(1)),
+                        n => n.parse().ok().filter(|n| *n > 0).map(Command::Undo),
+                    }
+                } else if lower.starts_with("/scope ") {
+                    let dir = s.get(CMD_SCOPE.len()..).unwrap_or_default().trim();
+                    if dir.is_empty() {
+                        Some(Command::ScopeShow)
+                    } else if dir.eq_ignore_ascii_case(SCOPE_RESET_ARG) {
+                        Some(Command::ScopeReset)
+                    } else {
+                        Some(Command::ScopeSet(dir.to_string()))
+                    }
                 } else {
                     None
                 }
@@ -110,16 +262,44 @@ impl Command {
         match self {
             Command::Exit => builtin::exit_command(repl),
             Command::Clear => builtin::clear_command(repl),
+            Command::ClearKeepLast(count) => builtin::clear_keep_last_command(repl, *count),
             Command::Resume => builtin::resume_command(repl),
+            Command::ResumeFile(path) => builtin::resume_file_command(repl, path),
             Command::EffortPicker => builtin::effort_picker_command(repl),
             Command::EffortSet(effort) => builtin::effort_set_command(repl, *effort),
             Command::PermissionsPicker => builtin::permissions_picker_command(repl),
             Command::PermissionsSet(preset) => builtin::permissions_set_command(repl, *preset),
+            Command::PermissionRules => builtin::permission_rules_command(repl),
+            Command::PermissionRuleSet(list, rule) => {
+                builtin::permission_rule_set_command(repl, *list, rule)
+            }
+            Command::PermissionRuleRemove(rule) => {
+                builtin::permission_rule_remove_command(repl, rule)
+            }
             Command::Compact => builtin::compact_command(repl),
             Command::ModelPicker => builtin::model_picker_command(repl),
             Command::ModelSet(name) => builtin::model_set_command(repl, name),
             Command::ModePicker => builtin::mode_picker_command(repl),
             Command::ModeSet(mode) => builtin::mode_set_command(repl, *mode),
+            Command::VerbosityShow => builtin::verbosity_show_command(repl),
+            Command::VerbositySet(verbosity) => builtin::verbosity_set_command(repl, *verbosity),
+            Command::ScopeShow => builtin::scope_show_command(repl),
+            Command::ScopeSet(dir) => builtin::scope_set_command(repl, dir),
+            Command::ScopeReset => builtin::scope_reset_command(repl),
+            Command::Files => builtin::files_command(repl),
+            Command::Cost => builtin::cost_command(repl),
+            Command::Tokens => builtin::tokens_command(repl),
+            Command::Pin => builtin::pin_command(repl),
+            Command::Unpin => builtin::unpin_command(repl),
+            Command::Tools => builtin::tools_command(repl),
+            Command::Retry => builtin::retry_command(repl),
+            Command::Undo(count) => builtin::undo_command(repl, *count),
+            Command::Rename(title) => builtin::rename_command(repl, title),
+            Command::Save(name) => builtin::save_command(repl, name),
+            Command::Fork => builtin::fork_command(repl),
+            Command::Diff => builtin::diff_command(repl),
+            Command::Confirm => builtin::confirm_command(repl),
+            Command::Export(path) => builtin::export_command(repl, path),
         }
     }
 }
@@ -138,13 +318,29 @@ pub struct CommandEntry {
 /// Ordered list of every typeable command. Order here is the order shown
 /// in the popup, so put the most useful entries first.
 pub static COMMAND_CATALOG: &[CommandEntry] = &[
+    CommandEntry {
+        name: CMD_RETRY,
+        description: "drop the last answer and send the last prompt again",
+    },
+    CommandEntry {
+        name: CMD_UNDO,
+        description: "drop the last exchange, or the last n with /undo n",
+    },
     CommandEntry {
         name: CMD_COMPACT,
         description: "summarize the conversation to free up context",
     },
+    CommandEntry {
+        name: CMD_PIN,
+        description: "keep the last prompt when older context is trimmed",
+    },
+    CommandEntry {
+        name: CMD_UNPIN,
+        description: "let pinned prompts be trimmed again",
+    },
     CommandEntry {
         name: CMD_CLEAR,
-        description: "clear the conversation and start fresh",
+        description: "clear the conversation, or keep the last n exchanges with /clear keep-last n",
     },
     CommandEntry {
         name: CMD_MODEL,
@@ -158,13 +354,65 @@ pub static COMMAND_CATALOG: &[CommandEntry] = &[
         name: CMD_MODE,
         description: "switch standard/pro reasoning mode (GPT-5.6)",
     },
+    CommandEntry {
+        name: CMD_VERBOSITY,
+        description: "set response length: concise, normal, or detailed",
+    },
+    CommandEntry {
+        name: CMD_SCOPE,
+        description: "focus the tools on a subdirectory (`/scope reset` to undo)",
+    },
+    CommandEntry {
+        name: CMD_FILES,
+        description: "list the files read or changed this session",
+    },
+    CommandEntry {
+        name: CMD_DIFF,
+        description: "show uncommitted changes in the workspace",
+    },
+    CommandEntry {
+        name: CMD_CONFIRM,
+        description: "toggle asking before each file write, edit, move, or copy",
+    },
+    CommandEntry {
+        name: CMD_COST,
+        description: "show the tokens used and estimated cost so far",
+    },
+    CommandEntry {
+        name: CMD_TOKENS,
+        description: "show how much of the context window is in use",
+    },
+    CommandEntry {
+        name: CMD_TOOLS,
+        description: "list the available tools and which optional ones work",
+    },
     CommandEntry {
         name: CMD_RESUME,
         description: "resume a previously saved session",
     },
+    CommandEntry {
+        name: CMD_RENAME,
+        description: "name this session so it is easy to find later",
+    },
+    CommandEntry {
+        name: CMD_SAVE,
+        description: "save a named snapshot of this session to resume later",
+    },
+    CommandEntry {
+        name: CMD_FORK,
+        description: "continue in a copy of this session, keeping the original",
+    },
+    CommandEntry {
+        name: CMD_EXPORT,
+        description: "write the conversation to a Markdown file",
+    },
+    CommandEntry {
+        name: CMD_RESUME_FILE,
+        description: "import an exported session file and resume it",
+    },
     CommandEntry {
         name: CMD_PERMISSIONS,
-        description: "choose what the assistant may do (opens a picker)",
+        description: "choose what the assistant may do, or edit rules with /permissions rules",
     },
     CommandEntry {
         name: CMD_EXIT,
@@ -180,6 +428,103 @@ pub static COMMAND_CATALOG: &[CommandEntry] = &[
 mod tests {
     use super::*;
 
+    #[test]
+    fn slash_export_keeps_the_path_case() {
+        assert_eq!(
+            Command::from_str("/export docs/Session.md"),
+            Some(Command::Export("docs/Session.md".to_string()))
+        );
+    }
+
+    #[test]
+    fn slash_rename_keeps_the_title_case() {
+        assert_eq!(
+            Command::from_str("/rename  Parser Refactor "),
+            Some(Command::Rename("Parser Refactor".to_string()))
+        );
+        assert_eq!(
+            Command::from_str("/rename"),
+            Some(Command::Rename(String::new()))
+        );
+    }
+
+    #[test]
+    fn slash_save_keeps_the_name_case() {
+        assert_eq!(
+            Command::from_str("/save Before Refactor"),
+            Some(Command::Save("Before Refactor".to_string()))
+        );
+        assert_eq!(
+            Command::from_str("/save"),
+            Some(Command::Save(String::new()))
+        );
+        assert_eq!(Command::from_str("/FORK"), Some(Command::Fork));
+        assert_eq!(Command::from_str("/diff"), Some(Command::Diff));
+    }
+
+    #[test]
+    fn slash_clear_keep_last_parses_a_count() {
+        assert_eq!(Command::from_str("/clear"), Some(Command::Clear));
+        assert_eq!(
+            Command::from_str("/clear keep-last"),
+            Some(Command::ClearKeepLast(1))
+        );
+        assert_eq!(
+            Command::from_str("/clear Keep-Last 3"),
+            Some(Command::ClearKeepLast(3))
+        );
+        assert_eq!(Command::from_str("/clear keep-last 0"), None);
+        assert_eq!(Command::from_str("/clear keep-lastx"), None);
+        assert_eq!(Command::from_str("/clear all"), None);
+    }
+
+    #[test]
+    fn slash_undo_parses_an_optional_count() {
+        assert_eq!(Command::from_str("/undo"), Some(Command::Undo(1)));
+        assert_eq!(Command::from_str("/undo 3"), Some(Command::Undo(3)));
+        assert_eq!(Command::from_str("/undo 0"), None);
+        assert_eq!(Command::from_str("/undo all"), None);
+        assert!(COMMAND_CATALOG.iter().any(|entry| entry.name == "/undo"));
+    }
+
+    #[test]
+    fn slash_retry_parses_and_is_listed() {
+        assert_eq!(Command::from_str("/retry"), Some(Command::Retry));
+        assert!(COMMAND_CATALOG.iter().any(|entry| entry.name == "/retry"));
+    }
+
+    #[test]
+    fn slash_confirm_parses_and_is_listed() {
+        assert_eq!(Command::from_str("/confirm"), Some(Command::Confirm));
+        assert!(COMMAND_CATALOG.iter().any(|entry| entry.name == "/confirm"));
+    }
+
+    #[test]
+    fn slash_tools_parses_and_is_listed() {
+        assert_eq!(Command::from_str("/tools"), Some(Command::Tools));
+        assert!(COMMAND_CATALOG.iter().any(|entry| entry.name == "/tools"));
+    }
+
+    #[test]
+    fn slash_cost_parses_and_is_listed() {
+        assert_eq!(Command::from_str("/cost"), Some(Command::Cost));
+        assert!(COMMAND_CATALOG.iter().any(|entry| entry.name == "/cost"));
+    }
+
+    #[test]
+    fn slash_tokens_parses_and_is_listed() {
+        assert_eq!(Command::from_str("/tokens"), Some(Command::Tokens));
+        assert!(COMMAND_CATALOG.iter().any(|entry| entry.name == "/tokens"));
+    }
+
+    #[test]
+    fn slash_pin_and_unpin_parse_and_are_listed() {
+        assert_eq!(Command::from_str("/pin"), Some(Command::Pin));
+        assert_eq!(Command::from_str("/UNPIN"), Some(Command::Unpin));
+        assert!(COMMAND_CATALOG.iter().any(|entry| entry.name == "/pin"));
+        assert!(COMMAND_CATALOG.iter().any(|entry| entry.name == "/unpin"));
+    }
+
     #[test]
     fn bare_slash_model_opens_picker() {
         assert_eq!(Command::from_str("/model"), Some(Command::ModelPicker));
@@ -323,6 +668,61 @@ mod tests {
         assert!(Command::from_str("/permissions turbo").is_none());
     }
 
+    #[test]
+    fn slash_permissions_rule_edits_keep_the_rule_case() {
+        assert_eq!(
+            Command::from_str("/permissions rules"),
+            Some(Command::PermissionRules)
+        );
+        assert_eq!(
+            Command::from_str("/permissions Allow Bash(make -C Docs)"),
+            Some(Command::PermissionRuleSet(
+                RuleList::Allow,
+                "Bash(make -C Docs)".to_string()
+            ))
+        );
+        assert_eq!(
+            Command::from_str("/permissions remove  Read(./Secrets/**) "),
+            Some(Command::PermissionRuleRemove(
+                "Read(./Secrets/**)".to_string()
+            ))
+        );
+        assert!(Command::from_str("/permissions grant Bash(make)").is_none());
+    }
+
+    #[test]
+    fn slash_scope_parses_show_set_and_reset() {
+        assert_eq!(Command::from_str("/scope"), Some(Command::ScopeShow));
+        assert_eq!(Command::from_str("/scope  "), Some(Command::ScopeShow));
+        assert_eq!(Command::from_str("/scope Reset"), Some(Command::ScopeReset));
+        assert_eq!(
+            Command::from_str("/scope crates/Core"),
+            Some(Command::ScopeSet("crates/Core".to_string()))
+        );
+    }
+
+    #[test]
+    fn slash_resume_file_keeps_path_case() {
+        assert_eq!(
+            Command::from_str("/resume-file ~/Shared/Session.JSON"),
+            Some(Command::ResumeFile("~/Shared/Session.JSON".to_string()))
+        );
+        assert_eq!(Command::from_str("/resume"), Some(Command::Resume));
+    }
+
+    #[test]
+    fn slash_verbosity_parses_show_and_set() {
+        assert_eq!(
+            Command::from_str("/verbosity"),
+            Some(Command::VerbosityShow)
+        );
+        assert_eq!(
+            Command::from_str("/verbosity Detailed"),
+            Some(Command::VerbositySet(crate::config::Verbosity::Detailed))
+        );
+        assert!(Command::from_str("/verbosity chatty").is_none());
+    }
+
     /// Every catalog name must parse back into a known `Command`.
     #[test]
     fn every_catalog_entry_parses() {
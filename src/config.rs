@@ -1,7 +1,13 @@
+use std::collections::BTreeMap;
+
+/// Tool-call rounds in one turn before the loop stops and asks the model
+/// to wrap up, unless `--max-tool-iterations` says otherwise.
+pub const DEFAULT_MAX_TOOL_ITERATIONS: u32 = 200;
+
 /// Central configuration for Sofos. The actual file-size and bash-output
 /// caps live next to the code that enforces them — `MAX_FILE_SIZE` in
 /// `src/tools/filesystem.rs` (50 MB) and `MAX_BASH_OUTPUT_BYTES` in
-/// `src/tools/bashexec.rs` (10 MB) — not here, so this struct only
+/// `src/tools/bash/output.rs` (10 MB by default) — not here, so this struct only
 /// carries config values that the rest of the crate actually reads.
 ///
 /// Per-model knowledge (context window, auto-compact trigger,
@@ -12,11 +18,15 @@
 /// runtime values from the model lookup at REPL startup.
 #[derive(Debug, Clone)]
 pub struct SofosConfig {
+    /// Hard cap on retained messages, independent of token trimming.
     pub max_messages: usize,
+    /// Whether nearing `max_messages` summarises older history first.
+    pub message_limit: MessageLimit,
     /// Hard drop-trim floor in tokens. Above this, older messages are
     /// dropped without summary as a last resort. Populated from
     /// `Model::effective_window()` at startup.
     pub max_context_tokens: usize,
+    /// Tool-call rounds allowed in one turn (`--max-tool-iterations`).
     pub max_tool_iterations: u32,
     /// Auto-compaction trigger in tokens. Compaction runs an LLM
     /// summary step that preserves context, so this fires well below
@@ -39,8 +49,9 @@ impl Default for SofosConfig {
         let info = crate::api::Model::default();
         Self {
             max_messages: 500,
+            message_limit: MessageLimit::default(),
             max_context_tokens: info.effective_window() as usize,
-            max_tool_iterations: 200,
+            max_tool_iterations: DEFAULT_MAX_TOOL_ITERATIONS,
             auto_compact_token_limit: info.auto_compact_at() as usize,
             compaction_preserve_recent: 20,
             tool_result_truncate_threshold: 2000,
@@ -55,6 +66,11 @@ pub struct ModelConfig {
     pub max_tokens: u32,
     pub reasoning_effort: crate::api::ReasoningEffort,
     pub reasoning_mode: crate::api::ReasoningMode,
+    /// `--temperature` / `--top-p`, sent with every turn's requests.
+    pub sampling: crate::api::Sampling,
+    /// The client talks to an `--api-base` endpoint, which serves models
+    /// outside the catalog.
+    pub custom_endpoint: bool,
 }
 
 impl ModelConfig {
@@ -69,9 +85,17 @@ impl ModelConfig {
             max_tokens,
             reasoning_effort,
             reasoning_mode,
+            sampling: crate::api::Sampling::default(),
+            custom_endpoint: false,
         }
     }
 
+    /// Catalog entry for the active model, or the custom-endpoint entry
+    /// for a name only the `--api-base` server knows.
+    pub fn info(&self) -> &'static crate::api::Model {
+        crate::api::model_info::lookup_on(&self.model, self.custom_endpoint)
+    }
+
     pub fn set_reasoning_effort(&mut self, effort: crate::api::ReasoningEffort) {
         self.reasoning_effort = effort;
     }
@@ -81,22 +105,386 @@ impl ModelConfig {
     }
 }
 
+/// How long the assistant's answers should be. Chosen with `--verbosity`
+/// and switchable through `/verbosity`; each level contributes one
+/// guidance line to the system prompt.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Verbosity {
+    Concise,
+    #[default]
+    Normal,
+    Detailed,
+}
+
+/// Every verbosity level, in the order `/verbosity` lists them.
+pub const VERBOSITY_LEVELS: [Verbosity; 3] =
+    [Verbosity::Concise, Verbosity::Normal, Verbosity::Detailed];
+
+impl Verbosity {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "concise" => Some(Self::Concise),
+            "normal" => Some(Self::Normal),
+            "detailed" => Some(Self::Detailed),
+            _ => None,
+        }
+    }
+
+    pub fn as_label(self) -> &'static str {
+        match self {
+            Self::Concise => "concise",
+            Self::Normal => "normal",
+            Self::Detailed => "detailed",
+        }
+    }
+
+    /// The response-length line placed in the system prompt's
+    /// "When helping users" list.
+    pub fn prompt_instruction(self) -> &'static str {
+        match self {
+            Self::Concise => {
+                "- Keep responses brief: lead with the answer, skip preamble and recaps, and summarise tool results in a sentence or two"
+            }
+            Self::Normal => "- Be concise and practical",
+            Self::Detailed => {
+                "- Provide thorough explanations: walk through your reasoning, the alternatives you considered, and the trade-offs behind each answer or change"
+            }
+        }
+    }
+}
+
+/// What a `--prompt` run prints: the usual transcript, or a single JSON
+/// object for scripts. Chosen with `--output`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutputFormat {
+    #[default]
+    Text,
+    Json,
+}
+
+impl OutputFormat {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "text" => Some(Self::Text),
+            "json" => Some(Self::Json),
+            _ => None,
+        }
+    }
+}
+
+/// What `morph_edit_file` tells the model when Morph fails, times out or
+/// returns output that fails validation. Set with `[morph] fallback` in
+/// the config files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MorphFallback {
+    /// Return a tool result telling the model to redo the change with
+    /// `read_file` + `edit_file`.
+    #[default]
+    Instruct,
+    /// Report the failure only, without retry guidance.
+    None,
+}
+
+#[derive(Debug, Default, serde::Deserialize)]
+struct MorphSection {
+    fallback: Option<MorphFallback>,
+}
+
+/// Read `[morph] fallback` from the global and local config files, the
+/// local one winning.
+pub fn load_morph_fallback(workspace: &std::path::Path) -> MorphFallback {
+    let mut fallback = MorphFallback::default();
+    load_section(workspace, "morph", |section: MorphSection, _| {
+        fallback = section.fallback.unwrap_or(fallback);
+    });
+    fallback
+}
+
+/// How the diff after a file edit is drawn. Set with
+/// `[display] diff_style` in the config files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum DiffStyle {
+    /// Changed lines with syntax highlighting and a little context.
+    #[default]
+    Compact,
+    /// Changed lines with the changed words inside them highlighted.
+    Word,
+    /// Old and new versions in two columns.
+    SideBySide,
+}
+
+#[derive(Debug, Default, serde::Deserialize)]
+struct DisplaySection {
+    diff_style: Option<DiffStyle>,
+}
+
+/// Read `[display] diff_style` from the global and local config files,
+/// the local one winning.
+pub fn load_diff_style(workspace: &std::path::Path) -> DiffStyle {
+    let mut style = DiffStyle::default();
+    load_section(workspace, "display", |section: DisplaySection, _| {
+        style = section.diff_style.unwrap_or(style);
+    });
+    style
+}
+
+/// What happens as the conversation nears `max_messages`. Set with
+/// `[conversation] on_message_limit` in the config files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MessageLimit {
+    /// Drop the oldest messages once the cap is passed.
+    #[default]
+    Drop,
+    /// Summarise older messages through compaction before the cap is
+    /// reached; dropping stays as the last resort.
+    Compact,
+}
+
+/// The `[conversation]` section. Unset fields keep the defaults.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, serde::Deserialize)]
+pub struct ConversationSettings {
+    pub max_messages: Option<usize>,
+    pub on_message_limit: Option<MessageLimit>,
+}
+
+/// Read `[conversation]` from the global and local config files, the
+/// local one winning field by field.
+pub fn load_conversation_settings(workspace: &std::path::Path) -> ConversationSettings {
+    let mut settings = ConversationSettings::default();
+    load_section(
+        workspace,
+        "conversation",
+        |file: ConversationSettings, _| {
+            settings.max_messages = file.max_messages.or(settings.max_messages);
+            settings.on_message_limit = file.on_message_limit.or(settings.on_message_limit);
+        },
+    );
+    settings
+}
+
+/// What an interactive session does once `--max-cost` or
+/// `--max-total-tokens` is reached. Set with `[budget] on_exceeded`;
+/// one-shot runs always stop.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BudgetAction {
+    /// End the run before the next request.
+    #[default]
+    Stop,
+    /// Ask whether to stop or keep going without the limit.
+    Ask,
+}
+
+#[derive(Debug, Default, serde::Deserialize)]
+struct BudgetSection {
+    on_exceeded: Option<BudgetAction>,
+}
+
+/// Read `[budget] on_exceeded` from the global and local config files,
+/// the local one winning.
+pub fn load_budget_action(workspace: &std::path::Path) -> BudgetAction {
+    let mut action = BudgetAction::default();
+    load_section(workspace, "budget", |section: BudgetSection, _| {
+        action = section.on_exceeded.unwrap_or(action);
+    });
+    action
+}
+
+/// Per-million-token prices for one `[pricing]` entry, in US dollars.
+#[derive(Debug, Clone, Copy, PartialEq, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ModelPrice {
+    pub input: f64,
+    pub output: f64,
+}
+
+/// Read `[pricing]` from the global and local config files, the local
+/// one winning entry by entry. Keys are model names or name prefixes,
+/// compared without case. Negative or non-finite prices make the whole
+/// file's table invalid.
+pub fn load_pricing(workspace: &std::path::Path) -> BTreeMap<String, ModelPrice> {
+    let mut pricing = BTreeMap::new();
+    load_section(workspace, "pricing", |table, path| {
+        pricing.extend(valid_pricing(table, path).unwrap_or_default());
+    });
+    pricing
+}
+
+fn valid_pricing(
+    table: BTreeMap<String, ModelPrice>,
+    path: &std::path::Path,
+) -> Option<BTreeMap<String, ModelPrice>> {
+    let valid = |price: f64| price.is_finite() && price >= 0.0;
+    if !table.values().all(|p| valid(p.input) && valid(p.output)) {
+        tracing::warn!(path = %path.display(), "negative or non-finite [pricing] price; ignoring");
+        return None;
+    }
+    Some(
+        table
+            .into_iter()
+            .map(|(model, price)| (model.to_ascii_lowercase(), price))
+            .collect(),
+    )
+}
+
+/// Read `[aliases]`, short names for model ids, from the global and local
+/// config files, the local one winning alias by alias. Names are
+/// compared without case; an empty id is skipped.
+pub fn load_model_aliases(workspace: &std::path::Path) -> BTreeMap<String, String> {
+    let mut aliases = BTreeMap::new();
+    load_section(
+        workspace,
+        "aliases",
+        |table: BTreeMap<String, String>, _| {
+            aliases.extend(
+                table
+                    .into_iter()
+                    .filter(|(_, id)| !id.trim().is_empty())
+                    .map(|(alias, id)| (alias.to_ascii_lowercase(), id.trim().to_string())),
+            );
+        },
+    );
+    aliases
+}
+
+/// The `[retry]` section: how often a failed provider request is retried
+/// and the first backoff delay. Unset fields keep the defaults.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, serde::Deserialize)]
+pub struct RetrySettings {
+    pub max_retries: Option<u32>,
+    pub base_delay_ms: Option<u64>,
+}
+
+/// Read `[retry]` from the global and local config files, the local one
+/// winning field by field.
+pub fn load_retry_settings(workspace: &std::path::Path) -> RetrySettings {
+    let mut settings = RetrySettings::default();
+    load_section(workspace, "retry", |file: RetrySettings, _| {
+        settings.max_retries = file.max_retries.or(settings.max_retries);
+        settings.base_delay_ms = file.base_delay_ms.or(settings.base_delay_ms);
+    });
+    settings
+}
+
+/// The `[defaults]` section: values for command-line flags that were
+/// not given. Unset fields keep the built-in defaults.
+#[derive(Debug, Default, Clone, PartialEq, Eq, serde::Deserialize)]
+pub struct DefaultFlags {
+    pub model: Option<String>,
+    pub max_tokens: Option<u32>,
+    pub reasoning_effort: Option<String>,
+    pub readonly: Option<bool>,
+    pub theme: Option<String>,
+}
+
+/// Read `[defaults]` from the global and local config files, the local
+/// one winning field by field.
+pub fn load_default_flags(workspace: &std::path::Path) -> DefaultFlags {
+    let mut flags = DefaultFlags::default();
+    load_section(workspace, "defaults", |file: DefaultFlags, _| {
+        flags.model = file.model.or(flags.model.take());
+        flags.max_tokens = file.max_tokens.or(flags.max_tokens);
+        flags.reasoning_effort = file.reasoning_effort.or(flags.reasoning_effort.take());
+        flags.readonly = file.readonly.or(flags.readonly);
+        flags.theme = file.theme.or(flags.theme.take());
+    });
+    flags
+}
+
+#[derive(Debug, Default, serde::Deserialize)]
+struct BashSection {
+    #[serde(default)]
+    env_allow: Vec<String>,
+}
+
+/// Read `[bash] env_allow`, the inherited environment variables shell
+/// commands get on top of the minimal set, from the global and local
+/// config files. Both lists apply.
+pub fn load_bash_env_allow(workspace: &std::path::Path) -> Vec<String> {
+    let mut allowed = Vec::new();
+    load_section(workspace, "bash", |section: BashSection, _| {
+        allowed.extend(section.env_allow);
+    });
+    allowed
+}
+
+#[derive(Debug, Default, serde::Deserialize)]
+struct ImagesSection {
+    max_dimension: Option<u32>,
+}
+
+/// Read `[images] max_dimension`, the longest edge in pixels a loaded
+/// image is downscaled to, from the global and local config files, the
+/// local one winning. `None` keeps the built-in bound.
+pub fn load_image_max_dimension(workspace: &std::path::Path) -> Option<u32> {
+    let mut max_dimension = None;
+    load_section(
+        workspace,
+        "images",
+        |section: ImagesSection, path| match section.max_dimension {
+            Some(0) => {
+                tracing::warn!(path = %path.display(), "[images] max_dimension must be positive; ignoring");
+            }
+            value => max_dimension = value.or(max_dimension),
+        },
+    );
+    max_dimension
+}
+
+/// Read the `[key]` section of the global and then the local config
+/// file and hand each one found to `merge`, with the file it came from.
+/// A missing file or section is skipped, and so is an unparsable one, so
+/// a typo there keeps the defaults rather than blocking startup.
+fn load_section<T: serde::de::DeserializeOwned>(
+    workspace: &std::path::Path,
+    key: &str,
+    mut merge: impl FnMut(T, &std::path::Path),
+) {
+    let paths = global_config_path()
+        .into_iter()
+        .chain(std::iter::once(workspace.join(LOCAL_CONFIG_FILE)));
+    for path in paths {
+        let Ok(content) = std::fs::read_to_string(&path) else {
+            continue;
+        };
+        let section = toml::from_str::<toml::Table>(&content)
+            .map_err(|e| e.to_string())
+            .and_then(|mut table| {
+                table
+                    .remove(key)
+                    .map(|value| value.try_into::<T>().map_err(|e| e.to_string()))
+                    .transpose()
+            });
+        match section {
+            Ok(Some(value)) => merge(value, &path),
+            Ok(None) => {}
+            Err(e) => {
+                tracing::warn!(path = %path.display(), section = key, "invalid config section; ignoring");
+                tracing::debug!(error = %e, "config parse error");
+            }
+        }
+    }
+}
+
 /// Per-model trim-safety floor. Above this value the conversation
 /// trim drops older messages without summary as a last resort —
 /// auto-compaction (which preserves context) runs much earlier at
 /// [`crate::api::Model::auto_compact_at`]. Both numbers come
-/// from the same per-model lookup so a single
-/// [`crate::api::model_info::lookup`] call is the source of truth.
-pub fn max_context_tokens_for(model: &str) -> usize {
-    crate::api::model_info::lookup(model).effective_window() as usize
+/// from the same [`crate::api::Model`] entry, so one catalog record is
+/// the source of truth.
+pub fn max_context_tokens_for(model: &crate::api::Model) -> usize {
+    model.effective_window() as usize
 }
 
 /// Auto-compaction trigger for `model`. Keeps the cost-shaping cap and
 /// the API ceiling as separate concepts: this is where the LLM-summary
 /// phase fires, while [`max_context_tokens_for`] is where the hard
 /// drop-trim kicks in.
-pub fn auto_compact_token_limit_for(model: &str) -> usize {
-    crate::api::model_info::lookup(model).auto_compact_at() as usize
+pub fn auto_compact_token_limit_for(model: &crate::api::Model) -> usize {
+    model.auto_compact_at() as usize
 }
 
 /// Opening marker of the injected `[SYSTEM: ...]` preambles below. They
@@ -115,7 +503,7 @@ pub fn readonly_mode_message() -> String {
      move_file, copy_file all unavailable).\n\
      External paths: not reachable.\n\
      \n\
-     Available native tools: list_directory, read_file, glob_files, search_code \
+     Available native tools: list_directory, tree, read_file, glob_files, search_code \
      (when ripgrep is installed), update_plan, web_fetch, web_search. MCP tools \
      are filtered out unless their server is marked readonly = \"read_only\" or \
      \"allow\" in the configuration.\n\
@@ -218,8 +606,9 @@ pub fn sandbox_on_message(_policy: ApprovalPolicy) -> String {
      - Any other command prompts the user for approval before running.\n\
      \n\
      Always refused: parent traversal (..), hidden subcommands ($(...), backticks, \
-     <(...), >(...)), file redirection (>, >>) and here-documents (use write_file or \
-     edit_file instead; 2>&1 is allowed), and dangerous git operations.\n\
+     <(...), >(...)), here-documents (use write_file or edit_file instead), file \
+     redirection (>, >>) to anything but a plain relative path inside the workspace \
+     (cargo build 2> build.log is fine; 2>&1 is allowed), and dangerous git operations.\n\
      \n\
      Operating-system confinement is NOT engaged on Windows in this release: the \
      default shell cannot start under the restricted access token, so shell commands \
@@ -265,9 +654,9 @@ pub fn sandbox_off_message() -> String {
      - Any other command prompts the user for approval before running.\n\
      \n\
      Structural rules still apply: parent traversal (..), hidden subcommands \
-     ($(...), backticks, <(...), >(...)), file redirection (>, >>) and here-documents \
-     (use write_file or edit_file instead; 2>&1 is allowed), and dangerous git \
-     operations are refused outright.\n\
+     ($(...), backticks, <(...), >(...)), here-documents (use write_file or edit_file \
+     instead), file redirection (>, >>) to anything but a plain relative path inside \
+     the workspace (2>&1 is allowed), and dangerous git operations are refused outright.\n\
      \n\
      No operating-system confinement is applied; intended for trusted environments only.\n\
      \n\
@@ -275,6 +664,23 @@ pub fn sandbox_off_message() -> String {
         .to_string()
 }
 
+/// Tells the assistant which subdirectory `/scope` narrowed the tools to,
+/// or that the narrowing was lifted when `scope` is `None`.
+pub fn scope_message(scope: Option<&std::path::Path>) -> String {
+    match scope {
+        Some(dir) => format!(
+            "[SYSTEM: The working scope is now the `{}` subdirectory of the project. \
+             Relative paths in file tools, code search and shell commands resolve from \
+             there, and files outside it are treated as outside the workspace. \
+             Keep your work inside this scope.]",
+            dir.display()
+        ),
+        None => "[SYSTEM: The working scope has been reset to the project root. \
+                 Relative paths resolve from the root again.]"
+            .to_string(),
+    }
+}
+
 /// How much access the assistant has to the workspace and the shell.
 ///
 /// Chosen at startup from the command line (`--readonly`,
@@ -763,4 +1169,175 @@ mod tests {
             assert_eq!(PermissionPreset::current(preset.mode(), policy), preset);
         }
     }
+
+    #[test]
+    fn morph_fallback_reads_the_local_config() {
+        let workspace = tempfile::TempDir::new().unwrap();
+        let config = workspace.path().join(LOCAL_CONFIG_FILE);
+        std::fs::create_dir_all(config.parent().unwrap()).unwrap();
+
+        std::fs::write(&config, "[morph]\nfallback = \"none\"\n").unwrap();
+        assert_eq!(load_morph_fallback(workspace.path()), MorphFallback::None);
+
+        // No [morph] section, or an unknown value, leaves the default.
+        std::fs::write(&config, "[permissions]\nallow = []\n").unwrap();
+        assert_eq!(
+            load_morph_fallback(workspace.path()),
+            MorphFallback::Instruct
+        );
+        std::fs::write(&config, "[morph]\nfallback = \"retry\"\n").unwrap();
+        assert_eq!(
+            load_morph_fallback(workspace.path()),
+            MorphFallback::Instruct
+        );
+    }
+
+    #[test]
+    fn default_flags_read_the_local_config() {
+        let workspace = tempfile::TempDir::new().unwrap();
+        let config = workspace.path().join(LOCAL_CONFIG_FILE);
+        std::fs::create_dir_all(config.parent().unwrap()).unwrap();
+
+        std::fs::write(
+            &config,
+            "[defaults]\nmodel = \"gpt-5.6-luna\"\nmax_tokens = 64000\nreadonly = true\n",
+        )
+        .unwrap();
+        let flags = load_default_flags(workspace.path());
+        assert_eq!(flags.model.as_deref(), Some("gpt-5.6-luna"));
+        assert_eq!(flags.max_tokens, Some(64000));
+        assert_eq!(flags.readonly, Some(true));
+        assert_eq!(flags.theme, None);
+
+        std::fs::write(&config, "[defaults]\nmax_tokens = \"lots\"\n").unwrap();
+        assert_eq!(
+            load_default_flags(workspace.path()),
+            DefaultFlags::default()
+        );
+    }
+
+    #[test]
+    fn diff_style_reads_the_local_config() {
+        let workspace = tempfile::TempDir::new().unwrap();
+        let config = workspace.path().join(LOCAL_CONFIG_FILE);
+        std::fs::create_dir_all(config.parent().unwrap()).unwrap();
+
+        std::fs::write(&config, "[display]\ndiff_style = \"side-by-side\"\n").unwrap();
+        assert_eq!(load_diff_style(workspace.path()), DiffStyle::SideBySide);
+
+        std::fs::write(&config, "[display]\ndiff_style = \"fancy\"\n").unwrap();
+        assert_eq!(load_diff_style(workspace.path()), DiffStyle::Compact);
+    }
+
+    #[test]
+    fn conversation_settings_read_the_local_config() {
+        let workspace = tempfile::TempDir::new().unwrap();
+        let config = workspace.path().join(LOCAL_CONFIG_FILE);
+        std::fs::create_dir_all(config.parent().unwrap()).unwrap();
+
+        std::fs::write(
+            &config,
+            "[conversation]\nmax_messages = 200\non_message_limit = \"compact\"\n",
+        )
+        .unwrap();
+        assert_eq!(
+            load_conversation_settings(workspace.path()),
+            ConversationSettings {
+                max_messages: Some(200),
+                on_message_limit: Some(MessageLimit::Compact),
+            }
+        );
+
+        std::fs::write(&config, "[conversation]\non_message_limit = \"keep\"\n").unwrap();
+        assert_eq!(
+            load_conversation_settings(workspace.path()),
+            ConversationSettings::default()
+        );
+    }
+
+    #[test]
+    fn budget_action_reads_the_local_config() {
+        let workspace = tempfile::TempDir::new().unwrap();
+        let config = workspace.path().join(LOCAL_CONFIG_FILE);
+        std::fs::create_dir_all(config.parent().unwrap()).unwrap();
+
+        assert_eq!(load_budget_action(workspace.path()), BudgetAction::Stop);
+        std::fs::write(&config, "[budget]\non_exceeded = \"ask\"\n").unwrap();
+        assert_eq!(load_budget_action(workspace.path()), BudgetAction::Ask);
+        std::fs::write(&config, "[budget]\non_exceeded = \"warn\"\n").unwrap();
+        assert_eq!(load_budget_action(workspace.path()), BudgetAction::Stop);
+    }
+
+    #[test]
+    fn pricing_entries_are_read_and_bad_prices_rejected() {
+        let workspace = tempfile::TempDir::new().unwrap();
+        let config = workspace.path().join(LOCAL_CONFIG_FILE);
+        std::fs::create_dir_all(config.parent().unwrap()).unwrap();
+        std::fs::write(
+            &config,
+            "[pricing]\n\"Llama-3\" = { input = 0.2, output = 0.6 }\n",
+        )
+        .unwrap();
+
+        let pricing = load_pricing(workspace.path());
+        assert_eq!(
+            pricing.get("llama-3"),
+            Some(&ModelPrice {
+                input: 0.2,
+                output: 0.6
+            })
+        );
+        std::fs::write(&config, "[pricing]\nm = { input = -1.0, output = 1.0 }\n").unwrap();
+        assert!(!load_pricing(workspace.path()).contains_key("m"));
+        std::fs::write(&config, "[pricing]\nm = { input = 1.0 }\n").unwrap();
+        assert!(!load_pricing(workspace.path()).contains_key("m"));
+    }
+
+    #[test]
+    fn retry_settings_merge_field_by_field() {
+        let workspace = tempfile::TempDir::new().unwrap();
+        let config = workspace.path().join(LOCAL_CONFIG_FILE);
+        std::fs::create_dir_all(config.parent().unwrap()).unwrap();
+        std::fs::write(&config, "[retry]\nmax_retries = 5\n").unwrap();
+
+        let settings = load_retry_settings(workspace.path());
+        assert_eq!(settings.max_retries, Some(5));
+        assert_eq!(settings.base_delay_ms, None);
+        std::fs::write(&config, "[retry]\nmax_retries = -1\n").unwrap();
+        assert_eq!(
+            load_retry_settings(workspace.path()),
+            RetrySettings::default()
+        );
+    }
+
+    #[test]
+    fn model_aliases_are_lowercased_and_skip_empty_ids() {
+        let workspace = tempfile::TempDir::new().unwrap();
+        let config = workspace.path().join(LOCAL_CONFIG_FILE);
+        std::fs::create_dir_all(config.parent().unwrap()).unwrap();
+
+        std::fs::write(
+            &config,
+            "[aliases]\nFast = \"gpt-5.4-mini\"\nnone = \" \"\n",
+        )
+        .unwrap();
+        let aliases = load_model_aliases(workspace.path());
+        assert_eq!(
+            aliases.get("fast").map(String::as_str),
+            Some("gpt-5.4-mini")
+        );
+        assert!(!aliases.contains_key("none"));
+    }
+
+    #[test]
+    fn image_max_dimension_ignores_zero() {
+        let workspace = tempfile::TempDir::new().unwrap();
+        let config = workspace.path().join(LOCAL_CONFIG_FILE);
+        std::fs::create_dir_all(config.parent().unwrap()).unwrap();
+
+        std::fs::write(&config, "[images]\nmax_dimension = 1568\n").unwrap();
+        assert_eq!(load_image_max_dimension(workspace.path()), Some(1568));
+        std::fs::write(&config, "[images]\nmax_dimension = 0\n").unwrap();
+        assert_eq!(load_image_max_dimension(workspace.path()), None);
+    }
 }
@@ -131,6 +131,8 @@ impl SofosError {
                     || msg.contains("unauthorized")
                 {
                     Some("Check that your API key is valid and has not expired".to_string())
+                } else if msg.contains("not available for this key") {
+                    Some("Pick another model with --model, or check the key's access".to_string())
                 } else if msg.contains("429") || msg.contains("rate limit") {
                     Some("Wait a moment and try again, or reduce request frequency".to_string())
                 } else if msg.contains("500") || msg.contains("server error") {
@@ -175,6 +177,11 @@ impl SofosError {
                         "Set OPENAI_API_KEY environment variable or use --openai-api-key flag"
                             .to_string(),
                     )
+                } else if msg.contains("GEMINI_API_KEY") {
+                    Some(
+                        "Set GEMINI_API_KEY environment variable or use --gemini-api-key flag"
+                            .to_string(),
+                    )
                 } else if msg.contains("max_tokens") && msg.contains("thinking-budget ceiling") {
                     // Matches the validation message in `Repl::new`. The
                     // suggestion no longer mentions `--thinking-budget`
@@ -10,14 +10,17 @@ mod session;
 mod tools;
 mod ui;
 
-use api::{AnthropicClient, LlmClient, MorphClient, OpenAIClient};
-use clap::Parser;
+use api::{LlmClient, MorphClient, OpenAIClient};
+use clap::{CommandFactory, FromArgMatches};
 use cli::Cli;
 use colored::Colorize;
 use error::Result;
+use repl::conversation::SystemPromptFile;
 use repl::{Repl, ReplConfig};
 use session::HistoryManager;
 use std::env;
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
 use ui::UI;
 
 fn main() -> Result<()> {
@@ -29,7 +32,35 @@ fn main() -> Result<()> {
         .without_time()
         .init();
 
-    let cli = Cli::parse();
+    let matches = Cli::command().get_matches();
+    let mut cli = Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+    let workspace = workspace_root(cli.workspace.as_deref()).unwrap_or_else(|e| {
+        eprintln!("{} {}", "error:".bright_red().bold(), e);
+        std::process::exit(2)
+    });
+    cli.apply_config_defaults(&matches, crate::config::load_default_flags(&workspace));
+    crate::api::model_info::set_model_aliases(crate::config::load_model_aliases(&workspace));
+
+    if cli.no_color || !crate::ui::appearance::color_enabled() {
+        crate::ui::appearance::disable_color();
+    }
+    if let Some(name) = cli.theme.as_deref() {
+        match crate::ui::appearance::parse_theme(name) {
+            Some(theme) => crate::ui::appearance::set_theme(theme),
+            None => {
+                eprintln!(
+                    "{} Theme `{}` is not bundled.",
+                    "error:".bright_red().bold(),
+                    name
+                );
+                eprintln!(
+                    "  [supported themes: {}]",
+                    crate::ui::appearance::THEMES.join(", ")
+                );
+                std::process::exit(2);
+            }
+        }
+    }
 
     // API keys on the command line land in `ps` output and shell
     // history; the env-var form is the safe alternative. clap's
@@ -42,6 +73,11 @@ fn main() -> Result<()> {
             "OPENAI_API_KEY",
             "--openai-api-key",
         ),
+        (
+            cli.gemini_api_key.as_deref(),
+            "GEMINI_API_KEY",
+            "--gemini-api-key",
+        ),
         (
             cli.morph_api_key.as_deref(),
             "MORPH_API_KEY",
@@ -68,13 +104,18 @@ fn main() -> Result<()> {
         );
     }
 
+    // Aliases expand first, so the checks and provider detection below
+    // only ever see a full model id.
+    let model_alias = crate::api::model_info::expand_alias(&cli.model)
+        .map(|id| std::mem::replace(&mut cli.model, id));
     // Reject `--model` values outside the supported whitelist before
     // anything else looks at the slug, and normalise the case to the
     // canonical form so internal state and the provider wire payload
     // never carry a mixed-case spelling.
-    let mut cli = cli;
     match crate::api::model_info::canonical_model(&cli.model) {
         Some(choice) => cli.model = choice.name.to_string(),
+        // The custom endpoint knows its own models; send the name as given.
+        None if cli.api_base.is_some() => {}
         None => {
             let reason = crate::api::model_info::model_support_error(&cli.model)
                 .unwrap_or_else(|| format!("Model `{}` is not supported.", cli.model));
@@ -91,7 +132,7 @@ fn main() -> Result<()> {
     // the per-model rejection both render in the same clap-style
     // envelope. The supported-values list is per-model, which clap's
     // `ValueEnum` derive can't produce.
-    let model_info = crate::api::model_info::lookup(&cli.model);
+    let model_info = crate::api::model_info::lookup_on(&cli.model, cli.api_base.is_some());
     let bail = |reason: String| -> ! {
         eprintln!("{} {}", "error:".bright_red().bold(), reason);
         eprintln!(
@@ -132,6 +173,88 @@ fn main() -> Result<()> {
         }
     };
 
+    let verbosity = match crate::config::Verbosity::parse(&cli.verbosity) {
+        Some(v) => v,
+        None => {
+            eprintln!(
+                "{} invalid verbosity '{}'; expected one of: concise, normal, detailed",
+                "error:".bright_red().bold(),
+                cli.verbosity
+            );
+            std::process::exit(2);
+        }
+    };
+
+    let output_format = match crate::config::OutputFormat::parse(&cli.output) {
+        Some(format) => format,
+        None => {
+            eprintln!(
+                "{} invalid output format '{}'; expected one of: text, json",
+                "error:".bright_red().bold(),
+                cli.output
+            );
+            std::process::exit(2);
+        }
+    };
+
+    let inputs = match crate::tools::inputs::load_inputs(&cli.inputs, std::io::stdin()) {
+        Ok(inputs) => inputs,
+        Err(e) => {
+            eprintln!("{} {}", "error:".bright_red().bold(), e);
+            std::process::exit(2);
+        }
+    };
+
+    if let Err(e) = cli.apply_prompt_template() {
+        eprintln!("{} {}", "error:".bright_red().bold(), e);
+        std::process::exit(2);
+    }
+
+    // Piped stdin makes this a one-shot run, unless `--input <name>=-`
+    // already consumed it.
+    let stdin = std::io::stdin();
+    if !stdin.is_terminal()
+        && !cli.check_connection
+        && !crate::tools::inputs::reads_stdin(&cli.inputs)
+    {
+        if let Err(e) = cli.apply_piped_stdin(stdin.lock()) {
+            eprintln!("{} {}", "error:".bright_red().bold(), e);
+            std::process::exit(2);
+        }
+    }
+
+    let json_output = output_format == crate::config::OutputFormat::Json;
+    if json_output && cli.prompt.is_none() {
+        eprintln!(
+            "{} --output json needs --prompt or --prompt-template",
+            "error:".bright_red().bold()
+        );
+        std::process::exit(2);
+    }
+    // Scripts parse stdout, so JSON mode prints no colour and sends the
+    // transcript to stderr until the report is written.
+    if json_output {
+        colored::control::set_override(false);
+    }
+    let stdout_redirect = if json_output && !cli.check_connection {
+        Some(crate::ui::stdout_redirect::StdoutToStderr::install()?)
+    } else {
+        None
+    };
+
+    if cli.max_cost.is_some_and(|usd| usd.is_nan() || usd <= 0.0) {
+        eprintln!(
+            "{} --max-cost must be a positive amount in US dollars",
+            "error:".bright_red().bold()
+        );
+        std::process::exit(2);
+    }
+    let sampling = cli.sampling();
+    if let Some(reason) = sampling.validation_error() {
+        eprintln!("{} {}", "error:".bright_red().bold(), reason);
+        std::process::exit(2);
+    }
+
     // Historically the logo printed here, up front. It's now deferred:
     // in interactive mode the banner text is collected into
     // `startup_banner` below and replayed through the TUI's capture
@@ -155,12 +278,12 @@ fn main() -> Result<()> {
                  only the connectivity check will run.",
             );
         }
-        return check_api_connectivity(&client);
+        let model = cli.check_model.then_some(cli.model.as_str());
+        return check_api_connectivity(&client, model);
     }
 
-    let workspace = env::current_dir().map_err(|e| {
-        error::SofosError::Config(format!("Failed to get current directory: {}", e))
-    })?;
+    let client = client.with_retry_policy(crate::config::load_retry_settings(&workspace).into());
+    crate::ui::cost::set_pricing(crate::config::load_pricing(&workspace));
 
     // Collect the startup lines (logo + workspace/model/reasoning/morph)
     // into one string rather than `println!`-ing them. In interactive
@@ -179,14 +302,28 @@ fn main() -> Result<()> {
         "Workspace:".bright_cyan(),
         workspace.display().to_string().dimmed()
     ));
-    startup_banner.push_str(&format!("{} {}\n", "Model:".bright_green(), cli.model));
+    match &model_alias {
+        Some(alias) => startup_banner.push_str(&format!(
+            "{} {} {}\n",
+            "Model:".bright_green(),
+            cli.model,
+            format!("(alias `{}`)", alias).dimmed()
+        )),
+        None => startup_banner.push_str(&format!("{} {}\n", "Model:".bright_green(), cli.model)),
+    }
+    if let Some(base) = &cli.api_base {
+        startup_banner.push_str(&format!("{} {}\n", "API base:".bright_green(), base));
+    }
 
-    if matches!(client, LlmClient::OpenAI(_)) {
-        startup_banner.push_str(&format!(
-            "{} {}\n",
-            "Reasoning effort:".bright_green(),
-            reasoning_effort.as_label()
-        ));
+    if !matches!(client, LlmClient::Anthropic(_)) {
+        // Reasoning settings are not sent to a custom endpoint.
+        if !client.uses_custom_endpoint() {
+            startup_banner.push_str(&format!(
+                "{} {}\n",
+                "Reasoning effort:".bright_green(),
+                reasoning_effort.as_label()
+            ));
+        }
     } else if crate::api::anthropic::requires_adaptive_thinking(&cli.model) {
         // Adaptive-thinking models pick their own budget; advertising
         // a token count would be a lie.
@@ -229,6 +366,32 @@ fn main() -> Result<()> {
         }
     });
 
+    if cli.dry_run {
+        startup_banner.push_str(&format!(
+            "{}\n",
+            "Dry run: file changes and shell commands are previewed, not executed".bright_yellow()
+        ));
+    }
+
+    if cli.confirm_edits {
+        startup_banner.push_str(&format!(
+            "{}\n",
+            "Confirm edits: file changes are previewed and need your approval".bright_yellow()
+        ));
+    }
+
+    if cli.audit {
+        crate::tools::audit::enable(&workspace);
+        startup_banner.push_str(&format!(
+            "{}\n",
+            format!(
+                "Audit: tool calls and permission decisions are logged to {}",
+                crate::tools::audit::AUDIT_LOG_FILE
+            )
+            .dimmed()
+        ));
+    }
+
     if !interactive_mode {
         print!("{}", startup_banner);
     }
@@ -242,16 +405,32 @@ fn main() -> Result<()> {
     // `/permissions` sandboxed presets, not from the command line.
     let approval_policy = crate::config::ApprovalPolicy::default();
     // Startup enters the default preset without the `/permissions` notice,
-    // so show it here. Resume skips this and prints the notice for its
-    // restored preset from the resume flow instead.
-    if interactive_mode && !cli.resume && mode == crate::config::SandboxMode::Sandboxed {
+    // so show it here. Resume and import skip this and print the notice for
+    // their restored preset from the resume flow instead.
+    let continue_from = if cli.continue_session {
+        let latest = HistoryManager::new(workspace.clone())?.latest_session_id()?;
+        if latest.is_none() {
+            println!(
+                "{}",
+                "No saved sessions found; starting a new session.".yellow()
+            );
+        }
+        latest
+    } else {
+        None
+    };
+    let resuming =
+        cli.resume || cli.import.is_some() || continue_from.is_some() || cli.search.is_some();
+    if interactive_mode && !resuming && mode == crate::config::SandboxMode::Sandboxed {
         let preset = crate::config::PermissionPreset::current(mode, approval_policy);
         startup_banner.push_str(&format!(
             "{}\n",
             crate::repl::permission_preset_notice(preset)
         ));
     }
-    let config = ReplConfig::new(
+    let bash_limits = cli.bash_limits();
+    let provider_keys = cli.provider_keys();
+    let mut config = ReplConfig::new(
         cli.model,
         cli.max_tokens,
         reasoning_effort,
@@ -259,6 +438,66 @@ fn main() -> Result<()> {
         mode,
         approval_policy,
     );
+    config.smart_paths = cli.smart_paths;
+    config.dry_run = cli.dry_run;
+    config.confirm_edits = cli.confirm_edits;
+    config.backups = cli.backups;
+    config.no_image_resize = cli.no_image_resize;
+    config.bash_limits = bash_limits;
+    config.provider_keys = provider_keys;
+    config.verbosity = verbosity;
+    config.inputs = inputs;
+    config.max_messages = cli.max_messages;
+    if let Some(n) = cli.max_tool_iterations {
+        config.max_tool_iterations = n;
+    }
+    config.auto_continue = cli.auto_continue;
+    config.compact_tool_results = cli.compact_tool_results;
+    if let Some(path) = &cli.system_prompt_file {
+        match SystemPromptFile::load(path, cli.replace_system_prompt) {
+            Ok(file) => config.system_prompt_file = Some(file),
+            Err(e) => {
+                eprintln!("{} {}", "error:".bright_red().bold(), e);
+                std::process::exit(2);
+            }
+        }
+        if cli.replace_system_prompt {
+            UI::print_warning(
+                "--replace-system-prompt drops the built-in prompt, including its guidance on tools, permissions, and testing. The file has to cover whatever the model still needs.",
+            );
+        }
+    }
+    config.output_format = output_format;
+    let provider =
+        crate::api::model_info::lookup_on(&config.model, cli.api_base.is_some()).provider;
+    if sampling.temperature.is_some() && provider == crate::api::model_info::Provider::Anthropic {
+        UI::print_warning(
+            "--temperature has no effect on Claude models: thinking is always on, and it requires the default temperature.",
+        );
+    }
+    if (sampling.temperature.is_some() || sampling.top_p.is_some())
+        && provider == crate::api::model_info::Provider::OpenAI
+        && cli.api_base.is_none()
+    {
+        UI::print_warning(
+            "--temperature and --top-p have no effect on OpenAI reasoning models, which reject sampling parameters.",
+        );
+    }
+    config.sampling = sampling;
+    // A one-shot run has nobody to answer the prompt, so it always stops.
+    let budget_action = if interactive_mode {
+        crate::config::load_budget_action(&workspace)
+    } else {
+        crate::config::BudgetAction::Stop
+    };
+    config.budget = crate::repl::Budget::new(cli.max_cost, cli.max_total_tokens, budget_action);
+    if cli.max_cost.is_some() && !UI::has_price(&config.model) {
+        UI::print_warning(&format!(
+            "--max-cost cannot be enforced: no price is known for {}. Add one under [pricing] in {}.",
+            config.model,
+            crate::config::config_files_hint()
+        ));
+    }
 
     let mut repl = Repl::new(client, config, workspace.clone(), morph_client).unwrap_or_else(|e| {
         UI::print_error_with_hint(&e);
@@ -277,16 +516,40 @@ fn main() -> Result<()> {
         print!("{}", mcp_section);
     }
 
-    if cli.resume {
+    if let Some(path) = &cli.import {
+        let history_manager = HistoryManager::new(workspace.clone())?;
+        let session_id = history_manager.import_session(path)?;
+        repl.load_session_by_id(&session_id)?;
+        println!();
+    } else if let Some(session_id) = &continue_from {
+        repl.load_session_by_id(session_id)?;
+        println!();
+    } else if let Some(query) = &cli.search {
+        let history_manager = HistoryManager::new(workspace)?;
+        let sessions = history_manager.search_sessions(query)?;
+        if sessions.is_empty() {
+            println!(
+                "{}",
+                format!("No sessions match '{}'; starting a new session.", query).yellow()
+            );
+        } else if let Some(session_id) = session::select_session(&history_manager, sessions)? {
+            repl.load_session_by_id(&session_id)?;
+            println!();
+        }
+    } else if cli.resume {
         let history_manager = HistoryManager::new(workspace)?;
         let sessions = history_manager.list_sessions()?;
 
-        if let Some(session_id) = session::select_session(sessions)? {
+        if let Some(session_id) = session::select_session(&history_manager, sessions)? {
             repl.load_session_by_id(&session_id)?;
             println!();
         }
     }
 
+    // `process_single_prompt` redirects the turn itself so it can write
+    // the report to the real stdout afterwards.
+    drop(stdout_redirect);
+
     if let Some(prompt) = cli.prompt {
         repl.process_single_prompt(&prompt)?;
     } else {
@@ -296,6 +559,25 @@ fn main() -> Result<()> {
     Ok(())
 }
 
+/// The workspace every tool is confined to: `--workspace` when given,
+/// canonicalized and checked to be a directory, else the current one.
+fn workspace_root(dir: Option<&Path>) -> Result<PathBuf> {
+    let Some(dir) = dir else {
+        return env::current_dir().map_err(|e| {
+            error::SofosError::Config(format!("Failed to get current directory: {}", e))
+        });
+    };
+    let canonical = std::fs::canonicalize(dir)
+        .map_err(|e| error::SofosError::Config(format!("workspace '{}': {}", dir.display(), e)))?;
+    if !canonical.is_dir() {
+        return Err(error::SofosError::Config(format!(
+            "workspace '{}' is not a directory",
+            dir.display()
+        )));
+    }
+    Ok(canonical)
+}
+
 /// Construct the LLM client matching `cli.model`. Both the API-key
 /// fetch and the client constructor exit the process via
 /// `UI::print_error_with_hint` on failure — funnelled through one
@@ -303,16 +585,17 @@ fn main() -> Result<()> {
 /// sync across all four failure modes.
 fn build_llm_client(cli: &Cli) -> LlmClient {
     fn try_build(cli: &Cli) -> Result<LlmClient> {
-        match crate::api::model_info::provider_for(&cli.model) {
-            crate::api::model_info::Provider::OpenAI => {
-                let key = cli.get_openai_api_key()?;
-                Ok(LlmClient::OpenAI(OpenAIClient::new(key)?))
-            }
-            crate::api::model_info::Provider::Anthropic => {
-                let key = cli.get_anthropic_api_key()?;
-                Ok(LlmClient::Anthropic(AnthropicClient::new(key)?))
-            }
+        if let Some(base) = &cli.api_base {
+            let key = cli.api_base_key.clone().unwrap_or_default();
+            return Ok(LlmClient::OpenAI(OpenAIClient::new(
+                key,
+                Some(base.clone()),
+            )?));
         }
+        LlmClient::for_provider(
+            crate::api::model_info::provider_for(&cli.model),
+            &cli.provider_keys(),
+        )
     }
     try_build(cli).unwrap_or_else(|e| {
         UI::print_error_with_hint(&e);
@@ -320,7 +603,7 @@ fn build_llm_client(cli: &Cli) -> LlmClient {
     })
 }
 
-fn check_api_connectivity(client: &LlmClient) -> Result<()> {
+fn check_api_connectivity(client: &LlmClient, model: Option<&str>) -> Result<()> {
     let provider = client.provider_name();
     println!("Checking {} API connectivity...", provider.bright_cyan());
 
@@ -340,6 +623,24 @@ fn check_api_connectivity(client: &LlmClient) -> Result<()> {
                 provider,
                 "/".dimmed()
             );
+        }
+        Err(e) => {
+            UI::print_error_with_hint(&e);
+            std::process::exit(1);
+        }
+    }
+
+    let Some(model) = model else {
+        return Ok(());
+    };
+    println!("Checking model {}...", model.bright_cyan());
+    match runtime.block_on(client.check_model(model)) {
+        Ok(()) => {
+            println!(
+                "{} model '{}' answered; key accepted",
+                "✓".bright_green().bold(),
+                model
+            );
             Ok(())
         }
         Err(e) => {
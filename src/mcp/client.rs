@@ -3,9 +3,11 @@ use crate::mcp::config::McpServerConfig;
 use crate::mcp::protocol::*;
 use crate::mcp::transport::{HttpClient, StdioClient};
 use serde_json::Value;
+use std::path::Path;
 use std::time::Duration;
 
-/// Ceiling on a single MCP request (stdio read + HTTP round-trip). A
+/// Default ceiling on a single MCP request (stdio read + HTTP
+/// round-trip); a server entry can override it with `timeout_secs`. A
 /// misbehaving MCP server used to freeze every subsequent MCP call
 /// because `BufRead::read_line` blocks indefinitely and the stdout
 /// mutex serialises all requests. Two minutes is generous enough for
@@ -57,9 +59,12 @@ pub enum McpClient {
 }
 
 impl McpClient {
-    pub async fn connect(name: String, config: McpServerConfig) -> Result<Self> {
+    pub async fn connect(name: String, config: McpServerConfig, workspace: &Path) -> Result<Self> {
+        let config = config
+            .expand_env_vars()
+            .map_err(|e| SofosError::McpError(format!("MCP server '{}': {}", name, e)))?;
         if config.is_stdio() {
-            let client = StdioClient::new(name, config).await?;
+            let client = StdioClient::new(name, config, workspace).await?;
             Ok(McpClient::Stdio(client))
         } else if config.is_http() {
             let client = HttpClient::new(name, config).await?;
@@ -71,6 +76,15 @@ impl McpClient {
         }
     }
 
+    /// Whether the server was stopped after a timeout. HTTP servers are
+    /// never stopped; each request stands alone.
+    pub fn is_stopped(&self) -> bool {
+        match self {
+            McpClient::Stdio(client) => client.is_stopped(),
+            McpClient::Http(_) => false,
+        }
+    }
+
     pub async fn list_tools(&self) -> Result<Vec<McpTool>> {
         match self {
             McpClient::Stdio(client) => client.list_tools().await,
@@ -1,6 +1,8 @@
+use crate::mcp::client::MCP_REQUEST_TIMEOUT;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
+use std::time::Duration;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct McpConfig {
@@ -32,6 +34,18 @@ impl ReadOnlyAccess {
     }
 }
 
+/// How an HTTP server's responses are read. When unset, the
+/// `Content-Type` of each response decides.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum HttpTransport {
+    /// A single `application/json` body per request.
+    Http,
+    /// A `text/event-stream` body carrying the JSON-RPC response as an
+    /// event, possibly after progress notifications.
+    Sse,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct McpServerConfig {
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -54,6 +68,15 @@ pub struct McpServerConfig {
     /// access to read-only sessions.
     #[serde(default, alias = "safe_mode")]
     pub readonly: ReadOnlyAccess,
+
+    /// Per-request ceiling in seconds. Unset or zero falls back to the
+    /// shared default.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_secs: Option<u64>,
+
+    /// Response format hint for HTTP servers; see [`HttpTransport`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transport: Option<HttpTransport>,
 }
 
 impl McpServerConfig {
@@ -65,6 +88,13 @@ impl McpServerConfig {
         self.url.is_some()
     }
 
+    pub fn request_timeout(&self) -> Duration {
+        match self.timeout_secs {
+            Some(secs) if secs > 0 => Duration::from_secs(secs),
+            _ => MCP_REQUEST_TIMEOUT,
+        }
+    }
+
     pub fn validate(&self) -> Result<(), String> {
         if self.command.is_none() && self.url.is_none() {
             return Err("MCP server must have either 'command' or 'url'".to_string());
@@ -74,8 +104,71 @@ impl McpServerConfig {
             return Err("MCP server cannot have both 'command' and 'url'".to_string());
         }
 
+        if self.transport.is_some() && self.url.is_none() {
+            return Err("MCP server 'transport' applies only to servers with a 'url'".to_string());
+        }
+
         Ok(())
     }
+
+    /// Replace `${VAR}` references in `command`, `args`, `env` values,
+    /// `url` and `headers` values with the variable's value, so a
+    /// committed config can point at per-developer secrets and paths.
+    /// Resolved at connect time; an unset variable is an error.
+    pub fn expand_env_vars(self) -> Result<Self, String> {
+        self.expand_with(|name| std::env::var(name).ok())
+    }
+
+    fn expand_with(mut self, lookup: impl Fn(&str) -> Option<String>) -> Result<Self, String> {
+        let expand = |value: &mut String| -> Result<(), String> {
+            *value = expand_vars(value, &lookup)?;
+            Ok(())
+        };
+        if let Some(command) = self.command.as_mut() {
+            expand(command)?;
+        }
+        for arg in self.args.iter_mut().flatten() {
+            expand(arg)?;
+        }
+        for value in self.env.iter_mut().flat_map(|env| env.values_mut()) {
+            expand(value)?;
+        }
+        if let Some(url) = self.url.as_mut() {
+            expand(url)?;
+        }
+        for value in self.headers.iter_mut().flat_map(|h| h.values_mut()) {
+            expand(value)?;
+        }
+        Ok(self)
+    }
+}
+
+/// Expand every `${NAME}` in `value`. A `$` not followed by `{` is kept.
+fn expand_vars(value: &str, lookup: &impl Fn(&str) -> Option<String>) -> Result<String, String> {
+    let mut out = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(start) = rest.find("${") {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let end = after
+            .find('}')
+            .ok_or_else(|| format!("unterminated '${{' in '{}'", value))?;
+        let name = &after[..end];
+        let valid_name = name
+            .chars()
+            .next()
+            .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+            && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+        if !valid_name {
+            return Err(format!("invalid variable name '${{{}}}'", name));
+        }
+        let resolved =
+            lookup(name).ok_or_else(|| format!("environment variable '{}' is not set", name))?;
+        out.push_str(&resolved);
+        rest = &after[end + 1..];
+    }
+    out.push_str(rest);
+    Ok(out)
 }
 
 /// Load MCP configuration from the global and local config files. The
@@ -191,6 +284,55 @@ headers = { "Authorization" = "Bearer token" }
         assert!(server.is_http());
     }
 
+    #[test]
+    fn expands_env_vars_in_every_string_field() {
+        let toml_content = r#"
+[mcp-servers.remote]
+url = "https://${HOST}/mcp"
+headers = { "Authorization" = "Bearer ${TOKEN}" }
+
+[mcp-servers.local]
+command = "${TOOLS}/server"
+args = ["--price", "$5", "--dir=${TOOLS}"]
+env = { "KEY" = "${TOKEN}" }
+"#;
+        let config: McpConfig = toml::from_str(toml_content).unwrap();
+        let lookup = |name: &str| match name {
+            "HOST" => Some("mcp.example.com".to_string()),
+            "TOKEN" => Some("s3cret".to_string()),
+            "TOOLS" => Some("/opt/tools".to_string()),
+            _ => None,
+        };
+
+        let remote = config.mcp_servers["remote"]
+            .clone()
+            .expand_with(lookup)
+            .unwrap();
+        assert_eq!(remote.url.as_deref(), Some("https://mcp.example.com/mcp"));
+        assert_eq!(remote.headers.unwrap()["Authorization"], "Bearer s3cret");
+
+        let local = config.mcp_servers["local"]
+            .clone()
+            .expand_with(lookup)
+            .unwrap();
+        assert_eq!(local.command.as_deref(), Some("/opt/tools/server"));
+        assert_eq!(
+            local.args.unwrap(),
+            ["--price", "$5", "--dir=/opt/tools"].map(String::from)
+        );
+        assert_eq!(local.env.unwrap()["KEY"], "s3cret");
+    }
+
+    #[test]
+    fn unset_or_malformed_env_vars_are_errors() {
+        let lookup = |_: &str| None;
+        let err = expand_vars("${MISSING_VAR}", &lookup).unwrap_err();
+        assert!(err.contains("'MISSING_VAR' is not set"), "{err}");
+        assert!(expand_vars("${OPEN", &lookup).is_err());
+        assert!(expand_vars("${1BAD}", &lookup).is_err());
+        assert_eq!(expand_vars("plain $HOME", &lookup).unwrap(), "plain $HOME");
+    }
+
     #[test]
     fn load_filters_out_invalid_server_entries() {
         // The loader used to log a warning and return invalid entries,
@@ -243,6 +385,8 @@ url = "https://example.com/mcp"
             url: None,
             headers: None,
             readonly: ReadOnlyAccess::default(),
+            timeout_secs: None,
+            transport: None,
         };
         assert!(valid_stdio.validate().is_ok());
 
@@ -253,6 +397,8 @@ url = "https://example.com/mcp"
             url: Some("https://example.com".to_string()),
             headers: None,
             readonly: ReadOnlyAccess::default(),
+            timeout_secs: None,
+            transport: None,
         };
         assert!(valid_http.validate().is_ok());
 
@@ -263,6 +409,8 @@ url = "https://example.com/mcp"
             url: None,
             headers: None,
             readonly: ReadOnlyAccess::default(),
+            timeout_secs: None,
+            transport: None,
         };
         assert!(invalid_empty.validate().is_err());
 
@@ -273,10 +421,51 @@ url = "https://example.com/mcp"
             url: Some("https://example.com".to_string()),
             headers: None,
             readonly: ReadOnlyAccess::default(),
+            timeout_secs: None,
+            transport: None,
         };
         assert!(invalid_both.validate().is_err());
     }
 
+    #[test]
+    fn timeout_secs_overrides_the_default_ceiling() {
+        let toml_content = r#"
+[mcp-servers.slow]
+command = "/path/to/server"
+timeout_secs = 30
+
+[mcp-servers.plain]
+command = "/path/to/server"
+
+[mcp-servers.zero]
+command = "/path/to/server"
+timeout_secs = 0
+"#;
+        let config: McpConfig = toml::from_str(toml_content).unwrap();
+        let timeout = |name: &str| config.mcp_servers[name].request_timeout();
+        assert_eq!(timeout("slow"), Duration::from_secs(30));
+        assert_eq!(timeout("plain"), MCP_REQUEST_TIMEOUT);
+        assert_eq!(timeout("zero"), MCP_REQUEST_TIMEOUT);
+    }
+
+    #[test]
+    fn transport_hint_parses_and_needs_a_url() {
+        let toml_content = r#"
+[mcp-servers.hosted]
+url = "https://example.com/mcp"
+transport = "sse"
+
+[mcp-servers.local]
+command = "/path/to/server"
+transport = "http"
+"#;
+        let config: McpConfig = toml::from_str(toml_content).unwrap();
+        let hosted = &config.mcp_servers["hosted"];
+        assert_eq!(hosted.transport, Some(HttpTransport::Sse));
+        assert!(hosted.validate().is_ok());
+        assert!(config.mcp_servers["local"].validate().is_err());
+    }
+
     #[test]
     fn readonly_defaults_to_disabled() {
         let toml_content = r#"
@@ -60,6 +60,20 @@ pub struct McpManager {
     tools_by_server: Arc<HashMap<String, Vec<McpTool>>>,
     tool_to_server: Arc<HashMap<String, String>>,
     readonly_by_server: Arc<HashMap<String, ReadOnlyAccess>>,
+    /// Configured servers that never came up, with the reason, so
+    /// `/tools` can report them after the startup warning scrolled away.
+    failed_servers: Arc<Vec<(String, String)>>,
+}
+
+/// Health of one configured MCP server, as shown by `/tools`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum McpServerStatus {
+    Connected {
+        tools: usize,
+    },
+    /// Connected at startup, then stopped after a request timed out.
+    Stopped,
+    Failed(String),
 }
 
 /// Reject server and tool names that contain the prefix separator or
@@ -121,14 +135,16 @@ impl McpManager {
         let mut tool_to_server: HashMap<String, String> = HashMap::new();
         let mut readonly_by_server: HashMap<String, ReadOnlyAccess> = HashMap::new();
         let mut bullets = String::new();
+        let mut failed_servers: Vec<(String, String)> = Vec::new();
 
         for (server_name, config) in server_configs {
             if let Err(e) = validate_mcp_name("server", &server_name) {
                 tracing::warn!(server = %server_name, error = %e, "skipping MCP server");
+                failed_servers.push((server_name, e.to_string()));
                 continue;
             }
             let server_readonly = config.readonly;
-            match McpClient::connect(server_name.clone(), config).await {
+            match McpClient::connect(server_name.clone(), config, &workspace).await {
                 Ok(client) => match client.list_tools().await {
                     Ok(tools) => {
                         let mut accepted: Vec<McpTool> = Vec::with_capacity(tools.len());
@@ -172,6 +188,7 @@ impl McpManager {
                             error = %e,
                             "failed to list tools from MCP server"
                         );
+                        failed_servers.push((server_name, e.to_string()));
                     }
                 },
                 Err(e) => {
@@ -180,6 +197,7 @@ impl McpManager {
                         error = %e,
                         "failed to connect to MCP server"
                     );
+                    failed_servers.push((server_name, e.to_string()));
                 }
             }
         }
@@ -189,6 +207,7 @@ impl McpManager {
             tools_by_server: Arc::new(tools_by_server),
             tool_to_server: Arc::new(tool_to_server),
             readonly_by_server: Arc::new(readonly_by_server),
+            failed_servers: Arc::new(failed_servers),
         };
         let init_block = if bullets.is_empty() {
             String::new()
@@ -214,6 +233,31 @@ impl McpManager {
         names
     }
 
+    /// Every configured server with its current health, sorted by name.
+    pub async fn server_statuses(&self) -> Vec<(String, McpServerStatus)> {
+        let clients = self.clients.lock().await;
+        let mut statuses: Vec<(String, McpServerStatus)> = clients
+            .iter()
+            .map(|(name, client)| {
+                let status = if client.is_stopped() {
+                    McpServerStatus::Stopped
+                } else {
+                    McpServerStatus::Connected {
+                        tools: self.tools_by_server.get(name).map_or(0, Vec::len),
+                    }
+                };
+                (name.clone(), status)
+            })
+            .collect();
+        statuses.extend(
+            self.failed_servers
+                .iter()
+                .map(|(name, reason)| (name.clone(), McpServerStatus::Failed(reason.clone()))),
+        );
+        statuses.sort_by(|a, b| a.0.cmp(&b.0));
+        statuses
+    }
+
     pub fn is_server_available_in_readonly(&self, server: &str) -> bool {
         self.readonly_by_server
             .get(server)
@@ -351,6 +395,7 @@ impl Clone for McpManager {
             tools_by_server: Arc::clone(&self.tools_by_server),
             tool_to_server: Arc::clone(&self.tool_to_server),
             readonly_by_server: Arc::clone(&self.readonly_by_server),
+            failed_servers: Arc::clone(&self.failed_servers),
         }
     }
 }
@@ -368,6 +413,33 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn servers_that_failed_at_startup_are_reported() {
+        let manager = McpManager {
+            clients: Arc::new(Mutex::new(HashMap::new())),
+            tools_by_server: Arc::new(HashMap::new()),
+            tool_to_server: Arc::new(HashMap::new()),
+            readonly_by_server: Arc::new(HashMap::new()),
+            failed_servers: Arc::new(vec![
+                ("zeta".to_string(), "timed out".to_string()),
+                ("alpha".to_string(), "not found".to_string()),
+            ]),
+        };
+        assert_eq!(
+            manager.server_statuses().await,
+            vec![
+                (
+                    "alpha".to_string(),
+                    McpServerStatus::Failed("not found".to_string())
+                ),
+                (
+                    "zeta".to_string(),
+                    McpServerStatus::Failed("timed out".to_string())
+                ),
+            ]
+        );
+    }
+
     #[test]
     fn validate_rejects_reserved_separator_in_names() {
         assert!(validate_mcp_name("server", "good").is_ok());
@@ -1,21 +1,22 @@
 use crate::error::{Result, SofosError};
 use crate::mcp::client::{
-    MCP_INIT_TIMEOUT, MCP_REQUEST_TIMEOUT, create_call_tool_request, create_init_request,
-    parse_call_tool_response, parse_list_tools_response,
+    MCP_INIT_TIMEOUT, create_call_tool_request, create_init_request, parse_call_tool_response,
+    parse_list_tools_response,
 };
-use crate::mcp::config::McpServerConfig;
+use crate::mcp::config::{HttpTransport, McpServerConfig};
 use crate::mcp::protocol::*;
 use futures::StreamExt;
 use serde_json::Value;
 use std::collections::HashMap;
-use std::sync::Arc;
 use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
 /// Bound on the TCP/TLS connect phase for the HTTP MCP transport.
 /// Without this, a network outage waits the full request timeout
-/// (`MCP_REQUEST_TIMEOUT`, currently 120 s) before failing — confusing
-/// when the user just wants a quick "server unreachable" signal.
+/// (`MCP_REQUEST_TIMEOUT` unless the server sets `timeout_secs`)
+/// before failing — confusing when the user just wants a quick
+/// "server unreachable" signal.
 const HTTP_CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
 
 /// Cap on a single MCP HTTP response body. A hostile or buggy server
@@ -23,25 +24,80 @@ const HTTP_CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
 /// OOM the host long before the request timeout fires.
 const MCP_HTTP_BODY_CAP: usize = 32 * 1024 * 1024;
 
+/// Header the server uses to hand out a session id on `initialize`;
+/// every later request must echo it back.
+const SESSION_ID_HEADER: &str = "mcp-session-id";
+
+const EVENT_STREAM_MIME: &str = "text/event-stream";
+
 pub struct HttpClient {
     server_name: String,
     url: String,
     headers: HashMap<String, String>,
     client: reqwest::Client,
     next_id: Arc<AtomicU64>,
+    transport: Option<HttpTransport>,
+    session_id: Mutex<Option<String>>,
+}
+
+/// Incremental `text/event-stream` decoder. Bytes are buffered until a
+/// line ends, so an event split across chunks (or a codepoint split
+/// across them) reassembles intact. Only `data` fields matter here;
+/// `event`, `id`, `retry` and comment lines are ignored.
+#[derive(Default)]
+struct SseDecoder {
+    buffer: Vec<u8>,
+    data: Option<String>,
+}
+
+impl SseDecoder {
+    /// Feed a chunk and return the `data` payload of every event it
+    /// completes.
+    fn push(&mut self, chunk: &[u8]) -> Vec<String> {
+        self.buffer.extend_from_slice(chunk);
+        let mut events = Vec::new();
+        while let Some(pos) = self.buffer.iter().position(|b| *b == b'\n') {
+            let line = String::from_utf8_lossy(&self.buffer[..pos]).into_owned();
+            self.buffer.drain(..=pos);
+            let line = line.strip_suffix('\r').unwrap_or(&line);
+            if line.is_empty() {
+                events.extend(self.data.take());
+                continue;
+            }
+            let Some(value) = line.strip_prefix("data:") else {
+                continue;
+            };
+            let value = value.strip_prefix(' ').unwrap_or(value);
+            match self.data.as_mut() {
+                Some(data) => {
+                    data.push('\n');
+                    data.push_str(value);
+                }
+                None => self.data = Some(value.to_string()),
+            }
+        }
+        events
+    }
+
+    /// Bytes held for an event that hasn't finished yet.
+    fn pending_len(&self) -> usize {
+        self.buffer.len() + self.data.as_ref().map_or(0, String::len)
+    }
 }
 
 impl HttpClient {
     pub async fn new(server_name: String, config: McpServerConfig) -> Result<Self> {
+        let request_timeout = config.request_timeout();
         let url = config
             .url
             .ok_or_else(|| SofosError::McpError("Missing URL for HTTP server".to_string()))?;
 
         let headers = config.headers.unwrap_or_default();
+        let transport = config.transport;
 
         // A bare `reqwest::Client::new()` uses no request timeout at
         // all, so a slow remote MCP server could stall a turn forever.
-        // Set the shared MCP ceiling at client-construction time so
+        // Set the server's MCP ceiling at client-construction time so
         // every call-site inherits it without extra threading. The
         // connect timeout is shorter than the overall ceiling so an
         // unreachable host fails fast instead of holding the full
@@ -53,7 +109,7 @@ impl HttpClient {
         // bearer token to whatever host the server pointed at. A 3xx
         // here surfaces as an explicit error instead.
         let client = reqwest::Client::builder()
-            .timeout(MCP_REQUEST_TIMEOUT)
+            .timeout(request_timeout)
             .connect_timeout(HTTP_CONNECT_TIMEOUT)
             .redirect(reqwest::redirect::Policy::none())
             .build()
@@ -65,6 +121,8 @@ impl HttpClient {
             headers,
             client,
             next_id: Arc::new(AtomicU64::new(1)),
+            transport,
+            session_id: Mutex::new(None),
         };
 
         http_client.initialize().await?;
@@ -104,13 +162,7 @@ impl HttpClient {
             "method": "notifications/initialized",
         });
 
-        let mut req = self.client.post(&self.url).json(&notification);
-
-        for (key, value) in &self.headers {
-            req = req.header(key, value);
-        }
-
-        req.send().await.map_err(|e| {
+        self.post(&notification).send().await.map_err(|e| {
             SofosError::McpError(format!(
                 "Failed to send `notifications/initialized` to MCP server '{}': {}",
                 self.server_name, e
@@ -120,17 +172,47 @@ impl HttpClient {
         Ok(())
     }
 
-    async fn send_request(&self, method: &str, params: Option<Value>) -> Result<Value> {
-        let id = self.next_id.fetch_add(1, Ordering::SeqCst);
-        let request = JsonRpcRequest::new(id, method.to_string(), params);
-
-        let mut req = self.client.post(&self.url).json(&request);
+    /// POST a JSON-RPC message with the configured headers, the
+    /// `Accept` list the streamable HTTP transport requires, and the
+    /// session id once the server has issued one.
+    fn post(&self, body: &impl serde::Serialize) -> reqwest::RequestBuilder {
+        let mut req = self
+            .client
+            .post(&self.url)
+            .header(
+                reqwest::header::ACCEPT,
+                format!("application/json, {}", EVENT_STREAM_MIME),
+            )
+            .json(body);
 
         for (key, value) in &self.headers {
             req = req.header(key, value);
         }
+        if let Some(session_id) = self.session_id.lock().ok().and_then(|id| id.clone()) {
+            req = req.header(SESSION_ID_HEADER, session_id);
+        }
+        req
+    }
 
-        let response = req.send().await.map_err(|e| {
+    /// Whether to read `response` as an event stream: the configured
+    /// hint wins, otherwise the `Content-Type` decides.
+    fn is_event_stream(&self, response: &reqwest::Response) -> bool {
+        match self.transport {
+            Some(HttpTransport::Sse) => true,
+            Some(HttpTransport::Http) => false,
+            None => response
+                .headers()
+                .get(reqwest::header::CONTENT_TYPE)
+                .and_then(|value| value.to_str().ok())
+                .is_some_and(|value| value.starts_with(EVENT_STREAM_MIME)),
+        }
+    }
+
+    async fn send_request(&self, method: &str, params: Option<Value>) -> Result<Value> {
+        let id = self.next_id.fetch_add(1, Ordering::SeqCst);
+        let request = JsonRpcRequest::new(id, method.to_string(), params);
+
+        let response = self.post(&request).send().await.map_err(|e| {
             SofosError::McpError(format!(
                 "Failed to send request to MCP server '{}': {}",
                 self.server_name, e
@@ -157,31 +239,22 @@ impl HttpClient {
             }
         }
 
-        let mut buffered: Vec<u8> = Vec::with_capacity(8 * 1024);
-        let mut stream = response.bytes_stream();
-        while let Some(chunk) = stream.next().await {
-            let chunk = chunk.map_err(|e| {
-                SofosError::McpError(format!(
-                    "Failed to read response body from MCP server '{}': {}",
-                    self.server_name, e
-                ))
-            })?;
-            if buffered.len().saturating_add(chunk.len()) > MCP_HTTP_BODY_CAP {
-                return Err(SofosError::McpError(format!(
-                    "MCP server '{}' exceeded the {} MB response cap mid-stream",
-                    self.server_name,
-                    MCP_HTTP_BODY_CAP / (1024 * 1024)
-                )));
+        let issued_session = response
+            .headers()
+            .get(SESSION_ID_HEADER)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string);
+        if let Some(issued) = issued_session {
+            if let Ok(mut session_id) = self.session_id.lock() {
+                *session_id = Some(issued);
             }
-            buffered.extend_from_slice(&chunk);
         }
 
-        let raw: Value = serde_json::from_slice(&buffered).map_err(|e| {
-            SofosError::McpError(format!(
-                "Failed to parse response from MCP server '{}': {}",
-                self.server_name, e
-            ))
-        })?;
+        let raw = if self.is_event_stream(&response) {
+            self.read_event_stream(response).await?
+        } else {
+            self.read_json_body(response).await?
+        };
         if raw.get("method").is_some() {
             return Err(SofosError::McpError(format!(
                 "MCP server '{}' sent a server-initiated message; sofos does not \
@@ -218,6 +291,79 @@ impl HttpClient {
         })
     }
 
+    async fn read_json_body(&self, response: reqwest::Response) -> Result<Value> {
+        let mut buffered: Vec<u8> = Vec::with_capacity(8 * 1024);
+        let mut stream = response.bytes_stream();
+        while let Some(chunk) = stream.next().await {
+            let chunk = self.body_chunk(chunk)?;
+            if buffered.len().saturating_add(chunk.len()) > MCP_HTTP_BODY_CAP {
+                return Err(self.body_cap_error());
+            }
+            buffered.extend_from_slice(&chunk);
+        }
+
+        serde_json::from_slice(&buffered).map_err(|e| {
+            SofosError::McpError(format!(
+                "Failed to parse response from MCP server '{}': {}",
+                self.server_name, e
+            ))
+        })
+    }
+
+    /// Read events until one carries a JSON-RPC response. Server
+    /// notifications sent ahead of it (progress, logging) are skipped,
+    /// and the stream is dropped as soon as the response arrives since
+    /// servers may hold it open afterwards.
+    async fn read_event_stream(&self, response: reqwest::Response) -> Result<Value> {
+        let mut decoder = SseDecoder::default();
+        let mut stream = response.bytes_stream();
+        while let Some(chunk) = stream.next().await {
+            let chunk = self.body_chunk(chunk)?;
+            for data in decoder.push(&chunk) {
+                let event: Value = match serde_json::from_str(&data) {
+                    Ok(event) => event,
+                    Err(e) => {
+                        tracing::debug!(
+                            server = %self.server_name,
+                            error = %e,
+                            "skipping non-JSON MCP event"
+                        );
+                        continue;
+                    }
+                };
+                if event.get("method").is_some() && event.get("id").is_none() {
+                    continue;
+                }
+                return Ok(event);
+            }
+            if decoder.pending_len() > MCP_HTTP_BODY_CAP {
+                return Err(self.body_cap_error());
+            }
+        }
+
+        Err(SofosError::McpError(format!(
+            "Event stream from MCP server '{}' ended without a response",
+            self.server_name
+        )))
+    }
+
+    fn body_chunk<B>(&self, chunk: reqwest::Result<B>) -> Result<B> {
+        chunk.map_err(|e| {
+            SofosError::McpError(format!(
+                "Failed to read response body from MCP server '{}': {}",
+                self.server_name, e
+            ))
+        })
+    }
+
+    fn body_cap_error(&self) -> SofosError {
+        SofosError::McpError(format!(
+            "MCP server '{}' exceeded the {} MB response cap mid-stream",
+            self.server_name,
+            MCP_HTTP_BODY_CAP / (1024 * 1024)
+        ))
+    }
+
     pub async fn list_tools(&self) -> Result<Vec<McpTool>> {
         let result = self.send_request("tools/list", None).await?;
         parse_list_tools_response(result)
@@ -235,3 +381,137 @@ impl HttpClient {
         parse_call_tool_response(result)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    #[test]
+    fn decoder_joins_data_lines_and_survives_split_chunks() {
+        let mut decoder = SseDecoder::default();
+        assert!(decoder.push(b"event: message\r\ndata: {\"a\":").is_empty());
+        assert!(decoder.push(b"1}\r\n: keep-alive\r\n").is_empty());
+        assert_eq!(decoder.push(b"\r\n"), ["{\"a\":1}"]);
+
+        let events = decoder.push(b"data: first\ndata: second\n\ndata:x\n\n");
+        assert_eq!(events, ["first\nsecond", "x"]);
+        assert_eq!(decoder.pending_len(), 0);
+    }
+
+    /// Read one HTTP/1.1 request and return its head and body.
+    async fn read_request(socket: &mut tokio::net::TcpStream) -> (String, String) {
+        let mut raw = Vec::new();
+        let mut buf = [0u8; 4096];
+        loop {
+            let n = socket.read(&mut buf).await.unwrap();
+            raw.extend_from_slice(&buf[..n]);
+            let text = String::from_utf8_lossy(&raw).into_owned();
+            if let Some((head, body)) = text.split_once("\r\n\r\n") {
+                let length = head
+                    .lines()
+                    .find_map(|line| {
+                        line.to_lowercase()
+                            .strip_prefix("content-length:")
+                            .map(|v| v.trim().parse::<usize>().unwrap())
+                    })
+                    .unwrap_or(0);
+                if body.len() >= length || n == 0 {
+                    return (head.to_lowercase(), body.to_string());
+                }
+            }
+        }
+    }
+
+    /// Minimal streamable HTTP MCP server: answers every request over
+    /// SSE, issues a session id on `initialize`, and sends a progress
+    /// notification ahead of the `tools/list` result. Returns the
+    /// address and the request heads it saw.
+    async fn spawn_sse_server() -> (String, Arc<Mutex<Vec<String>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let heads = Arc::new(Mutex::new(Vec::new()));
+        let seen = Arc::clone(&heads);
+        tokio::spawn(async move {
+            loop {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let (head, body) = read_request(&mut socket).await;
+                seen.lock().unwrap().push(head);
+                let request: Value = serde_json::from_str(&body).unwrap();
+                let events = match request["method"].as_str().unwrap() {
+                    "initialize" => vec![serde_json::json!({
+                        "jsonrpc": "2.0", "id": request["id"],
+                        "result": {
+                            "protocolVersion": "2024-11-05",
+                            "capabilities": {},
+                            "serverInfo": {"name": "fake", "version": "0"}
+                        }
+                    })],
+                    "tools/list" => vec![
+                        serde_json::json!({
+                            "jsonrpc": "2.0", "method": "notifications/progress",
+                            "params": {"progress": 1}
+                        }),
+                        serde_json::json!({
+                            "jsonrpc": "2.0", "id": request["id"],
+                            "result": {"tools": [
+                                {"name": "ping", "description": "Ping", "inputSchema": {}}
+                            ]}
+                        }),
+                    ],
+                    _ => Vec::new(),
+                };
+                let response = if events.is_empty() {
+                    "HTTP/1.1 202 Accepted\r\ncontent-length: 0\r\nconnection: close\r\n\r\n"
+                        .to_string()
+                } else {
+                    let body: String = events
+                        .iter()
+                        .map(|event| format!("event: message\ndata: {}\n\n", event))
+                        .collect();
+                    format!(
+                        "HTTP/1.1 200 OK\r\ncontent-type: text/event-stream\r\n\
+                         mcp-session-id: abc123\r\nconnection: close\r\n\r\n{}",
+                        body
+                    )
+                };
+                socket.write_all(response.as_bytes()).await.unwrap();
+                socket.shutdown().await.unwrap();
+            }
+        });
+        (format!("http://{}/mcp", addr), heads)
+    }
+
+    #[tokio::test]
+    async fn event_stream_responses_are_parsed_and_the_session_is_kept() {
+        let (url, heads) = spawn_sse_server().await;
+        let config = McpServerConfig {
+            command: None,
+            args: None,
+            env: None,
+            url: Some(url),
+            headers: None,
+            readonly: crate::mcp::config::ReadOnlyAccess::default(),
+            timeout_secs: Some(5),
+            transport: None,
+        };
+
+        let client = HttpClient::new("fake".to_string(), config)
+            .await
+            .expect("handshake over SSE succeeds");
+        let tools = client.list_tools().await.expect("tools listed");
+        assert_eq!(tools.len(), 1);
+        assert_eq!(tools[0].name, "ping");
+
+        let heads = heads.lock().unwrap();
+        assert!(heads[0].contains("accept: application/json, text/event-stream"));
+        assert!(!heads[0].contains("mcp-session-id"));
+        assert!(
+            heads[1..]
+                .iter()
+                .all(|head| head.contains("mcp-session-id: abc123")),
+            "{heads:?}"
+        );
+    }
+}
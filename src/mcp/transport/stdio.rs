@@ -1,21 +1,23 @@
 use crate::error::{Result, SofosError};
 use crate::mcp::client::{
-    MCP_INIT_TIMEOUT, MCP_REQUEST_TIMEOUT, create_call_tool_request, create_init_request,
-    parse_call_tool_response, parse_list_tools_response,
+    MCP_INIT_TIMEOUT, create_call_tool_request, create_init_request, parse_call_tool_response,
+    parse_list_tools_response,
 };
 use crate::mcp::config::McpServerConfig;
 use crate::mcp::protocol::*;
 use serde_json::Value;
 use std::collections::HashMap;
 use std::io::{BufRead, BufReader, Write};
+use std::path::Path;
 use std::process::{Child, ChildStderr, ChildStdin, ChildStdout, Command, Stdio};
-use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
 /// Cross-platform env keys forwarded to MCP stdio children after
 /// [`Command::env_clear`]. Everything outside this allowlist (including
-/// `ANTHROPIC_API_KEY`, `OPENAI_API_KEY`, `MORPH_API_KEY`, ssh agent
+/// `ANTHROPIC_API_KEY`, `OPENAI_API_KEY`, `GEMINI_API_KEY`,
+/// `MORPH_API_KEY`, ssh agent
 /// sockets, AWS credentials, etc.) is dropped unless the user opts in
 /// through the server's `env` config field.
 const FORWARDED_ENV_KEYS: &[&str] = &[
@@ -59,16 +61,20 @@ const STDIO_DROP_POLL_INTERVAL: Duration = Duration::from_millis(10);
 /// Clears the parent env, forwards a small platform-specific allowlist
 /// (locale, paths, Windows essentials), then applies the user's
 /// configured `env` entries on top. This is what keeps
-/// `ANTHROPIC_API_KEY` / `OPENAI_API_KEY` / `MORPH_API_KEY` and
+/// `ANTHROPIC_API_KEY` / `OPENAI_API_KEY` / `GEMINI_API_KEY` /
+/// `MORPH_API_KEY` and
 /// arbitrary other secrets out of every MCP child unless the user
-/// explicitly forwards them.
+/// explicitly forwards them. The child runs in the workspace, so a
+/// server given relative paths reads the project sofos was pointed at.
 fn spawn_stdio_child(
     command: &str,
     args: &[String],
     env_vars: &HashMap<String, String>,
+    workspace: &Path,
 ) -> std::io::Result<Child> {
     let mut cmd = Command::new(command);
     cmd.args(args)
+        .current_dir(workspace)
         .stdin(Stdio::piped())
         .stdout(Stdio::piped())
         .stderr(Stdio::piped());
@@ -238,32 +244,10 @@ fn stdio_request_blocking(
         .lock()
         .map_err(|e| SofosError::McpError(format!("Failed to lock stdout: {}", e)))?;
 
-    let mut response_line = String::new();
-    let bytes_read = stdout_guard.read_line(&mut response_line).map_err(|e| {
-        SofosError::McpError(format!(
-            "Failed to read from MCP server '{}': {}",
-            server_name, e
-        ))
-    })?;
-    // Zero bytes from `read_line` means the server closed stdout
-    // cleanly — typically a crash or exit between requests.
-    // Surface that plainly so the user isn't chasing a bogus
-    // "parse error" message for what's really a dead server.
-    if bytes_read == 0 {
-        return Err(SofosError::McpError(format!(
-            "MCP server '{}' closed stdout unexpectedly (server crashed or exited?)",
-            server_name
-        )));
-    }
+    let raw = read_json_line(server_name, &mut *stdout_guard)?;
 
-    // Parse as Value first; a `method` field means a server-initiated
-    // request, which sofos doesn't implement — reject explicitly.
-    let raw: Value = serde_json::from_str(&response_line).map_err(|e| {
-        SofosError::McpError(format!(
-            "Failed to parse response from MCP server '{}': {}",
-            server_name, e
-        ))
-    })?;
+    // A `method` field means a server-initiated request, which sofos
+    // doesn't implement — reject explicitly.
     if raw.get("method").is_some() {
         return Err(SofosError::McpError(format!(
             "MCP server '{}' sent a server-initiated message while a response was expected; \
@@ -291,6 +275,39 @@ fn stdio_request_blocking(
     Ok(response)
 }
 
+/// Read stdout until a line parses as JSON. Some servers print banners
+/// or log lines to stdout despite the spec; those are skipped rather
+/// than failing the request. The caller's timeout bounds the loop.
+fn read_json_line(server_name: &str, stdout: &mut impl BufRead) -> Result<Value> {
+    loop {
+        let mut line = String::new();
+        let bytes_read = stdout.read_line(&mut line).map_err(|e| {
+            SofosError::McpError(format!(
+                "Failed to read from MCP server '{}': {}",
+                server_name, e
+            ))
+        })?;
+        // Zero bytes from `read_line` means the server closed stdout
+        // cleanly — typically a crash or exit between requests.
+        // Surface that plainly so the user isn't chasing a bogus
+        // "parse error" message for what's really a dead server.
+        if bytes_read == 0 {
+            return Err(SofosError::McpError(format!(
+                "MCP server '{}' closed stdout unexpectedly (server crashed or exited?)",
+                server_name
+            )));
+        }
+        match serde_json::from_str(&line) {
+            Ok(value) => return Ok(value),
+            Err(_) => tracing::debug!(
+                server = %server_name,
+                line = %strip_ansi_escapes(line.trim_end()),
+                "skipping non-JSON line on MCP stdout"
+            ),
+        }
+    }
+}
+
 pub struct StdioClient {
     server_name: String,
     process: Arc<Mutex<Child>>,
@@ -300,6 +317,10 @@ pub struct StdioClient {
     /// for why write+read must stay coupled.
     request_lock: Arc<Mutex<()>>,
     next_id: Arc<AtomicU64>,
+    request_timeout: Duration,
+    /// Set once a call times out and the child is killed, so later
+    /// calls fail fast instead of writing to a dead pipe.
+    stopped: Arc<AtomicBool>,
 }
 
 /// Kill and reap with a bounded `try_wait` loop. Shared by `Drop`
@@ -335,7 +356,12 @@ impl Drop for StdioClient {
 }
 
 impl StdioClient {
-    pub async fn new(server_name: String, config: McpServerConfig) -> Result<Self> {
+    pub async fn new(
+        server_name: String,
+        config: McpServerConfig,
+        workspace: &Path,
+    ) -> Result<Self> {
+        let request_timeout = config.request_timeout();
         let command = config
             .command
             .ok_or_else(|| SofosError::McpError("Missing command for stdio server".to_string()))?;
@@ -346,8 +372,9 @@ impl StdioClient {
         // Spawn off the executor — `Command::spawn` is synchronous and
         // can pause tokio noticeably on slow filesystems.
         let spawn_name = server_name.clone();
+        let workspace = workspace.to_path_buf();
         let process = tokio::task::spawn_blocking(move || -> std::io::Result<Child> {
-            spawn_stdio_child(&command, &args, &env_vars)
+            spawn_stdio_child(&command, &args, &env_vars, &workspace)
         })
         .await
         .map_err(|e| {
@@ -379,6 +406,8 @@ impl StdioClient {
             stdout: Arc::new(Mutex::new(BufReader::new(stdout))),
             request_lock: Arc::new(Mutex::new(())),
             next_id: Arc::new(AtomicU64::new(1)),
+            request_timeout,
+            stopped: Arc::new(AtomicBool::new(false)),
         };
 
         client.initialize().await?;
@@ -386,16 +415,23 @@ impl StdioClient {
         Ok(client)
     }
 
-    /// Run a blocking closure with the shared MCP timeout ceiling. On
+    /// Run a blocking closure with the server's MCP timeout ceiling. On
     /// timeout the child is killed off-thread so the async caller
-    /// doesn't pause the executor waiting for the OS to reap it. Used
-    /// by both `send_request` and `send_notification` so they share
-    /// the same lock/panic/timeout error vocabulary.
+    /// doesn't pause the executor waiting for the OS to reap it, and
+    /// the client is marked stopped. Used by both `send_request` and
+    /// `send_notification` so they share the same lock/panic/timeout
+    /// error vocabulary.
     async fn run_with_timeout<T, F>(&self, label: &str, timeout: Duration, blocking: F) -> Result<T>
     where
         F: FnOnce() -> Result<T> + Send + 'static,
         T: Send + 'static,
     {
+        if self.stopped.load(Ordering::SeqCst) {
+            return Err(SofosError::McpError(format!(
+                "MCP server '{}' was stopped after an earlier timeout; restart sofos to reconnect",
+                self.server_name
+            )));
+        }
         let task = tokio::task::spawn_blocking(blocking);
         match tokio::time::timeout(timeout, task).await {
             Ok(Ok(Ok(value))) => Ok(value),
@@ -405,6 +441,7 @@ impl StdioClient {
                 self.server_name, label, join_err
             ))),
             Err(_) => {
+                self.stopped.store(true, Ordering::SeqCst);
                 self.kill_child_detached();
                 Err(SofosError::McpError(format!(
                     "MCP server '{}' {} timed out after {}s",
@@ -437,6 +474,11 @@ impl StdioClient {
         });
     }
 
+    /// Whether an earlier call timed out and the child was killed.
+    pub fn is_stopped(&self) -> bool {
+        self.stopped.load(Ordering::SeqCst)
+    }
+
     async fn initialize(&self) -> Result<()> {
         // The handshake uses a tighter ceiling than tool calls so a
         // frozen server can't hold session startup hostage for two
@@ -458,7 +500,7 @@ impl StdioClient {
     }
 
     async fn send_request(&self, method: &str, params: Option<Value>) -> Result<Value> {
-        self.send_request_with_timeout(method, params, MCP_REQUEST_TIMEOUT)
+        self.send_request_with_timeout(method, params, self.request_timeout)
             .await
     }
 
@@ -516,7 +558,7 @@ impl StdioClient {
         // wedged its read side. Same timeout path as `send_request`.
         let server_name = self.server_name.clone();
         let stdin = Arc::clone(&self.stdin);
-        self.run_with_timeout("notification", MCP_REQUEST_TIMEOUT, move || {
+        self.run_with_timeout("notification", self.request_timeout, move || {
             stdio_write_blocking(&server_name, &stdin, &notification_json)
         })
         .await
@@ -576,6 +618,78 @@ mod tests {
         assert!(!Id::String("eight".to_string()).matches_outgoing(7));
     }
 
+    /// Spawn `sh -c script` as a stdio MCP server with a short timeout.
+    #[cfg(unix)]
+    async fn fake_server(script: &str, timeout_secs: u64) -> Result<StdioClient> {
+        fake_server_in(script, timeout_secs, &std::env::temp_dir()).await
+    }
+
+    #[cfg(unix)]
+    async fn fake_server_in(
+        script: &str,
+        timeout_secs: u64,
+        workspace: &Path,
+    ) -> Result<StdioClient> {
+        let config = McpServerConfig {
+            command: Some("sh".to_string()),
+            args: Some(vec!["-c".to_string(), script.to_string()]),
+            env: None,
+            url: None,
+            headers: None,
+            readonly: crate::mcp::config::ReadOnlyAccess::default(),
+            timeout_secs: Some(timeout_secs),
+            transport: None,
+        };
+        StdioClient::new("fake".to_string(), config, workspace).await
+    }
+
+    #[cfg(unix)]
+    const FAKE_INIT: &str = r#"read line; echo 'booting fake server'; echo '{"jsonrpc":"2.0","id":1,"result":{"protocolVersion":"2024-11-05","capabilities":{},"serverInfo":{"name":"fake","version":"0"}}}'; read line; "#;
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn log_lines_on_stdout_are_skipped() {
+        let script = format!(
+            r#"{FAKE_INIT}read line; echo 'still here'; echo '{{"jsonrpc":"2.0","id":2,"result":{{"tools":[{{"name":"ping","description":"Ping","inputSchema":{{}}}}]}}}}'"#
+        );
+        let client = fake_server(&script, 5).await.expect("handshake succeeds");
+        let tools = client.list_tools().await.expect("tools listed");
+        assert_eq!(tools.len(), 1);
+        assert_eq!(tools[0].name, "ping");
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn server_starts_in_the_workspace() {
+        let workspace = tempfile::TempDir::new().unwrap();
+        let script = format!("touch started; {FAKE_INIT}");
+        fake_server_in(&script, 5, workspace.path())
+            .await
+            .expect("handshake succeeds");
+        assert!(workspace.path().join("started").exists());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn silent_server_times_out_and_stays_stopped() {
+        let script = format!("{FAKE_INIT}read line; sleep 30");
+        let client = fake_server(&script, 1).await.expect("handshake succeeds");
+
+        let err = client
+            .list_tools()
+            .await
+            .expect_err("request must time out");
+        assert!(
+            matches!(&err, SofosError::McpError(msg) if msg.contains("timed out after 1s")),
+            "{err:?}"
+        );
+        let err = client.list_tools().await.expect_err("stopped client fails");
+        assert!(
+            matches!(&err, SofosError::McpError(msg) if msg.contains("stopped")),
+            "{err:?}"
+        );
+    }
+
     /// Built MCP children must not inherit the parent env. We can't
     /// spawn here without a real binary, but we can verify the helper
     /// shape: pulling `spawn_stdio_child` would create a Command with
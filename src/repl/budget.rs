@@ -0,0 +1,169 @@
+//! Spend limits from `--max-cost` and `--max-total-tokens`, checked
+//! against the session's running usage before every provider request.
+
+use crate::config::BudgetAction;
+use crate::session::SessionState;
+use crate::tools::utils::{ConfirmationType, confirm_multi_choice};
+use crate::ui::UI;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+const CHOICE_STOP: &str = "Stop";
+const CHOICE_CONTINUE: &str = "Continue without the budget";
+
+/// Session usage as tracked in [`SessionState`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SpendTotals {
+    pub input: u32,
+    pub output: u32,
+    pub cache_read: u32,
+    pub cache_creation: u32,
+    pub peak_single_turn_input: u32,
+}
+
+impl From<&SessionState> for SpendTotals {
+    fn from(state: &SessionState) -> Self {
+        Self {
+            input: state.total_input_tokens,
+            output: state.total_output_tokens,
+            cache_read: state.total_cache_read_tokens,
+            cache_creation: state.total_cache_creation_tokens,
+            peak_single_turn_input: state.peak_single_turn_input_tokens,
+        }
+    }
+}
+
+impl SpendTotals {
+    /// Estimated dollars spent, or `None` when no price is known for `model`.
+    pub(super) fn cost_usd(&self, model: &str) -> Option<f64> {
+        UI::calculate_cost(
+            model,
+            self.input,
+            self.output,
+            self.cache_read,
+            self.cache_creation,
+            self.peak_single_turn_input,
+        )
+    }
+
+    /// Every token the model read or wrote, cached or not.
+    fn total_tokens(&self, model: &str) -> u64 {
+        u64::from(UI::total_input_seen_by_model(
+            model,
+            self.input,
+            self.cache_read,
+        )) + u64::from(self.cache_creation)
+            + u64::from(self.output)
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Budget {
+    max_cost_usd: Option<f64>,
+    max_total_tokens: Option<u64>,
+    action: BudgetAction,
+    /// Set once the user chooses to carry on past the limit. Shared with
+    /// the per-turn clones so the choice lasts for the session.
+    waived: Arc<AtomicBool>,
+}
+
+impl Budget {
+    pub fn new(
+        max_cost_usd: Option<f64>,
+        max_total_tokens: Option<u64>,
+        action: BudgetAction,
+    ) -> Self {
+        Self {
+            max_cost_usd,
+            max_total_tokens,
+            action,
+            waived: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// The limit already reached, described for the user.
+    fn exceeded(&self, model: &str, totals: &SpendTotals) -> Option<String> {
+        // Without a known price the cost limit cannot be checked; startup
+        // warns about that when `--max-cost` is given.
+        if let (Some(max), Some(cost)) = (self.max_cost_usd, totals.cost_usd(model)) {
+            if cost >= max {
+                return Some(format!("${:.2} spent of the ${:.2} cost budget", cost, max));
+            }
+        }
+        if let Some(max) = self.max_total_tokens {
+            let tokens = totals.total_tokens(model);
+            if tokens >= max {
+                return Some(format!(
+                    "{} tokens used of the {} token budget",
+                    tokens, max
+                ));
+            }
+        }
+        None
+    }
+
+    /// Whether another request may be sent. When a limit is reached this
+    /// prints why, and under [`BudgetAction::Ask`] lets the user carry on
+    /// for the rest of the session.
+    pub fn allows_request(&self, model: &str, totals: &SpendTotals) -> bool {
+        if self.waived.load(Ordering::Relaxed) {
+            return true;
+        }
+        let Some(reason) = self.exceeded(model, totals) else {
+            return true;
+        };
+        if self.action == BudgetAction::Ask {
+            let choice = confirm_multi_choice(
+                &format!("Budget reached: {}. Keep going?", reason),
+                &[CHOICE_STOP, CHOICE_CONTINUE],
+                0,
+                ConfirmationType::Info,
+            );
+            if matches!(choice, Ok(1)) {
+                self.waived.store(true, Ordering::Relaxed);
+                return true;
+            }
+        }
+        UI::print_warning(&format!(
+            "Budget reached: {}. Stopping before the next request.",
+            reason
+        ));
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MODEL: &str = crate::api::model_info::CLAUDE_SONNET;
+
+    #[test]
+    fn token_budget_stops_once_reached() {
+        let budget = Budget::new(None, Some(1_000), BudgetAction::Stop);
+        let mut totals = SpendTotals {
+            input: 400,
+            output: 100,
+            ..SpendTotals::default()
+        };
+        assert!(budget.allows_request(MODEL, &totals));
+
+        totals.cache_read = 500;
+        assert!(!budget.allows_request(MODEL, &totals));
+    }
+
+    #[test]
+    fn cost_budget_compares_the_estimated_cost() {
+        let totals = SpendTotals {
+            input: 1_000_000,
+            ..SpendTotals::default()
+        };
+        let cost = totals.cost_usd(MODEL).unwrap();
+        assert!(cost > 0.0);
+        assert!(
+            Budget::new(Some(cost * 2.0), None, BudgetAction::Stop).allows_request(MODEL, &totals)
+        );
+        assert!(!Budget::new(Some(cost), None, BudgetAction::Stop).allows_request(MODEL, &totals));
+        assert!(Budget::default().allows_request(MODEL, &totals));
+    }
+}
@@ -29,6 +29,8 @@ impl Repl {
             }
             return Ok(false);
         }
+        // Old read results are shortened or summarised from here on.
+        self.tool_executor.forget_reads();
 
         // Truncate a clone first: commit the truncation only when it
         // alone frees enough tokens, so a failed or interrupted phase 2
@@ -40,10 +42,11 @@ impl Repl {
                 let tokens_after = truncated.estimate_total_tokens();
                 self.session_state.conversation = truncated;
                 println!(
-                    "\n{} {} -> {} tokens (tool results truncated)\n",
+                    "\n{} {} -> {} tokens (tool results truncated in {} older messages)\n",
                     "Compacted:".bright_green(),
                     tokens_before,
-                    tokens_after
+                    tokens_after,
+                    split_point
                 );
                 return Ok(true);
             }
@@ -88,6 +91,8 @@ impl Repl {
             // a long-running conversation, so server-side compaction
             // would be a no-op even on supported models.
             context_management: None,
+            temperature: None,
+            top_p: None,
         };
 
         let interrupt_flag = Arc::clone(&self.interrupt_flag);
@@ -154,8 +159,9 @@ impl Repl {
                     0
                 };
                 println!(
-                    "{} {} -> {} tokens (saved {}%)",
+                    "{} {} messages summarized, {} -> {} tokens (saved {}%)",
                     "Compacted:".bright_green(),
+                    split_point,
                     tokens_before,
                     tokens_after,
                     saved_percent
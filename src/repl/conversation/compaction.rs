@@ -12,13 +12,20 @@ use crate::repl::conversation::ConversationHistory;
 /// replaced with an elision marker.
 const COMPACTION_TOOL_RESULT_KEEP_CHARS: usize = 500;
 
+/// Older messages needed beyond the preserved ones before summarising
+/// them is worth a request.
+pub(super) const COMPACTION_MIN_OLDER_MESSAGES: usize = 5;
+
 impl ConversationHistory {
     /// Check if conversation needs compaction. The trigger is the
     /// per-model `auto_compact_token_limit` (clamped to 90% of the
     /// API ceiling at lookup time), populated at REPL startup from
-    /// [`crate::api::Model::auto_compact_at`].
+    /// [`crate::api::Model::auto_compact_at`]. With
+    /// `on_message_limit = "compact"` it also fires as the message count
+    /// nears `max_messages`.
     pub fn needs_compaction(&self) -> bool {
         self.estimate_total_tokens() > self.config.auto_compact_token_limit
+            || self.near_message_limit()
     }
 
     /// Set the auto-compaction trigger, picked by model via
@@ -32,8 +39,8 @@ impl ConversationHistory {
     /// Find a clean split point for compaction, keeping at least `preserve_recent` messages.
     /// Returns the index where "recent" messages start (split on user-message boundary).
     pub fn compaction_split_point(&self) -> usize {
-        let preserve = self.config.compaction_preserve_recent;
-        if self.messages.len() <= preserve + 5 {
+        let preserve = self.compaction_preserve_recent();
+        if self.messages.len() <= preserve + COMPACTION_MIN_OLDER_MESSAGES {
             return 0;
         }
 
@@ -171,6 +178,8 @@ impl ConversationHistory {
         parts.join("\n\n")
     }
 
+    /// Replace the unpinned messages before `split_point` with `summary`.
+    /// Pinned messages stay verbatim, right after the summary.
     pub fn replace_with_summary(&mut self, summary: String, split_point: usize) {
         if split_point == 0 || split_point > self.messages.len() {
             return;
@@ -178,12 +187,15 @@ impl ConversationHistory {
         // Front-drain + insert shifts every remaining index; the anchor
         // can't carry across this transformation.
         self.invalidate_cache_anchor();
-        self.messages.drain(0..split_point);
+        let summarized: Vec<usize> = (0..split_point)
+            .filter(|i| !self.pinned.contains(i))
+            .collect();
+        self.remove_messages(&summarized);
         let summary_msg = Message::user(format!(
             "[Conversation Summary]\n\nThe following is a summary of our earlier conversation:\n\n{}",
             summary
         ));
-        self.messages.insert(0, summary_msg);
+        self.prepend_message(summary_msg);
         self.maintain_cache_anchor();
     }
 }
@@ -6,7 +6,9 @@
 //! summarisation isn't available.
 
 use crate::api::{Message, utils::truncate_at_char_boundary};
+use crate::config::MessageLimit;
 use crate::repl::conversation::ConversationHistory;
+use crate::repl::conversation::compaction::COMPACTION_MIN_OLDER_MESSAGES;
 
 /// Hard floor on the number of messages [`ConversationHistory::trim_if_needed`]
 /// will keep, even when the per-message budget would normally drop more.
@@ -14,23 +16,58 @@ use crate::repl::conversation::ConversationHistory;
 /// hallucinating prior tool results.
 const TRIM_MIN_MESSAGES: usize = 10;
 
+/// With [`MessageLimit::Compact`], compaction is due once the history
+/// holds this share of `max_messages`, leaving room for the messages a
+/// long tool loop adds before the next turn can compact.
+const MESSAGE_LIMIT_COMPACT_PERCENT: usize = 80;
+
 impl ConversationHistory {
-    /// Trim messages to stay within token budget.
-    pub(super) fn trim_if_needed(&mut self) {
-        let len_before = self.messages.len();
+    /// Set the hard message cap, raised to the trim floor if lower.
+    pub fn set_max_messages(&mut self, n: usize) {
+        self.config.max_messages = n.max(TRIM_MIN_MESSAGES);
+    }
 
-        if self.messages.len() > self.config.max_messages {
-            let remove_count = self.messages.len() - self.config.max_messages;
-            self.messages.drain(0..remove_count);
-        }
+    pub fn set_message_limit(&mut self, limit: MessageLimit) {
+        self.config.message_limit = limit;
+    }
+
+    /// Whether the message count alone calls for compaction.
+    pub(super) fn near_message_limit(&self) -> bool {
+        self.config.message_limit == MessageLimit::Compact
+            && self.messages.len() >= self.message_limit_compact_at()
+    }
 
-        let mut total_tokens = self.estimate_total_tokens();
+    /// Message count at which [`MessageLimit::Compact`] compacts.
+    fn message_limit_compact_at(&self) -> usize {
+        (self.config.max_messages * MESSAGE_LIMIT_COMPACT_PERCENT).div_ceil(100)
+    }
 
-        while total_tokens > self.config.max_context_tokens && self.messages.len() > 10 {
-            let removed_tokens = Self::estimate_message_tokens(&self.messages[0]);
-            self.messages.remove(0);
-            total_tokens -= removed_tokens;
+    /// Messages compaction keeps verbatim. Under [`MessageLimit::Compact`]
+    /// a small `max_messages` lowers `compaction_preserve_recent`: to at
+    /// most half the cap, so several turns pass before the next
+    /// compaction, and low enough that the history can be split once
+    /// compaction is due.
+    pub(super) fn compaction_preserve_recent(&self) -> usize {
+        let preserve = self.config.compaction_preserve_recent;
+        if self.config.message_limit != MessageLimit::Compact {
+            return preserve;
         }
+        let splittable = self
+            .message_limit_compact_at()
+            .saturating_sub(COMPACTION_MIN_OLDER_MESSAGES + 1);
+        preserve.min(self.config.max_messages / 2).min(splittable)
+    }
+
+    /// Trim to the message cap, then to the token budget. The two are
+    /// independent: the message cap always applies, and token trimming
+    /// then drops further until the estimate fits or the floor is hit.
+    /// Both drop the oldest messages without a summary; auto-compaction
+    /// normally runs first and keeps either from firing.
+    pub(super) fn trim_if_needed(&mut self) {
+        let len_before = self.messages.len();
+
+        let plan = self.trim_plan();
+        self.remove_messages(&plan);
 
         // Trimming from the front can strand a user message whose
         // ToolResult blocks reference a ToolUse in an already-dropped
@@ -41,7 +78,7 @@ impl ConversationHistory {
         // drop can move the total token count, so recompute before the
         // "approaching limit" warning to avoid reporting stale numbers.
         let stripped_orphan = self.drop_leading_orphaned_tool_results();
-        total_tokens = self.estimate_total_tokens();
+        let total_tokens = self.estimate_total_tokens();
 
         // Invalidate the anchor when ANY front-of-history mutation
         // happened — index shift OR in-place strip of `messages[0]`
@@ -79,6 +116,64 @@ impl ConversationHistory {
         self.maintain_cache_anchor();
     }
 
+    /// Indices `trim_if_needed` would drop, oldest first. Pinned
+    /// messages are skipped, so the cap and the token budget are met by
+    /// dropping the oldest unpinned messages; when only pinned messages
+    /// remain, the history stays over budget.
+    pub(super) fn trim_plan(&self) -> Vec<usize> {
+        let len = self.messages.len();
+        let mut unpinned: Vec<usize> = (0..len).filter(|i| !self.pinned.contains(i)).collect();
+
+        let mut drop = len
+            .saturating_sub(self.config.max_messages)
+            .min(unpinned.len());
+        let mut tokens = self.estimate_total_tokens().saturating_sub(
+            unpinned[..drop]
+                .iter()
+                .map(|&i| Self::estimate_message_tokens(&self.messages[i]))
+                .sum(),
+        );
+        while drop < unpinned.len()
+            && tokens > self.config.max_context_tokens
+            && len - drop > TRIM_MIN_MESSAGES
+        {
+            tokens = tokens.saturating_sub(Self::estimate_message_tokens(
+                &self.messages[unpinned[drop]],
+            ));
+            drop += 1;
+        }
+
+        unpinned.truncate(drop);
+        unpinned
+    }
+
+    /// Remove the messages at the sorted `indices`, shifting pins down
+    /// past them. Callers own the cache-anchor invalidation.
+    pub(super) fn remove_messages(&mut self, indices: &[usize]) {
+        if indices.is_empty() {
+            return;
+        }
+        let mut index = 0;
+        self.messages.retain(|_| {
+            let keep = indices.binary_search(&index).is_err();
+            index += 1;
+            keep
+        });
+        self.pinned = self
+            .pinned
+            .iter()
+            .filter(|p| indices.binary_search(p).is_err())
+            .map(|&p| p - indices.partition_point(|&i| i < p))
+            .collect();
+    }
+
+    /// Insert `message` at the front of the history, shifting pins up.
+    pub(super) fn prepend_message(&mut self, message: Message) {
+        self.invalidate_cache_anchor();
+        self.messages.insert(0, message);
+        self.pinned = self.pinned.iter().map(|&p| p + 1).collect();
+    }
+
     pub(super) fn message_block_count(msg: &Message) -> usize {
         match &msg.content {
             crate::api::MessageContent::Text { .. } => 1,
@@ -167,7 +262,9 @@ impl ConversationHistory {
 
     /// Drop leading messages whose content still references tool calls
     /// that have been trimmed away. Called after any operation that
-    /// removes messages from the front of the history. Returns `true`
+    /// removes messages from the front of the history. Pinned prompts
+    /// never carry tool results, so the first unpinned message is the
+    /// one that can be stranded. Returns `true`
     /// if any blocks were stripped or any message was removed — the
     /// cache anchor must be invalidated in either case because the
     /// prefix bytes up to the anchor include `messages[0]`.
@@ -182,23 +279,27 @@ impl ConversationHistory {
     pub(super) fn drop_leading_orphaned_tool_results(&mut self) -> bool {
         let mut mutated = false;
         loop {
-            let head_has_orphan = self
-                .messages
-                .first()
-                .is_some_and(|m| m.role == "user" && Self::message_has_tool_result(m));
+            let Some(head) = (0..self.messages.len()).find(|i| !self.pinned.contains(i)) else {
+                return mutated;
+            };
+            let head_has_orphan = {
+                let m = &self.messages[head];
+                m.role == "user" && Self::message_has_tool_result(m)
+            };
             if !head_has_orphan {
                 return mutated;
             }
 
             mutated = true;
-            if let crate::api::MessageContent::Blocks { content } = &mut self.messages[0].content {
+            if let crate::api::MessageContent::Blocks { content } = &mut self.messages[head].content
+            {
                 content
                     .retain(|b| !matches!(b, crate::api::MessageContentBlock::ToolResult { .. }));
                 if !content.is_empty() {
                     return mutated;
                 }
             }
-            self.messages.remove(0);
+            self.remove_messages(&[head]);
         }
     }
 
@@ -321,25 +422,10 @@ impl ConversationHistory {
             return;
         }
 
-        // Simulate trim_if_needed to find which messages will be dropped
-        let max_msg_drop = self.messages.len().saturating_sub(self.config.max_messages);
-        let mut token_drop = 0;
-        let mut simulated_tokens = self.estimate_total_tokens();
-        for msg in self.messages.iter().take(max_msg_drop) {
-            simulated_tokens -= Self::estimate_message_tokens(msg);
-        }
-        let remaining = self.messages.len() - max_msg_drop;
-        for i in 0..remaining.saturating_sub(10) {
-            if simulated_tokens <= self.config.max_context_tokens {
-                break;
-            }
-            simulated_tokens -= Self::estimate_message_tokens(&self.messages[max_msg_drop + i]);
-            token_drop += 1;
-        }
-        let total_drop = max_msg_drop + token_drop;
-
-        let summary = if total_drop >= 5 {
-            Self::build_drop_summary(&self.messages[..total_drop])
+        let plan = self.trim_plan();
+        let summary = if plan.len() >= 5 {
+            let dropped: Vec<Message> = plan.iter().map(|&i| self.messages[i].clone()).collect();
+            Self::build_drop_summary(&dropped)
         } else {
             String::new()
         };
@@ -352,7 +438,7 @@ impl ConversationHistory {
                 "[Context trimmed — {} earlier messages dropped]\n\n{}",
                 dropped, summary
             ));
-            self.messages.insert(0, summary_msg);
+            self.prepend_message(summary_msg);
         }
     }
 }
@@ -4,7 +4,7 @@
 //! accessors for `messages` / `system_prompt`. Append paths run
 //! [`Self::trim_if_needed`] so the budget is enforced on every write.
 
-use crate::api::{Message, MessageContentBlock, SystemPrompt};
+use crate::api::{Message, MessageContent, MessageContentBlock, SystemPrompt};
 use crate::repl::conversation::ConversationHistory;
 
 impl ConversationHistory {
@@ -23,6 +23,33 @@ impl ConversationHistory {
         self.trim_if_needed();
     }
 
+    /// Append `blocks` to the last message when it's an assistant turn,
+    /// so a response that resumes a `pause_turn` extends the paused turn
+    /// instead of following it as a second assistant message. Starts a
+    /// new assistant turn otherwise.
+    pub fn extend_last_assistant(&mut self, blocks: Vec<MessageContentBlock>) {
+        let Some(last) = self
+            .messages
+            .last_mut()
+            .filter(|last| last.role == "assistant")
+        else {
+            self.add_assistant_with_blocks(blocks);
+            return;
+        };
+        match &mut last.content {
+            crate::api::MessageContent::Blocks { content } => content.extend(blocks),
+            crate::api::MessageContent::Text { content } => {
+                let mut merged = vec![MessageContentBlock::Text {
+                    text: std::mem::take(content),
+                    cache_control: None,
+                }];
+                merged.extend(blocks);
+                last.content = crate::api::MessageContent::Blocks { content: merged };
+            }
+        }
+        self.trim_if_needed();
+    }
+
     pub fn add_tool_results(&mut self, results: Vec<MessageContentBlock>) {
         self.messages.push(Message::user_with_tool_results(results));
         self.trim_if_needed();
@@ -67,6 +94,54 @@ impl ConversationHistory {
         &self.messages
     }
 
+    /// Index of the user message that sent `prompt`, when it is still the
+    /// last user turn other than tool results. `None` when a later note
+    /// (from a slash command) follows it, or it was compacted away.
+    pub fn last_prompt_index(&self, prompt: &str) -> Option<usize> {
+        let index = self.messages.iter().rposition(|m| user_text(m).is_some())?;
+        // Error notes are appended to the prompt's message, so match on
+        // the prefix.
+        user_text(&self.messages[index])?
+            .starts_with(prompt)
+            .then_some(index)
+    }
+
+    /// Index of the last user message before `before` that sent `prompt`,
+    /// skipping any notes in between.
+    pub fn prompt_index_before(&self, prompt: &str, before: usize) -> Option<usize> {
+        self.messages
+            .get(..before)?
+            .iter()
+            .rposition(|m| user_text(m).is_some_and(|text| text.starts_with(prompt)))
+    }
+
+    /// Drop every message from `len` on.
+    pub fn truncate(&mut self, len: usize) {
+        self.messages.truncate(len);
+        self.pinned.retain(|&p| p < len);
+        self.invalidate_cache_anchor();
+    }
+
+    /// Indices of the pinned messages, in order.
+    pub fn pinned_messages(&self) -> Vec<usize> {
+        self.pinned.iter().copied().collect()
+    }
+
+    /// Pin the most recent user prompt so trimming and compaction keep
+    /// it. Returns the prompt's text, or `None` when there is no prompt.
+    pub fn pin_last_prompt(&mut self) -> Option<String> {
+        let index = self.messages.iter().rposition(|m| user_text(m).is_some())?;
+        self.pinned.insert(index);
+        user_text(&self.messages[index]).map(str::to_string)
+    }
+
+    /// Unpin every message. Returns how many were pinned.
+    pub fn unpin_all(&mut self) -> usize {
+        let count = self.pinned.len();
+        self.pinned.clear();
+        count
+    }
+
     pub fn system_prompt(&self) -> &Vec<SystemPrompt> {
         &self.system_prompt
     }
@@ -81,15 +156,47 @@ impl ConversationHistory {
         self.invalidate_cache_anchor();
     }
 
+    /// Swap the response-length guidance line in the system prompt for
+    /// `verbosity`'s. Matches whichever level's line is present, so it
+    /// also works on a prompt restored from a saved session. Returns
+    /// `false` when no guidance line is found (a custom prompt), leaving
+    /// the prompt untouched.
+    pub fn set_verbosity(&mut self, verbosity: crate::config::Verbosity) -> bool {
+        let replacement = verbosity.prompt_instruction();
+        let Some(block) = self.system_prompt.first_mut() else {
+            return false;
+        };
+        let Some(current) = crate::config::VERBOSITY_LEVELS
+            .iter()
+            .map(|level| level.prompt_instruction())
+            .find(|line| block.text.contains(line))
+        else {
+            return false;
+        };
+        if current != replacement {
+            block.text = block.text.replacen(current, replacement, 1);
+            self.invalidate_cache_anchor();
+        }
+        true
+    }
+
     pub fn clear(&mut self) {
         self.messages.clear();
+        self.pinned.clear();
         self.invalidate_cache_anchor();
     }
 
-    pub fn restore_messages(&mut self, messages: Vec<Message>) {
+    /// Replace the history with `messages`, keeping the ones at `pins`
+    /// pinned. Indices past the end of `messages` are ignored.
+    pub fn restore_messages(
+        &mut self,
+        messages: Vec<Message>,
+        pins: impl IntoIterator<Item = usize>,
+    ) {
         // The new history has no relationship to the prior conversation;
         // any inherited anchor index is meaningless content-wise.
         self.invalidate_cache_anchor();
+        self.pinned = pins.into_iter().filter(|&p| p < messages.len()).collect();
         self.messages = messages;
         // A session file written between `add_assistant_with_blocks`
         // and `add_tool_results` carries a tail assistant message with
@@ -99,3 +206,31 @@ impl ConversationHistory {
         self.trim_if_needed();
     }
 }
+
+/// Text the user typed into `message`: `None` for assistant turns and
+/// for the tool-result messages that carry no prompt.
+fn user_text(message: &Message) -> Option<&str> {
+    if message.role != "user" {
+        return None;
+    }
+    match &message.content {
+        MessageContent::Text { content } => Some(content.as_str()),
+        MessageContent::Blocks { content } => {
+            if content
+                .iter()
+                .any(|block| matches!(block, MessageContentBlock::ToolResult { .. }))
+            {
+                return None;
+            }
+            Some(
+                content
+                    .iter()
+                    .find_map(|block| match block {
+                        MessageContentBlock::Text { text, .. } => Some(text.as_str()),
+                        _ => None,
+                    })
+                    .unwrap_or(""),
+            )
+        }
+    }
+}
@@ -4,7 +4,40 @@ pub mod messages;
 pub mod tokens;
 
 use crate::api::{Message, SystemPrompt};
-use crate::config::SofosConfig;
+use crate::config::{SofosConfig, Verbosity};
+use crate::error::{Result, SofosError};
+use std::collections::BTreeSet;
+use std::path::Path;
+
+/// A prompt from `--system-prompt-file`: appended to the built-in
+/// system prompt, or used instead of it with `--replace-system-prompt`.
+#[derive(Debug, Clone)]
+pub struct SystemPromptFile {
+    pub content: String,
+    pub replace: bool,
+}
+
+impl SystemPromptFile {
+    pub fn load(path: &Path, replace: bool) -> Result<Self> {
+        let content = std::fs::read_to_string(path).map_err(|e| {
+            SofosError::Config(format!(
+                "cannot read system prompt file '{}': {}",
+                path.display(),
+                e
+            ))
+        })?;
+        if content.trim().is_empty() {
+            return Err(SofosError::Config(format!(
+                "system prompt file '{}' is empty",
+                path.display()
+            )));
+        }
+        Ok(Self {
+            content: content.trim_end().to_string(),
+            replace,
+        })
+    }
+}
 
 #[derive(Clone)]
 pub struct ConversationHistory {
@@ -33,17 +66,23 @@ pub struct ConversationHistory {
     /// anchor sits strictly before the rolling by construction; the
     /// next [`Self::maintain_cache_anchor`] re-validates the index.
     pub(super) cache_anchor_message_idx: Option<usize>,
+    /// Indices of messages the user pinned with `/pin`. Trimming and
+    /// compaction drop the oldest unpinned messages around them; every
+    /// mutator that removes or inserts messages shifts these to match.
+    pub(super) pinned: BTreeSet<usize>,
 }
 
 impl ConversationHistory {
     pub fn new() -> Self {
-        Self::with_features(false, false, None)
+        Self::with_features(false, false, None, None, Verbosity::default())
     }
 
     pub fn with_features(
         has_morph: bool,
         has_code_search: bool,
+        prompt_file: Option<SystemPromptFile>,
         custom_instructions: Option<String>,
+        verbosity: Verbosity,
     ) -> Self {
         let mut features = vec![
             "1. Read files in the current project directory",
@@ -77,9 +116,9 @@ impl ConversationHistory {
 {}
 
 When helping users:
-- Be concise and practical
+{}
 - Context interpretation: When users refer to "this code", "these files", or similar context-dependent terms without specifying a path, they mean the code in the current working directory
-- ALWAYS explore first: Use list_directory to find files before trying to read them if you're unsure of their location
+- ALWAYS explore first: Use tree for an overview of an unfamiliar project and list_directory to find files before trying to read them if you're unsure of their location
 - Use your tools to read files before suggesting changes
 {}
 - Search the web when you need current information or documentation
@@ -115,8 +154,8 @@ CRITICAL - Making Changes:
 - If unsure whether to implement or just suggest, always ask first
 
 Testing after code changes:
-- After editing code files (not comments, README, or documentation), ALWAYS test the changes using execute_bash
-- Run appropriate build/test commands based on the project type:
+- After editing code files (not comments, README, or documentation), ALWAYS test the changes with run_tests, which picks the project's test command and summarises the result
+- Use execute_bash for builds, or when run_tests cannot tell the project type. The conventional commands are:
   * Rust: 'cargo build' and/or 'cargo test'
   * JavaScript/TypeScript: 'npm run build' and/or 'npm test'
   * Python: 'python -m pytest' or 'python -m unittest'
@@ -128,10 +167,20 @@ Your goal is to help users with coding tasks efficiently and accurately.
 Always use the metric system for all measurements. If the user uses other units, convert them and answer in metric.
 Show imperial units only when the user explicitly asks for them."#,
             features.join("\n"),
+            verbosity.prompt_instruction(),
             edit_instruction,
             write_scope_tools
         );
 
+        if let Some(file) = prompt_file {
+            if file.replace {
+                system_text = file.content;
+            } else {
+                system_text.push_str("\n\n");
+                system_text.push_str(&file.content);
+            }
+        }
+
         // Append custom instructions if provided
         if let Some(instructions) = custom_instructions {
             system_text.push_str("\n\n");
@@ -151,6 +200,7 @@ Show imperial units only when the user explicitly asks for them."#,
             config: SofosConfig::default(),
             warned_at_floor: false,
             cache_anchor_message_idx: None,
+            pinned: BTreeSet::new(),
         }
     }
 }
@@ -181,6 +231,127 @@ mod tests {
         }
     }
 
+    #[test]
+    fn message_limit_can_compact_before_the_cap() {
+        let mut history = ConversationHistory::new();
+        history.set_max_messages(3);
+        assert_eq!(history.config.max_messages, 10, "raised to the trim floor");
+
+        history.set_max_messages(50);
+        for i in 0..40 {
+            history.add_user_message(format!("Message {}", i));
+        }
+        assert!(
+            !history.needs_compaction(),
+            "drop mode never compacts on count"
+        );
+
+        history.set_message_limit(crate::config::MessageLimit::Compact);
+        assert!(history.needs_compaction());
+
+        for i in 40..60 {
+            history.add_user_message(format!("Message {}", i));
+        }
+        assert_eq!(history.messages().len(), 50, "the hard cap still applies");
+    }
+
+    #[test]
+    fn a_small_message_cap_still_compacts_with_room_to_spare() {
+        let mut history = ConversationHistory::new();
+        history.set_max_messages(30);
+        history.set_message_limit(crate::config::MessageLimit::Compact);
+        for i in 0..24 {
+            history.add_user_message(format!("Message {}", i));
+        }
+        assert!(history.needs_compaction());
+
+        let split = history.compaction_split_point();
+        assert!(split > 0, "the history can be split when compaction is due");
+        let after = history.messages().len() - split + 1;
+        assert!(
+            after <= 30 / 2 + 1,
+            "{after} messages left after compaction, too close to the cap"
+        );
+    }
+
+    #[test]
+    fn last_prompt_is_found_past_tool_turns_but_not_past_notes() {
+        let mut history = ConversationHistory::new();
+        history.add_user_message("first".to_string());
+        history.add_user_message("fix the build".to_string());
+        history.append_text_to_last_user_blocks("[SYSTEM ERROR: x]".to_string());
+        history.add_assistant_with_blocks(vec![MessageContentBlock::ToolUse {
+            id: "call_1".to_string(),
+            name: "read_file".to_string(),
+            input: serde_json::json!({"path": "a.rs"}),
+            cache_control: None,
+        }]);
+        history.add_tool_results(vec![MessageContentBlock::ToolResult {
+            tool_use_id: "call_1".to_string(),
+            content: "fn main() {}".to_string(),
+            cache_control: None,
+        }]);
+
+        assert_eq!(history.last_prompt_index("fix the build"), Some(1));
+        assert_eq!(history.last_prompt_index("first"), None);
+
+        history.add_user_message("Scope is now src/".to_string());
+        assert_eq!(history.last_prompt_index("fix the build"), None);
+
+        assert_eq!(history.prompt_index_before("fix the build", 5), Some(1));
+        assert_eq!(history.prompt_index_before("fix the build", 1), None);
+        assert_eq!(history.prompt_index_before("first", 1), Some(0));
+
+        history.truncate(1);
+        assert_eq!(history.messages().len(), 1);
+    }
+
+    #[test]
+    fn verbosity_guidance_is_injected_and_swappable() {
+        let mut history =
+            ConversationHistory::with_features(false, false, None, None, Verbosity::Concise);
+        let text = |h: &ConversationHistory| h.system_prompt()[0].text.clone();
+        assert!(text(&history).contains(Verbosity::Concise.prompt_instruction()));
+
+        assert!(history.set_verbosity(Verbosity::Detailed));
+        assert!(text(&history).contains(Verbosity::Detailed.prompt_instruction()));
+        assert!(!text(&history).contains(Verbosity::Concise.prompt_instruction()));
+
+        history.set_system_prompt(vec![SystemPrompt::new_cached_with_ttl(
+            "custom".to_string(),
+            None,
+        )]);
+        assert!(!history.set_verbosity(Verbosity::Normal));
+        assert_eq!(text(&history), "custom");
+    }
+
+    #[test]
+    fn a_prompt_file_is_appended_or_replaces_the_built_in_prompt() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("review.md");
+        std::fs::write(&path, "Review code strictly.\n\n").unwrap();
+        let text = |replace: bool| {
+            let file = SystemPromptFile::load(&path, replace).unwrap();
+            let history = ConversationHistory::with_features(
+                false,
+                false,
+                Some(file),
+                Some("Use tabs.".to_string()),
+                Verbosity::default(),
+            );
+            history.system_prompt()[0].text.clone()
+        };
+
+        let appended = text(false);
+        assert!(appended.starts_with("You are Sofos"));
+        assert!(appended.ends_with("Review code strictly.\n\nUse tabs."));
+        assert_eq!(text(true), "Review code strictly.\n\nUse tabs.");
+
+        std::fs::write(&path, " \n").unwrap();
+        assert!(SystemPromptFile::load(&path, false).is_err());
+        assert!(SystemPromptFile::load(&dir.path().join("missing.md"), false).is_err());
+    }
+
     #[test]
     fn test_drop_orphaned_tool_results_preserves_mixed_text_block() {
         // A user turn carrying `[ToolResult, Text]` models the mid-turn
@@ -200,7 +371,7 @@ mod tests {
                 cache_control: None,
             },
         ])];
-        history.restore_messages(messages);
+        history.restore_messages(messages, []);
         history.drop_leading_orphaned_tool_results();
 
         assert_eq!(
@@ -228,7 +399,7 @@ mod tests {
                 cache_control: None,
             },
         ])];
-        history.restore_messages(messages);
+        history.restore_messages(messages, []);
         history.drop_leading_orphaned_tool_results();
         assert_eq!(history.messages().len(), 0);
     }
@@ -252,7 +423,7 @@ mod tests {
                 cache_control: None,
             }]),
         ];
-        history.restore_messages(messages);
+        history.restore_messages(messages, []);
         history.drop_leading_orphaned_tool_results();
         assert_eq!(history.messages().len(), 2);
     }
@@ -286,7 +457,7 @@ mod tests {
             Message::user("next".to_string()),
         ];
 
-        history.restore_messages(messages);
+        history.restore_messages(messages, []);
 
         // Both the original user query and the assistant ToolUse must
         // be trimmed. The orphaned ToolResult that used to follow them
@@ -350,7 +521,7 @@ mod tests {
             }]),
             Message::user("next".to_string()),
         ];
-        history.restore_messages(messages);
+        history.restore_messages(messages, []);
 
         // OpenAI Responses path: every function_call_output must
         // reference a prior function_call with the same call_id.
@@ -366,6 +537,8 @@ mod tests {
             reasoning: None,
             prompt_cache_key: None,
             context_management: None,
+            temperature: None,
+            top_p: None,
         };
         let openai_input = build_response_input(&request);
         let mut seen_call_ids: HashSet<String> = HashSet::new();
@@ -655,6 +828,63 @@ mod tests {
         }
     }
 
+    fn text_of(message: &Message) -> &str {
+        match &message.content {
+            crate::api::MessageContent::Text { content } => content,
+            crate::api::MessageContent::Blocks { .. } => "",
+        }
+    }
+
+    #[test]
+    fn pinned_prompt_survives_trimming() {
+        let mut history = ConversationHistory::new();
+        history.add_user_message("The spec".to_string());
+        assert_eq!(history.pin_last_prompt().as_deref(), Some("The spec"));
+
+        for i in 0..510 {
+            history.add_user_message(format!("Message {}", i));
+        }
+
+        assert_eq!(history.messages().len(), 500);
+        assert_eq!(text_of(&history.messages()[0]), "The spec");
+        assert_eq!(text_of(&history.messages()[1]), "Message 11");
+        assert_eq!(history.pinned_messages(), vec![0]);
+
+        assert_eq!(history.unpin_all(), 1);
+        history.add_user_message("Message 510".to_string());
+        assert_eq!(text_of(&history.messages()[0]), "Message 11");
+    }
+
+    #[test]
+    fn pinned_prompt_stays_verbatim_after_compaction() {
+        let mut history = ConversationHistory::new();
+        for i in 0..10 {
+            history.messages.push(Message::user(format!("msg {}", i)));
+        }
+        history.pinned.insert(2);
+        history.pinned.insert(8);
+
+        history.replace_with_summary("This is the summary".to_string(), 7);
+
+        // 6 summarized, the pin at 2 kept, 3 recent messages.
+        assert_eq!(history.messages().len(), 5);
+        assert!(text_of(&history.messages()[0]).contains("This is the summary"));
+        assert_eq!(text_of(&history.messages()[1]), "msg 2");
+        assert_eq!(history.pinned_messages(), vec![1, 3]);
+        assert_eq!(text_of(&history.messages()[3]), "msg 8");
+    }
+
+    #[test]
+    fn restored_pins_outside_the_history_are_ignored() {
+        let mut history = ConversationHistory::new();
+        let messages = vec![Message::user("a"), Message::user("b")];
+        history.restore_messages(messages, [1, 5]);
+        assert_eq!(history.pinned_messages(), vec![1]);
+
+        history.truncate(1);
+        assert!(history.pinned_messages().is_empty());
+    }
+
     #[test]
     fn test_serialize_messages_for_summary() {
         let messages = vec![
@@ -1142,7 +1372,7 @@ mod tests {
 
         // Restore to a totally new conversation with the same shape.
         let new_messages: Vec<Message> = (0..12).map(|_| blocks_msg_with("user", 1)).collect();
-        history.restore_messages(new_messages);
+        history.restore_messages(new_messages, []);
 
         // The anchor must be re-established from current state, not
         // carried across from the prior conversation. After
@@ -1155,7 +1385,7 @@ mod tests {
         // checking the anchor is None (would be Some(stale) without
         // the fix).
         let small: Vec<Message> = (0..3).map(|_| blocks_msg_with("user", 1)).collect();
-        history.restore_messages(small);
+        history.restore_messages(small, []);
         assert!(
             history.cache_anchor_message_idx().is_none(),
             "small restored history must leave anchor None"
@@ -1228,4 +1458,29 @@ mod tests {
         let appended = history.append_text_to_last_user_blocks("note".to_string());
         assert!(!appended, "empty history has no tail to extend");
     }
+
+    #[test]
+    fn extend_last_assistant_merges_into_a_paused_turn() {
+        // A response resuming a `pause_turn` continues the paused
+        // assistant message, keeping the roles alternating.
+        let text = |t: &str| MessageContentBlock::Text {
+            text: t.to_string(),
+            cache_control: None,
+        };
+        let mut history = ConversationHistory::new();
+        history.add_user_message("query".to_string());
+        history.add_assistant_with_blocks(vec![text("searching")]);
+        history.extend_last_assistant(vec![text("found it")]);
+        assert_eq!(history.messages().len(), 2);
+        match &history.messages()[1].content {
+            crate::api::MessageContent::Blocks { content } => assert_eq!(content.len(), 2),
+            other => panic!("expected Blocks content, got {:?}", other),
+        }
+
+        // Without an assistant tail it starts a new turn.
+        history.add_user_message("next".to_string());
+        history.extend_last_assistant(vec![text("reply")]);
+        assert_eq!(history.messages().len(), 4);
+        assert_eq!(history.messages()[3].role, "assistant");
+    }
 }
@@ -16,9 +16,21 @@ impl ConversationHistory {
         self.config.max_context_tokens = n;
     }
 
+    /// The context-window ceiling [`Self::estimate_total_tokens`] is
+    /// measured against.
+    pub fn max_context_tokens(&self) -> usize {
+        self.config.max_context_tokens
+    }
+
     pub fn estimate_tokens(text: &str) -> usize {
+        Self::estimate_tokens_for_len(text.len())
+    }
+
+    /// [`Self::estimate_tokens`] for `len` bytes of text, for callers such
+    /// as `file_info` that know a size without holding the text.
+    pub fn estimate_tokens_for_len(len: usize) -> usize {
         // Conservative: 1 token per 3.5 chars (accounts for code/JSON being token-heavy)
-        (text.len() as f64 / 3.5).ceil() as usize
+        (len as f64 / 3.5).ceil() as usize
     }
 
     pub(super) fn estimate_system_tokens(&self) -> usize {
@@ -121,13 +133,16 @@ impl ConversationHistory {
     }
 
     pub fn estimate_total_tokens(&self) -> usize {
-        let system_tokens = self.estimate_system_tokens();
-        let message_tokens: usize = self
-            .messages
+        self.estimate_system_tokens() + self.estimate_tokens_from(0)
+    }
+
+    /// Estimated tokens of the messages from index `start` on, without
+    /// the system prompt.
+    pub fn estimate_tokens_from(&self, start: usize) -> usize {
+        self.messages
             .iter()
+            .skip(start)
             .map(Self::estimate_message_tokens)
-            .sum();
-
-        system_tokens + message_tokens
+            .sum()
     }
 }
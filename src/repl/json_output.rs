@@ -0,0 +1,170 @@
+//! The `--output json` report for a one-shot prompt. Built from the
+//! messages the turn appended to the conversation, so the tool inputs
+//! and outputs are exactly what the model saw.
+
+use crate::api::{Message, MessageContent, MessageContentBlock};
+use crate::repl::budget::SpendTotals;
+use serde::Serialize;
+use serde_json::Value;
+
+#[derive(Debug, Serialize)]
+pub(super) struct PromptReport {
+    pub model: String,
+    /// Text of the last assistant message.
+    pub text: String,
+    pub tools: Vec<ToolRun>,
+    pub usage: Usage,
+    /// `null` when no price is known for the model.
+    pub cost_usd: Option<f64>,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+pub(super) struct ToolRun {
+    pub name: String,
+    pub input: Value,
+    /// `None` when the turn ended before the tool ran.
+    pub output: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub(super) struct Usage {
+    pub input_tokens: u32,
+    pub output_tokens: u32,
+    pub cache_read_tokens: u32,
+    pub cache_creation_tokens: u32,
+}
+
+impl PromptReport {
+    /// `turn` is the slice of the conversation this prompt added;
+    /// `totals` is the session's usage, matching the text summary.
+    pub(super) fn new(model: &str, turn: &[Message], totals: &SpendTotals) -> Self {
+        Self {
+            model: model.to_string(),
+            text: final_text(turn),
+            tools: tool_runs(turn),
+            usage: Usage {
+                input_tokens: totals.input,
+                output_tokens: totals.output,
+                cache_read_tokens: totals.cache_read,
+                cache_creation_tokens: totals.cache_creation,
+            },
+            cost_usd: totals.cost_usd(model),
+        }
+    }
+}
+
+fn final_text(turn: &[Message]) -> String {
+    let Some(last) = turn.iter().rev().find(|m| m.role == "assistant") else {
+        return String::new();
+    };
+    match &last.content {
+        MessageContent::Text { content } => content.clone(),
+        MessageContent::Blocks { content } => content
+            .iter()
+            .filter_map(|block| match block {
+                MessageContentBlock::Text { text, .. } => Some(text.as_str()),
+                _ => None,
+            })
+            .collect::<Vec<_>>()
+            .join("\n"),
+    }
+}
+
+fn tool_runs(turn: &[Message]) -> Vec<ToolRun> {
+    let blocks = turn.iter().filter_map(|m| match &m.content {
+        MessageContent::Blocks { content } => Some(content),
+        MessageContent::Text { .. } => None,
+    });
+    let mut runs: Vec<(String, ToolRun)> = Vec::new();
+    for block in blocks.flatten() {
+        match block {
+            MessageContentBlock::ToolUse {
+                id, name, input, ..
+            } => runs.push((
+                id.clone(),
+                ToolRun {
+                    name: name.clone(),
+                    input: input.clone(),
+                    output: None,
+                },
+            )),
+            MessageContentBlock::ToolResult {
+                tool_use_id,
+                content,
+                ..
+            } => {
+                if let Some((_, run)) = runs.iter_mut().find(|(id, _)| id == tool_use_id) {
+                    run.output = Some(content.clone());
+                }
+            }
+            _ => {}
+        }
+    }
+    runs.into_iter().map(|(_, run)| run).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn assistant(content: Vec<MessageContentBlock>) -> Message {
+        Message {
+            role: "assistant".to_string(),
+            content: MessageContent::Blocks { content },
+        }
+    }
+
+    fn text(text: &str) -> MessageContentBlock {
+        MessageContentBlock::Text {
+            text: text.to_string(),
+            cache_control: None,
+        }
+    }
+
+    #[test]
+    fn report_pairs_tool_calls_with_results_and_keeps_the_final_text() {
+        let turn = vec![
+            Message::user("list the files"),
+            assistant(vec![
+                text("Looking."),
+                MessageContentBlock::ToolUse {
+                    id: "call_1".to_string(),
+                    name: "list_directory".to_string(),
+                    input: json!({"path": "."}),
+                    cache_control: None,
+                },
+            ]),
+            Message::user_with_tool_results(vec![MessageContentBlock::ToolResult {
+                tool_use_id: "call_1".to_string(),
+                content: "a.rs".to_string(),
+                cache_control: None,
+            }]),
+            assistant(vec![text("There is one file: a.rs.")]),
+        ];
+        let totals = SpendTotals {
+            input: 100,
+            output: 20,
+            ..SpendTotals::default()
+        };
+
+        let report = PromptReport::new(crate::api::model_info::DEFAULT_MODEL_NAME, &turn, &totals);
+
+        assert_eq!(report.text, "There is one file: a.rs.");
+        assert_eq!(
+            report.tools,
+            vec![ToolRun {
+                name: "list_directory".to_string(),
+                input: json!({"path": "."}),
+                output: Some("a.rs".to_string()),
+            }]
+        );
+        assert_eq!(report.usage.input_tokens, 100);
+        assert!(report.cost_usd.is_some_and(|usd| usd > 0.0));
+
+        let value = serde_json::to_value(&report).unwrap();
+        for key in ["model", "text", "tools", "usage", "cost_usd"] {
+            assert!(value.get(key).is_some(), "missing {key}");
+        }
+    }
+}
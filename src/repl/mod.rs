@@ -1,11 +1,14 @@
+mod budget;
 pub mod compaction;
 pub mod conversation;
+mod json_output;
 mod request_builder;
 mod response_handler;
 pub mod sessions;
 pub mod tui;
 pub mod turn;
 
+pub use budget::Budget;
 pub use conversation::ConversationHistory;
 pub use request_builder::RequestBuilder;
 pub use response_handler::ResponseHandler;
@@ -20,8 +23,10 @@ use crate::config::{
 };
 use crate::error::{Result, SofosError};
 use crate::mcp::McpManager;
-use crate::session::{HistoryManager, SessionState};
-use crate::tools::ToolExecutor;
+use crate::session::{DisplayMessage, HistoryManager, SessionState};
+use crate::tools::permissions::PermissionManager;
+use crate::tools::permissions::settings::RuleList;
+use crate::tools::{ToolExecutor, ToolName};
 use crate::ui::{UI, set_default_cursor_style, set_readonly_cursor_style};
 use colored::Colorize;
 use std::path::PathBuf;
@@ -36,6 +41,23 @@ use tokio::time::sleep;
 /// the accumulated text into the user turn that carries the tool results.
 pub type SteerBuffer = Arc<Mutex<Vec<String>>>;
 
+/// Server-side web search has no description of its own in the tool list.
+const WEB_SEARCH_DESCRIPTION: &str = "Search the web (provider-side)";
+/// Characters of a tool description `/tools` shows before cutting it.
+const TOOL_DESCRIPTION_MAX_CHARS: usize = 80;
+
+/// Exit status after a second Ctrl+C in a one-shot run: 128 + SIGINT,
+/// what a shell reports for a process killed by Ctrl+C.
+const FORCED_INTERRUPT_EXIT_CODE: i32 = 130;
+
+fn truncate_description(text: &str) -> String {
+    if text.chars().count() <= TOOL_DESCRIPTION_MAX_CHARS {
+        return text.to_string();
+    }
+    let cut: String = text.chars().take(TOOL_DESCRIPTION_MAX_CHARS - 1).collect();
+    format!("{}…", cut.trim_end())
+}
+
 /// Build the assistant-facing preamble for the active access `mode` and
 /// escalation `policy`. Centralised so the startup path, slash-command path,
 /// and `/clear` path all show the same text.
@@ -74,6 +96,40 @@ pub struct ReplConfig {
     pub reasoning_mode: crate::api::ReasoningMode,
     pub mode: SandboxMode,
     pub approval_policy: ApprovalPolicy,
+    /// Resolve bare file names by basename search (`--smart-paths`).
+    pub smart_paths: bool,
+    /// Preview mutating tool calls instead of running them (`--dry-run`).
+    pub dry_run: bool,
+    /// Ask before each file change (`--confirm-edits`).
+    pub confirm_edits: bool,
+    /// Back up files before overwriting them (`--backups`).
+    pub backups: bool,
+    /// Send local images at full size (`--no-image-resize`).
+    pub no_image_resize: bool,
+    /// `--bash-timeout` / `--max-output-bytes` for `execute_bash`.
+    pub bash_limits: crate::tools::bash::output::BashLimits,
+    /// API keys `/model` uses to switch providers mid-session.
+    pub provider_keys: crate::api::ProviderKeys,
+    /// Response-length guidance for the system prompt (`--verbosity`).
+    pub verbosity: crate::config::Verbosity,
+    /// Named inputs for `read_input` (`--input`).
+    pub inputs: crate::tools::inputs::NamedInputs,
+    /// `--max-messages`, which wins over `[conversation] max_messages`.
+    pub max_messages: Option<usize>,
+    /// `--max-tool-iterations`: tool-call rounds allowed per turn.
+    pub max_tool_iterations: u32,
+    /// `--auto-continue`: continue responses cut off at `max_tokens`.
+    pub auto_continue: bool,
+    /// `--compact-tool-results`: shorter read results and no repeat reads.
+    pub compact_tool_results: bool,
+    /// `--system-prompt-file`, with `--replace-system-prompt`.
+    pub system_prompt_file: Option<conversation::SystemPromptFile>,
+    /// `--max-cost` / `--max-total-tokens` limits for the session.
+    pub budget: Budget,
+    /// What `--prompt` prints (`--output`).
+    pub output_format: crate::config::OutputFormat,
+    /// `--temperature` / `--top-p`.
+    pub sampling: crate::api::Sampling,
 }
 
 impl ReplConfig {
@@ -92,12 +148,31 @@ impl ReplConfig {
             reasoning_mode,
             mode,
             approval_policy,
+            smart_paths: false,
+            dry_run: false,
+            confirm_edits: false,
+            backups: false,
+            no_image_resize: false,
+            bash_limits: crate::tools::bash::output::BashLimits::default(),
+            provider_keys: crate::api::ProviderKeys::default(),
+            verbosity: crate::config::Verbosity::default(),
+            inputs: crate::tools::inputs::NamedInputs::new(),
+            max_messages: None,
+            max_tool_iterations: crate::config::DEFAULT_MAX_TOOL_ITERATIONS,
+            auto_continue: false,
+            compact_tool_results: false,
+            system_prompt_file: None,
+            budget: Budget::default(),
+            output_format: crate::config::OutputFormat::default(),
+            sampling: crate::api::Sampling::default(),
         }
     }
 }
 
 pub struct Repl {
     pub(super) client: LlmClient,
+    /// Keys for building a client on another provider after `/model`.
+    pub(super) provider_keys: crate::api::ProviderKeys,
     pub(super) tool_executor: ToolExecutor,
     pub(super) history_manager: HistoryManager,
     pub(super) ui: UI,
@@ -105,7 +180,14 @@ pub struct Repl {
     pub(super) session_state: SessionState,
     pub(super) mode: SandboxMode,
     pub(super) approval_policy: ApprovalPolicy,
+    /// Response-length level last applied to the system prompt.
+    pub(super) verbosity: crate::config::Verbosity,
     pub(super) available_tools: Vec<crate::api::Tool>,
+    /// Spend limits checked before every provider request.
+    pub(super) budget: Budget,
+    pub(super) max_tool_iterations: u32,
+    pub(super) auto_continue: bool,
+    pub(super) output_format: crate::config::OutputFormat,
     /// Interrupt flag shared with the TUI. Set to `true` when the user presses
     /// ESC/Ctrl+C during an AI turn; checked by the API request loop.
     pub(super) interrupt_flag: Arc<AtomicBool>,
@@ -136,7 +218,7 @@ pub struct Repl {
 impl Repl {
     pub fn new(
         client: LlmClient,
-        config: ReplConfig,
+        mut config: ReplConfig,
         workspace: PathBuf,
         morph_client: Option<MorphClient>,
     ) -> Result<Self> {
@@ -164,6 +246,22 @@ impl Repl {
             std::io::stdin().is_terminal(),
         )?;
         tool_executor.set_approval_policy(config.approval_policy);
+        tool_executor.set_smart_paths(config.smart_paths);
+        tool_executor.set_dry_run(config.dry_run);
+        tool_executor.set_confirm_edits(config.confirm_edits);
+        tool_executor.set_compact_results(config.compact_tool_results);
+        tool_executor.set_backups(config.backups);
+        tool_executor.set_bash_limits(config.bash_limits);
+        tool_executor.set_bash_env_allow(crate::config::load_bash_env_allow(&workspace));
+        tool_executor.set_live_bash_output(true);
+        if config.no_image_resize {
+            tool_executor.set_image_max_dimension(None);
+        } else if let Some(max) = crate::config::load_image_max_dimension(&workspace) {
+            tool_executor.set_image_max_dimension(Some(max));
+        }
+        tool_executor.set_inputs(std::mem::take(&mut config.inputs));
+        tool_executor.set_morph_fallback(crate::config::load_morph_fallback(&workspace));
+        tool_executor.set_diff_style(crate::config::load_diff_style(&workspace));
 
         let has_morph = tool_executor.has_morph();
         let has_code_search = tool_executor.has_code_search();
@@ -197,9 +295,9 @@ impl Repl {
         // accept, e.g. `xhigh` on a model that tops out at `high`.
         // Catching it here turns a runtime 400 into a clear startup
         // error.
-        if let Some(msg) =
-            crate::api::model_info::effort_support_error(&config.model, config.reasoning_effort)
-        {
+        let custom_endpoint = client.uses_custom_endpoint();
+        let model_info = crate::api::model_info::lookup_on(&config.model, custom_endpoint);
+        if let Some(msg) = model_info.effort_support_error(&config.model, config.reasoning_effort) {
             return Err(SofosError::Config(msg));
         }
 
@@ -211,12 +309,21 @@ impl Repl {
             return Err(SofosError::Config(msg));
         }
 
-        let mut conversation =
-            ConversationHistory::with_features(has_morph, has_code_search, custom_instructions);
-        conversation.set_max_context_tokens(crate::config::max_context_tokens_for(&config.model));
-        conversation.set_auto_compact_token_limit(crate::config::auto_compact_token_limit_for(
-            &config.model,
-        ));
+        let mut conversation = ConversationHistory::with_features(
+            has_morph,
+            has_code_search,
+            config.system_prompt_file.take(),
+            custom_instructions,
+            config.verbosity,
+        );
+        conversation.set_max_context_tokens(crate::config::max_context_tokens_for(model_info));
+        conversation
+            .set_auto_compact_token_limit(crate::config::auto_compact_token_limit_for(model_info));
+        let conversation_settings = crate::config::load_conversation_settings(&workspace);
+        if let Some(n) = config.max_messages.or(conversation_settings.max_messages) {
+            conversation.set_max_messages(n);
+        }
+        conversation.set_message_limit(conversation_settings.on_message_limit.unwrap_or_default());
 
         // Every mode gets a startup preamble so the assistant knows from
         // turn 1 which tier rules and platform caveats apply, not just
@@ -242,12 +349,14 @@ impl Repl {
 
         let session_id = history_manager.generate_unique_session_id();
         let session_state = SessionState::new(session_id, conversation);
-        let model_config = ModelConfig::new(
+        let mut model_config = ModelConfig::new(
             config.model,
             config.max_tokens,
             config.reasoning_effort,
             config.reasoning_mode,
         );
+        model_config.sampling = config.sampling;
+        model_config.custom_endpoint = custom_endpoint;
 
         let ui = UI::new();
 
@@ -259,6 +368,7 @@ impl Repl {
 
         Ok(Self {
             client,
+            provider_keys: config.provider_keys,
             tool_executor,
             history_manager,
             ui,
@@ -266,7 +376,12 @@ impl Repl {
             session_state,
             mode: config.mode,
             approval_policy: config.approval_policy,
+            verbosity: config.verbosity,
             available_tools,
+            budget: config.budget,
+            max_tool_iterations: config.max_tool_iterations,
+            auto_continue: config.auto_continue,
+            output_format: config.output_format,
             interrupt_flag: Arc::new(AtomicBool::new(false)),
             steer_buffer: Arc::new(Mutex::new(Vec::new())),
             startup_banner: String::new(),
@@ -354,6 +469,10 @@ impl Repl {
             approval: self.approval_policy,
             reasoning,
             reasoning_mode,
+            scope: self
+                .tool_executor
+                .scope()
+                .map(|dir| dir.display().to_string()),
             input_tokens: self.session_state.total_input_tokens,
             output_tokens: self.session_state.total_output_tokens,
             cache_read_tokens: self.session_state.total_cache_read_tokens,
@@ -373,10 +492,14 @@ impl Repl {
             &self.session_state.session_id,
         )
         .with_reasoning_mode(self.model_config.reasoning_mode)
+        .with_sampling(self.model_config.sampling)
         .build()
     }
 
     pub fn process_single_prompt(&mut self, prompt: &str) -> Result<()> {
+        if self.output_format == crate::config::OutputFormat::Json {
+            return self.process_single_prompt_json(prompt);
+        }
         let symbol = match self.mode {
             SandboxMode::ReadOnly => ":",
             SandboxMode::Sandboxed => ">",
@@ -384,11 +507,43 @@ impl Repl {
         };
         println!("{} {}", symbol.bright_green().bold(), prompt);
         println!();
+        let interrupted = self.run_single_turn(prompt)?;
+        UI::display_session_summary(
+            &self.model_config.model,
+            self.session_state.total_input_tokens,
+            self.session_state.total_output_tokens,
+            self.session_state.total_cache_read_tokens,
+            self.session_state.total_cache_creation_tokens,
+            self.session_state.peak_single_turn_input_tokens,
+        );
+        if interrupted {
+            return Err(SofosError::Interrupted);
+        }
+        Ok(())
+    }
+
+    /// Run the one-shot turn and save the session whatever its outcome.
+    /// Ctrl+C stops the turn the way ESC does in the TUI, keeping what
+    /// was streamed and every finished tool call; a second Ctrl+C exits
+    /// at once. Returns whether the turn was interrupted.
+    fn run_single_turn(&mut self, prompt: &str) -> Result<bool> {
+        let flag = Arc::clone(&self.interrupt_flag);
+        let ctrl_c = self.runtime.spawn(async move {
+            if tokio::signal::ctrl_c().await.is_err() {
+                return;
+            }
+            flag.store(true, Ordering::SeqCst);
+            UI::print_warning("Interrupting; press Ctrl+C again to quit immediately.");
+            if tokio::signal::ctrl_c().await.is_ok() {
+                std::process::exit(FORCED_INTERRUPT_EXIT_CODE);
+            }
+        });
         // Capture the turn result so we can persist the session even
         // when the turn errored out — without this the user can't
         // `--resume` after any failed -p invocation. Save failures are
         // logged as warnings rather than overriding the original error.
         let turn_result = self.process_message(prompt, vec![]);
+        ctrl_c.abort();
         if let Err(e) = self.save_current_session() {
             // Mirror the interactive worker: surface save failures
             // through `UI::print_warning` so they appear in the
@@ -400,15 +555,29 @@ impl Repl {
             ));
         }
         turn_result?;
-        UI::display_session_summary(
+        Ok(self.interrupt_flag.load(Ordering::SeqCst))
+    }
+
+    /// `--output json`: run the turn with its transcript on stderr, then
+    /// print one [`json_output::PromptReport`] on stdout.
+    fn process_single_prompt_json(&mut self, prompt: &str) -> Result<()> {
+        let turn_start = self.session_state.conversation.messages().len();
+        let redirect = crate::ui::stdout_redirect::StdoutToStderr::install()?;
+        let turn_result = self.run_single_turn(prompt);
+        drop(redirect);
+        let interrupted = turn_result?;
+
+        let messages = self.session_state.conversation.messages();
+        let turn = messages.get(turn_start..).unwrap_or(messages);
+        let report = json_output::PromptReport::new(
             &self.model_config.model,
-            self.session_state.total_input_tokens,
-            self.session_state.total_output_tokens,
-            self.session_state.total_cache_read_tokens,
-            self.session_state.total_cache_creation_tokens,
-            self.session_state.peak_single_turn_input_tokens,
+            turn,
+            &budget::SpendTotals::from(&self.session_state),
         );
-
+        println!("{}", serde_json::to_string(&report)?);
+        if interrupted {
+            return Err(SofosError::Interrupted);
+        }
         Ok(())
     }
 
@@ -425,20 +594,77 @@ impl Repl {
     }
 
     pub fn handle_clear_command(&mut self) -> Result<()> {
+        self.reset_history("[SYSTEM: The session history has been cleared.]");
+        println!("\n{}\n", "Conversation history cleared.".bright_yellow());
+        Ok(())
+    }
+
+    /// `/clear keep-last <n>`: start a new session holding only the last
+    /// `count` exchanges, with the token counters cut to the share of
+    /// the conversation kept. Pins among the kept messages survive.
+    pub fn handle_clear_keep_last(&mut self, count: usize) -> Result<()> {
+        let Some((display_index, index, kept)) = self.last_exchanges_start(count) else {
+            return self.handle_clear_command();
+        };
+        let conversation = &self.session_state.conversation;
+        let share = conversation.estimate_tokens_from(index) as f64
+            / conversation.estimate_tokens_from(0).max(1) as f64;
+        let kept_messages = conversation.messages()[index..].to_vec();
+        let kept_pins: Vec<usize> = conversation
+            .pinned_messages()
+            .into_iter()
+            .filter_map(|pin| pin.checked_sub(index))
+            .collect();
+        let kept_display = self.session_state.display_messages.split_off(display_index);
+        let totals = budget::SpendTotals::from(&self.session_state);
+
+        let exchanges = if kept == 1 { "exchange" } else { "exchanges" };
+        self.reset_history(&format!(
+            "[SYSTEM: The session history has been cleared except for the last {} {}, which follow.]",
+            kept, exchanges
+        ));
+        let mut messages = self.session_state.conversation.messages().to_vec();
+        let offset = messages.len();
+        messages.extend(kept_messages);
+        self.session_state
+            .conversation
+            .restore_messages(messages, kept_pins.into_iter().map(|pin| pin + offset));
+        self.session_state.display_messages = kept_display;
+        let state = &mut self.session_state;
+        state.total_input_tokens = totals.input;
+        state.total_output_tokens = totals.output;
+        state.total_cache_read_tokens = totals.cache_read;
+        state.total_cache_creation_tokens = totals.cache_creation;
+        state.peak_single_turn_input_tokens = totals.peak_single_turn_input;
+        state.scale_usage(share);
+        println!(
+            "\n{}\n",
+            format!("Conversation history cleared except for the last {kept} {exchanges}.")
+                .bright_yellow()
+        );
+        Ok(())
+    }
+
+    /// Empty the history under a new session id, then re-send what
+    /// outlives it (the mode preamble and any `/scope`) followed by `note`.
+    fn reset_history(&mut self, note: &str) {
         let new_session_id = self.history_manager.generate_unique_session_id();
         self.session_state.conversation.clear();
         self.session_state.clear(new_session_id);
+        self.tool_executor.forget_reads();
         // The active mode survives `/clear`, so the preamble has to ride
         // along too — otherwise the model proposes blocked tools (in
         // readonly mode) or assumes a different policy than is in effect.
         self.session_state
             .conversation
             .add_user_message(mode_preamble_for(self.mode, self.approval_policy));
+        // Likewise for a `/scope` narrowing, which outlives the history.
+        if self.tool_executor.scope().is_some() {
+            self.announce_scope();
+        }
         self.session_state
             .conversation
-            .add_user_message("[SYSTEM: The session history has been cleared.]".to_string());
-        println!("\n{}\n", "Conversation history cleared.".bright_yellow());
-        Ok(())
+            .add_user_message(note.to_string());
     }
 
     /// True when the active model uses adaptive thinking.
@@ -474,9 +700,19 @@ impl Repl {
         }
     }
 
+    /// Switch to the model's next supported effort level, as Ctrl+T does.
+    pub fn handle_effort_cycle(&mut self) {
+        let info = self.model_config.info();
+        if let Some(next) = info.next_effort(self.model_config.reasoning_effort) {
+            self.handle_effort_set(next);
+        }
+    }
+
     pub fn handle_effort_set(&mut self, effort: crate::api::ReasoningEffort) {
-        if let Some(msg) =
-            crate::api::model_info::effort_support_error(&self.model_config.model, effort)
+        if let Some(msg) = self
+            .model_config
+            .info()
+            .effort_support_error(&self.model_config.model, effort)
         {
             println!();
             UI::print_error(&msg);
@@ -521,6 +757,339 @@ impl Repl {
         );
     }
 
+    pub fn handle_verbosity_set(&mut self, verbosity: crate::config::Verbosity) {
+        if !self.session_state.conversation.set_verbosity(verbosity) {
+            println!();
+            UI::print_error(
+                "The system prompt has no response-length guidance to replace, so verbosity cannot be changed in this session.",
+            );
+            println!();
+            return;
+        }
+        self.verbosity = verbosity;
+        println!(
+            "\n{} {}\n",
+            "Verbosity:".bright_green(),
+            verbosity.as_label()
+        );
+    }
+
+    /// `/scope <dir>`: narrow the tools to a subdirectory of the project.
+    pub fn handle_scope_set(&mut self, dir: &str) -> Result<()> {
+        let scope = self.tool_executor.set_scope(Some(dir))?;
+        self.announce_scope();
+        if scope.as_os_str().is_empty() {
+            println!("\n{}\n", "Scope: project root".bright_green());
+        } else {
+            println!(
+                "\n{} {}\n",
+                "Scope:".bright_green(),
+                scope.display().to_string().bright_white()
+            );
+        }
+        Ok(())
+    }
+
+    /// `/scope reset`: work from the project root again.
+    pub fn handle_scope_reset(&mut self) -> Result<()> {
+        if self.tool_executor.scope().is_none() {
+            println!("\n{}\n", "Scope is already the project root".dimmed());
+            return Ok(());
+        }
+        self.tool_executor.set_scope(None)?;
+        self.announce_scope();
+        println!("\n{}\n", "Scope reset to the project root".bright_green());
+        Ok(())
+    }
+
+    /// `/scope` with no argument: print the active scope.
+    pub fn handle_scope_show(&self) {
+        let scope = match self.tool_executor.scope() {
+            Some(dir) => dir.display().to_string(),
+            None => "project root".to_string(),
+        };
+        println!();
+        println!("{} {}", "Scope:".bright_green(), scope.bright_white());
+        println!(
+            "{}",
+            "Narrow with `/scope <subdirectory>`, restore with `/scope reset`".dimmed()
+        );
+        println!();
+    }
+
+    /// `/files`: every file the file tools touched this session, grouped
+    /// by operation.
+    pub fn handle_files_show(&self) {
+        let files =
+            crate::session::SessionFiles::from_messages(&self.session_state.display_messages);
+        println!();
+        println!("{}", files.render());
+        println!();
+    }
+
+    /// `/confirm`: turn asking before each file change on or off.
+    pub fn handle_confirm_toggle(&mut self) {
+        let enabled = !self.tool_executor.confirm_edits();
+        self.tool_executor.set_confirm_edits(enabled);
+        if enabled {
+            println!(
+                "\n{}\n",
+                "Confirm edits: on. File changes are previewed and need your approval"
+                    .bright_green()
+            );
+        } else {
+            println!(
+                "\n{}\n",
+                "Confirm edits: off. File changes apply without asking".bright_green()
+            );
+        }
+    }
+
+    /// `/diff`: the workspace's uncommitted changes.
+    pub fn handle_diff_show(&self) {
+        match self.tool_executor.uncommitted_diff() {
+            Ok(Some(diff)) => println!("\n{}\n", diff),
+            Ok(None) => println!(
+                "\n{}\n",
+                "Not a git repository, and --backups is off: there is nothing to compare against."
+                    .yellow()
+            ),
+            Err(e) => UI::print_error_with_hint(&e),
+        }
+    }
+
+    /// `/cost`: the tokens and estimated cost of the session so far.
+    pub fn handle_cost_show(&self) {
+        let state = &self.session_state;
+        UI::display_session_cost(
+            &self.model_config.model,
+            state.total_input_tokens,
+            state.total_output_tokens,
+            state.total_cache_read_tokens,
+            state.total_cache_creation_tokens,
+            state.peak_single_turn_input_tokens,
+        );
+    }
+
+    /// `/tokens`: how much of the context window the conversation fills.
+    pub fn handle_tokens_show(&self) {
+        let conversation = &self.session_state.conversation;
+        UI::display_context_usage(
+            conversation.estimate_total_tokens(),
+            conversation.max_context_tokens(),
+        );
+    }
+
+    /// `/pin`: keep the last prompt through trimming and compaction.
+    pub fn handle_pin(&mut self) {
+        let Some(prompt) = self.session_state.conversation.pin_last_prompt() else {
+            println!("\n{}\n", "Nothing to pin.".yellow());
+            return;
+        };
+        let first_line = prompt.lines().next().unwrap_or_default();
+        println!(
+            "\n{} {}\n",
+            "Pinned:".bright_green(),
+            truncate_description(first_line).dimmed()
+        );
+    }
+
+    /// `/unpin`: let trimming and compaction drop pinned messages again.
+    pub fn handle_unpin(&mut self) {
+        let count = self.session_state.conversation.unpin_all();
+        if count == 0 {
+            println!("\n{}\n", "Nothing is pinned.".yellow());
+            return;
+        }
+        let messages = if count == 1 { "message" } else { "messages" };
+        println!(
+            "\n{}\n",
+            format!("Unpinned {count} {messages}.").bright_green()
+        );
+    }
+
+    /// Where the last `count` exchanges start: the display and
+    /// conversation indices of the earliest of their prompts, and how
+    /// many exchanges that covers. Exchanges already compacted or
+    /// cleared away are not counted.
+    fn last_exchanges_start(&self, count: usize) -> Option<(usize, usize, usize)> {
+        let state = &self.session_state;
+        let prompts = state
+            .display_messages
+            .iter()
+            .enumerate()
+            .rev()
+            .filter_map(|(display_index, message)| match message {
+                DisplayMessage::UserMessage { content } => Some((display_index, content)),
+                _ => None,
+            })
+            .take(count);
+        let mut cut = None;
+        let mut found = 0;
+        let mut before = state.conversation.messages().len();
+        for (display_index, prompt) in prompts {
+            let Some(index) = state.conversation.prompt_index_before(prompt, before) else {
+                break;
+            };
+            cut = Some((display_index, index));
+            before = index;
+            found += 1;
+        }
+        cut.map(|(display_index, index)| (display_index, index, found))
+    }
+
+    /// `/undo [n]`: drop the last `count` prompts and everything after
+    /// them, from both the conversation and the transcript. Stops early at
+    /// a prompt that was compacted away, so the startup preamble and any
+    /// summary are never removed.
+    pub fn handle_undo(&mut self, count: usize) {
+        let Some((display_index, index, undone)) = self.last_exchanges_start(count) else {
+            println!("\n{}\n", "Nothing to undo.".yellow());
+            return;
+        };
+
+        let removed = self.session_state.conversation.messages().len() - index;
+        self.session_state.conversation.truncate(index);
+        self.session_state.display_messages.truncate(display_index);
+        let exchanges = if undone == 1 { "exchange" } else { "exchanges" };
+        let messages = if removed == 1 { "message" } else { "messages" };
+        println!(
+            "\n{}",
+            format!("Undid {undone} {exchanges} ({removed} {messages} removed).").bright_green()
+        );
+        if undone < count {
+            println!(
+                "{}",
+                "Earlier exchanges were compacted or cleared and can't be undone.".dimmed()
+            );
+        }
+        println!();
+    }
+
+    /// `/tools`: every tool the model can call this turn, then the
+    /// optional tools and MCP servers with whether each is working.
+    pub fn handle_tools_show(&self) {
+        use crate::mcp::manager::McpServerStatus;
+
+        let (tools, mcp_statuses) = self.runtime.block_on(async {
+            (
+                self.tool_executor.get_available_tools().await,
+                self.tool_executor.mcp_server_statuses().await,
+            )
+        });
+        let entries: Vec<(&str, &str)> = tools
+            .iter()
+            .filter(|tool| self.client.sends_tool(tool))
+            .map(|tool| match tool {
+                crate::api::Tool::Regular {
+                    name, description, ..
+                } => (name.as_str(), description.lines().next().unwrap_or("")),
+                crate::api::Tool::AnthropicWebSearch { name, .. } => {
+                    (name.as_str(), WEB_SEARCH_DESCRIPTION)
+                }
+                crate::api::Tool::OpenAIWebSearch { .. } => {
+                    (ToolName::WebSearch.as_str(), WEB_SEARCH_DESCRIPTION)
+                }
+            })
+            .collect();
+        let width = entries
+            .iter()
+            .map(|(name, _)| name.len())
+            .max()
+            .unwrap_or(0);
+
+        println!();
+        println!("{} ({})", "Tools:".bright_green(), entries.len());
+        for (name, description) in &entries {
+            println!(
+                "  {}  {}",
+                format!("{:<width$}", name, width = width).bright_white(),
+                truncate_description(description).dimmed()
+            );
+        }
+
+        let check = |ok: bool| {
+            if ok {
+                "✓".bright_green()
+            } else {
+                "✗".bright_red()
+            }
+        };
+        println!("{}", "Optional tools:".bright_cyan());
+        let has_code_search = self.tool_executor.has_code_search();
+        println!(
+            "  {} search_code      {}",
+            check(has_code_search),
+            if has_code_search {
+                "ripgrep found"
+            } else {
+                "ripgrep not installed"
+            }
+        );
+        let has_morph = self.tool_executor.has_morph();
+        println!(
+            "  {} morph_edit_file  {}",
+            check(has_morph),
+            if has_morph {
+                "Morph enabled"
+            } else {
+                "MORPH_API_KEY not set"
+            }
+        );
+
+        println!("{}", "MCP servers:".bright_cyan());
+        if mcp_statuses.is_empty() {
+            println!("  {}", "none configured".dimmed());
+        }
+        for (name, status) in &mcp_statuses {
+            match status {
+                McpServerStatus::Connected { tools } => {
+                    println!("  {} {} ({} tools)", check(true), name.bright_cyan(), tools)
+                }
+                McpServerStatus::Stopped => println!(
+                    "  {} {}  {}",
+                    "!".bright_yellow(),
+                    name.bright_cyan(),
+                    "stopped after a request timed out".dimmed()
+                ),
+                McpServerStatus::Failed(reason) => println!(
+                    "  {} {}  {}",
+                    check(false),
+                    name.bright_cyan(),
+                    truncate_description(reason).dimmed()
+                ),
+            }
+        }
+        println!();
+    }
+
+    /// Tell the assistant about the active scope.
+    fn announce_scope(&mut self) {
+        let message = crate::config::scope_message(self.tool_executor.scope());
+        self.session_state.conversation.add_user_message(message);
+    }
+
+    /// `/verbosity` with no argument: print the current level and the
+    /// available ones.
+    pub fn handle_verbosity_show(&self) {
+        let levels: Vec<&str> = crate::config::VERBOSITY_LEVELS
+            .iter()
+            .map(|level| level.as_label())
+            .collect();
+        println!();
+        println!(
+            "{} {}",
+            "Verbosity:".bright_green(),
+            self.verbosity.as_label().bright_white()
+        );
+        println!(
+            "{} {}  (switch with `/verbosity <level>`)",
+            "Available:".bright_cyan(),
+            levels.join(", ")
+        );
+        println!();
+    }
+
     /// Non-interactive fallback for `/mode`. The TUI opens the picker;
     /// this path prints the current mode and the available options in
     /// `--prompt` mode.
@@ -549,7 +1118,7 @@ impl Repl {
     /// Non-interactive fallback for `/effort`. The TUI opens the
     /// picker; this path lists supported levels in `--prompt` mode.
     pub fn handle_effort_picker_fallback(&self) {
-        let info = crate::api::model_info::lookup(&self.model_config.model);
+        let info = self.model_config.info();
         let current = self.model_config.reasoning_effort;
         println!();
         println!(
@@ -575,18 +1144,31 @@ impl Repl {
         println!();
     }
 
-    /// Switch the active model to `name`. Refuses unsupported slugs,
-    /// cross-provider switches (we can't swap the constructed
-    /// [`LlmClient`] mid-session without re-reading API keys), and
-    /// switches that would leave the current reasoning effort
-    /// orphaned on a model that doesn't accept it. On success the
+    /// Whether `/model` can move this session onto `provider`: always for
+    /// the current one, and for another when its API key is set and no
+    /// custom `--api-base` pins every request to one endpoint.
+    pub(super) fn can_switch_to(&self, provider: crate::api::model_info::Provider) -> bool {
+        provider == self.model_config.info().provider
+            || (!self.model_config.custom_endpoint && self.provider_keys.has_key_for(provider))
+    }
+
+    /// Switch the active model to `name`. Refuses unsupported slugs and
+    /// a provider whose API key is missing. Within one provider it also
+    /// refuses switches that would leave the current reasoning effort or
+    /// mode orphaned; across providers those settings are reset to ones
+    /// the new model accepts, since the old ones rarely carry over. On
+    /// success the client is rebuilt if the provider changed, and the
     /// per-model context-window and auto-compact thresholds are
     /// refreshed so the new ceilings take effect immediately.
     pub fn handle_model_set(&mut self, name: &str) {
-        let Some(choice) = crate::api::model_info::canonical_model(name) else {
+        use crate::api::model_info;
+
+        let expanded = model_info::expand_alias(name);
+        let name = expanded.as_deref().unwrap_or(name);
+        let Some(choice) = model_info::canonical_model(name) else {
             println!();
             UI::print_error(
-                &crate::api::model_info::model_support_error(name)
+                &model_info::model_support_error(name)
                     .unwrap_or_else(|| format!("Model `{}` is not supported.", name)),
             );
             println!();
@@ -602,56 +1184,105 @@ impl Repl {
             return;
         }
 
-        let current_provider = crate::api::model_info::provider_for(&self.model_config.model);
-        if choice.provider != current_provider {
+        let current_provider = self.model_config.info().provider;
+        let switching_provider = choice.provider != current_provider;
+        if switching_provider && !self.can_switch_to(choice.provider) {
+            let reason = if self.model_config.custom_endpoint {
+                "this session sends every request to the --api-base endpoint".to_string()
+            } else {
+                format!("{} is not set", choice.provider.api_key_env())
+            };
             println!();
             UI::print_error(&format!(
-                "Cannot switch to `{}` ({}) from the current {} session. \
-                 Re-launch with `--model {}` to use it.",
+                "Cannot switch to `{}` ({}): {}. Keeping `{}`.",
                 choice.name,
                 choice.provider.label(),
-                current_provider.label(),
-                choice.name
+                reason,
+                self.model_config.model
             ));
             println!();
             return;
         }
 
-        if let Some(msg) = crate::api::model_info::effort_support_error(
-            choice.name,
-            self.model_config.reasoning_effort,
-        ) {
-            println!();
-            UI::print_error(&format!(
-                "{} Run `/effort <level>` to pick a supported level before switching.",
-                msg
-            ));
-            println!();
-            return;
+        let mut effort = self.model_config.reasoning_effort;
+        let mut mode = self.model_config.reasoning_mode;
+        if let Some(msg) = model_info::effort_support_error(choice.name, effort) {
+            if !switching_provider {
+                println!();
+                UI::print_error(&format!(
+                    "{} Run `/effort <level>` to pick a supported level before switching.",
+                    msg
+                ));
+                println!();
+                return;
+            }
+            let supported = choice.supported_efforts;
+            effort = if supported.contains(&crate::api::ReasoningEffort::default()) {
+                crate::api::ReasoningEffort::default()
+            } else {
+                supported.first().copied().unwrap_or_default()
+            };
+        }
+        if let Some(msg) = model_info::mode_support_error(choice.name, mode) {
+            if !switching_provider {
+                println!();
+                UI::print_error(&format!("{} Run `/mode standard` before switching.", msg));
+                println!();
+                return;
+            }
+            mode = crate::api::ReasoningMode::Standard;
         }
 
-        if let Some(msg) = crate::api::model_info::mode_support_error(
-            choice.name,
-            self.model_config.reasoning_mode,
-        ) {
-            println!();
-            UI::print_error(&format!("{} Run `/mode standard` before switching.", msg));
-            println!();
-            return;
+        if switching_provider {
+            match LlmClient::for_provider(choice.provider, &self.provider_keys) {
+                Ok(client) => {
+                    self.client = client.with_retry_policy(self.client.retry_policy());
+                }
+                Err(e) => {
+                    println!();
+                    UI::print_error_with_hint(&e);
+                    println!();
+                    return;
+                }
+            }
         }
 
+        let effort_reset = effort != self.model_config.reasoning_effort;
+        let mode_reset = mode != self.model_config.reasoning_mode;
         self.model_config.model = choice.name.to_string();
+        self.model_config.set_reasoning_effort(effort);
+        self.model_config.set_reasoning_mode(mode);
         self.session_state
             .conversation
-            .set_max_context_tokens(crate::config::max_context_tokens_for(choice.name));
+            .set_max_context_tokens(crate::config::max_context_tokens_for(choice));
         self.session_state
             .conversation
-            .set_auto_compact_token_limit(crate::config::auto_compact_token_limit_for(choice.name));
+            .set_auto_compact_token_limit(crate::config::auto_compact_token_limit_for(choice));
         println!(
-            "\n{} {}\n",
+            "\n{} {}",
             "Model:".bright_green(),
             choice.name.bright_white()
         );
+        if switching_provider {
+            println!(
+                "{}",
+                format!(
+                    "Now using {}. Thinking settings follow the new model and may differ from before.",
+                    choice.provider.label()
+                )
+                .dimmed()
+            );
+        }
+        if effort_reset {
+            println!(
+                "{}",
+                format!("Reasoning effort reset to {}.", effort.as_label()).dimmed()
+            );
+        }
+        if mode_reset {
+            println!("{}", "Reasoning mode reset to standard.".dimmed());
+        }
+        println!();
     }
 
     /// Non-interactive fallback for `/model` (no argument). The TUI
@@ -794,6 +1425,67 @@ impl Repl {
         );
     }
 
+    /// `/permissions rules`: the merged allow, deny, and ask lists, each
+    /// rule marked with the file it comes from.
+    pub fn handle_permission_rules_show(&self) -> Result<()> {
+        let manager = PermissionManager::new(self.tool_executor.workspace().to_path_buf())?;
+        println!();
+        for list in RuleList::ALL {
+            let rules = manager.rules(list);
+            println!(
+                "{} ({})",
+                format!("{}:", list.name()).bright_cyan(),
+                rules.len()
+            );
+            for rule in rules {
+                println!(
+                    "  {}  {}",
+                    rule.bright_white(),
+                    manager.get_rule_source(rule).dimmed()
+                );
+            }
+        }
+        println!();
+        println!(
+            "{}",
+            "Add or move a rule with `/permissions allow|deny|ask <rule>`, remove one with `/permissions remove <rule>`."
+                .dimmed()
+        );
+        println!();
+        Ok(())
+    }
+
+    /// `/permissions allow|deny|ask <rule>`: add `rule` to `list` in the
+    /// local config, moving it out of any other list.
+    pub fn handle_permission_rule_set(&self, list: RuleList, rule: &str) -> Result<()> {
+        let mut manager = PermissionManager::new(self.tool_executor.workspace().to_path_buf())?;
+        let message = match manager.set_rule(rule, list)? {
+            Some(previous) if previous == list => {
+                format!("{} is already in {}.", rule.trim(), list.name())
+            }
+            Some(previous) => format!(
+                "Moved {} from {} to {}.",
+                rule.trim(),
+                previous.name(),
+                list.name()
+            ),
+            None => format!("Added {} to {}.", rule.trim(), list.name()),
+        };
+        println!("\n{}\n", message.bright_green());
+        Ok(())
+    }
+
+    /// `/permissions remove <rule>`: drop `rule` from the local config.
+    pub fn handle_permission_rule_remove(&self, rule: &str) -> Result<()> {
+        let mut manager = PermissionManager::new(self.tool_executor.workspace().to_path_buf())?;
+        let list = manager.remove_rule(rule)?;
+        println!(
+            "\n{}\n",
+            format!("Removed {} from {}.", rule.trim(), list.name()).bright_green()
+        );
+        Ok(())
+    }
+
     fn print_mcp_readonly_summary(&self) {
         let summary = format_mcp_readonly_summary(
             &self.tool_executor.mcp_servers_excluded_from_readonly(),
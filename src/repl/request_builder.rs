@@ -1,7 +1,10 @@
-use crate::api::LlmClient::{Anthropic, OpenAI};
-use crate::api::{CreateMessageRequest, LlmClient, ReasoningEffort, ReasoningMode, Tool};
+use crate::api::LlmClient::Anthropic;
+use crate::api::{CreateMessageRequest, LlmClient, ReasoningEffort, ReasoningMode, Sampling, Tool};
 use crate::repl::conversation::ConversationHistory;
 
+/// Result sent for a tool call the history never answered.
+const INTERRUPTED_TOOL_RESULT: &str = "[interrupted: the tool call did not return a result]";
+
 pub struct RequestBuilder<'a> {
     client: &'a LlmClient,
     model: &'a str,
@@ -10,6 +13,7 @@ pub struct RequestBuilder<'a> {
     tools: Vec<Tool>,
     reasoning_effort: ReasoningEffort,
     reasoning_mode: ReasoningMode,
+    sampling: Sampling,
     /// Stable per-session identifier sent as `prompt_cache_key` on the
     /// OpenAI Responses path. Anthropic ignores it.
     session_id: &'a str,
@@ -33,6 +37,7 @@ impl<'a> RequestBuilder<'a> {
             tools,
             reasoning_effort,
             reasoning_mode: ReasoningMode::default(),
+            sampling: Sampling::default(),
             session_id,
         }
     }
@@ -45,6 +50,12 @@ impl<'a> RequestBuilder<'a> {
         self
     }
 
+    /// Set `temperature` and `top_p` (both unset by default).
+    pub fn with_sampling(mut self, sampling: Sampling) -> Self {
+        self.sampling = sampling;
+        self
+    }
+
     pub fn build(self) -> CreateMessageRequest {
         let is_anthropic = matches!(self.client, Anthropic(_));
         let adaptive =
@@ -79,10 +90,11 @@ impl<'a> RequestBuilder<'a> {
             (None, None)
         };
 
-        let reasoning_config = if matches!(self.client, OpenAI(_)) {
+        let reasoning_config = if !is_anthropic {
             // Every effort seen here already passed the per-model gate
             // (startup validation + `/effort`), so each level maps
-            // straight onto its OpenAI wire label.
+            // straight onto its OpenAI wire label. Gemini reads the same
+            // label and turns it into a thinking budget.
             let mut reasoning = match self.reasoning_effort {
                 ReasoningEffort::Low => crate::api::Reasoning::with_effort("low"),
                 ReasoningEffort::Medium => crate::api::Reasoning::with_effort("medium"),
@@ -99,7 +111,7 @@ impl<'a> RequestBuilder<'a> {
             None
         };
 
-        // Send system prompt to both Anthropic and OpenAI; cache hints are handled per API
+        // Send system prompt to every provider; cache hints are handled per API
         let system_prompt = Some(self.conversation.system_prompt().clone());
 
         // Anthropic server-side compaction. Enabled only on models that
@@ -145,7 +157,14 @@ impl<'a> RequestBuilder<'a> {
             reasoning: reasoning_config,
             prompt_cache_key: Some(self.session_id.to_string()),
             context_management,
+            temperature: self.sampling.temperature,
+            top_p: self.sampling.top_p,
         };
+        let inserted = answer_orphaned_tool_uses(&mut request.messages);
+        let cache_anchor = self
+            .conversation
+            .cache_anchor_message_idx()
+            .map(|idx| idx + inserted.iter().filter(|&&at| at <= idx).count());
 
         // Anthropic prompt caching is opt-in per content block. We mark
         // two breakpoints in addition to the system prompt (already
@@ -182,16 +201,111 @@ impl<'a> RequestBuilder<'a> {
                     }
                 }
             }
-            mark_rolling_cache_breakpoint(
-                &mut request.messages,
-                self.conversation.cache_anchor_message_idx(),
-            );
+            mark_rolling_cache_breakpoint(&mut request.messages, cache_anchor);
         }
 
         request
     }
 }
 
+/// Answer every `tool_use` with no `tool_result` in the message after
+/// it, as left by a turn interrupted between the model's call and the
+/// tool's result; every provider rejects such a history. The synthetic
+/// results go first in the following user message, or in a new one when
+/// nothing or another assistant turn follows. Returns the original
+/// indices that new messages were inserted before, so the cache anchor
+/// can be shifted to match.
+fn answer_orphaned_tool_uses(messages: &mut Vec<crate::api::Message>) -> Vec<usize> {
+    use crate::api::{Message, MessageContentBlock};
+
+    let mut inserted = Vec::new();
+    let mut idx = 0;
+    while idx < messages.len() {
+        let unanswered = unanswered_tool_uses(&messages[idx], messages.get(idx + 1));
+        if unanswered.is_empty() {
+            idx += 1;
+            continue;
+        }
+        let results: Vec<MessageContentBlock> = unanswered
+            .into_iter()
+            .map(|tool_use_id| MessageContentBlock::ToolResult {
+                tool_use_id,
+                content: INTERRUPTED_TOOL_RESULT.to_string(),
+                cache_control: None,
+            })
+            .collect();
+        match messages.get_mut(idx + 1) {
+            Some(next) if next.role == "user" => prepend_blocks(next, results),
+            _ => {
+                inserted.push(idx + 1 - inserted.len());
+                messages.insert(idx + 1, Message::user_with_tool_results(results));
+            }
+        }
+        idx += 2;
+    }
+    inserted
+}
+
+/// Ids of the `tool_use` blocks in an assistant `message` that `next`
+/// carries no `tool_result` for.
+fn unanswered_tool_uses(
+    message: &crate::api::Message,
+    next: Option<&crate::api::Message>,
+) -> Vec<String> {
+    use crate::api::{MessageContent, MessageContentBlock};
+
+    let MessageContent::Blocks { content } = &message.content else {
+        return Vec::new();
+    };
+    if message.role != "assistant" {
+        return Vec::new();
+    }
+    let answered: Vec<&str> = match next.map(|next| (next.role.as_str(), &next.content)) {
+        Some(("user", MessageContent::Blocks { content })) => content
+            .iter()
+            .filter_map(|block| match block {
+                MessageContentBlock::ToolResult { tool_use_id, .. } => Some(tool_use_id.as_str()),
+                _ => None,
+            })
+            .collect(),
+        _ => Vec::new(),
+    };
+    content
+        .iter()
+        .filter_map(|block| match block {
+            MessageContentBlock::ToolUse { id, .. } if !answered.contains(&id.as_str()) => {
+                Some(id.clone())
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// Put `blocks` ahead of `message`'s content; tool results must lead a
+/// user turn. Plain text content becomes a trailing text block.
+fn prepend_blocks(
+    message: &mut crate::api::Message,
+    mut blocks: Vec<crate::api::MessageContentBlock>,
+) {
+    use crate::api::{MessageContent, MessageContentBlock};
+
+    match &mut message.content {
+        MessageContent::Blocks { content } => {
+            blocks.append(content);
+            *content = blocks;
+        }
+        MessageContent::Text { content } => {
+            if !content.is_empty() {
+                blocks.push(MessageContentBlock::Text {
+                    text: std::mem::take(content),
+                    cache_control: None,
+                });
+            }
+            message.content = MessageContent::Blocks { content: blocks };
+        }
+    }
+}
+
 /// Stamp `cache_control: ephemeral` on the rolling breakpoint (last
 /// block of the last message) and, if `anchor_idx` is provided, on the
 /// last block of `messages[anchor_idx]`. The anchor is the secondary
@@ -257,7 +371,7 @@ mod tests {
     }
 
     fn openai_client() -> LlmClient {
-        LlmClient::OpenAI(OpenAIClient::new("test-key".to_string()).unwrap())
+        LlmClient::OpenAI(OpenAIClient::new("test-key".to_string(), None).unwrap())
     }
 
     fn one_regular_tool() -> Vec<Tool> {
@@ -390,6 +504,90 @@ mod tests {
         assert!(cc.is_none(), "OpenAI must not stamp Anthropic markers");
     }
 
+    #[test]
+    fn tool_calls_cut_off_by_an_interrupt_get_synthetic_results() {
+        use crate::api::MessageContent;
+
+        let tool_use = |id: &str| MessageContentBlock::ToolUse {
+            id: id.to_string(),
+            name: "execute_bash".to_string(),
+            input: serde_json::json!({"command": "cargo test"}),
+            cache_control: None,
+        };
+        // Interrupted mid-tool: the next prompt follows the call directly.
+        let mut conv = ConversationHistory::new();
+        conv.add_user_message("run the tests".to_string());
+        conv.add_assistant_with_blocks(vec![tool_use("call_1")]);
+        conv.add_user_message("never mind, just build".to_string());
+        // Interrupted again, with nothing after the call yet.
+        conv.add_assistant_with_blocks(vec![tool_use("call_2")]);
+
+        let request = RequestBuilder::new(
+            &anthropic_client(),
+            crate::api::model_info::CLAUDE_SONNET,
+            8192,
+            &conv,
+            one_regular_tool(),
+            ReasoningEffort::Medium,
+            "s1",
+        )
+        .build();
+
+        assert_eq!(request.messages.len(), 5);
+        let blocks = |idx: usize| match &request.messages[idx].content {
+            MessageContent::Blocks { content } => content.clone(),
+            MessageContent::Text { .. } => panic!("expected blocks in message {idx}"),
+        };
+        let first = blocks(2);
+        assert!(matches!(
+            &first[0],
+            MessageContentBlock::ToolResult { tool_use_id, content, .. }
+                if tool_use_id == "call_1" && content == INTERRUPTED_TOOL_RESULT
+        ));
+        assert!(matches!(
+            &first[1],
+            MessageContentBlock::Text { text, .. } if text == "never mind, just build"
+        ));
+        assert_eq!(request.messages[4].role, "user");
+        assert!(matches!(
+            &blocks(4)[0],
+            MessageContentBlock::ToolResult { tool_use_id, .. } if tool_use_id == "call_2"
+        ));
+
+        // The OpenAI wire form pairs every call with an output too.
+        let input = crate::api::openai::wire::build_response_input(&request);
+        let call_ids = |kind: &str| -> Vec<String> {
+            input
+                .iter()
+                .filter(|item| item["type"] == kind)
+                .filter_map(|item| item["call_id"].as_str().map(str::to_string))
+                .collect()
+        };
+        assert_eq!(call_ids("function_call"), ["call_1", "call_2"]);
+        assert_eq!(call_ids("function_call_output"), ["call_1", "call_2"]);
+    }
+
+    #[test]
+    fn answered_tool_calls_are_left_alone() {
+        let mut messages = vec![
+            Message::user("read it"),
+            Message::assistant_with_blocks(vec![MessageContentBlock::ToolUse {
+                id: "call_1".to_string(),
+                name: "read_file".to_string(),
+                input: serde_json::json!({"path": "a.rs"}),
+                cache_control: None,
+            }]),
+            Message::user_with_tool_results(vec![MessageContentBlock::ToolResult {
+                tool_use_id: "call_1".to_string(),
+                content: "fn a() {}".to_string(),
+                cache_control: None,
+            }]),
+        ];
+        let before = serde_json::to_value(&messages).unwrap();
+        assert!(answer_orphaned_tool_uses(&mut messages).is_empty());
+        assert_eq!(serde_json::to_value(&messages).unwrap(), before);
+    }
+
     #[test]
     fn rolling_breakpoint_is_noop_on_plain_text_user_message() {
         let mut messages = vec![Message::user("just text")];
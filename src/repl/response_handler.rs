@@ -2,17 +2,219 @@ use crate::api::{ContentBlock, CreateMessageRequest, LlmClient};
 use crate::config::SofosConfig;
 use crate::error::{Result, SofosError};
 use crate::repl::SteerBuffer;
+use crate::repl::budget::{Budget, SpendTotals};
 use crate::repl::conversation::ConversationHistory;
 use crate::repl::request_builder::RequestBuilder;
 use crate::session::DisplayMessage;
 use crate::tools::ToolExecutor;
 use crate::ui::UI;
 use colored::Colorize;
+use std::collections::HashMap;
 use std::io::Write;
 use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, Ordering};
 use tokio::time::{Duration, sleep};
 
+/// Stop reason Anthropic returns when a long-running server tool (such as
+/// web search) paused the turn; re-sending the conversation lets the
+/// model finish it.
+const STOP_REASON_PAUSE_TURN: &str = "pause_turn";
+
+/// Consecutive `pause_turn` responses resumed before giving up, so a
+/// turn that never finishes cannot loop forever.
+const MAX_PAUSE_TURN_CONTINUATIONS: u32 = 10;
+
+/// Stop reason for a response cut off at the output token limit.
+const STOP_REASON_MAX_TOKENS: &str = "max_tokens";
+
+/// Cut-off responses continued in one turn with `--auto-continue`
+/// before the truncation is reported as usual.
+const MAX_AUTO_CONTINUATIONS: u32 = 3;
+
+/// User message asking the model to pick up a cut-off response.
+const AUTO_CONTINUE_PROMPT: &str = "[Your previous response was cut off by the output token limit. \
+     Continue exactly where it stopped, without repeating what you already wrote.]";
+
+/// Stop reason Anthropic returns when the model declines to answer.
+const STOP_REASON_REFUSAL: &str = "refusal";
+
+/// Stored in place of a refused response. Anthropic asks for the refused
+/// turn to be replaced before the conversation continues, and any partial
+/// text or tool calls in it are not safe to send back.
+const REFUSAL_PLACEHOLDER: &str = "[Declined to respond to this request.]";
+
+/// Stop reasons for a response that finished on its own.
+const STOP_REASON_END_TURN: &str = "end_turn";
+const STOP_REASON_STOP_SEQUENCE: &str = "stop_sequence";
+
+/// Stop reason for a response that ends in tool calls.
+const STOP_REASON_TOOL_USE: &str = "tool_use";
+
+/// Stop reason for a response cut off by a full context window.
+const STOP_REASON_CONTEXT_WINDOW_EXCEEDED: &str = "model_context_window_exceeded";
+
+/// OpenAI's finish reason for a response its content filter stopped.
+const STOP_REASON_CONTENT_FILTER: &str = "content_filter";
+
+/// Why a response ended. OpenAI and Gemini map their finish reasons onto
+/// Anthropic's `stop_reason` strings, so one reading covers every
+/// provider, for the first response of a turn and every follow-up.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum StopReason {
+    /// `end_turn`, `stop_sequence`, or no reason given.
+    Finished,
+    ToolUse,
+    MaxTokens,
+    /// The conversation filled the model's context window mid-response.
+    ContextWindowExceeded,
+    PauseTurn,
+    /// Anthropic's `refusal`, or OpenAI's `content_filter`.
+    Refusal,
+    Unknown(String),
+}
+
+impl StopReason {
+    fn parse(reason: Option<&str>) -> Self {
+        match reason {
+            None | Some(STOP_REASON_END_TURN | STOP_REASON_STOP_SEQUENCE) => Self::Finished,
+            Some(STOP_REASON_TOOL_USE) => Self::ToolUse,
+            Some(STOP_REASON_MAX_TOKENS) => Self::MaxTokens,
+            Some(STOP_REASON_CONTEXT_WINDOW_EXCEEDED) => Self::ContextWindowExceeded,
+            Some(STOP_REASON_PAUSE_TURN) => Self::PauseTurn,
+            Some(STOP_REASON_REFUSAL | STOP_REASON_CONTENT_FILTER) => Self::Refusal,
+            Some(other) => Self::Unknown(other.to_string()),
+        }
+    }
+
+    /// Whether the response was cut off, so its tool calls may be
+    /// half-formed.
+    fn is_truncated(&self) -> bool {
+        matches!(self, Self::MaxTokens | Self::ContextWindowExceeded)
+    }
+}
+
+/// Tool result for a call stopped by ESC, and for every call after it
+/// in the same response.
+const INTERRUPTED_TOOL_RESULT: &str = "[interrupted by user]";
+
+/// Tools that never change anything, so consecutive calls to them in one
+/// response run concurrently. A read outside the workspace may still
+/// prompt; prompts from concurrent calls are asked one at a time.
+const CONCURRENT_TOOLS: &[crate::tools::ToolName] = &[
+    crate::tools::ToolName::ReadFile,
+    crate::tools::ToolName::ListDirectory,
+    crate::tools::ToolName::Tree,
+    crate::tools::ToolName::SearchCode,
+];
+
+fn runs_concurrently(tool_name: &str) -> bool {
+    CONCURRENT_TOOLS
+        .iter()
+        .any(|tool| tool.as_str() == tool_name)
+}
+
+/// Position of the nearest earlier call identical to `tool_uses[index]`,
+/// provided only read-only calls ran in between. A write, bash command
+/// or MCP call in between may have changed what the repeat would see.
+fn duplicate_of(tool_uses: &[(String, String, serde_json::Value)], index: usize) -> Option<usize> {
+    let (_, tool_name, tool_input) = &tool_uses[index];
+    let first = tool_uses[..index]
+        .iter()
+        .rposition(|(_, name, input)| name == tool_name && input == tool_input)?;
+    tool_uses[first + 1..index]
+        .iter()
+        .all(|(_, name, _)| {
+            crate::tools::ToolName::from_str(name).is_ok_and(|tool| tool.is_read_only_safe())
+        })
+        .then_some(first)
+}
+
+/// Run the consecutive concurrent-safe calls starting at `start` together,
+/// skipping repeats of earlier calls, and key each result by its position
+/// in `tool_uses`. The run ends at the first call that has to run alone,
+/// so a read never overtakes a write before it. The file tools block, so
+/// each call gets its own blocking thread; one abandoned by ESC finishes
+/// in the background.
+async fn execute_concurrent_run(
+    tool_uses: &[(String, String, serde_json::Value)],
+    start: usize,
+    executor: &ToolExecutor,
+) -> HashMap<usize, Result<crate::tools::executor::ToolExecutionResult>> {
+    let runtime = tokio::runtime::Handle::current();
+    let tasks: Vec<_> = (start..tool_uses.len())
+        .take_while(|&index| runs_concurrently(&tool_uses[index].1))
+        .filter(|&index| duplicate_of(tool_uses, index).is_none())
+        .map(|index| {
+            let (_, name, input) = tool_uses[index].clone();
+            let executor = executor.clone();
+            let runtime = runtime.clone();
+            let task = tokio::task::spawn_blocking(move || {
+                runtime.block_on(executor.execute(&name, &input))
+            });
+            (index, task)
+        })
+        .collect();
+    let mut results = HashMap::new();
+    for (index, task) in tasks {
+        let result = task.await.unwrap_or_else(|e| {
+            Err(SofosError::ToolExecution(format!(
+                "Tool call stopped unexpectedly: {}",
+                e
+            )))
+        });
+        results.insert(index, result);
+    }
+    results
+}
+
+/// Run `fut` unless the interrupt flag is set first; `None` when ESC won.
+/// Dropping the future abandons the call: an MCP request is cancelled,
+/// and `execute_bash` also watches the flag and kills its child.
+async fn until_interrupted<T>(
+    flag: &AtomicBool,
+    fut: impl std::future::Future<Output = T>,
+) -> Option<T> {
+    if flag.load(Ordering::Relaxed) {
+        return None;
+    }
+    tokio::select! {
+        result = fut => Some(result),
+        _ = async {
+            while !flag.load(Ordering::Relaxed) {
+                sleep(Duration::from_millis(50)).await;
+            }
+        } => None,
+    }
+}
+
+/// Give each call in `skipped` the interrupted result, so every tool use
+/// still has its matching result on the next request.
+fn interrupted_results(
+    tool_results: &mut Vec<crate::api::MessageContentBlock>,
+    skipped: &[(String, String, serde_json::Value)],
+) {
+    for (tool_id, _, _) in skipped {
+        tool_results.push(crate::api::MessageContentBlock::ToolResult {
+            tool_use_id: tool_id.clone(),
+            content: INTERRUPTED_TOOL_RESULT.to_string(),
+            cache_control: None,
+        });
+    }
+}
+
+/// Record a response's text for the session view. With `stitch` the
+/// text continues the previous assistant message, as the continuation of
+/// a cut-off response does.
+fn push_assistant_text(display_messages: &mut Vec<DisplayMessage>, text: String, stitch: bool) {
+    if stitch {
+        if let Some(DisplayMessage::AssistantMessage { content }) = display_messages.last_mut() {
+            content.push_str(&text);
+            return;
+        }
+    }
+    display_messages.push(DisplayMessage::AssistantMessage { content: text });
+}
+
 /// Handles AI's responses and manages tool execution iteration
 pub struct ResponseHandler {
     client: LlmClient,
@@ -28,6 +230,10 @@ pub struct ResponseHandler {
     interrupt_flag: Arc<AtomicBool>,
     steer_buffer: SteerBuffer,
     session_id: String,
+    budget: Budget,
+    sampling: crate::api::Sampling,
+    /// `--auto-continue`: re-request when a response hits `max_tokens`.
+    auto_continue: bool,
 }
 
 impl ResponseHandler {
@@ -59,6 +265,9 @@ impl ResponseHandler {
             interrupt_flag,
             steer_buffer,
             session_id,
+            budget: Budget::default(),
+            sampling: crate::api::Sampling::default(),
+            auto_continue: false,
         }
     }
 
@@ -79,6 +288,44 @@ impl ResponseHandler {
         }
     }
 
+    pub fn set_budget(&mut self, budget: Budget) {
+        self.budget = budget;
+    }
+
+    /// Cap the tool loop; values below 1 are raised to 1.
+    pub fn set_max_tool_iterations(&mut self, max: u32) {
+        self.config.max_tool_iterations = max.max(1);
+    }
+
+    pub fn set_sampling(&mut self, sampling: crate::api::Sampling) {
+        self.sampling = sampling;
+    }
+
+    pub fn set_auto_continue(&mut self, auto_continue: bool) {
+        self.auto_continue = auto_continue;
+    }
+
+    /// Whether the session totals leave room for another request.
+    fn within_budget(
+        &self,
+        input: u32,
+        output: u32,
+        cache_read: u32,
+        cache_creation: u32,
+        peak_single_turn_input: u32,
+    ) -> bool {
+        self.budget.allows_request(
+            &self.model,
+            &SpendTotals {
+                input,
+                output,
+                cache_read,
+                cache_creation,
+                peak_single_turn_input,
+            },
+        )
+    }
+
     /// Atomically drain all pending steer messages the user typed while
     /// this turn was running. Returns `None` if the queue is empty, or
     /// `Some(text)` with the messages joined by blank lines (preserving
@@ -110,6 +357,9 @@ impl ResponseHandler {
         peak_single_turn_input_tokens: &mut u32,
     ) -> Result<()> {
         let mut iteration = 0;
+        let mut pause_continuations = 0;
+        let mut auto_continuations = 0;
+        let mut continuing_cut_off = false;
 
         loop {
             iteration += 1;
@@ -135,16 +385,47 @@ impl ResponseHandler {
                 return Ok(());
             }
 
-            let truncated_by_max_tokens = matches!(stop_reason.as_deref(), Some("max_tokens"));
+            let reason = StopReason::parse(stop_reason.as_deref());
+            let truncated_by_max_tokens = reason.is_truncated();
+            // Text that continues a cut-off response joins it on screen.
+            let stitch = std::mem::take(&mut continuing_cut_off);
+            let paused = reason == StopReason::PauseTurn;
+            let refused = reason == StopReason::Refusal;
+            // A response that resumes a paused turn continues the same
+            // assistant message rather than starting a new one.
+            let resuming_pause = pause_continuations > 0;
 
             let (text_output, tool_uses, had_reasoning) =
                 self.process_content_blocks(&content_blocks);
+            match &reason {
+                StopReason::Unknown(other) => {
+                    tracing::warn!(stop_reason = %other, "response ended for an unrecognised reason");
+                }
+                StopReason::ToolUse if tool_uses.is_empty() => {
+                    tracing::warn!("response stopped for tool use but carried no tool call");
+                }
+                _ => {}
+            }
 
             if !text_output.is_empty() {
-                let combined_text = text_output.join("\n");
-                display_messages.push(DisplayMessage::AssistantMessage {
-                    content: combined_text,
-                });
+                push_assistant_text(display_messages, text_output.join("\n"), stitch);
+            }
+
+            if refused {
+                let placeholder = vec![crate::api::MessageContentBlock::Text {
+                    text: REFUSAL_PLACEHOLDER.to_string(),
+                    cache_control: None,
+                }];
+                if resuming_pause {
+                    self.conversation.extend_last_assistant(placeholder);
+                } else {
+                    self.conversation.add_assistant_with_blocks(placeholder);
+                }
+                UI::print_warning(
+                    "The model declined this request. This is not an error; \
+                     rephrase it or continue with something else.",
+                );
+                return Ok(());
             }
 
             if !content_blocks.is_empty() {
@@ -175,10 +456,61 @@ impl ResponseHandler {
                     }
                 }
                 if !message_blocks.is_empty() {
-                    self.conversation.add_assistant_with_blocks(message_blocks);
+                    if resuming_pause {
+                        self.conversation.extend_last_assistant(message_blocks);
+                    } else {
+                        self.conversation.add_assistant_with_blocks(message_blocks);
+                    }
                 }
             }
 
+            if reason == StopReason::MaxTokens
+                && self.auto_continue
+                && auto_continuations < MAX_AUTO_CONTINUATIONS
+                && self.within_budget(
+                    *total_input_tokens,
+                    *total_output_tokens,
+                    *total_cache_read_tokens,
+                    *total_cache_creation_tokens,
+                    *peak_single_turn_input_tokens,
+                )
+            {
+                auto_continuations += 1;
+                println!(
+                    "{}",
+                    format!(
+                        "Response cut off at the token limit; continuing ({}/{})",
+                        auto_continuations, MAX_AUTO_CONTINUATIONS
+                    )
+                    .dimmed()
+                );
+                self.conversation
+                    .add_user_message(AUTO_CONTINUE_PROMPT.to_string());
+                let response = self.get_next_response().await?;
+
+                Self::accumulate_usage(
+                    &response.usage,
+                    total_input_tokens,
+                    total_output_tokens,
+                    total_cache_read_tokens,
+                    total_cache_creation_tokens,
+                    peak_single_turn_input_tokens,
+                );
+
+                continuing_cut_off = true;
+                stop_reason = response.stop_reason;
+                content_blocks = response.content;
+                continue;
+            }
+
+            if reason == StopReason::ContextWindowExceeded {
+                UI::print_warning(
+                    "Response was cut off because the conversation filled the model's context window. \
+                     Run /compact or /clear before continuing.",
+                );
+                return Ok(());
+            }
+
             if truncated_by_max_tokens {
                 UI::print_warning("Response was cut off due to token limit.");
                 eprintln!(
@@ -188,12 +520,56 @@ impl ResponseHandler {
                 return Ok(());
             }
 
-            // OpenAI can return reasoning/summary-only blocks; auto-continue once to get real text
+            if paused && tool_uses.is_empty() {
+                pause_continuations += 1;
+                if pause_continuations > MAX_PAUSE_TURN_CONTINUATIONS {
+                    UI::print_warning(&format!(
+                        "The response stayed paused after {} continuations; stopping here.",
+                        MAX_PAUSE_TURN_CONTINUATIONS
+                    ));
+                    return Ok(());
+                }
+                if !self.within_budget(
+                    *total_input_tokens,
+                    *total_output_tokens,
+                    *total_cache_read_tokens,
+                    *total_cache_creation_tokens,
+                    *peak_single_turn_input_tokens,
+                ) {
+                    return Ok(());
+                }
+                let response = self.get_next_response().await?;
+
+                Self::accumulate_usage(
+                    &response.usage,
+                    total_input_tokens,
+                    total_output_tokens,
+                    total_cache_read_tokens,
+                    total_cache_creation_tokens,
+                    peak_single_turn_input_tokens,
+                );
+
+                stop_reason = response.stop_reason;
+                content_blocks = response.content;
+                continue;
+            }
+            pause_continuations = 0;
+
+            // OpenAI and Gemini can return reasoning/summary-only blocks; auto-continue once to get real text
             if tool_uses.is_empty()
                 && text_output.is_empty()
                 && had_reasoning
-                && matches!(self.client, LlmClient::OpenAI(_))
+                && !matches!(self.client, LlmClient::Anthropic(_))
             {
+                if !self.within_budget(
+                    *total_input_tokens,
+                    *total_output_tokens,
+                    *total_cache_read_tokens,
+                    *total_cache_creation_tokens,
+                    *peak_single_turn_input_tokens,
+                ) {
+                    return Ok(());
+                }
                 let response = self.get_next_response().await?;
 
                 Self::accumulate_usage(
@@ -282,9 +658,21 @@ impl ResponseHandler {
                 if std::env::var("SOFOS_DEBUG").is_ok() {
                     eprintln!("=== Returning early due to user cancellation ===");
                 }
+                if self.interrupt_flag.load(Ordering::Relaxed) {
+                    return Err(SofosError::Interrupted);
+                }
                 return Ok(());
             }
 
+            if !self.within_budget(
+                *total_input_tokens,
+                *total_output_tokens,
+                *total_cache_read_tokens,
+                *total_cache_creation_tokens,
+                *peak_single_turn_input_tokens,
+            ) {
+                return Ok(());
+            }
             let response = self.get_next_response().await?;
 
             Self::accumulate_usage(
@@ -305,7 +693,7 @@ impl ResponseHandler {
             }
 
             if response.content.is_empty()
-                && !matches!(response.stop_reason.as_deref(), Some("max_tokens"))
+                && !StopReason::parse(response.stop_reason.as_deref()).is_truncated()
             {
                 println!("{}", "Assistant:".bright_blue().bold());
                 println!("{}", "I've completed the tool operations but didn't generate a response. Please let me know if you need any clarification.".dimmed());
@@ -393,12 +781,24 @@ impl ResponseHandler {
     ) -> (Vec<crate::api::MessageContentBlock>, bool) {
         let mut tool_results = Vec::new();
         let mut user_cancelled = false;
+        // Results of read-only calls already run as a concurrent batch,
+        // keyed by position; they are reported in order as the loop
+        // reaches them.
+        let mut prefetched = HashMap::new();
+        // Result text sent for each call, so a repeat can reuse it.
+        let mut sent: HashMap<usize, String> = HashMap::new();
 
         if std::env::var("SOFOS_DEBUG").is_ok() {
             eprintln!("\n=== Executing {} tools ===", tool_uses.len());
         }
 
         for (i, (tool_id, tool_name, tool_input)) in tool_uses.iter().enumerate() {
+            if self.interrupt_flag.load(Ordering::Relaxed) {
+                interrupted_results(&mut tool_results, &tool_uses[i..]);
+                user_cancelled = true;
+                break;
+            }
+
             if std::env::var("SOFOS_DEBUG").is_ok() {
                 eprintln!(
                     "=== Tool {}/{}: {} (id: {}) ===",
@@ -409,27 +809,72 @@ impl ResponseHandler {
                 );
             }
 
-            let command = if tool_name == crate::tools::ToolName::ExecuteBash.as_str() {
-                tool_input.get("command").and_then(|v| v.as_str())
-            } else {
-                None
-            };
-            self.ui.print_tool_header(tool_name, command);
+            // The model sometimes repeats an identical call in one
+            // response. Run it once; the repeat still needs its own
+            // result, so it gets a copy of the first call's.
+            if let Some(content) = duplicate_of(tool_uses, i).and_then(|first| sent.get(&first)) {
+                println!(
+                    "{}",
+                    format!("Skipped a duplicate {} call", tool_name).dimmed()
+                );
+                let content = content.clone();
+                sent.insert(i, content.clone());
+                tool_results.push(crate::api::MessageContentBlock::ToolResult {
+                    tool_use_id: tool_id.clone(),
+                    content,
+                    cache_control: None,
+                });
+                continue;
+            }
 
-            // Hide cursor during bash execution
-            if tool_name == crate::tools::ToolName::ExecuteBash.as_str() {
+            let runs_shell = crate::tools::ToolName::from_str(tool_name)
+                .is_ok_and(|tool| tool.runs_shell_command());
+            self.ui
+                .print_tool_header(tool_name, UI::shell_command(tool_name, tool_input));
+
+            // Hide cursor while a shell command runs
+            if runs_shell {
                 print!("\x1B[?25l");
                 let _ = std::io::stdout().flush();
             }
 
-            let result = self.tool_executor.execute(tool_name, tool_input).await;
+            let interrupt = &*self.interrupt_flag;
+            if runs_concurrently(tool_name) && !prefetched.contains_key(&i) {
+                let run = execute_concurrent_run(tool_uses, i, &self.tool_executor);
+                prefetched = until_interrupted(interrupt, run).await.unwrap_or_default();
+            }
+            let result = match prefetched.remove(&i) {
+                Some(result) => Some(result),
+                None => {
+                    until_interrupted(interrupt, self.tool_executor.execute(tool_name, tool_input))
+                        .await
+                }
+            };
 
-            // Show cursor and add newline after bash execution completes
-            if tool_name == crate::tools::ToolName::ExecuteBash.as_str() {
+            // Show cursor and add newline after the shell command completes
+            if runs_shell {
                 print!("\x1B[?25h");
                 println!();
             }
 
+            // A call that finished before ESC keeps its result; one that
+            // was stopped, and every call after it, is marked interrupted.
+            let result = match result {
+                Some(result) if result.is_ok() || !interrupt.load(Ordering::Relaxed) => result,
+                _ => {
+                    println!("{}", INTERRUPTED_TOOL_RESULT.dimmed());
+                    display_messages.push(DisplayMessage::ToolExecution {
+                        tool_name: tool_name.clone(),
+                        tool_input: tool_input.clone(),
+                        tool_output: INTERRUPTED_TOOL_RESULT.to_string(),
+                        failed: true,
+                    });
+                    interrupted_results(&mut tool_results, &tool_uses[i..]);
+                    user_cancelled = true;
+                    break;
+                }
+            };
+
             match result {
                 Ok(output) => {
                     if std::env::var("SOFOS_DEBUG").is_ok() {
@@ -440,22 +885,41 @@ impl ResponseHandler {
                         );
                     }
 
-                    let display_output = UI::create_tool_display_message(
-                        tool_name,
-                        tool_input,
-                        output.display_text(),
-                    );
-
-                    if !display_output.is_empty() {
+                    let display_output = if output.renders_own_display() {
+                        output.display_text().to_string()
+                    } else {
+                        UI::create_tool_display_message(
+                            tool_name,
+                            tool_input,
+                            output.display_text(),
+                        )
+                    };
+
+                    let read_path = (tool_name == crate::tools::ToolName::ReadFile.as_str()
+                        && !output.renders_own_display())
+                    .then(|| tool_input.get("path").and_then(|v| v.as_str()))
+                    .flatten();
+                    let shown_live = runs_shell && self.tool_executor.live_bash_output();
+                    if let Some(path) = read_path {
+                        UI::shared().print_read_file_output(&display_output, path, output.text());
+                    } else if !display_output.is_empty() && !shown_live {
                         UI::shared().print_tool_output(&display_output);
                     }
 
+                    let deletion_cancelled =
+                        output.text().starts_with("File deletion cancelled by user")
+                            || output
+                                .text()
+                                .starts_with("Directory deletion cancelled by user");
+
                     display_messages.push(DisplayMessage::ToolExecution {
                         tool_name: tool_name.clone(),
                         tool_input: tool_input.clone(),
                         tool_output: display_output.clone(),
+                        failed: deletion_cancelled,
                     });
 
+                    sent.insert(i, output.text().to_string());
                     tool_results.push(crate::api::MessageContentBlock::ToolResult {
                         tool_use_id: tool_id.clone(),
                         content: output.text().to_string(),
@@ -480,11 +944,7 @@ impl ResponseHandler {
                         });
                     }
 
-                    if output.text().starts_with("File deletion cancelled by user")
-                        || output
-                            .text()
-                            .starts_with("Directory deletion cancelled by user")
-                    {
+                    if deletion_cancelled {
                         user_cancelled = true;
                         // Synthesize cancellation results for every
                         // tool that hasn't run yet. Every assistant
@@ -522,8 +982,10 @@ impl ResponseHandler {
                         tool_name: tool_name.clone(),
                         tool_input: tool_input.clone(),
                         tool_output: error_msg.clone(),
+                        failed: true,
                     });
 
+                    sent.insert(i, error_msg.clone());
                     tool_results.push(crate::api::MessageContentBlock::ToolResult {
                         tool_use_id: tool_id.clone(),
                         content: error_msg,
@@ -710,6 +1172,7 @@ impl ResponseHandler {
             &self.session_id,
         )
         .with_reasoning_mode(self.reasoning_mode)
+        .with_sampling(self.sampling)
         .build()
     }
 
@@ -818,6 +1281,173 @@ mod truncation_tests {
         .expect("handle_response should not error on the truncation early-return paths");
     }
 
+    #[test]
+    fn calls_after_an_interrupt_are_not_run() {
+        let (ws, handler) = build_handler();
+        handler.interrupt_flag.store(true, Ordering::SeqCst);
+        let write = json!({"path": "out.txt", "content": "x"});
+        let tool_uses = vec![
+            (
+                "call_1".to_string(),
+                "write_file".to_string(),
+                write.clone(),
+            ),
+            ("call_2".to_string(), "read_file".to_string(), write),
+        ];
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .expect("test runtime");
+
+        let (results, cancelled) = rt.block_on(handler.execute_tools(&tool_uses, &mut Vec::new()));
+
+        assert!(cancelled);
+        assert!(!ws.path().join("out.txt").exists());
+        assert_eq!(results.len(), 2);
+        assert!(results.iter().all(|block| matches!(
+            block,
+            MessageContentBlock::ToolResult { content, .. } if content == INTERRUPTED_TOOL_RESULT
+        )));
+    }
+
+    #[tokio::test]
+    async fn a_running_call_is_abandoned_on_interrupt() {
+        let flag = Arc::new(AtomicBool::new(false));
+        assert_eq!(until_interrupted(&flag, async { 7 }).await, Some(7));
+
+        let setter = Arc::clone(&flag);
+        tokio::spawn(async move {
+            sleep(Duration::from_millis(50)).await;
+            setter.store(true, Ordering::SeqCst);
+        });
+        let outcome = tokio::time::timeout(
+            Duration::from_secs(5),
+            until_interrupted(&flag, std::future::pending::<()>()),
+        )
+        .await;
+        assert_eq!(outcome, Ok(None), "the pending call should be dropped");
+    }
+
+    #[test]
+    fn identical_tool_calls_in_one_response_run_once() {
+        let (ws, handler) = build_handler();
+        let append = json!({"path": "log.txt", "content": "line\n", "append": true});
+        let tool_uses = vec![
+            (
+                "call_1".to_string(),
+                "write_file".to_string(),
+                append.clone(),
+            ),
+            ("call_2".to_string(), "write_file".to_string(), append),
+        ];
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .expect("test runtime");
+        let mut display = Vec::new();
+
+        let (results, cancelled) = rt.block_on(handler.execute_tools(&tool_uses, &mut display));
+
+        assert!(!cancelled);
+        assert_eq!(
+            std::fs::read_to_string(ws.path().join("log.txt")).unwrap(),
+            "line\n"
+        );
+        let sent = tool_result_contents(&results);
+        assert_eq!(sent[0].0, "call_1");
+        assert_eq!(sent[1].0, "call_2");
+        assert_eq!(sent[0].1, sent[1].1, "the repeat reuses the first result");
+    }
+
+    #[test]
+    fn repeated_read_after_a_write_runs_again() {
+        let (ws, handler) = build_handler();
+        std::fs::write(ws.path().join("a.txt"), "old\n").unwrap();
+        let read = json!({"path": "a.txt"});
+        let tool_uses = vec![
+            ("call_1".to_string(), "read_file".to_string(), read.clone()),
+            (
+                "call_2".to_string(),
+                "write_file".to_string(),
+                json!({"path": "a.txt", "content": "new\n"}),
+            ),
+            ("call_3".to_string(), "read_file".to_string(), read),
+        ];
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .expect("test runtime");
+        let mut display = Vec::new();
+
+        let (results, cancelled) = rt.block_on(handler.execute_tools(&tool_uses, &mut display));
+
+        assert!(!cancelled);
+        let sent = tool_result_contents(&results);
+        assert!(sent[0].1.contains("old"), "{}", sent[0].1);
+        assert!(sent[2].1.contains("new"), "{}", sent[2].1);
+    }
+
+    fn tool_result_contents(results: &[MessageContentBlock]) -> Vec<(&str, &str)> {
+        results
+            .iter()
+            .filter_map(|block| match block {
+                MessageContentBlock::ToolResult {
+                    tool_use_id,
+                    content,
+                    ..
+                } => Some((tool_use_id.as_str(), content.as_str())),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// A refusal is stored as a placeholder turn: its partial text and
+    /// tool calls are neither kept nor run.
+    #[test]
+    fn refusal_replaces_the_response_and_runs_no_tools() {
+        let (ws, mut handler) = build_handler();
+        let blocks = vec![
+            ContentBlock::Text {
+                text: "Sure, here is".to_string(),
+            },
+            ContentBlock::ToolUse {
+                id: "tool_001".to_string(),
+                name: "write_file".to_string(),
+                input: json!({ "path": "out.txt", "content": "x" }),
+            },
+        ];
+
+        call_handler(&mut handler, blocks, Some(STOP_REASON_REFUSAL));
+
+        match assistant_blocks(&handler).as_slice() {
+            [MessageContentBlock::Text { text, .. }] => assert_eq!(text, REFUSAL_PLACEHOLDER),
+            other => panic!(
+                "expected the refusal placeholder, got {:?}",
+                block_kinds(other)
+            ),
+        }
+        assert!(!ws.path().join("out.txt").exists());
+    }
+
+    #[test]
+    fn a_continuation_joins_the_cut_off_message_on_screen() {
+        let mut display = vec![DisplayMessage::UserMessage {
+            content: "write it".to_string(),
+        }];
+        push_assistant_text(&mut display, "The first ha".to_string(), true);
+        push_assistant_text(&mut display, "lf and the rest.".to_string(), true);
+        push_assistant_text(&mut display, "A new reply.".to_string(), false);
+
+        let texts: Vec<&str> = display
+            .iter()
+            .filter_map(|message| match message {
+                DisplayMessage::AssistantMessage { content } => Some(content.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(texts, ["The first half and the rest.", "A new reply."]);
+    }
+
     /// A truncated response that contains text plus a partial `tool_use`
     /// must keep the text in the conversation and drop the `tool_use`,
     /// because storing a tool call without the matching tool result
@@ -842,6 +1472,51 @@ mod truncation_tests {
         assert_eq!(kinds, vec!["text"], "tool_use must not survive truncation");
     }
 
+    #[test]
+    fn stop_reasons_from_every_provider_are_read_the_same_way() {
+        assert_eq!(StopReason::parse(None), StopReason::Finished);
+        assert_eq!(
+            StopReason::parse(Some("stop_sequence")),
+            StopReason::Finished
+        );
+        assert_eq!(StopReason::parse(Some("tool_use")), StopReason::ToolUse);
+        assert_eq!(StopReason::parse(Some("pause_turn")), StopReason::PauseTurn);
+        assert_eq!(
+            StopReason::parse(Some("content_filter")),
+            StopReason::Refusal
+        );
+        assert_eq!(
+            StopReason::parse(Some("recitation")),
+            StopReason::Unknown("recitation".to_string())
+        );
+        assert!(StopReason::parse(Some("model_context_window_exceeded")).is_truncated());
+        assert!(!StopReason::parse(Some("end_turn")).is_truncated());
+    }
+
+    #[test]
+    fn a_full_context_window_drops_the_tool_call_like_a_token_limit() {
+        let (_ws, mut handler) = build_handler();
+        let blocks = vec![
+            ContentBlock::Text {
+                text: "Reading next...".to_string(),
+            },
+            ContentBlock::ToolUse {
+                id: "tool_003".to_string(),
+                name: "read_file".to_string(),
+                input: json!({ "path": "src/main.rs" }),
+            },
+        ];
+
+        call_handler(
+            &mut handler,
+            blocks,
+            Some(STOP_REASON_CONTEXT_WINDOW_EXCEEDED),
+        );
+
+        let kinds = block_kinds(&assistant_blocks(&handler));
+        assert_eq!(kinds, vec!["text"]);
+    }
+
     /// When the only block in a truncated response is a `tool_use`,
     /// stripping it would leave the assistant turn empty and the next
     /// user message would land directly after the prior user message,
@@ -913,7 +1588,7 @@ mod truncation_tests {
             text: "All done.".to_string(),
         }];
 
-        call_handler(&mut handler, blocks, Some("end_turn"));
+        call_handler(&mut handler, blocks, Some(STOP_REASON_END_TURN));
 
         let assistant = assistant_blocks(&handler);
         assert_eq!(block_kinds(&assistant), vec!["text"]);
@@ -952,3 +1627,90 @@ mod truncation_tests {
         assert_eq!(kinds, vec!["thinking", "text"]);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::time::Duration;
+
+    fn call(id: &str, name: &str, path: &str) -> (String, String, serde_json::Value) {
+        (id.to_string(), name.to_string(), json!({ "path": path }))
+    }
+
+    fn make_fifo(path: &std::path::Path) {
+        let c_path = std::ffi::CString::new(path.as_os_str().as_encoded_bytes()).unwrap();
+        assert_eq!(unsafe { libc::mkfifo(c_path.as_ptr(), 0o600) }, 0);
+    }
+
+    /// Each read is of a FIFO, which blocks until a writer opens it. A
+    /// non-blocking open for writing fails until a reader has the FIFO
+    /// open, so both writers only get in while both reads are in flight.
+    #[tokio::test]
+    async fn consecutive_reads_run_together_and_keep_their_positions() {
+        use std::io::Write as _;
+        use std::os::unix::fs::OpenOptionsExt;
+
+        let workspace = tempfile::TempDir::new().unwrap();
+        let fifos = [
+            workspace.path().join("a.txt"),
+            workspace.path().join("b.txt"),
+        ];
+        for fifo in &fifos {
+            make_fifo(fifo);
+        }
+        let executor = ToolExecutor::new(
+            workspace.path().to_path_buf(),
+            None,
+            None,
+            crate::config::SandboxMode::Sandboxed,
+            false,
+        )
+        .unwrap();
+        let tool_uses = vec![
+            call("toolu_a", "read_file", "a.txt"),
+            call("toolu_b", "read_file", "b.txt"),
+            call("toolu_dup", "read_file", "a.txt"),
+            call("toolu_w", "write_file", "c.txt"),
+            call("toolu_c", "read_file", "c.txt"),
+        ];
+
+        let feeder = std::thread::spawn(move || {
+            let deadline = std::time::Instant::now() + Duration::from_secs(5);
+            let mut writers = [None, None];
+            while writers.iter().any(Option::is_none) && std::time::Instant::now() < deadline {
+                for (writer, fifo) in writers.iter_mut().zip(&fifos) {
+                    if writer.is_none() {
+                        *writer = std::fs::OpenOptions::new()
+                            .write(true)
+                            .custom_flags(libc::O_NONBLOCK)
+                            .open(fifo)
+                            .ok();
+                    }
+                }
+                std::thread::sleep(Duration::from_millis(10));
+            }
+            let together = writers.iter().all(Option::is_some);
+            // Feed any read still waiting too, so a failure cannot hang.
+            for (writer, fifo) in writers.into_iter().zip(&fifos) {
+                let mut writer = writer
+                    .unwrap_or_else(|| std::fs::OpenOptions::new().write(true).open(fifo).unwrap());
+                writer.write_all(b"from the fifo\n").unwrap();
+            }
+            together
+        });
+
+        let results = execute_concurrent_run(&tool_uses, 0, &executor).await;
+
+        assert!(feeder.join().unwrap(), "reads ran one after another");
+        assert_eq!(
+            results.len(),
+            2,
+            "the write ends the run; repeats are skipped"
+        );
+        for index in [0, 1] {
+            let text = results[&index].as_ref().unwrap().text().to_string();
+            assert!(text.contains("from the fifo"), "{}", tool_uses[index].0);
+        }
+    }
+}
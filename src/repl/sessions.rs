@@ -14,8 +14,10 @@ use colored::Colorize;
 /// Single-fact wrapper over the canonical provider lookup in
 /// `api::model_info`. `load_session_by_id` calls this to detect a
 /// cross-provider resume without spinning up the wrong API client.
-fn provider_of(model: &str) -> &'static str {
-    crate::api::model_info::provider_for(model).label()
+fn provider_of(model: &str, custom_endpoint: bool) -> &'static str {
+    crate::api::model_info::lookup_on(model, custom_endpoint)
+        .provider
+        .label()
 }
 
 /// Decide which permissions preset a resumed session should restore to.
@@ -63,10 +65,14 @@ impl Repl {
         if self.session_state.conversation.messages().is_empty() {
             return Ok(());
         }
+        self.save_session_as(&self.session_state.session_id)
+    }
 
+    /// Write the live conversation to the session file `session_id`.
+    fn save_session_as(&self, session_id: &str) -> Result<()> {
         let preset = PermissionPreset::current(self.mode, self.approval_policy);
         self.history_manager.save_session(
-            &self.session_state.session_id,
+            session_id,
             self.session_state.conversation.messages(),
             &self.session_state.display_messages,
             self.session_state.conversation.system_prompt(),
@@ -80,6 +86,7 @@ impl Repl {
             &self.model_config.model,
             self.mode.is_readonly(),
             Some(preset.label()),
+            &self.session_state.conversation.pinned_messages(),
         )?;
 
         Ok(())
@@ -118,7 +125,7 @@ impl Repl {
             return Ok(());
         }
 
-        let selected_id = crate::session::select_session(sessions)?;
+        let selected_id = crate::session::select_session(&self.history_manager, sessions)?;
 
         if let Some(session_id) = selected_id {
             self.load_session_by_id(&session_id)?;
@@ -133,6 +140,136 @@ impl Repl {
         Ok(())
     }
 
+    /// Delete a saved session from the resume picker and return the ones
+    /// left. The session in use is refused: its next save would bring it
+    /// straight back.
+    pub fn delete_saved_session(&self, session_id: &str) -> Result<Vec<SessionMetadata>> {
+        if session_id == self.session_state.session_id {
+            return Err(SofosError::Config(
+                "That is the current session; it can't be deleted while in use".to_string(),
+            ));
+        }
+        self.history_manager.delete_session(session_id)?;
+        self.list_saved_sessions()
+    }
+
+    /// `/resume-file <path>`: import an exported session file into this
+    /// workspace's store and resume it.
+    pub fn handle_resume_file_command(&mut self, path: &str) -> Result<()> {
+        if path.is_empty() {
+            return Err(SofosError::Config(
+                "Usage: /resume-file <path to an exported session file>".to_string(),
+            ));
+        }
+        let expanded = crate::tools::permissions::PermissionManager::expand_tilde_pub(path);
+        let session_id = self
+            .history_manager
+            .import_session(std::path::Path::new(&expanded))?;
+        self.load_session_by_id(&session_id)?;
+        println!(
+            "{} {}",
+            "Session imported:".bright_green(),
+            "Continue your conversation below".dimmed()
+        );
+        println!();
+        Ok(())
+    }
+
+    /// `/export <file.md>`: write the conversation shown on screen as a
+    /// Markdown document inside the workspace.
+    pub fn handle_export_command(&self, path: &str) -> Result<()> {
+        if path.is_empty() {
+            return Err(SofosError::Config(
+                "Usage: /export <file.md inside the workspace>".to_string(),
+            ));
+        }
+        let session_id = &self.session_state.session_id;
+        let title = self
+            .history_manager
+            .list_sessions()?
+            .into_iter()
+            .find(|session| &session.id == session_id)
+            .and_then(|session| session.title)
+            .unwrap_or_else(|| format!("Sofos session {}", session_id));
+        let markdown = crate::session::to_markdown(&title, &self.session_state.display_messages);
+        self.tool_executor.write_workspace_file(path, &markdown)?;
+        println!(
+            "\n{} {}\n",
+            "Conversation exported to".bright_green(),
+            path.bright_white()
+        );
+        Ok(())
+    }
+
+    /// `/rename <title>`: name the current session in the index. The
+    /// session is saved first so a fresh one has a file to carry it.
+    pub fn handle_rename_command(&mut self, title: &str) -> Result<()> {
+        if title.is_empty() {
+            return Err(SofosError::Config(
+                "Usage: /rename <title for this session>".to_string(),
+            ));
+        }
+        self.save_current_session()?;
+        self.history_manager
+            .rename_session(&self.session_state.session_id, title)?;
+        println!(
+            "\n{} {}\n",
+            "Session renamed:".bright_green(),
+            title.bright_white()
+        );
+        Ok(())
+    }
+
+    /// `/save <name>`: write a copy of the conversation under a new id,
+    /// titled `name`. The live session keeps its own id, so later turns
+    /// never touch the snapshot and `/resume` can return to it.
+    pub fn handle_save_command(&mut self, name: &str) -> Result<()> {
+        if name.is_empty() {
+            return Err(SofosError::Config(
+                "Usage: /save <name for the snapshot>".to_string(),
+            ));
+        }
+        if self.session_state.conversation.messages().is_empty() {
+            return Err(SofosError::Config(
+                "Nothing to save yet: the conversation is empty.".to_string(),
+            ));
+        }
+        let snapshot_id = self.history_manager.generate_unique_session_id();
+        self.save_session_as(&snapshot_id)?;
+        self.history_manager.rename_session(&snapshot_id, name)?;
+        println!(
+            "\n{} {} {}\n",
+            "Snapshot saved:".bright_green(),
+            name.bright_white(),
+            "(resume it with /resume)".dimmed()
+        );
+        Ok(())
+    }
+
+    /// `/fork`: save the session as it stands, then carry on under a new
+    /// id. The conversation, token counters, and pins come along; the
+    /// original stays on disk as it was at the fork.
+    pub fn handle_fork_command(&mut self) -> Result<()> {
+        if self.session_state.conversation.messages().is_empty() {
+            return Err(SofosError::Config(
+                "Nothing to fork yet: the conversation is empty.".to_string(),
+            ));
+        }
+        self.save_current_session()?;
+        let original_id = std::mem::replace(
+            &mut self.session_state.session_id,
+            self.history_manager.generate_unique_session_id(),
+        );
+        self.save_current_session()?;
+        println!(
+            "\n{} {}\n{}\n",
+            "Forked into session".bright_green(),
+            self.session_state.session_id.bright_white(),
+            format!("The original session {} is saved unchanged.", original_id).dimmed()
+        );
+        Ok(())
+    }
+
     pub fn load_session_by_id(&mut self, session_id: &str) -> Result<()> {
         let session = self.history_manager.load_session(session_id)?;
 
@@ -153,7 +290,8 @@ impl Repl {
         // current Repl untouched.
         if let Some(saved_model) = session.model.as_deref() {
             if !saved_model.is_empty() {
-                let saved_provider = provider_of(saved_model);
+                let custom_endpoint = self.model_config.custom_endpoint;
+                let saved_provider = provider_of(saved_model, custom_endpoint);
                 let current_provider = self.client.provider_name();
                 if saved_provider != current_provider {
                     return Err(SofosError::Config(format!(
@@ -167,7 +305,9 @@ impl Repl {
                 // on a slug the application no longer supports. The
                 // resumed session would otherwise send an unrecognised
                 // model id on the wire and fail at request time.
-                if crate::api::model_info::canonical_model(saved_model).is_none() {
+                if !custom_endpoint
+                    && crate::api::model_info::model_support_error(saved_model).is_some()
+                {
                     return Err(SofosError::Config(format!(
                         "Session was saved under model '{}', which is no longer supported. \
                          Supported models: {}.",
@@ -176,10 +316,10 @@ impl Repl {
                     )));
                 }
                 if saved_model != self.model_config.model {
-                    if let Some(msg) = crate::api::model_info::effort_support_error(
-                        saved_model,
-                        self.model_config.reasoning_effort,
-                    ) {
+                    if let Some(msg) =
+                        crate::api::model_info::lookup_on(saved_model, custom_endpoint)
+                            .effort_support_error(saved_model, self.model_config.reasoning_effort)
+                    {
                         return Err(SofosError::Config(format!(
                             "{} Re-launch with `--reasoning-effort` set to a level the saved model accepts.",
                             msg
@@ -191,9 +331,11 @@ impl Repl {
 
         self.session_state.session_id = session.id.clone();
         self.session_state.conversation.clear();
-        self.session_state
-            .conversation
-            .restore_messages(session.api_messages.clone());
+        self.tool_executor.forget_reads();
+        self.session_state.conversation.restore_messages(
+            session.api_messages.clone(),
+            session.pinned_messages.iter().copied(),
+        );
         // Restore the persisted system prompt so the resumed conversation
         // sees the same system context the assistant was answering
         // against at save time. Older session files always carry a
@@ -324,7 +466,7 @@ mod tests {
         // these strings against `LlmClient::provider_name` directly.
         for m in crate::api::model_info::SUPPORTED_MODELS {
             assert_eq!(
-                provider_of(m.name),
+                provider_of(m.name, false),
                 m.provider.label(),
                 "{} should route to {}",
                 m.name,
@@ -334,7 +476,9 @@ mod tests {
         // Unsupported slugs fall through to the default model's
         // provider (Anthropic, because the default is an Anthropic
         // model); `build_llm_client` mirrors that fallback.
-        assert_eq!(provider_of("unknown-model"), "Anthropic");
+        assert_eq!(provider_of("unknown-model", false), "Anthropic");
+        // Behind `--api-base` every model is served by the OpenAI client.
+        assert_eq!(provider_of("unknown-model", true), "OpenAI");
     }
 
     /// The core of L-g: a saved sandboxed preset resumes as itself instead
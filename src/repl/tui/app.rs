@@ -25,6 +25,10 @@ use super::event::{
 };
 use super::slash_popup::SlashPopup;
 
+/// Opens and closes a Markdown code block; Enter inside an unclosed one
+/// adds a line instead of submitting.
+const CODE_FENCE: &str = "```";
+
 const SPINNER_FRAMES: &[&str] = &["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
 
 /// Entire UI state for one REPL session.
@@ -36,6 +40,9 @@ pub struct App {
     busy: bool,
     /// Short label shown next to the spinner ("processing", "thinking", ...).
     pub busy_label: String,
+    /// Shown instead of `busy_label` while a provider request waits to be
+    /// retried ("reconnecting (1/2)").
+    pub retry_label: Option<String>,
     /// Jobs queued while the worker was busy. Drained FIFO once it becomes idle.
     pub queue: VecDeque<Job>,
     /// Spinner frame cursor, advanced on tick.
@@ -85,6 +92,8 @@ pub struct App {
     /// `None` means the textarea holds the user's live draft (not a
     /// historical entry).
     pub history_cursor: Option<usize>,
+    /// `.sofos/input_history`, once loaded; new entries are appended.
+    pub history_file: Option<std::path::PathBuf>,
     /// Inline overlay shown under the input box while the user is typing
     /// a `/…` command. Stays in sync with the textarea on every keystroke
     /// via [`App::sync_slash_popup`].
@@ -96,6 +105,8 @@ pub const INPUT_HISTORY_CAP: usize = 100;
 pub struct Picker {
     pub sessions: Vec<SessionMetadata>,
     pub cursor: usize,
+    /// `d` was pressed on the highlighted session; `y` deletes it.
+    pub confirm_delete: bool,
 }
 
 /// Inline overlay shown by `/model`. Holds the rows, the cursor,
@@ -288,6 +299,7 @@ impl App {
             textarea,
             busy: false,
             busy_label: String::new(),
+            retry_label: None,
             queue: VecDeque::new(),
             spinner_tick: 0,
             busy_since: None,
@@ -305,6 +317,7 @@ impl App {
             input_history: VecDeque::new(),
             history_draft: None,
             history_cursor: None,
+            history_file: None,
             slash_popup: SlashPopup::new(),
         }
     }
@@ -333,6 +346,12 @@ impl App {
         self.mode().is_readonly()
     }
 
+    /// Seed the history ring from `path` and keep appending to it.
+    pub fn load_input_history(&mut self, path: std::path::PathBuf) {
+        self.input_history = super::input_history::load(&path);
+        self.history_file = Some(path);
+    }
+
     /// Push a successfully-submitted line into the input-history ring.
     /// No-op on empty strings and on consecutive duplicates (so hammering
     /// Enter on the same message doesn't pollute history).
@@ -345,6 +364,9 @@ impl App {
             return;
         }
         self.input_history.push_back(text.to_string());
+        if let Some(path) = &self.history_file {
+            super::input_history::append(path, text);
+        }
         while self.input_history.len() > INPUT_HISTORY_CAP {
             self.input_history.pop_front();
         }
@@ -415,6 +437,7 @@ impl App {
     pub fn finish_busy(&mut self) {
         self.busy = false;
         self.busy_label.clear();
+        self.retry_label = None;
         self.busy_since = None;
     }
 
@@ -435,6 +458,30 @@ impl App {
         self.textarea.lines().join("\n")
     }
 
+    /// Handle a bare Enter on an unfinished prompt. A trailing `\` at the
+    /// cursor becomes a newline, and Enter inside an unclosed code fence
+    /// inserts one. Returns false when the input is ready to submit.
+    pub fn continue_open_input(&mut self) -> bool {
+        let lines = self.textarea.lines();
+        let (row, col) = self.textarea.cursor();
+        let continued = row + 1 == lines.len()
+            && col == lines[row].chars().count()
+            && lines[row].ends_with('\\');
+        let in_fence = lines
+            .iter()
+            .filter(|line| line.trim_start().starts_with(CODE_FENCE))
+            .count()
+            % 2
+            == 1;
+        if continued {
+            self.textarea.delete_char();
+        } else if !in_fence {
+            return false;
+        }
+        self.textarea.insert_newline();
+        true
+    }
+
     /// Clear the input widget after submitting.
     pub fn clear_input(&mut self) {
         self.textarea = TextArea::default();
@@ -544,6 +591,25 @@ mod tests {
         assert_eq!(a.input_text(), "");
     }
 
+    #[test]
+    fn enter_continues_after_a_backslash_or_inside_a_fence() {
+        let mut a = app();
+        a.textarea.insert_str("first \\");
+        assert!(a.continue_open_input());
+        assert_eq!(a.input_text(), "first \n");
+
+        a.clear_input();
+        a.textarea.insert_str("```rust\nfn main() {}");
+        assert!(a.continue_open_input());
+        a.textarea.insert_str("```");
+        assert!(!a.continue_open_input(), "a closed fence submits");
+
+        a.clear_input();
+        a.textarea.insert_str("plain prompt");
+        assert!(!a.continue_open_input());
+        assert_eq!(a.input_text(), "plain prompt");
+    }
+
     #[test]
     fn textarea_has_soft_wrap_enabled() {
         let a = app();
@@ -615,6 +681,7 @@ mod tests {
             approval: crate::config::ApprovalPolicy::OnRequest,
             reasoning: String::new(),
             reasoning_mode: None,
+            scope: None,
             input_tokens: 0,
             output_tokens: 0,
             cache_read_tokens: 0,
@@ -953,6 +1020,7 @@ mod tests {
             approval: crate::config::ApprovalPolicy::OnRequest,
             reasoning: "thinking: 10000 tok".into(),
             reasoning_mode: None,
+            scope: None,
             input_tokens: 123,
             output_tokens: 456,
             cache_read_tokens: 0,
@@ -41,6 +41,9 @@ pub struct StatusSnapshot {
     /// `None` on models where pro mode does not apply, which hides the
     /// status-line field.
     pub reasoning_mode: Option<ReasoningMode>,
+    /// The `/scope` subdirectory, relative to the project root; `None`
+    /// when tools work from the root, which hides the status-line field.
+    pub scope: Option<String>,
     pub input_tokens: u32,
     pub output_tokens: u32,
     /// Cumulative cache-read tokens for the session — exposed in the
@@ -68,6 +71,8 @@ pub enum Job {
     Command(Command),
     /// User confirmed a choice inside the resume picker.
     ResumeSelected(Option<String>),
+    /// User confirmed deleting a session from the resume picker.
+    DeleteSession(String),
     /// User confirmed a choice inside the `/model` picker; `None` on
     /// cancel. Carries the canonical `&'static str` slug from
     /// [`crate::api::model_info::SUPPORTED_MODELS`] — model names are
@@ -76,6 +81,8 @@ pub enum Job {
     ModelSelected(Option<&'static str>),
     /// User confirmed a level inside the `/effort` picker; `None` on cancel.
     EffortSelected(Option<ReasoningEffort>),
+    /// Ctrl+T: step to the next supported effort level.
+    CycleEffort,
     /// Access preset chosen in the `/permissions` picker; `None` on cancel.
     PermissionsSelected(Option<crate::config::PermissionPreset>),
     /// Reasoning mode chosen in the `/mode` picker; `None` on cancel.
@@ -99,9 +106,9 @@ pub struct ModelPickerEntry {
     /// "(current)" tag so the user can see what they are about to
     /// replace.
     pub is_current: bool,
-    /// False when the row is on the other provider — the running
-    /// session can't switch there without a relaunch. Disabled rows
-    /// stay visible; the cursor skips over them.
+    /// False when the row is on another provider whose API key is not
+    /// set, so the session can't switch there without a relaunch.
+    /// Disabled rows stay visible; the cursor skips over them.
     pub is_available: bool,
 }
 
@@ -158,7 +165,11 @@ pub enum UiEvent {
     WorkerBusy(String),
     /// Worker finished the current job (regardless of success).
     WorkerIdle,
-    /// Worker wants the UI to show the session picker.
+    /// A provider request is waiting to be retried (`Some`), or the
+    /// retry was sent (`None`).
+    RetryNotice(Option<String>),
+    /// Worker wants the UI to show the session picker, or to refresh
+    /// the open one. An empty list closes it.
     ShowResumePicker(Vec<SessionMetadata>),
     /// Worker wants the UI to show the model picker.
     ShowModelPicker { entries: Vec<ModelPickerEntry> },
@@ -182,14 +182,21 @@ pub(super) async fn event_loop(
                     }
                     break;
                 }
+                UiEvent::RetryNotice(label) => {
+                    app.retry_label = label;
+                    break;
+                }
                 UiEvent::Status(snapshot) => {
                     app.status = Some(snapshot);
                     break;
                 }
                 UiEvent::ShowResumePicker(sessions) => {
-                    app.picker = Some(Picker {
+                    // A refresh after a delete keeps the cursor in place.
+                    let cursor = app.picker.as_ref().map_or(0, |picker| picker.cursor);
+                    app.picker = (!sessions.is_empty()).then(|| Picker {
+                        cursor: cursor.min(sessions.len() - 1),
                         sessions,
-                        cursor: 0,
+                        confirm_delete: false,
                     });
                     break;
                 }
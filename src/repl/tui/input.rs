@@ -87,6 +87,11 @@ pub(super) fn handle_idle_key(
             KeyCode::Char('k') if ctrl => {
                 app.textarea.delete_line_by_end();
             }
+            // Ctrl+T steps the reasoning effort without touching the input.
+            // Ignored while busy so the switch never queues behind a turn.
+            KeyCode::Char('t') if ctrl && !app.busy() => {
+                let _ = job_tx.send(Job::CycleEffort);
+            }
             // Alt+Up / Alt+Down cycle previously-submitted messages
             // without shadowing the textarea's own Up/Down cursor keys.
             KeyCode::Up if alt && !ctrl => {
@@ -115,8 +120,12 @@ pub(super) fn handle_idle_key(
             //     iTerm with the flag turned on). `TerminalGuard` pushes
             //     the `DISAMBIGUATE_ESCAPE_CODES` flag so those terminals
             //     start delivering Shift+Enter with the SHIFT modifier set.
+            // A line ending in `\` or an unclosed code fence also takes
+            // a plain Enter as a newline.
             KeyCode::Enter if bare => {
-                submit_input(app, job_tx, steer_buffer);
+                if !app.continue_open_input() {
+                    submit_input(app, job_tx, steer_buffer);
+                }
             }
             // Plain Tab on a `/…` line opens the slash-command popup
             // (auto-completing immediately when only one match remains).
@@ -270,7 +279,16 @@ pub(super) fn handle_picker_key(app: &mut App, key: KeyEvent, job_tx: &std_mpsc:
     let Some(picker) = app.picker.as_mut() else {
         return;
     };
+    if picker.confirm_delete {
+        picker.confirm_delete = false;
+        if matches!(key.code, KeyCode::Char('y') | KeyCode::Char('Y')) {
+            let id = picker.sessions[picker.cursor].id.clone();
+            let _ = job_tx.send(Job::DeleteSession(id));
+        }
+        return;
+    }
     match key.code {
+        KeyCode::Char('d') if !ctrl => picker.confirm_delete = true,
         KeyCode::Up | KeyCode::Char('k') => {
             if picker.cursor > 0 {
                 picker.cursor -= 1;
@@ -0,0 +1,118 @@
+//! Prompt history kept across runs in `.sofos/input_history`, so Alt+Up
+//! recalls what was typed in earlier sessions. One JSON string per line,
+//! which keeps multi-line prompts intact. Slash commands and prompts that
+//! look like they carry a credential are never written.
+
+use std::collections::VecDeque;
+use std::fs::{self, OpenOptions};
+use std::io::Write as _;
+use std::path::Path;
+
+use super::app::INPUT_HISTORY_CAP;
+
+/// History file, relative to the project root.
+pub const INPUT_HISTORY_FILE: &str = ".sofos/input_history";
+
+/// Markers that keep a prompt out of the file, matched case-insensitively.
+const SECRET_MARKERS: &[&str] = &["API_KEY", "APIKEY", "SECRET", "PASSWORD", "TOKEN="];
+
+/// The newest [`INPUT_HISTORY_CAP`] entries, oldest first. A missing or
+/// unreadable file gives an empty history. When the file has grown to
+/// twice the cap it is rewritten with only the entries kept.
+pub fn load(path: &Path) -> VecDeque<String> {
+    let Ok(contents) = fs::read_to_string(path) else {
+        return VecDeque::new();
+    };
+    let mut entries: VecDeque<String> = contents
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect();
+    let stored = entries.len();
+    while entries.len() > INPUT_HISTORY_CAP {
+        entries.pop_front();
+    }
+    if stored >= INPUT_HISTORY_CAP * 2 {
+        let kept: String = entries
+            .iter()
+            .filter_map(|entry| serde_json::to_string(entry).ok())
+            .map(|line| line + "\n")
+            .collect();
+        if let Err(e) = fs::write(path, kept) {
+            tracing::warn!(error = %e, path = %path.display(), "failed to trim input history");
+        }
+    }
+    entries
+}
+
+/// Append `entry` unless it is a command or looks secret. Write errors
+/// are only traced; losing a history line never interrupts the prompt.
+pub fn append(path: &Path, entry: &str) {
+    if !should_persist(entry) {
+        return;
+    }
+    let Ok(line) = serde_json::to_string(entry) else {
+        return;
+    };
+    let written = path
+        .parent()
+        .map_or(Ok(()), fs::create_dir_all)
+        .and_then(|()| OpenOptions::new().create(true).append(true).open(path))
+        .and_then(|mut file| writeln!(file, "{}", line));
+    if let Err(e) = written {
+        tracing::warn!(error = %e, path = %path.display(), "failed to save input history");
+    }
+}
+
+fn should_persist(entry: &str) -> bool {
+    let upper = entry.to_ascii_uppercase();
+    !entry.trim_start().starts_with('/')
+        && !SECRET_MARKERS.iter().any(|marker| upper.contains(marker))
+        && crate::api::utils::redact_api_secrets(entry) == entry
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn entries_round_trip_without_commands_or_secrets() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(INPUT_HISTORY_FILE);
+        append(&path, "explain\nthis function");
+        append(&path, "/model");
+        append(&path, "export OPENAI_API_KEY=abc");
+        append(&path, "use sk-abcdefghijklmnop for the call");
+        append(&path, "fix the tests");
+
+        assert_eq!(
+            load(&path),
+            VecDeque::from([
+                "explain\nthis function".to_string(),
+                "fix the tests".to_string()
+            ])
+        );
+    }
+
+    #[test]
+    fn an_overgrown_file_is_trimmed_to_the_cap() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("input_history");
+        for i in 0..INPUT_HISTORY_CAP * 2 {
+            append(&path, &format!("prompt {i}"));
+        }
+
+        let entries = load(&path);
+        assert_eq!(entries.len(), INPUT_HISTORY_CAP);
+        assert_eq!(
+            entries.back().map(String::as_str),
+            Some(format!("prompt {}", INPUT_HISTORY_CAP * 2 - 1).as_str())
+        );
+        assert_eq!(
+            load(&path),
+            entries,
+            "the rewritten file keeps the same entries"
+        );
+        let lines = fs::read_to_string(&path).unwrap().lines().count();
+        assert_eq!(lines, INPUT_HISTORY_CAP);
+    }
+}
@@ -35,6 +35,14 @@ pub(super) fn install_confirm_handler(ui_tx: UnboundedSender<UiEvent>) {
     crate::tools::utils::set_confirm_handler(handler);
 }
 
+/// Install a process-wide retry notice handler so a provider request
+/// waiting to be retried shows next to the spinner.
+pub(super) fn install_retry_notice_handler(ui_tx: UnboundedSender<UiEvent>) {
+    crate::api::utils::set_retry_notice_handler(Box::new(move |status: Option<&str>| {
+        let _ = ui_tx.send(UiEvent::RetryNotice(status.map(str::to_string)));
+    }));
+}
+
 /// Route a key into the confirmation modal.
 ///
 /// - `Up`/`k` / `Down`/`j` — move the selection
@@ -15,6 +15,7 @@ pub mod event_loop;
 pub mod inline_terminal;
 pub mod inline_tui;
 pub mod input;
+pub mod input_history;
 pub mod keymap;
 pub mod output;
 pub mod scrollback;
@@ -40,7 +41,7 @@ use app::App;
 use event::{Job, UiEvent};
 use event_loop::event_loop;
 use input::spawn_input_reader;
-use keymap::install_confirm_handler;
+use keymap::{install_confirm_handler, install_retry_notice_handler};
 use output::OutputCapture;
 
 /// Real-tty handle saved for the panic hook so its restoration
@@ -263,8 +264,12 @@ pub fn run(mut repl: Repl) -> Result<()> {
 
     let capture = OutputCapture::install(ui_tx.clone())?;
     // colored detects its output is a pipe after redirection and disables
-    // styling — force it back on so ANSI reaches the log.
-    colored::control::set_override(true);
+    // styling — force it back on so ANSI reaches the log, unless the user
+    // turned colour off.
+    let color = crate::ui::appearance::color_enabled();
+    if color {
+        colored::control::set_override(true);
+    }
 
     // Register a confirmation handler so destructive tools like
     // `delete_file` can prompt the user through the TUI modal instead of
@@ -272,6 +277,7 @@ pub fn run(mut repl: Repl) -> Result<()> {
     // closure is stored in a process-wide `OnceLock` so it only installs
     // once per process.
     install_confirm_handler(ui_tx.clone());
+    install_retry_notice_handler(ui_tx.clone());
 
     let interrupt = Arc::new(AtomicBool::new(false));
     repl.install_interrupt_flag(Arc::clone(&interrupt));
@@ -284,6 +290,10 @@ pub fn run(mut repl: Repl) -> Result<()> {
     repl.install_steer_buffer(Arc::clone(&steer_buffer));
 
     let model_label = repl.model_label();
+    let history_path = repl
+        .tool_executor
+        .workspace()
+        .join(input_history::INPUT_HISTORY_FILE);
     // Grab the deferred startup text (logo + workspace / model / etc.)
     // before moving `repl` into the worker — we replay it through the
     // capture pipe below so it lands above the viewport.
@@ -293,6 +303,7 @@ pub fn run(mut repl: Repl) -> Result<()> {
     spawn_input_reader(ui_tx.clone())?;
 
     let mut app = App::new(model_label.clone());
+    app.load_input_history(history_path);
     // Everything we emit here rides the `OutputCapture` pipe (installed
     // above) and is handed to `scrollback::scroll_strings_above_viewport`
     // in the event loop — the same path every later tool/stdout line
@@ -330,7 +341,9 @@ pub fn run(mut repl: Repl) -> Result<()> {
     // bracketed paste enabled.
     drop(capture);
     drop(_terminal_guard);
-    colored::control::unset_override();
+    if color {
+        colored::control::unset_override();
+    }
 
     if let Some(summary) = app.exit_summary.take() {
         if summary.panicked {
@@ -84,14 +84,32 @@ where
     // so a blank trailing line from `println!()` still renders a row.
     let mut joined = captured_lines.join("\n");
     joined.push('\n');
-    let text: Text<'static> = joined
+    let mut text: Text<'static> = joined
         .as_bytes()
         .into_text()
         .unwrap_or_else(|_| Text::from(joined.clone()));
+    if !crate::ui::appearance::color_enabled() {
+        strip_colors(&mut text);
+    }
 
     scroll_text_above_viewport(terminal, text)
 }
 
+/// Drop foreground and background colours from `text`, keeping bold,
+/// dim and the other modifiers, for output that hard-codes escapes.
+fn strip_colors(text: &mut Text<'static>) {
+    text.style.fg = None;
+    text.style.bg = None;
+    for line in &mut text.lines {
+        line.style.fg = None;
+        line.style.bg = None;
+        for span in &mut line.spans {
+            span.style.fg = None;
+            span.style.bg = None;
+        }
+    }
+}
+
 /// Shared implementation for [`scroll_strings_above_viewport`]: runs
 /// Phase 1 (optional DECSTBM reverse-index to shift the viewport down)
 /// and Phase 2 (paint each pre-wrapped row above the viewport via a
@@ -295,10 +295,12 @@ fn draw_hint(frame: &mut Frame, area: Rect, app: &App) {
         spans.push(Span::styled(" ❯ ", Style::default().fg(HINT_KEY)));
         spans.push(Span::styled(label, Style::default().fg(Color::Gray)));
         spans.push(Span::styled(SEP, Style::default().fg(Color::DarkGray)));
-        spans.push(Span::styled(
-            "↑↓ ⏎  select  ·  esc cancel",
-            Style::default().fg(Color::DarkGray),
-        ));
+        let keys = if app.picker.is_some() {
+            "↑↓ ⏎  select  ·  d delete  ·  esc cancel"
+        } else {
+            "↑↓ ⏎  select  ·  esc cancel"
+        };
+        spans.push(Span::styled(keys, Style::default().fg(Color::DarkGray)));
         frame.render_widget(Paragraph::new(Line::from(spans)), area);
         return;
     }
@@ -309,7 +311,9 @@ fn draw_hint(frame: &mut Frame, area: Rect, app: &App) {
             format!(" {} ", frame_ch),
             Style::default().fg(ACCENT).add_modifier(Modifier::BOLD),
         ));
-        let label = if app.busy_label.is_empty() {
+        let label = if let Some(retry) = &app.retry_label {
+            retry.as_str()
+        } else if app.busy_label.is_empty() {
             "working"
         } else {
             app.busy_label.as_str()
@@ -447,6 +451,12 @@ fn status_spans(app: &App) -> Vec<Span<'_>> {
         spans.push(Span::styled(reasoning_mode.as_label(), mode_value_style));
     }
 
+    if let Some(scope) = app.status.as_ref().and_then(|s| s.scope.as_deref()) {
+        spans.push(sep());
+        spans.push(Span::styled("scope: ", label_style));
+        spans.push(Span::styled(scope, value_style));
+    }
+
     if in_tok > 0 || out_tok > 0 {
         spans.push(sep());
         spans.push(Span::styled("tokens: ", label_style));
@@ -867,7 +877,7 @@ fn draw_model_picker(frame: &mut Frame, area: Rect, picker: &ModelPicker) {
             }
             if !entry.is_available {
                 spans.push(Span::styled(
-                    "  (re-launch session to activate)",
+                    "  (API key not set)",
                     Style::default()
                         .fg(Color::DarkGray)
                         .add_modifier(Modifier::ITALIC),
@@ -1059,6 +1069,7 @@ fn draw_picker(frame: &mut Frame, area: Rect, picker: &Picker) {
     let (scroll, visible) =
         picker_visible_window(popup.height, picker.cursor, picker.sessions.len());
 
+    let now = crate::session::now_secs();
     let items: Vec<ListItem> = picker
         .sessions
         .iter()
@@ -1079,16 +1090,26 @@ fn draw_picker(frame: &mut Frame, area: Rect, picker: &Picker) {
             } else {
                 Style::default().fg(Color::Gray)
             };
-            let meta = format!(" ({} msgs)", s.message_count).dim();
+            let meta = format!(
+                " ({} • {} msgs)",
+                crate::session::relative_time(s.updated_at, now),
+                s.message_count
+            )
+            .dim();
             ListItem::new(Line::from(vec![
                 Span::raw(marker),
-                Span::styled(s.preview.clone(), style),
+                Span::styled(s.label().to_string(), style),
                 meta,
             ]))
         })
         .collect();
 
-    let list = List::new(items).block(picker_block(" Resume session "));
+    let title = if picker.confirm_delete {
+        " Delete the highlighted session? y to confirm, any other key to keep it "
+    } else {
+        " Resume session "
+    };
+    let list = List::new(items).block(picker_block(title));
     frame.render_widget(list, popup);
 }
 
@@ -1109,6 +1130,7 @@ mod tests {
             approval: crate::config::ApprovalPolicy::OnRequest,
             reasoning: "effort: high".into(),
             reasoning_mode: Some(crate::api::ReasoningMode::Pro),
+            scope: None,
             input_tokens: 10,
             output_tokens: 20,
             cache_read_tokens: 5,
@@ -141,6 +141,7 @@ fn run(
                 interrupt.store(false, Ordering::SeqCst);
                 let label = match cmd {
                     crate::commands::Command::Compact => "compacting",
+                    crate::commands::Command::Retry => "processing",
                     _ => "command",
                 };
                 let _ = ui_tx.send(UiEvent::WorkerBusy(label.into()));
@@ -184,6 +185,20 @@ fn run(
                 let _ = ui_tx.send(UiEvent::Status(repl.status_snapshot()));
                 let _ = ui_tx.send(UiEvent::WorkerIdle);
             }
+            Job::DeleteSession(session_id) => {
+                let _ = ui_tx.send(UiEvent::WorkerBusy("deleting".into()));
+                match repl.delete_saved_session(&session_id) {
+                    Ok(sessions) => {
+                        if sessions.is_empty() {
+                            println!("No saved sessions left.");
+                        }
+                        let _ = ui_tx.send(UiEvent::ShowResumePicker(sessions));
+                    }
+                    Err(e) => UI::print_error_with_hint(&e),
+                }
+                flush_captured_streams();
+                let _ = ui_tx.send(UiEvent::WorkerIdle);
+            }
             Job::ResumeSelected(None) => {
                 // User cancelled the picker — nothing to do besides
                 // signalling idle so the queue can resume draining.
@@ -212,6 +227,14 @@ fn run(
                 let _ = ui_tx.send(UiEvent::Status(repl.status_snapshot()));
                 let _ = ui_tx.send(UiEvent::WorkerIdle);
             }
+            Job::CycleEffort => {
+                interrupt.store(false, Ordering::SeqCst);
+                let _ = ui_tx.send(UiEvent::WorkerBusy("switching effort".into()));
+                repl.handle_effort_cycle();
+                flush_captured_streams();
+                let _ = ui_tx.send(UiEvent::Status(repl.status_snapshot()));
+                let _ = ui_tx.send(UiEvent::WorkerIdle);
+            }
             Job::EffortSelected(None) => {
                 flush_captured_streams();
                 let _ = ui_tx.send(UiEvent::WorkerIdle);
@@ -299,8 +322,7 @@ fn run_command(
 }
 
 fn build_effort_picker_entries(repl: &Repl) -> Vec<EffortPickerEntry> {
-    use crate::api::model_info;
-    let info = model_info::lookup(&repl.model_label());
+    let info = repl.model_config.info();
     let current = repl.current_reasoning_effort();
     info.supported_efforts
         .iter()
@@ -342,16 +364,14 @@ fn build_mode_picker_entries(repl: &Repl) -> Vec<ModePickerEntry> {
 fn build_model_picker_entries(repl: &Repl) -> Vec<ModelPickerEntry> {
     use crate::api::model_info;
     let current_model = repl.model_label();
-    // Other-provider rows are unreachable mid-session — the LlmClient
-    // is built once at startup.
-    let current_provider = model_info::provider_for(&current_model);
+    // Another provider's rows are reachable only when its API key is set.
     model_info::SUPPORTED_MODELS
         .iter()
         .map(|choice| ModelPickerEntry {
             name: choice.name,
             description: choice.description,
             is_current: choice.name == current_model,
-            is_available: choice.provider == current_provider,
+            is_available: repl.can_switch_to(choice.provider),
         })
         .collect()
 }
@@ -14,11 +14,64 @@ use std::sync::Arc;
 use std::time::Instant;
 
 impl Repl {
+    /// `/retry`: drop the last prompt and everything after it, then send
+    /// the prompt again with the images that were pasted into it.
+    pub fn handle_retry(&mut self) -> Result<()> {
+        let display = &self.session_state.display_messages;
+        let last_prompt = display
+            .iter()
+            .rposition(|message| matches!(message, DisplayMessage::UserMessage { .. }));
+        let retry_point = last_prompt.and_then(|display_index| {
+            let DisplayMessage::UserMessage { content } = &display[display_index] else {
+                return None;
+            };
+            let index = self.session_state.conversation.last_prompt_index(content)?;
+            Some((display_index, index, content.clone()))
+        });
+        let Some((display_index, index, prompt)) = retry_point else {
+            println!(
+                "{}",
+                "Nothing to retry: the last entry is not a prompt.".yellow()
+            );
+            return Ok(());
+        };
+
+        let images = match &self.session_state.conversation.messages()[index].content {
+            crate::api::MessageContent::Blocks { content } => content
+                .iter()
+                .filter_map(|block| match block {
+                    MessageContentBlock::Image {
+                        source: ImageSource::Base64 { media_type, data },
+                        ..
+                    } => Some(crate::clipboard::PastedImage {
+                        media_type: media_type.clone(),
+                        base64_data: data.clone(),
+                    }),
+                    _ => None,
+                })
+                .collect(),
+            crate::api::MessageContent::Text { .. } => Vec::new(),
+        };
+        self.session_state.conversation.truncate(index);
+        self.session_state.display_messages.truncate(display_index);
+
+        println!("{} {}", "Retrying:".dimmed(), prompt);
+        println!();
+        self.process_message(&prompt, images)
+    }
+
     pub fn process_message(
         &mut self,
         user_input: &str,
         pasted_images: Vec<crate::clipboard::PastedImage>,
     ) -> Result<()> {
+        if !self
+            .budget
+            .allows_request(&self.model_config.model, &(&self.session_state).into())
+        {
+            return Ok(());
+        }
+
         // Record turn start so we can show "Finished in Xs" when the
         // model is fully done (after every text reply, tool call, and
         // continuation). Steer messages typed mid-turn don't reset
@@ -26,6 +79,7 @@ impl Repl {
         // the same `process_message` call keeps running until the
         // agent loop exits.
         let turn_start = Instant::now();
+        self.tool_executor.begin_turn();
 
         let has_pasted_images = !pasted_images.is_empty();
 
@@ -147,7 +201,15 @@ impl Repl {
                         // the retry. A message that was image-only gets
                         // dropped entirely.
                         let mut cleaned_messages: Vec<crate::api::Message> = Vec::new();
-                        for m in self.session_state.conversation.messages() {
+                        let pinned = self.session_state.conversation.pinned_messages();
+                        let mut cleaned_pins = Vec::new();
+                        for (i, m) in self
+                            .session_state
+                            .conversation
+                            .messages()
+                            .iter()
+                            .enumerate()
+                        {
                             use crate::api::{Message, MessageContent, MessageContentBlock};
                             let cleaned = match &m.content {
                                 MessageContent::Blocks { content } => {
@@ -166,13 +228,16 @@ impl Repl {
                                 }
                                 _ => m.clone(),
                             };
+                            if pinned.contains(&i) {
+                                cleaned_pins.push(cleaned_messages.len());
+                            }
                             cleaned_messages.push(cleaned);
                         }
 
                         self.session_state.conversation.clear();
                         self.session_state
                             .conversation
-                            .restore_messages(cleaned_messages);
+                            .restore_messages(cleaned_messages, cleaned_pins);
 
                         let system_note = if has_pasted_images {
                             "[SYSTEM ERROR: An image attached to your message could not be loaded and has been removed from the conversation.]"
@@ -194,6 +259,7 @@ impl Repl {
                         // the image-laden one that caused the 400.
                         let conversation_backup =
                             self.session_state.conversation.messages().to_vec();
+                        let pins_backup = self.session_state.conversation.pinned_messages();
 
                         let new_request = self.build_initial_request();
 
@@ -227,7 +293,7 @@ impl Repl {
                                 self.session_state.conversation.clear();
                                 self.session_state
                                     .conversation
-                                    .restore_messages(conversation_backup);
+                                    .restore_messages(conversation_backup, pins_backup);
                                 let failure_note = format!(
                                     "[SYSTEM ERROR: Image loading failed and the retry also failed: {}.]",
                                     retry_err
@@ -297,6 +363,10 @@ impl Repl {
             Arc::clone(&self.steer_buffer),
             self.session_state.session_id.clone(),
         );
+        handler.set_budget(self.budget.clone());
+        handler.set_max_tool_iterations(self.max_tool_iterations);
+        handler.set_auto_continue(self.auto_continue);
+        handler.set_sampling(self.model_config.sampling);
 
         let result = runtime.block_on(handler.handle_response(
             response.content,
@@ -312,6 +382,12 @@ impl Repl {
         // Always preserve conversation state so the AI retains context on retry
         self.session_state.conversation = handler.conversation().clone();
 
+        // Printed even when the turn was interrupted or failed part-way,
+        // since the files on disk changed either way.
+        if let Some(summary) = self.tool_executor.take_file_changes().render() {
+            println!("{}", summary);
+        }
+
         match result {
             Ok(_) => {
                 println!(
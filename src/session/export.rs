@@ -0,0 +1,117 @@
+//! Markdown rendering of a session's display history for `/export`, so
+//! a conversation can be shared as a readable document.
+
+use super::DisplayMessage;
+
+/// Shortest fence that Markdown renderers accept.
+const MIN_FENCE_LEN: usize = 3;
+
+/// Render `messages` as Markdown under a `title` heading. Prompts and
+/// replies become headed sections, with replies copied verbatim so their
+/// code blocks survive. Each tool call becomes a collapsed `<details>`
+/// block with its input and output.
+pub fn to_markdown(title: &str, messages: &[DisplayMessage]) -> String {
+    let mut out = format!("# {}\n", title);
+    for message in messages {
+        out.push('\n');
+        match message {
+            DisplayMessage::UserMessage { content } => {
+                out.push_str(&format!("## User\n\n{}\n", content.trim_end()));
+            }
+            DisplayMessage::AssistantMessage { content } => {
+                out.push_str(&format!("## Assistant\n\n{}\n", content.trim_end()));
+            }
+            DisplayMessage::ToolExecution {
+                tool_name,
+                tool_input,
+                tool_output,
+                failed,
+            } => {
+                let mut summary = format!("<code>{}</code>", escape_html(tool_name));
+                if let Some(target) = ["command", "path", "pattern", "query"]
+                    .iter()
+                    .find_map(|key| tool_input.get(*key).and_then(|v| v.as_str()))
+                {
+                    summary.push_str(&format!(": {}", escape_html(target)));
+                }
+                if *failed {
+                    summary.push_str(" (failed)");
+                }
+                let input = serde_json::to_string_pretty(tool_input)
+                    .unwrap_or_else(|_| tool_input.to_string());
+                out.push_str(&format!(
+                    "<details>\n<summary>{}</summary>\n\n**Input**\n\n{}\n**Output**\n\n{}\n</details>\n",
+                    summary,
+                    fenced(&input, "json"),
+                    fenced(tool_output, "")
+                ));
+            }
+        }
+    }
+    out
+}
+
+/// `content` in a code fence one backtick longer than any run of
+/// backticks inside it, so nested fences in tool output stay literal.
+fn fenced(content: &str, lang: &str) -> String {
+    let longest_run = content.split(|c| c != '`').map(str::len).max().unwrap_or(0);
+    let fence = "`".repeat((longest_run + 1).max(MIN_FENCE_LEN));
+    format!("{fence}{lang}\n{}\n{fence}\n", content.trim_end())
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn renders_sections_and_collapsible_tool_calls() {
+        let messages = vec![
+            DisplayMessage::UserMessage {
+                content: "Show the README".to_string(),
+            },
+            DisplayMessage::ToolExecution {
+                tool_name: "execute_bash".to_string(),
+                tool_input: json!({"command": "cat README.md"}),
+                tool_output: "```rust\nfn main() {}\n```".to_string(),
+                failed: false,
+            },
+            DisplayMessage::AssistantMessage {
+                content: "It has one example:\n\n```rust\nfn main() {}\n```\n".to_string(),
+            },
+        ];
+
+        let markdown = to_markdown("Sofos session", &messages);
+
+        assert!(markdown.starts_with("# Sofos session\n\n## User\n\nShow the README\n"));
+        assert!(
+            markdown
+                .contains("<details>\n<summary><code>execute_bash</code>: cat README.md</summary>")
+        );
+        assert!(markdown.contains("````\n```rust\nfn main() {}\n```\n````\n\n</details>"));
+        assert!(
+            markdown
+                .contains("## Assistant\n\nIt has one example:\n\n```rust\nfn main() {}\n```\n")
+        );
+    }
+
+    #[test]
+    fn failed_tools_are_marked_and_markup_is_escaped() {
+        let messages = vec![DisplayMessage::ToolExecution {
+            tool_name: "read_file".to_string(),
+            tool_input: json!({"path": "<missing>.rs"}),
+            tool_output: "not found".to_string(),
+            failed: true,
+        }];
+
+        let markdown = to_markdown("t", &messages);
+
+        assert!(markdown.contains("<code>read_file</code>: &lt;missing&gt;.rs (failed)"));
+    }
+}
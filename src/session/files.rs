@@ -0,0 +1,208 @@
+//! Session-wide list of the files the file tools touched, rebuilt from
+//! the display history for `/files`, so resumed sessions are covered too.
+
+use super::DisplayMessage;
+use crate::tools::ToolName;
+use colored::Colorize;
+
+/// How a file was touched, in the order the groups are listed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileOperation {
+    Read,
+    Written,
+    Created,
+    Deleted,
+    Moved,
+}
+
+impl FileOperation {
+    const ALL: [Self; 5] = [
+        Self::Read,
+        Self::Written,
+        Self::Created,
+        Self::Deleted,
+        Self::Moved,
+    ];
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::Read => "Read",
+            Self::Written => "Written",
+            Self::Created => "Created",
+            Self::Deleted => "Deleted",
+            Self::Moved => "Moved",
+        }
+    }
+
+    /// The operation a successful call to `tool` performs, with the
+    /// path or `from → to` pair it performed it on.
+    fn of(tool: ToolName, input: &serde_json::Value) -> Option<(Self, String)> {
+        let field = |name: &str| input.get(name).and_then(|v| v.as_str());
+        let path = || field("path").map(str::to_string);
+        match tool {
+            ToolName::ReadFile => Some((Self::Read, path()?)),
+            ToolName::WriteFile
+            | ToolName::EditFile
+            | ToolName::MorphEditFile
+            | ToolName::UndoEdit => Some((Self::Written, path()?)),
+            ToolName::CreateDirectory => Some((Self::Created, path()?)),
+            ToolName::CopyFile => Some((Self::Created, field("destination")?.to_string())),
+            ToolName::DeleteFile | ToolName::DeleteDirectory => Some((Self::Deleted, path()?)),
+            ToolName::MoveFile => Some((
+                Self::Moved,
+                format!("{} → {}", field("source")?, field("destination")?),
+            )),
+            _ => None,
+        }
+    }
+}
+
+/// Every file touched by one operation, in first-touched order, with
+/// how many times.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct FileGroup {
+    operation: FileOperation,
+    files: Vec<(String, usize)>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct SessionFiles {
+    groups: Vec<FileGroup>,
+}
+
+impl SessionFiles {
+    /// Collect the successful file-tool calls in `messages`. Failed and
+    /// cancelled calls changed nothing and are left out.
+    pub fn from_messages(messages: &[DisplayMessage]) -> Self {
+        let mut groups: Vec<FileGroup> = FileOperation::ALL
+            .iter()
+            .map(|&operation| FileGroup {
+                operation,
+                files: Vec::new(),
+            })
+            .collect();
+        for message in messages {
+            let DisplayMessage::ToolExecution {
+                tool_name,
+                tool_input,
+                failed: false,
+                ..
+            } = message
+            else {
+                continue;
+            };
+            let Some((operation, path)) = ToolName::from_str(tool_name)
+                .ok()
+                .and_then(|tool| FileOperation::of(tool, tool_input))
+            else {
+                continue;
+            };
+            let Some(group) = groups.iter_mut().find(|g| g.operation == operation) else {
+                continue;
+            };
+            match group.files.iter_mut().find(|(p, _)| *p == path) {
+                Some((_, count)) => *count += 1,
+                None => group.files.push((path, 1)),
+            }
+        }
+        groups.retain(|g| !g.files.is_empty());
+        Self { groups }
+    }
+
+    /// The `/files` listing.
+    pub fn render(&self) -> String {
+        if self.groups.is_empty() {
+            return "No files touched this session.".dimmed().to_string();
+        }
+        let mut lines = vec![
+            "Files touched this session:"
+                .bright_cyan()
+                .bold()
+                .to_string(),
+        ];
+        for group in &self.groups {
+            lines.push(format!(
+                "  {} {}",
+                group.operation.label().bold(),
+                format!("({})", group.files.len()).dimmed()
+            ));
+            for (path, count) in &group.files {
+                if *count > 1 {
+                    lines.push(format!("    {} {}", path, format!("×{}", count).dimmed()));
+                } else {
+                    lines.push(format!("    {}", path));
+                }
+            }
+        }
+        lines.join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn tool(name: &str, input: serde_json::Value, failed: bool) -> DisplayMessage {
+        DisplayMessage::ToolExecution {
+            tool_name: name.to_string(),
+            tool_input: input,
+            tool_output: String::new(),
+            failed,
+        }
+    }
+
+    #[test]
+    fn groups_successful_file_tools_by_operation() {
+        let messages = vec![
+            DisplayMessage::UserMessage {
+                content: "tidy up".to_string(),
+            },
+            tool("read_file", json!({"path": "src/lib.rs"}), false),
+            tool("edit_file", json!({"path": "src/lib.rs"}), false),
+            tool("edit_file", json!({"path": "src/lib.rs"}), false),
+            tool("read_file", json!({"path": "README.md"}), false),
+            tool("delete_file", json!({"path": "old.rs"}), true),
+            tool(
+                "move_file",
+                json!({"source": "a.rs", "destination": "b.rs"}),
+                false,
+            ),
+            tool("execute_bash", json!({"command": "ls"}), false),
+        ];
+
+        let files = SessionFiles::from_messages(&messages);
+
+        assert_eq!(
+            files.groups,
+            [
+                FileGroup {
+                    operation: FileOperation::Read,
+                    files: vec![("src/lib.rs".to_string(), 1), ("README.md".to_string(), 1)],
+                },
+                FileGroup {
+                    operation: FileOperation::Written,
+                    files: vec![("src/lib.rs".to_string(), 2)],
+                },
+                FileGroup {
+                    operation: FileOperation::Moved,
+                    files: vec![("a.rs → b.rs".to_string(), 1)],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn sessions_saved_before_failures_were_recorded_still_load() {
+        let message: DisplayMessage = serde_json::from_value(json!({
+            "ToolExecution": {
+                "tool_name": "write_file",
+                "tool_input": {"path": "notes.md"},
+                "tool_output": "ok"
+            }
+        }))
+        .unwrap();
+        let files = SessionFiles::from_messages(&[message]);
+        assert_eq!(files.groups.len(), 1);
+    }
+}
@@ -9,6 +9,7 @@ use crate::session::history::atomic_write;
 use crate::session::history::model::{Session, SessionMetadata};
 use serde::{Deserialize, Serialize};
 use std::fs;
+use std::path::Path;
 
 pub(super) const INDEX_FILE: &str = "index.json";
 
@@ -21,25 +22,17 @@ impl HistoryManager {
     pub(super) fn update_index(&self, session: &Session) -> Result<()> {
         let index_path = self.index_path();
         let mut index: SessionIndex = if index_path.exists() {
-            // Treat a parse failure as a missing index — we're about
-            // to rewrite the file anyway, so a single corrupt entry
-            // shouldn't poison every later save. The session JSON
-            // itself already landed on disk above; rebuilding the
-            // index from one entry is correct, just less complete
-            // until the next pass walks the directory.
-            match fs::read_to_string(&index_path)
-                .ok()
-                .and_then(|s| serde_json::from_str::<SessionIndex>(&s).ok())
-            {
+            // A corrupt index shouldn't poison every later save: we're
+            // about to rewrite the file anyway, so rebuild it from the
+            // session files, which already include this one.
+            match Self::read_index(&index_path) {
                 Some(parsed) => parsed,
                 None => {
                     tracing::warn!(
                         path = %index_path.display(),
-                        "session index unreadable or malformed; rebuilding from this save"
+                        "session index unreadable or malformed; rebuilding from session files"
                     );
-                    SessionIndex {
-                        sessions: Vec::new(),
-                    }
+                    self.scan_sessions()
                 }
             }
         } else {
@@ -48,15 +41,7 @@ impl HistoryManager {
             }
         };
 
-        let preview = Self::extract_preview(&session.api_messages);
-        let metadata = SessionMetadata {
-            id: session.id.clone(),
-            preview,
-            created_at: session.created_at,
-            updated_at: session.updated_at,
-            message_count: session.api_messages.len(),
-        };
-
+        let metadata = Self::metadata_for(session);
         if let Some(pos) = index.sessions.iter().position(|s| s.id == session.id) {
             index.sessions[pos] = metadata;
         } else {
@@ -80,9 +65,83 @@ impl HistoryManager {
             return Ok(Vec::new());
         }
 
-        let content = fs::read_to_string(index_path)?;
-        let index: SessionIndex = serde_json::from_str(&content)?;
+        if let Some(index) = Self::read_index(&index_path) {
+            return Ok(index.sessions);
+        }
+
+        tracing::warn!(
+            path = %index_path.display(),
+            "session index unreadable or malformed; rebuilding from session files"
+        );
+        let _lock = self.acquire_save_lock()?;
+        let index = self.scan_sessions();
+        let content = serde_json::to_string_pretty(&index)?;
+        atomic_write(&index_path, &content)?;
 
         Ok(index.sessions)
     }
+
+    /// Id of the most recently updated session, for `--continue`.
+    pub fn latest_session_id(&self) -> Result<Option<String>> {
+        Ok(self
+            .list_sessions()?
+            .into_iter()
+            .max_by_key(|session| session.updated_at)
+            .map(|session| session.id))
+    }
+
+    /// Sessions whose title or preview contains `query`, ignoring case,
+    /// for `--search`.
+    pub fn search_sessions(&self, query: &str) -> Result<Vec<SessionMetadata>> {
+        Ok(self
+            .list_sessions()?
+            .into_iter()
+            .filter(|session| session.matches(query))
+            .collect())
+    }
+
+    fn read_index(path: &Path) -> Option<SessionIndex> {
+        fs::read_to_string(path)
+            .ok()
+            .and_then(|s| serde_json::from_str(&s).ok())
+    }
+
+    fn metadata_for(session: &Session) -> SessionMetadata {
+        SessionMetadata {
+            id: session.id.clone(),
+            preview: Self::extract_preview(&session.api_messages),
+            title: session.title.clone(),
+            created_at: session.created_at,
+            updated_at: session.updated_at,
+            message_count: session.api_messages.len(),
+        }
+    }
+
+    /// Build an index from every readable session file in the sessions
+    /// directory, skipping files that don't parse. Callers hold the
+    /// save-lock and write the result.
+    fn scan_sessions(&self) -> SessionIndex {
+        let mut sessions: Vec<SessionMetadata> = fs::read_dir(self.sessions_dir())
+            .into_iter()
+            .flatten()
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| {
+                path.extension().is_some_and(|ext| ext == "json")
+                    && path.file_name().is_some_and(|name| name != INDEX_FILE)
+            })
+            .filter_map(|path| {
+                let session = fs::read_to_string(&path)
+                    .ok()
+                    .and_then(|s| serde_json::from_str::<Session>(&s).ok());
+                if session.is_none() {
+                    tracing::debug!(path = %path.display(), "skipping unreadable session file");
+                }
+                session
+            })
+            .map(|session| Self::metadata_for(&session))
+            .collect();
+        sessions.sort_by_key(|s| std::cmp::Reverse(s.updated_at));
+        SessionIndex { sessions }
+    }
 }
@@ -7,9 +7,11 @@ use crate::api::{Message, SystemPrompt};
 use crate::error::{Result, SofosError};
 use crate::session::history::atomic_write;
 use crate::session::history::index::{INDEX_FILE, SessionIndex};
-use crate::session::history::model::{DisplayMessage, Session, SessionTokenCounters};
+use crate::session::history::model::{
+    DisplayMessage, SESSION_FORMAT_VERSION, Session, SessionTokenCounters,
+};
 use std::fs::{self, File, OpenOptions};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 pub(super) const SOFOS_DIR: &str = ".sofos";
@@ -80,7 +82,7 @@ impl HistoryManager {
     /// Acquire an exclusive OS-level lock on the save-lock file for
     /// the lifetime of the returned `File`; the OS releases the lock
     /// when the handle drops, including on crash.
-    fn acquire_save_lock(&self) -> Result<File> {
+    pub(super) fn acquire_save_lock(&self) -> Result<File> {
         let path = self.save_lock_path();
         let file = OpenOptions::new()
             .read(true)
@@ -159,6 +161,7 @@ impl HistoryManager {
         model: &str,
         readonly: bool,
         permission_preset: Option<&str>,
+        pinned_messages: &[usize],
     ) -> Result<()> {
         Self::validate_session_id(session_id)?;
         let _lock = self.acquire_save_lock()?;
@@ -170,26 +173,27 @@ impl HistoryManager {
 
         let session_path = self.sessions_dir().join(format!("{}.json", session_id));
 
-        // Preserve `created_at` from any prior save. If the old file is
-        // unreadable or no longer parses (user edited it, disk
+        // Preserve `created_at` and the title from any prior save. If the
+        // old file is unreadable or no longer parses (user edited it, disk
         // corruption, schema change), fall back to `now` rather than
         // propagating the error — losing the in-memory conversation to
         // save a `created_at` stamp would be an awful trade.
-        let created_at = match fs::read_to_string(&session_path) {
+        let (created_at, title) = match fs::read_to_string(&session_path) {
             Ok(raw) => match serde_json::from_str::<Session>(&raw) {
-                Ok(existing) => existing.created_at,
+                Ok(existing) => (existing.created_at, existing.title),
                 Err(e) => {
                     tracing::warn!(
                         session_id = %session_id,
                         error = %e,
                         "failed to parse prior session save; resetting created_at to now"
                     );
-                    now
+                    (now, None)
                 }
             },
-            Err(_) => now,
+            Err(_) => (now, None),
         };
         let session = Session {
+            format_version: SESSION_FORMAT_VERSION,
             id: session_id.to_string(),
             api_messages: messages.to_vec(),
             display_messages: display_messages.to_vec(),
@@ -200,6 +204,8 @@ impl HistoryManager {
             model: Some(model.to_string()),
             readonly: Some(readonly),
             permission_preset: permission_preset.map(str::to_string),
+            title,
+            pinned_messages: pinned_messages.to_vec(),
         };
 
         let content = serde_json::to_string_pretty(&session)?;
@@ -210,6 +216,18 @@ impl HistoryManager {
         Ok(())
     }
 
+    /// Give a saved session a title. The session keeps its place in the
+    /// list: `updated_at` is unchanged.
+    pub fn rename_session(&self, session_id: &str, title: &str) -> Result<()> {
+        let _lock = self.acquire_save_lock()?;
+        let mut session = self.load_session(session_id)?;
+        session.title = Some(title.to_string());
+        let session_path = self.sessions_dir().join(format!("{}.json", session_id));
+        let content = serde_json::to_string_pretty(&session)?;
+        atomic_write(&session_path, &content)?;
+        self.update_index(&session)
+    }
+
     pub fn load_session(&self, session_id: &str) -> Result<Session> {
         Self::validate_session_id(session_id)?;
         let session_path = self.sessions_dir().join(format!("{}.json", session_id));
@@ -227,7 +245,60 @@ impl HistoryManager {
         Ok(session)
     }
 
-    #[allow(dead_code)]
+    /// Copy a session file exported from another workspace or machine
+    /// into this workspace's store and add it to the index, so it shows
+    /// up in the `/resume` picker. The file must parse as a session, carry
+    /// this build's format version, and hold at least one message. The original id is
+    /// kept unless it is unsafe as a file name or already taken here, in
+    /// which case a fresh one is assigned. Returns the stored id.
+    pub fn import_session(&self, path: &Path) -> Result<String> {
+        let content = fs::read_to_string(path).map_err(|e| {
+            SofosError::Config(format!(
+                "Failed to read session file '{}': {}",
+                path.display(),
+                e
+            ))
+        })?;
+        let mut session: Session = serde_json::from_str(&content).map_err(|e| {
+            SofosError::Config(format!(
+                "'{}' is not a valid Sofos session file: {}",
+                path.display(),
+                e
+            ))
+        })?;
+        if session.format_version != SESSION_FORMAT_VERSION {
+            return Err(SofosError::Config(format!(
+                "Session file '{}' uses format version {}, but this version of Sofos reads version {}",
+                path.display(),
+                session.format_version,
+                SESSION_FORMAT_VERSION
+            )));
+        }
+        if session.api_messages.is_empty() {
+            return Err(SofosError::Config(format!(
+                "Session file '{}' contains no messages",
+                path.display()
+            )));
+        }
+
+        let _lock = self.acquire_save_lock()?;
+        let id_taken = Self::validate_session_id(&session.id).is_err()
+            || self
+                .sessions_dir()
+                .join(format!("{}.json", session.id))
+                .exists();
+        if id_taken {
+            session.id = self.generate_unique_session_id();
+        }
+
+        let session_path = self.sessions_dir().join(format!("{}.json", session.id));
+        let content = serde_json::to_string_pretty(&session)?;
+        atomic_write(&session_path, &content)?;
+        self.update_index(&session)?;
+
+        Ok(session.id)
+    }
+
     pub fn delete_session(&self, session_id: &str) -> Result<()> {
         Self::validate_session_id(session_id)?;
         let _lock = self.acquire_save_lock()?;
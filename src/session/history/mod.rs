@@ -21,13 +21,17 @@ pub use model::{DisplayMessage, Session, SessionMetadata, SessionTokenCounters};
 
 use crate::error::Result;
 use std::fs;
+use std::io::Write;
 use std::path::PathBuf;
 
 /// Write content to a file atomically by writing to a temp file first, then renaming.
-/// This prevents corruption if the process crashes mid-write.
+/// This prevents corruption if the process crashes mid-write. The temp file is
+/// synced before the rename so a power loss can't leave an empty file behind.
 pub(super) fn atomic_write(path: &PathBuf, content: &str) -> Result<()> {
     let tmp_path = path.with_extension("json.tmp");
-    fs::write(&tmp_path, content)?;
+    let mut file = fs::File::create(&tmp_path)?;
+    file.write_all(content.as_bytes())?;
+    file.sync_all()?;
     fs::rename(&tmp_path, path)?;
     Ok(())
 }
@@ -93,6 +97,7 @@ mod tests {
                 "",
                 false,
                 None,
+                &[],
             )
             .unwrap();
 
@@ -102,6 +107,161 @@ mod tests {
         assert_eq!(loaded.system_prompt, vec![system_prompt]);
     }
 
+    #[test]
+    fn import_session_adds_file_to_index_and_renames_on_collision() {
+        let source_dir = TempDir::new().unwrap();
+        let source = HistoryManager::new(source_dir.path().to_path_buf()).unwrap();
+        let session_id = HistoryManager::generate_session_id();
+        let system_prompt = SystemPrompt::new_cached_with_ttl("System".to_string(), None);
+        source
+            .save_session(
+                &session_id,
+                &[Message::user("Shared by a teammate")],
+                &[],
+                std::slice::from_ref(&system_prompt),
+                SessionTokenCounters::default(),
+                "",
+                false,
+                None,
+                &[],
+            )
+            .unwrap();
+        let exported = source_dir
+            .path()
+            .join(SOFOS_DIR)
+            .join(SESSIONS_DIR)
+            .join(format!("{}.json", session_id));
+
+        let target_dir = TempDir::new().unwrap();
+        let target = HistoryManager::new(target_dir.path().to_path_buf()).unwrap();
+        let first = target.import_session(&exported).unwrap();
+        assert_eq!(first, session_id);
+        let second = target.import_session(&exported).unwrap();
+        assert_ne!(second, session_id);
+
+        let listed = target.list_sessions().unwrap();
+        assert_eq!(listed.len(), 2);
+        assert!(listed.iter().all(|s| s.preview == "Shared by a teammate"));
+        assert_eq!(target.load_session(&second).unwrap().id, second);
+    }
+
+    #[test]
+    fn latest_session_id_is_none_without_sessions() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = HistoryManager::new(temp_dir.path().to_path_buf()).unwrap();
+        assert_eq!(manager.latest_session_id().unwrap(), None);
+    }
+
+    #[test]
+    fn renamed_session_keeps_its_title_and_is_searchable() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = HistoryManager::new(temp_dir.path().to_path_buf()).unwrap();
+        let system_prompt = SystemPrompt::new_cached_with_ttl("System".to_string(), None);
+        let save = |id: &str, prompt: &str| {
+            manager
+                .save_session(
+                    id,
+                    &[Message::user(prompt)],
+                    &[],
+                    std::slice::from_ref(&system_prompt),
+                    SessionTokenCounters::default(),
+                    "",
+                    false,
+                    None,
+                    &[],
+                )
+                .unwrap();
+        };
+        save("session_1", "Fix the flaky login test");
+        save("session_2", "Split the parser module");
+
+        manager
+            .rename_session("session_2", "Parser Refactor")
+            .unwrap();
+        // A later save must not drop the title.
+        save("session_2", "Split the parser module");
+
+        let found = manager.search_sessions("refactor").unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].id, "session_2");
+        assert_eq!(found[0].label(), "Parser Refactor");
+        let by_preview = manager.search_sessions("LOGIN").unwrap();
+        assert_eq!(by_preview.len(), 1);
+        assert_eq!(by_preview[0].label(), "Fix the flaky login test");
+        assert!(manager.search_sessions("deploy").unwrap().is_empty());
+    }
+
+    #[test]
+    fn delete_session_removes_file_and_index_entry() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = HistoryManager::new(temp_dir.path().to_path_buf()).unwrap();
+        let system_prompt = SystemPrompt::new_cached_with_ttl("System".to_string(), None);
+        for id in ["session_1", "session_2"] {
+            manager
+                .save_session(
+                    id,
+                    &[Message::user("Abandoned")],
+                    &[],
+                    std::slice::from_ref(&system_prompt),
+                    SessionTokenCounters::default(),
+                    "",
+                    false,
+                    None,
+                    &[],
+                )
+                .unwrap();
+        }
+
+        manager.delete_session("session_1").unwrap();
+
+        let listed = manager.list_sessions().unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].id, "session_2");
+        assert!(manager.load_session("session_1").is_err());
+    }
+
+    #[test]
+    fn import_session_rejects_non_session_json() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = HistoryManager::new(temp_dir.path().to_path_buf()).unwrap();
+        let bogus = temp_dir.path().join("notes.json");
+        fs::write(&bogus, r#"{"hello": "world"}"#).unwrap();
+        let err = manager.import_session(&bogus).unwrap_err().to_string();
+        assert!(err.contains("not a valid Sofos session file"), "{err}");
+        assert!(manager.list_sessions().unwrap().is_empty());
+    }
+
+    #[test]
+    fn import_session_rejects_another_format_version() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = HistoryManager::new(temp_dir.path().to_path_buf()).unwrap();
+        let session = |version: Option<u32>| {
+            let mut json = serde_json::json!({
+                "id": "session_1",
+                "api_messages": [Message::user("From another build")],
+                "system_prompt": [],
+                "created_at": 0,
+                "updated_at": 0,
+            });
+            if let Some(version) = version {
+                json["format_version"] = version.into();
+            }
+            let path = temp_dir.path().join("shared.json");
+            fs::write(&path, json.to_string()).unwrap();
+            path
+        };
+
+        let err = manager
+            .import_session(&session(Some(model::SESSION_FORMAT_VERSION + 1)))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("format version"), "{err}");
+        assert!(manager.list_sessions().unwrap().is_empty());
+
+        // Files from before the version field are the first format.
+        assert_eq!(manager.import_session(&session(None)).unwrap(), "session_1");
+    }
+
     #[test]
     fn test_list_sessions() {
         let temp_dir = TempDir::new().unwrap();
@@ -120,6 +280,7 @@ mod tests {
                 "",
                 false,
                 None,
+                &[],
             )
             .unwrap();
 
@@ -136,6 +297,7 @@ mod tests {
                 "",
                 false,
                 None,
+                &[],
             )
             .unwrap();
 
@@ -143,6 +305,46 @@ mod tests {
         assert_eq!(sessions.len(), 2);
         assert_eq!(sessions[0].preview, "Second session");
         assert_eq!(sessions[1].preview, "First session");
+        assert_eq!(manager.latest_session_id().unwrap(), Some(session_id2));
+    }
+
+    #[test]
+    fn corrupt_index_is_rebuilt_from_session_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = HistoryManager::new(temp_dir.path().to_path_buf()).unwrap();
+        let system_prompt = SystemPrompt::new_cached_with_ttl("System".to_string(), None);
+        let save = |id: &str, text: &str| {
+            manager
+                .save_session(
+                    id,
+                    &[Message::user(text)],
+                    &[],
+                    std::slice::from_ref(&system_prompt),
+                    SessionTokenCounters::default(),
+                    "",
+                    false,
+                    None,
+                    &[],
+                )
+                .unwrap();
+        };
+        save(&HistoryManager::generate_session_id(), "Kept");
+        fs::write(
+            manager.sessions_dir().join("session_0_broken.json"),
+            "{truncated",
+        )
+        .unwrap();
+        fs::write(manager.index_path(), "{\"sessions\": [").unwrap();
+
+        let sessions = manager.list_sessions().unwrap();
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(sessions[0].preview, "Kept");
+        let on_disk = fs::read_to_string(manager.index_path()).unwrap();
+        assert!(serde_json::from_str::<index::SessionIndex>(&on_disk).is_ok());
+
+        fs::write(manager.index_path(), "").unwrap();
+        save(&HistoryManager::generate_session_id(), "Another");
+        assert_eq!(manager.list_sessions().unwrap().len(), 2);
     }
 
     #[test]
@@ -214,6 +416,7 @@ mod tests {
                 "",
                 false,
                 None,
+                &[],
             )
             .unwrap();
 
@@ -273,6 +476,7 @@ mod tests {
             "",
             false,
             None,
+            &[],
         );
         assert!(
             save_result.is_ok(),
@@ -325,6 +529,7 @@ mod tests {
                             "",
                             false,
                             None,
+                            &[],
                         )
                         .unwrap();
                 }
@@ -366,6 +571,7 @@ mod tests {
                 crate::api::model_info::CLAUDE_OPUS,
                 true,
                 Some(crate::config::PermissionPreset::ReadOnly.label()),
+                &[],
             )
             .unwrap();
 
@@ -428,6 +634,7 @@ mod tests {
                     "",
                     false,
                     None,
+                    &[],
                 )
                 .err();
             assert!(save_err.is_some(), "save_session must reject '{}'", bad);
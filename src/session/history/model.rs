@@ -18,6 +18,9 @@ pub enum DisplayMessage {
         tool_name: String,
         tool_input: serde_json::Value,
         tool_output: String,
+        /// The tool errored or was cancelled, so it changed nothing.
+        #[serde(default)]
+        failed: bool,
     },
 }
 
@@ -25,11 +28,31 @@ pub enum DisplayMessage {
 pub struct SessionMetadata {
     pub id: String,
     pub preview: String,
+    /// Name given with `/rename`, shown instead of the preview.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
     pub created_at: u64,
     pub updated_at: u64,
     pub message_count: usize,
 }
 
+impl SessionMetadata {
+    /// What the pickers show for this session: its title, else the preview.
+    pub fn label(&self) -> &str {
+        self.title.as_deref().unwrap_or(&self.preview)
+    }
+
+    /// Case-insensitive match of `query` against the title and preview.
+    pub fn matches(&self, query: &str) -> bool {
+        let query = query.to_lowercase();
+        self.preview.to_lowercase().contains(&query)
+            || self
+                .title
+                .as_ref()
+                .is_some_and(|title| title.to_lowercase().contains(&query))
+    }
+}
+
 /// Snapshot of session token counters persisted alongside the
 /// conversation. Every field has `#[serde(default)]` so older session
 /// files (written before persistence was added) load with all counters
@@ -56,8 +79,21 @@ pub struct SessionTokenCounters {
     pub peak_single_turn_input_tokens: u32,
 }
 
+/// Version of the session file layout, bumped when a change would make
+/// an older build misread a newer file. Files written before the field
+/// existed are version 1, the layout they already had.
+pub const SESSION_FORMAT_VERSION: u32 = 1;
+
+fn unversioned_session_format() -> u32 {
+    1
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Session {
+    /// [`SESSION_FORMAT_VERSION`] of the build that wrote the file.
+    /// Checked before an exported session is imported.
+    #[serde(default = "unversioned_session_format")]
+    pub format_version: u32,
     pub id: String,
     /// Messages in API format (for continuing the conversation with AI)
     pub api_messages: Vec<Message>,
@@ -96,4 +132,11 @@ pub struct Session {
     /// existed — `readonly` is used as the fallback there.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub permission_preset: Option<String>,
+    /// Name given with `/rename`. Carried over by every later save.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// Indices into `api_messages` of the messages pinned with `/pin`,
+    /// which context trimming and compaction keep.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pinned_messages: Vec<usize>,
 }
@@ -1,7 +1,11 @@
+mod export;
+mod files;
 pub mod history;
 mod selector;
 mod state;
 
+pub use export::to_markdown;
+pub use files::SessionFiles;
 pub use history::{DisplayMessage, HistoryManager, SessionMetadata, SessionTokenCounters};
-pub use selector::select_session;
+pub use selector::{now_secs, relative_time, select_session};
 pub use state::SessionState;
@@ -1,78 +1,164 @@
 use crate::error::Result;
-use crate::session::SessionMetadata;
+use crate::session::{HistoryManager, SessionMetadata};
+use chrono::{DateTime, Local, NaiveDate};
 use colored::Colorize;
 use std::io::{self, Write};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 const SECONDS_PER_MINUTE: u64 = 60;
 const SECONDS_PER_HOUR: u64 = 60 * SECONDS_PER_MINUTE;
 const SECONDS_PER_DAY: u64 = 24 * SECONDS_PER_HOUR;
 const SECONDS_PER_WEEK: u64 = 7 * SECONDS_PER_DAY;
 
-pub fn select_session(sessions: Vec<SessionMetadata>) -> Result<Option<String>> {
+/// Prompt for a session to resume. `d <number>` deletes that session
+/// after a confirmation and shows the list again; `None` when the user
+/// cancels or no sessions are left.
+pub fn select_session(
+    manager: &HistoryManager,
+    mut sessions: Vec<SessionMetadata>,
+) -> Result<Option<String>> {
     if sessions.is_empty() {
         println!("{}", "No saved sessions found.".yellow());
         return Ok(None);
     }
 
-    println!("\n{}", "Select a session to resume:".bright_cyan().bold());
-    println!();
+    loop {
+        println!("\n{}", "Select a session to resume:".bright_cyan().bold());
+        println!();
 
-    for (i, session) in sessions.iter().enumerate() {
-        let date = format_timestamp(session.updated_at);
-        let msg_count = format!("{} messages", session.message_count);
-
-        println!(
-            "  {} {} {}",
-            format!("[{}]", i + 1).bright_green().bold(),
-            session.preview.bright_white(),
-            format!("({} • {})", date, msg_count).dimmed()
-        );
-    }
-
-    println!();
-    print!("{} ", "Enter number (or 'q' to cancel):".dimmed());
-    io::stdout().flush()?;
+        let now = now_secs();
+        let grouped = sessions
+            .iter()
+            .any(|s| day_group(s.updated_at, now) != day_group(sessions[0].updated_at, now));
+        let mut group = "";
+        for (i, session) in sessions.iter().enumerate() {
+            let session_group = day_group(session.updated_at, now);
+            if grouped && session_group != group {
+                group = session_group;
+                println!("  {}", group.bold());
+            }
+            println!(
+                "  {} {} {}",
+                format!("[{}]", i + 1).bright_green().bold(),
+                session.label().bright_white(),
+                format!(
+                    "({} • {} messages)",
+                    relative_time(session.updated_at, now),
+                    session.message_count
+                )
+                .dimmed()
+            );
+        }
 
-    let mut input = String::new();
-    io::stdin().read_line(&mut input)?;
-    let input = input.trim();
+        println!();
+        let input = prompt_line("Enter number, 'd <number>' to delete, or 'q' to cancel:")?;
 
-    if input.is_empty() || input == "q" || input == "quit" {
-        return Ok(None);
-    }
+        if input.is_empty() || input == "q" || input == "quit" {
+            return Ok(None);
+        }
 
-    match input.parse::<usize>() {
-        Ok(num) if num > 0 && num <= sessions.len() => Ok(Some(sessions[num - 1].id.clone())),
-        _ => {
+        let Some(target) = input.strip_prefix('d') else {
+            return match parse_choice(&input, sessions.len()) {
+                Some(index) => Ok(Some(sessions[index].id.clone())),
+                None => {
+                    println!("{}", "Invalid selection".red());
+                    Ok(None)
+                }
+            };
+        };
+        let Some(index) = parse_choice(target, sessions.len()) else {
             println!("{}", "Invalid selection".red());
-            Ok(None)
+            continue;
+        };
+        let question = format!("Delete \"{}\"? [y/N]", sessions[index].label());
+        if !matches!(prompt_line(&question)?.as_str(), "y" | "yes") {
+            continue;
+        }
+        manager.delete_session(&sessions[index].id)?;
+        sessions.remove(index);
+        println!("{}", "Session deleted.".bright_green());
+        if sessions.is_empty() {
+            println!("{}", "No saved sessions left.".yellow());
+            return Ok(None);
         }
     }
 }
 
-fn format_timestamp(timestamp: u64) -> String {
-    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+/// Zero-based index for a 1-based `input` within `len` entries.
+fn parse_choice(input: &str, len: usize) -> Option<usize> {
+    match input.trim().parse::<usize>() {
+        Ok(num) if num > 0 && num <= len => Some(num - 1),
+        _ => None,
+    }
+}
 
-    let now = SystemTime::now()
+fn prompt_line(label: &str) -> Result<String> {
+    print!("{} ", label.dimmed());
+    io::stdout().flush()?;
+    let mut input = String::new();
+    io::stdin().read_line(&mut input)?;
+    Ok(input.trim().to_lowercase())
+}
+
+/// Seconds since the Unix epoch, for [`relative_time`] and [`day_group`].
+pub fn now_secs() -> u64 {
+    SystemTime::now()
         .duration_since(UNIX_EPOCH)
         .unwrap_or(Duration::ZERO)
-        .as_secs();
-    let diff = now.saturating_sub(timestamp);
+        .as_secs()
+}
 
+/// How long before `now` `timestamp` was: "just now", "5m ago", "2h ago",
+/// "3d ago", and the date once it is a week or more.
+pub fn relative_time(timestamp: u64, now: u64) -> String {
+    let diff = now.saturating_sub(timestamp);
     if diff < SECONDS_PER_MINUTE {
         "just now".to_string()
     } else if diff < SECONDS_PER_HOUR {
-        let mins = diff / SECONDS_PER_MINUTE;
-        format!("{} min{} ago", mins, if mins == 1 { "" } else { "s" })
+        format!("{}m ago", diff / SECONDS_PER_MINUTE)
     } else if diff < SECONDS_PER_DAY {
-        let hours = diff / SECONDS_PER_HOUR;
-        format!("{} hour{} ago", hours, if hours == 1 { "" } else { "s" })
+        format!("{}h ago", diff / SECONDS_PER_HOUR)
     } else if diff < SECONDS_PER_WEEK {
-        let days = diff / SECONDS_PER_DAY;
-        format!("{} day{} ago", days, if days == 1 { "" } else { "s" })
+        format!("{}d ago", diff / SECONDS_PER_DAY)
     } else {
-        use chrono::{DateTime, Utc};
-        let dt = DateTime::<Utc>::from(UNIX_EPOCH + Duration::from_secs(timestamp));
-        dt.format("%Y-%m-%d").to_string()
+        local_date(timestamp).format("%Y-%m-%d").to_string()
+    }
+}
+
+/// The heading a session updated at `timestamp` is listed under, by
+/// calendar day in local time.
+fn day_group(timestamp: u64, now: u64) -> &'static str {
+    let days = (local_date(now) - local_date(timestamp)).num_days();
+    match days {
+        ..=0 => "Today",
+        1 => "Yesterday",
+        2..=6 => "Earlier this week",
+        _ => "Older",
+    }
+}
+
+fn local_date(timestamp: u64) -> NaiveDate {
+    DateTime::<Local>::from(UNIX_EPOCH + Duration::from_secs(timestamp)).date_naive()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ages_are_short_and_days_are_grouped() {
+        let now = 1_800_000_000;
+        assert_eq!(relative_time(now - 30, now), "just now");
+        assert_eq!(relative_time(now - 5 * SECONDS_PER_MINUTE, now), "5m ago");
+        assert_eq!(relative_time(now - 2 * SECONDS_PER_HOUR, now), "2h ago");
+        assert_eq!(relative_time(now - 3 * SECONDS_PER_DAY, now), "3d ago");
+        assert_eq!(
+            relative_time(now - 30 * SECONDS_PER_DAY, now),
+            local_date(now - 30 * SECONDS_PER_DAY).to_string()
+        );
+        assert_eq!(relative_time(now + 60, now), "just now", "clock skew");
+
+        assert_eq!(day_group(now, now), "Today");
+        assert_eq!(day_group(now - 30 * SECONDS_PER_DAY, now), "Older");
     }
 }
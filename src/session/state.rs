@@ -44,6 +44,21 @@ mod tests {
         assert_eq!(state.total_output_tokens, 800);
         assert_eq!(state.peak_single_turn_input_tokens, 2_500);
     }
+
+    #[test]
+    fn scale_usage_keeps_the_share_of_every_counter() {
+        let mut state = SessionState::new("test".to_string(), ConversationHistory::new());
+        state.add_usage(&usage_with_inputs(1_000, 300));
+        state.total_cache_read_tokens = 4_000;
+
+        state.scale_usage(0.25);
+        assert_eq!(state.total_input_tokens, 250);
+        assert_eq!(state.total_output_tokens, 75);
+        assert_eq!(state.total_cache_read_tokens, 1_000);
+        assert_eq!(state.peak_single_turn_input_tokens, 250);
+        state.scale_usage(2.0);
+        assert_eq!(state.total_input_tokens, 250, "a share above 1 is capped");
+    }
 }
 
 /// Manages the state of a single REPL session
@@ -117,6 +132,21 @@ impl SessionState {
         self.peak_single_turn_input_tokens = 0;
     }
 
+    /// Scale every token counter by `share`, between 0 and 1, when only
+    /// that part of the conversation is kept.
+    pub fn scale_usage(&mut self, share: f64) {
+        let share = share.clamp(0.0, 1.0);
+        for counter in [
+            &mut self.total_input_tokens,
+            &mut self.total_output_tokens,
+            &mut self.total_cache_read_tokens,
+            &mut self.total_cache_creation_tokens,
+            &mut self.peak_single_turn_input_tokens,
+        ] {
+            *counter = (f64::from(*counter) * share).round() as u32;
+        }
+    }
+
     pub fn add_usage(&mut self, usage: &crate::api::Usage) {
         // `saturating_add` instead of `+=`: each counter is `u32`, and a
         // session that survives across `--resume` invocations
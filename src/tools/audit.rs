@@ -0,0 +1,193 @@
+//! Append-only JSONL audit log at `.sofos/audit.log`, enabled with
+//! `--audit`. Every tool call and every permission decision becomes one
+//! line, so a team can review what the assistant did in a repository.
+//! Recording never fails a tool call: write errors are only traced.
+
+use crate::error::SofosError;
+use crate::tools::executor::ToolExecutionResult;
+use serde_json::{Value, json};
+use std::fs::{self, OpenOptions};
+use std::io::Write as _;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+
+/// Log file, relative to the project root.
+pub const AUDIT_LOG_FILE: &str = ".sofos/audit.log";
+
+/// Size past which the log is moved to `audit.log.1` and started afresh,
+/// replacing the previous rotation.
+const MAX_AUDIT_LOG_BYTES: u64 = 10 * 1024 * 1024;
+
+/// Suffix of the single rotated log kept next to the live one.
+const ROTATED_SUFFIX: &str = ".1";
+
+/// Characters of a tool result, or of any string in a tool input, kept
+/// in an entry. File contents and long outputs are cut to this.
+const MAX_AUDIT_TEXT_CHARS: usize = 500;
+
+static AUDIT_LOG: OnceLock<AuditLog> = OnceLock::new();
+
+pub(crate) struct AuditLog {
+    path: PathBuf,
+    max_bytes: u64,
+    /// Serialises appends from concurrently running tools.
+    write_lock: Mutex<()>,
+}
+
+impl AuditLog {
+    fn new(path: PathBuf, max_bytes: u64) -> Self {
+        Self {
+            path,
+            max_bytes,
+            write_lock: Mutex::new(()),
+        }
+    }
+
+    fn append(&self, mut entry: Value) {
+        entry["time"] = json!(chrono::Utc::now().to_rfc3339());
+        let _guard = self.write_lock.lock();
+        if let Err(e) = self.try_append(&entry) {
+            tracing::warn!(error = %e, path = %self.path.display(), "failed to write audit log");
+        }
+    }
+
+    fn try_append(&self, entry: &Value) -> std::io::Result<()> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        if fs::metadata(&self.path).is_ok_and(|m| m.len() >= self.max_bytes) {
+            let mut rotated = self.path.clone().into_os_string();
+            rotated.push(ROTATED_SUFFIX);
+            fs::rename(&self.path, rotated)?;
+        }
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        writeln!(file, "{}", entry)
+    }
+}
+
+/// Start recording to `<root>/.sofos/audit.log`. Only the first call
+/// takes effect.
+pub fn enable(root: &Path) {
+    let _ = AUDIT_LOG.set(AuditLog::new(
+        root.join(AUDIT_LOG_FILE),
+        MAX_AUDIT_LOG_BYTES,
+    ));
+}
+
+/// Record a finished tool call: its input, whether it succeeded, and the
+/// start of its result or error.
+pub(crate) fn record_tool(
+    tool: &str,
+    input: &Value,
+    result: &crate::error::Result<ToolExecutionResult>,
+) {
+    if let Some(log) = AUDIT_LOG.get() {
+        log.append(tool_entry(tool, input, result));
+    }
+}
+
+/// Record a permission decision: `decision` is `allowed`, `denied`, or
+/// `ask` for a rule verdict, or the user's answer to a prompt.
+pub(crate) fn record_permission(scope: &str, subject: &str, decision: &str) {
+    if let Some(log) = AUDIT_LOG.get() {
+        log.append(json!({
+            "event": "permission",
+            "scope": scope,
+            "subject": subject,
+            "decision": decision,
+        }));
+    }
+}
+
+fn tool_entry(
+    tool: &str,
+    input: &Value,
+    result: &crate::error::Result<ToolExecutionResult>,
+) -> Value {
+    let (outcome, text) = match result {
+        Ok(result) => ("ok", result.text().to_string()),
+        Err(SofosError::Interrupted) => ("interrupted", String::new()),
+        Err(e) => ("error", e.to_string()),
+    };
+    json!({
+        "event": "tool",
+        "tool": tool,
+        "input": shorten_strings(input),
+        "outcome": outcome,
+        "result": shorten(&text),
+    })
+}
+
+fn shorten(text: &str) -> String {
+    if text.chars().count() <= MAX_AUDIT_TEXT_CHARS {
+        return text.to_string();
+    }
+    let kept: String = text.chars().take(MAX_AUDIT_TEXT_CHARS).collect();
+    format!("{}… [truncated]", kept)
+}
+
+/// `value` with every string cut to [`MAX_AUDIT_TEXT_CHARS`], so a
+/// `write_file` call does not copy the whole file into the log.
+fn shorten_strings(value: &Value) -> Value {
+    match value {
+        Value::String(s) => Value::String(shorten(s)),
+        Value::Array(items) => Value::Array(items.iter().map(shorten_strings).collect()),
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .map(|(key, value)| (key.clone(), shorten_strings(value)))
+                .collect(),
+        ),
+        other => other.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn entries_are_jsonl_with_long_text_cut() {
+        let dir = tempfile::tempdir().unwrap();
+        let log = AuditLog::new(dir.path().join(AUDIT_LOG_FILE), MAX_AUDIT_LOG_BYTES);
+        let content = "x".repeat(MAX_AUDIT_TEXT_CHARS * 2);
+        let result = Ok(ToolExecutionResult::Text("Successfully wrote".to_string()));
+        log.append(tool_entry(
+            "write_file",
+            &json!({"path": "a.txt", "content": content}),
+            &result,
+        ));
+        log.append(json!({"event": "permission", "decision": "denied"}));
+
+        let written = fs::read_to_string(dir.path().join(AUDIT_LOG_FILE)).unwrap();
+        let lines: Vec<Value> = written
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["tool"], "write_file");
+        assert_eq!(lines[0]["outcome"], "ok");
+        assert_eq!(lines[0]["input"]["path"], "a.txt");
+        let logged = lines[0]["input"]["content"].as_str().unwrap();
+        assert!(logged.ends_with("[truncated]") && logged.len() < content.len());
+        assert!(lines[1]["time"].is_string());
+    }
+
+    #[test]
+    fn a_full_log_is_rotated() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("audit.log");
+        let log = AuditLog::new(path.clone(), 64);
+        for i in 0..4 {
+            log.append(json!({"event": "tool", "n": i}));
+        }
+
+        let rotated = fs::read_to_string(dir.path().join("audit.log.1")).unwrap();
+        let live = fs::read_to_string(&path).unwrap();
+        assert!(live.len() as u64 <= 64 + 80, "{live}");
+        assert!(live.contains("\"n\":3"));
+        assert!(!rotated.contains("\"n\":3"));
+    }
+}
@@ -7,23 +7,26 @@
 
 use crate::config::{ApprovalPolicy, SandboxMode};
 use crate::error::{Result, SofosError};
+use crate::tools::audit;
 #[cfg(unix)]
 use crate::tools::bash::output::TERMINATION_GRACE_PERIOD;
 use crate::tools::bash::output::{
-    BASH_COMMAND_TIMEOUT, BASH_READ_CHUNK_BYTES, MAX_BASH_OUTPUT_BYTES, SUPERVISOR_POLL_INTERVAL,
-    TerminationReason,
+    BASH_READ_CHUNK_BYTES, BashLimits, LineEcho, OUTPUT_TRUNCATED_MARKER, SUPERVISOR_POLL_INTERVAL,
+    TerminationReason, Utf8ChunkDecoder, describe_byte_cap,
 };
 use crate::tools::bash::sandbox::{self, SandboxPolicy};
 use crate::tools::bash::validate::{
     command_contains_askable_git_checkout, command_redirects_output, command_runs_only_git,
-    detect_ansi_c_quoting, detect_command_substitution, has_path_traversal,
+    detect_ansi_c_quoting, detect_command_substitution, has_path_traversal, split_leading_cd,
 };
 use crate::tools::bash::{BashExecutor, EscalationRequest};
+use crate::tools::permissions::pattern::BLANKET_BASH;
 use crate::tools::permissions::{CommandPermission, PermissionManager};
 use crate::tools::utils::{
     MAX_TOOL_OUTPUT_TOKENS, TruncationKind, normalize_command_whitespace, truncate_for_context,
 };
-use std::collections::HashSet;
+use colored::Colorize;
+use std::collections::{HashMap, HashSet};
 use std::ffi::OsString;
 use std::io::Read;
 use std::path::PathBuf;
@@ -107,6 +110,7 @@ impl BashExecutor {
         // resolution would look like an escape.
         let workspace = std::fs::canonicalize(&workspace).unwrap_or(workspace);
         Ok(Self {
+            scope: workspace.clone(),
             workspace,
             interactive,
             has_morph,
@@ -116,12 +120,15 @@ impl BashExecutor {
             // sets the real mode on the next line.
             mode: SandboxMode::Sandboxed,
             approval_policy: ApprovalPolicy::default(),
-            session_allowed: Arc::new(Mutex::new(HashSet::new())),
+            session_allowed: Arc::new(Mutex::new(HashMap::new())),
             session_denied: Arc::new(Mutex::new(HashSet::new())),
             session_unsandboxed: Arc::new(Mutex::new(HashSet::new())),
             bash_path_session_allowed: Arc::new(Mutex::new(HashSet::new())),
             bash_path_session_denied: Arc::new(Mutex::new(HashSet::new())),
             interrupt_flag: Arc::new(AtomicBool::new(false)),
+            limits: BashLimits::default(),
+            live_output: false,
+            env_allow: Vec::new(),
         })
     }
 
@@ -138,6 +145,15 @@ impl BashExecutor {
         command: &str,
         escalation: Option<EscalationRequest>,
     ) -> Result<String> {
+        // `cd <subdir> && <rest>` runs the rest from that subdirectory. The
+        // directory is resolved here, so `cd` itself never reaches the
+        // shell and the rest goes through every check on its own.
+        if let Some((dir, rest)) = split_leading_cd(command) {
+            let mut scoped = self.clone();
+            scoped.scope = self.resolve_cd_target(dir)?;
+            return scoped.execute_with_escalation(rest, escalation);
+        }
+
         let mut permission_manager = PermissionManager::new(self.workspace.clone())?;
         let normalized = PermissionManager::normalize_command_key(command);
 
@@ -146,13 +162,13 @@ impl BashExecutor {
         // the sandbox, so when the model asks to escalate the same command let
         // it fall through to the escalation path below and ask afresh.
         let escalating = escalation.is_some() && self.sandbox_active();
-        if let Ok(allowed) = self.session_allowed.lock() {
-            if allowed.contains(&normalized) && !escalating {
-                return self.execute_after_permission_check(command, &mut permission_manager);
-            }
+        if !escalating && self.has_session_grant(&normalized) {
+            audit::record_permission(BLANKET_BASH, command, "allowed earlier this session");
+            return self.execute_after_permission_check(command, &mut permission_manager);
         }
         if let Ok(denied) = self.session_denied.lock() {
             if denied.contains(&normalized) {
+                audit::record_permission(BLANKET_BASH, command, "denied earlier this session");
                 return Err(SofosError::ToolExecution(format!(
                     "User already declined '{}' earlier this session. \
                      Propose a different approach or ask the user to clarify \
@@ -201,9 +217,11 @@ impl BashExecutor {
                     // Run it confined instead of prompting. The sandbox
                     // bounds writes and the network but not reads, so the
                     // gates in execute_after_permission_check still run.
+                    audit::record_permission(BLANKET_BASH, command, "sandboxed");
                     return self.execute_after_permission_check(command, &mut permission_manager);
                 }
-                let (allowed, remember) = permission_manager.ask_user_permission(command)?;
+                let (allowed, remember, allow_until) =
+                    permission_manager.ask_user_permission(command)?;
                 if !allowed {
                     if !remember {
                         // Store session-scoped denial
@@ -219,9 +237,9 @@ impl BashExecutor {
                     )));
                 }
                 if !remember {
-                    // Store session-scoped allowance
+                    // Store session-scoped allowance, timed or not
                     if let Ok(mut allowed) = self.session_allowed.lock() {
-                        allowed.insert(normalized);
+                        allowed.insert(normalized, allow_until);
                     }
                 }
             }
@@ -230,6 +248,22 @@ impl BashExecutor {
         self.execute_after_permission_check(command, &mut permission_manager)
     }
 
+    /// Whether `key` holds a live session grant. A lapsed timed grant
+    /// is dropped, so the command is asked about again.
+    pub(super) fn has_session_grant(&self, key: &str) -> bool {
+        let Ok(mut allowed) = self.session_allowed.lock() else {
+            return false;
+        };
+        match allowed.get(key) {
+            Some(Some(until)) if *until <= Instant::now() => {
+                allowed.remove(key);
+                false
+            }
+            Some(_) => true,
+            None => false,
+        }
+    }
+
     /// True when shell commands are confined here: the sandboxed mode plus a
     /// usable OS sandbox on this machine.
     fn sandbox_active(&self) -> bool {
@@ -315,29 +349,65 @@ impl BashExecutor {
         if let Some(reason) = outcome.terminated_for {
             return match reason {
                 TerminationReason::StdoutCapExceeded | TerminationReason::StderrCapExceeded => {
-                    let stream = if reason == TerminationReason::StdoutCapExceeded {
+                    let stdout_capped = reason == TerminationReason::StdoutCapExceeded;
+                    let stream = if stdout_capped {
                         "output"
                     } else {
                         "error output"
                     };
-                    Err(SofosError::ToolExecution(format!(
-                        "Command {} too large (exceeded {} MB cap). The process was terminated.",
+                    let mut message = format!(
+                        "Command {} too large (exceeded {} cap). The process was terminated.",
                         stream,
-                        MAX_BASH_OUTPUT_BYTES / (1024 * 1024)
+                        describe_byte_cap(self.limits.max_output_bytes)
+                    );
+                    for (label, text, capped) in [
+                        ("STDOUT", &outcome.stdout, stdout_capped),
+                        ("STDERR", &outcome.stderr, !stdout_capped),
+                    ] {
+                        if text.is_empty() {
+                            continue;
+                        }
+                        message.push_str(&format!("\n{} (partial):\n{}", label, text));
+                        if capped {
+                            message.push('\n');
+                            message.push_str(OUTPUT_TRUNCATED_MARKER);
+                        }
+                    }
+                    Err(SofosError::ToolExecution(truncate_for_context(
+                        &message,
+                        MAX_TOOL_OUTPUT_TOKENS,
+                        TruncationKind::BashOutput,
+                    )))
+                }
+                // Keep what the command printed before it was killed: it
+                // usually shows where a build or test run got stuck.
+                TerminationReason::Timeout => {
+                    let mut message = format!(
+                        "Command timed out after {}s and was terminated.",
+                        self.limits.timeout.as_secs()
+                    );
+                    if !outcome.stdout.is_empty() {
+                        message.push_str("\nSTDOUT (partial):\n");
+                        message.push_str(&outcome.stdout);
+                    }
+                    if !outcome.stderr.is_empty() {
+                        message.push_str("\nSTDERR (partial):\n");
+                        message.push_str(&outcome.stderr);
+                    }
+                    Err(SofosError::ToolExecution(truncate_for_context(
+                        &message,
+                        MAX_TOOL_OUTPUT_TOKENS,
+                        TruncationKind::BashOutput,
                     )))
                 }
-                TerminationReason::Timeout => Err(SofosError::ToolExecution(format!(
-                    "Command exceeded the {} second time limit and was terminated.",
-                    BASH_COMMAND_TIMEOUT.as_secs()
-                ))),
                 TerminationReason::Interrupt => Err(SofosError::ToolExecution(
                     "Command was interrupted by the user before it finished.".to_string(),
                 )),
             };
         }
 
-        let stdout = String::from_utf8_lossy(&outcome.stdout);
-        let stderr = String::from_utf8_lossy(&outcome.stderr);
+        let stdout = &outcome.stdout;
+        let stderr = &outcome.stderr;
 
         if !outcome.status.success() {
             let exit_info = match outcome.status.code() {
@@ -361,6 +431,12 @@ impl BashExecutor {
                     }
                 }
             };
+            if self.live_output {
+                println!(
+                    "{}",
+                    format!("Command failed with {}", exit_info).bright_red()
+                );
+            }
             let mut error_output = format!(
                 "Command failed with {}\nSTDOUT:\n{}\nSTDERR:\n{}",
                 exit_info, stdout, stderr
@@ -384,14 +460,14 @@ impl BashExecutor {
         let mut result = String::new();
         if !stdout.is_empty() {
             result.push_str("STDOUT:\n");
-            result.push_str(&stdout);
+            result.push_str(stdout);
         }
         if !stderr.is_empty() {
             if !result.is_empty() {
                 result.push('\n');
             }
             result.push_str("STDERR:\n");
-            result.push_str(&stderr);
+            result.push_str(stderr);
         }
 
         if result.is_empty() {
@@ -433,8 +509,8 @@ impl BashExecutor {
         }
 
         // The retry runs unconfined, so it must clear the same structural bar
-        // as any unsandboxed command — output redirection and here-documents
-        // stay refused because nothing would bound their writes. A confined
+        // as any unsandboxed command — redirection out of the workspace and
+        // here-documents stay refused because nothing would bound their writes. A confined
         // command that used them is simply not offered an unsandboxed retry.
         if !self.is_safe_command_structure(command) {
             return Ok(None);
@@ -499,9 +575,10 @@ impl BashExecutor {
         // Escalation lifts the sandbox, not the other gates. The command
         // then runs unconfined, so it must clear the same structural bar as
         // any unsandboxed command: parent traversal, hidden subcommands,
-        // dangerous git, output redirection, and here-documents stay refused,
-        // because without the sandbox nothing bounds their writes. The
-        // read-deny, git-checkout, and external-path checks still run below.
+        // dangerous git, redirection out of the workspace, and here-documents
+        // stay refused, because without the sandbox nothing bounds their
+        // writes. The read-deny, git-checkout, and external-path checks
+        // still run below.
         if !self.is_safe_command_structure(command) {
             return Err(SofosError::ToolExecution(
                 self.get_rejection_reason(command),
@@ -672,10 +749,11 @@ impl BashExecutor {
 
         let mut cmd = Command::new(&program);
         cmd.args(&args)
-            .current_dir(&self.workspace)
+            .current_dir(&self.scope)
             .stdin(Stdio::null())
             .stdout(Stdio::piped())
             .stderr(Stdio::piped());
+        crate::tools::child_env::restrict_shell_env(&mut cmd, &self.env_allow);
 
         if let Some(extra) = shell.extra_path_dir.as_ref() {
             let original = std::env::var_os("PATH").unwrap_or_default();
@@ -714,6 +792,10 @@ impl BashExecutor {
         let mut child = cmd
             .spawn()
             .map_err(|e| SofosError::ToolExecution(format!("Failed to execute command: {}", e)))?;
+        if self.live_output {
+            // End the "Executing:" header line before the echo starts.
+            println!();
+        }
 
         let stdout = child.stdout.take().ok_or_else(|| {
             SofosError::ToolExecution("Failed to capture command stdout".to_string())
@@ -722,8 +804,8 @@ impl BashExecutor {
             SofosError::ToolExecution("Failed to capture command stderr".to_string())
         })?;
 
-        let stdout_buf = Arc::new(Mutex::new(Vec::<u8>::new()));
-        let stderr_buf = Arc::new(Mutex::new(Vec::<u8>::new()));
+        let stdout_buf = Arc::new(Mutex::new(String::new()));
+        let stderr_buf = Arc::new(Mutex::new(String::new()));
         let stdout_overflow = Arc::new(AtomicBool::new(false));
         let stderr_overflow = Arc::new(AtomicBool::new(false));
 
@@ -731,11 +813,15 @@ impl BashExecutor {
             stdout,
             Arc::clone(&stdout_buf),
             Arc::clone(&stdout_overflow),
+            self.limits.max_output_bytes,
+            self.live_output.then(LineEcho::to_stdout),
         );
         let stderr_handle = spawn_capped_reader(
             stderr,
             Arc::clone(&stderr_buf),
             Arc::clone(&stderr_overflow),
+            self.limits.max_output_bytes,
+            self.live_output.then(LineEcho::to_stdout),
         );
 
         let start = Instant::now();
@@ -757,7 +843,7 @@ impl BashExecutor {
                 termination = Some(TerminationReason::Interrupt);
                 break;
             }
-            if start.elapsed() > BASH_COMMAND_TIMEOUT {
+            if start.elapsed() > self.limits.timeout {
                 termination = Some(TerminationReason::Timeout);
                 break;
             }
@@ -807,8 +893,8 @@ impl BashExecutor {
         let status = wait_result
             .map_err(|e| SofosError::ToolExecution(format!("Failed to reap command: {}", e)))?;
 
-        let stdout = drain_into_vec(stdout_buf);
-        let stderr = drain_into_vec(stderr_buf);
+        let stdout = drain_into_string(stdout_buf);
+        let stderr = drain_into_string(stderr_buf);
 
         // Remove the empty mount points the tmpfs masks left for protected
         // or denied paths that did not exist before the run. remove_dir
@@ -843,12 +929,14 @@ impl BashExecutor {
             extra_path,
             &policy,
             &self.interrupt_flag,
+            self.limits,
+            self.live_output,
         )
         .map_err(|e| SofosError::ToolExecution(format!("Failed to execute command: {}", e)))?;
         let status = ExitStatus::from_raw(outcome.exit_code.unwrap_or(1) as u32);
         Ok(SupervisedOutput {
-            stdout: outcome.stdout,
-            stderr: outcome.stderr,
+            stdout: String::from_utf8_lossy(&outcome.stdout).into_owned(),
+            stderr: String::from_utf8_lossy(&outcome.stderr).into_owned(),
             status,
             terminated_for: outcome.terminated_for,
         })
@@ -896,8 +984,8 @@ impl BashExecutor {
 }
 
 struct SupervisedOutput {
-    stdout: Vec<u8>,
-    stderr: Vec<u8>,
+    stdout: String,
+    stderr: String,
     status: ExitStatus,
     terminated_for: Option<TerminationReason>,
 }
@@ -985,12 +1073,7 @@ fn is_likely_sandbox_denied(confined: bool, output: &SupervisedOutput) -> bool {
         "landlock",
         "failed to write file",
     ];
-    let has_keyword = [
-        String::from_utf8_lossy(&output.stdout),
-        String::from_utf8_lossy(&output.stderr),
-    ]
-    .iter()
-    .any(|section| {
+    let has_keyword = [&output.stdout, &output.stderr].iter().any(|section| {
         let lower = section.to_lowercase();
         SANDBOX_DENIED_KEYWORDS
             .iter()
@@ -1042,17 +1125,31 @@ fn should_prompt_unsandboxed(
 
 fn spawn_capped_reader<R>(
     reader: R,
-    buf: Arc<Mutex<Vec<u8>>>,
+    buf: Arc<Mutex<String>>,
     overflow: Arc<AtomicBool>,
+    cap: usize,
+    echo: Option<LineEcho>,
 ) -> thread::JoinHandle<()>
 where
     R: Read + Send + 'static,
 {
-    thread::spawn(move || read_capped(reader, &buf, &overflow))
+    thread::spawn(move || read_capped(reader, &buf, &overflow, cap, echo))
 }
 
-fn read_capped<R: Read>(mut reader: R, buf: &Mutex<Vec<u8>>, overflow: &AtomicBool) {
+/// Decode `reader` into `buf` chunk by chunk until EOF, flagging
+/// `overflow` once the stream passes `cap` bytes. Decoding goes through
+/// [`Utf8ChunkDecoder`] so a character split across two pipe reads is
+/// not turned into replacement characters. With `echo`, the kept text
+/// is also shown live, ending in a truncation marker past the cap.
+fn read_capped<R: Read>(
+    mut reader: R,
+    buf: &Mutex<String>,
+    overflow: &AtomicBool,
+    cap: usize,
+    mut echo: Option<LineEcho>,
+) {
     let mut chunk = [0u8; BASH_READ_CHUNK_BYTES];
+    let mut decoder = Utf8ChunkDecoder::default();
     loop {
         match reader.read(&mut chunk) {
             Ok(0) => break,
@@ -1063,23 +1160,43 @@ fn read_capped<R: Read>(mut reader: R, buf: &Mutex<Vec<u8>>, overflow: &AtomicBo
                 let Ok(mut stored) = buf.lock() else {
                     return;
                 };
-                let remaining = MAX_BASH_OUTPUT_BYTES.saturating_sub(stored.len());
+                let remaining = cap.saturating_sub(stored.len());
                 if remaining == 0 {
                     overflow.store(true, Ordering::SeqCst);
+                    if let Some(echo) = echo.take() {
+                        echo.finish_truncated();
+                    }
                     continue;
                 }
                 let take = read.min(remaining);
-                stored.extend_from_slice(&chunk[..take]);
+                let decoded = decoder.decode(&chunk[..take]);
+                stored.push_str(&decoded);
+                if let Some(echo) = echo.as_mut() {
+                    echo.push(&decoded);
+                }
                 if take < read {
                     overflow.store(true, Ordering::SeqCst);
+                    if let Some(echo) = echo.take() {
+                        echo.finish_truncated();
+                    }
                 }
             }
             Err(_) => break,
         }
     }
+    if !overflow.load(Ordering::Relaxed) {
+        let rest = decoder.finish();
+        if let Ok(mut stored) = buf.lock() {
+            stored.push_str(&rest);
+        }
+        if let Some(mut echo) = echo {
+            echo.push(&rest);
+            echo.finish();
+        }
+    }
 }
 
-fn drain_into_vec(buf: Arc<Mutex<Vec<u8>>>) -> Vec<u8> {
+fn drain_into_string(buf: Arc<Mutex<String>>) -> String {
     Arc::try_unwrap(buf)
         .map(|inner| inner.into_inner().unwrap_or_default())
         .unwrap_or_else(|shared| shared.lock().map(|guard| guard.clone()).unwrap_or_default())
@@ -1123,12 +1240,47 @@ fn terminate_child_tree(child: &mut Child) {
 mod tests {
     use super::*;
 
+    /// Reader that hands out one pre-split chunk per `read` call, the way
+    /// a pipe can deliver a character's bytes in two separate writes.
+    struct ChunkedReader(std::collections::VecDeque<Vec<u8>>);
+
+    impl Read for ChunkedReader {
+        fn read(&mut self, out: &mut [u8]) -> std::io::Result<usize> {
+            let Some(chunk) = self.0.pop_front() else {
+                return Ok(0);
+            };
+            out[..chunk.len()].copy_from_slice(&chunk);
+            Ok(chunk.len())
+        }
+    }
+
+    #[test]
+    fn read_capped_keeps_characters_split_across_reads() {
+        let text = "error: ─── ✗ naïve";
+        let split = text.find('✗').unwrap() + 1;
+        let reader = ChunkedReader(
+            [&text.as_bytes()[..split], &text.as_bytes()[split..]]
+                .into_iter()
+                .map(<[u8]>::to_vec)
+                .collect(),
+        );
+        let buf = Mutex::new(String::new());
+        read_capped(
+            reader,
+            &buf,
+            &AtomicBool::new(false),
+            BashLimits::default().max_output_bytes,
+            None,
+        );
+        assert_eq!(buf.into_inner().unwrap(), text);
+    }
+
     #[cfg(unix)]
     fn output_with(code: i32, stdout: &str, stderr: &str) -> SupervisedOutput {
         use std::os::unix::process::ExitStatusExt;
         SupervisedOutput {
-            stdout: stdout.as_bytes().to_vec(),
-            stderr: stderr.as_bytes().to_vec(),
+            stdout: stdout.to_string(),
+            stderr: stderr.to_string(),
             status: ExitStatus::from_raw(code << 8),
             terminated_for: None,
         }
@@ -1200,16 +1352,16 @@ mod tests {
         use std::os::unix::process::ExitStatusExt;
         // The 128+signal exit convention.
         let by_code = SupervisedOutput {
-            stdout: Vec::new(),
-            stderr: Vec::new(),
+            stdout: String::new(),
+            stderr: String::new(),
             status: ExitStatus::from_raw((128 + libc::SIGSYS) << 8),
             terminated_for: None,
         };
         assert!(is_likely_sandbox_denied(true, &by_code));
         // A direct SIGSYS signal death.
         let by_signal = SupervisedOutput {
-            stdout: Vec::new(),
-            stderr: Vec::new(),
+            stdout: String::new(),
+            stderr: String::new(),
             status: ExitStatus::from_raw(libc::SIGSYS),
             terminated_for: None,
         };
@@ -1381,17 +1533,17 @@ mod tests {
     #[cfg(unix)]
     #[test]
     fn model_escalation_refuses_redirection() {
-        // Escalation runs unconfined, so it refuses output redirection just
-        // as an unsandboxed preset does — it is never more permissive than
-        // the explicit unsandboxed mode.
+        // Escalation runs unconfined, so it refuses redirection out of the
+        // workspace just as an unsandboxed preset does — it is never more
+        // permissive than the explicit unsandboxed mode.
         let (_temp, executor) = escalation_executor(ApprovalPolicy::OnRequest, true);
         let mut pm = PermissionManager::new(executor.workspace.clone()).unwrap();
         let escalation = EscalationRequest {
             justification: Some("write a log file".to_string()),
         };
-        let normalized = PermissionManager::normalize_command_key("echo hi > out.txt");
+        let normalized = PermissionManager::normalize_command_key("echo hi > /tmp/out.txt");
         let err = executor
-            .run_model_escalation("echo hi > out.txt", &normalized, &escalation, &mut pm)
+            .run_model_escalation("echo hi > /tmp/out.txt", &normalized, &escalation, &mut pm)
             .unwrap_err();
         assert!(matches!(err, SofosError::ToolExecution(_)));
     }
@@ -1452,8 +1604,8 @@ mod tests {
         .unwrap();
 
         let output = SupervisedOutput {
-            stdout: Vec::new(),
-            stderr: b"cat: /x: Operation not permitted".to_vec(),
+            stdout: String::new(),
+            stderr: "cat: /x: Operation not permitted".to_string(),
             status: ExitStatus::from_raw(1 << 8),
             terminated_for: None,
         };
@@ -6,8 +6,9 @@
 //!   it.
 //! - [`validate`] — structural checks, path policy, and the rejection
 //!   messages the executor returns when a command is refused.
-//! - [`output`] — per-stream byte caps and signal-name lookup used by
-//!   the executor when shaping the result string.
+//! - [`output`] — the timeout and per-stream byte caps, and the
+//!   signal-name lookup used by the executor when shaping the result
+//!   string.
 
 pub mod executor;
 pub mod output;
@@ -15,10 +16,11 @@ pub mod sandbox;
 pub mod validate;
 
 use crate::config::{ApprovalPolicy, SandboxMode};
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::path::PathBuf;
 use std::sync::atomic::AtomicBool;
 use std::sync::{Arc, Mutex};
+use std::time::Instant;
 
 /// A model-driven request to run a single command outside the
 /// operating-system sandbox (`sandbox_permissions: "require_escalated"`).
@@ -32,6 +34,9 @@ pub struct EscalationRequest {
 #[derive(Clone)]
 pub struct BashExecutor {
     pub(super) workspace: PathBuf,
+    /// Directory commands run in: the workspace, or the subdirectory
+    /// selected with `/scope`. Relative path arguments resolve against it.
+    pub(super) scope: PathBuf,
     /// Whether interactive prompts (stdin) are available
     pub(super) interactive: bool,
     /// Whether `morph_edit_file` is exposed (drives error-message hints)
@@ -42,8 +47,10 @@ pub struct BashExecutor {
     /// When the user is asked before a command runs outside the sandbox.
     /// Gates both escalation paths; see [`ApprovalPolicy`].
     pub(super) approval_policy: ApprovalPolicy,
-    /// Session-scoped temporary permissions (not persisted to config)
-    pub(super) session_allowed: Arc<Mutex<HashSet<String>>>,
+    /// Session-scoped temporary permissions (not persisted to config).
+    /// An allow carries the instant it lapses, or `None` to last the
+    /// whole session.
+    pub(super) session_allowed: Arc<Mutex<HashMap<String, Option<Instant>>>>,
     pub(super) session_denied: Arc<Mutex<HashSet<String>>>,
     /// Commands the user has approved to run outside the sandbox for the
     /// rest of this session, so an escalation is not re-prompted for the
@@ -58,6 +65,14 @@ pub struct BashExecutor {
     /// Defaults to a fresh atomic in `new`; the REPL installs its own
     /// shared flag after construction via `install_interrupt_flag`.
     pub(super) interrupt_flag: Arc<AtomicBool>,
+    /// Timeout and output caps applied to every command.
+    pub(super) limits: output::BashLimits,
+    /// Echo command output to the terminal as it arrives. Off by
+    /// default so tests stay quiet; the REPL turns it on.
+    pub(super) live_output: bool,
+    /// `[bash] env_allow`: inherited variables passed to commands on top
+    /// of the minimal set.
+    pub(super) env_allow: Vec<String>,
 }
 
 impl BashExecutor {
@@ -69,9 +84,32 @@ impl BashExecutor {
         self.mode = mode;
     }
 
+    /// Run commands from `dir`, which must be canonical and inside the
+    /// workspace. The sandbox and permission rules still cover the whole
+    /// workspace.
+    pub fn set_scope(&mut self, dir: PathBuf) {
+        self.scope = dir;
+    }
+
     pub fn set_approval_policy(&mut self, policy: ApprovalPolicy) {
         self.approval_policy = policy;
     }
+
+    pub fn set_limits(&mut self, limits: output::BashLimits) {
+        self.limits = limits;
+    }
+
+    pub fn set_env_allow(&mut self, allowed: Vec<String>) {
+        self.env_allow = allowed;
+    }
+
+    pub fn set_live_output(&mut self, live: bool) {
+        self.live_output = live;
+    }
+
+    pub fn live_output(&self) -> bool {
+        self.live_output
+    }
 }
 
 #[cfg(test)]
@@ -80,7 +118,7 @@ mod tests {
     use crate::error::SofosError;
     use crate::tools::bash::validate::{
         command_contains_askable_git_checkout, command_runs_only_git, detect_ansi_c_quoting,
-        detect_command_substitution, has_path_traversal, path_token_shell_meta,
+        detect_command_substitution, has_path_traversal, path_token_shell_meta, split_leading_cd,
     };
     use crate::tools::test_support;
 
@@ -135,12 +173,60 @@ mod tests {
         let executor = BashExecutor::new(PathBuf::from("."), false, false).unwrap();
 
         // Test structural safety issues (not permission-based)
-        assert!(!executor.is_safe_command_structure("echo hello > file.txt"));
-        assert!(!executor.is_safe_command_structure("cat file.txt >> output.txt"));
+        assert!(!executor.is_safe_command_structure("echo hello > /tmp/file.txt"));
+        assert!(!executor.is_safe_command_structure("cat file.txt >> ../output.txt"));
 
         // These should still be blocked (file redirection even with 2>&1)
-        assert!(!executor.is_safe_command_structure("echo hello > file.txt 2>&1"));
-        assert!(!executor.is_safe_command_structure("cargo build 2>&1 > output.txt"));
+        assert!(!executor.is_safe_command_structure("echo hello > /tmp/file.txt 2>&1"));
+        assert!(!executor.is_safe_command_structure("cargo build 2>&1 > /dev/sda"));
+    }
+
+    #[test]
+    fn redirection_is_allowed_only_into_the_workspace() {
+        let (_temp, path) = test_support::workspace();
+        std::fs::create_dir(path.join("logs")).unwrap();
+        let executor = BashExecutor::new(path, false, false).unwrap();
+
+        for allowed in [
+            "echo hi > out.txt",
+            "cargo build 2> build.log",
+            "cat a.txt >> logs/all.log 2>&1",
+            "make >out.txt 2>&1",
+            "echo hi >&2",
+            "echo 'a > /etc/x' > out.txt",
+            "sh -c 'echo hi > out.txt'",
+        ] {
+            assert!(
+                executor.is_safe_command_structure(allowed),
+                "`{allowed}` should be allowed"
+            );
+        }
+        for blocked in [
+            "echo hi > /etc/x",
+            "echo hi > ../x",
+            "echo hi > logs/../../x",
+            "echo hi 2> /dev/null",
+            "echo hi > $HOME/x",
+            "echo hi > ~/x",
+            "echo hi > \"out.txt\"",
+            "echo hi > .git/hooks/pre-commit",
+            "echo hi > .sofos/config.local.toml",
+            "cd logs && echo hi > out.txt",
+            "echo hi >",
+            "echo hi >(cat)",
+            "sh -c 'echo x > /etc/x'",
+            "bash -c \"echo x > $HOME/x\"",
+            "sh -c 'echo x > $HOME/.bashrc'",
+            "timeout 5 bash -c 'sh -c \"echo x > /etc/x\"'",
+        ] {
+            assert!(
+                !executor.is_safe_command_structure(blocked),
+                "`{blocked}` should be blocked"
+            );
+        }
+
+        let reason = executor.get_rejection_reason("echo hi > /etc/x");
+        assert!(reason.contains("inside the workspace"), "{reason}");
     }
 
     #[test]
@@ -238,9 +324,15 @@ mod tests {
 
         // No sandbox to bound a command: structurally safe ones run
         // unconfined, and anything that needs the sandbox to be safe, such
-        // as output redirection, is refused rather than run unprotected.
+        // as output redirection out of the workspace, is refused rather
+        // than run unprotected.
         assert!(!executor.should_confine("ls -la", false).unwrap());
-        assert!(executor.should_confine("echo hi > out.txt", false).is_err());
+        assert!(!executor.should_confine("echo hi > out.txt", false).unwrap());
+        assert!(
+            executor
+                .should_confine("echo hi > /tmp/out.txt", false)
+                .is_err()
+        );
         assert!(executor.should_confine("cat ../secret", false).is_err());
     }
 
@@ -260,6 +352,123 @@ mod tests {
         }
     }
 
+    #[test]
+    fn leading_cd_runs_the_rest_in_the_subdirectory() {
+        let (_temp, path) = test_support::workspace();
+        std::fs::create_dir(path.join("sub")).unwrap();
+        std::fs::write(path.join("sub").join("marker.txt"), "").unwrap();
+        let executor = BashExecutor::new(path, false, false).unwrap();
+
+        let output = executor.execute("cd sub && ls").unwrap();
+
+        assert!(output.contains("marker.txt"), "got: {output}");
+    }
+
+    #[test]
+    fn leading_cd_keeps_read_deny_rules_on_relative_paths() {
+        let (_temp, path) = test_support::workspace();
+        std::fs::create_dir_all(path.join(".sofos")).unwrap();
+        std::fs::write(
+            path.join(".sofos").join("config.local.toml"),
+            "[permissions]\nallow = []\ndeny = [\"Read(./secrets/**)\"]\nask = []\n",
+        )
+        .unwrap();
+        std::fs::create_dir(path.join("secrets")).unwrap();
+        std::fs::write(path.join("secrets").join("key.pem"), "secret").unwrap();
+        let executor = BashExecutor::new(path, false, false).unwrap();
+
+        for command in ["cd secrets && cat key.pem", "cd secrets && cat ./key.pem"] {
+            match executor.execute(command) {
+                Err(SofosError::ToolExecution(msg)) => {
+                    assert!(msg.contains("Read access denied"), "{command}: {msg}")
+                }
+                other => panic!("{command} should be refused, got {other:?}"),
+            }
+        }
+    }
+
+    #[test]
+    fn leading_cd_outside_the_workspace_is_blocked() {
+        let (_temp, path) = test_support::workspace();
+        let executor = BashExecutor::new(path, false, false).unwrap();
+
+        for command in ["cd ../escape && ls", "cd /etc && ls", "cd /etc"] {
+            assert!(
+                executor.execute(command).is_err(),
+                "{command} should be refused"
+            );
+        }
+    }
+
+    #[test]
+    fn split_leading_cd_takes_only_a_literal_directory() {
+        assert_eq!(
+            split_leading_cd("cd frontend && npm test"),
+            Some(("frontend", "npm test"))
+        );
+        assert_eq!(
+            split_leading_cd("cd 'web app' && ls"),
+            None,
+            "a quoted directory with a space is not split"
+        );
+        assert_eq!(split_leading_cd("cd \"web\" && ls"), Some(("web", "ls")));
+        assert_eq!(split_leading_cd("cd web; ls"), None);
+        assert_eq!(split_leading_cd("cd $HOME && ls"), None);
+        assert_eq!(split_leading_cd("cd ~ && ls"), None);
+        assert_eq!(split_leading_cd("cd web &&"), None);
+        assert_eq!(split_leading_cd("cdx web && ls"), None);
+    }
+
+    #[test]
+    fn output_cap_follows_the_configured_limit() {
+        let (_temp, path) = test_support::workspace();
+        let mut executor = BashExecutor::new(path, false, false).unwrap();
+        executor.set_limits(output::BashLimits {
+            max_output_bytes: 100,
+            ..output::BashLimits::default()
+        });
+
+        let result = executor.execute("seq 1 1000");
+
+        match result {
+            Err(SofosError::ToolExecution(msg)) => {
+                assert!(msg.contains("too large"), "got: {msg}");
+                assert!(msg.contains("100 byte cap"), "got: {msg}");
+                assert!(msg.contains("STDOUT (partial):\n1\n2\n"), "got: {msg}");
+                assert!(msg.ends_with(output::OUTPUT_TRUNCATED_MARKER), "got: {msg}");
+            }
+            other => panic!("Expected ToolExecution error, got: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn timed_out_command_reports_its_partial_output() {
+        use std::time::{Duration, Instant};
+
+        let (_temp, path) = test_support::workspace();
+        let mut executor = BashExecutor::new(path, false, false).unwrap();
+        executor.set_limits(output::BashLimits {
+            timeout: Duration::from_secs(1),
+            ..output::BashLimits::default()
+        });
+        {
+            let mut allowed = executor.session_allowed.lock().unwrap();
+            allowed.insert("Bash(echo started; sleep 30)".to_string(), None);
+        }
+
+        let start = Instant::now();
+        let result = executor.execute("echo started; sleep 30");
+
+        assert!(start.elapsed() < Duration::from_secs(10));
+        match result {
+            Err(SofosError::ToolExecution(msg)) => {
+                assert!(msg.contains("timed out after 1s"), "got: {msg}");
+                assert!(msg.contains("STDOUT (partial):\nstarted"), "got: {msg}");
+            }
+            other => panic!("Expected ToolExecution error, got: {other:?}"),
+        }
+    }
+
     /// Windows canonicalises temp directories to paths like
     /// `\\?\C:\Users\...` that contain `?` in the verbatim prefix. The
     /// shell-meta check must not reject those, and the read-deny check
@@ -696,13 +905,13 @@ ask = []
         // Simulate adding a command to session_allowed
         {
             let mut allowed = executor.session_allowed.lock().unwrap();
-            allowed.insert("Bash(my_custom_cmd)".to_string());
+            allowed.insert("Bash(my_custom_cmd)".to_string(), None);
         }
 
         // Verify it's recognized on subsequent check
         {
             let allowed = executor.session_allowed.lock().unwrap();
-            assert!(allowed.contains("Bash(my_custom_cmd)"));
+            assert!(allowed.contains_key("Bash(my_custom_cmd)"));
         }
 
         // Simulate adding a command to session_denied
@@ -726,14 +935,45 @@ ask = []
         // Add permission via executor1
         {
             let mut allowed = executor1.session_allowed.lock().unwrap();
-            allowed.insert("Bash(shared_cmd)".to_string());
+            allowed.insert("Bash(shared_cmd)".to_string(), None);
         }
 
         // Verify executor2 sees it (Arc sharing)
         {
             let allowed = executor2.session_allowed.lock().unwrap();
-            assert!(allowed.contains("Bash(shared_cmd)"));
+            assert!(allowed.contains_key("Bash(shared_cmd)"));
+        }
+    }
+
+    #[test]
+    fn timed_session_grants_lapse() {
+        use std::time::Duration;
+
+        let executor = BashExecutor::new(PathBuf::from("."), false, false).unwrap();
+        {
+            let mut allowed = executor.session_allowed.lock().unwrap();
+            allowed.insert("Bash(a)".to_string(), None);
+            allowed.insert(
+                "Bash(b)".to_string(),
+                Some(Instant::now() + Duration::from_secs(60)),
+            );
+            allowed.insert(
+                "Bash(c)".to_string(),
+                Some(Instant::now() - Duration::from_secs(1)),
+            );
         }
+
+        assert!(executor.has_session_grant("Bash(a)"));
+        assert!(executor.has_session_grant("Bash(b)"));
+        assert!(!executor.has_session_grant("Bash(c)"));
+        assert!(
+            !executor
+                .session_allowed
+                .lock()
+                .unwrap()
+                .contains_key("Bash(c)"),
+            "an expired grant is dropped so the command is asked about again"
+        );
     }
 
     /// Shell substitution hides commands from the permission system.
@@ -928,7 +1168,7 @@ ask = []
         // sit on the permission prompt with no stdin to answer.
         {
             let mut allowed = executor.session_allowed.lock().unwrap();
-            allowed.insert("Bash(sleep 30)".to_string());
+            allowed.insert("Bash(sleep 30)".to_string(), None);
         }
 
         let flag_for_thread = Arc::clone(&flag);
@@ -1403,6 +1643,7 @@ ask = []
             None,
             &policy,
             &AtomicBool::new(false),
+            crate::tools::bash::output::BashLimits::default(),
         );
 
         let outcome = match outcome {
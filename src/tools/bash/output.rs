@@ -1,22 +1,60 @@
-//! Output capping and signal-name lookup for the bash executor. Both
-//! stdout and stderr are capped at [`MAX_BASH_OUTPUT_BYTES`] before
-//! truncation — large per-stream output is rejected outright so the
-//! tool result stays under the API's payload ceiling.
+//! Output capping, live echo, and signal-name lookup for the bash
+//! executor. Both stdout and stderr are capped at
+//! [`BashLimits::max_output_bytes`] before truncation — a stream past the
+//! cap stops the command so the tool result stays under the API's
+//! payload ceiling.
 
+use colored::Colorize;
+use std::io::Write;
 use std::time::Duration;
 
-/// Hard per-stream byte cap on bash output. Past this the executor
-/// returns a `ToolExecution` error rather than truncating, so the
+/// Default per-stream byte cap on bash output. Past this the executor
+/// stops the command and returns a `ToolExecution` error carrying the
+/// output kept so far, ending in [`OUTPUT_TRUNCATED_MARKER`], so the
 /// model sees the failure clearly instead of a silently chopped
 /// `stdout` that might happen to end mid-statement.
 pub(super) const MAX_BASH_OUTPUT_BYTES: usize = 10 * 1024 * 1024;
 
-/// Wall-clock ceiling on a single bash invocation. Past this the
+/// Default wall-clock ceiling on a single bash invocation. Past this the
 /// supervisor kills the child process tree and surfaces a clear
 /// "timeout" message instead of blocking the turn forever on a stuck
 /// build, hung test runner, or accidental `tail -f`.
 pub(super) const BASH_COMMAND_TIMEOUT: Duration = Duration::from_secs(300);
 
+/// Per-command limits the supervisor enforces. `--bash-timeout` and
+/// `--max-output-bytes` override the defaults.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BashLimits {
+    pub timeout: Duration,
+    /// Applies to stdout and stderr separately.
+    pub max_output_bytes: usize,
+}
+
+impl Default for BashLimits {
+    fn default() -> Self {
+        Self {
+            timeout: BASH_COMMAND_TIMEOUT,
+            max_output_bytes: MAX_BASH_OUTPUT_BYTES,
+        }
+    }
+}
+
+/// Closes a stream that was cut off at the byte cap, both in the tool
+/// result and in the live echo.
+pub(super) const OUTPUT_TRUNCATED_MARKER: &str = "(output truncated)";
+
+const BYTES_PER_MB: usize = 1024 * 1024;
+
+/// `cap` for the "output too large" message: whole megabytes as `N MB`,
+/// anything else in bytes.
+pub(super) fn describe_byte_cap(cap: usize) -> String {
+    if cap >= BYTES_PER_MB && cap.is_multiple_of(BYTES_PER_MB) {
+        format!("{} MB", cap / BYTES_PER_MB)
+    } else {
+        format!("{} byte", cap)
+    }
+}
+
 /// How often the supervisor polls for child exit, output overflow,
 /// interrupt, and timeout. Short enough that the user does not feel
 /// latency on ESC; long enough that the loop is not a busy wait.
@@ -66,3 +104,196 @@ pub(super) fn signal_name(sig: i32) -> &'static str {
         _ => "unknown",
     }
 }
+
+/// Incremental UTF-8 decoder for output that arrives in arbitrary read
+/// chunks. A multi-byte character split across two reads is held back
+/// until its remaining bytes arrive instead of being turned into two
+/// replacement characters, which `String::from_utf8_lossy` on each chunk
+/// would do. Genuinely invalid bytes still become `U+FFFD`.
+#[derive(Debug, Default)]
+pub(super) struct Utf8ChunkDecoder {
+    /// Trailing bytes of an incomplete character from the previous chunk.
+    pending: Vec<u8>,
+}
+
+impl Utf8ChunkDecoder {
+    /// Decode `chunk`, returning every complete character so far and
+    /// keeping an incomplete trailing sequence for the next call.
+    pub(super) fn decode(&mut self, chunk: &[u8]) -> String {
+        self.pending.extend_from_slice(chunk);
+        let mut decoded = String::with_capacity(self.pending.len());
+        let mut start = 0;
+        while start < self.pending.len() {
+            match std::str::from_utf8(&self.pending[start..]) {
+                Ok(valid) => {
+                    decoded.push_str(valid);
+                    start = self.pending.len();
+                }
+                Err(e) => {
+                    let valid_end = start + e.valid_up_to();
+                    decoded.push_str(&String::from_utf8_lossy(&self.pending[start..valid_end]));
+                    match e.error_len() {
+                        Some(invalid_len) => {
+                            decoded.push(char::REPLACEMENT_CHARACTER);
+                            start = valid_end + invalid_len;
+                        }
+                        // Incomplete sequence at the end: wait for more.
+                        None => {
+                            start = valid_end;
+                            break;
+                        }
+                    }
+                }
+            }
+        }
+        self.pending.drain(..start);
+        decoded
+    }
+
+    /// Flush whatever is still pending once the stream has ended. A
+    /// character cut off by EOF can never complete, so it is rendered as
+    /// a replacement character.
+    pub(super) fn finish(self) -> String {
+        String::from_utf8_lossy(&self.pending).into_owned()
+    }
+}
+
+/// Prints a running command's output, dimmed, one complete line at a
+/// time as it arrives, so a long build or test run shows progress before
+/// it finishes. Text after the last newline is held until the line
+/// completes or the stream ends.
+pub(super) struct LineEcho {
+    out: Box<dyn Write + Send>,
+    pending: String,
+}
+
+impl LineEcho {
+    pub(super) fn new(out: Box<dyn Write + Send>) -> Self {
+        Self {
+            out,
+            pending: String::new(),
+        }
+    }
+
+    pub(super) fn to_stdout() -> Self {
+        Self::new(Box::new(std::io::stdout()))
+    }
+
+    pub(super) fn push(&mut self, text: &str) {
+        self.pending.push_str(text);
+        while let Some(end) = self.pending.find('\n') {
+            let line: String = self.pending.drain(..=end).collect();
+            self.print_line(line.trim_end_matches(['\n', '\r']));
+        }
+    }
+
+    /// Print a final unterminated line, if any.
+    pub(super) fn finish(mut self) {
+        if !self.pending.is_empty() {
+            let line = std::mem::take(&mut self.pending);
+            self.print_line(&line);
+        }
+    }
+
+    /// Print what is held back, then the truncation marker.
+    pub(super) fn finish_truncated(mut self) {
+        let line = std::mem::take(&mut self.pending);
+        if !line.is_empty() {
+            self.print_line(&line);
+        }
+        self.print_line(OUTPUT_TRUNCATED_MARKER);
+    }
+
+    fn print_line(&mut self, line: &str) {
+        // Display only: a closed terminal must not fail the command.
+        let _ = writeln!(self.out, "{}", line.dimmed());
+        let _ = self.out.flush();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Clone, Default)]
+    struct SharedSink(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedSink {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl SharedSink {
+        fn lines(&self) -> Vec<String> {
+            let bytes = self.0.lock().unwrap().clone();
+            String::from_utf8(bytes)
+                .unwrap()
+                .lines()
+                .map(|line| line.replace("\x1b[2m", "").replace("\x1b[0m", ""))
+                .collect()
+        }
+    }
+
+    #[test]
+    fn line_echo_prints_complete_lines_as_they_arrive() {
+        let sink = SharedSink::default();
+        let mut echo = LineEcho::new(Box::new(sink.clone()));
+        echo.push("Compiling a\nCompil");
+        assert_eq!(sink.lines(), ["Compiling a"]);
+        echo.push("ing b\r\nFinished");
+        assert_eq!(sink.lines(), ["Compiling a", "Compiling b"]);
+        echo.finish();
+        assert_eq!(sink.lines(), ["Compiling a", "Compiling b", "Finished"]);
+    }
+
+    #[test]
+    fn line_echo_marks_a_truncated_stream() {
+        let sink = SharedSink::default();
+        let mut echo = LineEcho::new(Box::new(sink.clone()));
+        echo.push("1\n2");
+        echo.finish_truncated();
+        assert_eq!(sink.lines(), ["1", "2", OUTPUT_TRUNCATED_MARKER]);
+    }
+
+    #[test]
+    fn utf8_decoder_joins_character_split_across_reads() {
+        let text = "build ─ ok ✓";
+        let bytes = text.as_bytes();
+        // Split inside the three-byte box-drawing character.
+        let split = text.find('─').unwrap() + 1;
+        let mut decoder = Utf8ChunkDecoder::default();
+        let mut decoded = decoder.decode(&bytes[..split]);
+        assert!(!decoded.contains(char::REPLACEMENT_CHARACTER));
+        decoded.push_str(&decoder.decode(&bytes[split..]));
+        decoded.push_str(&decoder.finish());
+        assert_eq!(decoded, text);
+    }
+
+    #[test]
+    fn utf8_decoder_handles_one_byte_reads() {
+        let text = "ünïcødé ✓";
+        let mut decoder = Utf8ChunkDecoder::default();
+        let mut decoded = String::new();
+        for byte in text.as_bytes() {
+            decoded.push_str(&decoder.decode(std::slice::from_ref(byte)));
+        }
+        decoded.push_str(&decoder.finish());
+        assert_eq!(decoded, text);
+    }
+
+    #[test]
+    fn utf8_decoder_replaces_invalid_and_truncated_bytes() {
+        let mut decoder = Utf8ChunkDecoder::default();
+        assert_eq!(decoder.decode(b"a\xffb"), "a\u{FFFD}b");
+        // A lead byte with no continuation before EOF.
+        assert_eq!(decoder.decode(b"c\xe2\x94"), "c");
+        assert_eq!(decoder.finish(), "\u{FFFD}");
+    }
+}
@@ -30,8 +30,8 @@ mod winutil;
 
 use super::SandboxPolicy;
 use crate::tools::bash::output::{
-    BASH_COMMAND_TIMEOUT, BASH_READ_CHUNK_BYTES, MAX_BASH_OUTPUT_BYTES, SUPERVISOR_POLL_INTERVAL,
-    TerminationReason,
+    BASH_READ_CHUNK_BYTES, BashLimits, LineEcho, SUPERVISOR_POLL_INTERVAL, TerminationReason,
+    Utf8ChunkDecoder,
 };
 use std::collections::HashMap;
 use std::ffi::OsStr;
@@ -85,7 +85,8 @@ pub fn is_available() -> bool {
 
 /// Spawn `<shell> -c <command>` under the workspace sandbox and
 /// supervise it to completion: per-stream byte caps, wall-clock
-/// timeout, and the shared interrupt flag, matching the Unix path in
+/// timeout, the shared interrupt flag, and the live echo when
+/// `live_output` is set, matching the Unix path in
 /// `executor::spawn_supervised`.
 pub(in crate::tools::bash) fn run_confined(
     shell: &OsStr,
@@ -94,6 +95,8 @@ pub(in crate::tools::bash) fn run_confined(
     extra_path_dir: Option<&Path>,
     policy: &SandboxPolicy,
     interrupt_flag: &AtomicBool,
+    limits: BashLimits,
+    live_output: bool,
 ) -> io::Result<SupervisedOutput> {
     let cap_sid_string = cap::workspace_cap_sid(workspace)?;
     let cap_sid = token::LocalSid::from_string(&cap_sid_string)?;
@@ -176,11 +179,15 @@ pub(in crate::tools::bash) fn run_confined(
         stdout_read,
         Arc::clone(&stdout_buf),
         Arc::clone(&stdout_overflow),
+        limits.max_output_bytes,
+        live_output.then(LineEcho::to_stdout),
     );
     let stderr_handle = spawn_pipe_reader(
         stderr_read,
         Arc::clone(&stderr_buf),
         Arc::clone(&stderr_overflow),
+        limits.max_output_bytes,
+        live_output.then(LineEcho::to_stdout),
     );
 
     let start = Instant::now();
@@ -201,7 +208,7 @@ pub(in crate::tools::bash) fn run_confined(
             termination = Some(TerminationReason::Interrupt);
             break;
         }
-        if start.elapsed() > BASH_COMMAND_TIMEOUT {
+        if start.elapsed() > limits.timeout {
             termination = Some(TerminationReason::Timeout);
             break;
         }
@@ -310,12 +317,15 @@ fn spawn_pipe_reader(
     handle: HANDLE,
     buf: Arc<Mutex<Vec<u8>>>,
     overflow: Arc<AtomicBool>,
+    cap: usize,
+    mut echo: Option<LineEcho>,
 ) -> thread::JoinHandle<()> {
     // `*mut c_void` is not Send; an integer is. Cast on both sides.
     let raw: usize = handle as usize;
     thread::spawn(move || {
         let handle = raw as HANDLE;
         let mut chunk = [0u8; BASH_READ_CHUNK_BYTES];
+        let mut decoder = Utf8ChunkDecoder::default();
         loop {
             let mut read: u32 = 0;
             let ok = unsafe {
@@ -336,17 +346,30 @@ fn spawn_pipe_reader(
             let Ok(mut stored) = buf.lock() else {
                 break;
             };
-            let remaining = MAX_BASH_OUTPUT_BYTES.saturating_sub(stored.len());
+            let remaining = cap.saturating_sub(stored.len());
             if remaining == 0 {
                 overflow.store(true, Ordering::SeqCst);
+                if let Some(echo) = echo.take() {
+                    echo.finish_truncated();
+                }
                 continue;
             }
             let take = (read as usize).min(remaining);
             stored.extend_from_slice(&chunk[..take]);
+            if let Some(echo) = echo.as_mut() {
+                echo.push(&decoder.decode(&chunk[..take]));
+            }
             if take < read as usize {
                 overflow.store(true, Ordering::SeqCst);
+                if let Some(echo) = echo.take() {
+                    echo.finish_truncated();
+                }
             }
         }
+        if let Some(mut echo) = echo {
+            echo.push(&decoder.finish());
+            echo.finish();
+        }
         unsafe {
             CloseHandle(handle);
         }
@@ -10,6 +10,7 @@ use crate::config::{LOCAL_CONFIG_FILE, config_files_hint};
 use crate::error::{Result, SofosError};
 use crate::tools::ToolName;
 use crate::tools::bash::BashExecutor;
+use crate::tools::filesystem::resolve_within;
 use crate::tools::permissions::command_parse::{
     COMPOUND_HEADERS_NO_BODY, COMPOUND_HEADERS_WITH_BODY, COMPOUND_KEYWORDS, is_env_assignment,
 };
@@ -165,7 +166,8 @@ const GIT_LAUNCHERS: &[&str] = &[
 ];
 
 /// Shells that run the string after `-c` as a command, so `sh -c "git
-/// push"` is inspected by re-parsing that string.
+/// push"` and `sh -c 'echo x > /etc/x'` are inspected by re-parsing that
+/// string.
 const GIT_SHELLS: &[&str] = &["sh", "bash", "dash", "zsh", "ksh"];
 
 /// Git global options that take their value as the following word, so the
@@ -420,6 +422,18 @@ fn shell_c_payloads(args: &[String]) -> Vec<String> {
     out
 }
 
+/// The `-c` strings of every shell named anywhere in `command`, including
+/// one behind a launcher such as `timeout 5 sh -c "..."`.
+fn nested_shell_commands(command: &str) -> Vec<String> {
+    let words = shell_words(command);
+    words
+        .iter()
+        .enumerate()
+        .filter(|(_, word)| GIT_SHELLS.contains(&program_name(word).as_str()))
+        .flat_map(|(i, _)| shell_c_payloads(&words[i + 1..]))
+        .collect()
+}
+
 /// Indices into `args` that git could dispatch as the subcommand. A
 /// leading global option may or may not consume the next word as its
 /// value; known value-taking options consume it, the `=` form does not,
@@ -651,6 +665,80 @@ pub(super) fn command_redirects_output(command: &str) -> bool {
     command.replace("2>&1", "").contains('>')
 }
 
+/// Workspace directories an unconfined redirect may not write into: the
+/// git directory, where a file can become a hook, and the Sofos config
+/// directory, where one can become a permission grant.
+const REDIRECT_PROTECTED_DIRS: &[&str] = &[".git", ".sofos"];
+
+/// Characters that end an unquoted redirect target.
+const REDIRECT_TARGET_TERMINATORS: &[char] = &[';', '|', '&', '<', '>', '(', ')'];
+
+/// The files `command` redirects output to, in order. Duplications onto
+/// another descriptor (`2>&1`, `>&2`) name no file and are skipped.
+/// `None` when a target can't be known without running the shell
+/// because it is missing, quoted, escaped, or expanded.
+pub(super) fn output_redirect_targets(command: &str) -> Option<Vec<String>> {
+    let chars: Vec<char> = command.chars().collect();
+    let mut targets = Vec::new();
+    let mut in_single = false;
+    let mut in_double = false;
+    let mut i = 0;
+    while i < chars.len() {
+        match chars[i] {
+            '\\' if !in_single => {
+                i += 2;
+                continue;
+            }
+            '\'' if !in_double => in_single = !in_single,
+            '"' if !in_single => in_double = !in_double,
+            '>' if !in_single && !in_double => {
+                let mut j = i + 1;
+                if matches!(chars.get(j), Some('>' | '|')) {
+                    j += 1;
+                }
+                let duplicates = chars.get(j) == Some(&'&');
+                if duplicates {
+                    j += 1;
+                }
+                while chars.get(j).is_some_and(|c| c.is_whitespace()) {
+                    j += 1;
+                }
+                let start = j;
+                while chars
+                    .get(j)
+                    .is_some_and(|c| !c.is_whitespace() && !REDIRECT_TARGET_TERMINATORS.contains(c))
+                {
+                    j += 1;
+                }
+                let word: String = chars[start..j].iter().collect();
+                let is_descriptor =
+                    !word.is_empty() && (word == "-" || word.chars().all(|c| c.is_ascii_digit()));
+                if !(duplicates && is_descriptor) {
+                    if word.is_empty()
+                        || word.contains(['\'', '"', '\\', '$', '`', '*', '?', '[', '{', '~'])
+                    {
+                        return None;
+                    }
+                    targets.push(word);
+                }
+                i = j;
+                continue;
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    Some(targets)
+}
+
+/// Whether `command` may change its working directory before a redirect
+/// runs, which would move a relative target out from under the check.
+fn changes_directory(command: &str) -> bool {
+    command
+        .split(|c: char| c.is_whitespace() || REDIRECT_TARGET_TERMINATORS.contains(&c))
+        .any(|word| word == "cd" || word == "pushd")
+}
+
 /// The subcommand verb (lower-cased) of the first dangerous git invocation
 /// in `command`, or `"config"` when the offence is an exec-capable inline
 /// config option. Drives the wording of the rejection message.
@@ -716,6 +804,32 @@ fn base_is_bare_git(token: &str) -> bool {
     bare.eq_ignore_ascii_case("git")
 }
 
+/// Split a leading `cd <dir> && <rest>` into the directory and the rest
+/// of the command. Only a plain literal directory qualifies — one the
+/// executor can resolve without the shell expanding anything — so every
+/// other `cd` is left to the normal checks, which refuse it.
+pub(super) fn split_leading_cd(command: &str) -> Option<(&str, &str)> {
+    let after_cd = command.trim_start().strip_prefix("cd")?;
+    if !after_cd.starts_with(char::is_whitespace) {
+        return None;
+    }
+    let after_cd = after_cd.trim_start();
+    let (raw_dir, tail) = after_cd.split_at(after_cd.find(char::is_whitespace)?);
+    let rest = tail.trim_start().strip_prefix("&&")?.trim_start();
+    if rest.is_empty() {
+        return None;
+    }
+    let dir = ['\'', '"']
+        .iter()
+        .find_map(|quote| raw_dir.strip_prefix(*quote)?.strip_suffix(*quote))
+        .unwrap_or(raw_dir);
+    let literal = !dir.is_empty()
+        && !dir.starts_with('~')
+        && path_token_shell_meta(dir).is_none()
+        && !dir.contains(|c: char| "'\"\\;|&<>()".contains(c));
+    literal.then_some((dir, rest))
+}
+
 /// Returns the kind of expansion that would change the path between
 /// our deny check and the shell touching it: `$` / `${...}`, backticks,
 /// `~user`, or glob metacharacters (`?`, `*`, `[`, `{`). Plain `~/`
@@ -763,6 +877,27 @@ fn token_looks_like_path(tok: &str) -> bool {
 }
 
 impl BashExecutor {
+    /// Resolve the directory of a leading `cd` against the current scope.
+    /// It must be an existing directory inside the workspace; absolute
+    /// paths and `..` are refused the same way file tools refuse them.
+    pub(super) fn resolve_cd_target(&self, dir: &str) -> Result<PathBuf> {
+        let target = resolve_within(&self.scope, &self.workspace, dir).map_err(|e| match e {
+            SofosError::PathViolation(reason) => SofosError::PathViolation(format!(
+                "Cannot cd into '{}': {}. A command can only change into a \
+                 subdirectory of the workspace.",
+                dir, reason
+            )),
+            other => other,
+        })?;
+        if !target.is_dir() {
+            return Err(SofosError::ToolExecution(format!(
+                "Cannot cd into '{}': no such directory in the workspace",
+                dir
+            )));
+        }
+        Ok(target)
+    }
+
     /// Check all external paths (absolute or tilde) in a command against Bash path grants.
     /// Asks the user interactively for any paths not yet covered.
     pub(super) fn check_bash_external_paths(
@@ -835,7 +970,7 @@ impl BashExecutor {
         path_candidate: &str,
         permission_manager: &mut PermissionManager,
     ) -> Result<()> {
-        let joined = self.workspace.join(path_candidate);
+        let joined = self.scope.join(path_candidate);
         // Resolve symlinks through the deepest existing ancestor, so a
         // symlink that points outside the workspace is still caught when
         // its target does not exist yet. Plain `canonicalize` fails on a
@@ -953,6 +1088,21 @@ impl BashExecutor {
         }
     }
 
+    /// `token` as the Read rules see it. A relative path is resolved
+    /// against the command's scope and rewritten relative to the
+    /// workspace root, so `cd secrets && cat key.pem` is checked as
+    /// `secrets/key.pem`.
+    fn workspace_relative(&self, token: &str) -> String {
+        if self.scope == self.workspace || token.starts_with('~') || is_absolute_path(token) {
+            return token.to_string();
+        }
+        let joined = lexically_normalize(&self.scope.join(token));
+        match joined.strip_prefix(&self.workspace) {
+            Ok(relative) => relative.to_string_lossy().to_string(),
+            Err(_) => joined.to_string_lossy().to_string(),
+        }
+    }
+
     pub(super) fn enforce_read_permissions(
         &self,
         permission_manager: &PermissionManager,
@@ -1009,8 +1159,8 @@ impl BashExecutor {
 
             if is_path {
                 // For deny rules: check if explicitly denied
-                let (perm, matched_rule) =
-                    permission_manager.check_read_permission_with_source(cleaned);
+                let (perm, matched_rule) = permission_manager
+                    .check_read_permission_with_source(&self.workspace_relative(cleaned));
                 match perm {
                     CommandPermission::Allowed => {}
                     CommandPermission::Denied => {
@@ -1061,12 +1211,9 @@ impl BashExecutor {
         // Note: absolute paths (/...) and tilde paths (~/) are now handled by
         // check_bash_external_paths which asks the user interactively.
 
-        // Allow "2>&1" (stderr to stdout redirection) but block file output redirection
-        let command_without_stderr_redirect = command.replace("2>&1", "");
-
-        if command_without_stderr_redirect.contains('>')
-            || command_without_stderr_redirect.contains(">>")
-        {
+        // Descriptor duplication such as `2>&1` is always fine; a file
+        // redirect must land inside the workspace.
+        if !self.redirects_stay_in_workspace(command) {
             return false;
         }
 
@@ -1085,6 +1232,43 @@ impl BashExecutor {
         true
     }
 
+    /// Whether every file `command` redirects output to is a relative path
+    /// that resolves inside the workspace, the way `FileSystemTool` checks
+    /// a write. Absolute paths (so `/dev/*` too), `..`, symlinks that lead
+    /// out, [`REDIRECT_PROTECTED_DIRS`], unreadable targets, and redirects
+    /// after a `cd` are all refused. The string a shell runs with `-c` is
+    /// quoted at the top level, so it is checked on its own.
+    pub(super) fn redirects_stay_in_workspace(&self, command: &str) -> bool {
+        let Some(targets) = output_redirect_targets(command) else {
+            return false;
+        };
+        if !nested_shell_commands(command)
+            .iter()
+            .all(|payload| self.redirects_stay_in_workspace(payload))
+        {
+            return false;
+        }
+        if targets.is_empty() {
+            return true;
+        }
+        if changes_directory(command) {
+            return false;
+        }
+        targets.iter().all(|target| {
+            resolve_within(&self.scope, &self.workspace, target).is_ok_and(|resolved| {
+                resolved
+                    .strip_prefix(&self.workspace)
+                    .is_ok_and(|relative| {
+                        !relative.components().any(|component| {
+                            REDIRECT_PROTECTED_DIRS
+                                .iter()
+                                .any(|dir| component.as_os_str() == *dir)
+                        })
+                    })
+            })
+        })
+    }
+
     /// Whether `command` is free of dangerous git operations. Every git
     /// invocation it reaches — directly, behind a launcher such as `env` or
     /// `timeout`, or inside `sh -c "..."` — is parsed into its real
@@ -1129,10 +1313,7 @@ impl BashExecutor {
             return self.get_git_rejection_reason(command);
         }
 
-        let command_without_stderr_redirect = command.replace("2>&1", "");
-        if command_without_stderr_redirect.contains('>')
-            || command_without_stderr_redirect.contains(">>")
-        {
+        if !self.redirects_stay_in_workspace(command) {
             let edit_hint: String = if self.has_morph {
                 format!(
                     "{}/{}",
@@ -1143,8 +1324,10 @@ impl BashExecutor {
                 ToolName::EditFile.as_str().to_string()
             };
             return format!(
-                "Command '{}' contains output redirection ('>' or '>>')\n\
-                 Hint: Use write_file tool to create or {} to modify files. Note: '2>&1' is allowed.",
+                "Command '{}' redirects output ('>' or '>>') somewhere other than a file inside the workspace\n\
+                 Hint: Redirect only to a plain relative path inside the workspace, such as `cargo build 2> build.log`. \
+                 Absolute paths, /dev/*, '..', quoted or variable targets, .git, .sofos, and redirects after `cd` are refused. \
+                 Use write_file tool to create or {} to modify files. Note: '2>&1' is allowed.",
                 command, edit_hint
             );
         }
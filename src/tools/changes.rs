@@ -0,0 +1,229 @@
+//! Per-turn record of the files the file tools changed, printed as a
+//! "Changed this turn:" summary once the turn ends.
+
+use colored::Colorize;
+
+/// What happened to one path over the turn, after merging every change
+/// made to it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChangeKind {
+    Added,
+    Modified,
+    Deleted,
+    /// Moved here from `from`, possibly edited as well.
+    Moved {
+        from: String,
+    },
+}
+
+impl ChangeKind {
+    fn tag(&self) -> &'static str {
+        match self {
+            Self::Added => "A",
+            Self::Modified => "M",
+            Self::Deleted => "D",
+            Self::Moved { .. } => "R",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileChange {
+    pub path: String,
+    pub kind: ChangeKind,
+    pub added: usize,
+    pub removed: usize,
+}
+
+/// A changed file's earlier and current content, as compared by `/diff`.
+/// Either side is empty when the file was added or deleted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileVersions {
+    pub path: String,
+    pub original: String,
+    pub current: String,
+}
+
+/// Changes in the order each path was first touched.
+#[derive(Debug, Default, Clone)]
+pub struct FileChanges {
+    entries: Vec<FileChange>,
+}
+
+impl FileChanges {
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Record a change to `path`, folding it into any earlier change to
+    /// the same path: a file added then edited stays added, one added
+    /// then deleted drops out, and one deleted then rewritten counts as
+    /// modified.
+    pub fn record(&mut self, path: &str, kind: ChangeKind, added: usize, removed: usize) {
+        let Some(index) = self.entries.iter().position(|e| e.path == path) else {
+            self.entries.push(FileChange {
+                path: path.to_string(),
+                kind,
+                added,
+                removed,
+            });
+            return;
+        };
+        let entry = &mut self.entries[index];
+        let merged = match (&entry.kind, kind) {
+            (ChangeKind::Added, ChangeKind::Deleted) => None,
+            (ChangeKind::Added, ChangeKind::Modified) => Some(ChangeKind::Added),
+            (ChangeKind::Moved { from }, ChangeKind::Modified) => {
+                Some(ChangeKind::Moved { from: from.clone() })
+            }
+            (ChangeKind::Deleted, ChangeKind::Added | ChangeKind::Modified) => {
+                Some(ChangeKind::Modified)
+            }
+            (_, kind) => Some(kind),
+        };
+        match merged {
+            Some(kind) => {
+                entry.kind = kind;
+                entry.added += added;
+                entry.removed += removed;
+            }
+            None => {
+                self.entries.remove(index);
+            }
+        }
+    }
+
+    /// Record a move. Line counts already recorded against `from` carry
+    /// over to `to`, and a file created this turn stays "added" at its
+    /// new location.
+    pub fn record_move(&mut self, from: &str, to: &str) {
+        let previous = self
+            .entries
+            .iter()
+            .position(|e| e.path == from)
+            .map(|index| self.entries.remove(index));
+        let (kind, added, removed) = match previous {
+            Some(FileChange {
+                kind: ChangeKind::Added,
+                added,
+                removed,
+                ..
+            }) => (ChangeKind::Added, added, removed),
+            Some(FileChange {
+                kind: ChangeKind::Moved { from: origin },
+                added,
+                removed,
+                ..
+            }) => (ChangeKind::Moved { from: origin }, added, removed),
+            Some(FileChange { added, removed, .. }) => (
+                ChangeKind::Moved {
+                    from: from.to_string(),
+                },
+                added,
+                removed,
+            ),
+            None => (
+                ChangeKind::Moved {
+                    from: from.to_string(),
+                },
+                0,
+                0,
+            ),
+        };
+        self.record(to, kind, added, removed);
+    }
+
+    /// The "Changed this turn:" block, or `None` when nothing changed.
+    pub fn render(&self) -> Option<String> {
+        if self.is_empty() {
+            return None;
+        }
+        let mut lines = vec!["Changed this turn:".bright_cyan().bold().to_string()];
+        for entry in &self.entries {
+            let name = match &entry.kind {
+                ChangeKind::Moved { from } => format!("{} → {}", from, entry.path),
+                _ => entry.path.clone(),
+            };
+            let mut line = format!("  {} {}", entry.kind.tag().bold(), name);
+            if entry.added > 0 {
+                line.push_str(&format!(" {}", format!("+{}", entry.added).green()));
+            }
+            if entry.removed > 0 {
+                line.push_str(&format!(" {}", format!("-{}", entry.removed).red()));
+            }
+            lines.push(line);
+        }
+        Some(lines.join("\n"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn repeated_edits_collapse_into_one_entry_with_the_net_delta() {
+        let mut changes = FileChanges::default();
+        changes.record("src/lib.rs", ChangeKind::Modified, 3, 1);
+        changes.record("notes.txt", ChangeKind::Added, 10, 0);
+        changes.record("src/lib.rs", ChangeKind::Modified, 2, 2);
+        changes.record("notes.txt", ChangeKind::Modified, 1, 0);
+
+        assert_eq!(
+            changes.entries,
+            [
+                FileChange {
+                    path: "src/lib.rs".to_string(),
+                    kind: ChangeKind::Modified,
+                    added: 5,
+                    removed: 3,
+                },
+                FileChange {
+                    path: "notes.txt".to_string(),
+                    kind: ChangeKind::Added,
+                    added: 11,
+                    removed: 0,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn a_file_added_then_deleted_drops_out() {
+        let mut changes = FileChanges::default();
+        changes.record("scratch.txt", ChangeKind::Added, 4, 0);
+        changes.record("scratch.txt", ChangeKind::Deleted, 0, 0);
+        assert!(changes.is_empty());
+        assert!(changes.render().is_none());
+    }
+
+    #[test]
+    fn moves_carry_earlier_changes_to_the_destination() {
+        let mut changes = FileChanges::default();
+        changes.record("a.rs", ChangeKind::Modified, 2, 0);
+        changes.record_move("a.rs", "b.rs");
+        changes.record("b.rs", ChangeKind::Modified, 1, 1);
+        changes.record("new.rs", ChangeKind::Added, 5, 0);
+        changes.record_move("new.rs", "moved.rs");
+
+        assert_eq!(
+            changes.entries,
+            [
+                FileChange {
+                    path: "b.rs".to_string(),
+                    kind: ChangeKind::Moved {
+                        from: "a.rs".to_string()
+                    },
+                    added: 3,
+                    removed: 1,
+                },
+                FileChange {
+                    path: "moved.rs".to_string(),
+                    kind: ChangeKind::Added,
+                    added: 5,
+                    removed: 0,
+                },
+            ]
+        );
+    }
+}
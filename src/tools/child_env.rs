@@ -1,7 +1,8 @@
 //! Removing sensitive variables from the environment a child process
-//! inherits. Shell commands and ripgrep run with the rest of the
-//! environment intact — build tools and interpreters need it — but must
-//! not be handed Sofos's own credentials or a way to inject code.
+//! inherits. ripgrep and git run with the rest of the environment intact,
+//! but must not be handed Sofos's own credentials or a way to inject code.
+//! Shell commands get less: only a minimal set plus the variables
+//! allowed by `[bash] env_allow`.
 
 use std::ffi::OsString;
 use std::process::Command;
@@ -12,13 +13,45 @@ use std::process::Command;
 /// its output or write them to disk. Keep this list in step with the
 /// `#[arg(env = "...")]` keys in `cli.rs`; the `scrub_list_covers_*` test
 /// fails if a credential argument is added there without one here.
-const SECRET_ENV_KEYS: &[&str] = &["ANTHROPIC_API_KEY", "OPENAI_API_KEY", "MORPH_API_KEY"];
+const SECRET_ENV_KEYS: &[&str] = &[
+    "ANTHROPIC_API_KEY",
+    "OPENAI_API_KEY",
+    "GEMINI_API_KEY",
+    "MORPH_API_KEY",
+    "SOFOS_API_BASE_KEY",
+];
 
 /// Loader variables ordinary builds rely on, kept in the child even though
 /// the rest of the `LD_*` / `DYLD_*` family is stripped: the library search
 /// paths and the linker's run-path.
 const LOADER_KEEP_ENV_KEYS: &[&str] = &["LD_LIBRARY_PATH", "LD_RUN_PATH", "DYLD_LIBRARY_PATH"];
 
+/// Variables every shell command gets: enough to find programs, the
+/// home directory, the locale, and a temporary directory, plus what
+/// Windows needs to start processes at all.
+const BASE_SHELL_ENV_KEYS: &[&str] = &[
+    "PATH",
+    "HOME",
+    "USER",
+    "LOGNAME",
+    "SHELL",
+    "TERM",
+    "LANG",
+    "LC_ALL",
+    "LC_CTYPE",
+    "TMPDIR",
+    "TZ",
+    "SYSTEMROOT",
+    "WINDIR",
+    "COMSPEC",
+    "PATHEXT",
+    "TEMP",
+    "TMP",
+    "USERPROFILE",
+    "APPDATA",
+    "LOCALAPPDATA",
+];
+
 /// True for a dynamic-loader variable that can force or redirect what a child
 /// loads (`LD_PRELOAD`, `DYLD_FRAMEWORK_PATH`, `DYLD_FORCE_FLAT_NAMESPACE`, …).
 /// Matching the whole `LD_*` / `DYLD_*` family by prefix (minus the build
@@ -36,6 +69,49 @@ pub(crate) fn scrub_sensitive_env(cmd: &mut Command) {
     scrub_env_from(cmd, std::env::vars_os());
 }
 
+/// Start `cmd` from an empty environment and pass through only
+/// [`BASE_SHELL_ENV_KEYS`] and the inherited variables named in `allowed`.
+/// An entry ending in `*` allows every variable with that prefix, so `*`
+/// alone passes everything. Credentials are still removed afterwards by
+/// [`scrub_sensitive_env`].
+pub(crate) fn restrict_shell_env(cmd: &mut Command, allowed: &[String]) {
+    restrict_env_from(cmd, allowed, std::env::vars_os());
+}
+
+fn restrict_env_from(
+    cmd: &mut Command,
+    allowed: &[String],
+    inherited: impl Iterator<Item = (OsString, OsString)>,
+) {
+    cmd.env_clear();
+    for (key, value) in inherited {
+        let name = key.to_string_lossy();
+        let kept = BASE_SHELL_ENV_KEYS
+            .iter()
+            .any(|base| name_matches(base, &name))
+            || allowed
+                .iter()
+                .any(|pattern| match pattern.strip_suffix('*') {
+                    Some(prefix) => name
+                        .get(..prefix.len())
+                        .is_some_and(|start| name_matches(prefix, start)),
+                    None => name_matches(pattern, &name),
+                });
+        if kept {
+            cmd.env(&key, &value);
+        }
+    }
+}
+
+/// Variable names are case-insensitive on Windows only.
+fn name_matches(expected: &str, name: &str) -> bool {
+    if cfg!(windows) {
+        expected.eq_ignore_ascii_case(name)
+    } else {
+        expected == name
+    }
+}
+
 /// Body of [`scrub_sensitive_env`] with the inherited environment passed in,
 /// so the prefix sweep can be tested without mutating the process.
 fn scrub_env_from(cmd: &mut Command, inherited: impl Iterator<Item = (OsString, OsString)>) {
@@ -144,6 +220,48 @@ mod tests {
         }
     }
 
+    #[test]
+    fn shell_env_keeps_the_base_set_and_allowed_variables_only() {
+        let inherited = fake_env(&[
+            "PATH",
+            "HOME",
+            "AWS_SECRET_ACCESS_KEY",
+            "JAVA_HOME",
+            "CARGO_HOME",
+            "CARGO_TARGET_DIR",
+            "NODE_ENV",
+        ]);
+        let allowed = vec!["JAVA_HOME".to_string(), "CARGO_*".to_string()];
+
+        let mut cmd = Command::new("true");
+        restrict_env_from(&mut cmd, &allowed, inherited.into_iter());
+        let mut passed: Vec<String> = cmd
+            .get_envs()
+            .filter(|(_, value)| value.is_some())
+            .map(|(key, _)| key.to_string_lossy().into_owned())
+            .collect();
+        passed.sort();
+
+        assert_eq!(
+            passed,
+            [
+                "CARGO_HOME",
+                "CARGO_TARGET_DIR",
+                "HOME",
+                "JAVA_HOME",
+                "PATH"
+            ]
+        );
+
+        let mut cmd = Command::new("true");
+        restrict_env_from(
+            &mut cmd,
+            &["*".to_string()],
+            fake_env(&["NODE_ENV"]).into_iter(),
+        );
+        assert_eq!(cmd.get_envs().count(), 1);
+    }
+
     /// Every credential command-line argument in `cli.rs` must be covered
     /// by [`SECRET_ENV_KEYS`], so a new key/secret/token/password cannot be
     /// added there and silently leak into child processes. The markers are
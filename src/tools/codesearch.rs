@@ -1,6 +1,7 @@
 use crate::error::{Result, SofosError};
+use crate::tools::sofosignore::SofosIgnore;
 use crate::tools::utils::{MAX_TOOL_OUTPUT_TOKENS, TruncationKind, truncate_for_context};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 
 /// Shared so the UI display layer can strip it without duplicating the literal.
@@ -35,6 +36,13 @@ const MAX_COLUMNS_FLAG: &str = "--max-columns=300";
 /// direct `rg` invocation is the escape hatch.
 const MAX_FILESIZE_FLAG: &str = "--max-filesize=1M";
 
+/// Keeps `.git/` out of `include_hidden` searches.
+const GIT_DIR_EXCLUDE: &str = "!.git/**";
+
+/// Ceiling on `context_lines`, so a large request can't multiply the
+/// output past the tool-output budget.
+pub const MAX_CONTEXT_LINES: usize = 10;
+
 /// Render [`DEFAULT_EXCLUDE_DIRS`] as a comma-separated human string
 /// (e.g. `"target/, node_modules/, .git/, dist/, build/"`) for use in
 /// tool-schema descriptions. Kept in `codesearch` so the const array
@@ -47,9 +55,26 @@ pub fn default_exclude_dirs_human() -> String {
         .join(", ")
 }
 
+/// Optional `search_code` parameters. The defaults respect ignore files
+/// and skip hidden files, as ripgrep does.
+#[derive(Debug, Default)]
+pub struct SearchOptions<'a> {
+    pub file_type: Option<&'a str>,
+    pub max_results: Option<usize>,
+    /// Disable the [`DEFAULT_EXCLUDE_DIRS`] and ignore-file filtering.
+    pub include_ignored: bool,
+    /// Search dotfiles and dot-directories too (`--hidden`).
+    pub include_hidden: bool,
+    /// Lines of context around each match (`-C`), capped at
+    /// [`MAX_CONTEXT_LINES`].
+    pub context_lines: Option<usize>,
+}
+
 #[derive(Clone)]
 pub struct CodeSearchTool {
     workspace: PathBuf,
+    /// The project root, where `.sofosignore` lives.
+    root: PathBuf,
     rg_path: PathBuf,
 }
 
@@ -73,6 +98,7 @@ impl CodeSearchTool {
         if let Some(p) = env_override {
             if try_path(&p).is_ok() {
                 return Ok(Self {
+                    root: workspace.clone(),
                     workspace,
                     rg_path: p,
                 });
@@ -82,6 +108,7 @@ impl CodeSearchTool {
         let default_rg = PathBuf::from("rg");
         if try_path(&default_rg).is_ok() {
             return Ok(Self {
+                root: workspace.clone(),
                 workspace,
                 rg_path: default_rg,
             });
@@ -90,6 +117,7 @@ impl CodeSearchTool {
         for path in fallback_paths.iter().map(PathBuf::from) {
             if try_path(&path).is_ok() {
                 return Ok(Self {
+                    root: workspace.clone(),
                     workspace,
                     rg_path: path,
                 });
@@ -103,6 +131,11 @@ impl CodeSearchTool {
         )))
     }
 
+    /// Run searches from `dir`, the directory selected with `/scope`.
+    pub fn set_scope(&mut self, dir: PathBuf) {
+        self.workspace = dir;
+    }
+
     /// Search for a pattern in the codebase using ripgrep.
     ///
     /// When `include_ignored` is `true`, the hard-coded [`DEFAULT_EXCLUDE_DIRS`]
@@ -110,14 +143,9 @@ impl CodeSearchTool {
     /// disabled — the escape hatch for the rare case where the user genuinely
     /// needs to grep inside `target/`, `node_modules/`, or other normally-
     /// skipped paths. Defaults to `false` so the usual output-size protection
-    /// stays on.
-    pub fn search(
-        &self,
-        pattern: &str,
-        file_type: Option<&str>,
-        max_results: Option<usize>,
-        include_ignored: bool,
-    ) -> Result<String> {
+    /// stays on. `include_hidden` adds dotfiles, but `.git/` stays excluded.
+    /// Files matching `.sofosignore` are dropped either way.
+    pub fn search(&self, pattern: &str, options: &SearchOptions) -> Result<String> {
         let mut cmd = Command::new(&self.rg_path);
 
         cmd.arg("--heading")
@@ -129,7 +157,7 @@ impl CodeSearchTool {
             .arg("--max-columns-preview")
             .arg(MAX_FILESIZE_FLAG);
 
-        if include_ignored {
+        if options.include_ignored {
             // Bypass gitignore / .ignore / global ignores as well — callers
             // asking for "include ignored" almost always want everything,
             // not just our hard-coded extras.
@@ -140,10 +168,21 @@ impl CodeSearchTool {
             }
         }
 
-        let max_count = max_results.unwrap_or(DEFAULT_MAX_RESULTS_PER_FILE);
+        if options.include_hidden {
+            // Repository internals are never useful results, even when
+            // ignore files are bypassed too.
+            cmd.arg("--hidden").arg("--glob").arg(GIT_DIR_EXCLUDE);
+        }
+
+        let max_count = options.max_results.unwrap_or(DEFAULT_MAX_RESULTS_PER_FILE);
         cmd.arg("--max-count").arg(max_count.to_string());
 
-        if let Some(ft) = file_type {
+        if let Some(lines) = options.context_lines.filter(|n| *n > 0) {
+            cmd.arg("--context")
+                .arg(lines.min(MAX_CONTEXT_LINES).to_string());
+        }
+
+        if let Some(ft) = options.file_type {
             if !ft.trim().is_empty() {
                 cmd.arg("--type").arg(ft);
             }
@@ -164,6 +203,7 @@ impl CodeSearchTool {
             .map_err(|e| SofosError::ToolExecution(format!("Failed to execute ripgrep: {}", e)))?;
 
         let stdout = String::from_utf8_lossy(&output.stdout);
+        let stdout = drop_ignored_files(&stdout, &self.workspace, &SofosIgnore::load(&self.root));
         if output.status.success() && !stdout.trim().is_empty() {
             return Ok(truncate_for_context(
                 &stdout,
@@ -188,6 +228,73 @@ impl CodeSearchTool {
             )))
         }
     }
+
+    /// Files with at least one match for `pattern`, relative to the
+    /// search directory and sorted. Uses the same excludes, ignore files,
+    /// and size cap as [`Self::search`]; `glob` narrows the set like
+    /// `rg --glob`, and `.sofosignore` matches are left out.
+    pub fn files_with_matches(
+        &self,
+        pattern: &str,
+        file_type: Option<&str>,
+        glob: Option<&str>,
+    ) -> Result<Vec<String>> {
+        let mut cmd = Command::new(&self.rg_path);
+        cmd.arg("--files-with-matches")
+            .arg("--null")
+            .arg("--color=never")
+            .arg("--no-messages")
+            .arg(MAX_FILESIZE_FLAG);
+        for dir in DEFAULT_EXCLUDE_DIRS {
+            cmd.arg("--glob").arg(format!("!{}/**", dir));
+        }
+        if let Some(ft) = file_type.filter(|ft| !ft.trim().is_empty()) {
+            cmd.arg("--type").arg(ft);
+        }
+        if let Some(glob) = glob.filter(|glob| !glob.trim().is_empty()) {
+            cmd.arg("--glob").arg(glob);
+        }
+        cmd.arg("--").arg(pattern);
+        cmd.current_dir(&self.workspace);
+        crate::tools::child_env::scrub_sensitive_env(&mut cmd);
+
+        let output = cmd
+            .output()
+            .map_err(|e| SofosError::ToolExecution(format!("Failed to execute ripgrep: {}", e)))?;
+        // Exit code 1 is "no matches"; 2 is a real error such as a bad
+        // pattern or an unknown file type.
+        if output.status.code() == Some(2) {
+            return Err(SofosError::ToolExecution(format!(
+                "ripgrep error: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+
+        let ignored = SofosIgnore::load(&self.root);
+        let mut files: Vec<String> = String::from_utf8_lossy(&output.stdout)
+            .split('\0')
+            .filter(|file| !file.is_empty())
+            .filter(|file| !ignored.is_ignored(&self.workspace.join(file), false))
+            .map(str::to_string)
+            .collect();
+        files.sort();
+        Ok(files)
+    }
+}
+
+/// `--heading` output without the per-file blocks whose file matches
+/// `.sofosignore`. Blocks are separated by a blank line and start with
+/// the path relative to `dir`. ripgrep's own `--ignore-file` can't do
+/// this: `--no-ignore` turns it off and it anchors patterns at the cwd.
+fn drop_ignored_files(output: &str, dir: &Path, ignored: &SofosIgnore) -> String {
+    output
+        .split("\n\n")
+        .filter(|block| {
+            let file = block.lines().next().unwrap_or_default();
+            !ignored.is_ignored(&dir.join(file), false)
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n")
 }
 
 #[cfg(test)]
@@ -236,13 +343,44 @@ mod tests {
         fs::write(&test_file, "Hello World\nTest Pattern\nAnother Line").unwrap();
 
         if let Ok(tool) = CodeSearchTool::new(path) {
-            let result = tool.search("Pattern", None, None, false);
+            let result = tool.search("Pattern", &SearchOptions::default());
             if let Ok(output) = result {
                 assert!(output.contains("Pattern") || output.contains("No matches"));
             }
         }
     }
 
+    #[test]
+    fn files_with_matches_lists_matching_files_narrowed_by_glob() {
+        let (_temp, path) = test_support::workspace();
+        fs::create_dir_all(path.join("src")).unwrap();
+        fs::create_dir_all(path.join("target")).unwrap();
+        fs::write(path.join("src/a.rs"), "fn old_name() {}\n").unwrap();
+        fs::write(path.join("src/b.txt"), "old_name\n").unwrap();
+        fs::write(path.join("src/c.rs"), "fn other() {}\n").unwrap();
+        fs::write(path.join("target/d.rs"), "old_name\n").unwrap();
+
+        let Ok(tool) = CodeSearchTool::new(path) else {
+            return;
+        };
+
+        assert_eq!(
+            tool.files_with_matches("old_name", None, None).unwrap(),
+            ["src/a.rs", "src/b.txt"]
+        );
+        assert_eq!(
+            tool.files_with_matches("old_name", None, Some("*.rs"))
+                .unwrap(),
+            ["src/a.rs"]
+        );
+        assert!(
+            tool.files_with_matches("missing", None, None)
+                .unwrap()
+                .is_empty()
+        );
+        assert!(tool.files_with_matches("(", None, None).is_err());
+    }
+
     #[test]
     fn search_treats_flag_like_pattern_as_literal() {
         // Without `--` before the pattern, ripgrep would interpret `-v` as
@@ -260,14 +398,14 @@ mod tests {
             return;
         };
 
-        let out = tool.search("--files", None, None, false).unwrap();
+        let out = tool.search("--files", &SearchOptions::default()).unwrap();
         assert!(
             out.contains("--files checklist"),
             "pattern '--files' must be treated as literal; got: {}",
             out
         );
 
-        let out = tool.search("-v", None, None, false).unwrap();
+        let out = tool.search("-v", &SearchOptions::default()).unwrap();
         assert!(
             out.contains("some -v output"),
             "pattern '-v' must be treated as literal; got: {}",
@@ -286,18 +424,102 @@ mod tests {
             return;
         };
 
-        let default_output = tool.search("unique_marker_xyz", None, None, false).unwrap();
+        let default_output = tool
+            .search("unique_marker_xyz", &SearchOptions::default())
+            .unwrap();
         assert!(
             default_output.contains("No matches"),
             "target/ should be excluded by default; got: {}",
             default_output
         );
 
-        let override_output = tool.search("unique_marker_xyz", None, None, true).unwrap();
+        let override_output = tool
+            .search(
+                "unique_marker_xyz",
+                &SearchOptions {
+                    include_ignored: true,
+                    ..SearchOptions::default()
+                },
+            )
+            .unwrap();
         assert!(
             override_output.contains("unique_marker_xyz"),
             "include_ignored=true must surface files under target/; got: {}",
             override_output
         );
     }
+
+    #[test]
+    fn search_never_shows_files_matching_sofosignore() {
+        let (_temp, path) = test_support::workspace();
+        fs::create_dir_all(path.join("secrets")).unwrap();
+        fs::write(path.join("secrets/key.txt"), "sofosignore_marker\n").unwrap();
+        fs::write(path.join("notes.txt"), "sofosignore_marker\n").unwrap();
+        fs::write(path.join(".sofosignore"), "secrets/\n").unwrap();
+
+        let Ok(tool) = CodeSearchTool::new(path) else {
+            return;
+        };
+
+        for include_ignored in [false, true] {
+            let out = tool
+                .search(
+                    "sofosignore_marker",
+                    &SearchOptions {
+                        include_ignored,
+                        ..SearchOptions::default()
+                    },
+                )
+                .unwrap();
+            assert!(out.contains("notes.txt"), "{out}");
+            assert!(!out.contains("key.txt"), "{out}");
+        }
+    }
+
+    #[test]
+    fn search_can_include_hidden_files_and_context() {
+        let (_temp, path) = test_support::workspace();
+        fs::create_dir_all(path.join(".config")).unwrap();
+        fs::write(
+            path.join(".config/app.toml"),
+            "before
+hidden_marker
+after
+",
+        )
+        .unwrap();
+        fs::create_dir_all(path.join(".git")).unwrap();
+        fs::write(
+            path.join(".git/config"),
+            "hidden_marker
+",
+        )
+        .unwrap();
+
+        let Ok(tool) = CodeSearchTool::new(path) else {
+            return;
+        };
+
+        let default_output = tool
+            .search("hidden_marker", &SearchOptions::default())
+            .unwrap();
+        assert!(default_output.contains("No matches"), "{default_output}");
+
+        let output = tool
+            .search(
+                "hidden_marker",
+                &SearchOptions {
+                    include_hidden: true,
+                    context_lines: Some(1),
+                    ..SearchOptions::default()
+                },
+            )
+            .unwrap();
+        assert!(output.contains(".config/app.toml"), "{output}");
+        assert!(
+            output.contains("1-before") && output.contains("3-after"),
+            "{output}"
+        );
+        assert!(!output.contains(".git/config"), "{output}");
+    }
 }
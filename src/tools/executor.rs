@@ -4,21 +4,31 @@ use crate::error::{DEFAULT_PARENT_DIR, Result, SofosError};
 use crate::mcp::McpManager;
 use crate::mcp::manager::{ImageData, ToolResult as McpToolResult};
 use crate::tools::ToolName;
+use crate::tools::audit;
 use crate::tools::bash::BashExecutor;
-use crate::tools::codesearch::CodeSearchTool;
-use crate::tools::filesystem::FileSystemTool;
+use crate::tools::changes::{ChangeKind, FileChanges};
+use crate::tools::codesearch::{CodeSearchTool, SearchOptions};
+use crate::tools::filesystem::{DEFAULT_TREE_DEPTH, FileSystemTool, LineWindow};
 use crate::tools::image::ImageLoader;
+use crate::tools::inputs::NamedInputs;
 use crate::tools::morph_validate;
 use crate::tools::permissions::{self, PermissionManager};
 use crate::tools::plan;
+use crate::tools::reads::{self, ReadTracker};
+use crate::tools::replace::{self, MAX_REPLACE_FILES};
 use crate::tools::resolve::ResolvedPath;
+use crate::tools::sofosignore::SofosIgnore;
+use crate::tools::symbols;
+use crate::tools::testrun;
 use crate::tools::types::{
-    add_code_search_tool, get_all_tools, get_all_tools_with_morph, get_read_only_tools,
+    add_code_search_tool, add_read_input_tool, add_search_and_replace_tool, add_undo_edit_tool,
+    get_all_tools, get_all_tools_with_morph, get_read_only_tools,
 };
 use crate::tools::utils::{
-    MAX_DIFF_TOKENS, MAX_FILE_READ_TOKENS, MAX_MCP_IMAGE_BYTES, MAX_MCP_IMAGE_COUNT,
-    MAX_MCP_OUTPUT_TOKENS, MAX_PATH_LIST_TOKENS, TruncationKind, base64_approx_decoded_kb,
-    confirm_destructive, is_http_url, truncate_for_context,
+    ConfirmationType, MAX_DIFF_TOKENS, MAX_FILE_READ_TOKENS, MAX_MCP_IMAGE_BYTES,
+    MAX_MCP_IMAGE_COUNT, MAX_MCP_OUTPUT_TOKENS, MAX_PATH_LIST_TOKENS, TruncationKind,
+    base64_approx_decoded_kb, confirm_destructive, confirm_multi_choice, is_http_url,
+    truncate_for_context,
 };
 use crate::ui::diff;
 use colored::Colorize;
@@ -53,6 +63,9 @@ const MAX_WEB_FETCH_REDIRECTS: usize = 3;
 /// stretch the call to a multiple of this limit.
 const WEB_FETCH_TIMEOUT: Duration = Duration::from_secs(30);
 
+/// Lines of a new dotfile or script shown before asking to create it.
+const NEW_FILE_PREVIEW_LINES: usize = 20;
+
 /// The redirect status codes `web_fetch` follows manually. Other 3xx
 /// codes (300 Multiple Choices, 304 Not Modified) are not redirects to a
 /// new location and fall through to normal response handling.
@@ -135,6 +148,12 @@ impl ToolExecutionResult {
         }
     }
 
+    /// Whether the tool rendered its own display text, which is then
+    /// shown as is rather than summarised.
+    pub fn renders_own_display(&self) -> bool {
+        matches!(self, ToolExecutionResult::TextWithDisplay { .. })
+    }
+
     /// Get images if any
     pub fn images(&self) -> &[ImageData] {
         match self {
@@ -165,12 +184,27 @@ impl From<crate::tools::image::ImageSource> for ImageData {
     }
 }
 
+/// Retry guidance appended to a failed `morph_edit_file` result under the
+/// default `instruct` fallback.
+const MORPH_RETRY_HINT: &str = " Please use read_file to get the current file content, then use \
+     edit_file with exact old_string/new_string to make this change.";
+
 /// Header line of the model-facing summary every file-modification tool
 /// emits. Mirrors a unified-diff "files changed" preamble: a fixed first
 /// line followed by per-file lines tagged `A` (added), `M` (modified),
 /// or `D` (deleted).
 const FILE_MUTATION_SUMMARY_HEADER: &str = "Success. Updated the following files:";
 
+/// Terminal width assumed for side-by-side diffs when it can't be read.
+const DIFF_FALLBACK_WIDTH: usize = 120;
+
+/// Columns a side-by-side diff may use.
+fn diff_width() -> usize {
+    crossterm::terminal::size()
+        .map(|(columns, _)| usize::from(columns))
+        .unwrap_or(DIFF_FALLBACK_WIDTH)
+}
+
 /// Build a [`ToolExecutionResult`] for a file-modification tool that
 /// wants to keep the user's colored diff while shipping a constant-size
 /// summary to the model. The colored diff carries syntax-highlighting
@@ -185,8 +219,10 @@ fn file_modification_result(
     original: &str,
     modified: &str,
     success_prefix: &str,
+    diff_style: crate::config::DiffStyle,
 ) -> ToolExecutionResult {
-    let diff_output = diff::generate_compact_diff(original, modified, path);
+    let width = diff_width();
+    let diff_output = diff::generate_diff(diff_style, original, modified, path, width);
     let display_body = format!("{} '{}'\n\nChanges:\n{}", success_prefix, path, diff_output);
     let display = truncate_for_context(&display_body, MAX_DIFF_TOKENS, TruncationKind::DiffOutput);
     let summary = format!("{FILE_MUTATION_SUMMARY_HEADER}\nM {path}");
@@ -196,6 +232,14 @@ fn file_modification_result(
     }
 }
 
+/// Synthetic result a mutating tool returns under `--dry-run`.
+fn dry_run_result(tool_name: &str, input: &Value) -> String {
+    format!(
+        "[dry-run] would execute {} with {}\nDry-run mode is on, so nothing was changed.",
+        tool_name, input
+    )
+}
+
 /// ToolExecutor handles execution of tool calls from AI
 #[derive(Clone)]
 pub struct ToolExecutor {
@@ -208,6 +252,23 @@ pub struct ToolExecutor {
     mode: SandboxMode,
     /// Whether interactive prompts (stdin) are available (false in tests/pipes)
     interactive: bool,
+    /// `--smart-paths`: resolve bare file names by basename search.
+    pub(super) smart_paths: bool,
+    /// `--dry-run`: mutating tools report what they would do instead
+    /// of doing it.
+    dry_run: bool,
+    /// `--confirm-edits` / `/confirm`: file tools show their change and
+    /// wait for a yes before applying it.
+    confirm_edits: bool,
+    /// `[morph] fallback`: whether Morph failures carry retry guidance.
+    morph_fallback: crate::config::MorphFallback,
+    /// `[display] diff_style`: how the diff after a file edit is drawn.
+    diff_style: crate::config::DiffStyle,
+    /// `--input` contents served by `read_input`.
+    inputs: Arc<NamedInputs>,
+    /// Longest edge `view_image` downscales local images to; `None`
+    /// sends them at full size (`--no-image-resize`).
+    image_max_dimension: Option<u32>,
     // Not persisted across sessions.
     read_path_session_allowed: Arc<Mutex<HashSet<String>>>,
     read_path_session_denied: Arc<Mutex<HashSet<String>>>,
@@ -221,6 +282,14 @@ pub struct ToolExecutor {
     /// non-remembered choice is not re-asked for the same server.
     mcp_session_allowed: Arc<Mutex<HashSet<String>>>,
     mcp_session_denied: Arc<Mutex<HashSet<String>>>,
+    /// Files changed by the file tools since the last
+    /// `take_file_changes`, shared with the per-turn clone.
+    file_changes: Arc<Mutex<FileChanges>>,
+    /// `--compact-tool-results`: shorter read headers, and repeat reads
+    /// of an unchanged file answered with a pointer to the earlier one.
+    compact_results: bool,
+    /// Full reads seen this conversation, shared with the per-turn clone.
+    reads: Arc<Mutex<ReadTracker>>,
 }
 
 /// Apply both MCP-response caps (image count/bytes and text tokens) in
@@ -273,6 +342,36 @@ pub(super) fn cap_mcp_images(result: &mut McpToolResult) -> usize {
     original - result.images.len()
 }
 
+/// The `offset` / `limit` window of a `read_file` call, or `None` when
+/// neither is given and the whole file should be read.
+fn requested_line_window(input: &Value) -> Result<Option<LineWindow>> {
+    let positive = |name: &str| -> Result<Option<usize>> {
+        match input.get(name) {
+            None | Some(Value::Null) => Ok(None),
+            Some(value) => value
+                .as_u64()
+                .and_then(|n| usize::try_from(n).ok())
+                .filter(|&n| n >= 1)
+                .map(Some)
+                .ok_or_else(|| {
+                    SofosError::ToolExecution(format!(
+                        "'{}' must be a positive whole number, got {}",
+                        name, value
+                    ))
+                }),
+        }
+    };
+    let offset = positive("offset")?;
+    let limit = positive("limit")?;
+    if offset.is_none() && limit.is_none() {
+        return Ok(None);
+    }
+    Ok(Some(LineWindow {
+        offset: offset.unwrap_or(1),
+        limit,
+    }))
+}
+
 /// Ensure `path`'s parent directory exists, creating it (and any missing
 /// intermediates) if not. Used by move/copy when the destination is
 /// outside the workspace — inside-workspace writes go through
@@ -292,6 +391,39 @@ fn ensure_parent_dir(path: &std::path::Path) -> Result<()> {
     Ok(())
 }
 
+/// `path` relative to `root`, with `/` separators on every platform so it
+/// matches the protected-path globs.
+fn relative_slash_path(root: &std::path::Path, path: &std::path::Path) -> String {
+    let relative = path.strip_prefix(root).unwrap_or(path);
+    relative
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// The first protected path at or under `path`. Directories are walked
+/// without following symlinks.
+fn find_protected(
+    manager: &PermissionManager,
+    root: &std::path::Path,
+    path: &std::path::Path,
+) -> Option<std::path::PathBuf> {
+    let mut stack = vec![path.to_path_buf()];
+    while let Some(current) = stack.pop() {
+        if manager.is_protected_path(&relative_slash_path(root, &current)) {
+            return Some(current);
+        }
+        let is_dir = std::fs::symlink_metadata(&current).is_ok_and(|m| m.is_dir());
+        if is_dir {
+            if let Ok(entries) = std::fs::read_dir(&current) {
+                stack.extend(entries.flatten().map(|entry| entry.path()));
+            }
+        }
+    }
+    None
+}
+
 /// Rename `src` → `dst` handling the four inside/outside combinations.
 /// When both paths are inside the workspace we delegate to the
 /// workspace-sandboxed `FileSystemTool::move_file`; any other combination
@@ -382,6 +514,13 @@ impl ToolExecutor {
             image_loader: Arc::new(image_loader),
             mode,
             interactive,
+            smart_paths: false,
+            dry_run: false,
+            confirm_edits: false,
+            morph_fallback: crate::config::MorphFallback::default(),
+            diff_style: crate::config::DiffStyle::default(),
+            inputs: Arc::new(NamedInputs::new()),
+            image_max_dimension: Some(crate::tools::image::MAX_PROMPT_IMAGE_DIMENSION),
             read_path_session_allowed,
             read_path_session_denied,
             write_path_session_allowed: Arc::new(Mutex::new(HashSet::new())),
@@ -390,9 +529,43 @@ impl ToolExecutor {
             web_fetch_session_denied: Arc::new(Mutex::new(HashSet::new())),
             mcp_session_allowed: Arc::new(Mutex::new(HashSet::new())),
             mcp_session_denied: Arc::new(Mutex::new(HashSet::new())),
+            file_changes: Arc::new(Mutex::new(FileChanges::default())),
+            compact_results: false,
+            reads: Arc::new(Mutex::new(ReadTracker::default())),
         })
     }
 
+    /// The files changed since the last call, leaving the record empty.
+    pub fn take_file_changes(&self) -> FileChanges {
+        self.file_changes
+            .lock()
+            .map(|mut changes| std::mem::take(&mut *changes))
+            .unwrap_or_default()
+    }
+
+    /// How `canonical` appears in the change summary: relative to the
+    /// project root, or absolute when outside it.
+    fn change_display_path(&self, canonical: &std::path::Path) -> String {
+        let root = self.fs_tool.root();
+        if canonical.starts_with(root) {
+            relative_slash_path(root, canonical)
+        } else {
+            canonical.display().to_string()
+        }
+    }
+
+    fn record_change(
+        &self,
+        canonical: &std::path::Path,
+        kind: ChangeKind,
+        (added, removed): (usize, usize),
+    ) {
+        let path = self.change_display_path(canonical);
+        if let Ok(mut changes) = self.file_changes.lock() {
+            changes.record(&path, kind, added, removed);
+        }
+    }
+
     /// Send `GET` for `url` and follow up to [`MAX_WEB_FETCH_REDIRECTS`]
     /// http(s) redirects, returning the final non-redirect response. Each
     /// hop that moves to a different host is sent through the WebFetch
@@ -464,7 +637,7 @@ impl ToolExecutor {
                 // time against it.
                 let gate_started = Instant::now();
                 permissions::check_web_fetch_session_access(
-                    self.fs_tool.workspace(),
+                    self.fs_tool.root(),
                     next_host,
                     self.interactive,
                     &self.web_fetch_session_allowed,
@@ -481,6 +654,11 @@ impl ToolExecutor {
         self.morph_client.is_some()
     }
 
+    /// The project root, where `.sofos/` lives.
+    pub fn workspace(&self) -> &std::path::Path {
+        self.fs_tool.root()
+    }
+
     pub fn has_code_search(&self) -> bool {
         self.code_search_tool.is_some()
     }
@@ -500,6 +678,191 @@ impl ToolExecutor {
         self.bash_executor.set_approval_policy(policy);
     }
 
+    /// Timeout and output caps for `execute_bash` commands.
+    pub fn set_bash_limits(&mut self, limits: crate::tools::bash::output::BashLimits) {
+        self.bash_executor.set_limits(limits);
+    }
+
+    /// Inherited variables shell commands get beyond the minimal set.
+    pub fn set_bash_env_allow(&mut self, allowed: Vec<String>) {
+        self.bash_executor.set_env_allow(allowed);
+    }
+
+    /// Show `execute_bash` output on the terminal while the command runs.
+    pub fn set_live_bash_output(&mut self, live: bool) {
+        self.bash_executor.set_live_output(live);
+    }
+
+    /// Whether `execute_bash` output was already shown while it ran, so
+    /// the caller need not print it again.
+    pub fn live_bash_output(&self) -> bool {
+        self.bash_executor.live_output()
+    }
+
+    /// Turn basename search for bare `read_file` / `write_file` paths on
+    /// or off. Off by default so paths are taken literally.
+    pub fn set_smart_paths(&mut self, enabled: bool) {
+        self.smart_paths = enabled;
+    }
+
+    /// Copy a file's prior content to `.sofos/backups` before a file
+    /// tool overwrites it.
+    pub fn set_backups(&mut self, enabled: bool) {
+        self.fs_tool.set_backups(enabled);
+    }
+
+    /// Preview mutating tool calls instead of running them. Read-only
+    /// tools still run, so the model can keep investigating.
+    pub fn set_dry_run(&mut self, enabled: bool) {
+        self.dry_run = enabled;
+    }
+
+    /// Ask before every file write, edit, move, or copy.
+    pub fn set_confirm_edits(&mut self, enabled: bool) {
+        self.confirm_edits = enabled;
+    }
+
+    pub fn confirm_edits(&self) -> bool {
+        self.confirm_edits
+    }
+
+    pub fn set_compact_results(&mut self, enabled: bool) {
+        self.compact_results = enabled;
+    }
+
+    /// Start a user turn for the turn numbers in repeat-read notes.
+    pub fn begin_turn(&self) {
+        if let Ok(mut reads) = self.reads.lock() {
+            reads.begin_turn();
+        }
+    }
+
+    /// Forget earlier reads once the history holding them is cleared or
+    /// summarised, so the next read returns the content again.
+    pub fn forget_reads(&self) {
+        if let Ok(mut reads) = self.reads.lock() {
+            reads.forget();
+        }
+    }
+
+    /// Set the longest edge local images are downscaled to before
+    /// upload, or `None` to send them at full size.
+    pub fn set_image_max_dimension(&mut self, max_dimension: Option<u32>) {
+        self.image_max_dimension = max_dimension;
+    }
+
+    /// Serve `inputs` through `read_input`. The tool is offered only
+    /// when at least one input is set.
+    pub fn set_inputs(&mut self, inputs: NamedInputs) {
+        self.inputs = Arc::new(inputs);
+    }
+
+    /// Narrow file tools, code search and shell commands to `dir`, a
+    /// subdirectory of the project root given relative to it, or restore
+    /// the root with `None`. Returns the new scope relative to the root,
+    /// empty when restored. Config files and permission grants keep using
+    /// the root.
+    pub fn set_scope(&mut self, dir: Option<&str>) -> Result<std::path::PathBuf> {
+        let root = self.fs_tool.root().to_path_buf();
+        let target = match dir {
+            None => root.clone(),
+            Some(dir) => {
+                let expanded = PermissionManager::expand_tilde_pub(dir);
+                let canonical = std::fs::canonicalize(root.join(&expanded))
+                    .map_err(|_| SofosError::InvalidPath(format!("'{}' does not exist", dir)))?;
+                if !canonical.is_dir() {
+                    return Err(SofosError::InvalidPath(format!(
+                        "'{}' is not a directory",
+                        dir
+                    )));
+                }
+                if !canonical.starts_with(&root) {
+                    return Err(SofosError::PathViolation(format!(
+                        "'{}' is outside the workspace",
+                        dir
+                    )));
+                }
+                canonical
+            }
+        };
+        self.fs_tool.set_scope(target.clone());
+        self.bash_executor.set_scope(target.clone());
+        if let Some(code_search) = self.code_search_tool.as_mut() {
+            code_search.set_scope(target.clone());
+        }
+        Ok(target.strip_prefix(&root).unwrap_or(&target).to_path_buf())
+    }
+
+    pub fn set_diff_style(&mut self, style: crate::config::DiffStyle) {
+        self.diff_style = style;
+    }
+
+    /// `/diff`: every uncommitted change as a highlighted per-file diff,
+    /// against `HEAD` in a git repository or, outside one, against each
+    /// file's oldest `--backups` copy. `None` when neither is available.
+    pub fn uncommitted_diff(&self) -> Result<Option<String>> {
+        let changes = match crate::tools::git::uncommitted_changes(self.fs_tool.workspace())? {
+            Some(changes) => changes,
+            None if self.fs_tool.backups_enabled() => self.fs_tool.backup_originals(),
+            None => return Ok(None),
+        };
+        if changes.is_empty() {
+            return Ok(Some("No uncommitted changes.".to_string()));
+        }
+        let width = diff_width();
+        let sections: Vec<String> = changes
+            .iter()
+            .map(|change| {
+                let (added, removed) = diff::line_delta(&change.original, &change.current);
+                format!(
+                    "{} {} {}\n{}",
+                    change.path.bright_white().bold(),
+                    format!("+{}", added).green(),
+                    format!("-{}", removed).red(),
+                    diff::generate_diff(
+                        self.diff_style,
+                        &change.original,
+                        &change.current,
+                        &change.path,
+                        width,
+                    )
+                )
+            })
+            .collect();
+        Ok(Some(sections.join("\n\n")))
+    }
+
+    /// Write a file Sofos itself produces, such as a `/export`, under the
+    /// same workspace bounds as `write_file`.
+    pub fn write_workspace_file(&self, path: &str, content: &str) -> Result<()> {
+        self.fs_tool.write_file(path, content)
+    }
+
+    /// The active `/scope` relative to the project root, or `None` when
+    /// tools work from the root itself.
+    pub fn scope(&self) -> Option<&std::path::Path> {
+        self.fs_tool
+            .workspace()
+            .strip_prefix(self.fs_tool.root())
+            .ok()
+            .filter(|rel| !rel.as_os_str().is_empty())
+    }
+
+    /// Choose what `morph_edit_file` returns when Morph fails.
+    pub fn set_morph_fallback(&mut self, fallback: crate::config::MorphFallback) {
+        self.morph_fallback = fallback;
+    }
+
+    /// Tool result for a Morph call that left the file untouched. With
+    /// the `instruct` fallback it tells the model how to redo the change.
+    pub(super) fn morph_failure_result(&self, failure: String, path: &str) -> ToolExecutionResult {
+        let mut text = format!("{}. The file '{}' was NOT modified.", failure, path);
+        if self.morph_fallback == crate::config::MorphFallback::Instruct {
+            text.push_str(MORPH_RETRY_HINT);
+        }
+        ToolExecutionResult::Text(text)
+    }
+
     /// Names of MCP servers whose tools would be filtered out when
     /// read-only mode is on. Returned regardless of the current mode
     /// so the REPL can decide what to print at startup.
@@ -519,6 +882,14 @@ impl ToolExecutor {
             .unwrap_or_default()
     }
 
+    /// Health of every configured MCP server, for `/tools`.
+    pub async fn mcp_server_statuses(&self) -> Vec<(String, crate::mcp::manager::McpServerStatus)> {
+        match &self.mcp_manager {
+            Some(manager) => manager.server_statuses().await,
+            None => Vec::new(),
+        }
+    }
+
     /// Share the REPL's interrupt flag with the bash executor so that
     /// pressing ESC or Ctrl+C during a turn terminates a running
     /// shell command instead of waiting for it to exit on its own.
@@ -527,7 +898,8 @@ impl ToolExecutor {
     }
 
     /// Check read permissions on a path (both original and canonical forms),
-    /// and verify external access. Returns Ok if allowed, Err if denied.
+    /// refuse `.sofosignore` matches, and verify external access. Returns
+    /// Ok if allowed, Err if denied.
     fn check_read_access(
         &self,
         path: &str,
@@ -535,7 +907,8 @@ impl ToolExecutor {
         canonical_str: &str,
         is_inside_workspace: bool,
     ) -> Result<()> {
-        let permission_manager = PermissionManager::new(self.fs_tool.workspace().to_path_buf())?;
+        SofosIgnore::load(self.fs_tool.root()).check(path, canonical)?;
+        let permission_manager = PermissionManager::new(self.fs_tool.root().to_path_buf())?;
 
         let (perm_original, matched_rule_original) =
             permission_manager.check_read_permission_with_source(path);
@@ -608,7 +981,7 @@ impl ToolExecutor {
         canonical_str: &str,
         canonical: &std::path::Path,
     ) -> Result<()> {
-        let permission_manager = PermissionManager::new(self.fs_tool.workspace().to_path_buf())?;
+        let permission_manager = PermissionManager::new(self.fs_tool.root().to_path_buf())?;
 
         // Enforce Write deny rules first
         if permission_manager.check_write_permission(canonical_str)
@@ -638,6 +1011,249 @@ impl ToolExecutor {
         Ok(())
     }
 
+    /// Refuse to modify a protected path: a built-in default or a
+    /// `[permissions] protected_paths` glob, matched relative to the
+    /// project root. A directory is refused when anything inside it is
+    /// protected. Paths outside the project are never protected.
+    fn check_not_protected(&self, path: &str, canonical: &std::path::Path) -> Result<()> {
+        let root = self.fs_tool.root();
+        if !canonical.starts_with(root) {
+            return Ok(());
+        }
+        let manager = PermissionManager::new(root.to_path_buf())?;
+        let Some(protected) = find_protected(&manager, root, canonical) else {
+            return Ok(());
+        };
+        let location = if protected.as_path() == canonical {
+            String::new()
+        } else {
+            format!(
+                " (it contains the protected file '{}')",
+                relative_slash_path(root, &protected)
+            )
+        };
+        Err(SofosError::ToolExecution(format!(
+            "Blocked: '{}' is a protected path{}, so the file tools never change it. \
+             Ask the user to make this change manually. Protected paths are set with \
+             `protected_paths` under [permissions] in {}.",
+            path,
+            location,
+            crate::config::config_files_hint()
+        )))
+    }
+
+    /// Ask before `write_file` creates a dotfile, a script, or a file
+    /// with a shebang, showing its first lines. Skipped without a
+    /// terminal to answer, and when `confirm_new_files = false`.
+    fn confirm_new_file(
+        &self,
+        path: &str,
+        canonical: &std::path::Path,
+        content: &str,
+    ) -> Result<()> {
+        if !self.interactive {
+            return Ok(());
+        }
+        let root = self.fs_tool.root();
+        let relative = if canonical.starts_with(root) {
+            relative_slash_path(root, canonical)
+        } else {
+            canonical
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default()
+        };
+        let manager = PermissionManager::new(root.to_path_buf())?;
+        let Some(risk) = manager.new_file_risk(&relative, content) else {
+            return Ok(());
+        };
+
+        println!("{}", format!("New file '{}':", path).bright_yellow());
+        let total = content.lines().count();
+        for line in content.lines().take(NEW_FILE_PREVIEW_LINES) {
+            println!("  {}", line.dimmed());
+        }
+        if total > NEW_FILE_PREVIEW_LINES {
+            println!(
+                "  {}",
+                format!("… {} more lines", total - NEW_FILE_PREVIEW_LINES).dimmed()
+            );
+        }
+
+        let choice = confirm_multi_choice(
+            &format!("Create '{}'? It {}.", path, risk.describe()),
+            &["Create", "Don't create"],
+            1,
+            ConfirmationType::Permission,
+        )?;
+        if choice == 0 {
+            return Ok(());
+        }
+        Err(SofosError::ToolExecution(format!(
+            "The user declined to create '{}'. Ask them before trying again. \
+             This check is turned off with `confirm_new_files = false` under [permissions] in {}.",
+            path,
+            crate::config::config_files_hint()
+        )))
+    }
+
+    /// Under confirm mode, show what a file tool is about to do to `path`
+    /// and ask `question` before doing it. `change` is the content before
+    /// and after; a move has none. Without a terminal to answer, the edit
+    /// is declined.
+    fn confirm_edit(&self, question: &str, path: &str, change: Option<(&str, &str)>) -> Result<()> {
+        if !self.confirm_edits {
+            return Ok(());
+        }
+        let declined = |reason: &str| {
+            Err(SofosError::ToolExecution(format!(
+                "The user declined the edit to '{}'{}, so nothing was changed. \
+                 Ask them how to proceed instead of retrying the same change.",
+                path, reason
+            )))
+        };
+        if !self.interactive {
+            return declined(" (there is no terminal to confirm it)");
+        }
+        if let Some((original, modified)) = change {
+            let preview =
+                diff::generate_diff(self.diff_style, original, modified, path, diff_width());
+            println!(
+                "{}",
+                truncate_for_context(&preview, MAX_DIFF_TOKENS, TruncationKind::DiffOutput)
+            );
+        }
+        let choice = confirm_multi_choice(
+            question,
+            &["Apply", "Decline"],
+            1,
+            ConfirmationType::Permission,
+        )?;
+        if choice == 0 { Ok(()) } else { declined("") }
+    }
+
+    /// `search_and_replace`: ripgrep finds the files, each is planned in
+    /// memory, and nothing is written unless `confirm` is set and, in
+    /// confirm mode, the user accepts the combined diff.
+    fn search_and_replace(&self, input: &Value) -> Result<ToolExecutionResult> {
+        let code_search = self.code_search_tool.as_ref().ok_or_else(|| {
+            SofosError::ToolExecution(
+                "Code search not available. Please install ripgrep: https://github.com/BurntSushi/ripgrep"
+                    .to_string(),
+            )
+        })?;
+        let pattern = input["pattern"]
+            .as_str()
+            .ok_or_else(|| SofosError::ToolExecution("Missing 'pattern' parameter".to_string()))?;
+        let replacement = input["replacement"].as_str().ok_or_else(|| {
+            SofosError::ToolExecution("Missing 'replacement' parameter".to_string())
+        })?;
+        let confirm = input["confirm"].as_bool().unwrap_or(false);
+        let regex = regex::Regex::new(pattern).map_err(|e| {
+            SofosError::ToolExecution(format!("Invalid pattern '{}': {}", pattern, e))
+        })?;
+
+        let files = code_search.files_with_matches(
+            pattern,
+            input["file_type"].as_str(),
+            input["glob"].as_str(),
+        )?;
+        if files.is_empty() {
+            return Ok(ToolExecutionResult::Text(format!(
+                "No matches found for pattern: '{}'",
+                pattern
+            )));
+        }
+        if files.len() > MAX_REPLACE_FILES {
+            return Err(SofosError::ToolExecution(format!(
+                "'{}' matches {} files, more than the {} one call may change. \
+                 Narrow it with `glob` or `file_type`.",
+                pattern,
+                files.len(),
+                MAX_REPLACE_FILES
+            )));
+        }
+
+        let mut planned = Vec::new();
+        let mut skipped = Vec::new();
+        for path in &files {
+            let resolved = match self.resolve_existing(path) {
+                Ok(resolved) if resolved.is_inside_workspace => resolved,
+                _ => {
+                    skipped.push(format!("{}: outside the workspace", path));
+                    continue;
+                }
+            };
+            if self.check_not_protected(path, &resolved.canonical).is_err() {
+                skipped.push(format!("{}: protected path", path));
+                continue;
+            }
+            match self.fs_tool.read_file(path) {
+                Ok(original) => {
+                    if let Some(plan) = replace::replace_in(&regex, replacement, path, original) {
+                        planned.push((resolved.canonical, plan));
+                    }
+                }
+                Err(e) => skipped.push(format!("{}: {}", path, e)),
+            }
+        }
+
+        let plans: Vec<_> = planned.iter().map(|(_, plan)| plan).collect();
+        let summary = replace::summary(pattern, &plans, &skipped);
+        if planned.is_empty() {
+            return Ok(ToolExecutionResult::Text(summary));
+        }
+        if !confirm {
+            return Ok(ToolExecutionResult::Text(format!(
+                "{}\nPreview only; nothing was changed. Call search_and_replace again \
+                 with confirm: true to apply it.",
+                summary
+            )));
+        }
+
+        let width = diff_width();
+        let diffs: Vec<String> = plans
+            .iter()
+            .map(|plan| {
+                diff::generate_diff(
+                    self.diff_style,
+                    &plan.original,
+                    &plan.modified,
+                    &plan.path,
+                    width,
+                )
+            })
+            .collect();
+        let combined = truncate_for_context(
+            &diffs.join("\n"),
+            MAX_DIFF_TOKENS,
+            TruncationKind::DiffOutput,
+        );
+        if self.confirm_edits && self.interactive {
+            println!("{}", combined);
+        }
+        self.confirm_edit(
+            &format!("Apply this replacement to {} files?", planned.len()),
+            &format!("{} files", planned.len()),
+            None,
+        )?;
+
+        let mut written = vec![FILE_MUTATION_SUMMARY_HEADER.to_string()];
+        for (canonical, plan) in &planned {
+            self.fs_tool.write_file(&plan.path, &plan.modified)?;
+            self.record_change(
+                canonical,
+                ChangeKind::Modified,
+                diff::line_delta(&plan.original, &plan.modified),
+            );
+            written.push(format!("M {}", plan.path));
+        }
+        Ok(ToolExecutionResult::TextWithDisplay {
+            text: format!("{}\n\n{}", written.join("\n"), summary),
+            display: format!("{}\nChanges:\n{}", summary, combined),
+        })
+    }
+
     /// Check if an external path is allowed for the given scope, asking
     /// the user if needed. Thin wrapper that forwards to the shared
     /// `permissions::check_external_path_session_access` so the same
@@ -652,7 +1268,7 @@ impl ToolExecutor {
         session_denied: &Arc<Mutex<HashSet<String>>>,
     ) -> Result<()> {
         permissions::check_external_path_session_access(
-            self.fs_tool.workspace(),
+            self.fs_tool.root(),
             scope,
             canonical_path,
             dir_to_grant,
@@ -673,6 +1289,18 @@ impl ToolExecutor {
 
         if self.has_code_search() {
             add_code_search_tool(&mut tools);
+            if !self.mode.is_readonly() {
+                add_search_and_replace_tool(&mut tools);
+            }
+        }
+
+        if !self.inputs.is_empty() {
+            let names: Vec<&str> = self.inputs.keys().map(String::as_str).collect();
+            add_read_input_tool(&mut tools, &names);
+        }
+
+        if self.fs_tool.backups_enabled() && !self.mode.is_readonly() {
+            add_undo_edit_tool(&mut tools);
         }
 
         if let Some(mcp_manager) = &self.mcp_manager {
@@ -689,7 +1317,20 @@ impl ToolExecutor {
         tools
     }
 
+    /// Run one tool call, recording it in the audit log when `--audit`
+    /// is on.
     pub async fn execute(&self, tool_name: &str, input: &Value) -> Result<ToolExecutionResult> {
+        let mut result = self.dispatch(tool_name, input).await;
+        if self.compact_results && matches!(tool_name, "read_file" | "read_symbol") {
+            if let Ok(ToolExecutionResult::Text(text)) = &mut result {
+                *text = crate::tools::compact_read_file_output(text);
+            }
+        }
+        audit::record_tool(tool_name, input, &result);
+        result
+    }
+
+    async fn dispatch(&self, tool_name: &str, input: &Value) -> Result<ToolExecutionResult> {
         // Check if this is an MCP tool first
         if let Some(mcp_manager) = &self.mcp_manager {
             if mcp_manager.is_mcp_tool(tool_name) {
@@ -702,13 +1343,18 @@ impl ToolExecutor {
                         tool_name
                     )));
                 }
+                // A server not marked for read-only access may change
+                // things, so dry-run treats its tools as mutating.
+                if self.dry_run && !mcp_manager.is_server_available_in_readonly(server) {
+                    return Ok(ToolExecutionResult::Text(dry_run_result(tool_name, input)));
+                }
                 // MCP servers run unconfined with open network, so a server's
                 // first tool call is gated behind approval; the grant is
                 // remembered for the session or saved as an `Mcp(<server>)`
                 // rule.
                 let bare_tool = crate::mcp::manager::bare_tool_name(server, tool_name);
                 permissions::check_mcp_session_access(
-                    self.fs_tool.workspace(),
+                    self.fs_tool.root(),
                     server,
                     bare_tool,
                     self.interactive,
@@ -734,11 +1380,20 @@ impl ToolExecutor {
             )));
         }
 
+        if self.dry_run && !tool.is_read_only_safe() {
+            return Ok(ToolExecutionResult::Text(dry_run_result(
+                tool.as_str(),
+                input,
+            )));
+        }
+
         let text_result = match tool {
             ToolName::ReadFile => {
                 let path = input["path"].as_str().ok_or_else(|| {
                     SofosError::ToolExecution("Missing 'path' parameter".to_string())
                 })?;
+                let smart = self.smart_path(path)?;
+                let path = smart.as_deref().unwrap_or(path);
 
                 let resolved = self.resolve_existing(path).map_err(|_| {
                     let parent_dir = std::path::Path::new(path)
@@ -758,6 +1413,50 @@ impl ToolExecutor {
                     resolved.is_inside_workspace,
                 )?;
 
+                let window = requested_line_window(input)?;
+                if input.get("as_hex").and_then(Value::as_bool) == Some(true) {
+                    if window.is_some() {
+                        return Err(SofosError::ToolExecution(
+                            "`as_hex` cannot be combined with `offset` or `limit`".to_string(),
+                        ));
+                    }
+                    let dump = if resolved.is_inside_workspace {
+                        self.fs_tool.read_file_hex(path)?
+                    } else {
+                        self.fs_tool
+                            .read_file_hex_with_outside_access(&resolved.canonical_str)?
+                    };
+                    return Ok(ToolExecutionResult::Text(
+                        crate::tools::format_read_file_output(path, &dump),
+                    ));
+                }
+
+                if let Some(window) = window {
+                    let read = if resolved.is_inside_workspace {
+                        self.fs_tool.read_file_window(path, window)?
+                    } else {
+                        self.fs_tool
+                            .read_file_window_with_outside_access(&resolved.canonical_str, window)?
+                    };
+                    let content = truncate_for_context(
+                        &read.content,
+                        MAX_FILE_READ_TOKENS,
+                        TruncationKind::File,
+                    );
+                    let text = if read.line_count == 0 {
+                        crate::tools::format_read_file_output(path, &content)
+                    } else {
+                        crate::tools::format_read_file_window_output(
+                            path,
+                            window.offset,
+                            window.offset + read.line_count - 1,
+                            read.remaining,
+                            &content,
+                        )
+                    };
+                    return Ok(ToolExecutionResult::Text(text));
+                }
+
                 // Read raw file contents, then apply the model-facing
                 // truncation cap here at the dispatcher. Truncation lives
                 // in this layer (not inside `fs_tool.read_file`) so that
@@ -770,10 +1469,93 @@ impl ToolExecutor {
                     self.fs_tool
                         .read_file_with_outside_access(&resolved.canonical_str)?
                 };
+                if self.compact_results {
+                    let earlier =
+                        self.reads.lock().ok().and_then(|mut reads| {
+                            reads.earlier_read(&resolved.canonical_str, &raw)
+                        });
+                    if let Some(turn) = earlier {
+                        return Ok(ToolExecutionResult::TextWithDisplay {
+                            text: reads::unchanged_note(path, turn),
+                            display: reads::unchanged_display(path, turn),
+                        });
+                    }
+                }
                 let content =
                     truncate_for_context(&raw, MAX_FILE_READ_TOKENS, TruncationKind::File);
                 Ok(crate::tools::format_read_file_output(path, &content))
             }
+            ToolName::ReadSymbol => {
+                let path = input["path"].as_str().ok_or_else(|| {
+                    SofosError::ToolExecution("Missing 'path' parameter".to_string())
+                })?;
+                let query = input["symbol"].as_str().ok_or_else(|| {
+                    SofosError::ToolExecution("Missing 'symbol' parameter".to_string())
+                })?;
+                let smart = self.smart_path(path)?;
+                let path = smart.as_deref().unwrap_or(path);
+                let resolved = self.resolve_existing(path).map_err(|_| {
+                    SofosError::ToolExecution(format!("File not found: '{}'", path))
+                })?;
+                self.check_read_access(
+                    path,
+                    &resolved.canonical,
+                    &resolved.canonical_str,
+                    resolved.is_inside_workspace,
+                )?;
+                let raw = if resolved.is_inside_workspace {
+                    self.fs_tool.read_file(path)?
+                } else {
+                    self.fs_tool
+                        .read_file_with_outside_access(&resolved.canonical_str)?
+                };
+
+                let list = |candidates: &[symbols::Symbol]| -> String {
+                    candidates
+                        .iter()
+                        .map(|symbol| format!("\n  {}", symbol.describe()))
+                        .collect()
+                };
+                match symbols::lookup(path, &raw, query) {
+                    Some(symbols::Lookup::Found(symbol)) => {
+                        let lines: Vec<&str> = raw.lines().collect();
+                        let body = lines[symbol.first..=symbol.last].join("\n") + "\n";
+                        let content =
+                            truncate_for_context(&body, MAX_FILE_READ_TOKENS, TruncationKind::File);
+                        Ok(crate::tools::format_read_file_window_output(
+                            path,
+                            symbol.first + 1,
+                            symbol.last + 1,
+                            lines.len() - symbol.last - 1,
+                            &content,
+                        ))
+                    }
+                    Some(symbols::Lookup::Ambiguous(candidates)) => {
+                        Err(SofosError::ToolExecution(format!(
+                            "'{}' matches {} definitions in '{}'; qualify it with its parent (e.g. 'Type::name' or 'Class.name'):{}",
+                            query,
+                            candidates.len(),
+                            path,
+                            list(&candidates)
+                        )))
+                    }
+                    Some(symbols::Lookup::NotFound(candidates)) => {
+                        let hint = if candidates.is_empty() {
+                            String::new()
+                        } else {
+                            format!(" Candidates:{}", list(&candidates))
+                        };
+                        Err(SofosError::ToolExecution(format!(
+                            "Symbol '{}' not found in '{}'.{}",
+                            query, path, hint
+                        )))
+                    }
+                    None => Err(SofosError::ToolExecution(format!(
+                        "read_symbol supports Rust, Python, JavaScript, and TypeScript files; use read_file or search_code for '{}'",
+                        path
+                    ))),
+                }
+            }
             ToolName::WriteFile => {
                 // Accept common parameter-name variations. OpenAI
                 // models occasionally emit `file_path` / `file` /
@@ -832,7 +1614,11 @@ impl ToolExecutor {
                         ))
                     })?;
 
+                let smart = self.smart_path(path)?;
+                let path = smart.as_deref().unwrap_or(path);
+
                 let resolved = self.resolve_for_write(path)?;
+                self.check_not_protected(path, &resolved.canonical)?;
 
                 if !resolved.is_inside_workspace {
                     self.check_write_access(path, &resolved.canonical_str, &resolved.canonical)?;
@@ -862,6 +1648,19 @@ impl ToolExecutor {
                         .ok()
                 };
 
+                let existed = resolved.canonical.exists();
+                if self.confirm_edits {
+                    // The edit prompt shows the whole new file, so the
+                    // new-file prompt would only ask twice.
+                    let verb = if append { "Append to" } else { "Write" };
+                    self.confirm_edit(
+                        &format!("{} '{}'?", verb, path),
+                        path,
+                        Some((original_content.as_deref().unwrap_or_default(), content)),
+                    )?;
+                } else if !existed {
+                    self.confirm_new_file(path, &resolved.canonical, content)?;
+                }
                 match (append, resolved.is_inside_workspace) {
                     (true, true) => self.fs_tool.append_file(path, content)?,
                     (true, false) => self
@@ -873,6 +1672,17 @@ impl ToolExecutor {
                         .write_file_with_outside_access(&resolved.canonical_str, content)?,
                 }
 
+                let kind = if existed {
+                    ChangeKind::Modified
+                } else {
+                    ChangeKind::Added
+                };
+                let delta = match original_content.as_deref() {
+                    Some(original) => diff::line_delta(original, content),
+                    None => diff::line_delta("", content),
+                };
+                self.record_change(&resolved.canonical, kind, delta);
+
                 if append {
                     Ok(format!(
                         "Successfully appended {} bytes to '{}'",
@@ -885,6 +1695,7 @@ impl ToolExecutor {
                         &original,
                         content,
                         "Successfully wrote to file",
+                        self.diff_style,
                     ));
                 } else {
                     Ok(format!("Successfully created file '{}'", path))
@@ -925,6 +1736,35 @@ impl ToolExecutor {
                     TruncationKind::PathList,
                 ))
             }
+            ToolName::Tree => {
+                let path = input["path"].as_str().unwrap_or(".");
+                let max_depth = input["max_depth"]
+                    .as_u64()
+                    .map_or(DEFAULT_TREE_DEPTH, |depth| {
+                        usize::try_from(depth).unwrap_or(usize::MAX).max(1)
+                    });
+
+                let resolved = self.resolve_existing(path)?;
+                self.check_read_access(
+                    path,
+                    &resolved.canonical,
+                    &resolved.canonical_str,
+                    resolved.is_inside_workspace,
+                )?;
+                if !resolved.is_inside_workspace {
+                    return Err(SofosError::ToolExecution(format!(
+                        "tree only walks directories inside the workspace; use list_directory for '{}'",
+                        path
+                    )));
+                }
+
+                let tree = self.fs_tool.tree(path, max_depth)?;
+                Ok(truncate_for_context(
+                    &tree,
+                    MAX_PATH_LIST_TOKENS,
+                    TruncationKind::PathList,
+                ))
+            }
             ToolName::CreateDirectory => {
                 let path = input["path"].as_str().ok_or_else(|| {
                     SofosError::ToolExecution("Missing 'path' parameter".to_string())
@@ -961,18 +1801,22 @@ impl ToolExecutor {
                     SofosError::ToolExecution("Missing 'pattern' parameter".to_string())
                 })?;
 
-                let file_type = input["file_type"].as_str();
-                let max_results = input["max_results"].as_u64().map(|n| n as usize);
-                let include_ignored = input["include_ignored"].as_bool().unwrap_or(false);
+                let options = SearchOptions {
+                    file_type: input["file_type"].as_str(),
+                    max_results: input["max_results"].as_u64().map(|n| n as usize),
+                    include_ignored: input["include_ignored"].as_bool().unwrap_or(false),
+                    include_hidden: input["include_hidden"].as_bool().unwrap_or(false),
+                    context_lines: input["context_lines"].as_u64().map(|n| n as usize),
+                };
 
-                let results =
-                    code_search.search(pattern, file_type, max_results, include_ignored)?;
+                let results = code_search.search(pattern, &options)?;
                 Ok(format!(
                     "{}{}",
                     crate::tools::codesearch::SEARCH_RESULTS_PREFIX,
                     results
                 ))
             }
+            ToolName::SearchAndReplace => return self.search_and_replace(input),
             ToolName::GlobFiles => {
                 let pattern = input["pattern"].as_str().ok_or_else(|| {
                     SofosError::ToolExecution("Missing 'pattern' parameter".to_string())
@@ -1044,6 +1888,7 @@ impl ToolExecutor {
                     }
                 }
                 let mut hit_cap = false;
+                let ignored = SofosIgnore::load(self.fs_tool.root());
 
                 'walk: while let Some(dir) = stack.pop() {
                     let entries = match std::fs::read_dir(&dir) {
@@ -1062,7 +1907,11 @@ impl ToolExecutor {
                             continue;
                         }
                         let path = entry.path();
-                        if path.is_dir() {
+                        let is_dir = path.is_dir();
+                        if ignored.is_ignored(&path, is_dir) {
+                            continue;
+                        }
+                        if is_dir {
                             let dir_name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
                             if excluded_basenames.contains(dir_name) {
                                 continue;
@@ -1176,6 +2025,8 @@ impl ToolExecutor {
                         path
                     ))
                 })?;
+                self.check_not_protected(path, &resolved.canonical)?;
+                SofosIgnore::load(self.fs_tool.root()).check(path, &resolved.canonical)?;
 
                 // External paths require BOTH a Read grant (we read the
                 // file to compute the modified content and the diff) and
@@ -1227,6 +2078,11 @@ impl ToolExecutor {
                 } else {
                     original.replacen(old_string, new_string, 1)
                 };
+                self.confirm_edit(
+                    &format!("Apply this edit to '{}'?", path),
+                    path,
+                    Some((&original, &modified)),
+                )?;
 
                 // Re-stat: any mtime/length drift means another writer
                 // touched the file mid-edit. Best-effort.
@@ -1254,11 +2110,17 @@ impl ToolExecutor {
                         .write_file_with_outside_access(&resolved.canonical_str, &modified)?;
                 }
 
+                self.record_change(
+                    &resolved.canonical,
+                    ChangeKind::Modified,
+                    diff::line_delta(&original, &modified),
+                );
                 return Ok(file_modification_result(
                     path,
                     &original,
                     &modified,
                     "Successfully edited",
+                    self.diff_style,
                 ));
             }
             ToolName::MorphEditFile => {
@@ -1313,6 +2175,8 @@ impl ToolExecutor {
                         path
                     ))
                 })?;
+                self.check_not_protected(path, &resolved.canonical)?;
+                SofosIgnore::load(self.fs_tool.root()).check(path, &resolved.canonical)?;
 
                 // External paths require BOTH Read (we send the file to
                 // Morph as context) and Write (we write the merged
@@ -1351,13 +2215,13 @@ impl ToolExecutor {
                             "⚠".bright_yellow(),
                             morph_timeout.as_secs()
                         );
-                        return Ok(ToolExecutionResult::Text(format!(
-                            "morph_edit_file timed out after {}s. The file '{}' was NOT modified. \
-                             Please use read_file to get the current file content, then use edit_file \
-                             with exact old_string/new_string to make this change.",
-                            morph_timeout.as_secs(),
-                            path
-                        )));
+                        return Ok(self.morph_failure_result(
+                            format!(
+                                "morph_edit_file timed out after {}s",
+                                morph_timeout.as_secs()
+                            ),
+                            path,
+                        ));
                     }
                     Ok(Err(e)) => {
                         // Match only variants Morph produces; propagate anything
@@ -1372,12 +2236,10 @@ impl ToolExecutor {
                             "⚠".bright_yellow(),
                             msg
                         );
-                        return Ok(ToolExecutionResult::Text(format!(
-                            "morph_edit_file failed ({}). The file '{}' was NOT modified. \
-                             Please use read_file to get the current file content, then use edit_file \
-                             with exact old_string/new_string to make this change.",
-                            msg, path
-                        )));
+                        return Ok(self.morph_failure_result(
+                            format!("morph_edit_file failed ({})", msg),
+                            path,
+                        ));
                     }
                 };
 
@@ -1396,13 +2258,16 @@ impl ToolExecutor {
                         "⚠".bright_yellow(),
                         reason
                     );
-                    return Ok(ToolExecutionResult::Text(format!(
-                        "morph_edit_file rejected Morph's response ({}). The file '{}' was NOT modified. \
-                         Please use read_file to get the current file content, then use edit_file \
-                         with exact old_string/new_string to make this change.",
-                        reason, path
-                    )));
+                    return Ok(self.morph_failure_result(
+                        format!("morph_edit_file rejected Morph's response ({})", reason),
+                        path,
+                    ));
                 }
+                self.confirm_edit(
+                    &format!("Apply this edit to '{}'?", path),
+                    path,
+                    Some((&original_code, &merged_code)),
+                )?;
 
                 if resolved.is_inside_workspace {
                     self.fs_tool.write_file(path, &merged_code)?;
@@ -1411,11 +2276,17 @@ impl ToolExecutor {
                         .write_file_with_outside_access(&resolved.canonical_str, &merged_code)?;
                 }
 
+                self.record_change(
+                    &resolved.canonical,
+                    ChangeKind::Modified,
+                    diff::line_delta(&original_code, &merged_code),
+                );
                 return Ok(file_modification_result(
                     path,
                     &original_code,
                     &merged_code,
                     "Successfully applied Morph edit to",
+                    self.diff_style,
                 ));
             }
             ToolName::DeleteFile => {
@@ -1429,6 +2300,7 @@ impl ToolExecutor {
                 // BEFORE the user types y/n (consistent with
                 // `write_file` / `edit_file`).
                 let resolved = self.resolve_existing(path)?;
+                self.check_not_protected(path, &resolved.canonical)?;
 
                 if !resolved.is_inside_workspace {
                     self.check_write_access(path, &resolved.canonical_str, &resolved.canonical)?;
@@ -1443,12 +2315,16 @@ impl ToolExecutor {
                     )));
                 }
 
+                let removed = std::fs::read_to_string(&resolved.canonical)
+                    .map(|text| text.lines().count())
+                    .unwrap_or(0);
                 if resolved.is_inside_workspace {
                     self.fs_tool.delete_file(path)?;
                 } else {
                     self.fs_tool
                         .delete_file_with_outside_access(&resolved.canonical_str)?;
                 }
+                self.record_change(&resolved.canonical, ChangeKind::Deleted, (0, removed));
                 Ok(format!("Successfully deleted file '{}'", path))
             }
             ToolName::DeleteDirectory => {
@@ -1457,6 +2333,7 @@ impl ToolExecutor {
                 })?;
 
                 let resolved = self.resolve_existing(path)?;
+                self.check_not_protected(path, &resolved.canonical)?;
 
                 if !resolved.is_inside_workspace {
                     self.check_write_access(path, &resolved.canonical_str, &resolved.canonical)?;
@@ -1480,6 +2357,7 @@ impl ToolExecutor {
                     self.fs_tool
                         .delete_directory_with_outside_access(&resolved.canonical_str)?;
                 }
+                self.record_change(&resolved.canonical, ChangeKind::Deleted, (0, 0));
                 Ok(format!("Successfully deleted directory '{}'", path))
             }
             ToolName::MoveFile => {
@@ -1495,6 +2373,8 @@ impl ToolExecutor {
                 // External destinations need Write as usual.
                 let src_resolved = self.resolve_existing(source)?;
                 let dst_resolved = self.resolve_for_write(destination)?;
+                self.check_not_protected(source, &src_resolved.canonical)?;
+                self.check_not_protected(destination, &dst_resolved.canonical)?;
 
                 if !src_resolved.is_inside_workspace {
                     self.check_write_access(
@@ -1511,6 +2391,11 @@ impl ToolExecutor {
                     )?;
                 }
 
+                self.confirm_edit(
+                    &format!("Move '{}' to '{}'?", source, destination),
+                    source,
+                    None,
+                )?;
                 move_between(
                     source,
                     destination,
@@ -1518,6 +2403,11 @@ impl ToolExecutor {
                     &dst_resolved,
                     &self.fs_tool,
                 )?;
+                let from = self.change_display_path(&src_resolved.canonical);
+                let to = self.change_display_path(&dst_resolved.canonical);
+                if let Ok(mut changes) = self.file_changes.lock() {
+                    changes.record_move(&from, &to);
+                }
                 Ok(format!(
                     "Successfully moved '{}' to '{}'",
                     source, destination
@@ -1536,6 +2426,7 @@ impl ToolExecutor {
                 // need Write.
                 let src_resolved = self.resolve_existing(source)?;
                 let dst_resolved = self.resolve_for_write(destination)?;
+                self.check_not_protected(destination, &dst_resolved.canonical)?;
 
                 if !src_resolved.is_inside_workspace {
                     self.check_read_access(
@@ -1553,6 +2444,17 @@ impl ToolExecutor {
                     )?;
                 }
 
+                let previous = std::fs::read_to_string(&dst_resolved.canonical).ok();
+                if self.confirm_edits {
+                    let incoming = std::fs::read_to_string(&src_resolved.canonical).ok();
+                    self.confirm_edit(
+                        &format!("Copy '{}' to '{}'?", source, destination),
+                        destination,
+                        incoming
+                            .as_deref()
+                            .map(|text| (previous.as_deref().unwrap_or_default(), text)),
+                    )?;
+                }
                 copy_between(
                     source,
                     destination,
@@ -1560,6 +2462,14 @@ impl ToolExecutor {
                     &dst_resolved,
                     &self.fs_tool,
                 )?;
+                let copied = std::fs::read_to_string(&dst_resolved.canonical).unwrap_or_default();
+                let kind = if previous.is_some() {
+                    ChangeKind::Modified
+                } else {
+                    ChangeKind::Added
+                };
+                let delta = diff::line_delta(previous.as_deref().unwrap_or_default(), &copied);
+                self.record_change(&dst_resolved.canonical, kind, delta);
                 Ok(format!(
                     "Successfully copied '{}' to '{}'",
                     source, destination
@@ -1595,6 +2505,19 @@ impl ToolExecutor {
                 };
                 Ok(result)
             }
+            ToolName::RunTests => {
+                let command = match input["command"].as_str().map(str::trim) {
+                    Some(command) if !command.is_empty() => command,
+                    _ => testrun::detect(self.workspace()).ok_or_else(|| {
+                        SofosError::ToolExecution(format!(
+                            "No test command detected: none of {} is at the workspace root. Pass `command` to say how to run the tests.",
+                            testrun::known_markers()
+                        ))
+                    })?,
+                };
+                let output = self.bash_executor.execute(command)?;
+                Ok(testrun::summarize(command, &output))
+            }
             ToolName::UpdatePlan => {
                 let update = plan::parse_plan_update(input)?;
                 return Ok(ToolExecutionResult::TextWithDisplay {
@@ -1638,7 +2561,11 @@ impl ToolExecutor {
                             path
                         )));
                     }
-                    self.image_loader.load_local_image(trimmed)?
+                    self.image_loader.load_local_image(
+                        trimmed,
+                        self.fs_tool.workspace(),
+                        self.image_max_dimension,
+                    )?
                 };
 
                 let image = ImageData::from(source);
@@ -1659,6 +2586,109 @@ impl ToolExecutor {
                     images: vec![image],
                 }));
             }
+            ToolName::UndoEdit => {
+                let path = input["path"].as_str().ok_or_else(|| {
+                    SofosError::ToolExecution("Missing 'path' parameter".to_string())
+                })?;
+                let resolved = self.resolve_for_write(path)?;
+                if !resolved.is_inside_workspace {
+                    return Err(SofosError::ToolExecution(format!(
+                        "undo_edit only restores files inside the workspace, and '{}' is outside it.",
+                        path
+                    )));
+                }
+                self.check_not_protected(path, &resolved.canonical)?;
+                SofosIgnore::load(self.fs_tool.root()).check(path, &resolved.canonical)?;
+                // The reverted diff shows the backup's content, so a file
+                // the model may not read is not restored through it.
+                self.check_read_access(
+                    path,
+                    &resolved.canonical,
+                    &resolved.canonical_str,
+                    resolved.is_inside_workspace,
+                )?;
+                let backup = self.fs_tool.latest_backup(path)?.ok_or_else(|| {
+                    SofosError::ToolExecution(format!(
+                        "No backup found for '{}'. Backups are made only with --backups on, and only when a file tool overwrites an existing file.",
+                        path
+                    ))
+                })?;
+
+                let existed = resolved.canonical.exists();
+                let current = self.fs_tool.read_file(path).unwrap_or_default();
+                let restored = self.fs_tool.read_backup(&backup)?;
+                self.confirm_edit(
+                    &format!("Restore the previous version of '{}'?", path),
+                    path,
+                    Some((&current, &restored)),
+                )?;
+                self.fs_tool.restore_backup(path, &backup, &restored)?;
+                let kind = if existed {
+                    ChangeKind::Modified
+                } else {
+                    ChangeKind::Added
+                };
+                self.record_change(
+                    &resolved.canonical,
+                    kind,
+                    diff::line_delta(&current, &restored),
+                );
+
+                let result = file_modification_result(
+                    path,
+                    &current,
+                    &restored,
+                    "Restored the previous version of",
+                    self.diff_style,
+                );
+                let reverted = truncate_for_context(
+                    &diff::generate_plain_diff(&current, &restored),
+                    MAX_DIFF_TOKENS,
+                    TruncationKind::DiffOutput,
+                );
+                return Ok(ToolExecutionResult::TextWithDisplay {
+                    text: format!("{}\n\nReverted changes:\n{}", result.text(), reverted),
+                    display: result.display_text().to_string(),
+                });
+            }
+            ToolName::GitStatus => crate::tools::git::status(self.fs_tool.workspace()),
+            ToolName::FileInfo => {
+                let path = input["path"].as_str().ok_or_else(|| {
+                    SofosError::ToolExecution("Missing 'path' parameter".to_string())
+                })?;
+                let resolved = self.resolve_existing(path).map_err(|_| {
+                    SofosError::ToolExecution(format!("File not found: '{}'", path))
+                })?;
+                self.check_read_access(
+                    path,
+                    &resolved.canonical,
+                    &resolved.canonical_str,
+                    resolved.is_inside_workspace,
+                )?;
+                crate::tools::filesystem::file_info(&resolved.canonical, path)
+            }
+            ToolName::ReadInput => {
+                let name = input["name"].as_str().ok_or_else(|| {
+                    SofosError::ToolExecution("Missing 'name' parameter".to_string())
+                })?;
+                let content = self.inputs.get(name).ok_or_else(|| {
+                    let names: Vec<&str> = self.inputs.keys().map(String::as_str).collect();
+                    SofosError::ToolExecution(format!(
+                        "No input named '{}'. Available inputs: {}",
+                        name,
+                        if names.is_empty() {
+                            "none".to_string()
+                        } else {
+                            names.join(", ")
+                        }
+                    ))
+                })?;
+                Ok(truncate_for_context(
+                    content,
+                    MAX_FILE_READ_TOKENS,
+                    TruncationKind::File,
+                ))
+            }
             ToolName::WebFetch => {
                 use futures::StreamExt;
 
@@ -1684,7 +2714,7 @@ impl ToolExecutor {
                     )
                 })?;
                 permissions::check_web_fetch_session_access(
-                    self.fs_tool.workspace(),
+                    self.fs_tool.root(),
                     host,
                     self.interactive,
                     &self.web_fetch_session_allowed,
@@ -1,11 +1,71 @@
 use crate::error::{Result, ResultExt, SofosError};
+use crate::tools::changes::FileVersions;
+use crate::tools::permissions::{CommandPermission, PermissionManager};
+use crate::tools::sofosignore::SofosIgnore;
 use crate::tools::utils::is_absolute_path;
 use rand::RngExt;
+use std::collections::BTreeMap;
 use std::fs;
-use std::io::Write as _;
+use std::io::{BufRead, BufReader, Read as _, Write as _};
 use std::path::{Component, Path, PathBuf};
 
-const MAX_FILE_SIZE: u64 = 50 * 1024 * 1024; // 50MB limit
+pub(crate) const MAX_FILE_SIZE: u64 = 50 * 1024 * 1024; // 50MB limit
+
+/// Leading bytes checked to tell a text file from a binary one.
+const BINARY_SNIFF_BYTES: usize = 8 * 1024;
+
+/// Leading bytes of a file that `read_file` with `as_hex` dumps.
+pub(crate) const MAX_HEX_DUMP_BYTES: usize = 4 * 1024;
+
+/// Bytes per hex dump row.
+const HEX_DUMP_ROW_BYTES: usize = 16;
+
+/// Magic numbers for common binaries that are not images; images are
+/// recognised by the `image` crate.
+const BINARY_SIGNATURES: &[(&[u8], &str)] = &[
+    (b"%PDF-", "application/pdf"),
+    (b"PK\x03\x04", "application/zip"),
+    (b"\x1f\x8b", "application/gzip"),
+    (b"\x7fELF", "application/x-elf"),
+    (b"\0asm", "application/wasm"),
+    (b"SQLite format 3\0", "application/vnd.sqlite3"),
+    (b"MZ", "application/x-msdownload"),
+];
+
+/// Where `--backups` keeps prior file contents, relative to the project root.
+const BACKUPS_DIR: &str = ".sofos/backups";
+
+/// Subdirectory of [`BACKUPS_DIR`] for files outside the project.
+const EXTERNAL_BACKUPS_DIR: &str = "external";
+
+/// Suffix appended to a backup's file name. Nanoseconds keep back-to-back
+/// writes apart, and the fixed width sorts chronologically.
+const BACKUP_TIMESTAMP_FORMAT: &str = "%Y%m%dT%H%M%S%.9f";
+
+/// Levels `tree` descends when the caller gives no `max_depth`.
+pub(crate) const DEFAULT_TREE_DEPTH: usize = 3;
+
+/// Entries `tree` lists before it stops with a truncation note.
+pub(crate) const MAX_TREE_ENTRIES: usize = 500;
+
+/// Directories `tree` never descends into, whether ignored or not.
+pub(crate) const TREE_SKIPPED_DIRS: &[&str] = &[".git", "node_modules", "target"];
+
+/// A 1-based range of lines for a paged `read_file` call. `limit: None`
+/// reads to the end of the file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LineWindow {
+    pub offset: usize,
+    pub limit: Option<usize>,
+}
+
+/// The lines a [`LineWindow`] selected, and how many follow them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WindowedRead {
+    pub content: String,
+    pub line_count: usize,
+    pub remaining: usize,
+}
 
 /// Upper bound on retries when reserving the random-suffix temp file
 /// during atomic writes. The 64-bit suffix makes a collision astronomically
@@ -205,10 +265,228 @@ fn append_bytes(path: &Path, content: &str) -> std::io::Result<()> {
     file.flush()
 }
 
+/// Resolve the relative `path` against `base` and canonicalize it,
+/// refusing absolute paths, `..` components, and anything that lands
+/// outside `bound` once symlinks are followed.
+pub(crate) fn resolve_within(base: &Path, bound: &Path, path: &str) -> Result<PathBuf> {
+    // `is_absolute_path` catches both Unix (`/foo`) and Windows
+    // (`C:\foo`, UNC `\\server\share`) shapes. Using
+    // `Path::is_absolute` directly would miss Unix-style paths
+    // when running on Windows — a regression the helper
+    // specifically guards against.
+    if is_absolute_path(path) {
+        return Err(SofosError::PathViolation(
+            "Absolute paths are not allowed".to_string(),
+        ));
+    }
+
+    // Reject only real `..` path components — a substring match would
+    // also reject legitimate filenames like `my..file.txt` or
+    // `cache..old/note.md`. The canonical check below is still the
+    // ultimate guard against escapes via symlinks, but stopping the
+    // traversal here gives a clearer error message and avoids
+    // letting `..` segments mix with workspace-relative joins.
+    if Path::new(path)
+        .components()
+        .any(|c| matches!(c, Component::ParentDir))
+    {
+        return Err(SofosError::PathViolation(
+            "Parent directory traversal (..) is not allowed".to_string(),
+        ));
+    }
+
+    let full_path = base.join(path);
+
+    let canonical = if full_path.exists() {
+        fs::canonicalize(&full_path)?
+    } else if let Some(parent) = full_path.parent() {
+        if parent.exists() {
+            let canonical_parent = fs::canonicalize(parent)?;
+            canonical_parent.join(full_path.file_name().context("Invalid filename")?)
+        } else {
+            full_path
+        }
+    } else {
+        full_path
+    };
+
+    if !canonical.starts_with(bound) {
+        return Err(SofosError::PathViolation(format!(
+            "Path escapes workspace: {}",
+            path
+        )));
+    }
+
+    Ok(canonical)
+}
+
+/// Whether `sample`, the start of a file, looks binary: it holds a NUL
+/// byte or bytes that are not UTF-8. A multi-byte character cut off at
+/// the end of the sample does not count.
+fn looks_binary(sample: &[u8]) -> bool {
+    sample.contains(&0) || std::str::from_utf8(sample).is_err_and(|e| e.error_len().is_some())
+}
+
+/// The MIME type `sample` starts with, when it is a known format.
+fn sniff_mime(sample: &[u8]) -> Option<&'static str> {
+    image::guess_format(sample)
+        .ok()
+        .map(|format| format.to_mime_type())
+        .or_else(|| {
+            BINARY_SIGNATURES
+                .iter()
+                .find(|(magic, _)| sample.starts_with(magic))
+                .map(|(_, mime)| *mime)
+        })
+}
+
+/// Size, line count, estimated tokens, and language of the file at
+/// `path`, for `file_info`. The file is streamed, so its size is not
+/// limited by [`MAX_FILE_SIZE`]; binary files report size and type only.
+pub fn file_info(path: &Path, label: &str) -> Result<String> {
+    let metadata =
+        fs::metadata(path).with_context(|| format!("Failed to read metadata for: {}", label))?;
+    if metadata.is_dir() {
+        return Err(SofosError::ToolExecution(format!(
+            "'{}' is a directory; use list_directory or tree instead",
+            label
+        )));
+    }
+    let size = metadata.len();
+    let mut reader = BufReader::new(
+        fs::File::open(path).with_context(|| format!("Failed to read file: {}", label))?,
+    );
+
+    let mut info = format!("File: {}\nSize: {} bytes", label, size);
+    let sample = reader
+        .fill_buf()
+        .with_context(|| format!("Failed to read file: {}", label))?;
+    if looks_binary(&sample[..sample.len().min(BINARY_SNIFF_BYTES)]) {
+        let kind = sniff_mime(sample).unwrap_or("unknown type");
+        info.push_str(&format!(
+            "\nType: binary ({}); read_file shows it only with as_hex",
+            kind
+        ));
+        return Ok(info);
+    }
+
+    let mut lines = 0usize;
+    let mut ends_with_newline = true;
+    loop {
+        let chunk = reader
+            .fill_buf()
+            .with_context(|| format!("Failed to read file: {}", label))?;
+        if chunk.is_empty() {
+            break;
+        }
+        lines += chunk.iter().filter(|b| **b == b'\n').count();
+        ends_with_newline = chunk.last() == Some(&b'\n');
+        let consumed = chunk.len();
+        reader.consume(consumed);
+    }
+    if !ends_with_newline {
+        lines += 1;
+    }
+    let tokens = crate::repl::conversation::ConversationHistory::estimate_tokens_for_len(
+        usize::try_from(size).unwrap_or(usize::MAX),
+    );
+    info.push_str(&format!(
+        "\nLines: {}\nEstimated tokens: ~{}",
+        lines, tokens
+    ));
+    if let Some(language) = crate::ui::diff::language_for_path(label) {
+        info.push_str(&format!("\nLanguage: {}", language));
+    }
+    if size > MAX_FILE_SIZE {
+        info.push_str(&format!(
+            "\nToo large for read_file (max {} MB): use search_code to find the relevant part.",
+            MAX_FILE_SIZE / (1024 * 1024)
+        ));
+    } else if tokens > crate::tools::utils::MAX_FILE_READ_TOKENS {
+        info.push_str(
+            "\nA whole-file read_file would be truncated: page it with offset and limit, or use search_code.",
+        );
+    }
+    Ok(info)
+}
+
+/// The file a backup was taken from: `backup` without its
+/// `.<timestamp>` suffix, or `None` when the name carries no timestamp.
+fn backup_source(backup: &Path) -> Option<PathBuf> {
+    let name = backup.file_name()?.to_str()?;
+    name.match_indices('.').find_map(|(dot, _)| {
+        chrono::NaiveDateTime::parse_from_str(&name[dot + 1..], BACKUP_TIMESTAMP_FORMAT)
+            .is_ok()
+            .then(|| backup.with_file_name(&name[..dot]))
+    })
+}
+
+fn format_file_size(bytes: u64) -> String {
+    const KB: u64 = 1024;
+    const MB: u64 = KB * KB;
+    if bytes < KB {
+        format!("{} bytes", bytes)
+    } else if bytes < MB {
+        format!("{}KB", bytes.div_ceil(KB))
+    } else {
+        format!("{:.1}MB", bytes as f64 / MB as f64)
+    }
+}
+
+/// The error a text read of a binary file returns, naming its type and
+/// size and pointing at the hex dump instead of a UTF-8 decode failure.
+fn binary_file_error(label: &str, sample: &[u8], size: u64) -> SofosError {
+    SofosError::ToolExecution(format!(
+        "'{}' appears to be a binary file ({}, {}). Call read_file with `as_hex: true` \
+         for a hex dump of its first {} KB.",
+        label,
+        sniff_mime(sample).unwrap_or("unknown type"),
+        format_file_size(size),
+        MAX_HEX_DUMP_BYTES / 1024
+    ))
+}
+
+/// Classic hex dump of `bytes`: offset, sixteen hex bytes, and the
+/// printable ASCII with `.` for everything else.
+fn hex_dump(bytes: &[u8]) -> String {
+    bytes
+        .chunks(HEX_DUMP_ROW_BYTES)
+        .enumerate()
+        .map(|(row, chunk)| {
+            let hex: Vec<String> = chunk.iter().map(|b| format!("{:02x}", b)).collect();
+            let ascii: String = chunk
+                .iter()
+                .map(|&b| {
+                    if b.is_ascii_graphic() || b == b' ' {
+                        b as char
+                    } else {
+                        '.'
+                    }
+                })
+                .collect();
+            format!(
+                "{:08x}  {:<width$}  |{}|",
+                row * HEX_DUMP_ROW_BYTES,
+                hex.join(" "),
+                ascii,
+                width = HEX_DUMP_ROW_BYTES * 3 - 1
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
 /// FileSystemTool provides secure file operations sandboxed to a workspace directory
 #[derive(Clone)]
 pub struct FileSystemTool {
+    /// Directory paths resolve against: the project root, or the
+    /// subdirectory selected with `/scope`.
     workspace: PathBuf,
+    /// The project root. Config files and permission grants live here.
+    root: PathBuf,
+    /// `--backups`: copy a file's prior content into [`BACKUPS_DIR`]
+    /// before overwriting it.
+    backups: bool,
 }
 
 impl FileSystemTool {
@@ -225,62 +503,174 @@ impl FileSystemTool {
         })?;
 
         Ok(Self {
-            workspace: canonical,
+            workspace: canonical.clone(),
+            root: canonical,
+            backups: false,
         })
     }
 
-    /// Validate and resolve a path relative to the workspace
-    /// Returns an error if the path attempts to escape the workspace
-    fn validate_path(&self, path: &str) -> Result<PathBuf> {
-        // `is_absolute_path` catches both Unix (`/foo`) and Windows
-        // (`C:\foo`, UNC `\\server\share`) shapes. Using
-        // `Path::is_absolute` directly would miss Unix-style paths
-        // when running on Windows — a regression the helper
-        // specifically guards against.
-        if is_absolute_path(path) {
-            return Err(SofosError::PathViolation(
-                "Absolute paths are not allowed".to_string(),
-            ));
-        }
+    pub fn set_backups(&mut self, enabled: bool) {
+        self.backups = enabled;
+    }
 
-        // Reject only real `..` path components — a substring match would
-        // also reject legitimate filenames like `my..file.txt` or
-        // `cache..old/note.md`. The canonical check below is still the
-        // ultimate guard against escapes via symlinks, but stopping the
-        // traversal here gives a clearer error message and avoids
-        // letting `..` segments mix with workspace-relative joins.
-        if Path::new(path)
-            .components()
-            .any(|c| matches!(c, Component::ParentDir))
-        {
-            return Err(SofosError::PathViolation(
-                "Parent directory traversal (..) is not allowed".to_string(),
-            ));
+    pub fn backups_enabled(&self) -> bool {
+        self.backups
+    }
+
+    /// The directory `target`'s backups go in and the file name they
+    /// start with: `.sofos/backups/<relative path>`, or under
+    /// `external/` by absolute path for files outside the project.
+    fn backup_location(&self, target: &Path) -> Option<(PathBuf, std::ffi::OsString)> {
+        let relative = match target.strip_prefix(&self.root) {
+            Ok(relative) => relative.to_path_buf(),
+            Err(_) => Path::new(EXTERNAL_BACKUPS_DIR).join(
+                target
+                    .components()
+                    .filter(|c| matches!(c, Component::Normal(_)))
+                    .collect::<PathBuf>(),
+            ),
+        };
+        let file_name = relative.file_name()?.to_os_string();
+        let dir = self.root.join(BACKUPS_DIR).join(relative.parent()?);
+        Some((dir, file_name))
+    }
+
+    /// With backups on, copy the existing file at `target` to
+    /// `.sofos/backups/<relative path>.<timestamp>`. A file the tools may
+    /// not read is not backed up, since the copy would be readable.
+    fn back_up(&self, target: &Path) -> Result<()> {
+        if !self.backups || !target.is_file() || self.hidden_from_reads(target) {
+            return Ok(());
         }
+        let Some((dir, mut backup_name)) = self.backup_location(target) else {
+            return Ok(());
+        };
+        backup_name.push(format!(
+            ".{}",
+            chrono::Local::now().format(BACKUP_TIMESTAMP_FORMAT)
+        ));
+        fs::create_dir_all(&dir)
+            .with_context(|| format!("Failed to create backup directory: {}", dir.display()))?;
+        fs::copy(target, dir.join(backup_name))
+            .with_context(|| format!("Failed to back up {}", target.display()))?;
+        Ok(())
+    }
 
-        let full_path = self.workspace.join(path);
+    /// Whether `target` matches `.sofosignore` or a `Read(...)` deny rule.
+    /// Neither covers the file's path under [`BACKUPS_DIR`].
+    fn hidden_from_reads(&self, target: &Path) -> bool {
+        if SofosIgnore::load(&self.root).is_ignored(target, false) {
+            return true;
+        }
+        let Ok(manager) = PermissionManager::new(self.root.clone()) else {
+            return true;
+        };
+        let absolute = target.to_string_lossy();
+        let relative = target
+            .strip_prefix(&self.root)
+            .map(|relative| relative.to_string_lossy());
+        std::iter::once(absolute)
+            .chain(relative)
+            .any(|path| manager.check_read_permission(&path) == CommandPermission::Denied)
+    }
 
-        let canonical = if full_path.exists() {
-            fs::canonicalize(&full_path)?
-        } else if let Some(parent) = full_path.parent() {
-            if parent.exists() {
-                let canonical_parent = fs::canonicalize(parent)?;
-                canonical_parent.join(full_path.file_name().context("Invalid filename")?)
-            } else {
-                full_path
-            }
-        } else {
-            full_path
+    /// The newest backup of the workspace file at `path`, if any.
+    pub fn latest_backup(&self, path: &str) -> Result<Option<PathBuf>> {
+        let validated_path = self.validate_path(path)?;
+        let Some((dir, file_name)) = self.backup_location(&validated_path) else {
+            return Ok(None);
         };
+        let Ok(entries) = fs::read_dir(&dir) else {
+            return Ok(None);
+        };
+        let prefix = format!("{}.", file_name.to_string_lossy());
+        // The timestamp must parse, so `a.rs.orig`'s backups never pass
+        // for `a.rs`'s; its fixed-width format sorts chronologically.
+        Ok(entries
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| {
+                entry
+                    .file_name()
+                    .to_str()
+                    .and_then(|name| name.strip_prefix(&prefix))
+                    .is_some_and(|stamp| {
+                        chrono::NaiveDateTime::parse_from_str(stamp, BACKUP_TIMESTAMP_FORMAT)
+                            .is_ok()
+                    })
+            })
+            .map(|entry| entry.path())
+            .max())
+    }
 
-        if !canonical.starts_with(&self.workspace) {
-            return Err(SofosError::PathViolation(format!(
-                "Path escapes workspace: {}",
-                path
-            )));
+    /// Each project file with backups against its oldest one, the content
+    /// from before Sofos first changed it. Files outside the project and
+    /// binary files are left out, as are files back to their original.
+    pub fn backup_originals(&self) -> Vec<FileVersions> {
+        let backups_root = self.root.join(BACKUPS_DIR);
+        let mut oldest: BTreeMap<PathBuf, PathBuf> = BTreeMap::new();
+        let mut pending = vec![backups_root.clone()];
+        while let Some(dir) = pending.pop() {
+            let Ok(entries) = fs::read_dir(&dir) else {
+                continue;
+            };
+            for entry in entries.filter_map(|entry| entry.ok()) {
+                let path = entry.path();
+                if path.is_dir() {
+                    if path != backups_root.join(EXTERNAL_BACKUPS_DIR) {
+                        pending.push(path);
+                    }
+                    continue;
+                }
+                let Some(original) = backup_source(&path) else {
+                    continue;
+                };
+                let Ok(relative) = original.strip_prefix(&backups_root) else {
+                    continue;
+                };
+                let slot = oldest.entry(relative.to_path_buf()).or_insert(path.clone());
+                if path < *slot {
+                    *slot = path;
+                }
+            }
         }
 
-        Ok(canonical)
+        oldest
+            .into_iter()
+            .filter_map(|(relative, backup)| {
+                let original = fs::read_to_string(backup).ok()?;
+                let current = match fs::read(self.root.join(&relative)) {
+                    Ok(bytes) => String::from_utf8(bytes).ok()?,
+                    Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+                    Err(_) => return None,
+                };
+                (original != current).then(|| FileVersions {
+                    path: relative.to_string_lossy().into_owned(),
+                    original,
+                    current,
+                })
+            })
+            .collect()
+    }
+
+    /// The content saved in `backup`.
+    pub fn read_backup(&self, backup: &Path) -> Result<String> {
+        fs::read_to_string(backup)
+            .with_context(|| format!("Failed to read backup: {}", backup.display()))
+    }
+
+    /// Put `content`, read from `backup`, back at `path` and delete
+    /// `backup`, so the next restore steps further back. The content
+    /// being replaced is not itself backed up.
+    pub fn restore_backup(&self, path: &str, backup: &Path, content: &str) -> Result<()> {
+        self.write_workspace_file(path, content, false)?;
+        fs::remove_file(backup)
+            .with_context(|| format!("Failed to remove backup: {}", backup.display()))
+    }
+
+    /// Validate and resolve a path relative to the workspace
+    /// Returns an error if the path attempts to escape the workspace
+    fn validate_path(&self, path: &str) -> Result<PathBuf> {
+        resolve_within(&self.workspace, &self.workspace, path)
     }
 
     /// Read the full contents of a file inside the workspace.
@@ -312,6 +702,100 @@ impl FileSystemTool {
         Self::read_bytes_bounded(&canonical, path)
     }
 
+    /// Hex dump of the first [`MAX_HEX_DUMP_BYTES`] of a workspace file,
+    /// for files `read_file` refuses as binary.
+    pub fn read_file_hex(&self, path: &str) -> Result<String> {
+        let validated_path = self.validate_path(path)?;
+        Self::read_hex_bounded(&validated_path, path)
+    }
+
+    /// [`Self::read_file_hex`] for a path outside the workspace that the
+    /// caller has already canonicalised and cleared for reading.
+    pub fn read_file_hex_with_outside_access(&self, path: &str) -> Result<String> {
+        Self::read_hex_bounded(Path::new(path), path)
+    }
+
+    /// Read only the lines in `window`. Unlike [`Self::read_file`] this
+    /// works on files past the size limit, since only the window is kept.
+    pub fn read_file_window(&self, path: &str, window: LineWindow) -> Result<WindowedRead> {
+        let validated_path = self.validate_path(path)?;
+        Self::read_window(&validated_path, path, window)
+    }
+
+    /// [`Self::read_file_window`] for a path outside the workspace that the
+    /// caller has already canonicalised and cleared for reading.
+    pub fn read_file_window_with_outside_access(
+        &self,
+        path: &str,
+        window: LineWindow,
+    ) -> Result<WindowedRead> {
+        Self::read_window(Path::new(path), path, window)
+    }
+
+    fn read_window(path: &Path, label: &str, window: LineWindow) -> Result<WindowedRead> {
+        if !path.exists() {
+            return Err(SofosError::FileNotFound(label.to_string()));
+        }
+        let size = fs::metadata(path)
+            .with_context(|| format!("Failed to read metadata for: {}", label))?
+            .len();
+        let file =
+            fs::File::open(path).with_context(|| format!("Failed to open file: {}", label))?;
+        let mut reader = BufReader::with_capacity(BINARY_SNIFF_BYTES, file);
+        let sample = reader
+            .fill_buf()
+            .with_context(|| format!("Failed to read file: {}", label))?;
+        if looks_binary(sample) {
+            return Err(binary_file_error(label, sample, size));
+        }
+        let mut read = WindowedRead {
+            content: String::new(),
+            line_count: 0,
+            remaining: 0,
+        };
+        let mut total_lines = 0;
+        let mut line = String::new();
+        loop {
+            line.clear();
+            let bytes = match reader.read_line(&mut line) {
+                Ok(bytes) => bytes,
+                Err(e) if e.kind() == std::io::ErrorKind::InvalidData => {
+                    return Err(binary_file_error(label, &[], size));
+                }
+                Err(e) => {
+                    return Err(e).with_context(|| format!("Failed to read file: {}", label));
+                }
+            };
+            if bytes == 0 {
+                break;
+            }
+            total_lines += 1;
+            if total_lines < window.offset {
+                continue;
+            }
+            if window.limit.is_some_and(|limit| read.line_count >= limit) {
+                read.remaining += 1;
+                continue;
+            }
+            read.content.push_str(&line);
+            read.line_count += 1;
+            if read.content.len() as u64 > MAX_FILE_SIZE {
+                return Err(SofosError::ToolExecution(format!(
+                    "Requested lines of {} exceed {} MB; use a smaller limit",
+                    label,
+                    MAX_FILE_SIZE / (1024 * 1024)
+                )));
+            }
+        }
+        if total_lines > 0 && window.offset > total_lines {
+            return Err(SofosError::ToolExecution(format!(
+                "Offset {} is past the end of {} ({} lines)",
+                window.offset, label, total_lines
+            )));
+        }
+        Ok(read)
+    }
+
     /// Shared size-check + read logic for the two public read methods.
     /// `label` is the caller-facing path string used in error messages.
     fn read_bytes_bounded(path: &Path, label: &str) -> Result<String> {
@@ -330,10 +814,49 @@ impl FileSystemTool {
             )));
         }
 
-        fs::read_to_string(path).with_context(|| format!("Failed to read file: {}", label))
+        let bytes = fs::read(path).with_context(|| format!("Failed to read file: {}", label))?;
+        let sample = &bytes[..bytes.len().min(BINARY_SNIFF_BYTES)];
+        if looks_binary(sample) {
+            return Err(binary_file_error(label, sample, metadata.len()));
+        }
+        String::from_utf8(bytes).map_err(|e| {
+            let bytes = e.as_bytes();
+            binary_file_error(
+                label,
+                &bytes[..bytes.len().min(BINARY_SNIFF_BYTES)],
+                metadata.len(),
+            )
+        })
+    }
+
+    fn read_hex_bounded(path: &Path, label: &str) -> Result<String> {
+        if !path.exists() {
+            return Err(SofosError::FileNotFound(label.to_string()));
+        }
+        let size = fs::metadata(path)
+            .with_context(|| format!("Failed to read metadata for: {}", label))?
+            .len();
+        let mut bytes = Vec::with_capacity(MAX_HEX_DUMP_BYTES);
+        fs::File::open(path)
+            .and_then(|file| file.take(MAX_HEX_DUMP_BYTES as u64).read_to_end(&mut bytes))
+            .with_context(|| format!("Failed to read file: {}", label))?;
+
+        let mut dump = hex_dump(&bytes);
+        if size > bytes.len() as u64 {
+            dump.push_str(&format!(
+                "\n... (first {} of {} bytes shown)",
+                bytes.len(),
+                size
+            ));
+        }
+        Ok(dump)
     }
 
     pub fn write_file(&self, path: &str, content: &str) -> Result<()> {
+        self.write_workspace_file(path, content, true)
+    }
+
+    fn write_workspace_file(&self, path: &str, content: &str, back_up: bool) -> Result<()> {
         let validated_path = self.validate_path(path)?;
 
         if let Some(parent) = validated_path.parent() {
@@ -341,6 +864,9 @@ impl FileSystemTool {
                 .with_context(|| format!("Failed to create parent directories for: {}", path))?;
         }
 
+        if back_up {
+            self.back_up(&validated_path)?;
+        }
         write_atomic(&validated_path, content)
             .with_context(|| format!("Failed to write file: {}", path))
     }
@@ -359,6 +885,7 @@ impl FileSystemTool {
                 .with_context(|| format!("Failed to create parent directories for: {}", path))?;
         }
 
+        self.back_up(&full_path)?;
         write_atomic(&full_path, content).with_context(|| format!("Failed to write file: {}", path))
     }
 
@@ -418,11 +945,15 @@ impl FileSystemTool {
             )));
         }
 
+        let ignored = SofosIgnore::load(&self.root);
         let mut entries = Vec::new();
         for entry in fs::read_dir(&full_path)? {
             let entry = entry?;
             let name = entry.file_name().to_string_lossy().to_string();
             let is_dir = entry.file_type()?.is_dir();
+            if ignored.is_ignored(&entry.path(), is_dir) {
+                continue;
+            }
             entries.push(if is_dir { format!("{}/", name) } else { name });
         }
 
@@ -430,6 +961,71 @@ impl FileSystemTool {
         Ok(entries)
     }
 
+    /// Indented listing of the directory at `path`, `max_depth` levels
+    /// deep, honouring `.gitignore` and `.sofosignore` and skipping
+    /// [`TREE_SKIPPED_DIRS`].
+    /// Stops after [`MAX_TREE_ENTRIES`] entries with a note saying so.
+    pub fn tree(&self, path: &str, max_depth: usize) -> Result<String> {
+        let full_path = self.validate_path(path)?;
+
+        if !full_path.exists() {
+            return Err(SofosError::FileNotFound(path.to_string()));
+        }
+        if !full_path.is_dir() {
+            return Err(SofosError::InvalidPath(format!(
+                "'{}' is not a directory",
+                path
+            )));
+        }
+
+        let is_dir = |entry: &ignore::DirEntry| entry.file_type().is_some_and(|t| t.is_dir());
+        let ignored = SofosIgnore::load(&self.root);
+        let walker = ignore::WalkBuilder::new(&full_path)
+            .max_depth(Some(max_depth))
+            .hidden(false)
+            .require_git(false)
+            .sort_by_file_name(|a, b| a.cmp(b))
+            .filter_entry(move |entry| {
+                let dir = is_dir(entry);
+                let skipped = dir
+                    && TREE_SKIPPED_DIRS.contains(&entry.file_name().to_string_lossy().as_ref());
+                !skipped && !ignored.is_ignored(entry.path(), dir)
+            })
+            .build();
+
+        let mut lines = vec![format!("Tree of '{}':", path)];
+        let (mut dirs, mut files) = (0usize, 0usize);
+        let mut truncated = false;
+        for entry in walker.flatten() {
+            if entry.depth() == 0 {
+                continue;
+            }
+            if dirs + files == MAX_TREE_ENTRIES {
+                truncated = true;
+                break;
+            }
+            let indent = "  ".repeat(entry.depth() - 1);
+            let name = entry.file_name().to_string_lossy();
+            if is_dir(&entry) {
+                dirs += 1;
+                lines.push(format!("{}{}/", indent, name));
+            } else {
+                files += 1;
+                lines.push(format!("{}{}", indent, name));
+            }
+        }
+
+        lines.push(if truncated {
+            format!(
+                "(truncated after {} entries; pass a smaller max_depth or a subdirectory path)",
+                MAX_TREE_ENTRIES
+            )
+        } else {
+            format!("({} directories, {} files)", dirs, files)
+        });
+        Ok(lines.join("\n"))
+    }
+
     pub fn delete_file(&self, path: &str) -> Result<()> {
         let full_path = self.validate_path(path)?;
         Self::remove_file_at(&full_path, path)
@@ -534,6 +1130,16 @@ impl FileSystemTool {
     pub fn workspace(&self) -> &Path {
         &self.workspace
     }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Resolve paths against `dir` instead of the project root. `dir`
+    /// must be canonical and inside the root.
+    pub fn set_scope(&mut self, dir: PathBuf) {
+        self.workspace = dir;
+    }
 }
 
 #[cfg(test)]
@@ -541,6 +1147,164 @@ mod tests {
     use super::*;
     use crate::tools::test_support;
 
+    #[test]
+    fn binary_files_are_refused_with_type_and_size() {
+        let (_temp, path) = test_support::workspace();
+        let mut png = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR".to_vec();
+        png.resize(12 * 1024, 0);
+        fs::write(path.join("foo.png"), &png).unwrap();
+        fs::write(path.join("blob.bin"), [0xff, 0xfe, b'a']).unwrap();
+        let fs_tool = FileSystemTool::new(path).unwrap();
+
+        let err = fs_tool.read_file("foo.png").unwrap_err().to_string();
+        assert!(
+            err.contains("'foo.png' appears to be a binary file (image/png, 12KB)"),
+            "{err}"
+        );
+        let err = fs_tool
+            .read_file_window(
+                "blob.bin",
+                LineWindow {
+                    offset: 1,
+                    limit: None,
+                },
+            )
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("(unknown type, 3 bytes)"), "{err}");
+        assert!(err.contains("as_hex"), "{err}");
+    }
+
+    #[test]
+    fn file_info_counts_lines_and_tokens_without_the_content() {
+        let (_temp, path) = test_support::workspace();
+        fs::write(path.join("lib.rs"), "fn main() {}\n\nfn helper() {}").unwrap();
+        fs::write(path.join("foo.png"), b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR").unwrap();
+
+        let info = file_info(&path.join("lib.rs"), "lib.rs").unwrap();
+        assert_eq!(
+            info,
+            "File: lib.rs\nSize: 28 bytes\nLines: 3\nEstimated tokens: ~8\nLanguage: Rust"
+        );
+        let info = file_info(&path.join("foo.png"), "foo.png").unwrap();
+        assert!(info.contains("Type: binary (image/png)"), "{info}");
+        assert!(!info.contains("Lines"), "{info}");
+        let err = file_info(&path, ".").unwrap_err().to_string();
+        assert!(err.contains("is a directory"), "{err}");
+    }
+
+    #[test]
+    fn hex_dump_is_bounded_and_shows_ascii() {
+        let (_temp, path) = test_support::workspace();
+        let mut bytes = b"Hello,\0world!\n\xff\x01".to_vec();
+        bytes.resize(MAX_HEX_DUMP_BYTES + 100, b'x');
+        fs::write(path.join("data.bin"), &bytes).unwrap();
+        let fs_tool = FileSystemTool::new(path).unwrap();
+
+        let dump = fs_tool.read_file_hex("data.bin").unwrap();
+        let mut lines = dump.lines();
+        assert_eq!(
+            lines.next(),
+            Some("00000000  48 65 6c 6c 6f 2c 00 77 6f 72 6c 64 21 0a ff 01  |Hello,.world!...|")
+        );
+        assert_eq!(
+            dump.lines().count(),
+            MAX_HEX_DUMP_BYTES / HEX_DUMP_ROW_BYTES + 1
+        );
+        assert!(dump.ends_with(&format!(
+            "... (first {} of {} bytes shown)",
+            MAX_HEX_DUMP_BYTES,
+            MAX_HEX_DUMP_BYTES + 100
+        )));
+    }
+
+    #[test]
+    fn read_file_window_returns_the_requested_lines() {
+        let (_temp, path) = test_support::workspace();
+        fs::write(path.join("log.txt"), "one\ntwo\nthree\nfour\nfive").unwrap();
+        let fs_tool = FileSystemTool::new(path).unwrap();
+        let window = |offset, limit| LineWindow { offset, limit };
+
+        let read = fs_tool
+            .read_file_window("log.txt", window(2, Some(2)))
+            .unwrap();
+        assert_eq!(
+            read,
+            WindowedRead {
+                content: "two\nthree\n".to_string(),
+                line_count: 2,
+                remaining: 2,
+            }
+        );
+
+        let tail = fs_tool
+            .read_file_window("log.txt", window(4, None))
+            .unwrap();
+        assert_eq!(tail.content, "four\nfive");
+        assert_eq!(tail.remaining, 0);
+
+        assert!(
+            fs_tool
+                .read_file_window("log.txt", window(6, None))
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn tree_indents_by_depth_and_skips_ignored_entries() {
+        let (_temp, path) = test_support::workspace();
+        fs::create_dir_all(path.join("src/api/wire")).unwrap();
+        fs::create_dir_all(path.join("target/debug")).unwrap();
+        fs::write(path.join(".gitignore"), "*.log\n").unwrap();
+        fs::write(path.join("src/main.rs"), "").unwrap();
+        fs::write(path.join("src/api/mod.rs"), "").unwrap();
+        fs::write(path.join("src/api/wire/body.rs"), "").unwrap();
+        fs::write(path.join("debug.log"), "").unwrap();
+        let fs_tool = FileSystemTool::new(path).unwrap();
+
+        let tree = fs_tool.tree(".", 3).unwrap();
+
+        assert_eq!(
+            tree,
+            "Tree of '.':\n.gitignore\nsrc/\n  api/\n    mod.rs\n    wire/\n  main.rs\n(3 directories, 3 files)"
+        );
+    }
+
+    #[test]
+    fn listings_leave_out_sofosignore_matches() {
+        let (_temp, path) = test_support::workspace();
+        fs::create_dir_all(path.join("secrets")).unwrap();
+        fs::write(path.join("secrets/key.txt"), "").unwrap();
+        fs::write(path.join("server.pem"), "").unwrap();
+        fs::write(path.join("main.rs"), "").unwrap();
+        fs::write(path.join(".sofosignore"), "secrets/\n*.pem\n").unwrap();
+        let fs_tool = FileSystemTool::new(path).unwrap();
+
+        assert_eq!(
+            fs_tool.list_directory(".").unwrap(),
+            vec![".sofosignore".to_string(), "main.rs".to_string()]
+        );
+        assert_eq!(
+            fs_tool.tree(".", 2).unwrap(),
+            "Tree of '.':\n.sofosignore\nmain.rs\n(0 directories, 2 files)"
+        );
+    }
+
+    #[test]
+    fn tree_stops_at_the_entry_cap() {
+        let (_temp, path) = test_support::workspace();
+        for i in 0..=MAX_TREE_ENTRIES {
+            fs::write(path.join(format!("f{:04}.txt", i)), "").unwrap();
+        }
+        let fs_tool = FileSystemTool::new(path).unwrap();
+
+        let tree = fs_tool.tree(".", DEFAULT_TREE_DEPTH).unwrap();
+
+        assert_eq!(tree.lines().count(), MAX_TREE_ENTRIES + 2);
+        assert!(tree.ends_with("pass a smaller max_depth or a subdirectory path)"));
+        assert!(fs_tool.tree("f0000.txt", 1).is_err());
+    }
+
     #[test]
     fn test_path_validation_rejects_parent_traversal() {
         let (_temp, path) = test_support::workspace();
@@ -564,6 +1328,79 @@ mod tests {
         assert!(fs_tool.validate_path("foo..bar/baz..qux.txt").is_ok());
     }
 
+    #[test]
+    fn backups_keep_the_prior_content_before_an_overwrite() {
+        let (_temp, path) = test_support::workspace();
+        let mut fs_tool = FileSystemTool::new(path.clone()).unwrap();
+        fs_tool.write_file("src/doc.md", "first draft").unwrap();
+        assert!(!path.join(BACKUPS_DIR).exists(), "off by default");
+
+        fs_tool.set_backups(true);
+        fs_tool.write_file("src/new.md", "fresh").unwrap();
+        fs_tool.write_file("src/doc.md", "second pass").unwrap();
+
+        let backups: Vec<_> = std::fs::read_dir(path.join(BACKUPS_DIR).join("src"))
+            .unwrap()
+            .filter_map(|e| e.ok())
+            .collect();
+        assert_eq!(backups.len(), 1, "only the overwritten file is backed up");
+        let name = backups[0].file_name().to_string_lossy().into_owned();
+        assert!(name.starts_with("doc.md."), "{name}");
+        assert_eq!(
+            std::fs::read_to_string(backups[0].path()).unwrap(),
+            "first draft"
+        );
+        assert_eq!(fs_tool.read_file("src/doc.md").unwrap(), "second pass");
+    }
+
+    #[test]
+    fn files_hidden_from_reads_are_not_backed_up() {
+        let (_temp, path) = test_support::workspace();
+        std::fs::create_dir_all(path.join(".sofos")).unwrap();
+        std::fs::write(
+            path.join(".sofos").join("config.local.toml"),
+            "[permissions]\ndeny = [\"Read(./*.pem)\"]\n",
+        )
+        .unwrap();
+        std::fs::write(
+            path.join(crate::tools::sofosignore::SOFOSIGNORE_FILE),
+            ".env\n",
+        )
+        .unwrap();
+        let mut fs_tool = FileSystemTool::new(path.clone()).unwrap();
+        fs_tool.write_file(".env", "KEY=1").unwrap();
+        fs_tool.write_file("key.pem", "secret").unwrap();
+        fs_tool.set_backups(true);
+        fs_tool.write_file(".env", "KEY=2").unwrap();
+        fs_tool.write_file("key.pem", "rotated").unwrap();
+
+        assert!(!path.join(BACKUPS_DIR).exists());
+    }
+
+    #[test]
+    fn backup_originals_compare_the_first_backup_with_the_file_now() {
+        let (_temp, path) = test_support::workspace();
+        let mut fs_tool = FileSystemTool::new(path.clone()).unwrap();
+        fs_tool.write_file("src/doc.md", "first draft").unwrap();
+        fs_tool.write_file("same.txt", "unchanged").unwrap();
+        fs_tool.set_backups(true);
+        fs_tool.write_file("src/doc.md", "second pass").unwrap();
+        fs_tool.write_file("src/doc.md", "third pass").unwrap();
+        fs_tool.write_file("same.txt", "unchanged").unwrap();
+
+        assert_eq!(
+            fs_tool.backup_originals(),
+            vec![FileVersions {
+                path: Path::new("src")
+                    .join("doc.md")
+                    .to_string_lossy()
+                    .into_owned(),
+                original: "first draft".to_string(),
+                current: "third pass".to_string(),
+            }]
+        );
+    }
+
     #[test]
     fn write_atomic_replaces_existing_content_durably() {
         // Smoke test: write_atomic must produce a readable file with the
@@ -0,0 +1,307 @@
+//! `git_status`: the working tree's staged, unstaged, untracked, and
+//! conflicted files, plus diff stats, read straight from git's porcelain
+//! output so the model never has to parse `git status` text itself.
+
+use crate::error::{Result, SofosError};
+use crate::tools::changes::FileVersions;
+use crate::tools::utils::{MAX_TOOL_OUTPUT_TOKENS, TruncationKind, truncate_for_context};
+use std::path::Path;
+use std::process::Command;
+
+/// Settings that keep a repository's own config from running programs
+/// during a read: an fsmonitor hook runs on every `git status`.
+const SAFE_CONFIG: &[&str] = &["core.fsmonitor=false"];
+
+/// One changed path from `git status --porcelain=v1`.
+#[derive(Debug, PartialEq, Eq)]
+struct Entry {
+    /// Index (staged) and worktree (unstaged) status letters.
+    index: char,
+    worktree: char,
+    path: String,
+    /// Original path of a rename or copy.
+    from: Option<String>,
+}
+
+impl Entry {
+    fn is_untracked(&self) -> bool {
+        self.index == '?'
+    }
+
+    fn is_conflicted(&self) -> bool {
+        self.index == 'U'
+            || self.worktree == 'U'
+            || (self.index == 'A' && self.worktree == 'A')
+            || (self.index == 'D' && self.worktree == 'D')
+    }
+
+    fn describe(&self, code: char) -> String {
+        let change = match code {
+            'M' => "modified",
+            'A' => "added",
+            'D' => "deleted",
+            'R' => "renamed",
+            'C' => "copied",
+            'T' => "type changed",
+            _ => "changed",
+        };
+        match &self.from {
+            Some(from) => format!("{}: {} -> {}", change, from, self.path),
+            None => format!("{}: {}", change, self.path),
+        }
+    }
+}
+
+/// Whether `dir` is inside a git work tree, or the message to show when
+/// it is not.
+fn check_work_tree(dir: &Path) -> std::result::Result<(), String> {
+    let inside = git(dir, &["rev-parse", "--is-inside-work-tree"]);
+    if !matches!(&inside, Ok(out) if out.trim() == "true") {
+        return Err(match inside {
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                "git is not installed, so the repository status is unavailable.".to_string()
+            }
+            _ => "Not a git repository: there is no git status to report.".to_string(),
+        });
+    }
+    Ok(())
+}
+
+/// Summarise the repository state under `dir`. A directory outside any
+/// git repository, or a machine without git, gives a plain message
+/// rather than an error.
+pub fn status(dir: &Path) -> Result<String> {
+    if let Err(message) = check_work_tree(dir) {
+        return Ok(message);
+    }
+
+    let porcelain = run(
+        dir,
+        &[
+            "status",
+            "--porcelain=v1",
+            "-z",
+            "--branch",
+            "--untracked-files=all",
+            "--",
+            ".",
+        ],
+    )?;
+    let (branch, entries) = parse_porcelain(&porcelain);
+    let staged_stat = run(
+        dir,
+        &["diff", "--cached", "--stat", "--no-ext-diff", "--", "."],
+    )?;
+    let unstaged_stat = run(dir, &["diff", "--stat", "--no-ext-diff", "--", "."])?;
+
+    let summary = render(
+        branch.as_deref(),
+        &entries,
+        staged_stat.trim_end(),
+        unstaged_stat.trim_end(),
+    );
+    Ok(truncate_for_context(
+        &summary,
+        MAX_TOOL_OUTPUT_TOKENS,
+        TruncationKind::PathList,
+    ))
+}
+
+/// Every uncommitted text change under `dir`, each file's `HEAD` content
+/// against its working-tree content, or `None` outside a git repository.
+/// Binary and unreadable files are left out.
+pub fn uncommitted_changes(dir: &Path) -> Result<Option<Vec<FileVersions>>> {
+    if check_work_tree(dir).is_err() {
+        return Ok(None);
+    }
+    let top = run(dir, &["rev-parse", "--show-toplevel"])?;
+    let top = Path::new(top.trim_end());
+    let porcelain = run(
+        dir,
+        &[
+            "status",
+            "--porcelain=v1",
+            "-z",
+            "--untracked-files=all",
+            "--",
+            ".",
+        ],
+    )?;
+    let (_, entries) = parse_porcelain(&porcelain);
+
+    let mut changes = Vec::new();
+    for entry in entries {
+        // Porcelain paths, and `HEAD:<path>`, are relative to the top level.
+        let original = if entry.is_untracked() || entry.index == 'A' {
+            String::new()
+        } else {
+            let head_path = entry.from.as_deref().unwrap_or(&entry.path);
+            git(dir, &["cat-file", "blob", &format!("HEAD:{}", head_path)]).unwrap_or_default()
+        };
+        let current = match std::fs::read(top.join(&entry.path)) {
+            Ok(bytes) => match String::from_utf8(bytes) {
+                Ok(text) => text,
+                Err(_) => continue,
+            },
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+            Err(_) => continue,
+        };
+        if original.contains('\0') || original == current {
+            continue;
+        }
+        changes.push(FileVersions {
+            path: entry.path,
+            original,
+            current,
+        });
+    }
+    Ok(Some(changes))
+}
+
+fn git(dir: &Path, args: &[&str]) -> std::io::Result<String> {
+    let mut cmd = Command::new("git");
+    for setting in SAFE_CONFIG {
+        cmd.arg("-c").arg(setting);
+    }
+    // Reads must not take the index lock or refresh the index on disk.
+    cmd.arg("--no-optional-locks").args(args).current_dir(dir);
+    crate::tools::child_env::scrub_sensitive_env(&mut cmd);
+    let output = cmd.output()?;
+    if !output.status.success() {
+        return Err(std::io::Error::other(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+fn run(dir: &Path, args: &[&str]) -> Result<String> {
+    git(dir, args)
+        .map_err(|e| SofosError::ToolExecution(format!("git {} failed: {}", args.join(" "), e)))
+}
+
+/// Split `git status --porcelain=v1 -z --branch` output into the branch
+/// line and the changed paths. With `-z` a rename or copy is followed by
+/// a separate field holding its original path.
+fn parse_porcelain(output: &str) -> (Option<String>, Vec<Entry>) {
+    let mut branch = None;
+    let mut entries = Vec::new();
+    let mut fields = output.split('\0').filter(|field| !field.is_empty());
+    while let Some(field) = fields.next() {
+        if let Some(name) = field.strip_prefix("## ") {
+            branch = Some(name.to_string());
+            continue;
+        }
+        let mut chars = field.chars();
+        let (Some(index), Some(worktree)) = (chars.next(), chars.next()) else {
+            continue;
+        };
+        let Some(path) = field.get(3..) else {
+            continue;
+        };
+        let from = if matches!(index, 'R' | 'C') || matches!(worktree, 'R' | 'C') {
+            fields.next().map(str::to_string)
+        } else {
+            None
+        };
+        entries.push(Entry {
+            index,
+            worktree,
+            path: path.to_string(),
+            from,
+        });
+    }
+    (branch, entries)
+}
+
+fn render(branch: Option<&str>, entries: &[Entry], staged: &str, unstaged: &str) -> String {
+    let mut staged_lines = Vec::new();
+    let mut unstaged_lines = Vec::new();
+    let mut untracked = Vec::new();
+    let mut conflicted = Vec::new();
+    for entry in entries {
+        if entry.is_untracked() {
+            untracked.push(entry.path.clone());
+        } else if entry.is_conflicted() {
+            conflicted.push(entry.path.clone());
+        } else {
+            if entry.index != ' ' {
+                staged_lines.push(entry.describe(entry.index));
+            }
+            if entry.worktree != ' ' {
+                unstaged_lines.push(entry.describe(entry.worktree));
+            }
+        }
+    }
+
+    let mut out = format!("Branch: {}\n", branch.unwrap_or("(unknown)"));
+    if entries.is_empty() {
+        out.push_str("\nWorking tree clean.\n");
+        return out;
+    }
+    for (title, lines) in [
+        ("Staged", &staged_lines),
+        ("Unstaged", &unstaged_lines),
+        ("Untracked", &untracked),
+        ("Conflicted", &conflicted),
+    ] {
+        if lines.is_empty() {
+            continue;
+        }
+        out.push_str(&format!("\n{} ({}):\n", title, lines.len()));
+        for line in lines {
+            out.push_str(&format!("  {}\n", line));
+        }
+    }
+    for (title, stat) in [("Staged diff", staged), ("Unstaged diff", unstaged)] {
+        if !stat.is_empty() {
+            out.push_str(&format!("\n{}:\n{}\n", title, stat));
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn porcelain_is_grouped_into_staged_unstaged_untracked_and_conflicted() {
+        let output = "## main...origin/main [ahead 1]\0M  src/lib.rs\0 M README.md\0MM src/main.rs\0R  new.rs\0old.rs\0?? notes.txt\0UU merge.rs\0";
+
+        let (branch, entries) = parse_porcelain(output);
+        assert_eq!(branch.as_deref(), Some("main...origin/main [ahead 1]"));
+        assert_eq!(entries.len(), 6);
+        assert_eq!(entries[3].from.as_deref(), Some("old.rs"));
+
+        let summary = render(branch.as_deref(), &entries, " src/lib.rs | 2 +-", "");
+        assert!(summary.starts_with("Branch: main...origin/main [ahead 1]\n"));
+        assert!(summary.contains(
+            "Staged (3):\n  modified: src/lib.rs\n  modified: src/main.rs\n  renamed: old.rs -> new.rs\n"
+        ));
+        assert!(
+            summary.contains("Unstaged (2):\n  modified: README.md\n  modified: src/main.rs\n")
+        );
+        assert!(summary.contains("Untracked (1):\n  notes.txt\n"));
+        assert!(summary.contains("Conflicted (1):\n  merge.rs\n"));
+        assert!(summary.contains("Staged diff:\n src/lib.rs | 2 +-\n"));
+        assert!(!summary.contains("Unstaged diff"));
+    }
+
+    #[test]
+    fn a_clean_tree_says_so() {
+        let (branch, entries) = parse_porcelain("## main\0");
+        let summary = render(branch.as_deref(), &entries, "", "");
+        assert_eq!(summary, "Branch: main\n\nWorking tree clean.\n");
+    }
+
+    #[test]
+    fn a_directory_outside_git_is_reported_not_failed() {
+        let dir = tempfile::tempdir().unwrap();
+        let summary = status(dir.path()).unwrap();
+        assert!(
+            summary.starts_with("Not a git repository") || summary.starts_with("git is not"),
+            "{summary}"
+        );
+    }
+}
@@ -13,10 +13,24 @@ use std::sync::{Arc, Mutex};
 pub const MAX_IMAGE_SIZE_MB: u64 = 20;
 pub const MAX_IMAGE_SIZE_BYTES: u64 = MAX_IMAGE_SIZE_MB * 1024 * 1024;
 
-/// Long-side pixel bound used when resizing an image before sending it
-/// to the model. Larger images get scaled proportionally to fit.
+/// Default long-side pixel bound used when resizing an image before
+/// sending it to the model. Larger images get scaled proportionally to
+/// fit. `[images] max_dimension` overrides it.
 pub const MAX_PROMPT_IMAGE_DIMENSION: u32 = 2048;
 
+/// Largest base64 payload sent for one image, matching the smallest
+/// per-image limit among the providers (Anthropic's 5 MB). A bounded
+/// image still over it is scaled down further until it fits.
+pub const MAX_PROMPT_IMAGE_BASE64_BYTES: usize = 5 * 1024 * 1024;
+
+/// Factor each extra downscale shrinks the long side by while an image
+/// is over [`MAX_PROMPT_IMAGE_BASE64_BYTES`].
+const SIZE_REDUCTION_STEP: f64 = 0.75;
+
+/// Long side below which an oversized image is no longer shrunk; it is
+/// sent as is and the provider decides.
+const MIN_SHRINK_DIMENSION: u32 = 256;
+
 /// JPEG quality used when re-encoding a resized image.
 const JPEG_QUALITY: u8 = 85;
 
@@ -36,43 +50,75 @@ pub struct EncodedImage {
     pub mime: String,
 }
 
-/// Decode, optionally resize within `MAX_PROMPT_IMAGE_DIMENSION`, and
-/// return bytes ready for the model. Small images in a supported format
-/// pass through unchanged.
-pub fn encode_image_for_prompt(bytes: Vec<u8>) -> Result<EncodedImage> {
-    let detected = image::guess_format(&bytes).ok();
+/// Decode, resize to fit within `max_dimension` on the long side, and
+/// return bytes ready for the model. An image that still encodes past
+/// [`MAX_PROMPT_IMAGE_BASE64_BYTES`] is scaled down further. `None`
+/// never resizes. Images within both bounds in a supported format pass
+/// through unchanged.
+pub fn encode_image_for_prompt(bytes: Vec<u8>, max_dimension: Option<u32>) -> Result<EncodedImage> {
+    encode_image_within(bytes, max_dimension, MAX_PROMPT_IMAGE_BASE64_BYTES)
+}
+
+fn encode_image_within(
+    bytes: Vec<u8>,
+    max_dimension: Option<u32>,
+    max_base64_bytes: usize,
+) -> Result<EncodedImage> {
+    // The format comes from the header bytes, never the file name, so a
+    // mislabelled or extensionless image still gets its true MIME type.
+    let detected = image::guess_format(&bytes)
+        .ok()
+        .filter(|f| is_supported_format(*f))
+        .ok_or_else(|| {
+            SofosError::ToolExecution(format!(
+                "Not a supported image: the file content is not {SUPPORTED_FORMATS_HUMAN_LIST}, \
+                 whatever its extension."
+            ))
+        })?;
 
     let decoded = decode_with_orientation(&bytes)?;
     let (width, height) = decoded.dimensions();
 
-    let within_bound = width <= MAX_PROMPT_IMAGE_DIMENSION && height <= MAX_PROMPT_IMAGE_DIMENSION;
-    let passthrough_format = detected.filter(|f| is_passthrough_format(*f));
+    let passthrough_format = Some(detected).filter(|f| is_passthrough_format(*f));
+    let fits = |len: usize| max_dimension.is_none() || base64_len(len) <= max_base64_bytes;
 
-    if within_bound {
+    if max_dimension.is_none_or(|bound| width <= bound && height <= bound) {
         if let Some(format) = passthrough_format {
+            if fits(bytes.len()) {
+                return Ok(EncodedImage {
+                    bytes,
+                    mime: mime_for_image_format(format).to_string(),
+                });
+            }
+        } else {
+            let (encoded_bytes, format) = encode_image_to_bytes(&decoded, ImageCrateFormat::Png)?;
+            if fits(encoded_bytes.len()) {
+                return Ok(EncodedImage {
+                    bytes: encoded_bytes,
+                    mime: mime_for_image_format(format).to_string(),
+                });
+            }
+        }
+    }
+
+    let long_side = width.max(height);
+    let mut bound = max_dimension.map_or(long_side, |bound| bound.min(long_side));
+    let target = passthrough_format.unwrap_or(ImageCrateFormat::Png);
+    loop {
+        let resized = decoded.resize(bound, bound, image::imageops::FilterType::Triangle);
+        let (encoded_bytes, format) = encode_image_to_bytes(&resized, target)?;
+        if fits(encoded_bytes.len()) || bound <= MIN_SHRINK_DIMENSION {
             return Ok(EncodedImage {
-                bytes,
+                bytes: encoded_bytes,
                 mime: mime_for_image_format(format).to_string(),
             });
         }
-        let (encoded_bytes, format) = encode_image_to_bytes(&decoded, ImageCrateFormat::Png)?;
-        return Ok(EncodedImage {
-            bytes: encoded_bytes,
-            mime: mime_for_image_format(format).to_string(),
-        });
+        bound = ((f64::from(bound) * SIZE_REDUCTION_STEP) as u32).max(MIN_SHRINK_DIMENSION);
     }
+}
 
-    let resized = decoded.resize(
-        MAX_PROMPT_IMAGE_DIMENSION,
-        MAX_PROMPT_IMAGE_DIMENSION,
-        image::imageops::FilterType::Triangle,
-    );
-    let target = passthrough_format.unwrap_or(ImageCrateFormat::Png);
-    let (encoded_bytes, format) = encode_image_to_bytes(&resized, target)?;
-    Ok(EncodedImage {
-        bytes: encoded_bytes,
-        mime: mime_for_image_format(format).to_string(),
-    })
+fn base64_len(len: usize) -> usize {
+    len.div_ceil(3) * 4
 }
 
 /// Decode image bytes, applying any EXIF orientation. A resize +
@@ -95,6 +141,10 @@ fn decode_with_orientation(bytes: &[u8]) -> Result<DynamicImage> {
     Ok(decoded)
 }
 
+fn is_supported_format(format: ImageCrateFormat) -> bool {
+    is_passthrough_format(format) || format == ImageCrateFormat::Gif
+}
+
 fn is_passthrough_format(format: ImageCrateFormat) -> bool {
     matches!(
         format,
@@ -197,11 +247,19 @@ impl ImageLoader {
         self.read_path_session_denied = denied;
     }
 
-    pub fn load_local_image(&self, path: &str) -> Result<ImageSource> {
+    /// Load a local image. A relative `path` resolves against `base`, the
+    /// workspace or the subdirectory selected with `/scope`. An image
+    /// longer than `max_dimension` on either side is downscaled to fit.
+    pub fn load_local_image(
+        &self,
+        path: &str,
+        base: &Path,
+        max_dimension: Option<u32>,
+    ) -> Result<ImageSource> {
         let full_path = if is_absolute_or_tilde(path) {
             PathBuf::from(PermissionManager::expand_tilde_pub(path))
         } else {
-            self.workspace.join(path)
+            base.join(path)
         };
 
         let canonical = std::fs::canonicalize(&full_path)
@@ -270,7 +328,17 @@ impl ImageLoader {
         let raw_bytes = std::fs::read(&canonical)
             .with_context(|| format!("Failed to read image file: {}", path))?;
 
-        let encoded = encode_image_for_prompt(raw_bytes)?;
+        let original_len = raw_bytes.len();
+        let encoded = encode_image_for_prompt(raw_bytes, max_dimension)?;
+        if encoded.bytes.len() != original_len {
+            tracing::debug!(
+                path,
+                before_bytes = original_len,
+                after_bytes = encoded.bytes.len(),
+                mime = %encoded.mime,
+                "re-encoded image before upload"
+            );
+        }
         let base64_data = STANDARD.encode(&encoded.bytes);
 
         Ok(ImageSource::Base64 {
@@ -330,7 +398,8 @@ mod tests {
     #[test]
     fn passthroughs_small_png() {
         let bytes = png_bytes(64, 32, [10, 20, 30, 255]);
-        let encoded = encode_image_for_prompt(bytes.clone()).expect("encode");
+        let encoded = encode_image_for_prompt(bytes.clone(), Some(MAX_PROMPT_IMAGE_DIMENSION))
+            .expect("encode");
         assert_eq!(encoded.mime, "image/png");
         assert_eq!(
             encoded.bytes, bytes,
@@ -341,7 +410,8 @@ mod tests {
     #[test]
     fn resizes_wide_image_to_bound() {
         let bytes = png_bytes(4096, 2048, [200, 10, 10, 255]);
-        let encoded = encode_image_for_prompt(bytes).expect("encode");
+        let encoded =
+            encode_image_for_prompt(bytes, Some(MAX_PROMPT_IMAGE_DIMENSION)).expect("encode");
         let decoded = image::load_from_memory(&encoded.bytes).expect("decode resized");
         let (w, h) = decoded.dimensions();
         assert!(w <= MAX_PROMPT_IMAGE_DIMENSION && h <= MAX_PROMPT_IMAGE_DIMENSION);
@@ -351,7 +421,8 @@ mod tests {
     #[test]
     fn resizes_tall_image_proportionally() {
         let bytes = png_bytes(1024, 4096, [50, 60, 70, 255]);
-        let encoded = encode_image_for_prompt(bytes).expect("encode");
+        let encoded =
+            encode_image_for_prompt(bytes, Some(MAX_PROMPT_IMAGE_DIMENSION)).expect("encode");
         let decoded = image::load_from_memory(&encoded.bytes).expect("decode resized");
         assert_eq!(decoded.dimensions(), (512, MAX_PROMPT_IMAGE_DIMENSION));
     }
@@ -359,7 +430,8 @@ mod tests {
     #[test]
     fn reencodes_small_gif_as_png() {
         let bytes = encode_fixture(32, 32, [100, 150, 200, 255], ImageCrateFormat::Gif);
-        let encoded = encode_image_for_prompt(bytes).expect("encode");
+        let encoded =
+            encode_image_for_prompt(bytes, Some(MAX_PROMPT_IMAGE_DIMENSION)).expect("encode");
         assert_eq!(
             encoded.mime, "image/png",
             "GIF input should be re-encoded as PNG to avoid the animated-GIF case"
@@ -371,7 +443,8 @@ mod tests {
     #[test]
     fn keeps_jpeg_format_after_resize() {
         let bytes = encode_fixture(4096, 2048, [200, 50, 50, 255], ImageCrateFormat::Jpeg);
-        let encoded = encode_image_for_prompt(bytes).expect("encode");
+        let encoded =
+            encode_image_for_prompt(bytes, Some(MAX_PROMPT_IMAGE_DIMENSION)).expect("encode");
         assert_eq!(
             encoded.mime, "image/jpeg",
             "JPEG source should stay JPEG after resize"
@@ -382,10 +455,87 @@ mod tests {
         assert_eq!((w, h), (MAX_PROMPT_IMAGE_DIMENSION, 1024));
     }
 
+    #[test]
+    fn custom_bound_and_no_bound() {
+        let bytes = png_bytes(3136, 1000, [10, 20, 30, 255]);
+
+        let encoded = encode_image_for_prompt(bytes.clone(), Some(1568)).expect("encode");
+        let decoded = image::load_from_memory(&encoded.bytes).expect("decode resized");
+        assert_eq!(decoded.dimensions(), (1568, 500));
+
+        let encoded = encode_image_for_prompt(bytes.clone(), None).expect("encode");
+        assert_eq!(
+            encoded.bytes, bytes,
+            "no bound should leave the image as is"
+        );
+    }
+
+    /// Deterministic noise, which PNG cannot compress, so the fixture
+    /// is large without being large in pixels.
+    fn noise_png(width: u32, height: u32) -> Vec<u8> {
+        let mut state: u32 = 0x9e37_79b9;
+        let image = image::RgbImage::from_fn(width, height, |_, _| {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            let [r, g, b, _] = state.to_le_bytes();
+            image::Rgb([r, g, b])
+        });
+        let mut cursor = Cursor::new(Vec::new());
+        DynamicImage::ImageRgb8(image)
+            .write_to(&mut cursor, ImageCrateFormat::Png)
+            .expect("encode fixture");
+        cursor.into_inner()
+    }
+
+    #[test]
+    fn shrinks_image_over_the_size_limit_even_within_the_bound() {
+        const LIMIT: usize = 400 * 1024;
+        let bytes = noise_png(512, 512);
+        assert!(base64_len(bytes.len()) > LIMIT, "fixture must be oversized");
+
+        let encoded = encode_image_within(bytes.clone(), Some(MAX_PROMPT_IMAGE_DIMENSION), LIMIT)
+            .expect("encode");
+        assert_eq!(encoded.mime, "image/png");
+        assert!(base64_len(encoded.bytes.len()) <= LIMIT);
+        let decoded = image::load_from_memory(&encoded.bytes).expect("decode resized");
+        assert!(decoded.width() < 512);
+
+        let encoded = encode_image_within(bytes.clone(), None, LIMIT).expect("encode");
+        assert_eq!(encoded.bytes, bytes, "no bound skips the size limit too");
+    }
+
+    #[test]
+    fn local_images_are_typed_by_content_not_extension() {
+        let (_temp, workspace) = crate::tools::test_support::workspace();
+        let png = png_bytes(8, 8, [1, 2, 3, 255]);
+        let jpeg = encode_fixture(8, 8, [1, 2, 3, 255], ImageCrateFormat::Jpeg);
+        std::fs::write(workspace.join("photo.img"), &png).unwrap();
+        std::fs::write(workspace.join("download"), &png).unwrap();
+        std::fs::write(workspace.join("mislabelled.png"), &jpeg).unwrap();
+        std::fs::write(workspace.join("notes.png"), "just text").unwrap();
+        let loader = ImageLoader::new(workspace.clone()).unwrap();
+
+        let media_type = |path: &str| match loader.load_local_image(path, &workspace, None) {
+            Ok(ImageSource::Base64 { media_type, .. }) => media_type,
+            other => panic!("expected a local image for {path}, got {other:?}"),
+        };
+        assert_eq!(media_type("photo.img"), "image/png");
+        assert_eq!(media_type("download"), "image/png");
+        assert_eq!(media_type("mislabelled.png"), "image/jpeg");
+
+        let err = loader
+            .load_local_image("notes.png", &workspace, None)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("Not a supported image"), "{err}");
+    }
+
     #[test]
     fn rejects_non_image_bytes() {
-        let err = encode_image_for_prompt(b"not an image".to_vec())
-            .expect_err("non-image bytes must error");
+        let err =
+            encode_image_for_prompt(b"not an image".to_vec(), Some(MAX_PROMPT_IMAGE_DIMENSION))
+                .expect_err("non-image bytes must error");
         let msg = format!("{err}");
         assert!(
             msg.to_lowercase().contains("decode") || msg.to_lowercase().contains("supported"),
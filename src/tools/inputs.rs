@@ -0,0 +1,133 @@
+//! Named inputs supplied at launch with `--input <name>=<file-or->`.
+//! The model reads them by name through `read_input`, so external data
+//! reaches a sandboxed session without widening the path rules.
+
+use crate::error::{Result, SofosError};
+use std::collections::BTreeMap;
+use std::io::Read;
+
+/// Input name → content.
+pub type NamedInputs = BTreeMap<String, String>;
+
+/// The source that reads standard input instead of a file.
+pub const STDIN_SOURCE: &str = "-";
+
+/// Same cap `read_file` applies to a single file.
+const MAX_INPUT_BYTES: u64 = crate::tools::filesystem::MAX_FILE_SIZE;
+
+fn is_valid_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+}
+
+/// Split `name=source` and check the name.
+fn parse_spec(spec: &str) -> Result<(&str, &str)> {
+    let (name, source) = spec.split_once('=').ok_or_else(|| {
+        SofosError::Config(format!(
+            "invalid --input '{}'; expected <name>=<file> or <name>=-",
+            spec
+        ))
+    })?;
+    if !is_valid_name(name) {
+        return Err(SofosError::Config(format!(
+            "invalid --input name '{}'; use letters, digits, '-', '_' or '.'",
+            name
+        )));
+    }
+    if source.is_empty() {
+        return Err(SofosError::Config(format!(
+            "--input '{}' has no file; use <name>=<file> or <name>=-",
+            name
+        )));
+    }
+    Ok((name, source))
+}
+
+pub(crate) fn read_capped(reader: impl Read, name: &str) -> Result<String> {
+    let mut bytes = Vec::new();
+    reader.take(MAX_INPUT_BYTES + 1).read_to_end(&mut bytes)?;
+    if bytes.len() as u64 > MAX_INPUT_BYTES {
+        return Err(SofosError::Config(format!(
+            "input '{}' is larger than {} MB",
+            name,
+            MAX_INPUT_BYTES / (1024 * 1024)
+        )));
+    }
+    String::from_utf8(bytes)
+        .map_err(|_| SofosError::Config(format!("input '{}' is not UTF-8 text", name)))
+}
+
+/// Whether any `--input` spec reads standard input.
+pub fn reads_stdin(specs: &[String]) -> bool {
+    specs
+        .iter()
+        .any(|spec| parse_spec(spec).is_ok_and(|(_, source)| source == STDIN_SOURCE))
+}
+
+/// Read every `--input` spec. `stdin` is only consumed when a spec
+/// names [`STDIN_SOURCE`], and at most one spec may do so.
+pub fn load_inputs(specs: &[String], stdin: impl Read) -> Result<NamedInputs> {
+    let mut inputs = NamedInputs::new();
+    let mut stdin = Some(stdin);
+    for spec in specs {
+        let (name, source) = parse_spec(spec)?;
+        if inputs.contains_key(name) {
+            return Err(SofosError::Config(format!(
+                "--input '{}' is given more than once",
+                name
+            )));
+        }
+        let content = if source == STDIN_SOURCE {
+            let reader = stdin.take().ok_or_else(|| {
+                SofosError::Config("only one --input can read standard input".to_string())
+            })?;
+            read_capped(reader, name)?
+        } else {
+            let file = std::fs::File::open(source).map_err(|e| {
+                SofosError::Config(format!(
+                    "cannot read --input '{}' from '{}': {}",
+                    name, source, e
+                ))
+            })?;
+            read_capped(file, name)?
+        };
+        inputs.insert(name.to_string(), content);
+    }
+    Ok(inputs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn loads_files_and_stdin_by_name() {
+        let dir = tempdir().unwrap();
+        let log = dir.path().join("app.log");
+        std::fs::write(&log, "error: boom\n").unwrap();
+        let specs = vec![format!("logs={}", log.display()), "notes=-".to_string()];
+
+        let inputs = load_inputs(&specs, "from stdin".as_bytes()).unwrap();
+
+        assert_eq!(inputs["logs"], "error: boom\n");
+        assert_eq!(inputs["notes"], "from stdin");
+        assert!(reads_stdin(&specs));
+        assert!(!reads_stdin(&["notes=notes.txt".to_string()]));
+    }
+
+    #[test]
+    fn rejects_malformed_and_duplicate_specs() {
+        let stdin = || std::io::empty();
+        for spec in ["logs", "=file", "bad name=file", "logs="] {
+            assert!(load_inputs(&[spec.to_string()], stdin()).is_err(), "{spec}");
+        }
+        let twice = vec!["a=-".to_string(), "a=-".to_string()];
+        assert!(load_inputs(&twice, stdin()).is_err());
+        let two_stdin = vec!["a=-".to_string(), "b=-".to_string()];
+        assert!(load_inputs(&two_stdin, stdin()).is_err());
+        assert!(load_inputs(&["a=/no/such/file".to_string()], stdin()).is_err());
+    }
+}
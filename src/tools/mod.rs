@@ -1,13 +1,22 @@
+pub mod audit;
 pub mod bash;
+pub mod changes;
 pub mod child_env;
 pub mod codesearch;
 pub mod executor;
 pub mod filesystem;
+pub mod git;
 pub mod image;
+pub mod inputs;
 pub mod morph_validate;
 pub mod permissions;
 pub mod plan;
+pub mod reads;
+pub mod replace;
 pub mod resolve;
+pub mod sofosignore;
+pub mod symbols;
+pub mod testrun;
 pub mod tool_name;
 pub mod types;
 pub mod utils;
@@ -34,6 +43,37 @@ pub fn format_read_file_output(path: &str, content: &str) -> String {
     format!("{} '{}':\n\n{}", READ_FILE_HEADER, path, content)
 }
 
+/// [`format_read_file_output`] for a paged read of lines `first..=last`,
+/// noting how many lines of the file follow.
+pub fn format_read_file_window_output(
+    path: &str,
+    first: usize,
+    last: usize,
+    remaining: usize,
+    content: &str,
+) -> String {
+    let more = if remaining > 0 {
+        format!(" (file has {} more lines)", remaining)
+    } else {
+        String::new()
+    };
+    format!(
+        "{} '{}' (lines {}-{}){}:\n\n{}",
+        READ_FILE_HEADER, path, first, last, more, content
+    )
+}
+
+/// `output` from the formatters above with the header's wording and
+/// quotes dropped, for `--compact-tool-results`: `src/a.rs (lines 1-9):`.
+pub fn compact_read_file_output(output: &str) -> String {
+    let compacted = output.split_once('\n').and_then(|(header, body)| {
+        let quoted = header.strip_prefix(READ_FILE_HEADER)?.strip_prefix(" '")?;
+        let (path, tail) = quoted.rsplit_once('\'')?;
+        Some(format!("{}{}\n{}", path, tail, body))
+    });
+    compacted.unwrap_or_else(|| output.to_string())
+}
+
 /// Return the body portion of a `read_file` output payload, stripping
 /// the header line that `format_read_file_output` prepended. Falls
 /// back to the whole string if the expected separator is missing so
@@ -61,6 +101,18 @@ mod public_helpers_tests {
         assert_eq!(read_file_body("no separator here"), "no separator here");
     }
 
+    #[test]
+    fn compact_output_drops_the_header_wording_but_keeps_the_body() {
+        let window = format_read_file_window_output("it's.rs", 3, 4, 2, "a\nb\n");
+        let compact = compact_read_file_output(&window);
+        assert_eq!(
+            compact,
+            "it's.rs (lines 3-4) (file has 2 more lines):\n\na\nb\n"
+        );
+        assert_eq!(read_file_body(&compact), "a\nb\n");
+        assert_eq!(compact_read_file_output("plain text"), "plain text");
+    }
+
     #[test]
     fn read_file_body_handles_empty_body() {
         let payload = format_read_file_output("empty.txt", "");
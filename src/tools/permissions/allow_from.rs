@@ -0,0 +1,234 @@
+//! `allow_from`: commands allowed because a project file already
+//! declares them. `.tool-versions` and `mise.toml` name toolchains, whose
+//! binaries become allowed base commands; `package.json` scripts and
+//! `mise.toml` tasks become exact allowed commands such as
+//! `npm run build`. Deny rules and the forbidden commands still win.
+
+use serde_json::Value;
+use std::path::Path;
+
+/// Binaries a toolchain installs, for names that differ from the tool.
+/// Any other tool allows the binary of its own name.
+const TOOL_BINARIES: &[(&str, &[&str])] = &[
+    ("node", &["node", "npm", "npx"]),
+    ("nodejs", &["node", "npm", "npx"]),
+    ("python", &["python", "python3", "pip", "pip3"]),
+    ("go", &["go", "gofmt"]),
+    ("golang", &["go", "gofmt"]),
+    ("rust", &["cargo", "rustc", "rustup"]),
+    ("ruby", &["ruby", "gem", "bundle", "rake"]),
+    ("java", &["java", "javac"]),
+    ("bun", &["bun", "bunx"]),
+    ("elixir", &["elixir", "mix", "iex"]),
+    ("erlang", &["erl", "erlc"]),
+    ("ripgrep", &["rg"]),
+];
+
+/// Shells and command launchers, never allowed from a project file: with
+/// any arguments they run any command, and the files are ones the model
+/// can edit.
+const REFUSED_BINARIES: &[&str] = &[
+    "sh",
+    "bash",
+    "zsh",
+    "fish",
+    "dash",
+    "ksh",
+    "csh",
+    "tcsh",
+    "nu",
+    "pwsh",
+    "powershell",
+    "cmd",
+    "env",
+    "xargs",
+    "sudo",
+    "doas",
+    "nohup",
+    "exec",
+    "eval",
+    "busybox",
+];
+
+/// Commands that run a `package.json` script by name.
+const SCRIPT_RUNNERS: &[&str] = &["npm run", "pnpm run", "yarn run", "bun run"];
+
+/// Command that runs a `mise.toml` task by name.
+const MISE_TASK_RUNNER: &str = "mise run";
+
+/// What the configured sources allow.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Imported {
+    /// Base commands, allowed with any arguments.
+    pub commands: Vec<String>,
+    /// Whole commands, allowed exactly as written.
+    pub exact: Vec<String>,
+}
+
+/// Read each `allow_from` entry, a path relative to `workspace`. A
+/// missing file allows nothing; an unsupported or unreadable one is
+/// traced and skipped so a stale entry never blocks startup.
+pub fn import(workspace: &Path, sources: &[String]) -> Imported {
+    let mut imported = Imported::default();
+    for source in sources {
+        let path = workspace.join(source);
+        let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+        let parse: fn(&str, &mut Imported) -> Result<(), String> = match name {
+            ".tool-versions" => tool_versions,
+            "mise.toml" | ".mise.toml" => mise_toml,
+            "package.json" => package_json,
+            _ => {
+                tracing::warn!(source = %source, "unsupported allow_from source; expected .tool-versions, mise.toml, or package.json");
+                continue;
+            }
+        };
+        let Ok(content) = std::fs::read_to_string(&path) else {
+            tracing::debug!(path = %path.display(), "allow_from source not found; skipping it");
+            continue;
+        };
+        if let Err(e) = parse(&content, &mut imported) {
+            tracing::warn!(path = %path.display(), error = %e, "invalid allow_from source; skipping it");
+        }
+    }
+    imported
+}
+
+/// `<tool> <version>...` lines, with `#` comments.
+fn tool_versions(content: &str, imported: &mut Imported) -> Result<(), String> {
+    for line in content.lines() {
+        let line = line.split('#').next().unwrap_or("");
+        if let Some(tool) = line.split_whitespace().next() {
+            add_tool(tool, imported);
+        }
+    }
+    Ok(())
+}
+
+/// The keys of `[tools]`, and the names of `[tasks]`.
+fn mise_toml(content: &str, imported: &mut Imported) -> Result<(), String> {
+    let table: toml::Table = toml::from_str(content).map_err(|e| e.to_string())?;
+    if let Some(tools) = table.get("tools").and_then(|t| t.as_table()) {
+        for tool in tools.keys() {
+            add_tool(tool, imported);
+        }
+    }
+    if let Some(tasks) = table.get("tasks").and_then(|t| t.as_table()) {
+        for task in tasks.keys().filter(|task| is_plain_name(task)) {
+            push_unique(
+                &mut imported.exact,
+                format!("{} {}", MISE_TASK_RUNNER, task),
+            );
+        }
+    }
+    Ok(())
+}
+
+/// The names under `"scripts"`.
+fn package_json(content: &str, imported: &mut Imported) -> Result<(), String> {
+    let package: Value = serde_json::from_str(content).map_err(|e| e.to_string())?;
+    let Some(scripts) = package.get("scripts").and_then(Value::as_object) else {
+        return Ok(());
+    };
+    for script in scripts.keys().filter(|script| is_plain_name(script)) {
+        for runner in SCRIPT_RUNNERS {
+            push_unique(&mut imported.exact, format!("{} {}", runner, script));
+        }
+    }
+    Ok(())
+}
+
+/// Allow the binaries of `tool`. A mise backend prefix (`npm:`,
+/// `cargo:`) and an owner (`aqua:cli/cli`) are dropped first.
+fn add_tool(tool: &str, imported: &mut Imported) {
+    let name = tool.rsplit([':', '/']).next().unwrap_or(tool);
+    if !is_plain_name(name) {
+        return;
+    }
+    let own = [name];
+    let binaries = TOOL_BINARIES
+        .iter()
+        .find(|(known, _)| *known == name)
+        .map_or(&own[..], |(_, binaries)| binaries);
+    for binary in binaries {
+        if REFUSED_BINARIES.contains(binary) {
+            tracing::warn!(binary = %binary, "allow_from never allows a shell or command launcher; skipping it");
+            continue;
+        }
+        push_unique(&mut imported.commands, binary.to_string());
+    }
+}
+
+/// Whether `name` can go into a command unquoted. A name with spaces or
+/// shell punctuation is left out rather than allowed as a compound
+/// command.
+fn is_plain_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | ':' | '@' | '/'))
+}
+
+fn push_unique(list: &mut Vec<String>, entry: String) {
+    if !list.contains(&entry) {
+        list.push(entry);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn toolchains_allow_binaries_and_scripts_allow_exact_commands() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::write(
+            root.join(".tool-versions"),
+            "# pinned\nnodejs 20.11.0\nterraform 1.7.0 # infra\nbash 5.2\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("mise.toml"),
+            "[tools]\n\"cargo:ripgrep\" = \"latest\"\njust = \"1\"\nenv = \"1\"\n\n[tasks.lint]\nrun = \"cargo clippy\"\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("package.json"),
+            r#"{"scripts": {"build:prod": "vite build", "x; rm -rf ~": "evil"}}"#,
+        )
+        .unwrap();
+
+        let sources = [
+            ".tool-versions",
+            "mise.toml",
+            "package.json",
+            "Gemfile",
+            "absent/package.json",
+        ]
+        .map(String::from);
+        let imported = import(root, &sources);
+        assert_eq!(
+            imported.commands,
+            ["node", "npm", "npx", "terraform", "rg", "just"],
+            "shells and command launchers are left out"
+        );
+        assert_eq!(
+            imported.exact,
+            [
+                "mise run lint",
+                "npm run build:prod",
+                "pnpm run build:prod",
+                "yarn run build:prod",
+                "bun run build:prod",
+            ],
+            "a script name with shell punctuation is left out"
+        );
+
+        fs::write(root.join("package.json"), "{ not json").unwrap();
+        assert_eq!(
+            import(root, &["package.json".to_string()]),
+            Imported::default()
+        );
+    }
+}
@@ -1,14 +1,22 @@
 use crate::config::{GLOBAL_CONFIG_FILE, LOCAL_CONFIG_FILE, global_config_path, home_dir};
 use crate::error::{Result, SofosError};
+use crate::tools::audit;
 use crate::tools::permissions::CommandPermission;
+use crate::tools::permissions::allow_from;
 use crate::tools::permissions::command_parse::{command_lookup_key, leading_dangerous_env_prefix};
-use crate::tools::permissions::pattern::BLANKET_BASH;
-use crate::tools::permissions::settings::PermissionSettings;
-use crate::tools::utils::{ConfirmationType, confirm_multi_choice};
+use crate::tools::permissions::pattern::{BLANKET_BASH, MCP_SCOPE, WEB_FETCH_SCOPE};
+use crate::tools::permissions::settings::{PermissionSettings, RuleList};
+use crate::tools::utils::{ConfirmationType, confirm_multi_choice, normalize_command_whitespace};
 use globset::{Glob, GlobSet, GlobSetBuilder};
 use std::collections::HashSet;
 use std::fs;
 use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+/// How long a "Yes for 10 minutes" answer lets a command run unasked.
+pub(crate) const TIMED_COMMAND_GRANT: Duration = Duration::from_secs(10 * SECS_PER_MINUTE);
+
+const SECS_PER_MINUTE: u64 = 60;
 
 pub struct PermissionManager {
     /// The merged global + local view used for every runtime permission
@@ -27,9 +35,60 @@ pub struct PermissionManager {
     pub(super) write_deny_set: GlobSet,
     pub(super) bash_path_allow_set: GlobSet,
     pub(super) bash_path_deny_set: GlobSet,
+    /// Compiled `Bash(/regex/)` rules, matched against the whole command.
+    pub(super) bash_allow_regexes: Vec<regex::Regex>,
+    pub(super) bash_deny_regexes: Vec<regex::Regex>,
+    /// Built-in and configured `protected_paths`, matched against
+    /// workspace-relative paths.
+    pub(super) protected_set: GlobSet,
+    /// Built-in and configured `confirm_new_file_patterns`, or `None`
+    /// when `confirm_new_files = false`.
+    pub(super) confirm_new_file_set: Option<GlobSet>,
+    /// Exact commands allowed by `allow_from` scripts and tasks, as
+    /// `Bash(...)` keys. Kept out of `settings` so they are never saved.
+    pub(super) imported_commands: HashSet<String>,
     pub(super) global_rules: HashSet<String>,
 }
 
+/// Paths the file tools never modify, whatever the config says: lock
+/// files are regenerated by their tools, CI workflows run with repository
+/// secrets, and the license is a legal document. A pattern without a `/`
+/// matches that name in any directory.
+pub(crate) const DEFAULT_PROTECTED_PATHS: &[&str] =
+    &["Cargo.lock", "LICENSE", ".github/workflows/**"];
+
+/// New files `write_file` asks about before creating: dotfiles, which
+/// shells and tools load on their own, and shell or batch scripts.
+pub(crate) const DEFAULT_CONFIRM_NEW_FILE_PATTERNS: &[&str] = &[
+    ".*",
+    "*.sh",
+    "*.bash",
+    "*.zsh",
+    "*.fish",
+    "*.command",
+    "*.ps1",
+    "*.bat",
+    "*.cmd",
+];
+
+/// Why a new file needs confirmation before it is created.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NewFileRisk {
+    Shebang,
+    Dotfile,
+    Pattern,
+}
+
+impl NewFileRisk {
+    pub fn describe(self) -> &'static str {
+        match self {
+            Self::Shebang => "starts with a #! line, so it can run as a program",
+            Self::Dotfile => "is a dotfile, which shells and tools may load automatically",
+            Self::Pattern => "is a script or matches `confirm_new_file_patterns`",
+        }
+    }
+}
+
 impl PermissionManager {
     pub fn new(workspace: PathBuf) -> Result<Self> {
         let local_settings_path = workspace.join(LOCAL_CONFIG_FILE);
@@ -60,8 +119,11 @@ impl PermissionManager {
             Self::build_scope_globs(&settings, Self::extract_write_pattern)?;
         let (bash_path_allow_set, bash_path_deny_set) =
             Self::build_scope_globs(&settings, Self::extract_bash_path_pattern)?;
+        let (bash_allow_regexes, bash_deny_regexes) = Self::build_bash_regexes(&settings)?;
+        let protected_set = Self::build_protected_globs(&settings)?;
+        let confirm_new_file_set = Self::build_confirm_new_file_globs(&settings)?;
 
-        let allowed_commands = [
+        let mut allowed_commands = [
             // Build tools
             "cargo",
             "rustc",
@@ -162,7 +224,15 @@ impl PermissionManager {
         ]
         .iter()
         .map(|s| s.to_string())
-        .collect();
+        .collect::<HashSet<String>>();
+
+        let imported = allow_from::import(&workspace, &settings.permissions.allow_from);
+        allowed_commands.extend(imported.commands);
+        let imported_commands = imported
+            .exact
+            .iter()
+            .map(|command| Self::normalize_command_key(command))
+            .collect();
 
         let forbidden_commands = [
             // File deletion/modification. `cp`, `mv`, `mkdir` are NOT on
@@ -234,6 +304,11 @@ impl PermissionManager {
             write_deny_set,
             bash_path_allow_set,
             bash_path_deny_set,
+            bash_allow_regexes,
+            bash_deny_regexes,
+            protected_set,
+            confirm_new_file_set,
+            imported_commands,
             global_rules,
         })
     }
@@ -307,6 +382,103 @@ impl PermissionManager {
         Ok((allow, deny))
     }
 
+    /// Compile [`DEFAULT_PROTECTED_PATHS`], the configured
+    /// `protected_paths`, and the `allow_from` sources, which would
+    /// otherwise let the model allow its own commands by editing them.
+    /// Like `.gitignore`, a pattern with no `/` also matches in
+    /// subdirectories; one with a `/` is anchored at the workspace root.
+    pub(super) fn build_protected_globs(settings: &PermissionSettings) -> Result<GlobSet> {
+        let sources: Vec<String> = settings
+            .permissions
+            .allow_from
+            .iter()
+            .map(|source| format!("/{}", globset::escape(source.trim_start_matches("./"))))
+            .collect();
+        let patterns = DEFAULT_PROTECTED_PATHS
+            .iter()
+            .copied()
+            .chain(
+                settings
+                    .permissions
+                    .protected_paths
+                    .iter()
+                    .map(String::as_str),
+            )
+            .chain(sources.iter().map(String::as_str));
+        Self::build_relative_globs(patterns, "protected_paths")
+    }
+
+    /// Compile [`DEFAULT_CONFIRM_NEW_FILE_PATTERNS`] and the configured
+    /// `confirm_new_file_patterns`, anchored like `protected_paths`.
+    pub(super) fn build_confirm_new_file_globs(
+        settings: &PermissionSettings,
+    ) -> Result<Option<GlobSet>> {
+        if settings.permissions.confirm_new_files == Some(false) {
+            return Ok(None);
+        }
+        let patterns = DEFAULT_CONFIRM_NEW_FILE_PATTERNS.iter().copied().chain(
+            settings
+                .permissions
+                .confirm_new_file_patterns
+                .iter()
+                .map(String::as_str),
+        );
+        Self::build_relative_globs(patterns, "confirm_new_file_patterns").map(Some)
+    }
+
+    fn build_relative_globs<'a>(
+        patterns: impl Iterator<Item = &'a str>,
+        setting: &str,
+    ) -> Result<GlobSet> {
+        let mut builder = GlobSetBuilder::new();
+        for pattern in patterns {
+            let pattern = pattern.trim().trim_start_matches("./");
+            let anchored = if pattern.contains('/') {
+                pattern.trim_start_matches('/').to_string()
+            } else {
+                format!("**/{}", pattern)
+            };
+            let glob = globset::GlobBuilder::new(&anchored)
+                .literal_separator(true)
+                .build()
+                .map_err(|e| {
+                    SofosError::ToolExecution(format!(
+                        "Invalid {} pattern '{}': {}",
+                        setting, pattern, e
+                    ))
+                })?;
+            builder.add(glob);
+        }
+        builder.build().map_err(|e| {
+            SofosError::ToolExecution(format!("Failed to build {} set: {}", setting, e))
+        })
+    }
+
+    /// Whether `relative`, a path relative to the workspace root using `/`
+    /// separators, is protected from the file tools.
+    pub fn is_protected_path(&self, relative: &str) -> bool {
+        self.protected_set.is_match(relative)
+    }
+
+    /// Whether creating a new file at `relative` with `content` needs the
+    /// user's confirmation first, and why. Always `None` when
+    /// `confirm_new_files = false`.
+    pub fn new_file_risk(&self, relative: &str, content: &str) -> Option<NewFileRisk> {
+        let set = self.confirm_new_file_set.as_ref()?;
+        if content.starts_with("#!") {
+            return Some(NewFileRisk::Shebang);
+        }
+        if !set.is_match(relative) {
+            return None;
+        }
+        let name = relative.rsplit('/').next().unwrap_or(relative);
+        if name.starts_with('.') {
+            Some(NewFileRisk::Dotfile)
+        } else {
+            Some(NewFileRisk::Pattern)
+        }
+    }
+
     pub(super) fn load_settings(path: &PathBuf) -> Result<PermissionSettings> {
         if path.exists() {
             let content = fs::read_to_string(path).map_err(|e| {
@@ -428,7 +600,19 @@ impl PermissionManager {
         Self::expand_tilde(path)
     }
 
+    /// The rule verdict for `command`, recorded in the audit log.
     pub fn check_command_permission(&mut self, command: &str) -> Result<CommandPermission> {
+        let verdict = self.command_verdict(command)?;
+        let decision = match verdict {
+            CommandPermission::Allowed => "allowed",
+            CommandPermission::Denied => "denied",
+            CommandPermission::Ask => "ask",
+        };
+        audit::record_permission(BLANKET_BASH, command, decision);
+        Ok(verdict)
+    }
+
+    fn command_verdict(&mut self, command: &str) -> Result<CommandPermission> {
         // Blanket `"Bash"` rules trump every other check. Deny wins over
         // allow when both lists contain the blanket entry, matching the
         // existing "deny is strictest" pattern used elsewhere.
@@ -498,6 +682,27 @@ impl PermissionManager {
             return Ok(CommandPermission::Denied);
         }
 
+        // Regex rules can overlap, so deny is checked first.
+        let collapsed = normalize_command_whitespace(command);
+        let whole_command = collapsed.trim();
+        if self
+            .bash_deny_regexes
+            .iter()
+            .any(|re| re.is_match(whole_command))
+        {
+            return Ok(CommandPermission::Denied);
+        }
+        if self.imported_commands.contains(&normalized) {
+            return Ok(allow_verdict);
+        }
+        if self
+            .bash_allow_regexes
+            .iter()
+            .any(|re| re.is_match(whole_command))
+        {
+            return Ok(allow_verdict);
+        }
+
         // Walk every sub-command in a compound shell (`for ...; do ...; done`,
         // `cmd1 && cmd2`, `cmd1; cmd2 | cmd3`) so the verdict reflects the
         // whole pipeline, not just the first token. Two reasons:
@@ -556,9 +761,15 @@ impl PermissionManager {
             .any(|seg| leading_dangerous_env_prefix(seg).is_some())
     }
 
-    pub fn ask_user_permission(&mut self, command: &str) -> Result<(bool, bool)> {
+    /// Ask whether `command` may run. Returns `(confirmed, remember,
+    /// allow_until)`; `allow_until` is set when the user picks the
+    /// time-bounded "Yes for 10 minutes", after which the session grant
+    /// lapses and the command is asked about again.
+    pub fn ask_user_permission(&mut self, command: &str) -> Result<(bool, bool, Option<Instant>)> {
         let normalized = Self::normalize_command_key(command);
         let prompt = format!("Allow command `{}`?", command);
+        let minutes = TIMED_COMMAND_GRANT.as_secs() / SECS_PER_MINUTE;
+        let timed_choice = format!("Yes for {} minutes", minutes);
 
         // "Remember this exact command" only helps when the same string
         // can recur. Commands whose args change every call (sed line
@@ -568,12 +779,27 @@ impl PermissionManager {
         // Yes/No instead of offering a persistence option that would only
         // clutter config.local.toml. Users who want to allowlist an
         // invocation family can add `Bash(cmd:*)` to settings directly.
-        let (confirmed, remember) = if Self::command_not_rememberable(command) {
-            let choices = ["Yes", "No"];
-            let idx = confirm_multi_choice(&prompt, &choices, 1, ConfirmationType::Permission)?;
-            (idx == 0, false)
+        // The default on cancel is always the plain "No".
+        let (confirmed, remember, timed) = if Self::command_not_rememberable(command) {
+            let choices = ["Yes", timed_choice.as_str(), "No"];
+            let idx = confirm_multi_choice(&prompt, &choices, 2, ConfirmationType::Permission)?;
+            (idx < 2, false, idx == 1)
         } else {
-            Self::ask_three_way(&prompt)?
+            let choices = [
+                "Yes",
+                timed_choice.as_str(),
+                "Yes and remember",
+                "No",
+                "No and remember",
+            ];
+            let idx = confirm_multi_choice(&prompt, &choices, 3, ConfirmationType::Permission)?;
+            match idx {
+                0 => (true, false, false),
+                1 => (true, false, true),
+                2 => (true, true, false),
+                3 => (false, false, false),
+                _ => (false, true, false),
+            }
         };
 
         if remember {
@@ -582,7 +808,16 @@ impl PermissionManager {
             self.rebuild_all_globs()?;
         }
 
-        Ok((confirmed, remember))
+        if timed {
+            audit::record_permission(
+                BLANKET_BASH,
+                command,
+                &format!("user allowed for {} minutes", minutes),
+            );
+            return Ok((true, false, Some(Instant::now() + TIMED_COMMAND_GRANT)));
+        }
+        Self::record_answer(BLANKET_BASH, command, confirmed, remember);
+        Ok((confirmed, remember, None))
     }
 
     /// Whether the prompt for `command` should drop the "and remember"
@@ -742,6 +977,7 @@ impl PermissionManager {
             self.rebuild_all_globs()?;
         }
 
+        Self::record_answer(scope, &format!("{}/**", dir), confirmed, remember);
         Ok((confirmed, remember))
     }
 
@@ -758,6 +994,7 @@ impl PermissionManager {
             self.remember_rule(Self::normalize_web_fetch(host), confirmed);
             self.save_settings()?;
         }
+        Self::record_answer(WEB_FETCH_SCOPE, host, confirmed, remember);
         Ok((confirmed, remember))
     }
 
@@ -776,9 +1013,26 @@ impl PermissionManager {
             self.remember_rule(Self::normalize_mcp(server), confirmed);
             self.save_settings()?;
         }
+        Self::record_answer(
+            MCP_SCOPE,
+            &format!("{}/{}", server, tool),
+            confirmed,
+            remember,
+        );
         Ok((confirmed, remember))
     }
 
+    /// Audit a user's answer to a permission prompt.
+    fn record_answer(scope: &str, subject: &str, confirmed: bool, remember: bool) {
+        let decision = match (confirmed, remember) {
+            (true, false) => "user allowed",
+            (true, true) => "user allowed and remembered",
+            (false, false) => "user denied",
+            (false, true) => "user denied and remembered",
+        };
+        audit::record_permission(scope, subject, decision);
+    }
+
     /// Persist `rule` into the allow (`allow == true`) or deny list,
     /// skipping the push when an identical rule is already present so
     /// repeat grants of the same command or directory don't accumulate
@@ -795,6 +1049,71 @@ impl PermissionManager {
         }
     }
 
+    /// The merged rules in `list`, global and local.
+    pub fn rules(&self, list: RuleList) -> &[String] {
+        self.settings.permissions.list(list)
+    }
+
+    /// Put `rule` in `list` in the local config, taking it out of the
+    /// other local lists so it moves rather than appearing twice.
+    /// Returns the list it moved from, if any.
+    pub fn set_rule(&mut self, rule: &str, list: RuleList) -> Result<Option<RuleList>> {
+        let rule = rule.trim();
+        Self::validate_rule(rule, list)?;
+        let previous = self.take_local_rule(rule);
+        Self::push_unique(self.local_settings.permissions.list_mut(list), rule);
+        Self::push_unique(self.settings.permissions.list_mut(list), rule);
+        self.apply_rule_edit()?;
+        Ok(previous)
+    }
+
+    /// Remove `rule` from the local config and return the list it was
+    /// in. Rules from the global config are left alone.
+    pub fn remove_rule(&mut self, rule: &str) -> Result<RuleList> {
+        let rule = rule.trim();
+        let Some(list) = self.take_local_rule(rule) else {
+            return Err(SofosError::Config(if self.global_rules.contains(rule) {
+                format!(
+                    "'{}' comes from ~/{}; edit that file to remove it",
+                    rule, GLOBAL_CONFIG_FILE
+                )
+            } else {
+                format!("'{}' is not in any permission list", rule)
+            }));
+        };
+        self.apply_rule_edit()?;
+        Ok(list)
+    }
+
+    /// Take `rule` out of every local list, and out of the merged view
+    /// unless the global config also has it. Returns the first local
+    /// list that held it.
+    fn take_local_rule(&mut self, rule: &str) -> Option<RuleList> {
+        let mut found = None;
+        for list in RuleList::ALL {
+            let local = self.local_settings.permissions.list_mut(list);
+            if !local.iter().any(|r| r == rule) {
+                continue;
+            }
+            local.retain(|r| r != rule);
+            if !self.global_rules.contains(rule) {
+                self.settings
+                    .permissions
+                    .list_mut(list)
+                    .retain(|r| r != rule);
+            }
+            found.get_or_insert(list);
+        }
+        found
+    }
+
+    /// Recompile the rule sets, then write the local config. Compiling
+    /// first means a rule that fails to build is never saved.
+    fn apply_rule_edit(&mut self) -> Result<()> {
+        self.rebuild_all_globs()?;
+        self.save_settings()
+    }
+
     /// Append `rule` to `list` unless it is already present.
     fn push_unique(list: &mut Vec<String>, rule: &str) {
         if !list.iter().any(|r| r == rule) {
@@ -844,6 +1163,9 @@ impl PermissionManager {
         let (ba, bd) = Self::build_scope_globs(&self.settings, Self::extract_bash_path_pattern)?;
         self.bash_path_allow_set = ba;
         self.bash_path_deny_set = bd;
+        let (bash_allow_regexes, bash_deny_regexes) = Self::build_bash_regexes(&self.settings)?;
+        self.bash_allow_regexes = bash_allow_regexes;
+        self.bash_deny_regexes = bash_deny_regexes;
         Ok(())
     }
 }
@@ -1,3 +1,4 @@
+pub mod allow_from;
 pub mod command_parse;
 pub mod manager;
 pub mod pattern;
@@ -342,7 +343,8 @@ pub fn check_mcp_session_access(
 mod tests {
     use super::*;
     use crate::tools::permissions::command_parse::is_env_assignment;
-    use crate::tools::permissions::settings::PermissionSettings;
+    use crate::tools::permissions::manager::NewFileRisk;
+    use crate::tools::permissions::settings::{PermissionSettings, RuleList};
     use std::collections::HashSet;
     use std::path::PathBuf;
     use std::sync::Mutex;
@@ -404,6 +406,11 @@ mod tests {
             PermissionManager::extract_bash_path_pattern,
         )
         .unwrap();
+        let (bash_allow_regexes, bash_deny_regexes) =
+            PermissionManager::build_bash_regexes(&settings).unwrap();
+        let protected_set = PermissionManager::build_protected_globs(&settings).unwrap();
+        let confirm_new_file_set =
+            PermissionManager::build_confirm_new_file_globs(&settings).unwrap();
 
         PermissionManager {
             settings,
@@ -417,10 +424,106 @@ mod tests {
             write_deny_set: write_deny,
             bash_path_allow_set: bash_allow,
             bash_path_deny_set: bash_deny,
+            bash_allow_regexes,
+            bash_deny_regexes,
+            protected_set,
+            confirm_new_file_set,
+            imported_commands: HashSet::new(),
             global_rules: HashSet::new(),
         }
     }
 
+    #[test]
+    fn protected_paths_match_defaults_and_configured_globs() {
+        let temp = TempDir::new().unwrap();
+        let mut settings = PermissionSettings::default();
+        settings
+            .permissions
+            .protected_paths
+            .extend(["*.pem".to_string(), "/deploy/prod.toml".to_string()]);
+        let manager = create_test_manager(settings, &temp);
+
+        // Built-in defaults: bare names match at any depth, the workflow
+        // glob only under the root `.github/workflows`.
+        assert!(manager.is_protected_path("Cargo.lock"));
+        assert!(manager.is_protected_path("crates/core/Cargo.lock"));
+        assert!(manager.is_protected_path("LICENSE"));
+        assert!(manager.is_protected_path(".github/workflows/ci.yml"));
+        assert!(!manager.is_protected_path("docs/.github/workflows/ci.yml"));
+        assert!(!manager.is_protected_path("Cargo.toml"));
+        assert!(!manager.is_protected_path("LICENSE.md"));
+
+        // Configured globs: `*` stays within one directory level.
+        assert!(manager.is_protected_path("certs/server.pem"));
+        assert!(manager.is_protected_path("deploy/prod.toml"));
+        assert!(!manager.is_protected_path("other/deploy/prod.toml"));
+    }
+
+    #[test]
+    fn allow_from_sources_are_protected_at_their_own_path() {
+        let temp = TempDir::new().unwrap();
+        let mut settings = PermissionSettings::default();
+        settings
+            .permissions
+            .allow_from
+            .extend(["package.json".to_string(), "./.tool-versions".to_string()]);
+        let manager = create_test_manager(settings, &temp);
+
+        assert!(manager.is_protected_path("package.json"));
+        assert!(manager.is_protected_path(".tool-versions"));
+        assert!(!manager.is_protected_path("web/package.json"));
+    }
+
+    #[test]
+    fn protected_paths_merge_across_config_files() {
+        let mut global = PermissionSettings::default();
+        global
+            .permissions
+            .protected_paths
+            .push("secrets/**".to_string());
+        let local: PermissionSettings =
+            toml::from_str("[permissions]\nprotected_paths = [\"schema.sql\"]\n").unwrap();
+        global.merge(local);
+        assert_eq!(
+            global.permissions.protected_paths,
+            vec!["schema.sql".to_string(), "secrets/**".to_string()]
+        );
+    }
+
+    #[test]
+    fn new_file_risk_flags_dotfiles_scripts_and_shebangs() {
+        let temp = TempDir::new().unwrap();
+        let mut settings = PermissionSettings::default();
+        settings
+            .permissions
+            .confirm_new_file_patterns
+            .push("*.service".to_string());
+        let manager = create_test_manager(settings, &temp);
+
+        assert_eq!(
+            manager.new_file_risk("config/.bashrc", ""),
+            Some(NewFileRisk::Dotfile)
+        );
+        assert_eq!(
+            manager.new_file_risk("scripts/deploy.sh", "echo hi"),
+            Some(NewFileRisk::Pattern)
+        );
+        assert_eq!(
+            manager.new_file_risk("bin/run", "#!/usr/bin/env python3\n"),
+            Some(NewFileRisk::Shebang)
+        );
+        assert_eq!(
+            manager.new_file_risk("deploy/app.service", ""),
+            Some(NewFileRisk::Pattern)
+        );
+        assert_eq!(manager.new_file_risk("src/lib.rs", "fn main() {}"), None);
+
+        let mut disabled = PermissionSettings::default();
+        disabled.permissions.confirm_new_files = Some(false);
+        let manager = create_test_manager(disabled, &temp);
+        assert_eq!(manager.new_file_risk(".env", "#!/bin/sh"), None);
+    }
+
     #[test]
     fn has_read_deny_rules_detects_only_read_denies() {
         let temp = TempDir::new().unwrap();
@@ -567,6 +670,56 @@ mod tests {
         );
     }
 
+    #[test]
+    fn rules_can_be_added_moved_and_removed() {
+        let _lock = HOME_MUTEX.lock().unwrap();
+        let home = TempDir::new().unwrap();
+        let _home_guard = HomeDirGuard::set(home.path());
+        let workspace = TempDir::new().unwrap();
+        let reload = || PermissionManager::new(workspace.path().to_path_buf()).unwrap();
+
+        let mut manager = reload();
+        assert_eq!(
+            manager.set_rule("Bash(make)", RuleList::Allow).unwrap(),
+            None
+        );
+        assert_eq!(
+            manager
+                .set_rule("Read(./secrets/**)", RuleList::Deny)
+                .unwrap(),
+            None
+        );
+        assert_eq!(
+            reload().check_command_permission("make").unwrap(),
+            CommandPermission::Allowed
+        );
+
+        let mut manager = reload();
+        assert_eq!(
+            manager.set_rule("Bash(make)", RuleList::Ask).unwrap(),
+            Some(RuleList::Allow)
+        );
+        let moved = reload();
+        assert!(moved.rules(RuleList::Allow).is_empty());
+        assert_eq!(moved.rules(RuleList::Ask), ["Bash(make)"]);
+
+        let mut manager = reload();
+        assert_eq!(
+            manager.remove_rule("Read(./secrets/**)").unwrap(),
+            RuleList::Deny
+        );
+        assert!(reload().rules(RuleList::Deny).is_empty());
+        assert!(manager.remove_rule("Read(./secrets/**)").is_err());
+
+        assert!(manager.set_rule("make", RuleList::Allow).is_err());
+        assert!(manager.set_rule("Read(./x)", RuleList::Ask).is_err());
+        assert!(manager.set_rule("Bash(/(/)", RuleList::Allow).is_err());
+        assert!(
+            reload().rules(RuleList::Allow).is_empty(),
+            "bad rules are never saved"
+        );
+    }
+
     #[test]
     fn web_fetch_session_access_applies_config_rules_and_refuses_when_non_interactive() {
         // Isolate HOME so no real global config feeds rules into the
@@ -1057,6 +1210,47 @@ mod tests {
         );
     }
 
+    #[test]
+    fn allow_from_sources_allow_toolchains_and_scripts_below_deny_rules() {
+        let _lock = HOME_MUTEX.lock().unwrap();
+        let home = TempDir::new().unwrap();
+        let _home_guard = HomeDirGuard::set(home.path());
+
+        let workspace = TempDir::new().unwrap();
+        let sofos = workspace.path().join(".sofos");
+        std::fs::create_dir_all(&sofos).unwrap();
+        std::fs::write(
+            sofos.join("config.local.toml"),
+            "[permissions]\nallow_from = [\".tool-versions\", \"package.json\"]\ndeny = [\"Bash(terraform destroy)\"]\n",
+        )
+        .unwrap();
+        std::fs::write(workspace.path().join(".tool-versions"), "terraform 1.7.0\n").unwrap();
+        std::fs::write(
+            workspace.path().join("package.json"),
+            r#"{"scripts": {"build": "vite build"}}"#,
+        )
+        .unwrap();
+        let mut manager = PermissionManager::new(workspace.path().to_path_buf()).unwrap();
+
+        for (command, expected) in [
+            ("terraform plan", CommandPermission::Allowed),
+            ("terraform destroy", CommandPermission::Denied),
+            ("bun run build", CommandPermission::Allowed),
+            ("bun run deploy", CommandPermission::Ask),
+            ("terraform plan && rm -rf out", CommandPermission::Denied),
+        ] {
+            assert_eq!(
+                manager.check_command_permission(command).unwrap(),
+                expected,
+                "{command}"
+            );
+        }
+        assert!(
+            manager.rules(RuleList::Allow).is_empty(),
+            "imported commands are not listed as rules"
+        );
+    }
+
     #[test]
     fn web_fetch_session_allow_covers_subdomains() {
         // Isolate HOME so no global config supplies rules; the session set
@@ -1584,6 +1778,71 @@ mod tests {
         );
     }
 
+    #[test]
+    fn regex_rules_match_the_whole_command() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut settings = PermissionSettings::default();
+        settings
+            .permissions
+            .allow
+            .push("Bash(/cargo (build|test)( .*)?/)".to_string());
+        settings
+            .permissions
+            .deny
+            .push("Bash(/cargo test .*--ignored.*/)".to_string());
+        let mut manager = create_test_manager(settings, &temp_dir);
+
+        for command in ["cargo build", "cargo  test --workspace"] {
+            assert_eq!(
+                manager.check_command_permission(command).unwrap(),
+                CommandPermission::Allowed,
+                "{command}"
+            );
+        }
+        assert_eq!(
+            manager.check_command_permission("cargo publish").unwrap(),
+            CommandPermission::Ask
+        );
+        assert_eq!(
+            manager
+                .check_command_permission("cargo test -- --ignored")
+                .unwrap(),
+            CommandPermission::Denied,
+            "deny regexes win over allow regexes"
+        );
+        assert_eq!(
+            manager.check_command_permission("xcargo build").unwrap(),
+            CommandPermission::Ask,
+            "regexes are anchored to the start of the command"
+        );
+    }
+
+    #[test]
+    fn regex_rules_are_not_path_patterns_and_must_compile() {
+        assert_eq!(
+            PermissionManager::extract_bash_regex("Bash(/npm (ci|test)/)"),
+            Some("npm (ci|test)")
+        );
+        assert_eq!(
+            PermissionManager::extract_bash_path_pattern("Bash(/make .*/)"),
+            None
+        );
+        assert_eq!(
+            PermissionManager::extract_bash_path_pattern("Bash(/tmp/**)"),
+            Some("/tmp/**")
+        );
+
+        let mut settings = PermissionSettings::default();
+        settings
+            .permissions
+            .allow
+            .push("Bash(/cargo (/)".to_string());
+        let err = PermissionManager::build_bash_regexes(&settings)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("Invalid regex"), "{err}");
+    }
+
     #[test]
     fn test_exact_match_priority() {
         let temp_dir = TempDir::new().unwrap();
@@ -4,15 +4,18 @@
 //! pattern matching, so a future change to the rule syntax has one
 //! place to land.
 
+use crate::error::{Result, SofosError};
 use crate::tools::permissions::PermissionManager;
+use crate::tools::permissions::settings::{PermissionSettings, RuleList};
 use crate::tools::utils::{is_absolute_path, normalize_command_whitespace};
+use regex::Regex;
 
 /// Bare `"Bash"` in an `allow` or `deny` list acts as a blanket rule
 /// over all bash commands. In `allow` it auto-passes everything except
 /// the built-in forbidden set (`rm`, `chmod`, `sudo`, …); in `deny` it
 /// auto-rejects everything. Deny beats allow when both lists contain
 /// the blanket entry.
-pub(super) const BLANKET_BASH: &str = "Bash";
+pub(crate) const BLANKET_BASH: &str = "Bash";
 
 /// Scope token for the `WebFetch(domain:<host>)` rule that gates the
 /// `web_fetch` tool's network access by host.
@@ -27,6 +30,13 @@ pub(super) const WEB_FETCH_DOMAIN_PREFIX: &str = "domain:";
 /// may call tools from a given MCP server.
 pub(super) const MCP_SCOPE: &str = "Mcp";
 
+/// Scopes a `<Scope>(<pattern>)` rule may name.
+const RULE_SCOPES: &[&str] = &[BLANKET_BASH, "Read", "Write", WEB_FETCH_SCOPE, MCP_SCOPE];
+
+/// Delimiter that marks a `Bash(/.../)` entry as a regular expression
+/// rather than a command or a path pattern.
+const BASH_REGEX_DELIMITER: char = '/';
+
 impl PermissionManager {
     /// Extract path patterns from Bash() entries.
     /// Only treats entries as path grants if the content is absolute or
@@ -37,6 +47,9 @@ impl PermissionManager {
     /// mis-classify a Unix-style `Bash(/var/log/**)` config entry as a
     /// command pattern when the binary runs on Windows.
     pub(super) fn extract_bash_path_pattern(entry: &str) -> Option<&str> {
+        if Self::extract_bash_regex(entry).is_some() {
+            return None;
+        }
         let trimmed = entry.trim();
         if let Some(rest) = trimmed.strip_prefix("Bash(") {
             if let Some(end) = rest.rfind(')') {
@@ -50,6 +63,75 @@ impl PermissionManager {
         None
     }
 
+    /// The expression inside a `Bash(/regex/)` entry: content that both
+    /// starts and ends with `/`.
+    pub(super) fn extract_bash_regex(entry: &str) -> Option<&str> {
+        entry
+            .trim()
+            .strip_prefix("Bash(")?
+            .strip_suffix(')')?
+            .strip_prefix(BASH_REGEX_DELIMITER)?
+            .strip_suffix(BASH_REGEX_DELIMITER)
+            .filter(|pattern| !pattern.is_empty())
+    }
+
+    /// Compile the `Bash(/regex/)` entries of the allow and deny lists.
+    /// Each is anchored so it must match the whole command, not just
+    /// part of it; otherwise `cargo test` would also allow
+    /// `cargo test && curl … | sh`.
+    pub(super) fn build_bash_regexes(
+        settings: &PermissionSettings,
+    ) -> Result<(Vec<Regex>, Vec<Regex>)> {
+        let compile = |entries: &[String]| -> Result<Vec<Regex>> {
+            entries
+                .iter()
+                .filter_map(|entry| Self::extract_bash_regex(entry))
+                .map(|pattern| {
+                    Regex::new(&format!("^(?:{})$", pattern)).map_err(|e| {
+                        SofosError::ToolExecution(format!(
+                            "Invalid regex in 'Bash(/{}/)': {}",
+                            pattern, e
+                        ))
+                    })
+                })
+                .collect()
+        };
+        Ok((
+            compile(&settings.permissions.allow)?,
+            compile(&settings.permissions.deny)?,
+        ))
+    }
+
+    /// Check that `rule` has a shape the permission system understands
+    /// before it is written to `list`: a bare `Bash`, or
+    /// `<Scope>(<pattern>)` with a known scope. The ask list takes Bash
+    /// command rules only.
+    pub(super) fn validate_rule(rule: &str, list: RuleList) -> Result<()> {
+        let scope = if rule == BLANKET_BASH {
+            BLANKET_BASH
+        } else {
+            rule.strip_suffix(')')
+                .and_then(|rest| rest.split_once('('))
+                .filter(|(scope, pattern)| {
+                    RULE_SCOPES.contains(scope) && !pattern.trim().is_empty()
+                })
+                .map(|(scope, _)| scope)
+                .ok_or_else(|| {
+                    SofosError::Config(format!(
+                        "'{}' is not a permission rule. Expected Bash(...), Read(...), \
+                         Write(...), WebFetch(domain:...), or Mcp(...)",
+                        rule
+                    ))
+                })?
+        };
+        if list == RuleList::Ask && (rule == BLANKET_BASH || scope != BLANKET_BASH) {
+            return Err(SofosError::Config(
+                "The ask list takes only Bash command rules, such as Bash(git push)".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
     /// `Bash(...)` wrapper that preserves internal whitespace. Used by
     /// path-grant lookups so a filename with legitimate multi-whitespace
     /// matches its config entry verbatim.
@@ -26,6 +26,23 @@ pub struct Permissions {
     pub deny: Vec<String>,
     #[serde(default)]
     pub ask: Vec<String>,
+    /// Workspace-relative globs the file tools may never write, move, or
+    /// delete, on top of the built-in defaults. Skipped when empty so a
+    /// save does not add the key to configs that never set it.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub protected_paths: Vec<String>,
+    /// Ask before `write_file` creates a dotfile, a script, or a file
+    /// starting with a shebang. On unless set to `false`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub confirm_new_files: Option<bool>,
+    /// Globs for new files that also need that confirmation, on top of
+    /// the built-in ones.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub confirm_new_file_patterns: Vec<String>,
+    /// Project files (`.tool-versions`, `mise.toml`, `package.json`)
+    /// whose toolchains and scripts are allowed as bash commands.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allow_from: Vec<String>,
 }
 
 impl PermissionSettings {
@@ -50,9 +67,71 @@ impl PermissionSettings {
         let merged_allow = merge_list(&other.permissions.allow, &self.permissions.allow);
         let merged_deny = merge_list(&other.permissions.deny, &self.permissions.deny);
         let merged_ask = merge_list(&other.permissions.ask, &self.permissions.ask);
+        let merged_protected = merge_list(
+            &other.permissions.protected_paths,
+            &self.permissions.protected_paths,
+        );
+        let merged_confirm_patterns = merge_list(
+            &other.permissions.confirm_new_file_patterns,
+            &self.permissions.confirm_new_file_patterns,
+        );
+
+        let merged_allow_from =
+            merge_list(&other.permissions.allow_from, &self.permissions.allow_from);
 
         self.permissions.allow = merged_allow;
         self.permissions.deny = merged_deny;
         self.permissions.ask = merged_ask;
+        self.permissions.protected_paths = merged_protected;
+        self.permissions.confirm_new_file_patterns = merged_confirm_patterns;
+        self.permissions.allow_from = merged_allow_from;
+        if other.permissions.confirm_new_files.is_some() {
+            self.permissions.confirm_new_files = other.permissions.confirm_new_files;
+        }
+    }
+}
+
+/// One of the three rule lists in `[permissions]`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RuleList {
+    Allow,
+    Deny,
+    Ask,
+}
+
+impl RuleList {
+    pub const ALL: [RuleList; 3] = [RuleList::Allow, RuleList::Deny, RuleList::Ask];
+
+    /// The list's key in the config file.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Allow => "allow",
+            Self::Deny => "deny",
+            Self::Ask => "ask",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|list| list.name().eq_ignore_ascii_case(name.trim()))
+    }
+}
+
+impl Permissions {
+    pub fn list(&self, list: RuleList) -> &Vec<String> {
+        match list {
+            RuleList::Allow => &self.allow,
+            RuleList::Deny => &self.deny,
+            RuleList::Ask => &self.ask,
+        }
+    }
+
+    pub fn list_mut(&mut self, list: RuleList) -> &mut Vec<String> {
+        match list {
+            RuleList::Allow => &mut self.allow,
+            RuleList::Deny => &mut self.deny,
+            RuleList::Ask => &mut self.ask,
+        }
     }
 }
@@ -0,0 +1,90 @@
+//! Whole-file reads seen in the conversation, for
+//! `--compact-tool-results`: a file read again with the same content is
+//! answered with a pointer to the earlier result instead of its text.
+
+use colored::Colorize;
+use std::collections::HashMap;
+use std::hash::{DefaultHasher, Hash, Hasher};
+
+/// Content hash and turn of each file's last full read, by canonical path.
+#[derive(Debug, Default)]
+pub struct ReadTracker {
+    turn: usize,
+    reads: HashMap<String, (u64, usize)>,
+}
+
+impl ReadTracker {
+    /// Count a new user turn; reads are reported by the turn they ran in.
+    pub fn begin_turn(&mut self) {
+        self.turn += 1;
+    }
+
+    /// The turn `path` was last read in, when `content` has not changed
+    /// since. A hit is forgotten, so asking again returns the content in
+    /// case the earlier result has since left the conversation. A miss
+    /// records this read.
+    pub fn earlier_read(&mut self, path: &str, content: &str) -> Option<usize> {
+        let mut hasher = DefaultHasher::new();
+        content.hash(&mut hasher);
+        let hash = hasher.finish();
+        match self.reads.get(path) {
+            Some(&(earlier, turn)) if earlier == hash => {
+                self.reads.remove(path);
+                Some(turn)
+            }
+            _ => {
+                self.reads.insert(path.to_string(), (hash, self.turn));
+                None
+            }
+        }
+    }
+
+    /// Drop every recorded read, for when the history they are in is
+    /// cleared, replaced, or summarised.
+    pub fn forget(&mut self) {
+        self.reads.clear();
+    }
+}
+
+/// The `read_file` result for a file unchanged since `turn`.
+pub fn unchanged_note(path: &str, turn: usize) -> String {
+    format!(
+        "{}: unchanged since it was read in turn {}; use that result. If it is no longer in the conversation, read the file again.",
+        path, turn
+    )
+}
+
+/// What the user sees for [`unchanged_note`], in place of a file preview.
+pub fn unchanged_display(path: &str, turn: usize) -> String {
+    format!(
+        "Read file from {} - unchanged since turn {}",
+        path.bright_cyan(),
+        turn
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_repeat_read_points_back_once_and_a_change_reads_again() {
+        let mut reads = ReadTracker::default();
+        reads.begin_turn();
+        assert_eq!(reads.earlier_read("/w/a.rs", "fn a() {}"), None);
+
+        reads.begin_turn();
+        assert_eq!(reads.earlier_read("/w/a.rs", "fn a() {}"), Some(1));
+        assert_eq!(
+            reads.earlier_read("/w/a.rs", "fn a() {}"),
+            None,
+            "the hit was forgotten"
+        );
+        assert_eq!(reads.earlier_read("/w/a.rs", "fn b() {}"), None);
+        assert_eq!(reads.earlier_read("/w/a.rs", "fn b() {}"), Some(2));
+
+        reads.earlier_read("/w/c.rs", "c");
+        reads.forget();
+        assert_eq!(reads.earlier_read("/w/c.rs", "c"), None);
+    }
+}
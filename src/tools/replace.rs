@@ -0,0 +1,97 @@
+//! `search_and_replace`: one regex replacement across every file ripgrep
+//! finds a match in. Without `confirm` the tool only previews the files
+//! and counts; with it each file is rewritten through the file tools.
+
+use regex::Regex;
+
+/// Most files one call may change, so a pattern that matches half the
+/// repository is narrowed before anything is written.
+pub const MAX_REPLACE_FILES: usize = 200;
+
+/// One file's planned change.
+pub struct FileReplacement {
+    pub path: String,
+    pub original: String,
+    pub modified: String,
+    pub count: usize,
+}
+
+/// `original` with every match of `regex` replaced, or `None` when
+/// nothing in it would change. `$1` and `${name}` in `replacement` expand
+/// to capture groups.
+pub fn replace_in(
+    regex: &Regex,
+    replacement: &str,
+    path: &str,
+    original: String,
+) -> Option<FileReplacement> {
+    let count = regex.find_iter(&original).count();
+    if count == 0 {
+        return None;
+    }
+    let modified = regex.replace_all(&original, replacement).into_owned();
+    if modified == original {
+        return None;
+    }
+    Some(FileReplacement {
+        path: path.to_string(),
+        original,
+        modified,
+        count,
+    })
+}
+
+/// The affected files with their match counts, then the files left
+/// alone and why.
+pub fn summary(pattern: &str, replacements: &[&FileReplacement], skipped: &[String]) -> String {
+    let total: usize = replacements.iter().map(|r| r.count).sum();
+    let mut out = format!(
+        "{} match{} in {} file{} for '{}':\n",
+        total,
+        if total == 1 { "" } else { "es" },
+        replacements.len(),
+        if replacements.len() == 1 { "" } else { "s" },
+        pattern
+    );
+    for replacement in replacements {
+        out.push_str(&format!("  {} ({})\n", replacement.path, replacement.count));
+    }
+    if !skipped.is_empty() {
+        out.push_str("Skipped:\n");
+        for note in skipped {
+            out.push_str(&format!("  {}\n", note));
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn replacements_expand_groups_and_are_counted() {
+        let regex = Regex::new(r"old_(\w+)").unwrap();
+        let planned = replace_in(
+            &regex,
+            "new_$1",
+            "src/a.rs",
+            "old_name(); old_value;\n".to_string(),
+        )
+        .unwrap();
+        assert_eq!(planned.modified, "new_name(); new_value;\n");
+        assert_eq!(planned.count, 2);
+
+        assert!(replace_in(&regex, "x", "b.rs", "nothing here".to_string()).is_none());
+        assert!(
+            replace_in(&regex, "old_$1", "c.rs", "old_same".to_string()).is_none(),
+            "a replacement that changes nothing is left out"
+        );
+
+        let text = summary("old_(\\w+)", &[&planned], &["d.rs: protected".to_string()]);
+        assert_eq!(
+            text,
+            "2 matches in 1 file for 'old_(\\w+)':\n  src/a.rs (2)\nSkipped:\n  d.rs: protected\n"
+        );
+    }
+}
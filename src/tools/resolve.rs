@@ -112,6 +112,78 @@ impl ToolExecutor {
     pub(super) fn resolve_for_write(&self, caller_path: &str) -> Result<ResolvedPath> {
         self.resolve(caller_path, false)
     }
+
+    /// With `--smart-paths` on, map a bare file name (no directory
+    /// component) that does not exist at the workspace root onto the
+    /// workspace file with that basename. Returns the workspace-relative
+    /// path when exactly one file matches, `None` when smart paths are off,
+    /// the caller path is not bare, it already exists, or nothing matches,
+    /// and an error listing every candidate when several match so the model
+    /// can pick one instead of looping on "file not found".
+    pub(super) fn smart_path(&self, caller_path: &str) -> Result<Option<String>> {
+        if !self.smart_paths
+            || is_absolute_or_tilde(caller_path)
+            || caller_path.contains(['/', '\\'])
+            || matches!(caller_path, "" | "." | "..")
+            || self.fs_tool.workspace().join(caller_path).exists()
+        {
+            return Ok(None);
+        }
+
+        let mut candidates = find_by_basename(self.fs_tool.workspace(), caller_path);
+        match candidates.len() {
+            0 => Ok(None),
+            1 => Ok(candidates.pop()),
+            _ => {
+                candidates.sort();
+                Err(SofosError::ToolExecution(format!(
+                    "'{}' is ambiguous: {} files in the workspace share that name:\n{}\n\
+                     Retry with one of these paths.",
+                    caller_path,
+                    candidates.len(),
+                    candidates.join("\n")
+                )))
+            }
+        }
+    }
+}
+
+/// Stop collecting basename matches past this many. Enough to show the
+/// model it must be more specific without walking a huge tree to the end.
+const MAX_SMART_PATH_CANDIDATES: usize = 20;
+
+/// Workspace-relative paths of every regular file named `name`, skipping
+/// the default build/vendored directories and never following symlinks.
+fn find_by_basename(workspace: &std::path::Path, name: &str) -> Vec<String> {
+    let mut matches = Vec::new();
+    let mut stack = vec![workspace.to_path_buf()];
+    while let Some(dir) = stack.pop() {
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let Ok(file_type) = entry.file_type() else {
+                continue;
+            };
+            let entry_name = entry.file_name();
+            if file_type.is_dir() {
+                let excluded = entry_name
+                    .to_str()
+                    .is_some_and(|n| crate::tools::codesearch::DEFAULT_EXCLUDE_DIRS.contains(&n));
+                if !excluded {
+                    stack.push(entry.path());
+                }
+            } else if file_type.is_file() && entry_name == name {
+                if let Ok(rel) = entry.path().strip_prefix(workspace) {
+                    matches.push(rel.to_string_lossy().replace('\\', "/"));
+                }
+                if matches.len() >= MAX_SMART_PATH_CANDIDATES {
+                    return matches;
+                }
+            }
+        }
+    }
+    matches
 }
 
 #[cfg(test)]
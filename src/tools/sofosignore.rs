@@ -0,0 +1,94 @@
+//! `.sofosignore` at the project root: gitignore-syntax patterns naming
+//! paths the tools never show. `read_file`, `read_symbol`, `file_info`,
+//! the edit tools, `list_directory`, `tree`, `glob_files`, and
+//! `search_code` all skip a match, whatever `.gitignore` says and even
+//! with `include_ignored`.
+
+use crate::error::{Result, SofosError};
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use std::path::Path;
+
+/// Ignore file, relative to the project root.
+pub const SOFOSIGNORE_FILE: &str = ".sofosignore";
+
+/// The compiled patterns, or nothing when the project has no file.
+#[derive(Clone, Default)]
+pub struct SofosIgnore {
+    matcher: Option<Gitignore>,
+}
+
+impl SofosIgnore {
+    /// Read `<root>/.sofosignore`. A missing file ignores nothing; a
+    /// malformed pattern is traced and skipped like git skips it.
+    pub fn load(root: &Path) -> Self {
+        let path = root.join(SOFOSIGNORE_FILE);
+        if !path.is_file() {
+            return Self::default();
+        }
+        let mut builder = GitignoreBuilder::new(root);
+        if let Some(e) = builder.add(&path) {
+            tracing::warn!(path = %path.display(), error = %e, "invalid .sofosignore pattern; skipping it");
+        }
+        match builder.build() {
+            Ok(matcher) => Self {
+                matcher: Some(matcher),
+            },
+            Err(e) => {
+                tracing::warn!(path = %path.display(), error = %e, "invalid .sofosignore; ignoring it");
+                Self::default()
+            }
+        }
+    }
+
+    /// Whether `path`, or a directory above it, matches. `path` must be
+    /// absolute; anything outside the project root never matches.
+    pub fn is_ignored(&self, path: &Path, is_dir: bool) -> bool {
+        let Some(matcher) = &self.matcher else {
+            return false;
+        };
+        path.starts_with(matcher.path())
+            && matcher
+                .matched_path_or_any_parents(path, is_dir)
+                .is_ignore()
+    }
+
+    /// Refuse `canonical` when it matches, naming it as `path`.
+    pub fn check(&self, path: &str, canonical: &Path) -> Result<()> {
+        if self.is_ignored(canonical, canonical.is_dir()) {
+            return Err(SofosError::ToolExecution(format!(
+                "'{}': path is ignored by {}",
+                path, SOFOSIGNORE_FILE
+            )));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn patterns_match_files_and_everything_under_a_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = fs::canonicalize(dir.path()).unwrap();
+        fs::write(root.join(SOFOSIGNORE_FILE), "*.pem\nsecrets/\n!keep.pem\n").unwrap();
+        fs::create_dir_all(root.join("secrets/nested")).unwrap();
+        let ignored = SofosIgnore::load(&root);
+
+        assert!(ignored.is_ignored(&root.join("server.pem"), false));
+        assert!(ignored.is_ignored(&root.join("secrets"), true));
+        assert!(ignored.is_ignored(&root.join("secrets/nested/a.txt"), false));
+        assert!(!ignored.is_ignored(&root.join("keep.pem"), false));
+        assert!(!ignored.is_ignored(&root.join("src/main.rs"), false));
+        assert!(!ignored.is_ignored(Path::new("/elsewhere/server.pem"), false));
+
+        let err = ignored
+            .check("secrets", &root.join("secrets"))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("path is ignored by .sofosignore"), "{err}");
+        assert!(!SofosIgnore::load(&root.join("secrets")).is_ignored(&root.join("x.pem"), false));
+    }
+}
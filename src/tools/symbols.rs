@@ -0,0 +1,646 @@
+//! `read_symbol`: one definition out of a source file, found by name
+//! with a line scan rather than a parser. Rust, Python, and JavaScript /
+//! TypeScript are understood. A range follows braces, or indentation in
+//! Python, and takes in the doc comments, attributes, and decorators
+//! directly above the definition.
+
+use std::path::Path;
+
+/// Most candidates listed when a name is missing or ambiguous.
+const MAX_CANDIDATES: usize = 30;
+
+/// Words that open a statement, not a JavaScript method definition.
+const JS_STATEMENT_WORDS: &[&str] = &[
+    "if", "for", "while", "switch", "catch", "return", "function", "new", "await", "else", "do",
+    "try", "with", "typeof", "delete", "void", "yield", "super",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Language {
+    Rust,
+    Python,
+    /// JavaScript and TypeScript.
+    JavaScript,
+}
+
+impl Language {
+    fn for_path(path: &str) -> Option<Self> {
+        let extension = Path::new(path).extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "rs" => Some(Self::Rust),
+            "py" | "pyi" => Some(Self::Python),
+            "js" | "jsx" | "mjs" | "cjs" | "ts" | "tsx" | "mts" | "cts" => Some(Self::JavaScript),
+            _ => None,
+        }
+    }
+
+    fn separator(self) -> &'static str {
+        match self {
+            Self::Rust => "::",
+            Self::Python | Self::JavaScript => ".",
+        }
+    }
+}
+
+/// One definition. Line numbers are 0-based and inclusive.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Symbol {
+    pub name: String,
+    pub kind: &'static str,
+    /// The name with its enclosing definitions, such as `Parser::new`.
+    pub qualified: String,
+    /// First line, counting the doc comments and attributes above.
+    pub first: usize,
+    /// The line the definition itself starts on.
+    pub line: usize,
+    pub last: usize,
+    /// Names of the enclosing definitions, outermost first.
+    ancestors: Vec<String>,
+}
+
+impl Symbol {
+    /// `kind qualified (line n)`, for candidate lists.
+    pub fn describe(&self) -> String {
+        format!("{} {} (line {})", self.kind, self.qualified, self.line + 1)
+    }
+}
+
+pub enum Lookup {
+    Found(Symbol),
+    /// Several definitions share the name.
+    Ambiguous(Vec<Symbol>),
+    /// Nothing matched; the closest names in the file instead.
+    NotFound(Vec<Symbol>),
+}
+
+/// Find `query` in `content`, the file at `path`. A qualified query such
+/// as `Type::method` or `Class.method` only matches inside a definition
+/// with that name. `None` when the language is not supported.
+pub fn lookup(path: &str, content: &str, query: &str) -> Option<Lookup> {
+    let language = Language::for_path(path)?;
+    let symbols = scan(language, content);
+    let parts: Vec<&str> = query
+        .split("::")
+        .flat_map(|part| part.split('.'))
+        .map(str::trim)
+        .filter(|part| !part.is_empty())
+        .collect();
+    let Some((name, containers)) = parts.split_last() else {
+        return Some(Lookup::NotFound(Vec::new()));
+    };
+
+    let matches: Vec<&Symbol> = symbols
+        .iter()
+        .filter(|symbol| symbol.name == *name)
+        .filter(|symbol| {
+            containers
+                .last()
+                .is_none_or(|container| symbol.ancestors.iter().any(|a| a == container))
+        })
+        .collect();
+    // A Rust type and its `impl` blocks share a name; the type wins.
+    let definitions: Vec<&&Symbol> = matches.iter().filter(|s| s.kind != "impl").collect();
+    let lookup = match (matches.len(), definitions.as_slice()) {
+        (0, _) => Lookup::NotFound(candidates(&symbols, name)),
+        (1, _) => Lookup::Found(matches[0].clone()),
+        (_, [only]) => Lookup::Found((**only).clone()),
+        _ => Lookup::Ambiguous(matches.into_iter().take(MAX_CANDIDATES).cloned().collect()),
+    };
+    Some(lookup)
+}
+
+/// Definitions whose names contain `name`, ignoring case, or else every
+/// top-level definition.
+fn candidates(symbols: &[Symbol], name: &str) -> Vec<Symbol> {
+    let needle = name.to_lowercase();
+    let similar: Vec<Symbol> = symbols
+        .iter()
+        .filter(|symbol| symbol.name.to_lowercase().contains(&needle))
+        .take(MAX_CANDIDATES)
+        .cloned()
+        .collect();
+    if !similar.is_empty() {
+        return similar;
+    }
+    symbols
+        .iter()
+        .filter(|symbol| symbol.ancestors.is_empty())
+        .take(MAX_CANDIDATES)
+        .cloned()
+        .collect()
+}
+
+fn scan(language: Language, content: &str) -> Vec<Symbol> {
+    let lines: Vec<&str> = content.lines().collect();
+    let mut symbols: Vec<Symbol> = Vec::new();
+    for (line, text) in lines.iter().enumerate() {
+        let Some((kind, name)) = definition(language, text.trim_start()) else {
+            continue;
+        };
+        let last = match language {
+            Language::Python => indented_end(&lines, line),
+            Language::Rust | Language::JavaScript => braced_end(language, kind, &lines, line),
+        };
+        symbols.push(Symbol {
+            qualified: name.clone(),
+            name,
+            kind,
+            first: attached_start(language, &lines, line),
+            line,
+            last,
+            ancestors: Vec::new(),
+        });
+    }
+
+    // Enclosing definitions, outermost first: every earlier definition
+    // whose range covers this one.
+    for index in 0..symbols.len() {
+        let (line, last) = (symbols[index].line, symbols[index].last);
+        let ancestors: Vec<String> = symbols[..index]
+            .iter()
+            .filter(|outer| outer.line < line && last <= outer.last)
+            .map(|outer| outer.name.clone())
+            .collect();
+        if let Some(parent) = ancestors.last() {
+            symbols[index].qualified =
+                format!("{}{}{}", parent, language.separator(), symbols[index].name);
+        }
+        symbols[index].ancestors = ancestors;
+    }
+    symbols
+}
+
+/// The kind and name of a definition starting `text`, which has its
+/// indentation removed.
+fn definition(language: Language, text: &str) -> Option<(&'static str, String)> {
+    match language {
+        Language::Rust => rust_definition(text),
+        Language::Python => python_definition(text),
+        Language::JavaScript => js_definition(text),
+    }
+}
+
+fn rust_definition(text: &str) -> Option<(&'static str, String)> {
+    let mut text = text;
+    if let Some(rest) = text.strip_prefix("pub") {
+        if let Some(scoped) = rest.strip_prefix('(') {
+            text = scoped.split_once(')')?.1.trim_start();
+        } else if let Some(rest) = strip_word(text, "pub") {
+            text = rest;
+        }
+    }
+    loop {
+        let before = text;
+        for modifier in ["async", "unsafe", "default", "extern"] {
+            if let Some(rest) = strip_word(text, modifier) {
+                text = rest;
+            }
+        }
+        if let Some(abi) = text.strip_prefix('"') {
+            text = abi.split_once('"')?.1.trim_start();
+        }
+        // `const fn` is a function; a bare `const` is a definition.
+        if let Some(rest) = strip_word(text, "const") {
+            if ["fn", "unsafe", "async", "extern"]
+                .iter()
+                .any(|word| strip_word(rest, word).is_some())
+            {
+                text = rest;
+            }
+        }
+        if text == before {
+            break;
+        }
+    }
+    for (keyword, kind) in [
+        ("fn", "fn"),
+        ("struct", "struct"),
+        ("enum", "enum"),
+        ("trait", "trait"),
+        ("union", "union"),
+        ("type", "type"),
+        ("mod", "mod"),
+        ("const", "const"),
+        ("static", "static"),
+        ("macro_rules!", "macro"),
+    ] {
+        if let Some(rest) = strip_word(text, keyword) {
+            let rest = strip_word(rest, "mut").unwrap_or(rest);
+            return Some((kind, identifier(rest)?));
+        }
+    }
+    let rest = text.strip_prefix("impl")?;
+    if !rest.starts_with(['<', ' ']) {
+        return None;
+    }
+    Some(("impl", impl_target(rest)?))
+}
+
+/// The type an `impl` header is for: `Foo` in `impl<T> Display for Foo<T>`.
+fn impl_target(header: &str) -> Option<String> {
+    let header = skip_generics(header.trim_start());
+    let head = header.split('{').next()?;
+    let head = head.split(" where").next()?;
+    let target = head.rsplit_once(" for ").map_or(head, |(_, target)| target);
+    let target = target.trim().trim_start_matches('&');
+    let target = target.strip_prefix("dyn ").unwrap_or(target).trim();
+    let path = target.split('<').next()?;
+    identifier(path.rsplit("::").next()?)
+}
+
+/// `text` past a leading `<...>`, counting nested brackets.
+fn skip_generics(text: &str) -> &str {
+    if !text.starts_with('<') {
+        return text;
+    }
+    let mut depth = 0;
+    for (index, c) in text.char_indices() {
+        match c {
+            '<' => depth += 1,
+            '>' => {
+                depth -= 1;
+                if depth == 0 {
+                    return text[index + 1..].trim_start();
+                }
+            }
+            _ => {}
+        }
+    }
+    ""
+}
+
+fn python_definition(text: &str) -> Option<(&'static str, String)> {
+    let text = strip_word(text, "async").unwrap_or(text);
+    if let Some(rest) = strip_word(text, "def") {
+        return Some(("def", identifier(rest)?));
+    }
+    Some(("class", identifier(strip_word(text, "class")?)?))
+}
+
+fn js_definition(text: &str) -> Option<(&'static str, String)> {
+    let mut text = text;
+    loop {
+        let before = text;
+        for modifier in [
+            "export",
+            "default",
+            "declare",
+            "abstract",
+            "async",
+            "static",
+            "public",
+            "private",
+            "protected",
+            "readonly",
+            "override",
+        ] {
+            if let Some(rest) = strip_word(text, modifier) {
+                text = rest;
+            }
+        }
+        if text == before {
+            break;
+        }
+    }
+    if let Some(rest) = text.strip_prefix("function") {
+        if rest.starts_with([' ', '*']) {
+            let rest = rest.trim_start().trim_start_matches('*').trim_start();
+            return Some(("function", identifier(rest)?));
+        }
+    }
+    for keyword in [
+        "class",
+        "interface",
+        "type",
+        "enum",
+        "namespace",
+        "const",
+        "let",
+        "var",
+    ] {
+        if let Some(rest) = strip_word(text, keyword) {
+            return Some((keyword, identifier(rest)?));
+        }
+    }
+    js_method(text)
+}
+
+/// A class member such as `async load(path: string): Promise<void> {`:
+/// a name, a parameter list closed on the same line, and a body opened
+/// at its end.
+fn js_method(text: &str) -> Option<(&'static str, String)> {
+    let name = identifier(text.trim_start_matches(['*', '#']))?;
+    if JS_STATEMENT_WORDS.contains(&name.as_str()) {
+        return None;
+    }
+    let rest = text.trim_start_matches(['*', '#'])[name.len()..].trim_start();
+    let rest = skip_generics(rest);
+    let params = rest.strip_prefix('(')?;
+    let mut depth = 1;
+    let mut close = None;
+    for (index, c) in params.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => {
+                depth -= 1;
+                if depth == 0 {
+                    close = Some(index);
+                    break;
+                }
+            }
+            _ => {}
+        }
+    }
+    let after = params[close? + 1..].trim();
+    (after.ends_with('{') && (after.starts_with('{') || after.starts_with(':')))
+        .then_some(("method", name))
+}
+
+/// `text` after `word` and the whitespace following it, when `text`
+/// starts with that whole word.
+fn strip_word<'a>(text: &'a str, word: &str) -> Option<&'a str> {
+    let rest = text.strip_prefix(word)?;
+    rest.starts_with(char::is_whitespace)
+        .then(|| rest.trim_start())
+}
+
+fn identifier(text: &str) -> Option<String> {
+    let name: String = text
+        .chars()
+        .take_while(|c| c.is_alphanumeric() || *c == '_' || *c == '$')
+        .collect();
+    (!name.is_empty() && !name.starts_with(|c: char| c.is_ascii_digit())).then_some(name)
+}
+
+/// The first line of the comments, attributes, and decorators directly
+/// above `line`.
+fn attached_start(language: Language, lines: &[&str], line: usize) -> usize {
+    let mut first = line;
+    while first > 0 {
+        let above = lines[first - 1].trim_start();
+        let attached = match language {
+            Language::Rust => above.starts_with("//") || above.starts_with("#["),
+            Language::Python => above.starts_with('@') || above.starts_with('#'),
+            Language::JavaScript => {
+                above.starts_with("//")
+                    || above.starts_with("/*")
+                    || above.starts_with('*')
+                    || above.starts_with('@')
+            }
+        };
+        if !attached {
+            break;
+        }
+        first -= 1;
+    }
+    first
+}
+
+/// Last line of a definition in a brace language: where its outermost
+/// block closes, or the `;` that ends it first. Strings and comments are
+/// skipped so braces inside them do not count.
+fn braced_end(language: Language, kind: &str, lines: &[&str], start: usize) -> usize {
+    let mut depth = 0i32;
+    let mut seen_block = false;
+    let mut in_comment = false;
+    let mut in_string: Option<char> = None;
+    for (index, text) in lines.iter().enumerate().skip(start) {
+        let chars: Vec<char> = text.chars().collect();
+        let mut i = 0;
+        while i < chars.len() {
+            let c = chars[i];
+            let next = chars.get(i + 1).copied();
+            if in_comment {
+                if c == '*' && next == Some('/') {
+                    in_comment = false;
+                    i += 1;
+                }
+            } else if let Some(quote) = in_string {
+                if c == '\\' {
+                    i += 1;
+                } else if c == quote {
+                    in_string = None;
+                }
+            } else {
+                match c {
+                    '/' if next == Some('/') => break,
+                    '/' if next == Some('*') => {
+                        in_comment = true;
+                        i += 1;
+                    }
+                    '"' => in_string = Some(c),
+                    '\'' | '`' if language == Language::JavaScript => in_string = Some(c),
+                    // A Rust char literal; a lifetime has no closing quote.
+                    '\'' if next == Some('\\') => {
+                        i += 2;
+                        while i < chars.len() && chars[i] != '\'' {
+                            i += 1;
+                        }
+                    }
+                    '\'' if chars.get(i + 2) == Some(&'\'') => i += 2,
+                    '{' | '(' | '[' => {
+                        if c == '{' && depth == 0 {
+                            seen_block = true;
+                        }
+                        depth += 1;
+                    }
+                    '}' | ')' | ']' => {
+                        depth -= 1;
+                        if c == '}' && depth <= 0 && seen_block {
+                            return index;
+                        }
+                    }
+                    ';' if depth <= 0 => return index,
+                    _ => {}
+                }
+            }
+            i += 1;
+        }
+        // A JavaScript declaration may end without a semicolon.
+        let statement = matches!(kind, "const" | "let" | "var" | "type");
+        if language == Language::JavaScript && statement && depth <= 0 && !seen_block {
+            let trimmed = text.trim_end();
+            if !trimmed.ends_with(['=', '>', ',', '(', '[', '{', '+', '-', '?', ':', '|', '&']) {
+                return index;
+            }
+        }
+    }
+    lines.len().saturating_sub(1)
+}
+
+/// Last line of a Python definition: the signature, which may span
+/// lines, then every line indented deeper than the `def` or `class`.
+fn indented_end(lines: &[&str], start: usize) -> usize {
+    let indent = |text: &str| text.len() - text.trim_start().len();
+    let base = indent(lines[start]);
+    let mut depth = 0i32;
+    let mut signature_end = start;
+    for (index, text) in lines.iter().enumerate().skip(start) {
+        for c in text.split('#').next().unwrap_or_default().chars() {
+            match c {
+                '(' | '[' | '{' => depth += 1,
+                ')' | ']' | '}' => depth -= 1,
+                _ => {}
+            }
+        }
+        signature_end = index;
+        if depth <= 0 {
+            break;
+        }
+    }
+    let mut last = signature_end;
+    for (index, text) in lines.iter().enumerate().skip(signature_end + 1) {
+        if text.trim().is_empty() {
+            continue;
+        }
+        if indent(text) <= base {
+            break;
+        }
+        last = index;
+    }
+    last
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn found(path: &str, content: &str, query: &str) -> Symbol {
+        match lookup(path, content, query) {
+            Some(Lookup::Found(symbol)) => symbol,
+            _ => panic!("expected one match for {query}"),
+        }
+    }
+
+    #[test]
+    fn rust_items_take_their_docs_and_prefer_the_type_over_its_impls() {
+        let content = "\
+use std::fmt;
+
+/// A parser.
+#[derive(Debug)]
+pub struct Parser {
+    input: String, // '{'
+}
+
+impl Parser {
+    pub const fn new() -> Self {
+        let brace = '}';
+        Self { input: \"{\".into() }
+    }
+}
+
+impl fmt::Display for Parser {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, \"parser\")
+    }
+}
+
+pub(crate) type Alias = Parser;
+";
+        let parser = found("src/lib.rs", content, "Parser");
+        assert_eq!(
+            (parser.kind, parser.first, parser.line, parser.last),
+            ("struct", 2, 4, 6)
+        );
+
+        let new = found("src/lib.rs", content, "Parser::new");
+        assert_eq!((new.kind, new.line, new.last), ("fn", 9, 12));
+        assert_eq!(new.qualified, "Parser::new");
+
+        let fmt = found("src/lib.rs", content, "fmt");
+        assert_eq!((fmt.line, fmt.last), (16, 18));
+        assert_eq!(found("src/lib.rs", content, "Alias").last, 21);
+    }
+
+    #[test]
+    fn python_ranges_follow_indentation_and_decorators() {
+        let content = "\
+import os
+
+
+class Store:
+    @property
+    def path(
+        self,
+    ):
+        # The full path.
+        return os.path.join(self.root, 'x')
+
+    def load(self): return 1
+
+
+def main():
+    pass
+";
+        let path = found("app.py", content, "Store.path");
+        assert_eq!((path.first, path.line, path.last), (4, 5, 9));
+        let store = found("app.py", content, "Store");
+        assert_eq!((store.line, store.last), (3, 11));
+        assert_eq!(found("app.py", content, "main").last, 15);
+    }
+
+    #[test]
+    fn javascript_functions_classes_methods_and_arrow_constants() {
+        let content = "\
+export async function load(path) {
+  return `${path}}`;
+}
+
+/** Cache of results. */
+export class Cache {
+  get(key: string): string {
+    if (key) {
+      return this.map[key];
+    }
+  }
+}
+
+const double = (x) =>
+  x * 2
+
+describe(\"cache\", () => {
+});
+";
+        assert_eq!(found("src/a.ts", content, "load").last, 2);
+        let cache = found("src/a.ts", content, "Cache");
+        assert_eq!((cache.first, cache.line, cache.last), (4, 5, 11));
+        let get = found("src/a.ts", content, "Cache.get");
+        assert_eq!((get.kind, get.line, get.last), ("method", 6, 10));
+        assert_eq!(found("src/a.ts", content, "double").last, 14);
+        assert!(matches!(
+            lookup("src/a.ts", content, "describe"),
+            Some(Lookup::NotFound(_))
+        ));
+    }
+
+    #[test]
+    fn missing_and_ambiguous_names_list_candidates() {
+        let content = "\
+struct A;
+impl A {
+    fn new() -> Self { A }
+}
+struct B;
+impl B {
+    fn new() -> Self { B }
+}
+fn build_all() {}
+";
+        match lookup("m.rs", content, "new") {
+            Some(Lookup::Ambiguous(candidates)) => {
+                let described: Vec<String> = candidates.iter().map(Symbol::describe).collect();
+                assert_eq!(described, ["fn A::new (line 3)", "fn B::new (line 7)"]);
+            }
+            _ => panic!("expected an ambiguous match"),
+        }
+        assert_eq!(found("m.rs", content, "B::new").line, 6);
+        match lookup("m.rs", content, "build") {
+            Some(Lookup::NotFound(candidates)) => {
+                assert_eq!(candidates[0].qualified, "build_all");
+            }
+            _ => panic!("expected candidates"),
+        }
+        assert!(lookup("notes.md", content, "A").is_none());
+    }
+}
@@ -0,0 +1,166 @@
+//! `run_tests`: the project's conventional test command, picked from the
+//! build files at the workspace root and run through the bash executor,
+//! with the runner's summary lines pulled out of the output.
+
+use std::path::Path;
+
+/// Build files at the workspace root and the test command each implies,
+/// checked in order. `package.json` is refined by its lockfile.
+const PROJECT_MARKERS: &[(&str, &str)] = &[
+    ("Cargo.toml", "cargo test"),
+    ("go.mod", "go test ./..."),
+    ("package.json", "npm test"),
+    ("pyproject.toml", "python -m pytest"),
+    ("setup.py", "python -m pytest"),
+    ("pytest.ini", "python -m pytest"),
+    ("tox.ini", "python -m pytest"),
+    ("pom.xml", "mvn test"),
+    ("build.gradle", "gradle test"),
+    ("build.gradle.kts", "gradle test"),
+    ("mix.exs", "mix test"),
+];
+
+/// Lockfiles that name the package manager for a `package.json` project.
+const NODE_LOCKFILES: &[(&str, &str)] = &[
+    ("pnpm-lock.yaml", "pnpm test"),
+    ("yarn.lock", "yarn test"),
+    ("bun.lock", "bun run test"),
+    ("bun.lockb", "bun run test"),
+];
+
+/// Line starts that test runners use for their totals: cargo, go, Jest,
+/// Vitest, Maven, Gradle, and ExUnit.
+const SUMMARY_PREFIXES: &[&str] = &[
+    "test result:",
+    "ok ",
+    "FAIL",
+    "--- FAIL",
+    "Tests:",
+    "Test Suites:",
+    "Test Files",
+    "Tests run:",
+    "BUILD SUCCESSFUL",
+    "BUILD FAILED",
+    "Finished in",
+];
+
+/// Words in a pytest or mocha totals line such as `=== 3 passed in 0.1s ===`.
+const SUMMARY_WORDS: &[&str] = &[" passed", " failed", " passing", " failing", " error"];
+
+/// Prefix of the bash executor's output for a non-zero exit.
+const FAILED_PREFIX: &str = "Command failed with ";
+
+/// The test command for the project at `workspace`, or `None` when no
+/// known build file is there.
+pub fn detect(workspace: &Path) -> Option<&'static str> {
+    let (marker, command) = PROJECT_MARKERS
+        .iter()
+        .find(|(marker, _)| workspace.join(marker).is_file())?;
+    if *marker == "package.json" {
+        if let Some((_, node)) = NODE_LOCKFILES
+            .iter()
+            .find(|(lockfile, _)| workspace.join(lockfile).is_file())
+        {
+            return Some(node);
+        }
+    }
+    if matches!(*marker, "build.gradle" | "build.gradle.kts") && workspace.join("gradlew").is_file()
+    {
+        return Some("./gradlew test");
+    }
+    Some(command)
+}
+
+/// Names of the build files `detect` looks for, for the error shown when
+/// none is found.
+pub fn known_markers() -> String {
+    PROJECT_MARKERS
+        .iter()
+        .map(|(marker, _)| *marker)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Pass or fail and the runner's summary lines, followed by the whole
+/// output when the run failed. `output` is the bash executor's result.
+pub fn summarize(command: &str, output: &str) -> String {
+    let failure = output
+        .lines()
+        .next()
+        .and_then(|line| line.strip_prefix(FAILED_PREFIX));
+    let mut out = match failure {
+        Some(exit) => format!("Ran `{}`: failed ({})\n", command, exit),
+        None => format!("Ran `{}`: passed\n", command),
+    };
+    let summary: Vec<&str> = output
+        .lines()
+        .map(str::trim)
+        .filter(|line| is_summary_line(line))
+        .collect();
+    for line in &summary {
+        out.push_str(line);
+        out.push('\n');
+    }
+    if failure.is_some() {
+        out.push_str("\nOutput:\n");
+        out.push_str(output);
+    } else if summary.is_empty() {
+        out.push_str("(no test summary found in the output)\n");
+    }
+    out
+}
+
+fn is_summary_line(line: &str) -> bool {
+    if SUMMARY_PREFIXES
+        .iter()
+        .any(|prefix| line.starts_with(prefix))
+    {
+        return true;
+    }
+    let has_count =
+        line.chars().next().is_some_and(|c| c.is_ascii_digit()) || line.starts_with('=');
+    has_count && SUMMARY_WORDS.iter().any(|word| line.contains(word))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn the_build_file_and_lockfile_pick_the_command() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        assert_eq!(detect(root), None);
+
+        fs::write(root.join("package.json"), "{}").unwrap();
+        assert_eq!(detect(root), Some("npm test"));
+        fs::write(root.join("pnpm-lock.yaml"), "").unwrap();
+        assert_eq!(detect(root), Some("pnpm test"));
+
+        fs::write(root.join("Cargo.toml"), "").unwrap();
+        assert_eq!(detect(root), Some("cargo test"), "earlier markers win");
+    }
+
+    #[test]
+    fn summaries_keep_totals_and_failing_output() {
+        let passed =
+            "STDOUT:\nrunning 2 tests\ntest a ... ok\ntest result: ok. 2 passed; 0 failed\n";
+        assert_eq!(
+            summarize("cargo test", passed),
+            "Ran `cargo test`: passed\ntest result: ok. 2 passed; 0 failed\n"
+        );
+
+        let failed = "Command failed with exit code: 1\nSTDOUT:\nFAILED test_x.py::test_a\n===== 1 failed, 3 passed in 0.12s =====\nSTDERR:\n";
+        let summary = summarize("python -m pytest", failed);
+        assert!(
+            summary.starts_with(
+                "Ran `python -m pytest`: failed (exit code: 1)\nFAILED test_x.py::test_a\n===== 1 failed, 3 passed in 0.12s =====\n\nOutput:\n"
+            ),
+            "{summary}"
+        );
+        assert!(summary.ends_with(failed));
+
+        assert!(summarize("make check", "STDOUT:\ndone\n").contains("no test summary"));
+    }
+}
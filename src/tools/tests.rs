@@ -2,7 +2,7 @@ use super::*;
 use crate::config::SandboxMode;
 use crate::error::SofosError;
 use crate::mcp::manager::{ImageData, ToolResult as McpToolResult};
-use crate::tools::executor::{cap_mcp_images, cap_mcp_response};
+use crate::tools::executor::{ToolExecutionResult, cap_mcp_images, cap_mcp_response};
 use crate::tools::utils::{MAX_MCP_IMAGE_BYTES, MAX_MCP_IMAGE_COUNT};
 use serde_json::json;
 use tempfile::tempdir;
@@ -450,6 +450,58 @@ async fn test_edit_file_replaces_string() {
     assert_eq!(content, "hello rust");
 }
 
+#[tokio::test]
+async fn confirm_edits_declines_file_changes_without_a_terminal() {
+    let workspace = tempdir().unwrap();
+    std::fs::write(workspace.path().join("test.txt"), "hello world").unwrap();
+
+    let mut executor = ToolExecutor::new(
+        workspace.path().to_path_buf(),
+        None,
+        None,
+        SandboxMode::Sandboxed,
+        false,
+    )
+    .unwrap();
+    executor.set_confirm_edits(true);
+
+    for (tool, input) in [
+        (
+            "edit_file",
+            json!({"path": "test.txt", "old_string": "world", "new_string": "rust"}),
+        ),
+        ("write_file", json!({"path": "new.txt", "content": "x"})),
+        (
+            "move_file",
+            json!({"source": "test.txt", "destination": "moved.txt"}),
+        ),
+        (
+            "copy_file",
+            json!({"source": "test.txt", "destination": "copy.txt"}),
+        ),
+    ] {
+        let err = executor
+            .execute(tool, &input)
+            .await
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("The user declined the edit"), "{tool}: {err}");
+    }
+    let content = std::fs::read_to_string(workspace.path().join("test.txt")).unwrap();
+    assert_eq!(content, "hello world");
+    assert!(!workspace.path().join("new.txt").exists());
+    assert!(!workspace.path().join("copy.txt").exists());
+
+    executor.set_confirm_edits(false);
+    executor
+        .execute(
+            "edit_file",
+            &json!({"path": "test.txt", "old_string": "world", "new_string": "rust"}),
+        )
+        .await
+        .unwrap();
+}
+
 #[tokio::test]
 async fn test_edit_file_preserves_content_past_truncation_cap() {
     // Regression: `edit_file` used to read through `fs_tool.read_file`,
@@ -904,6 +956,109 @@ async fn test_glob_files_skips_default_excludes() {
     );
 }
 
+#[tokio::test]
+async fn sofosignore_hides_files_from_reads_and_globs() {
+    let workspace = tempdir().unwrap();
+    std::fs::create_dir_all(workspace.path().join("secrets")).unwrap();
+    std::fs::write(workspace.path().join("secrets/key.rs"), "secret").unwrap();
+    std::fs::write(workspace.path().join("main.rs"), "").unwrap();
+    std::fs::write(workspace.path().join(".sofosignore"), "secrets/\n").unwrap();
+
+    let executor = ToolExecutor::new(
+        workspace.path().to_path_buf(),
+        None,
+        None,
+        SandboxMode::Sandboxed,
+        false,
+    )
+    .unwrap();
+
+    for (tool, input) in [
+        ("read_file", json!({"path": "secrets/key.rs"})),
+        ("file_info", json!({"path": "secrets/key.rs"})),
+        ("list_directory", json!({"path": "secrets"})),
+    ] {
+        let err = executor
+            .execute(tool, &input)
+            .await
+            .unwrap_err()
+            .to_string();
+        assert!(
+            err.contains("path is ignored by .sofosignore"),
+            "{tool}: {err}"
+        );
+    }
+
+    let text = executor
+        .execute(
+            "glob_files",
+            &json!({"pattern": "**/*.rs", "include_ignored": true}),
+        )
+        .await
+        .unwrap()
+        .text()
+        .to_string();
+    assert!(text.contains("main.rs"), "{text}");
+    assert!(!text.contains("key.rs"), "{text}");
+}
+
+#[tokio::test]
+async fn search_and_replace_previews_then_applies_across_files() {
+    let workspace = tempdir().unwrap();
+    let root = workspace.path();
+    std::fs::write(root.join("a.rs"), "old_name(); old_name;\n").unwrap();
+    std::fs::write(root.join("b.rs"), "let x = old_name;\n").unwrap();
+    std::fs::write(root.join("notes.txt"), "old_name\n").unwrap();
+
+    let executor = ToolExecutor::new(
+        root.to_path_buf(),
+        None,
+        None,
+        SandboxMode::Sandboxed,
+        false,
+    )
+    .unwrap();
+    if !executor.has_code_search() {
+        return;
+    }
+
+    let input = json!({"pattern": "\\bold_name\\b", "replacement": "new_name", "glob": "*.rs"});
+    let preview = executor
+        .execute("search_and_replace", &input)
+        .await
+        .unwrap()
+        .text()
+        .to_string();
+    assert!(preview.contains("3 matches in 2 files"), "{preview}");
+    assert!(preview.contains("Preview only"), "{preview}");
+    assert_eq!(
+        std::fs::read_to_string(root.join("a.rs")).unwrap(),
+        "old_name(); old_name;\n"
+    );
+
+    let mut apply = input.clone();
+    apply["confirm"] = json!(true);
+    let applied = executor
+        .execute("search_and_replace", &apply)
+        .await
+        .unwrap()
+        .text()
+        .to_string();
+    assert!(applied.contains("M a.rs\nM b.rs"), "{applied}");
+    assert_eq!(
+        std::fs::read_to_string(root.join("a.rs")).unwrap(),
+        "new_name(); new_name;\n"
+    );
+    assert_eq!(
+        std::fs::read_to_string(root.join("b.rs")).unwrap(),
+        "let x = new_name;\n"
+    );
+    assert_eq!(
+        std::fs::read_to_string(root.join("notes.txt")).unwrap(),
+        "old_name\n"
+    );
+}
+
 #[tokio::test]
 async fn test_glob_files_gates_external_path_through_permissions() {
     // `base = ".."` used to pass through `workspace.join("..")` = workspace
@@ -2462,3 +2617,666 @@ async fn set_mode_propagates_to_bash_sandbox() {
         "the confined command should write inside the workspace"
     );
 }
+
+#[tokio::test]
+async fn undo_edit_steps_back_through_backups() {
+    let workspace = tempdir().unwrap();
+    let mut executor = ToolExecutor::new(
+        workspace.path().to_path_buf(),
+        None,
+        None,
+        SandboxMode::Sandboxed,
+        false,
+    )
+    .unwrap();
+    let offers_undo = |tools: Vec<crate::api::Tool>| {
+        tools.iter().any(
+            |tool| matches!(tool, crate::api::Tool::Regular { name, .. } if name == "undo_edit"),
+        )
+    };
+    assert!(!offers_undo(executor.get_available_tools().await));
+    executor.set_backups(true);
+    assert!(offers_undo(executor.get_available_tools().await));
+
+    for content in ["one\n", "two\n", "three\n"] {
+        executor
+            .execute("write_file", &json!({"path": "a.txt", "content": content}))
+            .await
+            .unwrap();
+    }
+    let file = workspace.path().join("a.txt");
+
+    let result = executor
+        .execute("undo_edit", &json!({"path": "a.txt"}))
+        .await
+        .unwrap();
+    assert_eq!(std::fs::read_to_string(&file).unwrap(), "two\n");
+    assert!(
+        result.text().contains("Reverted changes:"),
+        "{}",
+        result.text()
+    );
+    assert!(result.text().contains("- three"), "{}", result.text());
+    assert!(result.text().contains("+ two"), "{}", result.text());
+
+    executor
+        .execute("undo_edit", &json!({"path": "a.txt"}))
+        .await
+        .unwrap();
+    assert_eq!(std::fs::read_to_string(&file).unwrap(), "one\n");
+
+    let err = executor
+        .execute("undo_edit", &json!({"path": "a.txt"}))
+        .await
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("No backup found for 'a.txt'"), "{err}");
+}
+
+#[tokio::test]
+async fn undo_edit_refuses_files_hidden_from_reads() {
+    let workspace = tempdir().unwrap();
+    let mut executor = ToolExecutor::new(
+        workspace.path().to_path_buf(),
+        None,
+        None,
+        SandboxMode::Sandboxed,
+        false,
+    )
+    .unwrap();
+    executor.set_backups(true);
+    for content in ["KEY=old\n", "KEY=new\n"] {
+        executor
+            .execute("write_file", &json!({"path": ".env", "content": content}))
+            .await
+            .unwrap();
+    }
+    std::fs::write(workspace.path().join(".sofosignore"), ".env\n").unwrap();
+
+    let err = executor
+        .execute("undo_edit", &json!({"path": ".env"}))
+        .await
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("ignored by .sofosignore"), "{err}");
+    assert_eq!(
+        std::fs::read_to_string(workspace.path().join(".env")).unwrap(),
+        "KEY=new\n"
+    );
+}
+
+/// A repository at `root` with one commit, then a modified, a staged, and
+/// an untracked file. False when git cannot run here.
+fn repository_with_changes(root: &std::path::Path) -> bool {
+    let git = |args: &[&str]| {
+        std::process::Command::new("git")
+            .args([
+                "-c",
+                "user.name=t",
+                "-c",
+                "user.email=t@t",
+                "-c",
+                "commit.gpgsign=false",
+            ])
+            .args(args)
+            .current_dir(root)
+            .output()
+    };
+    if git(&["init", "-q"]).is_err() {
+        return false;
+    }
+    std::fs::write(root.join("tracked.txt"), "one\n").unwrap();
+    git(&["add", "tracked.txt"]).unwrap();
+    git(&["commit", "-q", "-m", "init"]).unwrap();
+    std::fs::write(root.join("tracked.txt"), "two\n").unwrap();
+    std::fs::write(root.join("staged.txt"), "new\n").unwrap();
+    git(&["add", "staged.txt"]).unwrap();
+    std::fs::write(root.join("loose.txt"), "x\n").unwrap();
+    true
+}
+
+#[tokio::test]
+async fn git_status_groups_changes_in_a_repository() {
+    let workspace = tempdir().unwrap();
+    let root = workspace.path();
+    if !repository_with_changes(root) {
+        return;
+    }
+
+    let executor =
+        ToolExecutor::new(root.to_path_buf(), None, None, SandboxMode::ReadOnly, false).unwrap();
+    let result = executor.execute("git_status", &json!({})).await.unwrap();
+    let text = result.text();
+
+    assert!(text.contains("Staged (1):\n  added: staged.txt"), "{text}");
+    assert!(
+        text.contains("Unstaged (1):\n  modified: tracked.txt"),
+        "{text}"
+    );
+    assert!(text.contains("Untracked (1):\n  loose.txt"), "{text}");
+    assert!(
+        text.contains("Unstaged diff:\n tracked.txt | 2 +-"),
+        "{text}"
+    );
+}
+
+#[test]
+fn uncommitted_changes_pair_head_with_the_working_tree() {
+    let workspace = tempdir().unwrap();
+    let root = workspace.path();
+    if !repository_with_changes(root) {
+        return;
+    }
+
+    let mut changes = crate::tools::git::uncommitted_changes(root)
+        .unwrap()
+        .expect("a repository");
+    changes.sort_by(|a, b| a.path.cmp(&b.path));
+    let versions: Vec<(&str, &str, &str)> = changes
+        .iter()
+        .map(|c| (c.path.as_str(), c.original.as_str(), c.current.as_str()))
+        .collect();
+    assert_eq!(
+        versions,
+        [
+            ("loose.txt", "", "x\n"),
+            ("staged.txt", "", "new\n"),
+            ("tracked.txt", "one\n", "two\n"),
+        ]
+    );
+
+    let outside = tempdir().unwrap();
+    assert!(
+        crate::tools::git::uncommitted_changes(outside.path())
+            .unwrap()
+            .is_none()
+    );
+}
+
+#[tokio::test]
+async fn dry_run_previews_mutating_tools_and_runs_read_only_ones() {
+    let workspace = tempdir().unwrap();
+    std::fs::write(workspace.path().join("notes.txt"), "keep me\n").unwrap();
+    let mut executor = ToolExecutor::new(
+        workspace.path().to_path_buf(),
+        None,
+        None,
+        SandboxMode::Sandboxed,
+        false,
+    )
+    .unwrap();
+    executor.set_dry_run(true);
+
+    let result = executor
+        .execute("write_file", &json!({"path": "new.txt", "content": "x"}))
+        .await
+        .unwrap();
+    assert!(
+        result
+            .text()
+            .starts_with("[dry-run] would execute write_file with {"),
+        "{}",
+        result.text()
+    );
+    assert!(!workspace.path().join("new.txt").exists());
+
+    let result = executor
+        .execute("execute_bash", &json!({"command": "rm notes.txt"}))
+        .await
+        .unwrap();
+    assert!(
+        result
+            .text()
+            .starts_with("[dry-run] would execute execute_bash")
+    );
+    assert!(workspace.path().join("notes.txt").exists());
+
+    let result = executor
+        .execute("read_file", &json!({"path": "notes.txt"}))
+        .await
+        .unwrap();
+    assert!(result.text().contains("keep me"));
+}
+
+fn smart_paths_executor(workspace: &std::path::Path) -> ToolExecutor {
+    let mut executor = ToolExecutor::new(
+        workspace.to_path_buf(),
+        None,
+        None,
+        SandboxMode::Sandboxed,
+        false,
+    )
+    .unwrap();
+    executor.set_smart_paths(true);
+    executor
+}
+
+#[tokio::test]
+async fn smart_paths_resolves_unique_basename() {
+    let workspace = tempdir().unwrap();
+    let nested = workspace.path().join("src").join("config");
+    std::fs::create_dir_all(&nested).unwrap();
+    std::fs::write(nested.join("settings.rs"), "fn settings() {}\n").unwrap();
+
+    let executor = smart_paths_executor(workspace.path());
+    let result = executor
+        .execute("read_file", &json!({"path": "settings.rs"}))
+        .await
+        .unwrap();
+    assert!(result.text().contains("fn settings() {}"));
+    assert!(result.text().contains("src/config/settings.rs"));
+}
+
+#[tokio::test]
+async fn smart_paths_lists_candidates_when_ambiguous() {
+    let workspace = tempdir().unwrap();
+    for dir in ["a", "b"] {
+        let path = workspace.path().join(dir);
+        std::fs::create_dir_all(&path).unwrap();
+        std::fs::write(path.join("mod.rs"), "").unwrap();
+    }
+    // Excluded build directories never contribute candidates.
+    let target = workspace.path().join("target");
+    std::fs::create_dir_all(&target).unwrap();
+    std::fs::write(target.join("mod.rs"), "").unwrap();
+
+    let executor = smart_paths_executor(workspace.path());
+    let err = executor
+        .execute("write_file", &json!({"path": "mod.rs", "content": "x"}))
+        .await
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("a/mod.rs"), "{err}");
+    assert!(err.contains("b/mod.rs"), "{err}");
+    assert!(!err.contains("target/mod.rs"), "{err}");
+    assert!(!workspace.path().join("mod.rs").exists());
+}
+
+#[tokio::test]
+async fn smart_paths_off_keeps_paths_literal() {
+    let workspace = tempdir().unwrap();
+    let src = workspace.path().join("src");
+    std::fs::create_dir_all(&src).unwrap();
+    std::fs::write(src.join("lib.rs"), "").unwrap();
+
+    let executor = ToolExecutor::new(
+        workspace.path().to_path_buf(),
+        None,
+        None,
+        SandboxMode::Sandboxed,
+        false,
+    )
+    .unwrap();
+    let result = executor
+        .execute("read_file", &json!({"path": "lib.rs"}))
+        .await;
+    assert!(result.is_err());
+}
+
+#[test]
+fn morph_failure_result_follows_the_fallback_setting() {
+    let workspace = tempdir().unwrap();
+    let mut executor = ToolExecutor::new(
+        workspace.path().to_path_buf(),
+        None,
+        None,
+        SandboxMode::Sandboxed,
+        false,
+    )
+    .unwrap();
+
+    let text = |result: ToolExecutionResult| match result {
+        ToolExecutionResult::Text(text) => text,
+        other => panic!("expected text result, got {other:?}"),
+    };
+
+    let instruct = text(executor.morph_failure_result("morph failed".to_string(), "a.rs"));
+    assert!(instruct.contains("'a.rs' was NOT modified"));
+    assert!(instruct.contains("edit_file"));
+
+    executor.set_morph_fallback(crate::config::MorphFallback::None);
+    let bare = text(executor.morph_failure_result("morph failed".to_string(), "a.rs"));
+    assert_eq!(bare, "morph failed. The file 'a.rs' was NOT modified.");
+}
+
+#[tokio::test]
+async fn scope_narrows_relative_paths_to_a_subdirectory() {
+    let workspace = tempdir().unwrap();
+    std::fs::create_dir_all(workspace.path().join("crates/core")).unwrap();
+    std::fs::write(workspace.path().join("crates/core/lib.rs"), "core").unwrap();
+    std::fs::write(workspace.path().join("top.txt"), "top").unwrap();
+    let mut executor = ToolExecutor::new(
+        workspace.path().to_path_buf(),
+        None,
+        None,
+        SandboxMode::Sandboxed,
+        false,
+    )
+    .unwrap();
+
+    let scope = executor.set_scope(Some("crates/core")).unwrap();
+    assert_eq!(scope, std::path::Path::new("crates/core"));
+    assert_eq!(executor.scope(), Some(std::path::Path::new("crates/core")));
+
+    let read = executor
+        .execute("read_file", &json!({"path": "lib.rs"}))
+        .await
+        .unwrap();
+    assert!(read.text().contains("core"));
+
+    // The root-level file is now outside the scope and needs a grant,
+    // which a non-interactive session cannot give.
+    assert!(
+        executor
+            .execute("read_file", &json!({"path": "../../top.txt"}))
+            .await
+            .is_err()
+    );
+
+    executor.set_scope(None).unwrap();
+    assert_eq!(executor.scope(), None);
+    assert!(
+        executor
+            .execute("read_file", &json!({"path": "top.txt"}))
+            .await
+            .is_ok()
+    );
+}
+
+#[test]
+fn scope_rejects_missing_files_and_paths_outside_the_workspace() {
+    let workspace = tempdir().unwrap();
+    std::fs::write(workspace.path().join("file.txt"), "x").unwrap();
+    let mut executor = ToolExecutor::new(
+        workspace.path().to_path_buf(),
+        None,
+        None,
+        SandboxMode::Sandboxed,
+        false,
+    )
+    .unwrap();
+
+    assert!(executor.set_scope(Some("missing")).is_err());
+    assert!(executor.set_scope(Some("file.txt")).is_err());
+    assert!(executor.set_scope(Some("..")).is_err());
+    assert_eq!(executor.scope(), None);
+}
+
+#[tokio::test]
+async fn protected_paths_block_file_tool_changes() {
+    let workspace = tempdir().unwrap();
+    let root = workspace.path();
+    std::fs::write(root.join("Cargo.lock"), "# lock").unwrap();
+    std::fs::create_dir_all(root.join(".github/workflows")).unwrap();
+    std::fs::write(root.join(".github/workflows/ci.yml"), "on: push").unwrap();
+    std::fs::create_dir_all(root.join(".sofos")).unwrap();
+    std::fs::write(
+        root.join(".sofos/config.local.toml"),
+        "[permissions]\nprotected_paths = [\"db/schema.sql\"]\n",
+    )
+    .unwrap();
+    let executor = ToolExecutor::new(
+        root.to_path_buf(),
+        None,
+        None,
+        SandboxMode::Sandboxed,
+        false,
+    )
+    .unwrap();
+
+    let blocked = [
+        json!({"path": "Cargo.lock", "content": "x"}),
+        json!({"path": "db/schema.sql", "content": "x"}),
+    ];
+    for input in blocked {
+        let err = executor.execute("write_file", &input).await.unwrap_err();
+        assert!(err.to_string().contains("protected"), "{err}");
+    }
+
+    let err = executor
+        .execute(
+            "move_file",
+            &json!({"source": ".github", "destination": "ci-backup"}),
+        )
+        .await
+        .unwrap_err();
+    assert!(
+        err.to_string().contains(".github/workflows/ci.yml"),
+        "{err}"
+    );
+    assert!(root.join(".github/workflows/ci.yml").exists());
+
+    // Unprotected files are unaffected.
+    executor
+        .execute("write_file", &json!({"path": "notes.txt", "content": "ok"}))
+        .await
+        .unwrap();
+    assert_eq!(
+        std::fs::read_to_string(root.join("Cargo.lock")).unwrap(),
+        "# lock"
+    );
+}
+
+#[tokio::test]
+async fn file_changes_are_collected_per_path_until_taken() {
+    let workspace = tempdir().unwrap();
+    std::fs::write(workspace.path().join("lib.rs"), "a\nb\n").unwrap();
+    let executor = ToolExecutor::new(
+        workspace.path().to_path_buf(),
+        None,
+        None,
+        SandboxMode::Sandboxed,
+        false,
+    )
+    .unwrap();
+
+    executor
+        .execute(
+            "write_file",
+            &json!({"path": "notes.txt", "content": "one\ntwo\n"}),
+        )
+        .await
+        .unwrap();
+    for (old, new) in [("a", "x\ny"), ("b", "z")] {
+        executor
+            .execute(
+                "edit_file",
+                &json!({"path": "lib.rs", "old_string": old, "new_string": new}),
+            )
+            .await
+            .unwrap();
+    }
+
+    let summary = executor.take_file_changes().render().unwrap();
+    assert!(summary.contains("notes.txt"), "{summary}");
+    assert!(summary.contains("+2"), "{summary}");
+    assert!(summary.contains("lib.rs"), "{summary}");
+    assert!(summary.contains("+3"), "{summary}");
+    assert!(summary.contains("-2"), "{summary}");
+    assert_eq!(summary.matches("lib.rs").count(), 1, "{summary}");
+    assert!(executor.take_file_changes().is_empty());
+}
+
+#[tokio::test]
+async fn read_input_serves_named_inputs_only() {
+    let workspace = tempdir().unwrap();
+    let mut executor = ToolExecutor::new(
+        workspace.path().to_path_buf(),
+        None,
+        None,
+        SandboxMode::Sandboxed,
+        false,
+    )
+    .unwrap();
+    let offered = |tools: &[crate::api::Tool]| {
+        tools
+            .iter()
+            .any(|t| matches!(t, crate::api::Tool::Regular { name, .. } if name == "read_input"))
+    };
+    assert!(!offered(&executor.get_available_tools().await));
+
+    let mut inputs = crate::tools::inputs::NamedInputs::new();
+    inputs.insert("logs".to_string(), "line one\nline two\n".to_string());
+    executor.set_inputs(inputs);
+    assert!(offered(&executor.get_available_tools().await));
+
+    let result = executor
+        .execute("read_input", &json!({"name": "logs"}))
+        .await
+        .unwrap();
+    assert_eq!(result.text(), "line one\nline two\n");
+
+    let err = executor
+        .execute("read_input", &json!({"name": "/etc/passwd"}))
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("Available inputs: logs"), "{err}");
+}
+
+#[tokio::test]
+async fn read_file_pages_with_offset_and_limit() {
+    let workspace = tempdir().unwrap();
+    let lines: String = (1..=10).map(|n| format!("line {n}\n")).collect();
+    std::fs::write(workspace.path().join("big.log"), lines).unwrap();
+    let executor = ToolExecutor::new(
+        workspace.path().to_path_buf(),
+        None,
+        None,
+        SandboxMode::Sandboxed,
+        false,
+    )
+    .unwrap();
+
+    let result = executor
+        .execute(
+            "read_file",
+            &json!({"path": "big.log", "offset": 4, "limit": 3}),
+        )
+        .await
+        .unwrap();
+    assert!(
+        result
+            .text()
+            .contains("(lines 4-6) (file has 4 more lines)")
+    );
+    assert_eq!(
+        crate::tools::read_file_body(result.text()),
+        "line 4\nline 5\nline 6\n"
+    );
+
+    for bad in [json!(0), json!(-2), json!("3")] {
+        let err = executor
+            .execute("read_file", &json!({"path": "big.log", "offset": bad}))
+            .await;
+        assert!(err.is_err(), "offset {bad} should be rejected");
+    }
+}
+
+#[tokio::test]
+async fn read_symbol_returns_one_definition_or_candidates() {
+    let workspace = tempdir().unwrap();
+    let root = workspace.path();
+    std::fs::write(
+        root.join("lib.rs"),
+        "use std::fmt;\n\n/// Adds.\nfn add(a: i32, b: i32) -> i32 {\n    a + b\n}\n\nfn sub() {}\n",
+    )
+    .unwrap();
+    std::fs::write(root.join("notes.txt"), "fn add() {}\n").unwrap();
+
+    let executor = ToolExecutor::new(
+        root.to_path_buf(),
+        None,
+        None,
+        SandboxMode::Sandboxed,
+        false,
+    )
+    .unwrap();
+
+    let result = executor
+        .execute("read_symbol", &json!({"path": "lib.rs", "symbol": "add"}))
+        .await
+        .unwrap();
+    assert_eq!(
+        result.text(),
+        "File content of 'lib.rs' (lines 3-6) (file has 2 more lines):\n\n/// Adds.\nfn add(a: i32, b: i32) -> i32 {\n    a + b\n}\n"
+    );
+
+    let missing = executor
+        .execute("read_symbol", &json!({"path": "lib.rs", "symbol": "mul"}))
+        .await
+        .unwrap_err()
+        .to_string();
+    assert!(missing.contains("Symbol 'mul' not found"), "{missing}");
+    assert!(missing.contains("fn add (line 4)"), "{missing}");
+
+    let unsupported = executor
+        .execute(
+            "read_symbol",
+            &json!({"path": "notes.txt", "symbol": "add"}),
+        )
+        .await
+        .unwrap_err()
+        .to_string();
+    assert!(unsupported.contains("supports Rust"), "{unsupported}");
+}
+
+#[tokio::test]
+async fn run_tests_without_a_build_file_asks_for_a_command() {
+    let workspace = tempdir().unwrap();
+    let executor = ToolExecutor::new(
+        workspace.path().to_path_buf(),
+        None,
+        None,
+        SandboxMode::Sandboxed,
+        false,
+    )
+    .unwrap();
+
+    let err = executor
+        .execute("run_tests", &json!({}))
+        .await
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("No test command detected"), "{err}");
+    assert!(err.contains("Cargo.toml"), "{err}");
+}
+
+#[tokio::test]
+async fn compact_results_point_repeat_reads_at_the_earlier_one() {
+    let workspace = tempdir().unwrap();
+    let root = workspace.path();
+    std::fs::write(root.join("a.rs"), "fn a() {}\n").unwrap();
+
+    let mut executor = ToolExecutor::new(
+        root.to_path_buf(),
+        None,
+        None,
+        SandboxMode::Sandboxed,
+        false,
+    )
+    .unwrap();
+    executor.set_compact_results(true);
+    executor.begin_turn();
+    let read = || async {
+        executor
+            .execute("read_file", &json!({"path": "a.rs"}))
+            .await
+            .unwrap()
+    };
+
+    assert_eq!(read().await.text(), "a.rs:\n\nfn a() {}\n");
+    executor.begin_turn();
+    let repeat = read().await;
+    assert!(
+        repeat
+            .text()
+            .starts_with("a.rs: unchanged since it was read in turn 1"),
+        "a repeat read of the same content is not returned again"
+    );
+    assert!(
+        repeat.renders_own_display(),
+        "the note is not previewed or counted as file content"
+    );
+    std::fs::write(root.join("a.rs"), "fn b() {}\n").unwrap();
+    assert_eq!(read().await.text(), "a.rs:\n\nfn b() {}\n");
+}
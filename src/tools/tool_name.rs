@@ -6,66 +6,90 @@ use serde_json::Value;
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum ToolName {
     ReadFile,
+    ReadSymbol,
     WriteFile,
     ListDirectory,
+    Tree,
     CreateDirectory,
     DeleteFile,
     DeleteDirectory,
     MoveFile,
     CopyFile,
     ExecuteBash,
+    RunTests,
     SearchCode,
+    SearchAndReplace,
     EditFile,
     GlobFiles,
     MorphEditFile,
     UpdatePlan,
     ViewImage,
+    ReadInput,
     WebFetch,
     WebSearch,
+    UndoEdit,
+    GitStatus,
+    FileInfo,
 }
 
 impl ToolName {
     pub fn as_str(&self) -> &'static str {
         match self {
             ToolName::ReadFile => "read_file",
+            ToolName::ReadSymbol => "read_symbol",
             ToolName::WriteFile => "write_file",
             ToolName::ListDirectory => "list_directory",
+            ToolName::Tree => "tree",
             ToolName::CreateDirectory => "create_directory",
             ToolName::DeleteFile => "delete_file",
             ToolName::DeleteDirectory => "delete_directory",
             ToolName::MoveFile => "move_file",
             ToolName::CopyFile => "copy_file",
             ToolName::ExecuteBash => "execute_bash",
+            ToolName::RunTests => "run_tests",
             ToolName::SearchCode => "search_code",
+            ToolName::SearchAndReplace => "search_and_replace",
             ToolName::EditFile => "edit_file",
             ToolName::GlobFiles => "glob_files",
             ToolName::MorphEditFile => "morph_edit_file",
             ToolName::UpdatePlan => "update_plan",
             ToolName::ViewImage => "view_image",
+            ToolName::ReadInput => "read_input",
             ToolName::WebFetch => "web_fetch",
             ToolName::WebSearch => "web_search",
+            ToolName::UndoEdit => "undo_edit",
+            ToolName::GitStatus => "git_status",
+            ToolName::FileInfo => "file_info",
         }
     }
 
     pub fn from_str(s: &str) -> Result<Self> {
         match s {
             "read_file" => Ok(ToolName::ReadFile),
+            "read_symbol" => Ok(ToolName::ReadSymbol),
             "write_file" => Ok(ToolName::WriteFile),
             "list_directory" => Ok(ToolName::ListDirectory),
+            "tree" => Ok(ToolName::Tree),
             "create_directory" => Ok(ToolName::CreateDirectory),
             "delete_file" => Ok(ToolName::DeleteFile),
             "delete_directory" => Ok(ToolName::DeleteDirectory),
             "move_file" => Ok(ToolName::MoveFile),
             "copy_file" => Ok(ToolName::CopyFile),
             "execute_bash" => Ok(ToolName::ExecuteBash),
+            "run_tests" => Ok(ToolName::RunTests),
             "search_code" => Ok(ToolName::SearchCode),
+            "search_and_replace" => Ok(ToolName::SearchAndReplace),
             "edit_file" => Ok(ToolName::EditFile),
             "glob_files" => Ok(ToolName::GlobFiles),
             "morph_edit_file" => Ok(ToolName::MorphEditFile),
             "update_plan" => Ok(ToolName::UpdatePlan),
             "view_image" => Ok(ToolName::ViewImage),
+            "read_input" => Ok(ToolName::ReadInput),
             "web_fetch" => Ok(ToolName::WebFetch),
             "web_search" => Ok(ToolName::WebSearch),
+            "undo_edit" => Ok(ToolName::UndoEdit),
+            "git_status" => Ok(ToolName::GitStatus),
+            "file_info" => Ok(ToolName::FileInfo),
             _ => Err(SofosError::ToolExecution(format!("Unknown tool: {}", s))),
         }
     }
@@ -79,13 +103,18 @@ impl ToolName {
     pub fn is_read_only_safe(&self) -> bool {
         match self {
             ToolName::ReadFile
+            | ToolName::ReadSymbol
             | ToolName::ListDirectory
+            | ToolName::Tree
             | ToolName::GlobFiles
             | ToolName::SearchCode
             | ToolName::UpdatePlan
             | ToolName::ViewImage
+            | ToolName::ReadInput
             | ToolName::WebFetch
-            | ToolName::WebSearch => true,
+            | ToolName::WebSearch
+            | ToolName::GitStatus
+            | ToolName::FileInfo => true,
             ToolName::WriteFile
             | ToolName::EditFile
             | ToolName::MorphEditFile
@@ -94,10 +123,32 @@ impl ToolName {
             | ToolName::DeleteDirectory
             | ToolName::MoveFile
             | ToolName::CopyFile
-            | ToolName::ExecuteBash => false,
+            | ToolName::SearchAndReplace
+            | ToolName::ExecuteBash
+            | ToolName::RunTests
+            | ToolName::UndoEdit => false,
         }
     }
 
+    /// Whether this tool runs a shell command: its header names the
+    /// command and its output may already have streamed to the terminal.
+    pub fn runs_shell_command(&self) -> bool {
+        matches!(self, ToolName::ExecuteBash | ToolName::RunTests)
+    }
+
+    /// First line of a paged `read_file` call, or `None` for a whole-file read.
+    fn read_window_start(tool_input: &Value) -> Option<u64> {
+        if tool_input.get("offset").is_none() && tool_input.get("limit").is_none() {
+            return None;
+        }
+        Some(
+            tool_input
+                .get("offset")
+                .and_then(|v| v.as_u64())
+                .unwrap_or(1),
+        )
+    }
+
     /// Render a one-line human summary of a completed tool call for the
     /// transcript UI. The four custom-shaped variants (read_file,
     /// list_directory, search_code, web_fetch) extract counts/paths from
@@ -123,6 +174,18 @@ impl ToolName {
                             file_path.bright_cyan()
                         )
                     }
+                } else if let Some(first) = Self::read_window_start(tool_input) {
+                    let last = first + content_lines - 1;
+                    if file_path.is_empty() {
+                        format!("Read lines {}-{}", first, last)
+                    } else {
+                        format!(
+                            "Read lines {}-{} from {}",
+                            first,
+                            last,
+                            file_path.bright_cyan()
+                        )
+                    }
                 } else if file_path.is_empty() {
                     format!("Read {} lines", content_lines)
                 } else {
@@ -160,12 +223,58 @@ impl ToolName {
                     format!("Found {} items in {}", item_count, path.bright_cyan())
                 }
             }
+            ToolName::Tree => {
+                let path = tool_input
+                    .get("path")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or(".");
+                // Everything between the heading and the closing count
+                // or truncation note is an entry.
+                let entries = output.lines().count().saturating_sub(2);
+                format!(
+                    "Listed {} entr{} under {}",
+                    entries,
+                    if entries == 1 { "y" } else { "ies" },
+                    path.bright_cyan()
+                )
+            }
+            ToolName::ReadSymbol => {
+                let field = |key: &str| tool_input.get(key).and_then(|v| v.as_str()).unwrap_or("");
+                let range = output
+                    .lines()
+                    .next()
+                    .and_then(|header| header.split_once("(lines "))
+                    .and_then(|(_, rest)| rest.split_once(')'))
+                    .map(|(range, _)| range);
+                match range {
+                    Some(range) => format!(
+                        "Read {} (lines {}) from {}",
+                        field("symbol"),
+                        range,
+                        field("path").bright_cyan()
+                    ),
+                    None => output.to_string(),
+                }
+            }
+            ToolName::ReadInput => {
+                let name = tool_input
+                    .get("name")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("");
+                let lines = output.lines().count();
+                format!(
+                    "Read {} line{} from input {}",
+                    lines,
+                    if lines == 1 { "" } else { "s" },
+                    name.bright_cyan()
+                )
+            }
             ToolName::WebFetch => {
                 let url = tool_input.get("url").and_then(|v| v.as_str()).unwrap_or("");
                 let char_count = output.len();
                 format!("Fetched {} ({} chars)", url.bright_cyan(), char_count)
             }
-            ToolName::ExecuteBash => {
+            ToolName::ExecuteBash | ToolName::RunTests => {
                 // Truncate the on-screen view. The full text still
                 // reaches the model via the tool_result block.
                 const MAX_DISPLAY_LINES: usize = 30;
@@ -247,10 +356,16 @@ mod tests {
     fn test_tool_name_roundtrip() {
         let tools = [
             ToolName::ReadFile,
+            ToolName::ReadSymbol,
             ToolName::WriteFile,
             ToolName::ExecuteBash,
             ToolName::MorphEditFile,
             ToolName::UpdatePlan,
+            ToolName::UndoEdit,
+            ToolName::SearchAndReplace,
+            ToolName::RunTests,
+            ToolName::GitStatus,
+            ToolName::FileInfo,
         ];
 
         for tool in &tools {
@@ -265,18 +380,37 @@ mod tests {
         assert!(ToolName::from_str("unknown_tool").is_err());
     }
 
+    #[test]
+    fn run_tests_is_shown_like_a_shell_command() {
+        assert!(ToolName::ExecuteBash.runs_shell_command());
+        assert!(ToolName::RunTests.runs_shell_command());
+        assert!(!ToolName::ReadFile.runs_shell_command());
+
+        let input = serde_json::json!({"command": "cargo test"});
+        assert_eq!(
+            crate::ui::UI::shell_command("run_tests", &input),
+            Some("cargo test")
+        );
+        assert_eq!(crate::ui::UI::shell_command("write_file", &input), None);
+    }
+
     #[test]
     fn read_only_safe_classification() {
         use ToolName::*;
         for tool in [
             ReadFile,
+            ReadSymbol,
             ListDirectory,
+            Tree,
             GlobFiles,
             SearchCode,
             UpdatePlan,
             ViewImage,
+            ReadInput,
             WebFetch,
             WebSearch,
+            GitStatus,
+            FileInfo,
         ] {
             assert!(tool.is_read_only_safe(), "{tool} should be read-only safe");
         }
@@ -289,7 +423,10 @@ mod tests {
             DeleteDirectory,
             MoveFile,
             CopyFile,
+            SearchAndReplace,
             ExecuteBash,
+            RunTests,
+            UndoEdit,
         ] {
             assert!(
                 !tool.is_read_only_safe(),
@@ -311,6 +448,26 @@ mod tests {
         assert!(summary.contains("src/foo.rs"));
     }
 
+    #[test]
+    fn read_file_summary_shows_the_line_range_of_a_paged_read() {
+        let output = crate::tools::format_read_file_window_output("big.log", 11, 12, 30, "a\nb\n");
+        let input = serde_json::json!({ "path": "big.log", "offset": 11, "limit": 2 });
+        let summary = ToolName::ReadFile.display_summary(&input, &output);
+        assert!(summary.contains("Read lines 11-12"), "got: {summary}");
+    }
+
+    #[test]
+    fn read_symbol_summary_names_the_symbol_and_its_lines() {
+        let output =
+            crate::tools::format_read_file_window_output("src/a.rs", 4, 9, 20, "fn a() {}\n");
+        let input = serde_json::json!({ "path": "src/a.rs", "symbol": "a" });
+        let summary = ToolName::ReadSymbol.display_summary(&input, &output);
+        assert!(
+            summary.contains("Read a (lines 4-9) from"),
+            "got: {summary}"
+        );
+    }
+
     #[test]
     fn read_file_summary_handles_empty_body() {
         let output = crate::tools::format_read_file_output("empty.txt", "");
@@ -4,13 +4,27 @@ use serde_json::json;
 fn read_file_tool() -> Tool {
     Tool::Regular {
         name: "read_file".to_string(),
-        description: "Read the contents of a file. Works within the workspace by default. Can also read files outside the workspace — the user will be prompted to allow access if not already configured. Files larger than 50 MB are rejected outright, and the returned content is itself capped (~64 KB) before being passed to the model, so for very large logs prefer `search_code` or page through with `execute_bash` (`head`, `sed -n 'A,Bp'`).".to_string(),
+        description: "Read the contents of a file. Works within the workspace by default. Can also read files outside the workspace — the user will be prompted to allow access if not already configured. Files larger than 50 MB are rejected outright, and the returned content is itself capped (~64 KB) before being passed to the model, so for very large files page through them with `offset` and `limit`, or use `search_code`. Binary files are refused with their type and size; set `as_hex` for a hex dump of the first 4 KB instead.".to_string(),
         input_schema: json!({
             "type": "object",
             "properties": {
                 "path": {
                     "type": "string",
                     "description": "The relative path to the file (e.g., 'src/main.rs'). Can also be absolute or ~/ paths for external files (user will be prompted for Read access)."
+                },
+                "offset": {
+                    "type": "integer",
+                    "minimum": 1,
+                    "description": "Optional 1-based line to start reading from. Use with `limit` to page through large files."
+                },
+                "limit": {
+                    "type": "integer",
+                    "minimum": 1,
+                    "description": "Optional number of lines to read. The result notes how many lines of the file follow."
+                },
+                "as_hex": {
+                    "type": "boolean",
+                    "description": "Return a hex dump of the first 4 KB instead of text. Use for binary files; cannot be combined with `offset` or `limit`."
                 }
             },
             "required": ["path"]
@@ -19,6 +33,28 @@ fn read_file_tool() -> Tool {
     }
 }
 
+fn read_symbol_tool() -> Tool {
+    Tool::Regular {
+        name: "read_symbol".to_string(),
+        description: "Read one definition from a source file instead of the whole file: a function, method, struct, enum, trait, impl block, class, interface, or type, with its doc comments, attributes, and decorators. Returns the definition with its line range. Supports Rust, Python, JavaScript, and TypeScript files. Qualify a name with its parent (`Type::method` or `Class.method`) when several definitions share it; if the name is missing or ambiguous, the error lists candidates with their lines.".to_string(),
+        input_schema: json!({
+            "type": "object",
+            "properties": {
+                "path": {
+                    "type": "string",
+                    "description": "The relative path to the source file (e.g., 'src/main.rs'). Can also be absolute or ~/ paths for external files (user will be prompted for Read access)."
+                },
+                "symbol": {
+                    "type": "string",
+                    "description": "The name to find, optionally qualified (e.g., 'parse', 'Parser::new', 'Store.load')."
+                }
+            },
+            "required": ["path", "symbol"]
+        }),
+        cache_control: None,
+    }
+}
+
 fn write_file_tool(has_morph: bool) -> Tool {
     let base = if has_morph {
         "Create a new file with the given content. For editing existing files, use morph_edit_file instead."
@@ -78,6 +114,35 @@ fn list_directory_tool() -> Tool {
     }
 }
 
+fn tree_tool() -> Tool {
+    let skipped = crate::tools::filesystem::TREE_SKIPPED_DIRS.join(", ");
+    Tool::Regular {
+        name: "tree".to_string(),
+        description: format!(
+            "Show the directory tree under a workspace path as an indented list, directories marked with a trailing '/'. Use this to get an overview of an unfamiliar project in one call instead of listing folders one by one. Respects .gitignore, never descends into {skipped}, and stops after {} entries.",
+            crate::tools::filesystem::MAX_TREE_ENTRIES
+        ),
+        input_schema: json!({
+            "type": "object",
+            "properties": {
+                "path": {
+                    "type": "string",
+                    "description": "Directory to show, relative to the workspace root. Default: '.'."
+                },
+                "max_depth": {
+                    "type": "integer",
+                    "minimum": 1,
+                    "description": format!(
+                        "How many levels below `path` to show. Default: {}.",
+                        crate::tools::filesystem::DEFAULT_TREE_DEPTH
+                    )
+                }
+            }
+        }),
+        cache_control: None,
+    }
+}
+
 fn create_directory_tool() -> Tool {
     Tool::Regular {
         name: "create_directory".to_string(),
@@ -116,7 +181,7 @@ fn openai_web_search_tool() -> Tool {
 fn execute_bash_tool() -> Tool {
     Tool::Regular {
         name: "execute_bash".to_string(),
-        description: "Execute a bash command in the workspace. Use the shell freely for project work — builds, tests, scripts, and creating, overwriting, or editing files inside the workspace are all expected and safe. When the sandbox is on, commands run confined by the operating system: their writes cannot leave the workspace and they have no network access. To run one command outside the sandbox, set sandbox_permissions to \"require_escalated\" with a short justification — but this is honored only when the active preset permits an up-front sandbox lift and is refused otherwise, so follow the current mode preamble for the active preset's escalation behavior. Commands may reference external absolute or ~/ paths (the user is prompted for access). Parent directory traversal (..) is always blocked. Do not run irreversible or system-wide commands (e.g., rm -rf, rm, rmdir, dd, mkfs*, fdisk/parted, wipefs, chmod/chown -R on broad paths, truncate, :>, >/dev/sd*, kill -9 on system services); if one seems genuinely necessary, stop and request explicit confirmation first.".to_string(),
+        description: "Execute a bash command in the workspace. Use the shell freely for project work — builds, tests, scripts, and creating, overwriting, or editing files inside the workspace are all expected and safe. When the sandbox is on, commands run confined by the operating system: their writes cannot leave the workspace and they have no network access. To run one command outside the sandbox, set sandbox_permissions to \"require_escalated\" with a short justification — but this is honored only when the active preset permits an up-front sandbox lift and is refused otherwise, so follow the current mode preamble for the active preset's escalation behavior. Commands may reference external absolute or ~/ paths (the user is prompted for access). Parent directory traversal (..) is always blocked. `cd` is only accepted as a leading `cd <subdir> && <command>` that runs the command from a workspace subdirectory. Do not run irreversible or system-wide commands (e.g., rm -rf, rm, rmdir, dd, mkfs*, fdisk/parted, wipefs, chmod/chown -R on broad paths, truncate, :>, >/dev/sd*, kill -9 on system services); if one seems genuinely necessary, stop and request explicit confirmation first.".to_string(),
         input_schema: json!({
             "type": "object",
             "properties": {
@@ -140,6 +205,23 @@ fn execute_bash_tool() -> Tool {
     }
 }
 
+fn run_tests_tool() -> Tool {
+    Tool::Regular {
+        name: "run_tests".to_string(),
+        description: "Run the project's tests and get a pass/fail summary. The test command is picked from the build file at the workspace root (Cargo.toml: cargo test, go.mod: go test ./..., package.json: npm, pnpm, yarn, or bun test by lockfile, pyproject.toml or setup.py: python -m pytest, pom.xml, build.gradle, mix.exs). Pass `command` to run a different one, such as a single test or a subdirectory with `cd <subdir> && <command>`. Runs through the same permission checks and sandbox as execute_bash. A failed run includes the full output.".to_string(),
+        input_schema: json!({
+            "type": "object",
+            "properties": {
+                "command": {
+                    "type": "string",
+                    "description": "Optional test command to run instead of the detected one (e.g., 'cargo test parser', 'npm test -- auth')."
+                }
+            }
+        }),
+        cache_control: None,
+    }
+}
+
 fn delete_file_tool() -> Tool {
     Tool::Regular {
         name: "delete_file".to_string(),
@@ -332,7 +414,7 @@ fn view_image_tool() -> Tool {
     let description = format!(
         "Attach an image to the conversation so you can see it. Use this when the user references a screenshot, diagram, photo, or other image by path or URL. \
          For a folder of images, call list_directory first to discover the files, then call view_image once per image. Passing a folder directly is rejected with a hint to do that. \
-         Supports {formats} up to {max_mb} MB per file; larger images are resized to fit within {max_dim} pixels on the long side before they reach the model. \
+         Supports {formats} up to {max_mb} MB per file; larger images are resized to fit within {max_dim} pixels on the long side (or the user's configured bound) before they reach the model. \
          Animated GIFs are decoded but only the first frame is sent — for an animation, ask the user for a still frame instead. \
          Local paths can be workspace-relative, absolute, or use ~/; external paths prompt for Read access the first time. \
          HTTP/HTTPS URLs are passed through to the model provider, which fetches them on its side. \
@@ -378,6 +460,37 @@ fn web_fetch_tool() -> Tool {
     }
 }
 
+fn git_status_tool() -> Tool {
+    Tool::Regular {
+        name: "git_status".to_string(),
+        description: "Show what has changed in the git working tree: the current branch, staged, unstaged, untracked, and conflicted files, and diff stats for staged and unstaged changes. Use this instead of running git status through execute_bash. Reports when the workspace is not a git repository.".to_string(),
+        input_schema: json!({
+            "type": "object",
+            "properties": {},
+            "additionalProperties": false
+        }),
+        cache_control: None,
+    }
+}
+
+fn file_info_tool() -> Tool {
+    Tool::Regular {
+        name: "file_info".to_string(),
+        description: "Report a file's size, line count, estimated token count, and language without returning its content. Use this before read_file to decide whether to read a large file whole, page it with `offset` and `limit`, or search it with `search_code`. Binary files report their size and type only.".to_string(),
+        input_schema: json!({
+            "type": "object",
+            "properties": {
+                "path": {
+                    "type": "string",
+                    "description": "The relative path to the file (e.g., 'src/main.rs'). Can also be absolute or ~/ paths for external files (user will be prompted for Read access)."
+                }
+            },
+            "required": ["path"]
+        }),
+        cache_control: None,
+    }
+}
+
 fn morph_edit_file_tool() -> Tool {
     // Schema matches the official Morph Fast Apply tool definition:
     // https://docs.morphllm.com/sdk/components/fast-apply
@@ -423,16 +536,21 @@ fn morph_edit_file_tool() -> Tool {
 pub fn get_all_tools() -> Vec<Tool> {
     vec![
         list_directory_tool(),
+        tree_tool(),
         read_file_tool(),
+        read_symbol_tool(),
         write_file_tool(false),
         edit_file_tool(),
         glob_files_tool(),
+        git_status_tool(),
+        file_info_tool(),
         create_directory_tool(),
         delete_file_tool(),
         delete_directory_tool(),
         move_file_tool(),
         copy_file_tool(),
         execute_bash_tool(),
+        run_tests_tool(),
         update_plan_tool(),
         view_image_tool(),
         web_fetch_tool(),
@@ -444,16 +562,21 @@ pub fn get_all_tools() -> Vec<Tool> {
 pub fn get_all_tools_with_morph() -> Vec<Tool> {
     vec![
         list_directory_tool(),
+        tree_tool(),
         read_file_tool(),
+        read_symbol_tool(),
         write_file_tool(true),
         edit_file_tool(),
         glob_files_tool(),
+        git_status_tool(),
+        file_info_tool(),
         create_directory_tool(),
         delete_file_tool(),
         delete_directory_tool(),
         move_file_tool(),
         copy_file_tool(),
         execute_bash_tool(),
+        run_tests_tool(),
         morph_edit_file_tool(),
         update_plan_tool(),
         view_image_tool(),
@@ -466,8 +589,12 @@ pub fn get_all_tools_with_morph() -> Vec<Tool> {
 pub fn get_read_only_tools() -> Vec<Tool> {
     vec![
         list_directory_tool(),
+        tree_tool(),
         read_file_tool(),
+        read_symbol_tool(),
         glob_files_tool(),
+        git_status_tool(),
+        file_info_tool(),
         update_plan_tool(),
         view_image_tool(),
         web_fetch_tool(),
@@ -478,11 +605,57 @@ pub fn get_read_only_tools() -> Vec<Tool> {
     ]
 }
 
+/// Add `undo_edit` to an existing tool list. Offered only with
+/// `--backups`, which is what gives it something to restore.
+pub fn add_undo_edit_tool(tools: &mut Vec<Tool>) {
+    tools.push(Tool::Regular {
+        name: "undo_edit".to_string(),
+        description: "Revert the last overwrite of a file inside the workspace by restoring its most recent backup, and show the lines that changed back. Call it again to step further back. Backups exist only for files overwritten by write_file, edit_file, or morph_edit_file this way, not for new files or changes made through bash.".to_string(),
+        input_schema: json!({
+            "type": "object",
+            "properties": {
+                "path": {
+                    "type": "string",
+                    "description": "The relative path to the file to restore (e.g., 'src/main.rs')."
+                }
+            },
+            "required": ["path"],
+            "additionalProperties": false
+        }),
+        cache_control: None,
+    });
+}
+
+/// Add `read_input` to an existing tool list, naming the inputs
+/// supplied with `--input`.
+pub fn add_read_input_tool(tools: &mut Vec<Tool>, names: &[&str]) {
+    tools.push(Tool::Regular {
+        name: "read_input".to_string(),
+        description: format!(
+            "Read a named input the user supplied when starting the session, such as logs or data piped in from outside the project. Inputs are read by name, not by path. Available inputs: {}.",
+            names.join(", ")
+        ),
+        input_schema: json!({
+            "type": "object",
+            "properties": {
+                "name": {
+                    "type": "string",
+                    "enum": names,
+                    "description": "The input name."
+                }
+            },
+            "required": ["name"],
+            "additionalProperties": false
+        }),
+        cache_control: None,
+    });
+}
+
 /// Add code search tool to an existing tool list
 pub fn add_code_search_tool(tools: &mut Vec<Tool>) {
     let excludes = crate::tools::codesearch::default_exclude_dirs_human();
     let tool_description = format!(
-        "Search for patterns in code using ripgrep. Supports regex patterns and file type filtering. Fast search across the entire codebase. By default skips build/vendored directories ({excludes}) and respects .gitignore; set include_ignored=true to search everywhere."
+        "Search for patterns in code using ripgrep. Supports regex patterns and file type filtering. Fast search across the entire codebase. By default skips build/vendored directories ({excludes}) and respects .gitignore; set include_ignored=true to search everywhere. Hidden files are skipped unless include_hidden=true."
     );
     let max_results_description = format!(
         "Maximum results per file (default: {})",
@@ -492,6 +665,11 @@ pub fn add_code_search_tool(tools: &mut Vec<Tool>) {
         "When true, bypass the default build/vendored excludes ({excludes}) and ignore files (.gitignore, .ignore). Default: false. Only set this when you specifically need to grep inside build artefacts or vendored code."
     );
 
+    let context_lines_description = format!(
+        "Lines of context to show before and after each match (at most {}). Default: 0.",
+        crate::tools::codesearch::MAX_CONTEXT_LINES
+    );
+
     tools.push(Tool::Regular {
         name: "search_code".to_string(),
         description: tool_description,
@@ -513,6 +691,14 @@ pub fn add_code_search_tool(tools: &mut Vec<Tool>) {
                 "include_ignored": {
                     "type": "boolean",
                     "description": include_ignored_description
+                },
+                "include_hidden": {
+                    "type": "boolean",
+                    "description": "When true, also search dotfiles and dot-directories such as .github/ (.git/ is still skipped). Default: false."
+                },
+                "context_lines": {
+                    "type": "integer",
+                    "description": context_lines_description
                 }
             },
             "required": ["pattern"]
@@ -521,6 +707,47 @@ pub fn add_code_search_tool(tools: &mut Vec<Tool>) {
     });
 }
 
+/// Add `search_and_replace` to an existing tool list. It finds files
+/// with ripgrep, so it is offered only alongside `search_code`.
+pub fn add_search_and_replace_tool(tools: &mut Vec<Tool>) {
+    let description = format!(
+        "Replace a regex pattern across every matching file in the workspace in one call, for renames and other repetitive refactors. Files are found with ripgrep, skipping build/vendored directories ({}), ignored files, and protected paths. Without confirm the call only previews the affected files and match counts; call it again with confirm: true to write the changes, which returns a combined diff. At most {} files may change in one call.",
+        crate::tools::codesearch::default_exclude_dirs_human(),
+        crate::tools::replace::MAX_REPLACE_FILES
+    );
+    tools.push(Tool::Regular {
+        name: "search_and_replace".to_string(),
+        description,
+        input_schema: json!({
+            "type": "object",
+            "properties": {
+                "pattern": {
+                    "type": "string",
+                    "description": "The regex to replace (ripgrep / Rust regex syntax, e.g., '\\bold_name\\b')."
+                },
+                "replacement": {
+                    "type": "string",
+                    "description": "The replacement text. $1 or ${name} insert capture groups; write $$ for a literal $."
+                },
+                "file_type": {
+                    "type": "string",
+                    "description": "Optional ripgrep file type filter (e.g., 'rust', 'py', 'js')."
+                },
+                "glob": {
+                    "type": "string",
+                    "description": "Optional glob limiting the files, relative to the workspace (e.g., 'src/**/*.rs')."
+                },
+                "confirm": {
+                    "type": "boolean",
+                    "description": "Apply the replacement. Default: false, which only previews it."
+                }
+            },
+            "required": ["pattern", "replacement"]
+        }),
+        cache_control: None,
+    });
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
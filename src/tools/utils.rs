@@ -288,7 +288,6 @@ pub enum ConfirmationType {
     /// Permission request (allow command) - defaults to No
     Permission,
     /// Informational confirmation - defaults to No
-    #[allow(dead_code)]
     Info,
 }
 
@@ -305,6 +304,10 @@ pub type ConfirmHandler =
 
 static CONFIRM_HANDLER: OnceLock<ConfirmHandler> = OnceLock::new();
 
+/// Held while a question is on screen, so tool calls running
+/// concurrently ask one at a time.
+static CONFIRM_LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+
 /// Install a process-global confirmation handler. Can only be set once —
 /// subsequent calls are silently ignored. Returns `true` if the handler
 /// was installed, `false` if one was already registered.
@@ -350,6 +353,9 @@ pub fn confirm_multi_choice(
         ));
     }
     let default_index = default_index.min(choices.len() - 1);
+    let _asking = CONFIRM_LOCK
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
 
     if let Some(handler) = CONFIRM_HANDLER.get() {
         let choices_owned: Vec<String> = choices.iter().map(|s| s.to_string()).collect();
@@ -0,0 +1,69 @@
+//! Display choices made once at startup and read wherever output is
+//! styled: the syntax-highlighting theme (`--theme` / `SOFOS_THEME`) and
+//! whether colour is on (`--no-color` / `NO_COLOR`).
+
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Theme used when none is chosen; suits dark terminals.
+pub const DEFAULT_THEME: &str = "base16-ocean.dark";
+
+/// Themes bundled with syntect, in the order `--theme` lists them.
+pub const THEMES: &[&str] = &[
+    "base16-ocean.dark",
+    "base16-eighties.dark",
+    "base16-mocha.dark",
+    "base16-ocean.light",
+    "InspiredGitHub",
+    "Solarized (dark)",
+    "Solarized (light)",
+];
+
+static THEME: OnceLock<&'static str> = OnceLock::new();
+static COLOR_DISABLED: AtomicBool = AtomicBool::new(false);
+
+/// The bundled theme called `name`, ignoring case.
+pub fn parse_theme(name: &str) -> Option<&'static str> {
+    THEMES
+        .iter()
+        .copied()
+        .find(|theme| theme.eq_ignore_ascii_case(name.trim()))
+}
+
+/// Select the highlighting theme. Only the first call takes effect.
+pub fn set_theme(theme: &'static str) {
+    let _ = THEME.set(theme);
+}
+
+pub fn theme() -> &'static str {
+    THEME.get().copied().unwrap_or(DEFAULT_THEME)
+}
+
+/// Turn colour off for the rest of the process, including the TUI,
+/// which otherwise forces `colored` on for its capture pipe.
+pub fn disable_color() {
+    COLOR_DISABLED.store(true, Ordering::SeqCst);
+    colored::control::set_override(false);
+}
+
+/// False after `disable_color` or when `NO_COLOR` is set to anything.
+pub fn color_enabled() -> bool {
+    !COLOR_DISABLED.load(Ordering::SeqCst)
+        && std::env::var_os("NO_COLOR").is_none_or(|v| v.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_listed_theme_is_bundled_and_parses() {
+        let bundled = syntect::highlighting::ThemeSet::load_defaults();
+        for theme in THEMES {
+            assert!(bundled.themes.contains_key(*theme), "{theme}");
+            assert_eq!(parse_theme(&theme.to_uppercase()), Some(*theme));
+        }
+        assert_eq!(parse_theme("solarized (LIGHT)"), Some("Solarized (light)"));
+        assert_eq!(parse_theme("dracula"), None);
+    }
+}
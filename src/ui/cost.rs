@@ -1,5 +1,8 @@
+use crate::config::ModelPrice;
 use crate::ui::UI;
 use colored::Colorize;
+use std::collections::BTreeMap;
+use std::sync::OnceLock;
 
 /// Fraction of the base input price charged for tokens served from
 /// the provider prompt cache. Anthropic and OpenAI both publish this
@@ -7,20 +10,126 @@ use colored::Colorize;
 /// as a single constant instead of being repeated on every model
 /// record.
 const CACHE_READ_RATE: f64 = 0.10;
+/// Gemini 2.5 bills cached input at 25% of the base input price.
+const GEMINI_CACHE_READ_RATE: f64 = 0.25;
 /// Multiplier applied to the base input price for tokens written to a
 /// 5-minute Anthropic cache breakpoint. OpenAI has no creation charge.
 /// The 1-hour breakpoint Anthropic exposes for the last tool definition
 /// bills at 2×, not 1.25× — the cost summary under-reports that anchor.
 const CACHE_CREATION_RATE: f64 = 1.25;
 
-/// True for models hosted by OpenAI. Used by the cost and
-/// token-display paths to route into the OpenAI pricing /
+/// Shown in place of a dollar figure for a model with no known price.
+const COST_UNKNOWN: &str = "cost unknown";
+
+/// `[pricing]` entries from the config files, keyed by lowercase model
+/// name or prefix.
+static PRICING: OnceLock<BTreeMap<String, ModelPrice>> = OnceLock::new();
+
+/// Install the `[pricing]` table read at startup. Only the first call
+/// takes effect.
+pub fn set_pricing(pricing: BTreeMap<String, ModelPrice>) {
+    let _ = PRICING.set(pricing);
+}
+
+/// The `[pricing]` entry for `model`: the longest key the name starts
+/// with, so an exact name beats a shorter prefix.
+fn configured_price(pricing: &BTreeMap<String, ModelPrice>, model: &str) -> Option<ModelPrice> {
+    let model = model.to_ascii_lowercase();
+    pricing
+        .iter()
+        .filter(|(key, _)| model.starts_with(key.as_str()))
+        .max_by_key(|(key, _)| key.len())
+        .map(|(_, price)| *price)
+}
+
+/// Per-million-token `(input, output)` prices in effect for `model`: a
+/// `[pricing]` entry, else the built-in record of a supported model,
+/// else `None`. A configured price replaces any premium tier.
+fn prices_from(
+    pricing: Option<&BTreeMap<String, ModelPrice>>,
+    model: &str,
+    peak_single_turn_input_tokens: u32,
+) -> Option<(f64, f64)> {
+    if let Some(price) = pricing.and_then(|pricing| configured_price(pricing, model)) {
+        return Some((price.input, price.output));
+    }
+    let info = crate::api::model_info::canonical_model(model)?;
+    // Tiered pricing: a model with a premium tier flips the entire
+    // session to a premium rate once any single prompt's input
+    // crosses the documented threshold. Compare the per-call
+    // high-water mark (not the cumulative session total) against
+    // the threshold, because the cliff is per-prompt, not
+    // per-session-cumulative.
+    Some(match info.premium_tier {
+        Some(tier) if peak_single_turn_input_tokens > tier.input_threshold => {
+            (tier.price_input_per_m, tier.price_output_per_m)
+        }
+        _ => (info.price_input_per_m, info.price_output_per_m),
+    })
+}
+
+/// `cost` in dollars, or [`COST_UNKNOWN`].
+fn format_cost(cost: Option<f64>) -> String {
+    cost.map_or_else(|| COST_UNKNOWN.to_string(), |usd| format!("${:.4}", usd))
+}
+
+/// The note shown where a cost is unknown, naming where to add a price.
+fn missing_price_hint(model: &str) -> String {
+    format!(
+        "(no price known for {}: add one under [pricing] in {})",
+        model,
+        crate::config::config_files_hint()
+    )
+}
+
+/// Cells in the `/tokens` context bar.
+const CONTEXT_BAR_WIDTH: usize = 30;
+/// Context use, in percent of the window, from which `/tokens` warns
+/// that older messages will soon be compacted or dropped.
+const CONTEXT_WARNING_PERCENT: usize = 80;
+
+/// `used` as a whole percentage of `max`.
+fn context_percent(used: usize, max: usize) -> usize {
+    used.saturating_mul(100) / max.max(1)
+}
+
+/// A bar of [`CONTEXT_BAR_WIDTH`] cells filled in proportion to `used`
+/// out of `max`, full once the window is.
+fn context_bar(used: usize, max: usize) -> String {
+    let filled = (used.saturating_mul(CONTEXT_BAR_WIDTH) / max.max(1)).min(CONTEXT_BAR_WIDTH);
+    format!(
+        "{}{}",
+        "█".repeat(filled),
+        "░".repeat(CONTEXT_BAR_WIDTH - filled)
+    )
+}
+
+/// Provider whose token accounting `model` follows. A name outside the
+/// catalog only gets this far behind `--api-base`, whose server speaks
+/// the OpenAI API.
+fn provider_of(model: &str) -> crate::api::model_info::Provider {
+    crate::api::model_info::canonical_model(model)
+        .map_or(crate::api::model_info::Provider::OpenAI, |m| m.provider)
+}
+
+/// True for models whose reported input count already includes the
+/// cache reads (OpenAI and Gemini; Anthropic reports them separately).
+/// Used by the cost and token-display paths to route into the
 /// uncached-tokens branches. The decision flows from the same
-/// per-model record as the rest of the application, so a new
-/// OpenAI model only has to be added to `SUPPORTED_MODELS` for
-/// costing to pick it up.
-fn is_openai_model(model: &str) -> bool {
-    crate::api::model_info::provider_for(model) == crate::api::model_info::Provider::OpenAI
+/// per-model record as the rest of the application, so a new model
+/// only has to be added to `SUPPORTED_MODELS` for costing to pick it
+/// up.
+fn input_includes_cache_reads(model: &str) -> bool {
+    provider_of(model) != crate::api::model_info::Provider::Anthropic
+}
+
+fn cache_read_rate(model: &str) -> f64 {
+    match provider_of(model) {
+        crate::api::model_info::Provider::Gemini => GEMINI_CACHE_READ_RATE,
+        crate::api::model_info::Provider::Anthropic | crate::api::model_info::Provider::OpenAI => {
+            CACHE_READ_RATE
+        }
+    }
 }
 
 impl UI {
@@ -80,7 +189,15 @@ impl UI {
                 "{:<20} {} {}",
                 "  cache read:".bright_white(),
                 Self::format_number(total_cache_read_tokens).bright_green(),
-                format!("({:.0}% hit)", cache_hit_pct).dimmed()
+                match Self::cache_savings(
+                    model,
+                    total_cache_read_tokens,
+                    peak_single_turn_input_tokens,
+                ) {
+                    Some(saved) => format!("({:.0}% hit, saved ~${:.4})", cache_hit_pct, saved),
+                    None => format!("({:.0}% hit)", cache_hit_pct),
+                }
+                .dimmed()
             );
             if total_cache_creation_tokens > 0 {
                 println!(
@@ -104,15 +221,28 @@ impl UI {
         println!(
             "{:<20} {}",
             "Estimated cost:".bright_white().bold(),
-            format!("${:.4}", estimated_cost).bright_yellow().bold()
+            format_cost(estimated_cost).bright_yellow().bold()
         );
+        if estimated_cost.is_none() {
+            println!(
+                "{:<20} {}",
+                "".bright_white(),
+                missing_price_hint(model).dimmed()
+            );
+        }
 
         // Surface the per-prompt cliff when premium pricing kicked in
         // — users otherwise have no way to tell that crossing the
         // premium-tier input-token threshold doubled the rate for
-        // every later turn in this session.
-        let info = crate::api::model_info::lookup(model);
-        if let Some(tier) = info.premium_tier {
+        // every later turn in this session. A `[pricing]` entry has
+        // no tier, so there is no cliff to report.
+        let configured = PRICING
+            .get()
+            .and_then(|pricing| configured_price(pricing, model));
+        let tier = crate::api::model_info::canonical_model(model)
+            .filter(|_| configured.is_none())
+            .and_then(|info| info.premium_tier);
+        if let Some(tier) = tier {
             if peak_single_turn_input_tokens > tier.input_threshold {
                 println!(
                     "{:<20} {}",
@@ -135,58 +265,172 @@ impl UI {
     /// Returns the count of input tokens the model actually saw (cached
     /// plus uncached, excluding cache-creation writes which are billed
     /// separately). Hides the per-provider semantic difference of
-    /// `total_input_tokens` (OpenAI already includes cached, Anthropic
-    /// excludes them).
-    fn total_input_seen_by_model(
+    /// `total_input_tokens` (OpenAI and Gemini already include cached,
+    /// Anthropic excludes them).
+    pub(crate) fn total_input_seen_by_model(
         model: &str,
         total_input_tokens: u32,
         cache_read_tokens: u32,
     ) -> u32 {
-        if is_openai_model(model) {
+        if input_includes_cache_reads(model) {
             total_input_tokens
         } else {
             total_input_tokens + cache_read_tokens
         }
     }
 
-    fn calculate_cost(
+    pub(crate) fn calculate_cost(
         model: &str,
         input_tokens: u32,
         output_tokens: u32,
         cache_read_tokens: u32,
         cache_creation_tokens: u32,
         peak_single_turn_input_tokens: u32,
-    ) -> f64 {
-        let info = crate::api::model_info::lookup(model);
-        // Tiered pricing: a model with a premium tier flips the entire
-        // session to a premium rate once any single prompt's input
-        // crosses the documented threshold. Compare the per-call
-        // high-water mark (not the cumulative session total) against
-        // the threshold, because the cliff is per-prompt, not
-        // per-session-cumulative.
-        let (input_price, output_price) = match info.premium_tier {
-            Some(tier) if peak_single_turn_input_tokens > tier.input_threshold => {
-                (tier.price_input_per_m, tier.price_output_per_m)
-            }
-            _ => (info.price_input_per_m, info.price_output_per_m),
-        };
+    ) -> Option<f64> {
+        let (input_price, output_price) = Self::prices_per_m(model, peak_single_turn_input_tokens)?;
 
-        // OpenAI's `input_tokens` is the total (cached + uncached);
-        // Anthropic's is uncached new tokens only. Normalize to "tokens
-        // billed at the full input rate" before pricing.
-        let uncached = if is_openai_model(model) {
+        // OpenAI's and Gemini's input counts are the total (cached +
+        // uncached); Anthropic's is uncached new tokens only. Normalize
+        // to "tokens billed at the full input rate" before pricing.
+        let uncached = if input_includes_cache_reads(model) {
             input_tokens.saturating_sub(cache_read_tokens)
         } else {
             input_tokens
         };
 
         let uncached_cost = (uncached as f64 / 1_000_000.0) * input_price;
-        let cached_cost = (cache_read_tokens as f64 / 1_000_000.0) * input_price * CACHE_READ_RATE;
+        let cached_cost =
+            (cache_read_tokens as f64 / 1_000_000.0) * input_price * cache_read_rate(model);
         let creation_cost =
             (cache_creation_tokens as f64 / 1_000_000.0) * input_price * CACHE_CREATION_RATE;
         let output_cost = (output_tokens as f64 / 1_000_000.0) * output_price;
 
-        uncached_cost + cached_cost + creation_cost + output_cost
+        Some(uncached_cost + cached_cost + creation_cost + output_cost)
+    }
+
+    /// Whether a price is known for `model`, built in or configured.
+    pub(crate) fn has_price(model: &str) -> bool {
+        Self::prices_per_m(model, 0).is_some()
+    }
+
+    /// Dollars the cache reads saved against paying the full input price
+    /// for the same tokens. Cache writes are not netted off.
+    pub(crate) fn cache_savings(
+        model: &str,
+        cache_read_tokens: u32,
+        peak_single_turn_input_tokens: u32,
+    ) -> Option<f64> {
+        let (input_price, _) = Self::prices_per_m(model, peak_single_turn_input_tokens)?;
+        Some(
+            (cache_read_tokens as f64 / 1_000_000.0) * input_price * (1.0 - cache_read_rate(model)),
+        )
+    }
+
+    /// Per-million-token `(input, output)` prices in effect for `model`,
+    /// or `None` when neither the built-in table nor `[pricing]` has one.
+    fn prices_per_m(model: &str, peak_single_turn_input_tokens: u32) -> Option<(f64, f64)> {
+        prices_from(PRICING.get(), model, peak_single_turn_input_tokens)
+    }
+
+    /// Print the running cost of the session so far for `/cost`: tokens
+    /// and estimated dollars split into input and output, with the
+    /// per-million prices of the active model. Cache reads and writes
+    /// are priced into the input side.
+    pub fn display_session_cost(
+        model: &str,
+        total_input_tokens: u32,
+        total_output_tokens: u32,
+        total_cache_read_tokens: u32,
+        total_cache_creation_tokens: u32,
+        peak_single_turn_input_tokens: u32,
+    ) {
+        let prices = Self::prices_per_m(model, peak_single_turn_input_tokens);
+        let input_cost = Self::calculate_cost(
+            model,
+            total_input_tokens,
+            0,
+            total_cache_read_tokens,
+            total_cache_creation_tokens,
+            peak_single_turn_input_tokens,
+        );
+        let output_cost = Self::calculate_cost(
+            model,
+            0,
+            total_output_tokens,
+            0,
+            0,
+            peak_single_turn_input_tokens,
+        );
+        let input_seen =
+            Self::total_input_seen_by_model(model, total_input_tokens, total_cache_read_tokens)
+                + total_cache_creation_tokens;
+
+        println!();
+        println!(
+            "{:<20} {} {}",
+            "Input:".bright_white(),
+            format!("{} tokens", Self::format_number(input_seen)).bright_green(),
+            format_cost(input_cost).bright_yellow()
+        );
+        println!(
+            "{:<20} {} {}",
+            "Output:".bright_white(),
+            format!("{} tokens", Self::format_number(total_output_tokens)).bright_green(),
+            format_cost(output_cost).bright_yellow()
+        );
+        println!(
+            "{:<20} {}",
+            "Estimated cost:".bright_white().bold(),
+            format_cost(input_cost.zip(output_cost).map(|(i, o)| i + o))
+                .bright_yellow()
+                .bold()
+        );
+        let note = match prices {
+            Some((input_price, output_price)) => format!(
+                "{} prices per million tokens: ${} input, ${} output",
+                model, input_price, output_price
+            ),
+            None => missing_price_hint(model),
+        };
+        println!("{}", note.dimmed());
+        println!();
+    }
+
+    /// Print `/tokens`: the estimated size of the conversation against the
+    /// model's context window, as a count, a percentage and a bar, with
+    /// a warning once it passes [`CONTEXT_WARNING_PERCENT`].
+    pub fn display_context_usage(estimated_tokens: usize, max_context_tokens: usize) {
+        let percent = context_percent(estimated_tokens, max_context_tokens);
+        let to_u32 = |n: usize| u32::try_from(n).unwrap_or(u32::MAX);
+        let bar = context_bar(estimated_tokens, max_context_tokens);
+        let near_full = percent >= CONTEXT_WARNING_PERCENT;
+
+        println!();
+        println!(
+            "{:<20} {} of {} tokens ({}%)",
+            "Context:".bright_white(),
+            format!("~{} tokens", Self::format_number(to_u32(estimated_tokens))).bright_green(),
+            Self::format_number(to_u32(max_context_tokens)),
+            percent
+        );
+        println!(
+            "{:<20} {}",
+            "",
+            if near_full {
+                bar.bright_yellow()
+            } else {
+                bar.bright_green()
+            }
+        );
+        if near_full {
+            println!(
+                "{}",
+                "The context is nearly full: older messages will soon be summarized or dropped. \
+                 Use /compact to summarize them now, or /clear to start fresh."
+                    .yellow()
+            );
+        }
+        println!();
     }
 
     /// Render the elapsed turn time as a short human-readable string for
@@ -237,6 +481,36 @@ mod tests {
         );
     }
 
+    #[test]
+    fn context_bar_fills_in_proportion_and_caps_at_full() {
+        let filled = |bar: &str| bar.chars().filter(|c| *c == '█').count();
+        assert_eq!(filled(&context_bar(0, 1000)), 0);
+        assert_eq!(filled(&context_bar(500, 1000)), CONTEXT_BAR_WIDTH / 2);
+        assert_eq!(filled(&context_bar(5000, 1000)), CONTEXT_BAR_WIDTH);
+        assert_eq!(context_bar(5000, 1000).chars().count(), CONTEXT_BAR_WIDTH);
+        assert_eq!(context_percent(850, 1000), 85);
+        assert_eq!(
+            context_percent(10, 0),
+            1000,
+            "an unset window cannot divide by zero"
+        );
+    }
+
+    #[test]
+    fn prices_follow_the_premium_tier_once_crossed() {
+        let model = crate::api::model_info::GPT_FLAGSHIP;
+        let info = crate::api::model_info::lookup(model);
+        let tier = info.premium_tier.expect("the flagship has a premium tier");
+        assert_eq!(
+            UI::prices_per_m(model, tier.input_threshold),
+            Some((info.price_input_per_m, info.price_output_per_m))
+        );
+        assert_eq!(
+            UI::prices_per_m(model, tier.input_threshold + 1),
+            Some((tier.price_input_per_m, tier.price_output_per_m))
+        );
+    }
+
     #[test]
     fn openai_cost_uses_full_rate_when_no_cache() {
         // 100k input @ $5/M, 5k output @ $30/M, no cache. Peak below
@@ -248,7 +522,8 @@ mod tests {
             0,
             0,
             100_000,
-        );
+        )
+        .unwrap();
         approx(cost, 100_000.0 / 1e6 * 5.0 + 5_000.0 / 1e6 * 30.0);
     }
 
@@ -261,7 +536,8 @@ mod tests {
             75_000,
             0,
             100_000,
-        );
+        )
+        .unwrap();
         approx(cost, 0.1625 + 0.15);
     }
 
@@ -275,7 +551,8 @@ mod tests {
             75_000,
             0,
             100_000,
-        );
+        )
+        .unwrap();
         let ratio = pre_fix_input / post_fix_input;
         assert!(
             (2.9..=3.2).contains(&ratio),
@@ -293,16 +570,31 @@ mod tests {
             75_000,
             0,
             100_000,
-        );
+        )
+        .unwrap();
         approx(cost, 0.1625 + 0.125);
     }
 
     #[test]
     fn anthropic_cost_charges_creation_at_125pct() {
-        let cost = UI::calculate_cost(crate::api::model_info::CLAUDE_OPUS, 0, 0, 0, 50_000, 0);
+        let cost =
+            UI::calculate_cost(crate::api::model_info::CLAUDE_OPUS, 0, 0, 0, 50_000, 0).unwrap();
         approx(cost, 50_000.0 / 1e6 * 5.0 * 1.25);
     }
 
+    #[test]
+    fn cache_savings_are_the_discount_off_the_full_input_price() {
+        // 75K cached tokens @ $5/M would cost $0.375 uncached; 90% of it is saved.
+        approx(
+            UI::cache_savings(crate::api::model_info::CLAUDE_OPUS, 75_000, 0).unwrap(),
+            0.3375,
+        );
+        approx(
+            UI::cache_savings(crate::api::model_info::GPT_FLAGSHIP, 0, 0).unwrap(),
+            0.0,
+        );
+    }
+
     #[test]
     fn cache_hit_does_not_underflow_when_read_exceeds_input() {
         let cost = UI::calculate_cost(
@@ -312,7 +604,8 @@ mod tests {
             100_000,
             0,
             100_000,
-        );
+        )
+        .unwrap();
         approx(cost, 100_000.0 / 1e6 * 5.0 * 0.10);
     }
 
@@ -326,7 +619,8 @@ mod tests {
             0,
             0,
             200_000,
-        );
+        )
+        .unwrap();
         approx(standard, 100_000.0 / 1e6 * 5.0);
 
         // Above cliff (peak observed > 272K): premium rate ($10/M input
@@ -340,7 +634,8 @@ mod tests {
             0,
             0,
             300_000,
-        );
+        )
+        .unwrap();
         approx(premium, 100_000.0 / 1e6 * 10.0);
         assert!((premium / standard - 2.0).abs() < 0.01);
     }
@@ -371,11 +666,41 @@ mod tests {
     }
 
     #[test]
-    fn unknown_model_falls_back_without_panic() {
-        // Default fallback uses the application-default model's pricing
-        // ($3 / $15) and the Anthropic semantics branch (input_tokens
-        // is uncached).
-        let cost = UI::calculate_cost("some-future-model", 1_000, 1_000, 0, 0, 1_000);
-        approx(cost, 1_000.0 / 1e6 * 3.0 + 1_000.0 / 1e6 * 15.0);
+    fn unknown_model_cost_is_unknown_rather_than_guessed() {
+        assert_eq!(
+            UI::calculate_cost("some-future-model", 1_000, 1_000, 0, 0, 1_000),
+            None
+        );
+        assert_eq!(UI::cache_savings("some-future-model", 1_000, 0), None);
+        assert_eq!(format_cost(None), COST_UNKNOWN);
+    }
+
+    #[test]
+    fn configured_prices_override_by_longest_prefix() {
+        let price = |input, output| ModelPrice { input, output };
+        let pricing = BTreeMap::from([
+            ("llama".to_string(), price(0.1, 0.2)),
+            ("llama-3.3".to_string(), price(0.5, 1.0)),
+            (
+                crate::api::model_info::GPT_FLAGSHIP.to_string(),
+                price(1.0, 2.0),
+            ),
+        ]);
+
+        assert_eq!(
+            prices_from(Some(&pricing), "Llama-3.3-70B", 0),
+            Some((0.5, 1.0))
+        );
+        assert_eq!(prices_from(Some(&pricing), "llama-2", 0), Some((0.1, 0.2)));
+        assert_eq!(prices_from(Some(&pricing), "mistral", 0), None);
+        assert_eq!(
+            prices_from(
+                Some(&pricing),
+                crate::api::model_info::GPT_FLAGSHIP,
+                u32::MAX
+            ),
+            Some((1.0, 2.0)),
+            "a configured price has no premium tier"
+        );
     }
 }
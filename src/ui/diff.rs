@@ -1,5 +1,7 @@
+use crate::config::DiffStyle;
+use crate::ui::appearance;
 use colored::Colorize;
-use similar::{ChangeTag, TextDiff};
+use similar::{ChangeTag, DiffTag, TextDiff};
 use std::sync::OnceLock;
 use syntect::easy::HighlightLines;
 use syntect::highlighting::{Color, Style, Theme, ThemeSet};
@@ -14,9 +16,20 @@ fn shared_syntax_set() -> &'static SyntaxSet {
     SET.get_or_init(SyntaxSet::load_defaults_newlines)
 }
 
-/// Shared dark theme used for diff highlighting. Same rationale as
-/// [`shared_syntax_set`] — `ThemeSet::load_defaults` is several
-/// megabytes of theme data that doesn't change between calls. Falls
+/// The language syntect recognises for `path` by its extension, such as
+/// "Rust", or `None` for plain text.
+pub fn language_for_path(path: &str) -> Option<&'static str> {
+    let ext = path.rsplit('.').next().unwrap_or("");
+    shared_syntax_set()
+        .find_syntax_by_extension(ext)
+        .or_else(|| shared_syntax_set().find_syntax_by_token(ext))
+        .map(|syntax| syntax.name.as_str())
+        .filter(|name| *name != "Plain Text")
+}
+
+/// Shared theme used for diff highlighting, picked by `--theme`. Same
+/// rationale as [`shared_syntax_set`] — `ThemeSet::load_defaults` is
+/// several megabytes of theme data that doesn't change between calls. Falls
 /// back to any other bundled theme, then to a default-constructed one,
 /// if the named theme is ever removed upstream — keeps the diff
 /// renderer panic-free rather than dying mid-edit on a future syntect
@@ -27,7 +40,8 @@ fn shared_diff_theme() -> &'static Theme {
         let mut theme_set = ThemeSet::load_defaults();
         theme_set
             .themes
-            .remove("base16-ocean.dark")
+            .remove(appearance::theme())
+            .or_else(|| theme_set.themes.remove(appearance::DEFAULT_THEME))
             .or_else(|| theme_set.themes.into_values().next())
             .unwrap_or_default()
     })
@@ -45,6 +59,31 @@ const INSERT_BG: Color = Color {
     b: 0x5f,
     a: 0xFF,
 };
+/// Brighter backgrounds for the words that changed inside a modified
+/// line, laid over `DELETE_BG` / `INSERT_BG`.
+const DELETE_WORD_BG: Color = Color {
+    r: 0xa0,
+    g: 0x20,
+    b: 0x20,
+    a: 0xFF,
+};
+const INSERT_WORD_BG: Color = Color {
+    r: 0x20,
+    g: 0x40,
+    b: 0xb0,
+    a: 0xFF,
+};
+
+/// Unchanged lines shown around each change.
+const DIFF_CONTEXT_LINES: usize = 2;
+/// Line numbers are left-aligned in this many columns.
+const LINE_NUMBER_WIDTH: usize = 4;
+/// Divider between the old and new columns of a side-by-side diff.
+const COLUMN_SEPARATOR: &str = " │ ";
+/// Narrowest text column a side-by-side diff shrinks to.
+const MIN_SIDE_BY_SIDE_COLUMN: usize = 10;
+/// Tabs are expanded so the side-by-side columns line up.
+const TAB_AS_SPACES: &str = "    ";
 
 fn highlight_line_with_bg(
     line: &str,
@@ -152,13 +191,299 @@ pub fn generate_contextual_diff(
 }
 
 pub fn generate_compact_diff(original: &str, modified: &str, file_path: &str) -> String {
-    generate_contextual_diff(original, modified, 2, file_path)
+    generate_contextual_diff(original, modified, DIFF_CONTEXT_LINES, file_path)
+}
+
+/// The diff shown after a file edit, in the configured `style`.
+/// Side-by-side columns split `width` terminal columns.
+pub fn generate_diff(
+    style: DiffStyle,
+    original: &str,
+    modified: &str,
+    file_path: &str,
+    width: usize,
+) -> String {
+    if !appearance::color_enabled() {
+        return generate_plain_diff(original, modified);
+    }
+    match style {
+        DiffStyle::Compact => generate_compact_diff(original, modified, file_path),
+        DiffStyle::Word => generate_word_diff(original, modified),
+        DiffStyle::SideBySide => generate_side_by_side(original, modified, width),
+    }
+}
+
+/// Uncoloured diff for `--no-color`: numbered lines marked `-`, `+` or
+/// left blank, in the same hunks as the compact view.
+pub fn generate_plain_diff(original: &str, modified: &str) -> String {
+    let diff = TextDiff::from_lines(original, modified);
+    let mut output = Vec::new();
+    for (idx, group) in diff.grouped_ops(DIFF_CONTEXT_LINES).iter().enumerate() {
+        if idx > 0 {
+            output.push("...".to_string());
+        }
+        for op in group {
+            for change in diff.iter_changes(op) {
+                let (marker, index) = match change.tag() {
+                    ChangeTag::Delete => ('-', change.old_index()),
+                    ChangeTag::Insert => ('+', change.new_index()),
+                    ChangeTag::Equal => (' ', change.old_index()),
+                };
+                output.push(format!(
+                    "{:<width$}{} {}",
+                    index.map_or(0, |i| i + 1),
+                    marker,
+                    change.value().trim_end(),
+                    width = LINE_NUMBER_WIDTH
+                ));
+            }
+        }
+    }
+    output.join("\n")
+}
+
+fn bg(color: Color) -> String {
+    format!("\x1b[48;2;{};{};{}m", color.r, color.g, color.b)
+}
+
+fn dim_line_number(index: usize) -> String {
+    format!(
+        "\x1b[2m{:<width$}\x1b[22m",
+        index + 1,
+        width = LINE_NUMBER_WIDTH
+    )
+}
+
+/// Pieces of a line, each flagged when it changed.
+type Segments = Vec<(bool, String)>;
+
+/// `segments` painted on `line_bg`, with the changed ones on `word_bg`.
+fn paint_segments(segments: &Segments, line_bg: Color, word_bg: Color) -> String {
+    segments
+        .iter()
+        .map(|(changed, text)| {
+            let color = if *changed { word_bg } else { line_bg };
+            format!("{}{}\x1b[0m", bg(color), text)
+        })
+        .collect()
+}
+
+/// Split two versions of a line into word segments, flagging those not
+/// shared with the other version.
+fn word_segments(old: &str, new: &str) -> (Segments, Segments) {
+    let diff = TextDiff::from_words(old, new);
+    let (mut old_segments, mut new_segments) = (Vec::new(), Vec::new());
+    for change in diff.iter_all_changes() {
+        let text = change.value().to_string();
+        match change.tag() {
+            ChangeTag::Equal => {
+                old_segments.push((false, text.clone()));
+                new_segments.push((false, text));
+            }
+            ChangeTag::Delete => old_segments.push((true, text)),
+            ChangeTag::Insert => new_segments.push((true, text)),
+        }
+    }
+    (old_segments, new_segments)
+}
+
+fn line_at<'a>(lines: &[&'a str], index: usize) -> &'a str {
+    lines.get(index).copied().unwrap_or("")
+}
+
+/// Line diff in which modified lines also highlight the words that
+/// changed, so a small edit inside a long line stands out.
+pub fn generate_word_diff(original: &str, modified: &str) -> String {
+    let diff = TextDiff::from_lines(original, modified);
+    let old_lines: Vec<&str> = original.lines().collect();
+    let new_lines: Vec<&str> = modified.lines().collect();
+    let removed = |index: usize, body: String| {
+        format!(
+            "{}{}- \x1b[0m{}",
+            dim_line_number(index),
+            bg(DELETE_BG),
+            body
+        )
+    };
+    let added = |index: usize, body: String| {
+        format!(
+            "{}{}+ \x1b[0m{}",
+            dim_line_number(index),
+            bg(INSERT_BG),
+            body
+        )
+    };
+    let whole = |text: &str| vec![(false, text.to_string())];
+
+    let mut output = Vec::new();
+    for (idx, group) in diff.grouped_ops(DIFF_CONTEXT_LINES).iter().enumerate() {
+        if idx > 0 {
+            output.push("".to_string());
+            output.push("...".dimmed().to_string());
+            output.push("".to_string());
+        }
+        for op in group {
+            let (tag, old_range, new_range) = op.as_tag_tuple();
+            if tag == DiffTag::Equal {
+                for index in old_range {
+                    output.push(format!(
+                        "{}  {}",
+                        dim_line_number(index),
+                        line_at(&old_lines, index)
+                    ));
+                }
+                continue;
+            }
+            // Pair the removed and added lines in order; the surplus on
+            // either side is shown whole.
+            let mut removed_rows = Vec::new();
+            let mut added_rows = Vec::new();
+            for offset in 0..old_range.len().max(new_range.len()) {
+                let old_index = old_range.start + offset;
+                let new_index = new_range.start + offset;
+                match (
+                    old_range.contains(&old_index),
+                    new_range.contains(&new_index),
+                ) {
+                    (true, true) => {
+                        let (old, new) = word_segments(
+                            line_at(&old_lines, old_index),
+                            line_at(&new_lines, new_index),
+                        );
+                        removed_rows.push(removed(
+                            old_index,
+                            paint_segments(&old, DELETE_BG, DELETE_WORD_BG),
+                        ));
+                        added_rows.push(added(
+                            new_index,
+                            paint_segments(&new, INSERT_BG, INSERT_WORD_BG),
+                        ));
+                    }
+                    (true, false) => removed_rows.push(removed(
+                        old_index,
+                        paint_segments(
+                            &whole(line_at(&old_lines, old_index)),
+                            DELETE_BG,
+                            DELETE_BG,
+                        ),
+                    )),
+                    (false, true) => added_rows.push(added(
+                        new_index,
+                        paint_segments(
+                            &whole(line_at(&new_lines, new_index)),
+                            INSERT_BG,
+                            INSERT_BG,
+                        ),
+                    )),
+                    (false, false) => {}
+                }
+            }
+            output.extend(removed_rows);
+            output.extend(added_rows);
+        }
+    }
+    output.join("\n")
+}
+
+/// `text` with tabs expanded, cut to `width` display columns (ending in
+/// `…` when cut) and padded to exactly that width.
+fn fit_column(text: &str, width: usize) -> String {
+    use unicode_width::UnicodeWidthChar;
+
+    let text = text.replace('\t', TAB_AS_SPACES);
+    let mut out = String::new();
+    let mut used = 0;
+    let total: usize = text.chars().filter_map(|c| c.width()).sum();
+    let budget = if total > width {
+        width.saturating_sub(1)
+    } else {
+        width
+    };
+    for c in text.chars() {
+        let w = c.width().unwrap_or(0);
+        if used + w > budget {
+            break;
+        }
+        out.push(c);
+        used += w;
+    }
+    if total > width {
+        out.push('…');
+        used += 1;
+    }
+    out.push_str(&" ".repeat(width.saturating_sub(used)));
+    out
+}
+
+/// Old and new versions in two columns that together fill `width`
+/// terminal columns. Long lines are cut to fit.
+pub fn generate_side_by_side(original: &str, modified: &str, width: usize) -> String {
+    let diff = TextDiff::from_lines(original, modified);
+    let old_lines: Vec<&str> = original.lines().collect();
+    let new_lines: Vec<&str> = modified.lines().collect();
+    let chrome = COLUMN_SEPARATOR.chars().count() + 2 * (LINE_NUMBER_WIDTH + 1);
+    let column = (width.saturating_sub(chrome) / 2).max(MIN_SIDE_BY_SIDE_COLUMN);
+    let cell = |lines: &[&str], index: Option<usize>, changed: Option<Color>| match index {
+        Some(index) => {
+            let text = fit_column(line_at(lines, index), column);
+            match changed {
+                Some(color) => format!("{} {}{}\x1b[0m", dim_line_number(index), bg(color), text),
+                None => format!("{} {}", dim_line_number(index), text),
+            }
+        }
+        None => " ".repeat(LINE_NUMBER_WIDTH + 1 + column),
+    };
+    let separator = COLUMN_SEPARATOR.dimmed().to_string();
+
+    let mut output = Vec::new();
+    for (idx, group) in diff.grouped_ops(DIFF_CONTEXT_LINES).iter().enumerate() {
+        if idx > 0 {
+            output.push("...".dimmed().to_string());
+        }
+        for op in group {
+            let (tag, old_range, new_range) = op.as_tag_tuple();
+            let (old_color, new_color) = if tag == DiffTag::Equal {
+                (None, None)
+            } else {
+                (Some(DELETE_BG), Some(INSERT_BG))
+            };
+            for offset in 0..old_range.len().max(new_range.len()) {
+                let old_index = Some(old_range.start + offset).filter(|i| old_range.contains(i));
+                let new_index = Some(new_range.start + offset).filter(|i| new_range.contains(i));
+                output.push(format!(
+                    "{}{}{}",
+                    cell(&old_lines, old_index, old_color),
+                    separator,
+                    cell(&new_lines, new_index, new_color)
+                ));
+            }
+        }
+    }
+    output.join("\n")
+}
+
+/// Lines added and removed going from `original` to `modified`.
+pub fn line_delta(original: &str, modified: &str) -> (usize, usize) {
+    let diff = TextDiff::from_lines(original, modified);
+    diff.iter_all_changes()
+        .fold((0, 0), |(added, removed), change| match change.tag() {
+            ChangeTag::Insert => (added + 1, removed),
+            ChangeTag::Delete => (added, removed + 1),
+            ChangeTag::Equal => (added, removed),
+        })
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn plain_diff_marks_changes_without_escapes() {
+        let diff = generate_plain_diff("a\nb\nc\n", "a\nB\nc\n");
+        assert_eq!(diff, "1     a\n2   - b\n2   + B\n3     c");
+        assert!(!diff.contains('\x1b'));
+    }
+
     #[test]
     fn test_simple_diff() {
         let original = "line 1\nline 2\nline 3\n";
@@ -177,4 +502,48 @@ mod tests {
         assert!(diff.contains("-"));
         assert!(diff.contains("+"));
     }
+
+    #[test]
+    fn word_diff_emphasises_only_the_changed_words() {
+        let original = "let total = price * quantity;\n";
+        let modified = "let total = price * count;\n";
+
+        let diff = generate_word_diff(original, modified);
+        let emphasised = |color: Color, word: &str| format!("{}{}\x1b[0m", bg(color), word);
+
+        assert!(diff.contains(&emphasised(DELETE_WORD_BG, "quantity;")));
+        assert!(diff.contains(&emphasised(INSERT_WORD_BG, "count;")));
+        assert!(diff.contains(&emphasised(INSERT_BG, "price")));
+        assert!(!diff.contains(&emphasised(INSERT_WORD_BG, "price")));
+    }
+
+    #[test]
+    fn side_by_side_rows_fill_the_width_and_cut_long_lines() {
+        let original = "keep\nold line that is much too long to fit\n";
+        let modified = "keep\nnew\nadded\n";
+        let width = 60;
+
+        let diff = generate_side_by_side(original, modified, width);
+        let plain = |line: &str| {
+            let mut out = String::new();
+            let mut chars = line.chars();
+            while let Some(c) = chars.next() {
+                if c == '\x1b' {
+                    chars.by_ref().find(|c| *c == 'm');
+                } else {
+                    out.push(c);
+                }
+            }
+            out
+        };
+        let rows: Vec<String> = diff.lines().map(plain).collect();
+
+        assert_eq!(rows.len(), 3, "{rows:?}");
+        assert!(
+            rows.iter().all(|row| row.chars().count() == width - 1),
+            "{rows:?}"
+        );
+        assert!(rows[1].contains('…'));
+        assert!(rows[2].starts_with(' ') && rows[2].trim_end().ends_with("added"));
+    }
 }
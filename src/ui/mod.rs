@@ -1,7 +1,9 @@
+pub mod appearance;
 pub mod cost;
 pub mod diff;
 pub mod markdown;
 pub mod session_display;
+pub mod stdout_redirect;
 pub mod syntax;
 
 use crate::ui::markdown::MarkdownStreamRenderer;
@@ -26,6 +28,10 @@ const THINKING_RGB: (u8, u8, u8) = (0x77, 0x00, 0xFF);
 /// meaning is "security restriction", not "highlight".
 const BLOCKED_RGB: (u8, u8, u8) = (0xFF, 0xA5, 0x00);
 
+/// Lines of a `read_file` result echoed under its summary. Enough to
+/// recognise the file without scrolling the transcript away.
+const READ_FILE_PREVIEW_LINES: usize = 8;
+
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum MessageSeverity {
     /// Operation rejected as expected behaviour. Covers both
@@ -60,7 +66,7 @@ pub struct UI {
 impl UI {
     pub fn new() -> Self {
         Self {
-            highlighter: SyntaxHighlighter::new(),
+            highlighter: SyntaxHighlighter::new(appearance::theme()),
         }
     }
 
@@ -172,20 +178,30 @@ impl UI {
         Ok(())
     }
 
+    /// The command named in the header of a shell-running tool call.
+    pub fn shell_command<'a>(
+        tool_name: &str,
+        tool_input: &'a serde_json::Value,
+    ) -> Option<&'a str> {
+        crate::tools::ToolName::from_str(tool_name)
+            .is_ok_and(|tool| tool.runs_shell_command())
+            .then(|| tool_input.get("command").and_then(|v| v.as_str()))
+            .flatten()
+            .filter(|command| !command.trim().is_empty())
+    }
+
     pub fn print_tool_header(&self, tool_name: &str, command: Option<&str>) {
         if tool_name == crate::tools::ToolName::UpdatePlan.as_str() {
             return;
         }
-        if tool_name == crate::tools::ToolName::ExecuteBash.as_str() {
-            if let Some(cmd) = command {
-                print!(
-                    "{} {}",
-                    "Executing:".bright_green().bold(),
-                    cmd.bright_cyan()
-                );
-                let _ = stdout().flush();
-            }
-        } else {
+        if let Some(cmd) = command {
+            print!(
+                "{} {}",
+                "Executing:".bright_green().bold(),
+                cmd.bright_cyan()
+            );
+            let _ = stdout().flush();
+        } else if tool_name != crate::tools::ToolName::ExecuteBash.as_str() {
             // MCP tools carry the internal `server<sep>tool` identifier; show
             // it as `server::tool` rather than leaking the raw separator.
             let label = tool_name.replace(crate::mcp::manager::MCP_NAME_SEPARATOR, "::");
@@ -197,6 +213,32 @@ impl UI {
         }
     }
 
+    /// Print a `read_file` summary line followed by the opening lines of
+    /// the file, highlighted by the language `path` implies. `output` is
+    /// the raw tool payload; an empty file gets the summary alone.
+    pub fn print_read_file_output(&self, summary: &str, path: &str, output: &str) {
+        let body = crate::tools::read_file_body(output);
+        let total = body.lines().count();
+        if total == 0 {
+            self.print_tool_output(summary);
+            return;
+        }
+        println!("{}", summary);
+        let preview: String = body
+            .lines()
+            .take(READ_FILE_PREVIEW_LINES)
+            .map(|line| format!("{}\n", line))
+            .collect();
+        println!("{}", self.highlighter.highlight_file(&preview, path));
+        if total > READ_FILE_PREVIEW_LINES {
+            println!(
+                "{}",
+                format!("… {} more lines", total - READ_FILE_PREVIEW_LINES).dimmed()
+            );
+        }
+        println!();
+    }
+
     pub fn print_tool_output(&self, tool_output: &str) {
         if tool_output.contains('\x1b') {
             println!("{}\n", tool_output);
@@ -34,20 +34,15 @@ impl UI {
                     tool_name,
                     tool_input,
                     tool_output,
+                    ..
                 } => {
-                    let command = if tool_name == crate::tools::ToolName::ExecuteBash.as_str() {
-                        tool_input.get("command").and_then(|v| v.as_str())
-                    } else {
-                        None
-                    };
+                    let command = Self::shell_command(tool_name, tool_input);
                     self.print_tool_header(tool_name, command);
-                    // `print_tool_header` doesn't terminate the bash
+                    // `print_tool_header` doesn't terminate the shell
                     // header with a newline — the live path relies on
                     // the post-execution `println!()` to do that. Replay
                     // it here so the header doesn't run into the output.
-                    if tool_name == crate::tools::ToolName::ExecuteBash.as_str()
-                        && command.is_some()
-                    {
+                    if command.is_some() {
                         println!();
                     }
                     self.print_tool_output(tool_output);
@@ -0,0 +1,42 @@
+//! Temporarily point fd 1 at stderr. `--output json` wraps the one-shot
+//! run in this so everything the REPL prints with `println!` still
+//! reaches the terminal while stdout is left for the final JSON object.
+
+use std::io::Write;
+
+/// Standard descriptors; the Microsoft CRT uses the same numbers. See
+/// `repl::tui::output` for why these aren't `libc::STDOUT_FILENO`.
+const STDOUT_FD: libc::c_int = 1;
+const STDERR_FD: libc::c_int = 2;
+
+pub struct StdoutToStderr {
+    saved_stdout: libc::c_int,
+}
+
+impl StdoutToStderr {
+    pub fn install() -> std::io::Result<Self> {
+        std::io::stdout().flush()?;
+        let saved_stdout = unsafe { libc::dup(STDOUT_FD) };
+        if saved_stdout < 0 {
+            return Err(std::io::Error::last_os_error());
+        }
+        if unsafe { libc::dup2(STDERR_FD, STDOUT_FD) } < 0 {
+            let err = std::io::Error::last_os_error();
+            unsafe { libc::close(saved_stdout) };
+            return Err(err);
+        }
+        Ok(Self { saved_stdout })
+    }
+}
+
+impl Drop for StdoutToStderr {
+    fn drop(&mut self) {
+        // Anything still in Rust's line buffer belongs to the redirected
+        // stream, so flush before restoring the descriptor.
+        let _ = std::io::stdout().flush();
+        unsafe {
+            libc::dup2(self.saved_stdout, STDOUT_FD);
+            libc::close(self.saved_stdout);
+        }
+    }
+}
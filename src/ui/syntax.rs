@@ -1,20 +1,24 @@
+use crate::ui::appearance;
 use colored::Colorize;
 use syntect::easy::HighlightLines;
 use syntect::highlighting::{Style, ThemeSet};
-use syntect::parsing::SyntaxSet;
+use syntect::parsing::{SyntaxReference, SyntaxSet};
 use syntect::util::as_24_bit_terminal_escaped;
 
 #[allow(dead_code)]
 pub struct SyntaxHighlighter {
     syntax_set: SyntaxSet,
     theme_set: ThemeSet,
+    /// Name of a bundled theme; see [`appearance::THEMES`].
+    theme: &'static str,
 }
 
 impl SyntaxHighlighter {
-    pub fn new() -> Self {
+    pub fn new(theme: &'static str) -> Self {
         Self {
             syntax_set: SyntaxSet::load_defaults_newlines(),
             theme_set: ThemeSet::load_defaults(),
+            theme,
         }
     }
 
@@ -62,19 +66,75 @@ impl SyntaxHighlighter {
                 .or_else(|| self.syntax_set.find_syntax_by_extension(language))
                 .unwrap_or_else(|| self.syntax_set.find_syntax_plain_text())
         };
+        self.render(code, syntax, false)
+    }
+
+    /// Highlight file content using the language implied by `path`: its
+    /// extension, then its bare file name (`Makefile`, `Dockerfile`), then
+    /// the first line of `content` (a shebang or `<?xml` header). Falls
+    /// back to plain text, which is dimmed like the frame. With colour
+    /// disabled (`NO_COLOR`, `--no-color`, or output that is not a
+    /// terminal) the lines are framed but left unstyled.
+    pub fn highlight_file(&self, content: &str, path: &str) -> String {
+        let syntax = if colored::control::SHOULD_COLORIZE.should_colorize() {
+            self.syntax_for_file(content, path)
+        } else {
+            self.syntax_set.find_syntax_plain_text()
+        };
+        self.render(content, syntax, true)
+    }
+
+    fn syntax_for_file(&self, content: &str, path: &str) -> &SyntaxReference {
+        let file = std::path::Path::new(path);
+        file.extension()
+            .and_then(|ext| ext.to_str())
+            .and_then(|ext| self.syntax_set.find_syntax_by_extension(ext))
+            .or_else(|| {
+                file.file_name()
+                    .and_then(|name| name.to_str())
+                    .and_then(|name| self.syntax_set.find_syntax_by_extension(name))
+            })
+            .or_else(|| {
+                content
+                    .lines()
+                    .next()
+                    .and_then(|line| self.syntax_set.find_syntax_by_first_line(line))
+            })
+            .unwrap_or_else(|| self.syntax_set.find_syntax_plain_text())
+    }
 
-        let theme = &self.theme_set.themes["base16-ocean.dark"];
-        let mut highlighter = HighlightLines::new(syntax, theme);
+    fn render(&self, code: &str, syntax: &SyntaxReference, dim_plain_text: bool) -> String {
+        // The TUI forces `colored` on for its capture pipe, so the colour
+        // switch is checked here as well.
+        let colour = appearance::color_enabled();
+        let dim = dim_plain_text && syntax.name == self.syntax_set.find_syntax_plain_text().name;
+        let theme = self
+            .theme_set
+            .themes
+            .get(self.theme)
+            .or_else(|| self.theme_set.themes.get(appearance::DEFAULT_THEME))
+            .or_else(|| self.theme_set.themes.values().next());
+        let mut highlighter = theme.map(|theme| HighlightLines::new(syntax, theme));
 
         let mut result = String::new();
         result.push_str(&format!("{}\n", "┌─────".dimmed()));
 
         for line in code.lines() {
-            let ranges: Vec<(Style, &str)> = highlighter
-                .highlight_line(line, &self.syntax_set)
-                .unwrap_or_default();
-            let escaped = as_24_bit_terminal_escaped(&ranges[..], false);
-            result.push_str(&format!("{}  {}\n", "│".dimmed(), escaped));
+            // No escapes at all with colour off. A file of no known
+            // language dims with the frame; code blocks keep the theme's
+            // normal text colour.
+            let rendered = match highlighter.as_mut() {
+                _ if !colour => line.to_string(),
+                _ if dim => line.dimmed().to_string(),
+                Some(highlighter) => {
+                    let ranges: Vec<(Style, &str)> = highlighter
+                        .highlight_line(line, &self.syntax_set)
+                        .unwrap_or_default();
+                    as_24_bit_terminal_escaped(&ranges[..], false)
+                }
+                None => line.to_string(),
+            };
+            result.push_str(&format!("{}  {}\n", "│".dimmed(), rendered));
         }
 
         result.push_str(&format!("{}", "└─────".dimmed()));
@@ -84,7 +144,7 @@ impl SyntaxHighlighter {
 
 impl Default for SyntaxHighlighter {
     fn default() -> Self {
-        Self::new()
+        Self::new(appearance::theme())
     }
 }
 
@@ -94,21 +154,55 @@ mod tests {
 
     #[test]
     fn test_highlighter_creation() {
-        let highlighter = SyntaxHighlighter::new();
+        let highlighter = SyntaxHighlighter::new(appearance::DEFAULT_THEME);
         assert!(!highlighter.syntax_set.syntaxes().is_empty());
     }
 
     #[test]
     fn test_plain_text() {
-        let highlighter = SyntaxHighlighter::new();
+        let highlighter = SyntaxHighlighter::new(appearance::DEFAULT_THEME);
         let text = "Hello, world!";
         let result = highlighter.highlight_text(text);
         assert!(result.contains("Hello, world!"));
     }
 
+    #[test]
+    fn highlight_file_picks_language_from_extension_and_shebang() {
+        let highlighter = SyntaxHighlighter::new(appearance::DEFAULT_THEME);
+        let name =
+            |content: &str, path: &str| highlighter.syntax_for_file(content, path).name.clone();
+        assert_eq!(name("fn main() {}\n", "src/main.rs"), "Rust");
+        assert_eq!(name("all:\n\ttrue\n", "Makefile"), "Makefile");
+        assert!(name("#!/bin/bash\necho hi\n", "run").contains("Bourne Again Shell"));
+        assert_eq!(name("just words\n", "notes"), "Plain Text");
+    }
+
+    #[test]
+    fn only_files_dim_plain_text() {
+        let highlighter = SyntaxHighlighter::new(appearance::DEFAULT_THEME);
+        let theme_colour = "\x1b[38;2;";
+        let block = highlighter.highlight_code("just words\n", "");
+        let file = highlighter.highlight_file("just words\n", "notes");
+        if appearance::color_enabled() {
+            assert!(block.contains(theme_colour), "{block:?}");
+        }
+        assert!(!file.contains(theme_colour), "{file:?}");
+    }
+
+    #[test]
+    fn light_theme_colours_differ_from_the_default() {
+        let code = "fn main() {}\n";
+        let dark = SyntaxHighlighter::new(appearance::DEFAULT_THEME).highlight_code(code, "rust");
+        let light = SyntaxHighlighter::new("InspiredGitHub").highlight_code(code, "rust");
+        if appearance::color_enabled() {
+            assert_ne!(dark, light);
+        }
+        assert!(light.contains("main"));
+    }
+
     #[test]
     fn test_code_block_detection() {
-        let highlighter = SyntaxHighlighter::new();
+        let highlighter = SyntaxHighlighter::new(appearance::DEFAULT_THEME);
         let text = "Here is some code:\n```rust\nfn main() {}\n```";
         let result = highlighter.highlight_text(text);
         assert!(result.contains("Here is some code:"));